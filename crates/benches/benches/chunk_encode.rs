@@ -0,0 +1,109 @@
+//! Chunk data encoding: 24 block sections plus light data, in the same wire
+//! layout as the server's chunk generation, with sections written by
+//! `mc_protocol`'s paletted container codec.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use mc_protocol::{PaletteFormat, write_paletted, write_varint};
+
+const SECTIONS: usize = 24;
+const SECTION_VOLUME: usize = 16 * 16 * 16;
+const AIR: i32 = 0;
+/// Bits of a direct block state ID (about 27k states in 1.21)
+const BLOCK_DIRECT_BITS: u8 = 15;
+/// Bits of a direct biome ID
+const BIOME_DIRECT_BITS: u8 = 6;
+
+/// Block state IDs for one chunk column, one array per section (y, z, x order).
+type Column = Vec<[i32; SECTION_VOLUME]>;
+
+/// Deterministic terrain: `kinds` distinct block ids below a wavy surface.
+fn terrain(kinds: i32) -> Column {
+    (0..SECTIONS)
+        .map(|section| {
+            let mut blocks = [AIR; SECTION_VOLUME];
+            for (index, block) in blocks.iter_mut().enumerate() {
+                let (y, z, x) = (index / 256, (index / 16) % 16, index % 16);
+                let world_y = (section as i32 - 4) * 16 + y as i32;
+                let surface = 64 + ((x * 7 + z * 13) % 9) as i32;
+                if world_y <= surface {
+                    *block = 1 + ((x * 31 + y * 17 + z * 11) as i32 % kinds);
+                }
+            }
+            blocks
+        })
+        .collect()
+}
+
+fn encode_section(blocks: &[i32; SECTION_VOLUME], out: &mut Vec<u8>) {
+    let block_format = PaletteFormat::blocks(BLOCK_DIRECT_BITS);
+    let biome_format = PaletteFormat::biomes(BIOME_DIRECT_BITS);
+
+    let block_count = blocks.iter().filter(|&&id| id != AIR).count() as i16;
+    out.extend_from_slice(&block_count.to_be_bytes());
+    write_paletted(out, &block_format, blocks).unwrap();
+    // Biomes: single-valued plains
+    write_paletted(out, &biome_format, &[0; 64]).unwrap();
+}
+
+fn encode_chunk(chunk_x: i32, chunk_z: i32, column: &Column, out: &mut Vec<u8>) {
+    out.extend_from_slice(&chunk_x.to_be_bytes());
+    out.extend_from_slice(&chunk_z.to_be_bytes());
+    // Heightmaps (empty)
+    write_varint(out, 0).unwrap();
+
+    let mut sections = Vec::with_capacity(SECTIONS * 2048);
+    for blocks in column {
+        encode_section(blocks, &mut sections);
+    }
+    write_varint(out, sections.len() as i32).unwrap();
+    out.extend_from_slice(&sections);
+
+    // Block entities (none)
+    write_varint(out, 0).unwrap();
+
+    // Full sky light above the surface, as the generator sends it
+    let sky_sections = 21;
+    let sky_mask: u64 = ((1 << sky_sections) - 1) << 5;
+    write_varint(out, 1).unwrap();
+    out.extend_from_slice(&sky_mask.to_be_bytes());
+    // Block light mask (empty)
+    write_varint(out, 0).unwrap();
+    for empty_mask in [0b11111_u64, (1 << 26) - 1] {
+        write_varint(out, 1).unwrap();
+        out.extend_from_slice(&empty_mask.to_be_bytes());
+    }
+    write_varint(out, sky_sections).unwrap();
+    for _ in 0..sky_sections {
+        write_varint(out, 2048).unwrap();
+        out.extend_from_slice(&[0xFF; 2048]);
+    }
+    write_varint(out, 0).unwrap();
+}
+
+fn chunk_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk_encode");
+
+    // Single-valued, indirect, and direct (more than 256 kinds)
+    for kinds in [1, 8, 64, 512] {
+        let column = terrain(kinds);
+        let mut out = Vec::new();
+        encode_chunk(0, 0, &column, &mut out);
+        group.throughput(Throughput::Bytes(out.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("palette", kinds), &column, |b, column| {
+            let mut out = Vec::with_capacity(out.len());
+            b.iter(|| {
+                out.clear();
+                encode_chunk(black_box(3), black_box(-7), column, &mut out);
+                black_box(&out);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, chunk_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,95 @@
+//! Archetype iteration: sequential query vs rayon over the matched entities.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rayon::prelude::*;
+use rgb_ecs::{Entity, World};
+
+#[derive(Clone, Copy)]
+struct Position {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[derive(Clone, Copy)]
+struct Velocity {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+/// Marker splitting entities across two archetypes.
+#[derive(Clone, Copy)]
+struct Npc;
+
+fn populate(count: u64) -> (World, Vec<Entity>) {
+    let mut world = World::new();
+    let entities = (0..count)
+        .map(|i| {
+            let entity = world.spawn(Position {
+                x: i as f32,
+                y: 64.0,
+                z: 0.0,
+            });
+            world.insert(
+                entity,
+                Velocity {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.5,
+                },
+            );
+            if i % 2 == 0 {
+                world.insert(entity, Npc);
+            }
+            entity
+        })
+        .collect();
+    (world, entities)
+}
+
+fn step(pos: Position, vel: Velocity) -> f32 {
+    (pos.x + vel.x) + (pos.y + vel.y) + (pos.z + vel.z)
+}
+
+fn iteration_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iteration");
+
+    for count in [1_000, 10_000, 100_000] {
+        group.throughput(Throughput::Elements(count));
+        let (world, entities) = populate(count);
+        let query = world.query().with::<Position>().with::<Velocity>().build();
+
+        group.bench_with_input(BenchmarkId::new("sequential", count), &count, |b, _| {
+            b.iter(|| {
+                let sum: f32 = query
+                    .iter(&world)
+                    .map(|row| step(row.get::<Position>(), row.get::<Velocity>()))
+                    .sum();
+                black_box(sum)
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("parallel", count), &count, |b, _| {
+            b.iter(|| {
+                let sum: f32 = entities
+                    .par_iter()
+                    .filter_map(|&entity| {
+                        Some(step(
+                            world.get::<Position>(entity)?,
+                            world.get::<Velocity>(entity)?,
+                        ))
+                    })
+                    .sum();
+                black_box(sum)
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, iteration_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,232 @@
+//! Cost of flecs-history's `OnSet` hook per component size.
+//!
+//! Each size is measured with and without tracking; the difference is the
+//! serialization + history-entry overhead paid on every `set`.
+//!
+//! `history_tick_read` isolates the tick read every hook performs, comparing
+//! the old `Mutex<u64>` against [`TickCounter`] when several threads set
+//! tracked components at once.
+//!
+//! `history_on_set_concurrent` runs whole `OnSet` hooks on several threads at
+//! once: each thread sets components in its own world, all worlds tracked by
+//! one shared [`HistoryTracker`]. The `mutex` variant is the old hook, which
+//! read the tick through a lock every thread shares; `tracker` is the current
+//! one. With a shared lock left in the hook path, `tracker` stops scaling with
+//! threads the way `mutex` does.
+
+use std::hint::black_box;
+use std::sync::{Arc, Barrier, Mutex};
+use std::time::{Duration, Instant};
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use flecs_ecs::prelude::*;
+use flecs_history::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+struct Small([u64; 1]);
+
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+struct Medium([u64; 8]);
+
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+struct Large([u64; 32]);
+
+const ENTITIES: usize = 256;
+
+/// Tick reads per thread in `history_tick_read`.
+const READS: u64 = 100_000;
+
+/// Entities set per thread and round in `history_on_set_concurrent`.
+const CONCURRENT_ENTITIES: usize = 64;
+
+fn bench_size<T>(c: &mut Criterion, value: T)
+where
+    T: ComponentId + ComponentType<Struct> + Copy + Serialize + for<'de> Deserialize<'de>,
+{
+    let size = core::mem::size_of::<T>();
+    let mut group = c.benchmark_group("history_on_set");
+    group.throughput(Throughput::Elements(ENTITIES as u64));
+
+    for tracked in [false, true] {
+        let world = World::new();
+        world.component::<T>().serializable::<T>();
+
+        let history = HistoryTracker::new(&world);
+        if tracked {
+            history.track_component::<T>(&world);
+        }
+
+        let entities: Vec<Entity> = (0..ENTITIES)
+            .map(|_| world.entity().set(value).id())
+            .collect();
+        let label = if tracked { "tracked" } else { "untracked" };
+
+        group.bench_with_input(BenchmarkId::new(label, size), &entities, |b, entities| {
+            // Entries are cleared between iterations (untimed) so later
+            // samples don't pay for an ever-growing history table
+            b.iter_custom(|iters| {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let start = Instant::now();
+                    for &entity in entities {
+                        world.entity_from_id(entity).set(black_box(value));
+                    }
+                    history.advance_tick(&world);
+                    elapsed += start.elapsed();
+
+                    history.clear_all_history(&world);
+                }
+                elapsed
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Run `read` `READS` times on each of `threads` threads.
+fn read_concurrently(threads: usize, read: impl Fn() -> u64 + Sync) {
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for _ in 0..READS {
+                    black_box(read());
+                }
+            });
+        }
+    });
+}
+
+fn bench_tick_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("history_tick_read");
+
+    for threads in [1, 4, 8] {
+        group.throughput(Throughput::Elements(threads as u64 * READS));
+
+        let mutex = Mutex::new(0_u64);
+        group.bench_with_input(
+            BenchmarkId::new("mutex", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| read_concurrently(threads, || *mutex.lock().unwrap()));
+            },
+        );
+
+        let counter = TickCounter::default();
+        group.bench_with_input(
+            BenchmarkId::new("atomic", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| read_concurrently(threads, || counter.get()));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// How a thread's world records its sets in `history_on_set_concurrent`.
+#[derive(Clone, Copy)]
+enum Recorder {
+    /// The old hook: the tick read through a lock shared by every world.
+    Mutex,
+    /// The shared `HistoryTracker`.
+    Tracker,
+}
+
+/// Set up a world on this thread, recording sets of `Small` with `recorder`.
+fn concurrent_world(recorder: Recorder, history: &HistoryTracker, tick: &Arc<Mutex<u64>>) -> World {
+    let world = World::new();
+    world.component::<Small>().serializable::<Small>();
+    match recorder {
+        Recorder::Tracker => history.track_component::<Small>(&world),
+        Recorder::Mutex => {
+            let tick = Arc::clone(tick);
+            world
+                .component::<Small>()
+                .on_set(move |entity: EntityView<'_>, value: &mut Small| {
+                    let tick = *tick.lock().unwrap();
+                    let world = entity.world();
+                    let component = world.component::<Small>().entity();
+                    world
+                        .entity()
+                        .set(HistoryEntry {
+                            tick,
+                            data: bincode::serialize(value).unwrap(),
+                            component_id: component.id().0,
+                            transaction: None,
+                        })
+                        .add((HistoryOf, component))
+                        .add((HistoryFor, entity));
+                });
+        }
+    }
+    world
+}
+
+/// Time `rounds` rounds of setting every entity on each of `threads` threads
+/// at once, returning the slowest thread's time.
+fn set_concurrently(
+    recorder: Recorder,
+    history: &HistoryTracker,
+    threads: usize,
+    rounds: u64,
+) -> Duration {
+    let tick = Arc::new(Mutex::new(0_u64));
+    let barrier = Barrier::new(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let world = concurrent_world(recorder, history, &tick);
+                    let entities: Vec<Entity> = (0..CONCURRENT_ENTITIES)
+                        .map(|_| world.entity().id())
+                        .collect();
+
+                    barrier.wait();
+                    let start = Instant::now();
+                    for round in 0..rounds {
+                        for &entity in &entities {
+                            world.entity_from_id(entity).set(black_box(Small([round])));
+                        }
+                    }
+                    start.elapsed()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .max()
+            .unwrap_or_default()
+    })
+}
+
+fn bench_concurrent_on_set(c: &mut Criterion) {
+    let mut group = c.benchmark_group("history_on_set_concurrent");
+    group.sample_size(20);
+    let history = HistoryTracker::new(&World::new());
+
+    for threads in [1, 4, 8] {
+        group.throughput(Throughput::Elements((threads * CONCURRENT_ENTITIES) as u64));
+        for (label, recorder) in [("mutex", Recorder::Mutex), ("tracker", Recorder::Tracker)] {
+            group.bench_with_input(BenchmarkId::new(label, threads), &threads, |b, &threads| {
+                b.iter_custom(|rounds| set_concurrently(recorder, &history, threads, rounds));
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn history_benchmarks(c: &mut Criterion) {
+    bench_size(c, Small([1]));
+    bench_size(c, Medium([2; 8]));
+    bench_size(c, Large([3; 32]));
+    bench_tick_read(c);
+    bench_concurrent_on_set(c);
+}
+
+criterion_group!(benches, history_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,150 @@
+//! Packet encode/decode through the `mc-protocol` derive macros.
+//!
+//! The generated `mc-data` packets carry no fields yet, so these mirror the
+//! shapes of the hot play-state packets.
+
+use std::hint::black_box;
+use std::io::Cursor;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use mc_protocol::{Decode, Encode, VarInt, read_varint, write_varint};
+
+/// Shape of `MovePlayerPos` (serverbound).
+#[derive(Debug, Clone, Encode, Decode)]
+struct MovePlayerPos {
+    x: f64,
+    y: f64,
+    z: f64,
+    flags: u8,
+}
+
+/// Shape of `SystemChat` (clientbound) with a plain-text body.
+#[derive(Debug, Clone, Encode, Decode)]
+struct SystemChat {
+    content: String,
+    overlay: bool,
+}
+
+/// Shape of `SetEntityMotion` (clientbound).
+#[derive(Debug, Clone, Encode, Decode)]
+struct SetEntityMotion {
+    entity_id: VarInt,
+    velocity_x: i16,
+    velocity_y: i16,
+    velocity_z: i16,
+}
+
+/// Encode a packet with its id and length prefix, as sent on the wire.
+fn frame<P: Encode>(id: i32, packet: &P, out: &mut Vec<u8>) {
+    let mut body = Vec::with_capacity(64);
+    write_varint(&mut body, id).unwrap();
+    packet.encode(&mut body).unwrap();
+    write_varint(out, body.len() as i32).unwrap();
+    out.extend_from_slice(&body);
+}
+
+/// Read a framed packet back.
+fn unframe<'a, P: Decode<'a>>(cursor: &mut Cursor<&[u8]>) -> P {
+    let _len = read_varint(cursor).unwrap();
+    let _id = read_varint(cursor).unwrap();
+    P::decode(cursor).unwrap()
+}
+
+fn bench_packet<P>(c: &mut Criterion, name: &str, id: i32, packet: &P)
+where
+    P: Encode + for<'a> Decode<'a>,
+{
+    let mut group = c.benchmark_group(format!("packet/{name}"));
+
+    let mut encoded = Vec::new();
+    frame(id, packet, &mut encoded);
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+    group.bench_function("encode", |b| {
+        let mut out = Vec::with_capacity(encoded.len());
+        b.iter(|| {
+            out.clear();
+            frame(id, black_box(packet), &mut out);
+            black_box(&out);
+        });
+    });
+
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            let mut cursor = Cursor::new(black_box(encoded.as_slice()));
+            black_box(unframe::<P>(&mut cursor))
+        });
+    });
+
+    group.finish();
+}
+
+fn packet_benchmarks(c: &mut Criterion) {
+    bench_packet(
+        c,
+        "move_player_pos",
+        0x1D,
+        &MovePlayerPos {
+            x: 128.5,
+            y: 64.0,
+            z: -32.25,
+            flags: 1,
+        },
+    );
+    bench_packet(
+        c,
+        "system_chat",
+        0x73,
+        &SystemChat {
+            content: "Player joined the game. ".repeat(8),
+            overlay: false,
+        },
+    );
+    bench_packet(
+        c,
+        "set_entity_motion",
+        0x5E,
+        &SetEntityMotion {
+            entity_id: VarInt(123_456),
+            velocity_x: 400,
+            velocity_y: -1200,
+            velocity_z: 0,
+        },
+    );
+}
+
+fn varint_benchmarks(c: &mut Criterion) {
+    let values: Vec<i32> = (0..1024).map(|i| i * 4099).collect();
+    let mut encoded = Vec::new();
+    for &value in &values {
+        write_varint(&mut encoded, value).unwrap();
+    }
+
+    let mut group = c.benchmark_group("varint");
+    group.throughput(Throughput::Elements(values.len() as u64));
+
+    group.bench_function("encode", |b| {
+        let mut out = Vec::with_capacity(encoded.len());
+        b.iter(|| {
+            out.clear();
+            for &value in &values {
+                write_varint(&mut out, black_box(value)).unwrap();
+            }
+            black_box(&out);
+        });
+    });
+
+    group.bench_function("decode", |b| {
+        b.iter(|| {
+            let mut cursor = Cursor::new(encoded.as_slice());
+            for _ in 0..values.len() {
+                black_box(read_varint(&mut cursor).unwrap());
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, packet_benchmarks, varint_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+//! Persistence write throughput: player components saved through `PersistDb`,
+//! one transaction per component versus one per player.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use persist::PersistDb;
+
+/// Components saved on disconnect, with roughly their encoded sizes
+const COMPONENTS: [(&str, usize); 6] = [
+    ("Position", 24),
+    ("Rotation", 8),
+    ("Health", 4),
+    ("Food", 8),
+    ("Experience", 12),
+    ("Inventory", 2048),
+];
+
+fn payloads() -> Vec<(&'static str, Vec<u8>)> {
+    COMPONENTS
+        .iter()
+        .map(|&(name, len)| (name, (0..len).map(|i| i as u8).collect()))
+        .collect()
+}
+
+fn save_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("persist_save");
+    group.sample_size(20);
+
+    let payloads = payloads();
+    let bytes: usize = payloads.iter().map(|(_, payload)| payload.len()).sum();
+
+    for players in [1, 16, 128] {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersistDb::open(dir.path().join("persist")).unwrap();
+        group.throughput(Throughput::Bytes((bytes * players) as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("save_bytes", players),
+            &players,
+            |b, &players| {
+                b.iter(|| {
+                    for uuid in 0..players as u128 {
+                        for (name, payload) in &payloads {
+                            db.save_bytes(black_box(uuid), name, payload).unwrap();
+                        }
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("save_many", players),
+            &players,
+            |b, &players| {
+                b.iter(|| {
+                    for uuid in 0..players as u128 {
+                        let entries = payloads
+                            .iter()
+                            .map(|(name, payload)| (*name, payload.as_slice()));
+                        black_box(db.save_many(black_box(uuid), entries).unwrap());
+                    }
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, save_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+//! Storage write throughput: ticks committed through `VersionedWorld`.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rgb_storage::VersionedWorld;
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct Position {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+fn position(i: usize) -> Position {
+    Position {
+        x: i as f64,
+        y: 64.0,
+        z: -(i as f64),
+    }
+}
+
+fn commit_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage_commit");
+    group.sample_size(20);
+
+    for entities in [16, 256, 4096] {
+        let dir = tempfile::tempdir().unwrap();
+        let mut world = VersionedWorld::open(dir.path()).unwrap();
+        let spawned: Vec<_> = (0..entities).map(|i| world.spawn(position(i))).collect();
+        world.commit_tick().unwrap();
+        group.throughput(Throughput::Elements(entities as u64));
+
+        // Every entity moves every tick (players and mobs)
+        group.bench_with_input(
+            BenchmarkId::new("update_all", entities),
+            &spawned,
+            |b, spawned| {
+                let mut step = 0;
+                b.iter(|| {
+                    step += 1;
+                    for (i, &entity) in spawned.iter().enumerate() {
+                        world.update(entity, position(i + step));
+                    }
+                    black_box(world.commit_tick().unwrap());
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn read_benchmarks(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let mut world = VersionedWorld::open(dir.path()).unwrap();
+    let spawned: Vec<_> = (0..1024).map(|i| world.spawn(position(i))).collect();
+    world.commit_tick().unwrap();
+
+    let mut group = c.benchmark_group("storage_read");
+    group.bench_function("get_from_storage", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i = (i + 1) % spawned.len();
+            black_box(world.get_from_storage::<Position>(spawned[i]).unwrap());
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, commit_benchmarks, read_benchmarks);
+criterion_main!(benches);
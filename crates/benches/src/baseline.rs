@@ -0,0 +1,268 @@
+//! Condensed benchmark baselines.
+//!
+//! Criterion stores one `new/estimates.json` per benchmark under
+//! `target/criterion`. A [`Baseline`] keeps only the mean and standard
+//! deviation of each, keyed by the benchmark's full id
+//! (e.g. `"iteration/sequential/10000"`).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Errors reading or writing baselines.
+#[derive(Debug, thiserror::Error)]
+pub enum BaselineError {
+    #[error("I/O error at {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("invalid JSON in {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("no criterion results found under {0}")]
+    NoResults(PathBuf),
+}
+
+/// Timing estimate for a single benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    /// Mean time per iteration in nanoseconds.
+    pub mean_ns: f64,
+    /// Standard deviation in nanoseconds.
+    pub std_dev_ns: f64,
+}
+
+/// A benchmark that got slower than the allowed threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// Full benchmark id.
+    pub id: String,
+    /// Mean from the baseline.
+    pub baseline_ns: f64,
+    /// Mean from the current run.
+    pub current_ns: f64,
+}
+
+impl Regression {
+    /// Relative slowdown (`0.25` = 25% slower).
+    #[must_use]
+    pub fn slowdown(&self) -> f64 {
+        self.current_ns / self.baseline_ns - 1.0
+    }
+}
+
+/// Benchmark id -> estimate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub benchmarks: BTreeMap<String, Estimate>,
+}
+
+#[derive(Deserialize)]
+struct CriterionBenchmark {
+    full_id: String,
+}
+
+#[derive(Deserialize)]
+struct CriterionStat {
+    point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct CriterionEstimates {
+    mean: CriterionStat,
+    std_dev: CriterionStat,
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, BaselineError> {
+    let text = fs::read_to_string(path).map_err(|source| BaselineError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_str(&text).map_err(|source| BaselineError::Json {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+impl Baseline {
+    /// Collect the latest results from a criterion output directory
+    /// (usually `target/criterion`).
+    pub fn collect(criterion_dir: &Path) -> Result<Self, BaselineError> {
+        let mut baseline = Self::default();
+        baseline.collect_dir(criterion_dir)?;
+
+        if baseline.benchmarks.is_empty() {
+            return Err(BaselineError::NoResults(criterion_dir.to_path_buf()));
+        }
+        Ok(baseline)
+    }
+
+    fn collect_dir(&mut self, dir: &Path) -> Result<(), BaselineError> {
+        let entries = fs::read_dir(dir).map_err(|source| BaselineError::Io {
+            path: dir.to_path_buf(),
+            source,
+        })?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let name = entry.file_name();
+            if name == "new" {
+                let benchmark = path.join("benchmark.json");
+                let estimates = path.join("estimates.json");
+                if benchmark.is_file() && estimates.is_file() {
+                    let benchmark: CriterionBenchmark = read_json(&benchmark)?;
+                    let estimates: CriterionEstimates = read_json(&estimates)?;
+                    self.benchmarks.insert(
+                        benchmark.full_id,
+                        Estimate {
+                            mean_ns: estimates.mean.point_estimate,
+                            std_dev_ns: estimates.std_dev.point_estimate,
+                        },
+                    );
+                }
+            } else if name != "report" && name != "base" && name != "change" {
+                self.collect_dir(&path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load a baseline file.
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        read_json(path)
+    }
+
+    /// Write this baseline as pretty-printed JSON.
+    pub fn save(&self, path: &Path) -> Result<(), BaselineError> {
+        let io_err = |source| BaselineError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|source| BaselineError::Json {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        fs::write(path, json + "\n").map_err(io_err)
+    }
+
+    /// Benchmarks in `current` that are more than `threshold` slower than here.
+    ///
+    /// Benchmarks missing from either side are ignored.
+    #[must_use]
+    pub fn regressions(&self, current: &Self, threshold: f64) -> Vec<Regression> {
+        self.benchmarks
+            .iter()
+            .filter_map(|(id, base)| {
+                let now = current.benchmarks.get(id)?;
+                let regression = Regression {
+                    id: id.clone(),
+                    baseline_ns: base.mean_ns,
+                    current_ns: now.mean_ns,
+                };
+                (regression.slowdown() > threshold).then_some(regression)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(entries: &[(&str, f64)]) -> Baseline {
+        Baseline {
+            benchmarks: entries
+                .iter()
+                .map(|&(id, mean_ns)| {
+                    (
+                        id.to_string(),
+                        Estimate {
+                            mean_ns,
+                            std_dev_ns: 0.0,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn write_result(root: &Path, dir: &str, full_id: &str, mean: f64) {
+        let new = root.join(dir).join("new");
+        fs::create_dir_all(&new).unwrap();
+        fs::write(
+            new.join("benchmark.json"),
+            serde_json::json!({ "full_id": full_id }).to_string(),
+        )
+        .unwrap();
+        fs::write(
+            new.join("estimates.json"),
+            serde_json::json!({
+                "mean": { "point_estimate": mean },
+                "std_dev": { "point_estimate": 1.0 },
+            })
+            .to_string(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_regressions_over_threshold() {
+        let base = baseline(&[("a", 100.0), ("b", 100.0), ("gone", 1.0)]);
+        let current = baseline(&[("a", 105.0), ("b", 130.0), ("added", 1.0)]);
+
+        let regressions = base.regressions(&current, 0.10);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].id, "b");
+        assert!((regressions[0].slowdown() - 0.30).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_collect_and_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        write_result(
+            dir.path(),
+            "iteration/sequential/100",
+            "iteration/sequential/100",
+            42.0,
+        );
+        write_result(dir.path(), "codec/encode", "codec/encode", 7.0);
+        // Criterion's previous-run copies must not be picked up
+        write_result(
+            &dir.path().join("codec/encode"),
+            "base",
+            "codec/encode",
+            999.0,
+        );
+
+        let collected = Baseline::collect(dir.path()).unwrap();
+        assert_eq!(collected.benchmarks.len(), 2);
+        assert!((collected.benchmarks["codec/encode"].mean_ns - 7.0).abs() < f64::EPSILON);
+
+        let file = dir.path().join("baselines/main.json");
+        collected.save(&file).unwrap();
+        assert_eq!(Baseline::load(&file).unwrap(), collected);
+    }
+
+    #[test]
+    fn test_collect_empty_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            Baseline::collect(dir.path()),
+            Err(BaselineError::NoResults(_))
+        ));
+    }
+}
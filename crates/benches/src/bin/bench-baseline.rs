@@ -0,0 +1,107 @@
+//! Save or compare condensed criterion baselines.
+//!
+//! ```text
+//! bench-baseline save <baseline.json> [criterion-dir]
+//! bench-baseline compare <baseline.json> [criterion-dir] [--threshold 0.10]
+//! ```
+//!
+//! `criterion-dir` defaults to `$CARGO_TARGET_DIR/criterion` (or `target/criterion`).
+//! `compare` exits with status 1 if any benchmark regressed past the threshold.
+
+// CLI report output goes to stdout
+#![allow(clippy::print_stdout)]
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use rgb_benches::Baseline;
+
+const USAGE: &str =
+    "usage: bench-baseline <save|compare> <baseline.json> [criterion-dir] [--threshold 0.10]";
+
+fn default_criterion_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR")
+        .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+        .join("criterion")
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut threshold = 0.10;
+    if let Some(pos) = args.iter().position(|arg| arg == "--threshold") {
+        let Some(value) = args.get(pos + 1).and_then(|v| v.parse().ok()) else {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        };
+        threshold = value;
+        args.drain(pos..=pos + 1);
+    }
+
+    let (Some(command), Some(baseline_path)) = (args.first(), args.get(1)) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let baseline_path = PathBuf::from(baseline_path);
+    let criterion_dir = args
+        .get(2)
+        .map_or_else(default_criterion_dir, PathBuf::from);
+
+    let current = match Baseline::collect(&criterion_dir) {
+        Ok(current) => current,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match command.as_str() {
+        "save" => {
+            if let Err(err) = current.save(&baseline_path) {
+                eprintln!("{err}");
+                return ExitCode::FAILURE;
+            }
+            println!(
+                "saved {} benchmarks to {}",
+                current.benchmarks.len(),
+                baseline_path.display()
+            );
+            ExitCode::SUCCESS
+        }
+        "compare" => {
+            let baseline = match Baseline::load(&baseline_path) {
+                Ok(baseline) => baseline,
+                Err(err) => {
+                    eprintln!("{err}");
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            let regressions = baseline.regressions(&current, threshold);
+            for regression in &regressions {
+                println!(
+                    "REGRESSED {}: {:.1} ns -> {:.1} ns (+{:.1}%)",
+                    regression.id,
+                    regression.baseline_ns,
+                    regression.current_ns,
+                    regression.slowdown() * 100.0
+                );
+            }
+
+            if regressions.is_empty() {
+                println!(
+                    "no regressions over {:.0}% across {} benchmarks",
+                    threshold * 100.0,
+                    baseline.benchmarks.len()
+                );
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Benchmark suite for the ECS, history, storage, and network paths.
+//!
+//! The benchmarks themselves live in `benches/`. This crate's library holds the
+//! tooling that turns criterion's raw output into a small, committable baseline
+//! file so regressions can be measured across runs:
+//!
+//! ```text
+//! cargo bench -p rgb-benches
+//! cargo run -p rgb-benches --bin bench-baseline -- save baselines/main.json
+//!
+//! # ...after a change...
+//! cargo bench -p rgb-benches
+//! cargo run -p rgb-benches --bin bench-baseline -- compare baselines/main.json
+//! ```
+
+pub mod baseline;
+
+pub use baseline::{Baseline, BaselineError, Estimate, Regression};
@@ -0,0 +1,194 @@
+//! Per-entity rate limiting of component writes.
+//!
+//! Some components change every tick (`Position` on every movement packet),
+//! but nobody needs them written to history or the database that often. A
+//! [`Throttle`] lets at most one write per entity through per interval, and
+//! holds on to the latest value that came in between, so the final state
+//! isn't lost:
+//!
+//! - [`Throttle::offer`] returns a value to write now, or holds it.
+//! - [`Throttle::take_due`] returns held values whose interval has passed,
+//!   for values that stopped changing. Call it periodically.
+//! - [`Throttle::flush`] returns an entity's held value right away, e.g.
+//!   when a player disconnects.
+//!
+//! Time is whatever unit the caller counts in: `flecs-history` uses ticks,
+//! `persist` milliseconds.
+//!
+//! ```
+//! use change_throttle::Throttle;
+//!
+//! let mut throttle = Throttle::new(20);
+//! assert_eq!(throttle.offer(1, "a", 0), Some("a"));
+//! assert_eq!(throttle.offer(1, "b", 5), None);
+//! assert_eq!(throttle.offer(1, "c", 10), None);
+//! assert_eq!(throttle.take_due(20), [(1, "c")]);
+//! ```
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// What a [`Throttle`] remembers about one key.
+#[derive(Debug)]
+struct Slot<V> {
+    /// When a value was last let through.
+    written: u64,
+    /// Latest value held back since.
+    held: Option<V>,
+}
+
+/// Lets through at most one value per key per interval, holding the latest
+/// of the rest.
+#[derive(Debug)]
+pub struct Throttle<K, V> {
+    interval: u64,
+    slots: HashMap<K, Slot<V>>,
+}
+
+impl<K: Hash + Eq + Copy, V> Throttle<K, V> {
+    /// Create a throttle letting one value per key through every `interval`.
+    #[must_use]
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval,
+            slots: HashMap::new(),
+        }
+    }
+
+    /// Least time between two values of a key.
+    #[must_use]
+    pub const fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// A new value of `key` at `now`.
+    ///
+    /// Returns it if it should be written now. Otherwise it's held, replacing
+    /// the value held before, and `None` is returned.
+    pub fn offer(&mut self, key: K, value: V, now: u64) -> Option<V> {
+        let Some(slot) = self.slots.get_mut(&key) else {
+            self.slots.insert(
+                key,
+                Slot {
+                    written: now,
+                    held: None,
+                },
+            );
+            return Some(value);
+        };
+
+        if now.saturating_sub(slot.written) >= self.interval {
+            slot.written = now;
+            slot.held = None;
+            Some(value)
+        } else {
+            slot.held = Some(value);
+            None
+        }
+    }
+
+    /// Take the held values whose key may be written again at `now`.
+    ///
+    /// They count as written at `now`. Keys with nothing held whose interval
+    /// has passed are forgotten, since their next value goes through anyway.
+    pub fn take_due(&mut self, now: u64) -> Vec<(K, V)> {
+        let interval = self.interval;
+        let mut due = Vec::new();
+        self.slots.retain(|&key, slot| {
+            if now.saturating_sub(slot.written) < interval {
+                return true;
+            }
+            match slot.held.take() {
+                Some(value) => {
+                    slot.written = now;
+                    due.push((key, value));
+                    true
+                }
+                None => false,
+            }
+        });
+        due
+    }
+
+    /// Take the value held for `key` regardless of the interval, and forget
+    /// the key.
+    pub fn flush(&mut self, key: K) -> Option<V> {
+        self.slots.remove(&key).and_then(|slot| slot.held)
+    }
+
+    /// Take every held value, forgetting all keys.
+    pub fn flush_all(&mut self) -> Vec<(K, V)> {
+        self.slots
+            .drain()
+            .filter_map(|(key, slot)| slot.held.map(|value| (key, value)))
+            .collect()
+    }
+
+    /// Forget `key` and drop its held value, so its next value goes through.
+    pub fn forget(&mut self, key: K) {
+        self.slots.remove(&key);
+    }
+
+    /// Number of values held back.
+    #[must_use]
+    pub fn held(&self) -> usize {
+        self.slots
+            .values()
+            .filter(|slot| slot.held.is_some())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_value_per_interval() {
+        let mut throttle = Throttle::new(10);
+        let written: Vec<u64> = (0..25)
+            .filter(|&now| throttle.offer(1, now, now).is_some())
+            .collect();
+        assert_eq!(written, [0, 10, 20]);
+        assert_eq!(throttle.held(), 1);
+    }
+
+    #[test]
+    fn test_latest_held_value_becomes_due() {
+        let mut throttle = Throttle::new(10);
+        assert_eq!(throttle.offer(1, 'a', 0), Some('a'));
+        assert_eq!(throttle.offer(1, 'b', 3), None);
+        assert_eq!(throttle.offer(1, 'c', 6), None);
+
+        assert!(throttle.take_due(9).is_empty());
+        assert_eq!(throttle.take_due(10), [(1, 'c')]);
+        // Written at 10, so the next value waits for 20
+        assert_eq!(throttle.offer(1, 'd', 15), None);
+        assert_eq!(throttle.take_due(20), [(1, 'd')]);
+    }
+
+    #[test]
+    fn test_idle_keys_are_forgotten() {
+        let mut throttle = Throttle::new(10);
+        throttle.offer(1, (), 0);
+        assert!(throttle.take_due(10).is_empty());
+        assert!(throttle.slots.is_empty());
+    }
+
+    #[test]
+    fn test_flush_ignores_interval() {
+        let mut throttle = Throttle::new(1000);
+        throttle.offer(1, 'a', 0);
+        throttle.offer(1, 'b', 1);
+        throttle.offer(2, 'x', 0);
+        throttle.offer(2, 'y', 1);
+
+        assert_eq!(throttle.flush(1), Some('b'));
+        assert_eq!(throttle.flush(1), None);
+        // Forgotten, so the next value goes straight through
+        assert_eq!(throttle.offer(1, 'c', 2), Some('c'));
+
+        assert_eq!(throttle.flush_all(), [(2, 'y')]);
+        assert_eq!(throttle.held(), 0);
+    }
+}
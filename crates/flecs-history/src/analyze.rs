@@ -0,0 +1,120 @@
+//! Anomaly detection over recorded history.
+//!
+//! An [`Analyzer`] registered with `HistoryTracker::analyze` sees every entry
+//! recorded for its component, together with the entity's previous entry,
+//! and reports anything suspicious:
+//!
+//! ```ignore
+//! struct Teleport { max_distance: f32 }
+//!
+//! impl Analyzer for Teleport {
+//!     fn name(&self) -> &str {
+//!         "teleport"
+//!     }
+//!
+//!     fn analyze(
+//!         &mut self,
+//!         _entity: EntityView<'_>,
+//!         entry: &HistoryEntry,
+//!         previous: Option<&HistoryEntry>,
+//!     ) -> Option<String> {
+//!         let old: Position = previous?.deserialize().ok()?;
+//!         let new: Position = entry.deserialize().ok()?;
+//!         let distance = old.distance(&new);
+//!         (distance > self.max_distance).then(|| format!("moved {distance:.1} blocks"))
+//!     }
+//! }
+//!
+//! history.analyze::<Position>(&world, Teleport { max_distance: 10.0 });
+//! ```
+//!
+//! Each report becomes an [`Anomaly`] entity related to the entity it is
+//! about with `(AnomalyFor, entity)`, and is logged as a warning.
+
+use std::collections::HashMap;
+
+use flecs_ecs::prelude::*;
+
+use crate::HistoryEntry;
+
+/// Inspects history entries of one component as they are recorded.
+pub trait Analyzer: Send {
+    /// Name recorded with this analyzer's anomalies.
+    fn name(&self) -> &str;
+
+    /// Check an entry just recorded for `entity`. `previous` is the last
+    /// entry recorded for the same entity and component, if any.
+    ///
+    /// Returns a description of the anomaly, or `None` if the entry is fine.
+    fn analyze(
+        &mut self,
+        entity: EntityView<'_>,
+        entry: &HistoryEntry,
+        previous: Option<&HistoryEntry>,
+    ) -> Option<String>;
+}
+
+/// Something an [`Analyzer`] flagged.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct Anomaly {
+    /// Tick of the entry that was flagged.
+    pub tick: u64,
+
+    /// Name of the analyzer that flagged it.
+    pub analyzer: String,
+
+    /// The component entity ID of the flagged entry.
+    pub component_id: u64,
+
+    /// What the analyzer found.
+    pub message: String,
+}
+
+/// Relation tag: anomaly is about this entity.
+/// Used as: entity.add((AnomalyFor, source_entity))
+#[derive(Component)]
+pub struct AnomalyFor;
+
+/// Analyzers of one component, and the last entry they saw per entity.
+#[derive(Default)]
+pub struct AnalyzerSet {
+    analyzers: Vec<Box<dyn Analyzer>>,
+    last: HashMap<u64, HistoryEntry>,
+}
+
+impl AnalyzerSet {
+    /// Add an analyzer.
+    pub fn push(&mut self, analyzer: Box<dyn Analyzer>) {
+        self.analyzers.push(analyzer);
+    }
+
+    /// Run every analyzer on an entry recorded for `entity`.
+    pub fn run(&mut self, entity: EntityView<'_>, entry: &HistoryEntry) -> Vec<Anomaly> {
+        let previous = self.last.get(&entity.id().0);
+        let anomalies = self
+            .analyzers
+            .iter_mut()
+            .filter_map(|analyzer| {
+                let message = analyzer.analyze(entity, entry, previous)?;
+                Some(Anomaly {
+                    tick: entry.tick,
+                    analyzer: analyzer.name().to_string(),
+                    component_id: entry.component_id,
+                    message,
+                })
+            })
+            .collect();
+        self.last.insert(entity.id().0, entry.clone());
+        anomalies
+    }
+
+    /// Forget the last entry of one entity.
+    pub fn forget(&mut self, entity: u64) {
+        self.last.remove(&entity);
+    }
+
+    /// Forget the last entry of every entity.
+    pub fn clear(&mut self) {
+        self.last.clear();
+    }
+}
@@ -0,0 +1,117 @@
+//! Compaction of repeated values.
+//!
+//! Some systems set a component every tick whether it changed or not, which
+//! records the same value over and over. [`SamplingPolicy::OnChange`] keeps
+//! that out when bytes are identical, but values can be equal without being
+//! byte-identical (a map serialized in a different order, `-0.0` and `0.0`),
+//! and components tracked with other policies still pile up repeats.
+//!
+//! Compaction cleans up what's already recorded. For each component opted in
+//! with `HistoryTracker::compact_on_equal`, consecutive entries of an entity
+//! whose deserialized values are equal (by `PartialEq`) are dropped, keeping
+//! the first of each run:
+//!
+//! ```ignore
+//! history.compact_on_equal::<Health>(&world);
+//!
+//! // Periodically
+//! let dropped = history.compact(&world);
+//! ```
+//!
+//! Entries recorded inside a [`history_scope`](crate::history_scope) are
+//! always kept, so a transaction never loses part of what it recorded.
+//!
+//! [`SamplingPolicy::OnChange`]: crate::SamplingPolicy::OnChange
+
+use serde::de::DeserializeOwned;
+
+use crate::HistoryEntry;
+
+/// How a component's recorded values are compared when compacting.
+#[derive(Clone, Copy, Debug)]
+pub struct Compaction {
+    equal: fn(&[u8], &[u8]) -> bool,
+}
+
+impl Compaction {
+    /// Compare values by deserializing them as `T`.
+    ///
+    /// Values that fail to deserialize are never equal.
+    #[must_use]
+    pub fn of<T: DeserializeOwned + PartialEq>() -> Self {
+        Self {
+            equal: |a, b| match (bincode::deserialize::<T>(a), bincode::deserialize::<T>(b)) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether two serialized values are equal.
+    #[must_use]
+    pub fn equal(self, a: &[u8], b: &[u8]) -> bool {
+        a == b || (self.equal)(a, b)
+    }
+
+    /// Keys of the entries in `entries`, one entity's history oldest first,
+    /// that repeat the value before them.
+    pub fn redundant<K: Copy>(self, entries: &[(K, &HistoryEntry)]) -> Vec<K> {
+        entries
+            .windows(2)
+            .filter(|pair| {
+                let (previous, entry) = (pair[0].1, pair[1].1);
+                entry.transaction.is_none() && self.equal(&previous.data, &entry.data)
+            })
+            .map(|pair| pair[1].0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Speed(f32);
+
+    fn entry(tick: u64, speed: f32, transaction: Option<u64>) -> HistoryEntry {
+        HistoryEntry {
+            tick,
+            data: bincode::serialize(&Speed(speed)).unwrap(),
+            component_id: 0,
+            transaction,
+        }
+    }
+
+    #[test]
+    fn test_runs_keep_their_first_entry() {
+        let entries = [
+            entry(0, 1.0, None),
+            entry(1, 1.0, None),
+            entry(2, 2.0, None),
+            entry(3, 2.0, None),
+            entry(4, 2.0, None),
+            entry(5, 1.0, None),
+        ];
+        let keyed: Vec<_> = entries.iter().map(|e| (e.tick, e)).collect();
+        assert_eq!(Compaction::of::<Speed>().redundant(&keyed), [1, 3, 4]);
+    }
+
+    #[test]
+    fn test_equal_values_with_different_bytes() {
+        // -0.0 and 0.0 serialize differently but compare equal
+        let entries = [entry(0, 0.0, None), entry(1, -0.0, None)];
+        assert_ne!(entries[0].data, entries[1].data);
+        let keyed: Vec<_> = entries.iter().map(|e| (e.tick, e)).collect();
+        assert_eq!(Compaction::of::<Speed>().redundant(&keyed), [1]);
+    }
+
+    #[test]
+    fn test_transaction_entries_are_kept() {
+        let entries = [entry(0, 1.0, None), entry(1, 1.0, Some(7))];
+        let keyed: Vec<_> = entries.iter().map(|e| (e.tick, e)).collect();
+        assert!(Compaction::of::<Speed>().redundant(&keyed).is_empty());
+    }
+}
@@ -6,6 +6,18 @@
 //! - `SerializeInfo`: A component attached to component entities that provides serialization functions
 //! - `SerializableExt`: An extension trait for ergonomic registration of serializable components
 //! - History tracking: Automatic recording of component changes for entities
+//! - `SamplingPolicy`: Per-component throttling of which changes get recorded,
+//!   including `Throttled`, which holds back the latest change for later
+//! - `Analyzer`: Pluggable detectors that flag suspicious entries as `Anomaly`
+//!   entities
+//! - `WatchExt`: Typed change notifications delivered over a channel, for
+//!   consumers on other threads
+//! - `Compaction`: Dropping recorded entries that repeat the value before
+//!   them, per component
+//! - `with_max_entries`: A cap on the entries kept per (entity, component)
+//!   pair, dropping the oldest first
+//! - `history_scope`: Grouping of the entries recorded by a closure into one
+//!   `HistoryTransaction`
 //!
 //! # Design
 //!
@@ -20,6 +32,9 @@
 //!    - `(HistoryOf, component_entity)` - which component type
 //!    - `(HistoryFor, source_entity)` - which entity the value came from
 //!
+//! The current tick is stored per world in the `HistoryClock` singleton, so a
+//! single tracker can record several worlds without their timelines mixing.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -42,7 +57,9 @@
 //!
 //! // Now any changes to Position will be recorded
 //! let entity = world.entity().set(Position { x: 0.0, y: 0.0 });
+//! history.advance_tick(&world);
 //! entity.set(Position { x: 1.0, y: 1.0 });
+//! history.advance_tick(&world);
 //! entity.set(Position { x: 2.0, y: 2.0 });
 //!
 //! // Query history
@@ -55,13 +72,31 @@
 #![allow(clippy::missing_panics_doc)]
 #![allow(clippy::missing_safety_doc)]
 
+mod analyze;
+mod compact;
+mod retention;
+mod sampling;
+mod scope;
+mod watch;
+
 use core::ffi::c_void;
 use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
+use crossbeam_channel::{Receiver, Sender, unbounded};
 use flecs_ecs::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::retention::{Pending, Retention};
+
+pub use crate::analyze::{Analyzer, AnalyzerSet, Anomaly, AnomalyFor};
+pub use crate::compact::Compaction;
+pub use crate::sampling::{Sampler, SamplingPolicy};
+pub use crate::scope::{HistoryTransaction, history_scope};
+pub use crate::watch::{Change, WatchExt};
+
 // ════════════════════════════════════════════════════════════════════════════
 // SerializeInfo - attached to component entities
 // ════════════════════════════════════════════════════════════════════════════
@@ -176,6 +211,10 @@ pub struct HistoryEntry {
 
     /// The component entity ID (which component type this is).
     pub component_id: u64,
+
+    /// Id of the [`HistoryTransaction`] this entry was recorded in, if it
+    /// was recorded inside a [`history_scope`].
+    pub transaction: Option<u64>,
 }
 
 impl HistoryEntry {
@@ -208,23 +247,137 @@ pub struct HistoryFor;
 // History Tracker - manages history recording
 // ════════════════════════════════════════════════════════════════════════════
 
+/// Source of unique ids for worlds attached to a tracker.
+static NEXT_WORLD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Shared, lock-free tick counter.
+///
+/// Clones share the same counter. `OnSet` hooks capture one of these, so
+/// reading the tick costs a single relaxed atomic load.
+#[derive(Clone, Debug, Default)]
+pub struct TickCounter(Arc<AtomicU64>);
+
+impl TickCounter {
+    /// Read the current tick.
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Set the current tick.
+    pub fn set(&self, tick: u64) {
+        self.0.store(tick, Ordering::Relaxed);
+    }
+
+    /// Advance by one tick, returning the new tick.
+    pub fn advance(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Per-world history clock, stored as a singleton.
+///
+/// Each world attached to a [`HistoryTracker`] has its own clock, so worlds
+/// sharing a tracker keep isolated timelines.
+#[derive(Component, Clone, Debug)]
+pub struct HistoryClock {
+    /// Current tick of this world's timeline.
+    pub tick: TickCounter,
+
+    /// Identifies this world in the tracker's per-world state.
+    world_id: u64,
+
+    /// Entry ids of this world's (entity, component) pairs, capped at the
+    /// tracker's max entries when `pending` is drained.
+    retention: Arc<Mutex<Retention>>,
+
+    /// Changes to `retention` queued by hooks, which never lock it.
+    pending: Sender<Pending>,
+    pending_rx: Receiver<Pending>,
+}
+
 /// Shared state for history tracking across observers.
 #[derive(Clone)]
 struct HistoryState {
-    /// Current tick counter.
-    tick: Arc<Mutex<u64>>,
-
-    /// Maximum number of entries per (entity, component) pair.
-    #[allow(dead_code)]
+    /// Entries kept per (entity, component) pair; see [`Retention`].
     max_entries: usize,
+
+    /// Samplers for components tracked with a policy other than `Always`,
+    /// keyed by (world id, component entity id).
+    samplers: Arc<Mutex<HashMap<(u64, u64), Sampler>>>,
+
+    /// Analyzers, keyed by (world id, component entity id).
+    analyzers: Arc<Mutex<HashMap<(u64, u64), AnalyzerSet>>>,
+
+    /// Whether any analyzer was registered, so hooks can skip the lock.
+    analyzing: Arc<AtomicBool>,
+
+    /// How to compare values of components that are compacted, keyed by
+    /// (world id, component entity id).
+    compactions: Arc<Mutex<HashMap<(u64, u64), Compaction>>>,
 }
 
 impl Default for HistoryState {
     fn default() -> Self {
         Self {
-            tick: Arc::new(Mutex::new(0)),
             max_entries: 1000,
+            samplers: Arc::new(Mutex::new(HashMap::new())),
+            analyzers: Arc::new(Mutex::new(HashMap::new())),
+            analyzing: Arc::new(AtomicBool::new(false)),
+            compactions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl HistoryState {
+    /// Run the analyzers of an entry's component, recording what they flag.
+    fn run_analyzers(&self, world_id: u64, entity: EntityView<'_>, entry: &HistoryEntry) {
+        let anomalies = {
+            let mut analyzers = self.analyzers.lock().unwrap();
+            let Some(set) = analyzers.get_mut(&(world_id, entry.component_id)) else {
+                return;
+            };
+            set.run(entity, entry)
+        };
+
+        let world = entity.world();
+        for anomaly in anomalies {
+            tracing::warn!(
+                "{} flagged #{} at tick {}: {}",
+                anomaly.analyzer,
+                entity.id().0,
+                anomaly.tick,
+                anomaly.message
+            );
+            world.entity().set(anomaly).add((AnomalyFor, entity));
+        }
+    }
+
+    /// Record `entry` as a history entry of `entity`'s `comp_entity`.
+    fn record(
+        &self,
+        clock: &HistoryClock,
+        entity: EntityView<'_>,
+        comp_entity: EntityView<'_>,
+        entry: HistoryEntry,
+    ) {
+        if self.analyzing.load(Ordering::Relaxed) {
+            self.run_analyzers(clock.world_id, entity, &entry);
         }
+
+        // Create a history entry as a new entity with pair relations
+        let world = entity.world();
+        let id = world
+            .entity()
+            .set(entry)
+            .add((HistoryOf, comp_entity))
+            .add((HistoryFor, entity))
+            .id();
+
+        // Counted towards the cap when the queue is drained
+        let key = (entity.id().0, comp_entity.id().0);
+        let _ = clock.pending.send(Pending::Push(key, id));
     }
 }
 
@@ -233,6 +386,9 @@ impl Default for HistoryState {
 /// Create one of these and call `track_component::<T>()` for each component
 /// type you want to track. The tracker will automatically record changes
 /// to any component that has `SerializeInfo` attached.
+///
+/// One tracker can serve several worlds. Ticks live in each world's
+/// [`HistoryClock`] singleton, so every world keeps its own timeline.
 pub struct HistoryTracker {
     state: HistoryState,
 }
@@ -244,19 +400,46 @@ impl HistoryTracker {
     }
 
     /// Create a new history tracker with a custom max entries limit.
+    ///
+    /// Each (entity, component) pair keeps at most `max_entries` entries;
+    /// recording past that deletes the pair's oldest entry.
     pub fn with_max_entries(world: &World, max_entries: usize) -> Self {
         let state = HistoryState {
             max_entries,
             ..Default::default()
         };
 
+        let tracker = Self { state };
+        tracker.attach(world);
+        tracker
+    }
+
+    /// Prepare a world for history tracking.
+    ///
+    /// Registers the history components and gives the world its own
+    /// [`HistoryClock`] starting at tick 0. Does nothing if the world already
+    /// has a clock. Called implicitly by [`new`](Self::new) and
+    /// [`track_component`](Self::track_component).
+    pub fn attach(&self, world: &World) {
+        if clock(world).is_some() {
+            return;
+        }
+
         // Register our components
         world.component::<SerializeInfo>();
         world.component::<HistoryEntry>();
         world.component::<HistoryOf>();
         world.component::<HistoryFor>();
-
-        Self { state }
+        world.component::<HistoryTransaction>();
+
+        let (pending, pending_rx) = unbounded();
+        world.set(HistoryClock {
+            tick: TickCounter::default(),
+            world_id: NEXT_WORLD_ID.fetch_add(1, Ordering::Relaxed),
+            retention: Arc::new(Mutex::new(Retention::new(self.state.max_entries))),
+            pending,
+            pending_rx,
+        });
     }
 
     /// Enable history tracking for a specific component type.
@@ -264,10 +447,27 @@ impl HistoryTracker {
     /// This sets up an `on_set` hook for the component. The component must
     /// already be registered with `.serializable()`.
     ///
+    /// Every set is recorded; use [`track_component_with`](Self::track_component_with)
+    /// to throttle high-frequency components.
+    ///
     /// # Panics
     ///
     /// Panics if the component doesn't have `SerializeInfo` attached.
     pub fn track_component<T>(&self, world: &World)
+    where
+        T: ComponentId + 'static,
+    {
+        self.track_component_with::<T>(world, SamplingPolicy::Always);
+    }
+
+    /// Enable history tracking for a component type with a sampling policy.
+    ///
+    /// The policy is applied per entity; see [`SamplingPolicy`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component doesn't have `SerializeInfo` attached.
+    pub fn track_component_with<T>(&self, world: &World, policy: SamplingPolicy)
     where
         T: ComponentId + 'static,
     {
@@ -283,55 +483,247 @@ impl HistoryTracker {
             core::any::type_name::<T>()
         );
 
+        self.attach(world);
+        let clock = clock(world).expect("world was just attached");
+        let world_id = clock.world_id;
         let comp_id = comp_entity.id().0;
+        let sampled = policy != SamplingPolicy::Always;
+        if sampled {
+            self.state
+                .samplers
+                .lock()
+                .unwrap()
+                .insert((world_id, comp_id), Sampler::new(policy));
+        }
+
+        // Free a pair's buffer and sampler state once the component is gone,
+        // despawns included, so neither grows with every entity ever seen
+        let pending = clock.pending.clone();
+        let samplers = Arc::clone(&self.state.samplers);
+        world
+            .observer::<flecs::OnRemove, ()>()
+            .with(comp_entity.id())
+            .each_entity(move |entity, ()| {
+                let _ = pending.send(Pending::ForgetPair((entity.id().0, comp_id)));
+                if sampled
+                    && let Some(sampler) = samplers.lock().unwrap().get_mut(&(world_id, comp_id))
+                {
+                    sampler.forget(entity.id().0);
+                }
+            });
 
         // Set up an OnSet hook for this component
         world.component::<T>().on_set(
             move |entity: EntityView<'_>, component: &mut <T as ComponentId>::UnderlyingType| {
-                // Get current tick
-                let tick = {
-                    let guard = state.tick.lock().unwrap();
-                    *guard
-                };
+                // Hooks are registered per world, so the captured clock is
+                // this world's
+                let tick = clock.tick.get();
+                let world = entity.world();
 
                 // Serialize the component value using the SerializeInfo
                 // We need to get SerializeInfo from the component entity
-                let world = entity.world();
                 let comp_entity = world.component::<T>().entity();
 
                 if let Some(info) = comp_entity.try_get::<&SerializeInfo>(|s| s.clone()) {
                     let ptr = core::ptr::from_ref(component).cast::<c_void>();
                     let bytes = (info.to_bytes)(ptr, info.component_size);
 
-                    // Create a history entry as a new entity with pair relations
-                    world
-                        .entity()
-                        .set(HistoryEntry {
-                            tick,
-                            data: bytes,
-                            component_id: comp_id,
-                        })
-                        .add((HistoryOf, comp_entity))
-                        .add((HistoryFor, entity));
+                    // `Always` skips the sampler lock entirely
+                    if sampled {
+                        let mut samplers = state.samplers.lock().unwrap();
+                        let admitted = samplers
+                            .get_mut(&(world_id, comp_id))
+                            .is_none_or(|sampler| sampler.admit(entity.id().0, tick, &bytes));
+                        if !admitted {
+                            return;
+                        }
+                    }
+
+                    let entry = HistoryEntry {
+                        tick,
+                        data: bytes,
+                        component_id: comp_id,
+                        transaction: scope::transaction(world_id, &world, tick),
+                    };
+                    state.record(&clock, entity, comp_entity, entry);
                 }
             },
         );
     }
 
-    /// Advance the tick counter.
-    pub fn advance_tick(&self) {
-        let mut guard = self.state.tick.lock().unwrap();
-        *guard += 1;
+    /// Advance the tick counter of `world`.
+    ///
+    /// Also records the sets [`SamplingPolicy::Throttled`] held back whose
+    /// interval has passed, and enforces the
+    /// [max entries](Self::enforce_max_entries).
+    pub fn advance_tick(&self, world: &World) {
+        self.enforce_max_entries(world);
+        let tick = self.tick_counter(world).advance();
+        let due = self.take_held(world, |sampler| {
+            sampler
+                .take_due(tick)
+                .into_iter()
+                .map(|(entity, tick, bytes)| (Entity(entity), tick, bytes))
+                .collect()
+        });
+        self.record_held(world, due);
     }
 
-    /// Get the current tick.
-    pub fn current_tick(&self) -> u64 {
-        *self.state.tick.lock().unwrap()
+    /// Record the sets [`SamplingPolicy::Throttled`] held back for `entity`
+    /// right away.
+    ///
+    /// Call this before despawning an entity (e.g. on disconnect), so its
+    /// last values aren't lost with it.
+    pub fn flush_entity(&self, world: &World, entity: impl Into<Entity>) {
+        let entity = entity.into();
+        let held = self.take_held(world, |sampler| {
+            sampler
+                .flush(entity.0)
+                .map(|(tick, bytes)| (entity, tick, bytes))
+                .into_iter()
+                .collect()
+        });
+        self.record_held(world, held);
     }
 
-    /// Set the current tick.
-    pub fn set_tick(&self, tick: u64) {
-        *self.state.tick.lock().unwrap() = tick;
+    /// Collect held sets from `world`'s samplers as (component entity id,
+    /// entity, tick, bytes).
+    fn take_held(
+        &self,
+        world: &World,
+        mut take: impl FnMut(&mut Sampler) -> Vec<(Entity, u64, Vec<u8>)>,
+    ) -> Vec<(u64, Entity, u64, Vec<u8>)> {
+        let Some(clock) = clock(world) else {
+            return Vec::new();
+        };
+        let mut samplers = self.state.samplers.lock().unwrap();
+        samplers
+            .iter_mut()
+            .filter(|((world_id, _), _)| *world_id == clock.world_id)
+            .flat_map(|(&(_, comp_id), sampler)| {
+                take(sampler)
+                    .into_iter()
+                    .map(move |(entity, tick, bytes)| (comp_id, entity, tick, bytes))
+            })
+            .collect()
+    }
+
+    /// Record sets taken with [`take_held`](Self::take_held), skipping
+    /// entities that no longer exist.
+    fn record_held(&self, world: &World, held: Vec<(u64, Entity, u64, Vec<u8>)>) {
+        let Some(clock) = clock(world) else {
+            return;
+        };
+        for (comp_id, entity, tick, data) in held {
+            if !world.is_alive(entity) {
+                continue;
+            }
+            let entry = HistoryEntry {
+                tick,
+                data,
+                component_id: comp_id,
+                transaction: None,
+            };
+            self.state.record(
+                &clock,
+                world.entity_from_id(entity),
+                world.entity_from_id(Entity(comp_id)),
+                entry,
+            );
+        }
+    }
+
+    /// Get the current tick of `world` (0 if it was never attached).
+    pub fn current_tick(&self, world: &World) -> u64 {
+        clock(world).map_or(0, |clock| clock.tick.get())
+    }
+
+    /// Set the current tick of `world`, enforcing the
+    /// [max entries](Self::enforce_max_entries) first.
+    pub fn set_tick(&self, world: &World, tick: u64) {
+        self.enforce_max_entries(world);
+        self.tick_counter(world).set(tick);
+    }
+
+    /// Delete the oldest entries of every pair of `world` over the cap set
+    /// with [`with_max_entries`](Self::with_max_entries).
+    ///
+    /// Hooks only queue their entries, so a pair can go over the cap until
+    /// this runs; [`advance_tick`](Self::advance_tick) and
+    /// [`set_tick`](Self::set_tick) call it.
+    pub fn enforce_max_entries(&self, world: &World) {
+        let Some(clock) = clock(world) else {
+            return;
+        };
+        let pruned = clock.retention.lock().unwrap().apply(&clock.pending_rx);
+        for oldest in pruned {
+            if world.is_alive(oldest) {
+                world.entity_from_id(oldest).destruct();
+            }
+        }
+    }
+
+    /// Get a handle to the tick counter of `world`.
+    ///
+    /// The handle can be cached and read from any thread without going
+    /// through the world, which is the fast path for hot code.
+    pub fn tick_counter(&self, world: &World) -> TickCounter {
+        self.attach(world);
+        clock(world).expect("world was just attached").tick
+    }
+
+    /// Run `analyzer` on every entry recorded for `T` in `world`.
+    ///
+    /// `T` still has to be tracked for entries to be recorded. What the
+    /// analyzer flags becomes an [`Anomaly`] entity; see
+    /// [`get_anomalies`](Self::get_anomalies).
+    pub fn analyze<T>(&self, world: &World, analyzer: impl Analyzer + 'static)
+    where
+        T: ComponentId + 'static,
+    {
+        self.attach(world);
+        let HistoryClock { world_id, .. } = clock(world).expect("world was just attached");
+        let comp_id = world.component::<T>().entity().id().0;
+        world.component::<Anomaly>();
+        world.component::<AnomalyFor>();
+
+        self.state
+            .analyzers
+            .lock()
+            .unwrap()
+            .entry((world_id, comp_id))
+            .or_default()
+            .push(Box::new(analyzer));
+        self.state.analyzing.store(true, Ordering::Relaxed);
+    }
+
+    /// Query all anomalies flagged for a specific entity, oldest first.
+    pub fn get_anomalies(&self, world: &World, entity: impl Into<Entity>) -> Vec<Anomaly> {
+        let entity = entity.into();
+        let mut results = Vec::new();
+
+        world
+            .query::<&Anomaly>()
+            .with((AnomalyFor, entity))
+            .build()
+            .each(|anomaly| {
+                results.push(anomaly.clone());
+            });
+
+        results.sort_by_key(|a| a.tick);
+        results
+    }
+
+    /// Query all anomalies, oldest first.
+    pub fn get_all_anomalies(&self, world: &World) -> Vec<Anomaly> {
+        let mut results = Vec::new();
+
+        world.query::<&Anomaly>().build().each(|anomaly| {
+            results.push(anomaly.clone());
+        });
+
+        results.sort_by_key(|a| a.tick);
+        results
     }
 
     /// Query all history entries for a specific entity and component type.
@@ -393,6 +785,41 @@ impl HistoryTracker {
         results
     }
 
+    /// Get the transaction with id `id`, if it recorded any entries.
+    pub fn get_transaction(&self, world: &World, id: u64) -> Option<HistoryTransaction> {
+        let mut found = None;
+        world
+            .query::<&HistoryTransaction>()
+            .build()
+            .each(|transaction| {
+                if transaction.id == id {
+                    found = Some(transaction.clone());
+                }
+            });
+        found
+    }
+
+    /// Query every entry of a transaction with the entity it belongs to,
+    /// oldest first.
+    pub fn get_transaction_entries(&self, world: &World, id: u64) -> Vec<(Entity, HistoryEntry)> {
+        let mut results = Vec::new();
+
+        world
+            .query::<&HistoryEntry>()
+            .build()
+            .each_entity(|e, entry| {
+                if entry.transaction != Some(id) {
+                    return;
+                }
+                if let Some(source) = e.target(HistoryFor, 0) {
+                    results.push((source.id(), entry.clone()));
+                }
+            });
+
+        results.sort_by_key(|(_, e)| e.tick);
+        results
+    }
+
     /// Get the value of a component at a specific tick.
     ///
     /// Returns the most recent value at or before the given tick.
@@ -424,9 +851,76 @@ impl HistoryTracker {
             .collect()
     }
 
+    /// Drop repeated values of `T` when `world` is [compacted](Self::compact).
+    pub fn compact_on_equal<T>(&self, world: &World)
+    where
+        T: ComponentId + for<'de> Deserialize<'de> + PartialEq + 'static,
+    {
+        self.attach(world);
+        let HistoryClock { world_id, .. } = clock(world).expect("world was just attached");
+        let comp_id = world.component::<T>().entity().id().0;
+        self.state
+            .compactions
+            .lock()
+            .unwrap()
+            .insert((world_id, comp_id), Compaction::of::<T>());
+    }
+
+    /// Drop entries that repeat the value recorded before them, for the
+    /// components opted in with [`compact_on_equal`](Self::compact_on_equal).
+    ///
+    /// Returns the number of entries dropped. See [`Compaction`].
+    pub fn compact(&self, world: &World) -> usize {
+        let Some(clock) = clock(world) else {
+            return 0;
+        };
+        self.enforce_max_entries(world);
+        let compactions: Vec<(u64, Compaction)> = self
+            .state
+            .compactions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((world_id, _), _)| *world_id == clock.world_id)
+            .map(|(&(_, comp_id), &compaction)| (comp_id, compaction))
+            .collect();
+
+        let mut dropped = Vec::new();
+        for (comp_id, compaction) in compactions {
+            let mut by_entity: HashMap<u64, Vec<(Entity, HistoryEntry)>> = HashMap::new();
+            world
+                .query::<&HistoryEntry>()
+                .with((HistoryOf, Entity(comp_id)))
+                .build()
+                .each_entity(|e, entry| {
+                    if let Some(source) = e.target(HistoryFor, 0) {
+                        by_entity
+                            .entry(source.id().0)
+                            .or_default()
+                            .push((e.id(), entry.clone()));
+                    }
+                });
+
+            let mut retention = clock.retention.lock().unwrap();
+            for (&source, entries) in &mut by_entity {
+                entries.sort_by_key(|(_, entry)| entry.tick);
+                let keyed: Vec<_> = entries.iter().map(|(id, entry)| (*id, entry)).collect();
+                let redundant = compaction.redundant(&keyed);
+                retention.remove((source, comp_id), &redundant);
+                dropped.extend(redundant);
+            }
+        }
+
+        for &id in &dropped {
+            world.entity_from_id(id).destruct();
+        }
+        dropped.len()
+    }
+
     /// Clear all history for a specific entity.
     pub fn clear_entity_history(&self, world: &World, entity: impl Into<Entity>) {
         let entity = entity.into();
+        self.enforce_max_entries(world);
 
         // Find all history entries for this entity and delete them
         let mut to_delete = Vec::new();
@@ -442,10 +936,18 @@ impl HistoryTracker {
         for id in to_delete {
             world.entity_from_id(id).destruct();
         }
+        if let Some(clock) = clock(world) {
+            clock.retention.lock().unwrap().forget_entity(entity.0);
+        }
+
+        // The next set should be recorded, whatever the policy
+        self.for_each_sampler(world, |sampler| sampler.forget(entity.0));
+        self.for_each_analyzer_set(world, |set| set.forget(entity.0));
     }
 
     /// Clear all history.
     pub fn clear_all_history(&self, world: &World) {
+        self.enforce_max_entries(world);
         let mut to_delete = Vec::new();
 
         world.query::<&HistoryEntry>().build().each_entity(|e, _| {
@@ -455,9 +957,46 @@ impl HistoryTracker {
         for id in to_delete {
             world.entity_from_id(id).destruct();
         }
+        if let Some(clock) = clock(world) {
+            clock.retention.lock().unwrap().clear();
+        }
+
+        self.for_each_sampler(world, Sampler::clear);
+        self.for_each_analyzer_set(world, AnalyzerSet::clear);
+    }
+
+    /// Run `f` on every sampler belonging to `world`.
+    fn for_each_sampler(&self, world: &World, mut f: impl FnMut(&mut Sampler)) {
+        let Some(clock) = clock(world) else {
+            return;
+        };
+        let mut samplers = self.state.samplers.lock().unwrap();
+        for ((world_id, _), sampler) in samplers.iter_mut() {
+            if *world_id == clock.world_id {
+                f(sampler);
+            }
+        }
+    }
+
+    /// Run `f` on every analyzer set belonging to `world`.
+    fn for_each_analyzer_set(&self, world: &World, mut f: impl FnMut(&mut AnalyzerSet)) {
+        let Some(clock) = clock(world) else {
+            return;
+        };
+        let mut analyzers = self.state.analyzers.lock().unwrap();
+        for ((world_id, _), set) in analyzers.iter_mut() {
+            if *world_id == clock.world_id {
+                f(set);
+            }
+        }
     }
 }
 
+/// Read a world's history clock, if it is attached to a tracker.
+fn clock(world: &World) -> Option<HistoryClock> {
+    world.try_get::<&HistoryClock>(HistoryClock::clone)
+}
+
 // ════════════════════════════════════════════════════════════════════════════
 // Utility functions
 // ════════════════════════════════════════════════════════════════════════════
@@ -508,9 +1047,10 @@ where
 
 pub mod prelude {
     pub use crate::{
-        HistoryEntry, HistoryFor, HistoryOf, HistoryTracker, SerializableExt, SerializeError,
-        SerializeInfo, get_serialize_info, is_serializable, serialize_component,
-        serialize_component_json,
+        Analyzer, Anomaly, AnomalyFor, Change, Compaction, HistoryClock, HistoryEntry, HistoryFor,
+        HistoryOf, HistoryTracker, HistoryTransaction, SamplingPolicy, SerializableExt,
+        SerializeError, SerializeInfo, TickCounter, WatchExt, get_serialize_info, history_scope,
+        is_serializable, serialize_component, serialize_component_json,
     };
 }
 
@@ -591,13 +1131,13 @@ mod tests {
         // Create an entity and set Position multiple times
         let entity = world.entity();
 
-        history.set_tick(0);
+        history.set_tick(&world, 0);
         entity.set(Position { x: 0.0, y: 0.0 });
 
-        history.set_tick(1);
+        history.set_tick(&world, 1);
         entity.set(Position { x: 1.0, y: 1.0 });
 
-        history.set_tick(2);
+        history.set_tick(&world, 2);
         entity.set(Position { x: 2.0, y: 2.0 });
 
         // Query history
@@ -625,13 +1165,13 @@ mod tests {
 
         let entity = world.entity();
 
-        history.set_tick(0);
+        history.set_tick(&world, 0);
         entity.set(Position { x: 0.0, y: 0.0 });
 
-        history.set_tick(5);
+        history.set_tick(&world, 5);
         entity.set(Position { x: 5.0, y: 5.0 });
 
-        history.set_tick(10);
+        history.set_tick(&world, 10);
         entity.set(Position { x: 10.0, y: 10.0 });
 
         // Query at specific ticks
@@ -688,11 +1228,11 @@ mod tests {
         let entity1 = world.entity();
         let entity2 = world.entity();
 
-        history.set_tick(0);
+        history.set_tick(&world, 0);
         entity1.set(Position { x: 1.0, y: 1.0 });
         entity2.set(Position { x: 100.0, y: 100.0 });
 
-        history.set_tick(1);
+        history.set_tick(&world, 1);
         entity1.set(Position { x: 2.0, y: 2.0 });
         entity2.set(Position { x: 200.0, y: 200.0 });
 
@@ -722,7 +1262,7 @@ mod tests {
 
         let entity = world.entity();
 
-        history.set_tick(0);
+        history.set_tick(&world, 0);
         entity.set(Position { x: 1.0, y: 1.0 });
         entity.set(Velocity { x: 10.0, y: 10.0 });
 
@@ -730,4 +1270,393 @@ mod tests {
         let all_entries = history.get_entity_history(&world, entity);
         assert_eq!(all_entries.len(), 2);
     }
+
+    #[test]
+    fn test_sampling_policy() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+        world.component::<Velocity>().serializable::<Velocity>();
+
+        let history = HistoryTracker::new(&world);
+        history.track_component_with::<Position>(&world, SamplingPolicy::MinInterval(5));
+        history.track_component_with::<Velocity>(&world, SamplingPolicy::OnChange);
+
+        let entity = world.entity();
+        for tick in 0..10 {
+            history.set_tick(&world, tick);
+            entity.set(Position { x: 0.0, y: 0.0 });
+            entity.set(Velocity { x: 1.0, y: 0.0 });
+        }
+
+        let positions = history.get_component_history::<Position>(&world, entity);
+        let ticks: Vec<_> = positions.iter().map(|e| e.tick).collect();
+        assert_eq!(ticks, [0, 5]);
+
+        let velocities = history.get_component_history::<Velocity>(&world, entity);
+        assert_eq!(velocities.len(), 1);
+
+        // Clearing history resets sampling, so the next set is recorded
+        history.clear_entity_history(&world, entity);
+        entity.set(Velocity { x: 1.0, y: 0.0 });
+        assert_eq!(
+            history
+                .get_component_history::<Velocity>(&world, entity)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_throttled_records_held_sets() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+
+        let history = HistoryTracker::new(&world);
+        history.track_component_with::<Position>(&world, SamplingPolicy::Throttled(5));
+
+        let entity = world.entity();
+        for tick in 0..4 {
+            history.set_tick(&world, tick);
+            entity.set(Position {
+                x: tick as f32,
+                y: 0.0,
+            });
+        }
+        // Ticks 1-3 were held; the last one is recorded once the interval passed
+        history.advance_tick(&world);
+        assert_eq!(
+            history
+                .get_component_history::<Position>(&world, entity)
+                .len(),
+            1
+        );
+        history.advance_tick(&world);
+
+        entity.set(Position { x: 6.0, y: 0.0 });
+        history.flush_entity(&world, entity);
+
+        let entries = history.get_component_history::<Position>(&world, entity);
+        let recorded: Vec<_> = entries
+            .iter()
+            .map(|e| (e.tick, e.deserialize::<Position>().unwrap().x))
+            .collect();
+        assert_eq!(recorded, [(0, 0.0), (3, 3.0), (5, 6.0)]);
+    }
+
+    #[test]
+    fn test_compact_drops_repeated_values() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+        world.component::<Velocity>().serializable::<Velocity>();
+
+        let history = HistoryTracker::new(&world);
+        history.track_component::<Position>(&world);
+        history.track_component::<Velocity>(&world);
+        history.compact_on_equal::<Position>(&world);
+
+        let entity = world.entity();
+        for (tick, x) in [0.0, 0.0, 1.0, 1.0, 1.0, 0.0].into_iter().enumerate() {
+            history.set_tick(&world, tick as u64);
+            entity.set(Position { x, y: 0.0 });
+            entity.set(Velocity { x: 0.0, y: 0.0 });
+        }
+
+        assert_eq!(history.compact(&world), 3);
+        let ticks: Vec<_> = history
+            .get_component_history::<Position>(&world, entity)
+            .iter()
+            .map(|e| e.tick)
+            .collect();
+        assert_eq!(ticks, [0, 2, 5]);
+
+        // Velocity wasn't opted in
+        assert_eq!(
+            history
+                .get_component_history::<Velocity>(&world, entity)
+                .len(),
+            6
+        );
+        assert_eq!(history.compact(&world), 0);
+    }
+
+    #[test]
+    fn test_max_entries_prunes_oldest() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+        world.component::<Velocity>().serializable::<Velocity>();
+
+        let history = HistoryTracker::with_max_entries(&world, 3);
+        history.track_component::<Position>(&world);
+        history.track_component::<Velocity>(&world);
+
+        let a = world.entity();
+        let b = world.entity();
+        for tick in 0..5 {
+            history.set_tick(&world, tick);
+            a.set(Position {
+                x: tick as f32,
+                y: 0.0,
+            });
+            a.set(Velocity { x: 0.0, y: 0.0 });
+            b.set(Position { x: 0.0, y: 0.0 });
+        }
+        history.enforce_max_entries(&world);
+
+        let ticks: Vec<_> = history
+            .get_component_history::<Position>(&world, a)
+            .iter()
+            .map(|e| e.tick)
+            .collect();
+        assert_eq!(ticks, [2, 3, 4]);
+        assert_eq!(
+            history.get_component_history::<Velocity>(&world, a).len(),
+            3
+        );
+        assert_eq!(
+            history.get_component_history::<Position>(&world, b).len(),
+            3
+        );
+
+        // Cleared entries don't count towards the cap
+        history.clear_entity_history(&world, a);
+        a.set(Position { x: 9.0, y: 0.0 });
+        assert_eq!(
+            history.get_component_history::<Position>(&world, a).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_on_set_leaves_retention_unlocked() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+
+        let history = HistoryTracker::with_max_entries(&world, 2);
+        history.track_component::<Position>(&world);
+
+        // Would deadlock if the hook locked the buffers
+        let entity = world.entity();
+        let clock = clock(&world).unwrap();
+        {
+            let _held = clock.retention.lock().unwrap();
+            for x in 0..4 {
+                entity.set(Position {
+                    x: x as f32,
+                    y: 0.0,
+                });
+            }
+        }
+        assert_eq!(
+            history
+                .get_component_history::<Position>(&world, entity)
+                .len(),
+            4
+        );
+
+        history.advance_tick(&world);
+        assert_eq!(
+            history
+                .get_component_history::<Position>(&world, entity)
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_despawn_frees_retention_buffers() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+
+        let history = HistoryTracker::with_max_entries(&world, 3);
+        history.track_component::<Position>(&world);
+
+        let a = world.entity().set(Position { x: 0.0, y: 0.0 });
+        let b = world.entity().set(Position { x: 0.0, y: 0.0 });
+        let buffers = || {
+            history.enforce_max_entries(&world);
+            clock(&world)
+                .unwrap()
+                .retention
+                .lock()
+                .unwrap()
+                .pair_count()
+        };
+        assert_eq!(buffers(), 2);
+
+        a.remove::<Position>();
+        assert_eq!(buffers(), 1);
+        b.destruct();
+        assert_eq!(buffers(), 0);
+
+        // The entries themselves stay queryable
+        assert_eq!(
+            history.get_component_history::<Position>(&world, a).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_despawn_frees_sampler_state() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+
+        let history = HistoryTracker::new(&world);
+        history.track_component_with::<Position>(&world, SamplingPolicy::MinInterval(5));
+
+        let a = world.entity().set(Position { x: 0.0, y: 0.0 });
+        let b = world.entity().set(Position { x: 0.0, y: 0.0 });
+        let sampled = || {
+            let mut count = 0;
+            history.for_each_sampler(&world, |sampler| count += sampler.entity_count());
+            count
+        };
+        assert_eq!(sampled(), 2);
+
+        a.remove::<Position>();
+        assert_eq!(sampled(), 1);
+        b.destruct();
+        assert_eq!(sampled(), 0);
+    }
+
+    #[test]
+    fn test_worlds_have_isolated_timelines() {
+        let world_a = World::new();
+        let world_b = World::new();
+        world_a.component::<Position>().serializable::<Position>();
+        world_b.component::<Position>().serializable::<Position>();
+
+        let history = HistoryTracker::new(&world_a);
+        history.track_component::<Position>(&world_a);
+        history.track_component::<Position>(&world_b);
+
+        history.set_tick(&world_a, 10);
+        history.advance_tick(&world_b);
+        assert_eq!(history.current_tick(&world_a), 10);
+        assert_eq!(history.current_tick(&world_b), 1);
+
+        let a = world_a.entity().set(Position { x: 1.0, y: 1.0 });
+        let b = world_b.entity().set(Position { x: 2.0, y: 2.0 });
+
+        let entries_a = history.get_component_history::<Position>(&world_a, a);
+        let entries_b = history.get_component_history::<Position>(&world_b, b);
+        assert_eq!(entries_a.len(), 1);
+        assert_eq!(entries_a[0].tick, 10);
+        assert_eq!(entries_b.len(), 1);
+        assert_eq!(entries_b[0].tick, 1);
+    }
+
+    #[test]
+    fn test_tick_counter_handle() {
+        let world = World::new();
+        let history = HistoryTracker::new(&world);
+
+        let counter = history.tick_counter(&world);
+        history.advance_tick(&world);
+        assert_eq!(counter.get(), 1);
+
+        counter.set(41);
+        assert_eq!(counter.advance(), 42);
+        assert_eq!(history.current_tick(&world), 42);
+    }
+
+    /// Flags moves longer than a distance.
+    struct Teleport {
+        max_distance: f32,
+    }
+
+    impl Analyzer for Teleport {
+        fn name(&self) -> &str {
+            "teleport"
+        }
+
+        fn analyze(
+            &mut self,
+            _entity: EntityView<'_>,
+            entry: &HistoryEntry,
+            previous: Option<&HistoryEntry>,
+        ) -> Option<String> {
+            let old: Position = previous?.deserialize().ok()?;
+            let new: Position = entry.deserialize().ok()?;
+            let distance = (new.x - old.x).hypot(new.y - old.y);
+            (distance > self.max_distance).then(|| format!("moved {distance}"))
+        }
+    }
+
+    #[test]
+    fn test_analyzer_flags_anomalies() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+
+        let history = HistoryTracker::new(&world);
+        history.track_component::<Position>(&world);
+        history.analyze::<Position>(&world, Teleport { max_distance: 10.0 });
+
+        let entity = world.entity();
+        let other = world.entity();
+        entity.set(Position { x: 0.0, y: 0.0 });
+        other.set(Position { x: 100.0, y: 0.0 });
+        history.set_tick(&world, 1);
+        entity.set(Position { x: 3.0, y: 4.0 });
+        history.set_tick(&world, 2);
+        entity.set(Position { x: 3.0, y: 24.0 });
+
+        let anomalies = history.get_anomalies(&world, entity);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].tick, 2);
+        assert_eq!(anomalies[0].analyzer, "teleport");
+        assert_eq!(anomalies[0].message, "moved 20");
+        assert!(history.get_anomalies(&world, other).is_empty());
+
+        // Cleared history leaves nothing to compare against
+        history.clear_entity_history(&world, entity);
+        entity.set(Position { x: 500.0, y: 0.0 });
+        assert_eq!(history.get_all_anomalies(&world).len(), 1);
+    }
+
+    #[test]
+    fn test_history_scope_groups_entries() {
+        let world = World::new();
+        world.component::<Position>().serializable::<Position>();
+        world.component::<Velocity>().serializable::<Velocity>();
+
+        let history = HistoryTracker::new(&world);
+        history.track_component::<Position>(&world);
+        history.track_component::<Velocity>(&world);
+
+        let entity = world.entity().set(Position { x: 0.0, y: 0.0 });
+        let other = world.entity();
+
+        history.set_tick(&world, 3);
+        history_scope(&world, "teleport", || {
+            entity.set(Position { x: 50.0, y: 50.0 });
+            history_scope(&world, "nested", || {
+                entity.set(Velocity { x: 0.0, y: 0.0 });
+            });
+            other.set(Position { x: 1.0, y: 1.0 });
+        });
+        entity.set(Position { x: 51.0, y: 50.0 });
+
+        let positions = history.get_component_history::<Position>(&world, entity);
+        assert_eq!(positions[0].transaction, None);
+        assert_eq!(positions[2].transaction, None);
+        let id = positions[1].transaction.unwrap();
+
+        let transaction = history.get_transaction(&world, id).unwrap();
+        assert_eq!(transaction.label, "teleport");
+        assert_eq!(transaction.tick, 3);
+
+        let entries = history.get_transaction_entries(&world, id);
+        let sources: Vec<_> = entries.iter().map(|(source, _)| *source).collect();
+        assert_eq!(sources.len(), 3);
+        assert_eq!(sources.iter().filter(|&&s| s == entity.id()).count(), 2);
+        assert!(sources.contains(&other.id()));
+
+        // Each scope is its own transaction
+        history_scope(&world, "again", || {
+            entity.set(Velocity { x: 1.0, y: 0.0 });
+        });
+        let velocities = history.get_component_history::<Velocity>(&world, entity);
+        assert_ne!(velocities[1].transaction, Some(id));
+        assert!(velocities[1].transaction.is_some());
+    }
 }
@@ -0,0 +1,172 @@
+//! Cap on the entries kept per (entity, component) pair.
+//!
+//! `HistoryTracker::with_max_entries` bounds how many entries each pair
+//! keeps. Every pair's entry ids sit in a ring buffer, oldest first, so
+//! enforcing the cap on insert is a push and a pop rather than a query over
+//! the pair's entries:
+//!
+//! ```ignore
+//! let history = HistoryTracker::with_max_entries(&world, 100);
+//! history.track_component::<Position>(&world);
+//!
+//! // The 101st set of an entity's Position deletes its oldest entry
+//! ```
+//!
+//! Entries deleted some other way (compaction, clearing) are taken out of
+//! the buffers too, so they don't count towards the cap.
+//!
+//! Hooks don't touch the buffers: they queue a [`Pending`] change on a
+//! lock-free channel, and the buffers are only locked when the queue is
+//! drained with [`Retention::apply`], by `advance_tick`, `set_tick` and
+//! `enforce_max_entries`. A pair can go over the cap until then, by the sets
+//! of one tick.
+//!
+//! Each world keeps its own buffers and queue in its [`HistoryClock`], next
+//! to its tick. A pair's buffer is freed once the component is removed from
+//! the entity, despawning included; the entries already recorded stay
+//! queryable.
+//!
+//! [`HistoryClock`]: crate::HistoryClock
+
+use std::collections::{HashMap, VecDeque};
+
+use crossbeam_channel::Receiver;
+use flecs_ecs::prelude::*;
+
+/// Identifies an (entity, component) pair within a world: (entity id,
+/// component entity id).
+pub type PairKey = (u64, u64);
+
+/// A change to the buffers, queued by hooks and observers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pending {
+    /// A new entry of a pair, see [`Retention::push`].
+    Push(PairKey, Entity),
+    /// The component was removed, see [`Retention::forget_pair`].
+    ForgetPair(PairKey),
+}
+
+/// Entry ids of every pair, oldest first, capped at `max_entries` each.
+#[derive(Debug)]
+pub struct Retention {
+    max_entries: usize,
+    pairs: HashMap<PairKey, VecDeque<Entity>>,
+}
+
+impl Retention {
+    /// Keep at most `max_entries` per pair. A limit of 0 keeps one.
+    #[must_use]
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            pairs: HashMap::new(),
+        }
+    }
+
+    /// Add `entry` as the newest of its pair.
+    ///
+    /// Returns the oldest entry if the pair went over the cap; the caller
+    /// deletes it.
+    pub fn push(&mut self, key: PairKey, entry: Entity) -> Option<Entity> {
+        let entries = self.pairs.entry(key).or_default();
+        entries.push_back(entry);
+        if entries.len() > self.max_entries {
+            entries.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Apply every queued change in order, returning the entries pushed out
+    /// over the cap; the caller deletes them.
+    pub fn apply(&mut self, pending: &Receiver<Pending>) -> Vec<Entity> {
+        let mut pruned = Vec::new();
+        for change in pending.try_iter() {
+            match change {
+                Pending::Push(key, entry) => pruned.extend(self.push(key, entry)),
+                Pending::ForgetPair(key) => self.forget_pair(key),
+            }
+        }
+        pruned
+    }
+
+    /// Take deleted entries of a pair out of its buffer.
+    pub fn remove(&mut self, key: PairKey, deleted: &[Entity]) {
+        if let Some(entries) = self.pairs.get_mut(&key) {
+            entries.retain(|entry| !deleted.contains(entry));
+            if entries.is_empty() {
+                self.pairs.remove(&key);
+            }
+        }
+    }
+
+    /// Forget a pair, freeing its buffer.
+    pub fn forget_pair(&mut self, key: PairKey) {
+        self.pairs.remove(&key);
+    }
+
+    /// Forget every pair of an entity.
+    pub fn forget_entity(&mut self, entity: u64) {
+        self.pairs.retain(|&(source, _), _| source != entity);
+    }
+
+    /// Forget every pair.
+    pub fn clear(&mut self) {
+        self.pairs.clear();
+    }
+
+    /// Number of pairs holding a buffer.
+    #[cfg(test)]
+    pub fn pair_count(&self) -> usize {
+        self.pairs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_pops_oldest_over_cap() {
+        let mut retention = Retention::new(2);
+        let key = (1, 2);
+        assert_eq!(retention.push(key, Entity(10)), None);
+        assert_eq!(retention.push(key, Entity(11)), None);
+        assert_eq!(retention.push(key, Entity(12)), Some(Entity(10)));
+        assert_eq!(retention.push(key, Entity(13)), Some(Entity(11)));
+
+        // Other pairs have their own cap
+        assert_eq!(retention.push((3, 2), Entity(14)), None);
+    }
+
+    #[test]
+    fn test_removed_entries_free_space() {
+        let mut retention = Retention::new(2);
+        let key = (1, 2);
+        retention.push(key, Entity(10));
+        retention.push(key, Entity(11));
+        retention.remove(key, &[Entity(10)]);
+        assert_eq!(retention.push(key, Entity(12)), None);
+
+        retention.push((1, 3), Entity(13));
+        retention.forget_pair((1, 3));
+        assert_eq!(retention.pairs.len(), 1);
+        retention.forget_entity(1);
+        assert!(retention.pairs.is_empty());
+    }
+
+    #[test]
+    fn test_apply_keeps_queue_order() {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut retention = Retention::new(1);
+        let key = (1, 2);
+        tx.send(Pending::Push(key, Entity(10))).unwrap();
+        tx.send(Pending::Push(key, Entity(11))).unwrap();
+        tx.send(Pending::ForgetPair(key)).unwrap();
+        tx.send(Pending::Push((3, 2), Entity(12))).unwrap();
+
+        assert_eq!(retention.apply(&rx), [Entity(10)]);
+        assert_eq!(retention.pairs.len(), 1);
+        assert!(retention.apply(&rx).is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! Per-component sampling policies.
+//!
+//! Components like `Position` are set on every movement packet, which is far
+//! more often than anyone needs to look back at. A [`SamplingPolicy`] chosen at
+//! `track_component_with` time decides which of those sets become history
+//! entries:
+//!
+//! ```ignore
+//! history.track_component_with::<Position>(&world, SamplingPolicy::MinInterval(5));
+//! history.track_component_with::<GameMode>(&world, SamplingPolicy::OnChange);
+//! ```
+//!
+//! Policies apply per entity: each entity's first set is always recorded, and
+//! what a sampler remembers about an entity is dropped when the component is
+//! removed or the entity despawns.
+//!
+//! [`SamplingPolicy::Throttled`] is `MinInterval` that doesn't lose the last
+//! value: the latest set skipped is held and recorded, with the tick it was
+//! set at, once the interval passed (by `advance_tick`) or when the entity is
+//! flushed with `flush_entity`, e.g. before it despawns.
+
+use std::collections::HashMap;
+
+use change_throttle::Throttle;
+
+/// Decides which `OnSet` events of a tracked component are recorded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SamplingPolicy {
+    /// Record every set.
+    #[default]
+    Always,
+
+    /// Record at most once per this many ticks.
+    MinInterval(u64),
+
+    /// Record only when the serialized value differs from the last recorded one.
+    OnChange,
+
+    /// Record every Nth set (the first set, then the N+1th, ...).
+    EveryN(core::num::NonZeroU32),
+
+    /// Record at most once per this many ticks, holding the latest set in
+    /// between and recording it once the interval passed.
+    Throttled(u64),
+}
+
+/// What a [`Sampler`] remembers about one entity.
+#[derive(Debug, Default)]
+struct SampleState {
+    /// Tick of the last recorded entry.
+    last_tick: u64,
+    /// Sets seen since the last recorded entry.
+    skipped: u32,
+    /// Last recorded bytes (only kept for [`SamplingPolicy::OnChange`]).
+    last_bytes: Vec<u8>,
+}
+
+/// Per-entity bookkeeping for one tracked component.
+#[derive(Debug, Default)]
+pub struct Sampler {
+    policy: SamplingPolicy,
+    entities: HashMap<u64, SampleState>,
+    /// Held sets by entity, as (tick, bytes), for [`SamplingPolicy::Throttled`].
+    throttle: Option<Throttle<u64, (u64, Vec<u8>)>>,
+}
+
+impl Sampler {
+    /// Create a sampler for `policy`.
+    #[must_use]
+    pub fn new(policy: SamplingPolicy) -> Self {
+        let throttle = match policy {
+            SamplingPolicy::Throttled(interval) => Some(Throttle::new(interval)),
+            _ => None,
+        };
+        Self {
+            policy,
+            entities: HashMap::new(),
+            throttle,
+        }
+    }
+
+    /// The policy this sampler applies.
+    #[must_use]
+    pub const fn policy(&self) -> SamplingPolicy {
+        self.policy
+    }
+
+    /// Decide whether a set of `entity` at `tick` should be recorded.
+    ///
+    /// Updates the per-entity state as if the set was seen. Under
+    /// [`SamplingPolicy::Throttled`] a set that isn't recorded now is held;
+    /// see [`take_due`](Self::take_due).
+    pub fn admit(&mut self, entity: u64, tick: u64, bytes: &[u8]) -> bool {
+        if let Some(throttle) = &mut self.throttle {
+            return throttle
+                .offer(entity, (tick, bytes.to_vec()), tick)
+                .is_some();
+        }
+
+        let Some(state) = self.entities.get_mut(&entity) else {
+            let last_bytes = if self.policy == SamplingPolicy::OnChange {
+                bytes.to_vec()
+            } else {
+                Vec::new()
+            };
+            self.entities.insert(
+                entity,
+                SampleState {
+                    last_tick: tick,
+                    skipped: 0,
+                    last_bytes,
+                },
+            );
+            return true;
+        };
+
+        let record = match self.policy {
+            SamplingPolicy::Always => true,
+            SamplingPolicy::MinInterval(interval) => {
+                tick.saturating_sub(state.last_tick) >= interval
+            }
+            SamplingPolicy::OnChange => state.last_bytes != bytes,
+            SamplingPolicy::EveryN(n) => state.skipped + 1 >= n.get(),
+            SamplingPolicy::Throttled(_) => unreachable!("throttled sets are offered above"),
+        };
+
+        if record {
+            state.last_tick = tick;
+            state.skipped = 0;
+            if self.policy == SamplingPolicy::OnChange {
+                bytes.clone_into(&mut state.last_bytes);
+            }
+        } else {
+            state.skipped += 1;
+        }
+        record
+    }
+
+    /// Take the held sets that should be recorded at `tick`, as
+    /// (entity, tick set at, bytes).
+    pub fn take_due(&mut self, tick: u64) -> Vec<(u64, u64, Vec<u8>)> {
+        let Some(throttle) = &mut self.throttle else {
+            return Vec::new();
+        };
+        throttle
+            .take_due(tick)
+            .into_iter()
+            .map(|(entity, (set_at, bytes))| (entity, set_at, bytes))
+            .collect()
+    }
+
+    /// Take the set held for `entity`, if any, as (tick set at, bytes).
+    ///
+    /// Its next set is recorded right away.
+    pub fn flush(&mut self, entity: u64) -> Option<(u64, Vec<u8>)> {
+        self.throttle.as_mut()?.flush(entity)
+    }
+
+    /// Forget everything about `entity`, so its next set is recorded.
+    pub fn forget(&mut self, entity: u64) {
+        self.entities.remove(&entity);
+        if let Some(throttle) = &mut self.throttle {
+            throttle.forget(entity);
+        }
+    }
+
+    /// Number of entities with sampling state.
+    #[cfg(test)]
+    pub(crate) fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Forget all entities.
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        if let Some(throttle) = &mut self.throttle {
+            throttle.flush_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn admitted(sampler: &mut Sampler, sets: &[(u64, &[u8])]) -> Vec<u64> {
+        sets.iter()
+            .filter(|(tick, bytes)| sampler.admit(1, *tick, bytes))
+            .map(|(tick, _)| *tick)
+            .collect()
+    }
+
+    #[test]
+    fn test_min_interval() {
+        let mut sampler = Sampler::new(SamplingPolicy::MinInterval(5));
+        let sets: Vec<(u64, &[u8])> = (0..12).map(|tick| (tick, &[][..])).collect();
+        assert_eq!(admitted(&mut sampler, &sets), [0, 5, 10]);
+    }
+
+    #[test]
+    fn test_on_change() {
+        let mut sampler = Sampler::new(SamplingPolicy::OnChange);
+        let sets: [(u64, &[u8]); 5] = [(0, &[1]), (1, &[1]), (2, &[2]), (3, &[2]), (4, &[1])];
+        assert_eq!(admitted(&mut sampler, &sets), [0, 2, 4]);
+    }
+
+    #[test]
+    fn test_every_n() {
+        let mut sampler = Sampler::new(SamplingPolicy::EveryN(
+            core::num::NonZeroU32::new(3).unwrap(),
+        ));
+        let sets: Vec<(u64, &[u8])> = (0..7).map(|tick| (tick, &[][..])).collect();
+        assert_eq!(admitted(&mut sampler, &sets), [0, 3, 6]);
+    }
+
+    #[test]
+    fn test_throttled_holds_latest() {
+        let mut sampler = Sampler::new(SamplingPolicy::Throttled(5));
+        let sets: Vec<(u64, &[u8])> = vec![(0, &[0]), (1, &[1]), (3, &[3])];
+        assert_eq!(admitted(&mut sampler, &sets), [0]);
+
+        assert!(sampler.take_due(4).is_empty());
+        assert_eq!(sampler.take_due(5), [(1, 3, vec![3])]);
+
+        assert!(!sampler.admit(1, 6, &[6]));
+        assert_eq!(sampler.flush(1), Some((6, vec![6])));
+        assert!(sampler.admit(1, 7, &[7]));
+    }
+
+    #[test]
+    fn test_entities_are_independent() {
+        let mut sampler = Sampler::new(SamplingPolicy::MinInterval(10));
+        assert!(sampler.admit(1, 0, &[]));
+        assert!(sampler.admit(2, 1, &[]));
+        assert!(!sampler.admit(1, 2, &[]));
+
+        sampler.forget(1);
+        assert!(sampler.admit(1, 3, &[]));
+    }
+}
@@ -0,0 +1,156 @@
+//! Transactions: entries recorded together as one logical change.
+//!
+//! A teleport sets `Position`, `ChunkPosition` and `Rotation`, which history
+//! would record as three unrelated entries. Running the change inside
+//! [`history_scope`] tags every entry recorded during the closure with one
+//! transaction id (see [`HistoryEntry::transaction`]) and records a
+//! [`HistoryTransaction`] entity with the scope's label:
+//!
+//! ```ignore
+//! history_scope(&world, "teleport", || {
+//!     player.set(position).set(chunk_position).set(rotation);
+//! });
+//! ```
+//!
+//! Scopes belong to the thread that opens them: only sets made on that
+//! thread are tagged. Scopes nest by joining: entries of an inner scope
+//! belong to the outermost one. Entries are recorded when a change is applied, so changes deferred
+//! inside a system are only recorded once its commands are flushed, after
+//! the scope has closed; open scopes around code that runs outside systems.
+//!
+//! [`HistoryEntry::transaction`]: crate::HistoryEntry::transaction
+
+use core::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use flecs_ecs::prelude::*;
+
+use crate::clock;
+
+/// Source of transaction ids; 0 is never handed out.
+static NEXT_TRANSACTION_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// Scopes open on this thread, by world id.
+    ///
+    /// Thread-local so that a scope only tags what its own thread sets:
+    /// another thread setting components while it's open records them
+    /// outside the transaction.
+    static OPEN: RefCell<HashMap<u64, OpenScope>> = RefCell::new(HashMap::new());
+}
+
+/// A group of entries recorded by one [`history_scope`].
+///
+/// Stored on its own entity, created when the scope records its first entry.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub struct HistoryTransaction {
+    /// Shared by every entry of the transaction.
+    pub id: u64,
+
+    /// What the scope was opened for.
+    pub label: String,
+
+    /// Tick of the first entry.
+    pub tick: u64,
+}
+
+#[derive(Debug)]
+struct OpenScope {
+    id: u64,
+    label: String,
+    /// Whether the `HistoryTransaction` entity exists yet.
+    recorded: bool,
+}
+
+/// Transaction id for an entry of world `world_id` being recorded at `tick`
+/// on this thread, if this thread has a scope open on it, creating the
+/// transaction's entity on its first entry.
+pub fn transaction(world_id: u64, world: &WorldRef<'_>, tick: u64) -> Option<u64> {
+    let (id, first) = claim(world_id)?;
+    if let Some(label) = first {
+        world.entity().set(HistoryTransaction { id, label, tick });
+    }
+    Some(id)
+}
+
+/// Id of the scope this thread has open on a world, with its label if this
+/// is its first entry.
+fn claim(world_id: u64) -> Option<(u64, Option<String>)> {
+    OPEN.with_borrow_mut(|open| {
+        let scope = open.get_mut(&world_id)?;
+        let first = (!scope.recorded).then(|| scope.label.clone());
+        scope.recorded = true;
+        Some((scope.id, first))
+    })
+}
+
+/// Open a scope on a world for this thread, unless one already is. Returns
+/// whether it was opened.
+fn begin(world_id: u64, label: String) -> bool {
+    OPEN.with_borrow_mut(|open| {
+        if open.contains_key(&world_id) {
+            return false;
+        }
+        open.insert(
+            world_id,
+            OpenScope {
+                id: NEXT_TRANSACTION_ID.fetch_add(1, Ordering::Relaxed),
+                label,
+                recorded: false,
+            },
+        );
+        true
+    })
+}
+
+fn end(world_id: u64) {
+    OPEN.with_borrow_mut(|open| open.remove(&world_id));
+}
+
+/// Closes the scope it opened, even if the closure panics.
+struct ScopeGuard(u64);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        end(self.0);
+    }
+}
+
+/// Run `f`, grouping every history entry it records in `world` into one
+/// transaction labelled `label`.
+///
+/// Only sets made on the calling thread join the transaction. Inside another
+/// scope on the same thread, `f` joins that scope's transaction. Runs `f` as
+/// is if `world` isn't attached to a [`HistoryTracker`](crate::HistoryTracker).
+pub fn history_scope<R>(world: &World, label: impl Into<String>, f: impl FnOnce() -> R) -> R {
+    let Some(clock) = clock(world) else {
+        return f();
+    };
+    if !begin(clock.world_id, label.into()) {
+        return f();
+    }
+    let _guard = ScopeGuard(clock.world_id);
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_is_per_thread() {
+        assert!(begin(7, "teleport".to_owned()));
+        let (id, first) = claim(7).unwrap();
+        assert_eq!(first.as_deref(), Some("teleport"));
+        assert_eq!(claim(7), Some((id, None)));
+
+        // Another thread setting meanwhile isn't part of it
+        std::thread::scope(|s| {
+            s.spawn(|| assert_eq!(claim(7), None));
+        });
+
+        end(7);
+        assert_eq!(claim(7), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Typed change notifications for consumers outside the game loop.
+//!
+//! [`WatchExt::watch`] registers `OnSet`/`OnRemove` observers for a component
+//! and forwards every change into a channel, so another thread (dashboard,
+//! metrics, persistence) can follow it without writing observers of its own:
+//!
+//! ```ignore
+//! let positions = world.watch::<Position>();
+//! std::thread::spawn(move || {
+//!     for change in positions {
+//!         println!("{:?}: {:?} -> {:?}", change.entity, change.old, change.new);
+//!     }
+//! });
+//! ```
+//!
+//! The watcher keeps the last value it sent per entity to fill in
+//! [`Change::old`], so `old` is `None` the first time it sees an entity,
+//! including entities that had the component before `watch` was called.
+//! Once the receiver is dropped the observers stop sending and free that
+//! cache; they stay registered until the world is dropped.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use flecs_ecs::prelude::*;
+
+/// A change of a watched component on one entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<T> {
+    pub entity: Entity,
+    /// Value before the change, `None` if the watcher hasn't seen one.
+    pub old: Option<T>,
+    /// Value after the change, `None` if the component was removed.
+    pub new: Option<T>,
+}
+
+/// What one watcher shares between its two observers.
+struct WatchState<T> {
+    tx: Sender<Change<T>>,
+    last: HashMap<Entity, T>,
+    closed: bool,
+}
+
+impl<T> WatchState<T> {
+    fn send(&mut self, change: Change<T>) {
+        if self.tx.send(change).is_err() {
+            self.closed = true;
+            self.last = HashMap::new();
+        }
+    }
+}
+
+/// Extension trait for watching components from other threads.
+pub trait WatchExt {
+    /// Receive a [`Change`] every time a `T` is set on or removed from an
+    /// entity, including on despawn.
+    ///
+    /// The channel is unbounded; a consumer that stops reading without
+    /// dropping its receiver makes it grow without limit.
+    fn watch<T>(&self) -> Receiver<Change<T>>
+    where
+        T: ComponentId + DataComponent + Clone + Send + 'static;
+}
+
+impl WatchExt for World {
+    fn watch<T>(&self) -> Receiver<Change<T>>
+    where
+        T: ComponentId + DataComponent + Clone + Send + 'static,
+    {
+        let (tx, rx) = unbounded();
+        let state = Arc::new(Mutex::new(WatchState {
+            tx,
+            last: HashMap::new(),
+            closed: false,
+        }));
+
+        let on_set = state.clone();
+        self.observer::<flecs::OnSet, &T>()
+            .each_entity(move |entity, value| {
+                let mut state = on_set.lock().unwrap();
+                if state.closed {
+                    return;
+                }
+                let entity = entity.id();
+                let old = state.last.insert(entity, value.clone());
+                state.send(Change {
+                    entity,
+                    old,
+                    new: Some(value.clone()),
+                });
+            });
+
+        self.observer::<flecs::OnRemove, &T>()
+            .each_entity(move |entity, _| {
+                let mut state = state.lock().unwrap();
+                if state.closed {
+                    return;
+                }
+                let entity = entity.id();
+                let old = state.last.remove(&entity);
+                state.send(Change {
+                    entity,
+                    old,
+                    new: None,
+                });
+            });
+
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+    struct Health(u32);
+
+    #[test]
+    fn test_watch_set_and_remove() {
+        let world = World::new();
+        let changes = world.watch::<Health>();
+
+        let entity = world.entity().set(Health(20));
+        entity.set(Health(15));
+        entity.remove::<Health>();
+
+        let changes: Vec<_> = changes.try_iter().collect();
+        let id = entity.id();
+        assert_eq!(
+            changes,
+            [
+                Change {
+                    entity: id,
+                    old: None,
+                    new: Some(Health(20)),
+                },
+                Change {
+                    entity: id,
+                    old: Some(Health(20)),
+                    new: Some(Health(15)),
+                },
+                Change {
+                    entity: id,
+                    old: Some(Health(15)),
+                    new: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_despawn_and_dropped_receiver() {
+        let world = World::new();
+        let changes = world.watch::<Health>();
+        let other = world.watch::<Health>();
+
+        let entity = world.entity().set(Health(20));
+        let id = entity.id();
+        drop(other);
+        entity.destruct();
+
+        let changes: Vec<_> = changes.try_iter().collect();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[1],
+            Change {
+                entity: id,
+                old: Some(Health(20)),
+                new: None,
+            }
+        );
+    }
+}
@@ -21,24 +21,32 @@
 //! 4. Blue regions in parallel (rayon) + readonly_end() to merge
 //! 5. Global phase (sequential) - network egress, etc.
 //! ```
+//!
+//! # Rebalancing
+//!
+//! A [`Rebalancer`] resizes and shifts regions when entities cluster, so one
+//! busy region doesn't hold up its whole color phase.
 
 #![allow(unsafe_code)]
 #![allow(clippy::missing_safety_doc)]
 
 mod event;
+mod rebalance;
 mod region;
 mod scoped;
 mod tick;
 
 pub use event::{Event, EventHandler, EventWorldExt, HandlerInfo};
-pub use region::{Chunk, Position, Region, RegionColor, chebyshev_distance};
-pub use scoped::{ScopeError, ScopedWorld};
+pub use rebalance::{ChunkMigration, Rebalancer};
+pub use region::{Chunk, Position, Region, RegionColor, RegionLayout, chebyshev_distance};
+pub use scoped::{ScopeError, ScopedWorld, WriteGuard};
 pub use tick::{RgbScheduler, TickPhase};
 
 /// Prelude for convenient imports
 pub mod prelude {
     pub use crate::{
-        Chunk, Event, EventHandler, EventWorldExt, HandlerInfo, Position, Region, RegionColor,
-        RgbScheduler, ScopeError, ScopedWorld, chebyshev_distance,
+        Chunk, ChunkMigration, Event, EventHandler, EventWorldExt, HandlerInfo, Position,
+        Rebalancer, Region, RegionColor, RegionLayout, RgbScheduler, ScopeError, ScopedWorld,
+        WriteGuard, chebyshev_distance,
     };
 }
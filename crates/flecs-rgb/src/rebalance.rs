@@ -0,0 +1,339 @@
+//! Region rebalancing when entities cluster
+//!
+//! Regions of one color run in parallel, so a color phase takes as long as
+//! its busiest region, or as long as the phase's whole load split across the
+//! thread pool, whichever is more. When entities pile into one region, that
+//! region alone sets the pace of its phase while the other threads idle.
+//!
+//! The [`Rebalancer`] periodically counts entities per chunk and looks for a
+//! [`RegionLayout`] (region size and origin) under which the phases would be
+//! cheaper. Layouts stay regular grids, so regions keep their color from
+//! their coordinates and same-colored regions still never share an edge.
+//! Smaller regions split a cluster over several regions of each color;
+//! shifting the origin moves region edges through it.
+//!
+//! ```ignore
+//! let mut rebalancer = Rebalancer::default();
+//!
+//! // Every tick, after the RGB phases
+//! for migration in rebalancer.tick(&world, &mut scheduler) {
+//!     chunk_index.move_chunk(migration.coords, migration.to_region);
+//! }
+//! ```
+//!
+//! To keep the layout from flapping, a new layout is only applied if it
+//! saves at least [`Rebalancer::min_gain`] of the current cost, and not
+//! within [`Rebalancer::cooldown`] ticks of the last change. Every chunk that
+//! changes region is reported as a [`ChunkMigration`], so indexes keyed by
+//! region can follow; regions left without chunks are deleted.
+
+use std::collections::HashMap;
+
+use flecs_ecs::prelude::*;
+
+use crate::region::{Chunk, Position, Region, RegionColor, RegionLayout};
+use crate::tick::RgbScheduler;
+
+/// A chunk moved to another region during a rebalance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkMigration {
+    /// The chunk entity
+    pub chunk: Entity,
+    /// Chunk coordinates
+    pub coords: (i32, i32),
+    /// Region the chunk was in (deleted if it has no chunks left)
+    pub from_region: Entity,
+    /// Region the chunk is in now
+    pub to_region: Entity,
+    pub from_color: RegionColor,
+    pub to_color: RegionColor,
+}
+
+/// Periodically picks a cheaper region layout when entity density is skewed
+#[derive(Debug, Clone)]
+pub struct Rebalancer {
+    /// Ticks between load checks
+    pub check_interval: u64,
+    /// Least fraction of the current cost a new layout must save
+    pub min_gain: f64,
+    /// Ticks after a rebalance before the next one may happen
+    pub cooldown: u64,
+    /// Smallest region size tried, in chunks per side
+    pub min_chunks_per_region: i32,
+    /// Largest region size tried, in chunks per side
+    pub max_chunks_per_region: i32,
+    /// Chunks between the region origins tried, on each axis
+    pub origin_step: i32,
+    /// Threads regions of one color are spread over
+    pub threads: usize,
+    /// Ticks counted so far
+    ticks: u64,
+    /// Tick of the last applied rebalance
+    last_rebalance: Option<u64>,
+}
+
+impl Default for Rebalancer {
+    fn default() -> Self {
+        Self {
+            check_interval: 100,
+            min_gain: 0.25,
+            cooldown: 1200,
+            min_chunks_per_region: 4,
+            max_chunks_per_region: 16,
+            origin_step: 4,
+            threads: rayon::current_num_threads(),
+            ticks: 0,
+            last_rebalance: None,
+        }
+    }
+}
+
+impl Rebalancer {
+    /// Count a tick, and rebalance if a check is due
+    ///
+    /// Returns the chunks that changed region, if any.
+    pub fn tick(&mut self, world: &World, scheduler: &mut RgbScheduler) -> Vec<ChunkMigration> {
+        self.ticks += 1;
+        let cooling = self
+            .last_rebalance
+            .is_some_and(|last| self.ticks - last < self.cooldown);
+        if cooling || !self.ticks.is_multiple_of(self.check_interval.max(1)) {
+            return Vec::new();
+        }
+        self.rebalance(world, scheduler)
+    }
+
+    /// Check the load now, and apply a better layout if there is one
+    ///
+    /// Ignores the check interval and cooldown. Returns the chunks that
+    /// changed region, if any.
+    pub fn rebalance(
+        &mut self,
+        world: &World,
+        scheduler: &mut RgbScheduler,
+    ) -> Vec<ChunkMigration> {
+        let loads = chunk_loads(world);
+        let current = scheduler.layout();
+        let current_cost = phase_cost(&loads, current, self.threads);
+        let (best, best_cost) = self.best_layout(&loads, current);
+
+        if best == current || (best_cost as f64) > (current_cost as f64) * (1.0 - self.min_gain) {
+            return Vec::new();
+        }
+        scheduler.set_layout(best);
+        self.last_rebalance = Some(self.ticks);
+        migrate(world, scheduler)
+    }
+
+    /// Cheapest layout among the candidates, with its cost
+    ///
+    /// Ties go to `current`, then to larger regions, since fewer regions
+    /// means less scheduling overhead.
+    fn best_layout(
+        &self,
+        loads: &HashMap<(i32, i32), usize>,
+        current: RegionLayout,
+    ) -> (RegionLayout, usize) {
+        let mut best = (current, phase_cost(loads, current, self.threads));
+        let mut size = self.max_chunks_per_region;
+        while size >= self.min_chunks_per_region.max(1) {
+            for ox in (0..size).step_by(self.origin_step.max(1) as usize) {
+                for oz in (0..size).step_by(self.origin_step.max(1) as usize) {
+                    let layout = RegionLayout {
+                        chunks_per_region: size,
+                        origin: (ox, oz),
+                    };
+                    let cost = phase_cost(loads, layout, self.threads);
+                    if cost < best.1 {
+                        best = (layout, cost);
+                    }
+                }
+            }
+            size /= 2;
+        }
+        best
+    }
+}
+
+/// Entities in each existing chunk, by chunk coordinates
+fn chunk_loads(world: &World) -> HashMap<(i32, i32), usize> {
+    let mut loads = HashMap::new();
+    world.query::<&Chunk>().build().each(|chunk| {
+        loads.insert((chunk.x, chunk.z), 0);
+    });
+    world.query::<&Position>().build().each(|position| {
+        if let Some(load) = loads.get_mut(&position.chunk_coords()) {
+            *load += 1;
+        }
+    });
+    loads
+}
+
+/// Estimated cost of a tick's color phases under `layout`, in entities
+///
+/// Each phase costs its busiest region's load, or its total load split over
+/// `threads`, whichever is more.
+fn phase_cost(loads: &HashMap<(i32, i32), usize>, layout: RegionLayout, threads: usize) -> usize {
+    let mut regions: HashMap<(i32, i32), usize> = HashMap::new();
+    for (&(x, z), &load) in loads {
+        *regions.entry(layout.region_coords(x, z)).or_default() += load;
+    }
+
+    RegionColor::all()
+        .into_iter()
+        .map(|color| {
+            let in_color = regions
+                .iter()
+                .filter(|&(&(rx, rz), _)| RegionColor::from_region_pos(rx, rz) == color)
+                .map(|(_, &load)| load);
+            let busiest = in_color.clone().max().unwrap_or(0);
+            let total: usize = in_color.sum();
+            busiest.max(total.div_ceil(threads.max(1)))
+        })
+        .sum()
+}
+
+/// Move every chunk to its region under the scheduler's layout
+fn migrate(world: &World, scheduler: &RgbScheduler) -> Vec<ChunkMigration> {
+    let layout = scheduler.layout();
+    let mut regions: HashMap<(i32, i32), Entity> = HashMap::new();
+    world
+        .query::<&Region>()
+        .build()
+        .each_entity(|entity, region| {
+            regions.insert((region.rx, region.rz), entity.id());
+        });
+    let mut chunks: Vec<(Entity, Chunk)> = Vec::new();
+    world
+        .query::<&Chunk>()
+        .build()
+        .each_entity(|entity, chunk| chunks.push((entity.id(), *chunk)));
+
+    let mut migrations = Vec::new();
+    for (chunk_id, chunk) in chunks {
+        let chunk_entity = world.entity_from_id(chunk_id);
+        let Some(from) = chunk_entity.parent() else {
+            continue;
+        };
+        let Some(from_region) = from.try_get::<&Region>(|region| *region) else {
+            continue;
+        };
+        let coords = layout.region_coords(chunk.x, chunk.z);
+        if (from_region.rx, from_region.rz) == coords {
+            continue;
+        }
+
+        let to = *regions
+            .entry(coords)
+            .or_insert_with(|| scheduler.create_region(world, coords.0, coords.1).id());
+        chunk_entity.child_of(to);
+        migrations.push(ChunkMigration {
+            chunk: chunk_id,
+            coords: (chunk.x, chunk.z),
+            from_region: from.id(),
+            to_region: to,
+            from_color: from_region.color(),
+            to_color: RegionColor::from_region_pos(coords.0, coords.1),
+        });
+    }
+
+    // Delete regions left without chunks
+    for region_id in regions.into_values() {
+        let region = world.entity_from_id(region_id);
+        let mut has_chunks = false;
+        region.each_child(|child| {
+            has_chunks |= child.try_get::<&Chunk>(|_| ()).is_some();
+        });
+        if !has_chunks {
+            region.destruct();
+        }
+    }
+    migrations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `per_chunk` entities in every chunk of a `side` x `side` block at the origin
+    fn cluster(side: i32, per_chunk: usize) -> HashMap<(i32, i32), usize> {
+        (0..side)
+            .flat_map(|x| (0..side).map(move |z| ((x, z), per_chunk)))
+            .collect()
+    }
+
+    #[test]
+    fn test_phase_cost() {
+        let loads = cluster(6, 3);
+
+        // All 108 entities in red region (0, 0)
+        assert_eq!(phase_cost(&loads, RegionLayout::new(16), 8), 108);
+
+        // Red (0, 0) has 48, green (1, 0) and (0, 1) 24 each, blue (1, 1) 12
+        assert_eq!(phase_cost(&loads, RegionLayout::new(4), 8), 48 + 24 + 12);
+
+        // Nine regions of 12, three of each color; with one thread a phase
+        // costs its whole load
+        assert_eq!(phase_cost(&loads, RegionLayout::new(2), 8), 36);
+        assert_eq!(phase_cost(&loads, RegionLayout::new(2), 1), 108);
+    }
+
+    #[test]
+    fn test_best_layout_splits_cluster() {
+        let rebalancer = Rebalancer {
+            min_chunks_per_region: 2,
+            threads: 8,
+            ..Rebalancer::default()
+        };
+        let (best, cost) = rebalancer.best_layout(&cluster(6, 3), RegionLayout::new(16));
+        assert_eq!(best.chunks_per_region, 2);
+        assert_eq!(cost, 36);
+    }
+
+    #[test]
+    fn test_even_load_keeps_layout() {
+        let rebalancer = Rebalancer {
+            threads: 1,
+            ..Rebalancer::default()
+        };
+        // With one thread no layout beats running everything in sequence
+        let current = RegionLayout::new(16);
+        let (best, _) = rebalancer.best_layout(&cluster(32, 1), current);
+        assert_eq!(best, current);
+    }
+
+    #[test]
+    fn test_rebalance_migrates_chunks() {
+        let world = World::new();
+        let mut scheduler = RgbScheduler::new();
+        for x in 0..6 {
+            for z in 0..6 {
+                scheduler.create_chunk(&world, x, z);
+                for _ in 0..3 {
+                    world.entity().set(Position::new(
+                        f64::from(x) * 16.0 + 8.0,
+                        64.0,
+                        f64::from(z) * 16.0 + 8.0,
+                    ));
+                }
+            }
+        }
+
+        let mut rebalancer = Rebalancer {
+            min_chunks_per_region: 2,
+            threads: 8,
+            ..Rebalancer::default()
+        };
+        let migrations = rebalancer.rebalance(&world, &mut scheduler);
+        assert_eq!(scheduler.layout().chunks_per_region, 2);
+        // The 4 chunks of new region (0, 0) stay in the old region (0, 0)
+        assert_eq!(migrations.len(), 32);
+
+        let mut regions = 0;
+        world.query::<&Region>().build().each(|_| regions += 1);
+        assert_eq!(regions, 9);
+
+        // Nothing left to gain
+        assert!(rebalancer.rebalance(&world, &mut scheduler).is_empty());
+    }
+}
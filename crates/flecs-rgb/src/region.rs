@@ -85,6 +85,36 @@ impl Chunk {
     }
 }
 
+/// How chunks are grouped into regions: square tiles of `chunks_per_region`
+/// chunks, with region (0, 0) starting at chunk `origin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegionLayout {
+    /// Chunks per region side
+    pub chunks_per_region: i32,
+    /// Chunk coordinates where region (0, 0) starts
+    pub origin: (i32, i32),
+}
+
+impl RegionLayout {
+    /// Create a layout with region (0, 0) starting at chunk (0, 0)
+    #[must_use]
+    pub const fn new(chunks_per_region: i32) -> Self {
+        Self {
+            chunks_per_region,
+            origin: (0, 0),
+        }
+    }
+
+    /// Get the coordinates of the region containing chunk (x, z)
+    #[must_use]
+    pub const fn region_coords(&self, x: i32, z: i32) -> (i32, i32) {
+        (
+            (x - self.origin.0).div_euclid(self.chunks_per_region),
+            (z - self.origin.1).div_euclid(self.chunks_per_region),
+        )
+    }
+}
+
 /// Position component determines which chunk an entity belongs to.
 #[derive(Component, Debug, Clone, Copy)]
 pub struct Position {
@@ -164,6 +194,22 @@ mod tests {
         assert_eq!(rz_neg, -2);
     }
 
+    #[test]
+    fn test_layout_origin() {
+        let layout = RegionLayout {
+            chunks_per_region: 4,
+            origin: (2, -1),
+        };
+        assert_eq!(layout.region_coords(2, -1), (0, 0));
+        assert_eq!(layout.region_coords(5, 2), (0, 0));
+        assert_eq!(layout.region_coords(6, 3), (1, 1));
+        assert_eq!(layout.region_coords(1, -2), (-1, -1));
+        assert_eq!(
+            RegionLayout::new(16).region_coords(17, 33),
+            Chunk::new(17, 33).region_coords(16)
+        );
+    }
+
     #[test]
     fn test_position_to_chunk() {
         let pos = Position::new(17.5, 64.0, 33.5);
@@ -1,8 +1,10 @@
 //! ScopedWorld - Safe boundary-checking wrapper for Flecs stages
 
+use core::any::Any;
+
 use flecs_ecs::prelude::*;
 
-use crate::region::{Position, chebyshev_distance};
+use crate::region::Position;
 
 /// Error returned when accessing entities outside the allowed scope
 #[derive(Debug, Clone, thiserror::Error)]
@@ -27,6 +29,19 @@ pub enum ScopeError {
     ComponentNotFound,
 }
 
+/// What a [`ScopedWorld`] does when a write falls outside its chunk neighborhood.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteGuard {
+    /// Panic in debug builds when a write leaves the neighborhood, so
+    /// parallelism bugs surface immediately; return a [`ScopeError`] in
+    /// release builds. Writes to entities without a `Position` can't be
+    /// placed and return [`ScopeError::NoPosition`] either way.
+    #[default]
+    DebugPanic,
+    /// Always return a [`ScopeError`].
+    Error,
+}
+
 /// A scoped view into the world that validates chunk boundaries.
 ///
 /// During parallel execution, each chunk processor gets a `ScopedWorld`
@@ -34,6 +49,10 @@ pub enum ScopeError {
 ///
 /// This is safe because 3-coloring ensures no two adjacent chunks run in parallel
 /// within the same color phase.
+///
+/// Every write is checked against the recorded set of permitted chunks, both
+/// for the entity being written and, for `Position` writes, its destination.
+/// See [`WriteGuard`] for how violations are reported.
 pub struct ScopedWorld<'w> {
     /// The Flecs stage for this thread
     stage: WorldRef<'w>,
@@ -41,6 +60,20 @@ pub struct ScopedWorld<'w> {
     center_chunk: (i32, i32),
     /// Maximum Chebyshev distance allowed (default: 1)
     max_distance: i32,
+    /// Chunks this scope may touch (the neighborhood around `center_chunk`),
+    /// sorted for binary search
+    permitted_chunks: Vec<(i32, i32)>,
+    /// How write violations are reported
+    write_guard: WriteGuard,
+}
+
+/// All chunks within `max_distance` (Chebyshev) of `center`, sorted.
+fn neighborhood(center: (i32, i32), max_distance: i32) -> Vec<(i32, i32)> {
+    let range = -max_distance..=max_distance;
+    range
+        .clone()
+        .flat_map(|dx| range.clone().map(move |dz| (center.0 + dx, center.1 + dz)))
+        .collect()
 }
 
 impl<'w> ScopedWorld<'w> {
@@ -51,11 +84,7 @@ impl<'w> ScopedWorld<'w> {
     /// * `center_chunk` - The (x, z) coordinates of the center chunk
     #[must_use]
     pub fn new(stage: WorldRef<'w>, center_chunk: (i32, i32)) -> Self {
-        Self {
-            stage,
-            center_chunk,
-            max_distance: 1,
-        }
+        Self::with_max_distance(stage, center_chunk, 1)
     }
 
     /// Create a ScopedWorld with custom max distance
@@ -69,15 +98,30 @@ impl<'w> ScopedWorld<'w> {
             stage,
             center_chunk,
             max_distance,
+            permitted_chunks: neighborhood(center_chunk, max_distance),
+            write_guard: WriteGuard::default(),
         }
     }
 
+    /// Set how write violations are reported
+    #[must_use]
+    pub const fn with_write_guard(mut self, write_guard: WriteGuard) -> Self {
+        self.write_guard = write_guard;
+        self
+    }
+
     /// Get the center chunk coordinates
     #[must_use]
     pub fn center_chunk(&self) -> (i32, i32) {
         self.center_chunk
     }
 
+    /// Get the chunks this scope is permitted to touch, sorted
+    #[must_use]
+    pub fn permitted_chunks(&self) -> &[(i32, i32)] {
+        &self.permitted_chunks
+    }
+
     /// Get the underlying stage
     #[must_use]
     pub fn stage(&self) -> &WorldRef<'w> {
@@ -91,13 +135,15 @@ impl<'w> ScopedWorld<'w> {
             return Err(ScopeError::NoPosition);
         };
 
-        let entity_chunk = pos.chunk_coords();
-        let dist = chebyshev_distance(self.center_chunk, entity_chunk);
+        self.validate_chunk(pos.chunk_coords())
+    }
 
-        if dist > self.max_distance {
+    /// Validate that a chunk is in the permitted set
+    fn validate_chunk(&self, chunk: (i32, i32)) -> Result<(), ScopeError> {
+        if self.permitted_chunks.binary_search(&chunk).is_err() {
             return Err(ScopeError::OutOfBounds {
-                entity_chunk_x: entity_chunk.0,
-                entity_chunk_z: entity_chunk.1,
+                entity_chunk_x: chunk.0,
+                entity_chunk_z: chunk.1,
                 center_chunk_x: self.center_chunk.0,
                 center_chunk_z: self.center_chunk.1,
             });
@@ -106,6 +152,27 @@ impl<'w> ScopedWorld<'w> {
         Ok(())
     }
 
+    /// Validate a write to `entity`, including where a new `Position` would move it
+    fn validate_write<T: Any>(&self, entity: EntityView<'_>, value: &T) -> Result<(), ScopeError> {
+        let result = self.validate_in_bounds(entity).and_then(|()| {
+            (value as &dyn Any)
+                .downcast_ref::<Position>()
+                .map_or(Ok(()), |pos| self.validate_chunk(pos.chunk_coords()))
+        });
+
+        #[cfg(debug_assertions)]
+        if let Err(err @ ScopeError::OutOfBounds { .. }) = &result
+            && self.write_guard == WriteGuard::DebugPanic
+        {
+            panic!(
+                "scope violation: write from chunk {:?} rejected: {err}",
+                self.center_chunk
+            );
+        }
+
+        result
+    }
+
     /// Get a component from an entity (validates bounds)
     ///
     /// Returns an owned clone of the component value.
@@ -124,11 +191,16 @@ impl<'w> ScopedWorld<'w> {
     /// Set a component on an entity (validates bounds, deferred)
     ///
     /// The set operation is deferred until `readonly_end()` is called.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds with [`WriteGuard::DebugPanic`] (the default), panics
+    /// instead of returning an error if the write leaves the scope.
     pub fn set<T>(&self, entity: EntityView<'_>, value: T) -> Result<(), ScopeError>
     where
-        T: ComponentId + ComponentType<Struct>,
+        T: ComponentId + ComponentType<Struct> + 'static,
     {
-        self.validate_in_bounds(entity)?;
+        self.validate_write(entity, &value)?;
 
         // Use the stage's deferred operations
         entity.set(value);
@@ -197,6 +269,57 @@ mod tests {
         assert!(matches!(result, Err(ScopeError::OutOfBounds { .. })));
     }
 
+    #[test]
+    fn test_permitted_chunks() {
+        let world = World::new();
+        let scoped = ScopedWorld::new((&world).world(), (2, -3));
+
+        assert_eq!(scoped.permitted_chunks().len(), 9);
+        assert!(scoped.permitted_chunks().is_sorted());
+        assert!(scoped.permitted_chunks().contains(&(1, -4)));
+        assert!(!scoped.permitted_chunks().contains(&(4, -3)));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "scope violation")]
+    fn test_write_out_of_scope_panics() {
+        let world = World::new();
+        let entity = world.entity().set(Position::new(88.0, 64.0, 88.0)); // chunk (5, 5)
+
+        let scoped = ScopedWorld::new((&world).world(), (0, 0));
+        let _ = scoped.set(entity, Position::new(88.0, 65.0, 88.0));
+    }
+
+    #[test]
+    fn test_write_guard_error() {
+        let world = World::new();
+        let far = world.entity().set(Position::new(88.0, 64.0, 88.0)); // chunk (5, 5)
+        let near = world.entity().set(Position::new(8.0, 64.0, 8.0)); // chunk (0, 0)
+
+        let scoped = ScopedWorld::new((&world).world(), (0, 0)).with_write_guard(WriteGuard::Error);
+
+        let result = scoped.set(far, Position::new(88.0, 65.0, 88.0));
+        assert!(matches!(result, Err(ScopeError::OutOfBounds { .. })));
+
+        // Moving an in-scope entity out of the neighborhood is also a violation
+        let result = scoped.set(near, Position::new(40.0, 64.0, 8.0)); // chunk (2, 0)
+        assert!(matches!(result, Err(ScopeError::OutOfBounds { .. })));
+
+        assert!(scoped.set(near, Position::new(20.0, 64.0, 8.0)).is_ok()); // chunk (1, 0)
+    }
+
+    #[test]
+    fn test_write_without_position_is_an_error() {
+        let world = World::new();
+        let entity = world.entity();
+
+        // Not a scope violation, so the default guard doesn't panic
+        let scoped = ScopedWorld::new((&world).world(), (0, 0));
+        let result = scoped.set(entity, Position::new(8.0, 64.0, 8.0));
+        assert!(matches!(result, Err(ScopeError::NoPosition)));
+    }
+
     #[test]
     fn test_scoped_world_no_position() {
         let world = World::new();
@@ -2,7 +2,7 @@
 
 use flecs_ecs::prelude::*;
 
-use crate::region::{Chunk, Region, RegionColor};
+use crate::region::{Chunk, Region, RegionColor, RegionLayout};
 use crate::scoped::ScopedWorld;
 
 /// Tick execution phase
@@ -18,8 +18,8 @@ pub enum TickPhase {
 
 /// Scheduler for RGB parallel tick execution
 pub struct RgbScheduler {
-    /// Number of chunks per region (default: 16)
-    chunks_per_region: i32,
+    /// How chunks are grouped into regions (default: 16 chunks per region)
+    layout: RegionLayout,
 }
 
 impl Default for RgbScheduler {
@@ -32,15 +32,29 @@ impl RgbScheduler {
     /// Create a new RGB scheduler
     #[must_use]
     pub const fn new() -> Self {
-        Self {
-            chunks_per_region: 16,
-        }
+        Self::with_chunks_per_region(16)
     }
 
     /// Create with custom chunks per region
     #[must_use]
     pub const fn with_chunks_per_region(chunks_per_region: i32) -> Self {
-        Self { chunks_per_region }
+        Self {
+            layout: RegionLayout::new(chunks_per_region),
+        }
+    }
+
+    /// Get the current region layout
+    #[must_use]
+    pub const fn layout(&self) -> RegionLayout {
+        self.layout
+    }
+
+    /// Change the region layout for chunks created from now on
+    ///
+    /// Existing chunks keep their region; [`Rebalancer`](crate::Rebalancer)
+    /// moves them when it changes the layout.
+    pub const fn set_layout(&mut self, layout: RegionLayout) {
+        self.layout = layout;
     }
 
     /// Run a complete tick with the given system functions (sequential version)
@@ -135,7 +149,7 @@ impl RgbScheduler {
     /// Create a chunk entity as child of a region
     pub fn create_chunk<'a>(&self, world: &'a World, x: i32, z: i32) -> EntityView<'a> {
         let chunk = Chunk::new(x, z);
-        let (rx, rz) = chunk.region_coords(self.chunks_per_region);
+        let (rx, rz) = self.layout.region_coords(x, z);
 
         // Find or create the parent region
         let region_id = self.find_or_create_region_id(world, rx, rz);
@@ -1,9 +1,18 @@
-use std::{collections::HashMap, env, fs, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 use heck::{ToSnakeCase, ToUpperCamelCase};
+use mc_protocol::{
+    Encode,
+    nbt::{NbtCompound, NbtList, NbtValue},
+    write_varint,
+};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
-use serde::Deserialize;
+use serde::{Deserialize, de::DeserializeOwned};
 
 /// Known types that can be derived with Encode/Decode
 const KNOWN_TYPES: &[&str] = &[
@@ -19,6 +28,7 @@ const KNOWN_TYPES: &[&str] = &[
     "Position",
     "Nbt",
     "BlockState",
+    "ItemStack",
 ];
 
 /// Packet ID info from Mojang's data generator
@@ -77,6 +87,87 @@ struct BlockInfo {
 /// BlockName -> BlockInfo
 type BlocksData = HashMap<String, BlockInfo>;
 
+/// Physical block data extracted by `mc-gen`, keyed by state ID where
+/// per-state. Boxes are `[min_x, min_y, min_z, max_x, max_y, max_z]` in
+/// block units.
+#[derive(Debug, Deserialize)]
+struct BlockShapeInfo {
+    /// `-1.0` for unbreakable blocks
+    hardness: f32,
+    collision: HashMap<String, Vec<[f64; 6]>>,
+    opaque: HashMap<String, bool>,
+}
+
+/// BlockName -> BlockShapeInfo
+type BlockShapes = HashMap<String, BlockShapeInfo>;
+
+/// Property names ordered slowest-varying first, matching how state IDs are
+/// numbered within a block. Panics if the states aren't a contiguous product.
+fn property_order(block_name: &str, states: &[&BlockStateInfo], info: &BlockInfo) -> Vec<String> {
+    let first = states[0];
+    let mut order: Vec<(usize, &String)> = info
+        .properties
+        .keys()
+        .map(|name| {
+            let changes_at = states
+                .iter()
+                .position(|s| s.properties.get(name) != first.properties.get(name))
+                .unwrap_or(usize::MAX);
+            (changes_at, name)
+        })
+        .collect();
+    order.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    let order: Vec<String> = order.into_iter().map(|(_, name)| name.clone()).collect();
+
+    for (index, state) in states.iter().enumerate() {
+        assert_eq!(
+            state.id,
+            first.id + index as i32,
+            "{block_name}: state IDs are not contiguous"
+        );
+        let mut offset = index;
+        for name in order.iter().rev() {
+            let values = &info.properties[name];
+            assert_eq!(
+                Some(&values[offset % values.len()]),
+                state.properties.get(name),
+                "{block_name}: state {} does not follow property order",
+                state.id
+            );
+            offset /= values.len();
+        }
+    }
+
+    order
+}
+
+/// A registry from Mojang's `registries.json` report
+#[derive(Debug, Deserialize)]
+struct RegistryInfo {
+    entries: HashMap<String, RegistryEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistryEntry {
+    protocol_id: i32,
+}
+
+/// RegistryName -> RegistryInfo
+type Registries = HashMap<String, RegistryInfo>;
+
+/// Item info from Mojang's `items.json` report (default components)
+#[derive(Debug, Default, Deserialize)]
+struct ItemReport {
+    #[serde(default)]
+    components: HashMap<String, serde_json::Value>,
+}
+
+/// ItemName -> ItemReport
+type ItemsData = HashMap<String, ItemReport>;
+
+/// Stack size reported for IDs outside the item registry
+const DEFAULT_MAX_STACK_SIZE: i32 = 64;
+
 fn is_known_type(t: &str) -> bool {
     if KNOWN_TYPES.contains(&t) {
         return true;
@@ -253,6 +344,7 @@ fn generate_state_module(
 
         let mut packet_tokens = Vec::new();
         let mut name_match_arms = Vec::new();
+        let mut roundtrip_arms = Vec::new();
 
         for (pkt_name, pkt_info) in packets {
             let pkt_id = pkt_info.protocol_id;
@@ -285,6 +377,14 @@ fn generate_state_module(
                 gen_empty_struct(&struct_name, pkt_id)
             };
 
+            // Only packets whose fields are all known derive Encode/Decode
+            if fields.is_none_or(|flds| flds.iter().all(|f| is_known_type(&f.rust_type))) {
+                let struct_ident = format_ident!("{}", struct_name);
+                roundtrip_arms.push(quote! {
+                    #pkt_id => Some(roundtrip_packet::<#struct_ident>(body))
+                });
+            }
+
             let impl_tokens = gen_packet_impl(&struct_name, pkt_id, state, direction);
 
             packet_tokens.push(quote! {
@@ -313,6 +413,15 @@ fn generate_state_module(
                         _ => None,
                     }
                 }
+
+                /// Decode a packet body (after the ID) and encode it again, or
+                /// None if the ID is unknown or the packet has no codec
+                pub fn roundtrip(id: i32, body: &[u8]) -> Option<mc_protocol::Result<Vec<u8>>> {
+                    match id {
+                        #(#roundtrip_arms,)*
+                        _ => None,
+                    }
+                }
             }
         };
         direction_modules.push(dir_module);
@@ -320,16 +429,23 @@ fn generate_state_module(
 
     let header = quote! {
         use std::borrow::Cow;
-        use mc_protocol::{Encode, Decode, Packet, State, Direction, VarInt, Uuid, Position, Nbt, BlockState};
+        use mc_protocol::{Encode, Decode, Packet, State, Direction, VarInt, Uuid, Position, Nbt, BlockState, ItemStack};
         use serde::{Serialize, Deserialize};
 
+        fn roundtrip_packet<'a, P: Encode + Decode<'a>>(mut body: &[u8]) -> mc_protocol::Result<Vec<u8>> {
+            let packet = P::decode(&mut body)?;
+            let mut encoded = Vec::new();
+            packet.encode(&mut encoded)?;
+            Ok(encoded)
+        }
+
         #(#direction_modules)*
     };
 
     prettyplease::unparse(&syn::parse2(header).expect("failed to parse generated code"))
 }
 
-fn generate_blocks_module(blocks_data: &BlocksData) -> String {
+fn generate_blocks_module(blocks_data: &BlocksData, shapes_data: &BlockShapes) -> String {
     // Collect all blocks sorted by their default state ID
     let mut blocks: Vec<(&String, &BlockInfo)> = blocks_data.iter().collect();
     blocks.sort_by_key(|(_, info)| {
@@ -344,6 +460,19 @@ fn generate_blocks_module(blocks_data: &BlocksData) -> String {
     let mut block_name_arms = Vec::new();
     let mut block_by_name_arms = Vec::new();
 
+    // Per-state physical data, indexed by state ID
+    let state_count = blocks_data
+        .values()
+        .flat_map(|info| &info.states)
+        .map(|s| s.id as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let mut shapes: Vec<Vec<[f64; 6]>> = Vec::new();
+    let mut shape_index: HashMap<String, u16> = HashMap::new();
+    let mut state_shapes = vec![0u16; state_count];
+    let mut state_opaque = vec![false; state_count];
+    let mut by_first_state: Vec<(i32, TokenStream)> = Vec::new();
+
     for (block_name, block_info) in &blocks {
         let clean_name = block_name.replace("minecraft:", "");
         let const_name = format_ident!("{}", clean_name.to_uppercase().replace('.', "_"));
@@ -369,9 +498,76 @@ fn generate_blocks_module(blocks_data: &BlocksData) -> String {
         block_by_name_arms.push(quote! {
             #full_name | #clean_name => Some(BlockState(#default_id))
         });
+
+        let mut states: Vec<&BlockStateInfo> = block_info.states.iter().collect();
+        states.sort_by_key(|s| s.id);
+        let first_id = states[0].id as u16;
+        let extracted = shapes_data
+            .get(block_name.as_str())
+            .unwrap_or_else(|| panic!("block-shapes.json has no {block_name}"));
+
+        for state in &states {
+            let key = state.id.to_string();
+            let boxes = extracted.collision.get(&key).cloned().unwrap_or_else(|| {
+                panic!("block-shapes.json has no collision for {block_name} state {key}")
+            });
+            let opaque = *extracted.opaque.get(&key).unwrap_or_else(|| {
+                panic!("block-shapes.json has no opacity for {block_name} state {key}")
+            });
+
+            let shape_key = format!("{boxes:?}");
+            let index = *shape_index.entry(shape_key).or_insert_with(|| {
+                shapes.push(boxes);
+                (shapes.len() - 1) as u16
+            });
+            state_shapes[state.id as usize] = index;
+            state_opaque[state.id as usize] = opaque;
+        }
+
+        let properties: Vec<TokenStream> = property_order(block_name, &states, block_info)
+            .iter()
+            .map(|name| {
+                let values = &block_info.properties[name];
+                quote! { BlockProperty { name: #name, values: &[#(#values),*] } }
+            })
+            .collect();
+        let hardness = extracted.hardness;
+        by_first_state.push((
+            i32::from(first_id),
+            quote! {
+                BlockInfo {
+                    name: #full_name,
+                    first_state: #first_id,
+                    default_state: #default_id,
+                    hardness: #hardness,
+                    properties: &[#(#properties),*],
+                }
+            },
+        ));
     }
 
+    by_first_state.sort_by_key(|(first, _)| *first);
+    let block_infos: Vec<TokenStream> = by_first_state
+        .into_iter()
+        .map(|(_, tokens)| tokens)
+        .collect();
+    let block_count = block_infos.len();
+
+    let shape_tokens: Vec<TokenStream> = shapes
+        .iter()
+        .map(|boxes| {
+            let boxes = boxes.iter().map(|[x0, y0, z0, x1, y1, z1]| {
+                quote! { Aabb::new([#x0, #y0, #z0], [#x1, #y1, #z1]) }
+            });
+            quote! { &[#(#boxes),*] }
+        })
+        .collect();
+    let shape_count = shape_tokens.len();
+
     let output = quote! {
+        use crate::block::{BlockInfo, BlockProperty};
+        use crate::shape::Aabb;
+
         /// A block state ID as used in the Minecraft protocol.
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
         #[repr(transparent)]
@@ -430,6 +626,18 @@ fn generate_blocks_module(blocks_data: &BlocksData) -> String {
             }
         }
 
+        /// Every block, ordered by first state ID
+        pub static BLOCK_INFO: [BlockInfo; #block_count] = [#(#block_infos),*];
+
+        /// Distinct collision shapes
+        pub static SHAPES: [&[Aabb]; #shape_count] = [#(#shape_tokens),*];
+
+        /// Index into [`SHAPES`] for each state ID
+        pub static STATE_SHAPE: [u16; #state_count] = [#(#state_shapes),*];
+
+        /// Whether each state ID is a full, light-blocking cube
+        pub static STATE_OPAQUE: [bool; #state_count] = [#(#state_opaque),*];
+
         /// Block constants for common blocks (default states)
         pub mod blocks {
             use super::BlockState;
@@ -441,6 +649,362 @@ fn generate_blocks_module(blocks_data: &BlocksData) -> String {
     prettyplease::unparse(&syn::parse2(output).expect("failed to parse blocks module"))
 }
 
+fn generate_items_module(registries: &Registries, items_data: &ItemsData) -> String {
+    let mut items: Vec<(&String, i32)> = registries
+        .get("minecraft:item")
+        .expect("registries.json has no minecraft:item registry")
+        .entries
+        .iter()
+        .map(|(name, entry)| (name, entry.protocol_id))
+        .collect();
+    items.sort_by_key(|(_, id)| *id);
+
+    let mut item_infos = Vec::new();
+    let mut item_consts = Vec::new();
+
+    for (index, (item_name, id)) in items.iter().enumerate() {
+        assert_eq!(
+            *id, index as i32,
+            "item registry IDs are not contiguous at {item_name}"
+        );
+        let clean_name = item_name.replace("minecraft:", "");
+        let const_name = format_ident!("{}", clean_name.to_uppercase());
+        let max_stack_size = items_data
+            .get(item_name.as_str())
+            .unwrap_or_else(|| panic!("items.json has no {item_name}"))
+            .components
+            .get("minecraft:max_stack_size")
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or_else(|| panic!("items.json has no max stack size for {item_name}"))
+            as i32;
+
+        let full_name = item_name.as_str();
+        item_infos.push(quote! {
+            ItemInfo { name: #full_name, max_stack_size: #max_stack_size }
+        });
+
+        let doc = format!("`{}` - ID: {}, stacks to {}", item_name, id, max_stack_size);
+        item_consts.push(quote! {
+            #[doc = #doc]
+            pub const #const_name: Item = Item(#id);
+        });
+    }
+
+    let item_count = item_infos.len();
+
+    let output = quote! {
+        use mc_protocol::ItemStack;
+
+        /// An item ID from the `minecraft:item` registry.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+        #[repr(transparent)]
+        pub struct Item(pub i32);
+
+        struct ItemInfo {
+            name: &'static str,
+            max_stack_size: i32,
+        }
+
+        /// Every item, indexed by ID
+        static ITEMS: [ItemInfo; #item_count] = [#(#item_infos),*];
+
+        impl Item {
+            /// Air (empty hand, ID 0)
+            pub const AIR: Item = Item(0);
+
+            /// Create an Item from a raw registry ID
+            #[inline]
+            pub const fn new(id: i32) -> Self {
+                Item(id)
+            }
+
+            /// Get the raw registry ID
+            #[inline]
+            pub const fn id(self) -> i32 {
+                self.0
+            }
+
+            fn info(self) -> Option<&'static ItemInfo> {
+                ITEMS.get(usize::try_from(self.0).ok()?)
+            }
+
+            /// Get the item name (e.g. `minecraft:stone`)
+            pub fn name(self) -> Option<&'static str> {
+                self.info().map(|item| item.name)
+            }
+
+            /// Look up an item by name, with or without the `minecraft:` prefix
+            pub fn by_name(name: &str) -> Option<Item> {
+                let name = name.strip_prefix("minecraft:").unwrap_or(name);
+                ITEMS
+                    .iter()
+                    .position(|item| item.name.strip_prefix("minecraft:") == Some(name))
+                    .map(|index| Item(index as i32))
+            }
+
+            /// Maximum stack size (64 for unknown items)
+            pub fn max_stack_size(self) -> i32 {
+                self.info().map_or(#DEFAULT_MAX_STACK_SIZE, |item| item.max_stack_size)
+            }
+
+            /// A stack of this item, clamped to its max stack size
+            pub fn stack(self, count: i32) -> ItemStack {
+                ItemStack::new(self.0, count.min(self.max_stack_size()))
+            }
+        }
+
+        /// Item constants
+        pub mod items {
+            use super::Item;
+
+            #(#item_consts)*
+        }
+    };
+
+    prettyplease::unparse(&syn::parse2(output).expect("failed to parse items module"))
+}
+
+/// A synced registry from `data/registries/<namespace>/<path>/<entry>.json`,
+/// the layout of the vanilla data pack
+struct RegistrySource {
+    name: String,
+    /// `(entry name, data)`, sorted by name (this order assigns network IDs)
+    entries: Vec<(String, serde_json::Value)>,
+}
+
+fn collect_registries(namespace: &str, root: &Path, dir: &Path, out: &mut Vec<RegistrySource>) {
+    let mut entries = Vec::new();
+    let mut children: Vec<PathBuf> = fs::read_dir(dir)
+        .expect("failed to read registry directory")
+        .map(|entry| entry.expect("failed to read registry entry").path())
+        .collect();
+    children.sort();
+
+    for path in children {
+        if path.is_dir() {
+            collect_registries(namespace, root, &path, out);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .expect("bad file name");
+            let json = fs::read_to_string(&path).expect("failed to read registry entry");
+            let value = serde_json::from_str(&json)
+                .unwrap_or_else(|e| panic!("failed to parse {}: {e}", path.display()));
+            entries.push((format!("{namespace}:{stem}"), value));
+        }
+    }
+
+    if !entries.is_empty() {
+        let path = dir.strip_prefix(root).expect("registry outside root");
+        let path = path.to_str().expect("bad registry path").replace('\\', "/");
+        out.push(RegistrySource {
+            name: format!("{namespace}:{path}"),
+            entries,
+        });
+    }
+}
+
+/// Convert registry JSON to NBT the way the client's codecs read it: integers
+/// become ints (longs if out of range), other numbers doubles, booleans bytes
+fn json_to_nbt(value: &serde_json::Value, context: &str) -> NbtValue {
+    use serde_json::Value;
+
+    match value {
+        Value::Bool(b) => NbtValue::from(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i32::try_from(i).map_or(NbtValue::Long(i), NbtValue::Int),
+            None => NbtValue::Double(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => NbtValue::String(s.clone()),
+        Value::Array(values) => NbtValue::List(json_list_to_nbt(values, context)),
+        Value::Object(map) => NbtValue::Compound(json_object_to_nbt(map, context)),
+        Value::Null => panic!("{context}: null is not representable in NBT"),
+    }
+}
+
+fn json_object_to_nbt(
+    map: &serde_json::Map<String, serde_json::Value>,
+    context: &str,
+) -> NbtCompound {
+    let mut compound = NbtCompound::new();
+    for (key, value) in map {
+        compound.insert(key.as_str(), json_to_nbt(value, context));
+    }
+    compound
+}
+
+fn json_list_to_nbt(values: &[serde_json::Value], context: &str) -> NbtList {
+    let nbt: Vec<NbtValue> = values.iter().map(|v| json_to_nbt(v, context)).collect();
+    let Some(first) = nbt.first() else {
+        return NbtList::Empty;
+    };
+
+    // Lists are homogeneous; mixed ints and doubles widen to doubles
+    let as_double = |v: &NbtValue| match v {
+        NbtValue::Int(i) => Some(f64::from(*i)),
+        NbtValue::Long(l) => Some(*l as f64),
+        NbtValue::Double(d) => Some(*d),
+        _ => None,
+    };
+    macro_rules! collect {
+        ($variant:ident) => {
+            nbt.iter()
+                .map(|v| match v {
+                    NbtValue::$variant(x) => Some(x.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(NbtList::$variant)
+        };
+    }
+    let list = match first {
+        NbtValue::Byte(_) => collect!(Byte),
+        NbtValue::Int(_) => collect!(Int).or_else(|| {
+            nbt.iter()
+                .map(as_double)
+                .collect::<Option<_>>()
+                .map(NbtList::Double)
+        }),
+        NbtValue::Long(_) | NbtValue::Double(_) => nbt
+            .iter()
+            .map(as_double)
+            .collect::<Option<_>>()
+            .map(NbtList::Double),
+        NbtValue::String(_) => collect!(String),
+        NbtValue::List(_) => collect!(List),
+        NbtValue::Compound(_) => collect!(Compound),
+        _ => None,
+    };
+    list.unwrap_or_else(|| panic!("{context}: list mixes element types"))
+}
+
+/// Encode the body of a Registry Data packet
+fn encode_registry(source: &RegistrySource) -> Vec<u8> {
+    let mut data = Vec::new();
+    source.name.encode(&mut data).expect("encode registry name");
+    write_varint(&mut data, source.entries.len() as i32).expect("encode entry count");
+
+    for (name, value) in &source.entries {
+        let context = format!("{} {name}", source.name);
+        let serde_json::Value::Object(map) = value else {
+            panic!("{context}: registry entry must be a JSON object");
+        };
+        name.encode(&mut data).expect("encode entry name");
+        true.encode(&mut data).expect("encode has-data flag");
+        data.extend_from_slice(&json_object_to_nbt(map, &context).to_network_bytes());
+    }
+
+    data
+}
+
+fn generate_registries_module(data_dir: &Path, out_dir: &Path) -> String {
+    let mut sources = Vec::new();
+    if let Ok(namespaces) = fs::read_dir(data_dir) {
+        let mut namespaces: Vec<PathBuf> = namespaces
+            .map(|entry| entry.expect("failed to read registries directory").path())
+            .filter(|path| path.is_dir())
+            .collect();
+        namespaces.sort();
+        for root in namespaces {
+            let namespace = root
+                .file_name()
+                .and_then(|s| s.to_str())
+                .expect("bad namespace");
+            collect_registries(namespace, &root, &root, &mut sources);
+        }
+    }
+
+    let registry_dir = out_dir.join("registries");
+    fs::create_dir_all(&registry_dir).expect("failed to create registries output directory");
+
+    let registries: Vec<TokenStream> = sources
+        .iter()
+        .enumerate()
+        .map(|(index, source)| {
+            let file = format!("registries/{index}.bin");
+            fs::write(out_dir.join(&file), encode_registry(source))
+                .expect("failed to write registry payload");
+            let file = format!("/{file}");
+            let name = &source.name;
+            let entries = source.entries.iter().map(|(entry, _)| entry);
+            quote! {
+                RegistryData {
+                    name: #name,
+                    entries: &[#(#entries),*],
+                    payload: include_bytes!(concat!(env!("OUT_DIR"), #file)),
+                }
+            }
+        })
+        .collect();
+    let count = registries.len();
+
+    let output = quote! {
+        use crate::registry::RegistryData;
+
+        /// Every synced registry, sorted by name
+        pub static REGISTRIES: [RegistryData; #count] = [#(#registries),*];
+    };
+
+    prettyplease::unparse(&syn::parse2(output).expect("failed to parse registries module"))
+}
+
+/// Built-in registries the server needs network IDs from at runtime
+const BUILTIN_REGISTRIES: &[&str] = &["minecraft:custom_stat", "minecraft:stat_type"];
+
+fn generate_builtin_module(registries: &Registries) -> String {
+    let builtins: Vec<TokenStream> = BUILTIN_REGISTRIES
+        .iter()
+        .map(|&name| {
+            let mut entries: Vec<(&String, i32)> = registries
+                .get(name)
+                .unwrap_or_else(|| panic!("registries.json has no {name} registry"))
+                .entries
+                .iter()
+                .map(|(entry, info)| (entry, info.protocol_id))
+                .collect();
+            entries.sort_by_key(|(_, id)| *id);
+            for (index, (entry, id)) in entries.iter().enumerate() {
+                assert_eq!(
+                    *id, index as i32,
+                    "{name} registry IDs are not contiguous at {entry}"
+                );
+            }
+            let entries = entries.iter().map(|(entry, _)| entry);
+            quote! {
+                BuiltinRegistry {
+                    name: #name,
+                    entries: &[#(#entries),*],
+                }
+            }
+        })
+        .collect();
+    let count = builtins.len();
+
+    let output = quote! {
+        use crate::registry::BuiltinRegistry;
+
+        /// Built-in registries the server looks IDs up in
+        pub static BUILTIN_REGISTRIES: [BuiltinRegistry; #count] = [#(#builtins),*];
+    };
+
+    prettyplease::unparse(&syn::parse2(output).expect("failed to parse builtin registries module"))
+}
+
+/// Read and parse a report `mc-gen` extracts into `data/`.
+///
+/// Panics if it's missing or malformed rather than generating empty tables.
+fn read_report<T: DeserializeOwned>(data_dir: &Path, name: &str) -> T {
+    let path = data_dir.join(name);
+    let json = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read {}: {e}; run `nix run .#mc-gen` to extract it",
+            path.display()
+        )
+    });
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("failed to parse {name}: {e}"))
+}
+
 fn main() {
     let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
@@ -451,6 +1015,10 @@ fn main() {
     println!("cargo:rerun-if-changed=data/packets-fields.json");
     println!("cargo:rerun-if-changed=data/protocol.json");
     println!("cargo:rerun-if-changed=data/blocks.json");
+    println!("cargo:rerun-if-changed=data/block-shapes.json");
+    println!("cargo:rerun-if-changed=data/registries.json");
+    println!("cargo:rerun-if-changed=data/items.json");
+    println!("cargo:rerun-if-changed=data/registries");
 
     // Load JSON files
     let ids_json = fs::read_to_string(data_dir.join("packets-ids.json"))
@@ -506,6 +1074,21 @@ fn main() {
         fs::read_to_string(data_dir.join("blocks.json")).expect("failed to read blocks.json");
     let blocks_data: BlocksData =
         serde_json::from_str(&blocks_json).expect("failed to parse blocks.json");
-    let blocks_content = generate_blocks_module(&blocks_data);
+    let shapes_data: BlockShapes = read_report(&data_dir, "block-shapes.json");
+    let blocks_content = generate_blocks_module(&blocks_data, &shapes_data);
     fs::write(out_dir.join("blocks.rs"), blocks_content).expect("failed to write blocks module");
+
+    // Item and built-in registries
+    let registries: Registries = read_report(&data_dir, "registries.json");
+    let items_data: ItemsData = read_report(&data_dir, "items.json");
+    let items_content = generate_items_module(&registries, &items_data);
+    fs::write(out_dir.join("items.rs"), items_content).expect("failed to write items module");
+    let builtin_content = generate_builtin_module(&registries);
+    fs::write(out_dir.join("builtin.rs"), builtin_content)
+        .expect("failed to write builtin registries module");
+
+    // Synced registries (Registry Data packets sent during configuration)
+    let registries_content = generate_registries_module(&data_dir.join("registries"), &out_dir);
+    fs::write(out_dir.join("registries.rs"), registries_content)
+        .expect("failed to write registries module");
 }
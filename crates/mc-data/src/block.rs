@@ -0,0 +1,153 @@
+//! Block state property and physics queries
+//!
+//! State IDs within a block are numbered as the product of its property
+//! values, so a state's properties are decoded from its offset from the
+//! block's first state rather than stored per state.
+
+use crate::BlockState;
+use crate::block_registry::{BLOCK_INFO, SHAPES, STATE_OPAQUE, STATE_SHAPE};
+use crate::shape::Aabb;
+
+/// A block state property and its possible values, in state ID order.
+#[derive(Debug)]
+pub struct BlockProperty {
+    pub name: &'static str,
+    pub values: &'static [&'static str],
+}
+
+/// Generated per-block data.
+#[derive(Debug)]
+pub struct BlockInfo {
+    pub name: &'static str,
+    pub first_state: u16,
+    pub default_state: u16,
+    /// Mining hardness (`-1.0` is unbreakable)
+    pub hardness: f32,
+    /// Ordered slowest-varying first
+    pub properties: &'static [BlockProperty],
+}
+
+impl BlockInfo {
+    /// Number of states one step of property `index` spans
+    fn stride(&self, index: usize) -> usize {
+        self.properties[index + 1..]
+            .iter()
+            .map(|p| p.values.len())
+            .product()
+    }
+
+    fn value_index(&self, state: BlockState, index: usize) -> usize {
+        let offset = usize::from(state.0 - self.first_state);
+        offset / self.stride(index) % self.properties[index].values.len()
+    }
+}
+
+impl BlockState {
+    /// Bits per entry of direct state IDs in a paletted container.
+    pub fn direct_bits() -> u8 {
+        (usize::BITS - STATE_SHAPE.len().saturating_sub(1).leading_zeros()) as u8
+    }
+
+    fn info(self) -> Option<&'static BlockInfo> {
+        let index = BLOCK_INFO.partition_point(|b| b.first_state <= self.0);
+        let info = BLOCK_INFO.get(index.checked_sub(1)?)?;
+        let count: usize = info.properties.iter().map(|p| p.values.len()).product();
+        (usize::from(self.0 - info.first_state) < count).then_some(info)
+    }
+
+    /// ID of this state's block in the `minecraft:block` registry.
+    pub fn block_id(self) -> Option<i32> {
+        let info = self.info()?;
+        Some(BLOCK_INFO.partition_point(|b| b.first_state < info.first_state) as i32)
+    }
+
+    /// Name of the block this state belongs to (e.g. `minecraft:oak_stairs`).
+    pub fn block_name(self) -> Option<&'static str> {
+        self.info().map(|b| b.name)
+    }
+
+    /// The default state of this state's block.
+    pub fn default_state(self) -> Option<Self> {
+        self.info().map(|b| Self(b.default_state))
+    }
+
+    /// The property definitions of this state's block.
+    pub fn block_properties(self) -> &'static [BlockProperty] {
+        self.info().map_or(&[], |b| b.properties)
+    }
+
+    /// Value of a property, e.g. `property("facing") == Some("north")`.
+    pub fn property(self, name: &str) -> Option<&'static str> {
+        let info = self.info()?;
+        let index = info.properties.iter().position(|p| p.name == name)?;
+        Some(info.properties[index].values[info.value_index(self, index)])
+    }
+
+    /// All `(name, value)` pairs of this state.
+    pub fn properties(self) -> impl Iterator<Item = (&'static str, &'static str)> {
+        let info = self.info();
+        info.into_iter().flat_map(move |info| {
+            info.properties
+                .iter()
+                .enumerate()
+                .map(move |(i, p)| (p.name, p.values[info.value_index(self, i)]))
+        })
+    }
+
+    /// The state with one property changed, or `None` if the block has no
+    /// such property or value.
+    pub fn with_property(self, name: &str, value: &str) -> Option<Self> {
+        let info = self.info()?;
+        let index = info.properties.iter().position(|p| p.name == name)?;
+        let new = info.properties[index]
+            .values
+            .iter()
+            .position(|v| *v == value)?;
+        let old = info.value_index(self, index);
+        let stride = info.stride(index);
+        let id = usize::from(self.0) - old * stride + new * stride;
+        Some(Self(id as u16))
+    }
+
+    /// Collision boxes in block-local coordinates; empty for passable blocks.
+    pub fn collision_shapes(self) -> &'static [Aabb] {
+        STATE_SHAPE
+            .get(usize::from(self.0))
+            .map_or(&[], |&shape| SHAPES[usize::from(shape)])
+    }
+
+    /// Whether the state has any collision.
+    pub fn is_solid(self) -> bool {
+        !self.collision_shapes().is_empty()
+    }
+
+    /// Whether the state is a full cube that blocks light and vision.
+    pub fn is_opaque(self) -> bool {
+        STATE_OPAQUE
+            .get(usize::from(self.0))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Whether the state holds water or lava, including waterlogged blocks
+    /// and underwater plants.
+    pub fn has_fluid(self) -> bool {
+        matches!(
+            self.block_name(),
+            Some(
+                "minecraft:water"
+                    | "minecraft:lava"
+                    | "minecraft:bubble_column"
+                    | "minecraft:kelp"
+                    | "minecraft:kelp_plant"
+                    | "minecraft:seagrass"
+                    | "minecraft:tall_seagrass"
+            )
+        ) || self.property("waterlogged") == Some("true")
+    }
+
+    /// Mining hardness (`-1.0` for unbreakable blocks).
+    pub fn hardness(self) -> Option<f32> {
+        self.info().map(|b| b.hardness)
+    }
+}
@@ -0,0 +1,388 @@
+//! Target selectors, as written in command arguments
+//!
+//! ```text
+//! @p  nearest player       @a  every player     @r  a random player
+//! @e  every entity         @s  the executor     Steve  the player named Steve
+//! @e[type=player,distance=..10,sort=furthest,limit=2]
+//! ```
+//!
+//! Supported arguments are `name`, `type` (either may be negated with `!`
+//! and repeated), `distance` (a range: `5`, `..10`, `5..`, `5..10`), `x`,
+//! `y` and `z` (the origin distances are measured from), `limit` and `sort`
+//! (`nearest`, `furthest`, `random`, `arbitrary`).
+//!
+//! Selectors don't know about any world: [`EntitySelector::select`] picks
+//! from [`Candidate`]s the caller collected, so every frontend (commands,
+//! RCON, scripts) resolves them the same way.
+
+use std::fmt;
+
+/// Who a selector starts from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorTarget {
+    /// `@p`
+    NearestPlayer,
+    /// `@a`
+    AllPlayers,
+    /// `@r`
+    RandomPlayer,
+    /// `@e`
+    AllEntities,
+    /// `@s`
+    Executor,
+    /// A player name
+    Player(String),
+}
+
+/// Order of selected entities before `limit` applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorSort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+/// A parsed selector
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySelector {
+    pub target: SelectorTarget,
+    /// `(name, negated)`; every one must match
+    pub names: Vec<(String, bool)>,
+    /// `(type, negated)` with the `minecraft:` prefix; every one must match
+    pub types: Vec<(String, bool)>,
+    /// Inclusive bounds on the distance from the origin
+    pub distance: Option<(Option<f64>, Option<f64>)>,
+    /// Overrides of the origin's coordinates
+    pub origin: [Option<f64>; 3],
+    pub limit: Option<usize>,
+    pub sort: Option<SelectorSort>,
+}
+
+/// An entity a selector may pick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate<'a> {
+    pub position: [f64; 3],
+    /// Entity type, e.g. `minecraft:player`
+    pub entity_type: &'a str,
+    pub name: Option<&'a str>,
+    /// Whether this is the entity running the command
+    pub executor: bool,
+}
+
+impl Candidate<'_> {
+    fn is_player(&self) -> bool {
+        self.entity_type == PLAYER
+    }
+}
+
+const PLAYER: &str = "minecraft:player";
+
+impl EntitySelector {
+    fn new(target: SelectorTarget) -> Self {
+        Self {
+            target,
+            names: Vec::new(),
+            types: Vec::new(),
+            distance: None,
+            origin: [None; 3],
+            limit: None,
+            sort: None,
+        }
+    }
+
+    /// Parse the selector at the start of `input`, returning it and the
+    /// input after it
+    ///
+    /// # Errors
+    /// Fails on an unknown selector, argument or invalid argument value.
+    pub fn parse(input: &str) -> Result<(Self, &str), SelectorError> {
+        let input = input.trim_start();
+        let Some(rest) = input.strip_prefix('@') else {
+            let end = input.find(char::is_whitespace).unwrap_or(input.len());
+            let (name, rest) = input.split_at(end);
+            if name.is_empty() {
+                return Err(SelectorError::Empty);
+            }
+            return Ok((Self::new(SelectorTarget::Player(name.to_string())), rest));
+        };
+
+        let mut chars = rest.chars();
+        let target = match chars.next() {
+            Some('p') => SelectorTarget::NearestPlayer,
+            Some('a') => SelectorTarget::AllPlayers,
+            Some('r') => SelectorTarget::RandomPlayer,
+            Some('e') => SelectorTarget::AllEntities,
+            Some('s') => SelectorTarget::Executor,
+            Some(c) => return Err(SelectorError::UnknownTarget(c)),
+            None => return Err(SelectorError::Empty),
+        };
+        let mut selector = Self::new(target);
+        let rest = chars.as_str();
+        let Some(arguments) = rest.strip_prefix('[') else {
+            return Ok((selector, rest));
+        };
+        let (arguments, rest) = split_arguments(arguments)?;
+        for argument in arguments {
+            selector.apply(argument)?;
+        }
+        Ok((selector, rest))
+    }
+
+    /// Apply one `key=value` argument
+    fn apply(&mut self, argument: &str) -> Result<(), SelectorError> {
+        let (key, value) = argument
+            .split_once('=')
+            .ok_or_else(|| SelectorError::UnknownArgument(argument.to_string()))?;
+        let (key, value) = (key.trim(), value.trim());
+        let invalid = || SelectorError::InvalidValue {
+            argument: key.to_string(),
+            value: value.to_string(),
+        };
+        let negatable = |value: &str| match value.strip_prefix('!') {
+            Some(value) => (unquote(value.trim()).to_string(), true),
+            None => (unquote(value).to_string(), false),
+        };
+
+        match key {
+            "name" => self.names.push(negatable(value)),
+            "type" => {
+                let (entity_type, negated) = negatable(value);
+                let entity_type = if entity_type.contains(':') {
+                    entity_type
+                } else {
+                    format!("minecraft:{entity_type}")
+                };
+                self.types.push((entity_type, negated));
+            }
+            "distance" => {
+                let range = parse_range(value).ok_or_else(invalid)?;
+                if range.0.is_some_and(|min| min < 0.0) {
+                    return Err(invalid());
+                }
+                self.distance = Some(range);
+            }
+            "x" | "y" | "z" => {
+                let axis = match key {
+                    "x" => 0,
+                    "y" => 1,
+                    _ => 2,
+                };
+                self.origin[axis] = Some(value.parse().map_err(|_| invalid())?);
+            }
+            "limit" => {
+                let limit = value.parse().ok().filter(|&limit| limit > 0);
+                self.limit = Some(limit.ok_or_else(invalid)?);
+            }
+            "sort" => {
+                self.sort = Some(match value {
+                    "nearest" => SelectorSort::Nearest,
+                    "furthest" => SelectorSort::Furthest,
+                    "random" => SelectorSort::Random,
+                    "arbitrary" => SelectorSort::Arbitrary,
+                    _ => return Err(invalid()),
+                });
+            }
+            _ => return Err(SelectorError::UnknownArgument(key.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Whether only players can be selected
+    #[must_use]
+    pub fn players_only(&self) -> bool {
+        match self.target {
+            SelectorTarget::NearestPlayer
+            | SelectorTarget::AllPlayers
+            | SelectorTarget::RandomPlayer
+            | SelectorTarget::Player(_) => true,
+            SelectorTarget::AllEntities | SelectorTarget::Executor => self
+                .types
+                .iter()
+                .any(|(entity_type, negated)| !negated && entity_type == PLAYER),
+        }
+    }
+
+    /// Whether at most one entity can be selected
+    #[must_use]
+    pub fn single(&self) -> bool {
+        self.max_selected() == Some(1)
+    }
+
+    fn max_selected(&self) -> Option<usize> {
+        match self.target {
+            SelectorTarget::Executor | SelectorTarget::Player(_) => Some(1),
+            SelectorTarget::NearestPlayer | SelectorTarget::RandomPlayer => {
+                Some(self.limit.unwrap_or(1))
+            }
+            SelectorTarget::AllPlayers | SelectorTarget::AllEntities => self.limit,
+        }
+    }
+
+    /// Indices of the selected candidates, in selection order. `origin` is
+    /// where the command runs from; `seed` drives `@r` and `sort=random`.
+    #[must_use]
+    pub fn select(&self, origin: [f64; 3], candidates: &[Candidate<'_>], seed: u64) -> Vec<usize> {
+        let origin = [0, 1, 2].map(|axis| self.origin[axis].unwrap_or(origin[axis]));
+        let distance = |candidate: &Candidate<'_>| {
+            let [dx, dy, dz] = [0, 1, 2].map(|axis| candidate.position[axis] - origin[axis]);
+            dz.mul_add(dz, dx.mul_add(dx, dy * dy)).sqrt()
+        };
+
+        let mut selected: Vec<usize> = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| self.matches(candidate, distance(candidate)))
+            .map(|(index, _)| index)
+            .collect();
+
+        let sort = self.sort.unwrap_or(match self.target {
+            SelectorTarget::NearestPlayer => SelectorSort::Nearest,
+            SelectorTarget::RandomPlayer => SelectorSort::Random,
+            _ => SelectorSort::Arbitrary,
+        });
+        match sort {
+            SelectorSort::Nearest | SelectorSort::Furthest => {
+                selected.sort_by(|&a, &b| {
+                    distance(&candidates[a]).total_cmp(&distance(&candidates[b]))
+                });
+                if sort == SelectorSort::Furthest {
+                    selected.reverse();
+                }
+            }
+            SelectorSort::Random => shuffle(&mut selected, seed),
+            SelectorSort::Arbitrary => {}
+        }
+
+        if let Some(max) = self.max_selected() {
+            selected.truncate(max);
+        }
+        selected
+    }
+
+    fn matches(&self, candidate: &Candidate<'_>, distance: f64) -> bool {
+        let base = match &self.target {
+            SelectorTarget::NearestPlayer
+            | SelectorTarget::AllPlayers
+            | SelectorTarget::RandomPlayer => candidate.is_player(),
+            SelectorTarget::AllEntities => true,
+            SelectorTarget::Executor => candidate.executor,
+            SelectorTarget::Player(name) => {
+                candidate.is_player()
+                    && candidate
+                        .name
+                        .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+            }
+        };
+        let names = self.names.iter().all(|(name, negated)| {
+            candidate.name.is_some_and(|candidate| candidate == name) != *negated
+        });
+        let types = self
+            .types
+            .iter()
+            .all(|(entity_type, negated)| (candidate.entity_type == entity_type) != *negated);
+        let in_range = self.distance.is_none_or(|(min, max)| {
+            min.is_none_or(|min| distance >= min) && max.is_none_or(|max| distance <= max)
+        });
+        base && names && types && in_range
+    }
+}
+
+/// Split `a=1,b=2] rest` (after the `[`) into its arguments and the rest
+fn split_arguments(input: &str) -> Result<(Vec<&str>, &str), SelectorError> {
+    let mut arguments = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (index, c) in input.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' | ']' if !quoted => {
+                let argument = input.get(start..index).unwrap_or_default().trim();
+                if !argument.is_empty() {
+                    arguments.push(argument);
+                }
+                start = index + 1;
+                if c == ']' {
+                    return Ok((arguments, input.get(start..).unwrap_or_default()));
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(SelectorError::Unterminated)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// `5`, `..10`, `5..` or `5..10`
+fn parse_range(value: &str) -> Option<(Option<f64>, Option<f64>)> {
+    let bound = |s: &str| -> Option<Option<f64>> {
+        if s.is_empty() {
+            Some(None)
+        } else {
+            s.parse().ok().map(Some)
+        }
+    };
+    match value.split_once("..") {
+        Some((min, max)) => {
+            let range = (bound(min)?, bound(max)?);
+            match range {
+                (None, None) => None,
+                (Some(min), Some(max)) if min > max => None,
+                range => Some(range),
+            }
+        }
+        None => {
+            let exact = value.parse().ok()?;
+            Some((Some(exact), Some(exact)))
+        }
+    }
+}
+
+/// Fisher-Yates with a xorshift generator
+fn shuffle(values: &mut [usize], seed: u64) {
+    let mut state = seed | 1;
+    for i in (1..values.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = (state % (i as u64 + 1)) as usize;
+        values.swap(i, j);
+    }
+}
+
+/// Why a selector didn't parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorError {
+    Empty,
+    /// `@` followed by something other than `p`, `a`, `r`, `e` or `s`
+    UnknownTarget(char),
+    UnknownArgument(String),
+    InvalidValue {
+        argument: String,
+        value: String,
+    },
+    /// No `]` closing the arguments
+    Unterminated,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Expected a selector or player name"),
+            Self::UnknownTarget(c) => write!(f, "Unknown selector type @{c}"),
+            Self::UnknownArgument(argument) => write!(f, "Unknown selector argument {argument}"),
+            Self::InvalidValue { argument, value } => {
+                write!(f, "Invalid value for {argument}: {value}")
+            }
+            Self::Unterminated => write!(f, "Expected ] to close the selector arguments"),
+        }
+    }
+}
+
+impl std::error::Error for SelectorError {}
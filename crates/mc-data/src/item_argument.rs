@@ -0,0 +1,114 @@
+//! Item arguments, as written after `/give` and in Skript item literals
+//!
+//! An item name, with or without the `minecraft:` prefix, optionally
+//! followed directly by an SNBT tag:
+//!
+//! ```text
+//! diamond_sword
+//! minecraft:diamond_sword{display:{Name:'"Excalibur"'},Damage:3}
+//! ```
+//!
+//! Since 1.20.5 items carry data components instead of a tag. The tag is
+//! kept whole as the stack's `minecraft:custom_data` component, which is
+//! where vanilla keeps tags it has no component for.
+
+use std::fmt;
+use std::str::FromStr;
+
+use mc_protocol::ItemStack;
+use mc_protocol::nbt::{NbtCompound, NbtError};
+
+use crate::Item;
+
+/// `minecraft:custom_data` in the `minecraft:data_component_type` registry
+pub const CUSTOM_DATA_COMPONENT: i32 = 0;
+
+/// A parsed item argument
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemArgument {
+    pub item: Item,
+    pub tag: Option<NbtCompound>,
+}
+
+impl ItemArgument {
+    /// Parse the item argument at the start of `input`, returning it and the
+    /// input after it
+    ///
+    /// # Errors
+    /// Fails on an unknown item or an invalid tag.
+    pub fn parse(input: &str) -> Result<(Self, &str), ItemArgumentError> {
+        let input = input.trim_start();
+        let end = input
+            .find(|c: char| {
+                !(c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '.' | '-' | '/'))
+            })
+            .unwrap_or(input.len());
+        let (name, rest) = input.split_at(end);
+        let item = Item::by_name(name)
+            .filter(|&item| item != Item::AIR)
+            .ok_or_else(|| ItemArgumentError::UnknownItem(name.to_string()))?;
+
+        if !rest.starts_with('{') {
+            return Ok((Self { item, tag: None }, rest));
+        }
+        let (tag, rest) = NbtCompound::parse_snbt_prefix(rest)?;
+        Ok((
+            Self {
+                item,
+                tag: Some(tag),
+            },
+            rest,
+        ))
+    }
+
+    /// A stack of `count` of this item. The count isn't clamped to the
+    /// item's max stack size; inventories split larger counts into stacks.
+    #[must_use]
+    pub fn stack(&self, count: i32) -> ItemStack {
+        let stack = ItemStack::new(self.item.id(), count);
+        match &self.tag {
+            Some(tag) => stack.with_component(CUSTOM_DATA_COMPONENT, tag.to_network_bytes()),
+            None => stack,
+        }
+    }
+}
+
+impl FromStr for ItemArgument {
+    type Err = ItemArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (argument, rest) = Self::parse(s)?;
+        let rest = rest.trim();
+        if !rest.is_empty() {
+            return Err(ItemArgumentError::TrailingInput(rest.to_string()));
+        }
+        Ok(argument)
+    }
+}
+
+/// Why an item argument didn't parse
+#[derive(Debug)]
+pub enum ItemArgumentError {
+    UnknownItem(String),
+    Tag(NbtError),
+    /// Input left after the item
+    TrailingInput(String),
+}
+
+impl fmt::Display for ItemArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownItem(name) => write!(f, "Unknown item: {name}"),
+            Self::Tag(e) => write!(f, "{e}"),
+            Self::TrailingInput(rest) => write!(f, "Unexpected input after the item: {rest}"),
+        }
+    }
+}
+
+impl std::error::Error for ItemArgumentError {}
+
+impl From<NbtError> for ItemArgumentError {
+    fn from(e: NbtError) -> Self {
+        Self::Tag(e)
+    }
+}
@@ -36,6 +36,12 @@ pub mod play {
     include!(concat!(env!("OUT_DIR"), "/play.rs"));
 }
 
+mod block;
+mod shape;
+
+pub use block::BlockProperty;
+pub use shape::Aabb;
+
 // Include generated block registry
 mod block_registry {
     include!(concat!(env!("OUT_DIR"), "/blocks.rs"));
@@ -44,3 +50,30 @@ mod block_registry {
 // Re-export block types at crate root
 pub use block_registry::BlockState;
 pub use block_registry::blocks;
+
+// Include generated item registry
+mod item_registry {
+    include!(concat!(env!("OUT_DIR"), "/items.rs"));
+}
+
+pub use item_registry::{Item, items};
+
+mod entity_selector;
+mod item_argument;
+
+pub use entity_selector::{Candidate, EntitySelector, SelectorError, SelectorSort, SelectorTarget};
+pub use item_argument::{CUSTOM_DATA_COMPONENT, ItemArgument, ItemArgumentError};
+
+mod registry;
+
+// Include generated registry payloads
+mod registry_data {
+    include!(concat!(env!("OUT_DIR"), "/registries.rs"));
+}
+
+// Include generated built-in registry entries
+mod builtin_registry {
+    include!(concat!(env!("OUT_DIR"), "/builtin.rs"));
+}
+
+pub use registry::{BuiltinRegistry, RegistryData, all_registries, builtin_registry, registry};
@@ -0,0 +1,73 @@
+//! Synced and built-in registries
+//!
+//! Synced registries are generated at build time from the vanilla-format
+//! JSON under `data/registries/<namespace>/<registry>/<entry>.json`; adding
+//! a directory there adds a registry, with no code changes. Built-in
+//! registries (which the client knows without syncing) come from Mojang's
+//! `registries.json` report, for the few the server needs IDs from.
+
+use crate::builtin_registry::BUILTIN_REGISTRIES;
+use crate::registry_data::REGISTRIES;
+
+/// One registry as sent in a Registry Data packet.
+#[derive(Debug)]
+pub struct RegistryData {
+    /// Registry name, e.g. `minecraft:worldgen/biome`
+    pub name: &'static str,
+    /// Entry names in network ID order
+    pub entries: &'static [&'static str],
+    /// Encoded packet body: registry name, then each entry with its NBT
+    pub payload: &'static [u8],
+}
+
+impl RegistryData {
+    /// Network ID of an entry, as the client assigns it from packet order.
+    pub fn entry_id(&self, name: &str) -> Option<i32> {
+        self.entries
+            .iter()
+            .position(|entry| *entry == name)
+            .map(|index| index as i32)
+    }
+
+    /// Bits per entry of direct IDs in a paletted container.
+    pub fn direct_bits(&self) -> u8 {
+        (usize::BITS - self.entries.len().saturating_sub(1).leading_zeros()) as u8
+    }
+}
+
+/// Every synced registry, to be sent during configuration.
+pub fn all_registries() -> impl Iterator<Item = &'static RegistryData> {
+    REGISTRIES.iter()
+}
+
+/// Look up a registry by name (e.g. `minecraft:worldgen/biome`).
+pub fn registry(name: &str) -> Option<&'static RegistryData> {
+    REGISTRIES.iter().find(|registry| registry.name == name)
+}
+
+/// A built-in registry, whose network IDs the client already knows.
+#[derive(Debug)]
+pub struct BuiltinRegistry {
+    /// Registry name, e.g. `minecraft:custom_stat`
+    pub name: &'static str,
+    /// Entry names in network ID order; empty until the registry reports
+    /// are extracted
+    pub entries: &'static [&'static str],
+}
+
+impl BuiltinRegistry {
+    /// Network ID of an entry.
+    pub fn entry_id(&self, name: &str) -> Option<i32> {
+        self.entries
+            .iter()
+            .position(|entry| *entry == name)
+            .map(|index| index as i32)
+    }
+}
+
+/// Look up a built-in registry by name (e.g. `minecraft:stat_type`).
+pub fn builtin_registry(name: &str) -> Option<&'static BuiltinRegistry> {
+    BUILTIN_REGISTRIES
+        .iter()
+        .find(|registry| registry.name == name)
+}
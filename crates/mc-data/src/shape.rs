@@ -0,0 +1,76 @@
+//! Block collision shapes
+
+/// An axis-aligned box in block-local coordinates (`0.0..=1.0` for a full
+/// block; fences and walls reach `1.5` on Y).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl Aabb {
+    /// The full `1x1x1` block.
+    pub const FULL: Self = Self::new([0.0; 3], [1.0; 3]);
+
+    #[must_use]
+    pub const fn new(min: [f64; 3], max: [f64; 3]) -> Self {
+        Self { min, max }
+    }
+
+    /// A box `width` wide and `height` tall standing centered on `feet`, like
+    /// an entity's hitbox.
+    #[must_use]
+    pub fn standing(feet: [f64; 3], width: f64, height: f64) -> Self {
+        let half = width / 2.0;
+        Self::new(
+            [feet[0] - half, feet[1], feet[2] - half],
+            [feet[0] + half, feet[1] + height, feet[2] + half],
+        )
+    }
+
+    /// Move the box to the block at `(x, y, z)`.
+    #[must_use]
+    pub fn offset(self, x: f64, y: f64, z: f64) -> Self {
+        let delta = [x, y, z];
+        Self {
+            min: std::array::from_fn(|i| self.min[i] + delta[i]),
+            max: std::array::from_fn(|i| self.max[i] + delta[i]),
+        }
+    }
+
+    /// Whether the two boxes overlap (touching faces don't count).
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] < other.max[i] && other.min[i] < self.max[i])
+    }
+
+    /// Whether the point lies inside the box (inclusive).
+    #[must_use]
+    pub fn contains(&self, point: [f64; 3]) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    /// Shortest horizontal move, along X or Z, that takes this box out of
+    /// `other`, or `None` if they don't overlap.
+    #[must_use]
+    pub fn push_out(&self, other: &Self) -> Option<[f64; 3]> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let along = |i: usize| {
+            let forward = other.max[i] - self.min[i];
+            let backward = self.max[i] - other.min[i];
+            if forward < backward {
+                forward
+            } else {
+                -backward
+            }
+        };
+        let (x, z) = (along(0), along(2));
+        Some(if x.abs() <= z.abs() {
+            [x, 0.0, 0.0]
+        } else {
+            [0.0, 0.0, z]
+        })
+    }
+}
@@ -0,0 +1,34 @@
+//! Extracted block shapes, opacity and hardness
+
+use mc_data::{Aabb, BlockState, blocks};
+
+#[test]
+fn hardness() {
+    assert_eq!(blocks::STONE.hardness(), Some(1.5));
+    assert_eq!(blocks::BEDROCK.hardness(), Some(-1.0));
+    assert_eq!(BlockState(u16::MAX).hardness(), None);
+}
+
+#[test]
+fn collision_shapes() {
+    assert!(BlockState::AIR.collision_shapes().is_empty());
+    assert!(!BlockState::AIR.is_opaque());
+
+    assert_eq!(
+        blocks::STONE.collision_shapes(),
+        [Aabb::new([0.0, 0.0, 0.0], [1.0, 1.0, 1.0])]
+    );
+    assert!(blocks::STONE.is_opaque());
+
+    let bottom = blocks::OAK_SLAB.with_property("type", "bottom").unwrap();
+    assert_eq!(
+        bottom.collision_shapes(),
+        [Aabb::new([0.0, 0.0, 0.0], [1.0, 0.5, 1.0])]
+    );
+    assert!(!bottom.is_opaque());
+    let top = bottom.with_property("type", "top").unwrap();
+    assert_eq!(
+        top.collision_shapes(),
+        [Aabb::new([0.0, 0.5, 0.0], [1.0, 1.0, 1.0])]
+    );
+}
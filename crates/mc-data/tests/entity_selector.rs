@@ -0,0 +1,102 @@
+//! Selector parsing and selection over hand-built candidates
+
+use mc_data::{Candidate, EntitySelector, SelectorError, SelectorSort, SelectorTarget};
+
+fn candidates() -> Vec<Candidate<'static>> {
+    let player = |name, x, executor| Candidate {
+        position: [x, 64.0, 0.0],
+        entity_type: "minecraft:player",
+        name: Some(name),
+        executor,
+    };
+    vec![
+        player("Alex", 20.0, false),
+        player("Steve", 0.0, true),
+        player("Notch", 5.0, false),
+        Candidate {
+            position: [1.0, 64.0, 0.0],
+            entity_type: "minecraft:zombie",
+            name: None,
+            executor: false,
+        },
+    ]
+}
+
+fn select(input: &str) -> Vec<usize> {
+    let (selector, rest) = EntitySelector::parse(input).unwrap();
+    assert!(rest.is_empty(), "{input}: left {rest:?}");
+    selector.select([0.0, 64.0, 0.0], &candidates(), 7)
+}
+
+#[test]
+fn selector_targets() {
+    assert_eq!(select("@p"), [1]);
+    assert_eq!(select("@a"), [0, 1, 2]);
+    assert_eq!(select("@e"), [0, 1, 2, 3]);
+    assert_eq!(select("@s"), [1]);
+    assert_eq!(select("notch"), [2]);
+    assert!(select("Herobrine").is_empty());
+
+    let random = select("@r");
+    assert_eq!(random.len(), 1);
+    assert_ne!(random, [3]);
+}
+
+#[test]
+fn selector_arguments() {
+    assert_eq!(select("@e[distance=..5]"), [1, 2, 3]);
+    assert_eq!(select("@e[distance=2..,type=!player]"), Vec::<usize>::new());
+    assert_eq!(select("@e[type=zombie]"), [3]);
+    assert_eq!(select("@a[name=!Steve]"), [0, 2]);
+    assert_eq!(select("@e[sort=nearest,limit=2]"), [1, 3]);
+    assert_eq!(select("@a[sort=furthest]"), [0, 2, 1]);
+    assert_eq!(select("@p[x=19]"), [0]);
+    assert_eq!(select("@p[limit=2]"), [1, 2]);
+    assert_eq!(select("@a[name=\"Alex\"]"), [0]);
+
+    let (selector, rest) = EntitySelector::parse("@e[ sort = random , limit=1 ] diamond").unwrap();
+    assert_eq!(rest, " diamond");
+    assert_eq!(selector.target, SelectorTarget::AllEntities);
+    assert_eq!(selector.sort, Some(SelectorSort::Random));
+    assert!(selector.single());
+    assert!(!selector.players_only());
+    assert!(
+        EntitySelector::parse("@e[type=player]")
+            .unwrap()
+            .0
+            .players_only()
+    );
+}
+
+#[test]
+fn selector_errors() {
+    for (input, error) in [
+        ("", SelectorError::Empty),
+        ("@x", SelectorError::UnknownTarget('x')),
+        ("@e[distance=..10", SelectorError::Unterminated),
+        (
+            "@e[color=red]",
+            SelectorError::UnknownArgument("color".to_string()),
+        ),
+        (
+            "@e[distance=10..5]",
+            SelectorError::InvalidValue {
+                argument: "distance".to_string(),
+                value: "10..5".to_string(),
+            },
+        ),
+        (
+            "@a[limit=0]",
+            SelectorError::InvalidValue {
+                argument: "limit".to_string(),
+                value: "0".to_string(),
+            },
+        ),
+    ] {
+        assert_eq!(
+            EntitySelector::parse(input).map(|_| ()),
+            Err(error),
+            "{input}"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Golden packet fixtures
+//!
+//! Every `tests/golden/*.json` file is an `mc-proxy` recording of a vanilla
+//! client talking to a vanilla server. Each recorded packet is decoded into
+//! its generated struct and encoded again; the bytes must match what was
+//! captured, so a field-order or type regression in the codegen shows up as
+//! a byte mismatch. Recordings also carry the protocol version from the
+//! handshake, and must be recaptured when `PROTOCOL_VERSION` changes.
+//!
+//! Compressed frames are inflated before decoding. To capture a fixture, run
+//! a vanilla server on port 25566 (`nix run .#run-vanilla-server`), then:
+//!
+//! ```text
+//! cargo run -p mc-proxy -- 25565 25566 crates/mc-data/tests/golden/join.json
+//! ```
+//!
+//! and join `localhost:25565` with a vanilla client. The test is ignored
+//! until a recording is committed; run it with `cargo test -p mc-data --test
+//! golden -- --ignored`, where it fails if the directory has no recordings.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::read::ZlibDecoder;
+
+use mc_data::{PROTOCOL_VERSION, configuration, handshake, login, play, status};
+use mc_protocol::{Packet, read_varint};
+use serde::Deserialize;
+
+/// A packet as recorded by `mc-proxy`
+#[derive(Deserialize)]
+struct RecordedPacket {
+    state: RecordedState,
+    direction: RecordedDirection,
+    /// Length-prefixed frame
+    raw_data: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct Recording {
+    packets: Vec<RecordedPacket>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum RecordedState {
+    Handshaking,
+    Status,
+    Login,
+    Configuration,
+    Play,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum RecordedDirection {
+    ClientToServer,
+    ServerToClient,
+}
+
+type PacketName = fn(i32) -> Option<&'static str>;
+type Roundtrip = fn(i32, &[u8]) -> Option<mc_protocol::Result<Vec<u8>>>;
+
+/// The generated lookup functions for a state and direction
+fn codegen(state: RecordedState, direction: RecordedDirection) -> Option<(PacketName, Roundtrip)> {
+    use RecordedDirection::{ClientToServer as C2S, ServerToClient as S2C};
+    use RecordedState::{Configuration, Handshaking, Login, Play, Status};
+
+    Some(match (state, direction) {
+        (Handshaking, C2S) => (
+            handshake::serverbound::packet_name,
+            handshake::serverbound::roundtrip,
+        ),
+        (Status, C2S) => (
+            status::serverbound::packet_name,
+            status::serverbound::roundtrip,
+        ),
+        (Status, S2C) => (
+            status::clientbound::packet_name,
+            status::clientbound::roundtrip,
+        ),
+        (Login, C2S) => (
+            login::serverbound::packet_name,
+            login::serverbound::roundtrip,
+        ),
+        (Login, S2C) => (
+            login::clientbound::packet_name,
+            login::clientbound::roundtrip,
+        ),
+        (Configuration, C2S) => (
+            configuration::serverbound::packet_name,
+            configuration::serverbound::roundtrip,
+        ),
+        (Configuration, S2C) => (
+            configuration::clientbound::packet_name,
+            configuration::clientbound::roundtrip,
+        ),
+        (Play, C2S) => (play::serverbound::packet_name, play::serverbound::roundtrip),
+        (Play, S2C) => (play::clientbound::packet_name, play::clientbound::roundtrip),
+        (Handshaking, S2C) => return None,
+    })
+}
+
+/// Packet ID and body of a frame, inflating it if it's compressed
+fn split_frame(frame: &[u8], compressed: bool) -> mc_protocol::Result<(i32, Vec<u8>)> {
+    let mut rest = frame;
+    read_varint(&mut rest)?;
+    let mut inflated = Vec::new();
+    if compressed {
+        // Data length: 0 if the packet was sent below the threshold
+        let length = read_varint(&mut rest)?;
+        if length != 0 {
+            ZlibDecoder::new(rest).read_to_end(&mut inflated)?;
+            if inflated.len() != length as usize {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("inflated {} bytes, expected {length}", inflated.len()),
+                )
+                .into());
+            }
+            rest = &inflated;
+        }
+    }
+    let id = read_varint(&mut rest)?;
+    Ok((id, rest.to_vec()))
+}
+
+/// Round-trip every packet of a recording, returning the failures
+fn check_recording(recording: &Recording) -> Vec<String> {
+    let mut failures = Vec::new();
+    let mut compressed = false;
+
+    for (index, packet) in recording.packets.iter().enumerate() {
+        let Some((packet_name, roundtrip)) = codegen(packet.state, packet.direction) else {
+            failures.push(format!(
+                "#{index}: no packets for {:?} {:?}",
+                packet.state, packet.direction
+            ));
+            continue;
+        };
+        let (id, body) = match split_frame(&packet.raw_data, compressed) {
+            Ok(frame) => frame,
+            Err(e) => {
+                failures.push(format!("#{index}: malformed frame: {e}"));
+                continue;
+            }
+        };
+        let name = packet_name(id).unwrap_or("<unknown>");
+        let label = format!(
+            "#{index} {:?} {:?} 0x{id:02X} {name}",
+            packet.state, packet.direction
+        );
+
+        if packet.state == RecordedState::Handshaking {
+            match read_varint(&mut &body[..]) {
+                Ok(PROTOCOL_VERSION) => {}
+                Ok(version) => {
+                    failures.push(format!(
+                        "{label}: recorded with protocol {version}, codegen is {PROTOCOL_VERSION}; recapture the fixture"
+                    ));
+                    return failures;
+                }
+                Err(e) => failures.push(format!("{label}: unreadable protocol version: {e}")),
+            }
+        }
+        if packet.state == RecordedState::Login
+            && packet.direction == RecordedDirection::ServerToClient
+            && id == login::clientbound::LoginCompression::ID
+        {
+            compressed = true;
+        }
+
+        match roundtrip(id, &body) {
+            None => failures.push(format!("{label}: no generated codec")),
+            Some(Err(e)) => failures.push(format!("{label}: decode failed: {e}")),
+            Some(Ok(encoded)) if encoded != body => {
+                let at = encoded
+                    .iter()
+                    .zip(&body)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or_else(|| encoded.len().min(body.len()));
+                failures.push(format!(
+                    "{label}: re-encoded {} bytes, recorded {} (first difference at byte {at})",
+                    encoded.len(),
+                    body.len()
+                ));
+            }
+            Some(Ok(_)) => {}
+        }
+    }
+
+    failures
+}
+
+#[test]
+#[ignore = "needs a recording"]
+fn golden_fixtures_roundtrip() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let recordings: Vec<_> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("{}: {e}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    assert!(
+        !recordings.is_empty(),
+        "no golden fixtures in {}; capture one with mc-proxy",
+        dir.display()
+    );
+
+    let mut failures = Vec::new();
+    for path in recordings {
+        let recording: Recording = serde_json::from_slice(&fs::read(&path).unwrap())
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        failures.extend(
+            check_recording(&recording)
+                .into_iter()
+                .map(|failure| format!("{}: {failure}", path.display())),
+        );
+    }
+
+    assert!(
+        failures.is_empty(),
+        "golden fixtures failed:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn harness_flags_leftover_bytes() {
+    let packet = |raw_data: Vec<u8>| RecordedPacket {
+        state: RecordedState::Status,
+        direction: RecordedDirection::ClientToServer,
+        raw_data,
+    };
+
+    // Status request: length 1, ID 0, no fields
+    let ok = Recording {
+        packets: vec![packet(vec![1, 0])],
+    };
+    assert!(check_recording(&ok).is_empty());
+
+    let trailing = Recording {
+        packets: vec![packet(vec![2, 0, 42])],
+    };
+    let failures = check_recording(&trailing);
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].contains("StatusRequest"), "{}", failures[0]);
+}
+
+#[test]
+fn harness_inflates_compressed_frames() {
+    use std::io::Write;
+
+    let mut packet = vec![0x2B];
+    packet.extend([7; 300]);
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&packet).unwrap();
+    let zlib = encoder.finish().unwrap();
+
+    let mut frame = Vec::new();
+    let mut data = Vec::new();
+    mc_protocol::write_varint(&mut data, packet.len() as i32).unwrap();
+    data.extend(&zlib);
+    mc_protocol::write_varint(&mut frame, data.len() as i32).unwrap();
+    frame.extend(&data);
+    assert_eq!(split_frame(&frame, true).unwrap(), (0x2B, vec![7; 300]));
+
+    // Below the threshold: data length 0, sent as is
+    assert_eq!(
+        split_frame(&[3, 0, 0x2B, 7], true).unwrap(),
+        (0x2B, vec![7])
+    );
+}
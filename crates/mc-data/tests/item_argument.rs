@@ -0,0 +1,52 @@
+//! Item arguments against the generated item registry
+
+use mc_data::{CUSTOM_DATA_COMPONENT, Item, ItemArgument, ItemArgumentError, items};
+use mc_protocol::nbt::NbtCompound;
+
+#[test]
+fn item_argument_with_tag() {
+    let sword = items::DIAMOND_SWORD;
+    assert_eq!(Item::by_name("diamond_sword"), Some(sword));
+    assert_eq!(sword.max_stack_size(), 1);
+
+    let (argument, rest) =
+        ItemArgument::parse("minecraft:diamond_sword{display:{Name:'\"Excalibur\"'}} 2").unwrap();
+    assert_eq!(argument.item, sword);
+    assert_eq!(rest, " 2");
+
+    let tag: NbtCompound = "{display:{Name:'\"Excalibur\"'}}".parse().unwrap();
+    assert_eq!(argument.tag.as_ref(), Some(&tag));
+
+    let stack = argument.stack(1);
+    assert_eq!(stack.item, sword.id());
+    assert_eq!(stack.added.len(), 1);
+    assert_eq!(stack.added[0].id, CUSTOM_DATA_COMPONENT);
+    assert_eq!(stack.added[0].data, tag.to_network_bytes());
+
+    let plain: ItemArgument = "diamond_sword".parse().unwrap();
+    assert_eq!(plain.item, sword);
+    assert!(plain.tag.is_none());
+    assert!(plain.stack(3).added.is_empty());
+
+    assert!(matches!(
+        "diamond_sword{Damage:".parse::<ItemArgument>(),
+        Err(ItemArgumentError::Tag(_))
+    ));
+    assert!(matches!(
+        "diamond_sword extra".parse::<ItemArgument>(),
+        Err(ItemArgumentError::TrailingInput(_))
+    ));
+}
+
+#[test]
+fn item_argument_unknown_item() {
+    for input in ["no_such_item", "air", "{Damage:3}"] {
+        assert!(
+            matches!(
+                input.parse::<ItemArgument>(),
+                Err(ItemArgumentError::UnknownItem(_))
+            ),
+            "{input}"
+        );
+    }
+}
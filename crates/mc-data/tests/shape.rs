@@ -0,0 +1,25 @@
+//! Box overlap and push-out
+
+use mc_data::Aabb;
+
+#[test]
+fn standing_box() {
+    let player = Aabb::standing([10.0, 64.0, -3.0], 0.6, 1.8);
+    assert_eq!(player, Aabb::new([9.7, 64.0, -3.3], [10.3, 65.8, -2.7]));
+}
+
+#[test]
+fn push_out_along_least_overlap() {
+    let a = Aabb::standing([0.0, 0.0, 0.0], 1.0, 2.0);
+
+    // 0.25 into `a` on X, 0.9 on Z: pushed back out along X
+    let b = Aabb::standing([0.75, 0.0, 0.1], 1.0, 2.0);
+    assert_eq!(b.push_out(&a), Some([0.25, 0.0, 0.0]));
+    assert_eq!(a.push_out(&b), Some([-0.25, 0.0, 0.0]));
+
+    let c = Aabb::standing([0.1, 1.0, -0.5], 1.0, 2.0);
+    assert_eq!(c.push_out(&a), Some([0.0, 0.0, -0.5]));
+
+    let d = b.offset(0.25, 0.0, 0.0);
+    assert_eq!(d.push_out(&a), None);
+}
@@ -0,0 +1,362 @@
+//! Chaos proxy for robustness tests.
+//!
+//! [`ChaosProxy`] sits between the client and the server and forwards TCP
+//! traffic both ways while making the network worse on purpose:
+//!
+//! - every chunk read is held back for [`ChaosConfig::latency`] plus up to
+//!   [`ChaosConfig::jitter`], without ever reordering bytes, since TCP
+//!   doesn't either;
+//! - writes are split into segments of random sizes up to
+//!   [`ChaosConfig::max_segment`] bytes, so packets arrive in pieces and
+//!   several packets can share a read, which exercises framing;
+//! - connections are cut after [`ChaosConfig::disconnect_after`], or all at
+//!   once with [`ChaosProxy::disconnect_all`];
+//! - traffic can be stalled with [`ChaosProxy::stall`] without closing
+//!   anything, which is how a keep-alive timeout looks to the server.
+//!
+//! Random choices come from [`ChaosConfig::seed`], so a failing run can be
+//! reproduced.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use eyre::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, info};
+
+/// How the proxy mistreats traffic; the default forwards it untouched
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// Delay added to every chunk in both directions
+    pub latency: Duration,
+    /// Extra random delay of up to this much per chunk
+    pub jitter: Duration,
+    /// Split writes into segments of at most this many bytes
+    pub max_segment: Option<usize>,
+    /// Cut every connection this long after it was accepted
+    pub disconnect_after: Option<Duration>,
+    /// Seed for the random delays and segment sizes
+    pub seed: u64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            max_segment: None,
+            disconnect_after: None,
+            seed: 0x5eed,
+        }
+    }
+}
+
+/// What went through the proxy so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    /// Connections accepted
+    pub connections: u64,
+    /// Connections the proxy cut
+    pub disconnects: u64,
+    /// Bytes forwarded from the client to the server
+    pub bytes_up: u64,
+    /// Bytes forwarded from the server to the client
+    pub bytes_down: u64,
+    /// Writes made, counting every segment
+    pub segments: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    connections: AtomicU64,
+    disconnects: AtomicU64,
+    bytes_up: AtomicU64,
+    bytes_down: AtomicU64,
+    segments: AtomicU64,
+}
+
+/// A running chaos proxy in front of a server
+pub struct ChaosProxy {
+    addr: SocketAddr,
+    counters: Arc<Counters>,
+    /// Bumped to cut every open connection
+    disconnect: watch::Sender<u64>,
+    stalled: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl ChaosProxy {
+    /// Listen on a free local port and forward connections to `upstream`
+    ///
+    /// # Errors
+    /// Returns an error if the proxy can't listen
+    pub async fn start(upstream: SocketAddr, config: ChaosConfig) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        info!("Chaos proxy on {addr} forwarding to {upstream} with {config:?}");
+
+        let counters = Arc::new(Counters::default());
+        let (disconnect, _) = watch::channel(0);
+        let (stalled, _) = watch::channel(false);
+        let task = tokio::spawn(accept_loop(
+            listener,
+            upstream,
+            config,
+            counters.clone(),
+            disconnect.clone(),
+            stalled.clone(),
+        ));
+
+        Ok(Self {
+            addr,
+            counters,
+            disconnect,
+            stalled,
+            task,
+        })
+    }
+
+    /// Get the port clients should connect to
+    #[must_use]
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// Cut every open connection; later ones are forwarded as usual
+    pub fn disconnect_all(&self) {
+        self.disconnect.send_modify(|generation| *generation += 1);
+    }
+
+    /// Stop forwarding until [`Self::resume`], keeping connections open;
+    /// traffic is buffered meanwhile
+    pub fn stall(&self) {
+        self.stalled.send_replace(true);
+    }
+
+    /// Forward the traffic held back by [`Self::stall`] and carry on
+    pub fn resume(&self) {
+        self.stalled.send_replace(false);
+    }
+
+    /// What went through the proxy so far
+    #[must_use]
+    pub fn stats(&self) -> ChaosStats {
+        let c = &self.counters;
+        ChaosStats {
+            connections: c.connections.load(Ordering::Relaxed),
+            disconnects: c.disconnects.load(Ordering::Relaxed),
+            bytes_up: c.bytes_up.load(Ordering::Relaxed),
+            bytes_down: c.bytes_down.load(Ordering::Relaxed),
+            segments: c.segments.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for ChaosProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.disconnect_all();
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    upstream: SocketAddr,
+    config: ChaosConfig,
+    counters: Arc<Counters>,
+    disconnect: watch::Sender<u64>,
+    stalled: watch::Sender<bool>,
+) {
+    let mut seed = config.seed;
+    while let Ok((client, peer)) = listener.accept().await {
+        let n = counters.connections.fetch_add(1, Ordering::Relaxed);
+        debug!("Chaos proxy accepted connection {n} from {peer}");
+        seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        tokio::spawn(proxy_connection(
+            client,
+            upstream,
+            config.clone(),
+            seed,
+            counters.clone(),
+            disconnect.subscribe(),
+            stalled.subscribe(),
+        ));
+    }
+}
+
+async fn proxy_connection(
+    client: TcpStream,
+    upstream: SocketAddr,
+    config: ChaosConfig,
+    seed: u64,
+    counters: Arc<Counters>,
+    mut disconnect: watch::Receiver<u64>,
+    stalled: watch::Receiver<bool>,
+) {
+    let server = match TcpStream::connect(upstream).await {
+        Ok(server) => server,
+        Err(e) => {
+            debug!("Chaos proxy couldn't reach {upstream}: {e}");
+            return;
+        }
+    };
+    // Segments should leave as separate TCP segments
+    let _ = client.set_nodelay(true);
+    let _ = server.set_nodelay(true);
+
+    let (client_read, client_write) = client.into_split();
+    let (server_read, server_write) = server.into_split();
+    let up = Direction {
+        config: config.clone(),
+        rng: Rng(seed),
+        counters: counters.clone(),
+        up: true,
+        stalled: stalled.clone(),
+    };
+    let down = Direction {
+        config: config.clone(),
+        rng: Rng(seed.rotate_left(32) ^ 1),
+        counters: counters.clone(),
+        up: false,
+        stalled,
+    };
+
+    let cut = async {
+        match config.disconnect_after {
+            Some(after) => {
+                tokio::select! {
+                    () = tokio::time::sleep(after) => {}
+                    _ = disconnect.changed() => {}
+                }
+            }
+            None => {
+                let _ = disconnect.changed().await;
+            }
+        }
+    };
+
+    // Runs until both sides closed, or the proxy cuts the connection
+    let forward = async {
+        tokio::join!(
+            up.forward(client_read, server_write),
+            down.forward(server_read, client_write)
+        )
+    };
+    tokio::select! {
+        _ = forward => {}
+        () = cut => {
+            counters.disconnects.fetch_add(1, Ordering::Relaxed);
+            debug!("Chaos proxy cut a connection");
+        }
+    }
+}
+
+/// One direction of a proxied connection
+struct Direction {
+    config: ChaosConfig,
+    rng: Rng,
+    counters: Arc<Counters>,
+    /// From the client to the server
+    up: bool,
+    stalled: watch::Receiver<bool>,
+}
+
+impl Direction {
+    /// Forward until either side closes
+    async fn forward(mut self, mut from: OwnedReadHalf, mut to: OwnedWriteHalf) {
+        let (tx, mut rx) = mpsc::unbounded_channel::<(Instant, Vec<u8>)>();
+
+        let jitter = self.config.jitter;
+        let latency = self.config.latency;
+        let mut rng = self.rng.clone();
+        let read = async move {
+            let mut buf = vec![0; 16 * 1024];
+            // Chunks are never delivered before the previous one
+            let mut last = Instant::now();
+            loop {
+                let n = match from.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                let delay = latency + jitter.mul_f64(rng.unit());
+                last = last.max(Instant::now() + delay);
+                if tx.send((last, buf[..n].to_vec())).is_err() {
+                    break;
+                }
+            }
+        };
+
+        let write = async move {
+            while let Some((at, chunk)) = rx.recv().await {
+                tokio::time::sleep_until(at).await;
+                let _ = self.stalled.wait_for(|stalled| !stalled).await;
+                if self.write_segments(&mut to, &chunk).await.is_err() {
+                    break;
+                }
+            }
+            let _ = to.shutdown().await;
+        };
+
+        tokio::join!(read, write);
+    }
+
+    async fn write_segments(&mut self, to: &mut OwnedWriteHalf, chunk: &[u8]) -> Result<()> {
+        let mut rest = chunk;
+        while !rest.is_empty() {
+            let len = match self.config.max_segment {
+                Some(max) => self.rng.below(max.max(1)) + 1,
+                None => rest.len(),
+            }
+            .min(rest.len());
+            let (segment, tail) = rest.split_at(len);
+            to.write_all(segment).await?;
+            to.flush().await?;
+            self.counters.segments.fetch_add(1, Ordering::Relaxed);
+            let bytes = if self.up {
+                &self.counters.bytes_up
+            } else {
+                &self.counters.bytes_down
+            };
+            bytes.fetch_add(len as u64, Ordering::Relaxed);
+            rest = tail;
+            if !rest.is_empty() {
+                // Give the segment a chance to leave on its own
+                tokio::task::yield_now().await;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Small seeded xorshift generator, good enough to make noise
+#[derive(Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        // xorshift can't leave 0
+        let mut x = self.0.max(1);
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform in `[0, 1)`
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[0, n)`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
@@ -0,0 +1,365 @@
+//! Assertions about the server's world state over its introspect channel.
+//!
+//! The server serves its ECS world over the Flecs REST API (the one the
+//! Flecs Explorer connects to). [`Introspect`] reads entities from it by
+//! path, and [`Introspect::expect_entity`] starts an assertion that is
+//! retried until it holds or times out, since the world only catches up
+//! with what the client did a few ticks later:
+//!
+//! ```rust,ignore
+//! test.server()
+//!     .introspect()
+//!     .expect_entity("players::TestPlayer")
+//!     .component::<Position>()
+//!     .field("y")
+//!     .near(64.0)
+//!     .await?;
+//! ```
+//!
+//! Components are matched by their name without the module path, so
+//! `component::<Position>()` finds `mc_server_lib.Position` too. Fields are
+//! dot-separated paths into the component's JSON value, with numbers
+//! indexing arrays (`"slots.0.count"`).
+
+use std::time::Duration;
+
+use eyre::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::debug;
+
+/// Port the Flecs REST API listens on unless configured otherwise
+pub const DEFAULT_REST_PORT: u16 = 27750;
+
+/// How long an assertion is retried by default
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait between two attempts by default
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Largest difference [`FieldExpectation::near`] accepts
+const NEAR_TOLERANCE: f64 = 0.01;
+
+/// Connection to a server's introspect channel
+#[derive(Debug, Clone)]
+pub struct Introspect {
+    addr: String,
+    timeout: Duration,
+    interval: Duration,
+}
+
+impl Introspect {
+    /// Read the world of the server whose REST API listens on `host:port`
+    #[must_use]
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            addr: format!("{host}:{port}"),
+            timeout: DEFAULT_TIMEOUT,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Retry assertions for up to `timeout`
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Wait `interval` between two attempts
+    #[must_use]
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Get an entity by its path, e.g. `players::TestPlayer`, or `None` if
+    /// there is no such entity
+    ///
+    /// # Errors
+    /// Returns an error if the server can't be reached or its response is
+    /// invalid
+    pub async fn entity(&self, path: &str) -> Result<Option<Value>> {
+        let url = format!("/entity/{}?values=true", path.replace("::", "/"));
+        let (status, body) = self.get(&url).await?;
+        match status {
+            200 => Ok(Some(serde_json::from_str(&body)?)),
+            404 => Ok(None),
+            _ => eyre::bail!("GET {url} failed with status {status}: {body}"),
+        }
+    }
+
+    /// Start an assertion about the entity at `path`
+    #[must_use]
+    pub fn expect_entity(&self, path: &str) -> EntityExpectation {
+        EntityExpectation {
+            introspect: self.clone(),
+            path: path.to_string(),
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<(u16, String)> {
+        let mut stream = TcpStream::connect(&self.addr).await?;
+        let request = format!(
+            "GET {url} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            self.addr
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        let response = String::from_utf8_lossy(&response);
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| eyre::eyre!("Incomplete HTTP response from {}", self.addr))?;
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| eyre::eyre!("Invalid HTTP status line from {}", self.addr))?;
+        Ok((status, body.to_string()))
+    }
+}
+
+/// An assertion about one entity, see [`Introspect::expect_entity`]
+#[derive(Debug, Clone)]
+pub struct EntityExpectation {
+    introspect: Introspect,
+    path: String,
+}
+
+impl EntityExpectation {
+    /// Retry this assertion for up to `timeout`
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.introspect.timeout = timeout;
+        self
+    }
+
+    /// Wait `interval` between two attempts
+    #[must_use]
+    pub fn every(mut self, interval: Duration) -> Self {
+        self.introspect.interval = interval;
+        self
+    }
+
+    /// Continue with the entity's component of type `T`
+    #[must_use]
+    pub fn component<T>(self) -> ComponentExpectation {
+        let name = std::any::type_name::<T>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+        self.component_named(name)
+    }
+
+    /// Continue with the entity's component called `name`
+    #[must_use]
+    pub fn component_named(self, name: &str) -> ComponentExpectation {
+        ComponentExpectation {
+            entity: self,
+            component: name.to_string(),
+        }
+    }
+
+    /// Wait for the entity to exist and return it
+    ///
+    /// # Errors
+    /// Returns an error if it still doesn't exist on timeout
+    pub async fn exists(&self) -> Result<Value> {
+        self.until("to exist", |entity| Ok(entity.clone())).await
+    }
+
+    /// Wait for the entity to be gone
+    ///
+    /// # Errors
+    /// Returns an error if it still exists on timeout
+    pub async fn missing(&self) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + self.introspect.timeout;
+        loop {
+            match self.fetch(deadline).await {
+                Ok(None) => return Ok(()),
+                Ok(Some(_)) => {}
+                Err(e) => debug!("Introspect request for {} failed: {e}", self.path),
+            }
+            if tokio::time::Instant::now() >= deadline {
+                eyre::bail!(
+                    "Timed out after {:?} waiting for {} to be gone",
+                    self.introspect.timeout,
+                    self.path
+                );
+            }
+            tokio::time::sleep(self.introspect.interval).await;
+        }
+    }
+
+    /// Fetch the entity until `check` accepts it; `check` returns what it
+    /// observed instead when it doesn't
+    async fn until<T>(
+        &self,
+        expected: &str,
+        mut check: impl FnMut(&Value) -> Result<T, String>,
+    ) -> Result<T> {
+        let deadline = tokio::time::Instant::now() + self.introspect.timeout;
+        loop {
+            let observed = match self.fetch(deadline).await {
+                Ok(Some(entity)) => match check(&entity) {
+                    Ok(value) => return Ok(value),
+                    Err(observed) => observed,
+                },
+                Ok(None) => "no such entity".to_string(),
+                Err(e) => format!("request failed: {e}"),
+            };
+            if tokio::time::Instant::now() >= deadline {
+                eyre::bail!(
+                    "Timed out after {:?} waiting for {} {expected}, last saw {observed}",
+                    self.introspect.timeout,
+                    self.path
+                );
+            }
+            tokio::time::sleep(self.introspect.interval).await;
+        }
+    }
+
+    async fn fetch(&self, deadline: tokio::time::Instant) -> Result<Option<Value>> {
+        let remaining = deadline
+            .saturating_duration_since(tokio::time::Instant::now())
+            .max(self.introspect.interval);
+        tokio::time::timeout(remaining, self.introspect.entity(&self.path))
+            .await
+            .map_err(|_| eyre::eyre!("no response"))?
+    }
+}
+
+/// An assertion about one component of an entity, see
+/// [`EntityExpectation::component`]
+#[derive(Debug, Clone)]
+pub struct ComponentExpectation {
+    entity: EntityExpectation,
+    component: String,
+}
+
+impl ComponentExpectation {
+    /// Continue with a field of the component, e.g. `y` or `inner.0.name`
+    #[must_use]
+    pub fn field(self, path: &str) -> FieldExpectation {
+        FieldExpectation {
+            component: self,
+            field: path.to_string(),
+        }
+    }
+
+    /// Wait for the entity to have the component and return its value
+    ///
+    /// # Errors
+    /// Returns an error if it still doesn't have it on timeout
+    pub async fn exists(&self) -> Result<Value> {
+        let expected = format!("to have {}", self.component);
+        self.entity
+            .until(&expected, |entity| self.select(entity).cloned())
+            .await
+    }
+
+    fn select<'a>(&self, entity: &'a Value) -> Result<&'a Value, String> {
+        let dotted = format!(".{}", self.component);
+        let scoped = format!("::{}", self.component);
+        entity
+            .get("components")
+            .and_then(Value::as_object)
+            .and_then(|components| {
+                components.iter().find_map(|(name, value)| {
+                    (*name == self.component || name.ends_with(&dotted) || name.ends_with(&scoped))
+                        .then_some(value)
+                })
+            })
+            .ok_or_else(|| format!("no {} component", self.component))
+    }
+}
+
+/// An assertion about one field of a component, see
+/// [`ComponentExpectation::field`]
+#[derive(Debug, Clone)]
+pub struct FieldExpectation {
+    component: ComponentExpectation,
+    field: String,
+}
+
+impl FieldExpectation {
+    /// Wait for the field to be a number within 0.01 of `expected` and
+    /// return it
+    ///
+    /// # Errors
+    /// Returns an error if it still isn't on timeout
+    pub async fn near(&self, expected: f64) -> Result<f64> {
+        self.within(expected, NEAR_TOLERANCE).await
+    }
+
+    /// Wait for the field to be a number within `tolerance` of `expected`
+    /// and return it
+    ///
+    /// # Errors
+    /// Returns an error if it still isn't on timeout
+    pub async fn within(&self, expected: f64, tolerance: f64) -> Result<f64> {
+        let description = format!("near {expected} (±{tolerance})");
+        self.satisfies(&description, |value| {
+            value
+                .as_f64()
+                .is_some_and(|actual| (actual - expected).abs() <= tolerance)
+        })
+        .await
+        .map(|value| value.as_f64().unwrap_or_default())
+    }
+
+    /// Wait for the field to equal `expected` once serialized to JSON and
+    /// return it
+    ///
+    /// # Errors
+    /// Returns an error if it still doesn't on timeout, or if `expected`
+    /// can't be serialized
+    pub async fn equals(&self, expected: impl Serialize) -> Result<Value> {
+        let expected = serde_json::to_value(expected)?;
+        self.satisfies(&expected.to_string(), |value| *value == expected)
+            .await
+    }
+
+    /// Wait for `check` to accept the field and return it; `description`
+    /// says what it checks for in the error on timeout
+    ///
+    /// # Errors
+    /// Returns an error if it still doesn't on timeout
+    pub async fn satisfies(
+        &self,
+        description: &str,
+        check: impl Fn(&Value) -> bool,
+    ) -> Result<Value> {
+        let expected = format!(
+            "{}.{} to be {description}",
+            self.component.component, self.field
+        );
+        self.component
+            .entity
+            .until(&expected, |entity| {
+                let value = self.select(entity)?;
+                if check(value) {
+                    Ok(value.clone())
+                } else {
+                    Err(value.to_string())
+                }
+            })
+            .await
+    }
+
+    fn select<'a>(&self, entity: &'a Value) -> Result<&'a Value, String> {
+        let mut value = self.component.select(entity)?;
+        for key in self.field.split('.') {
+            let next = match value {
+                Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => value.get(key),
+            };
+            value = next.ok_or_else(|| {
+                format!("no field {} in {}", self.field, self.component.component)
+            })?;
+        }
+        Ok(value)
+    }
+}
@@ -6,7 +6,7 @@
 //! # Example
 //!
 //! ```rust,ignore
-//! use mc_integration_tests::{IntegrationTest, TestConfig};
+//! use mc_integration_tests::{IntegrationTest, Position, TestConfig};
 //! use std::time::Duration;
 //!
 //! #[tokio::test]
@@ -18,14 +18,28 @@
 //!
 //!     let state = test.client().get_player_state().await.unwrap();
 //!     assert!(state.position.is_some());
+//!
+//!     // Assert on the server's world state too
+//!     test.server()
+//!         .introspect()
+//!         .expect_entity("players::TestPlayer")
+//!         .component::<Position>()
+//!         .field("y")
+//!         .near(64.0)
+//!         .await
+//!         .unwrap();
 //! }
 //! ```
 
+pub mod chaos;
 pub mod client;
+pub mod introspect;
 pub mod protocol;
 pub mod server;
 
+pub use chaos::{ChaosConfig, ChaosProxy, ChaosStats};
 pub use client::{ClientConfig, FabricClient};
+pub use introspect::{ComponentExpectation, EntityExpectation, FieldExpectation, Introspect};
 pub use protocol::{ChunkPos, PlayerState, Position, Rotation, TestEvent};
 pub use server::{ServerConfig, ServerProcess};
 
@@ -41,11 +55,14 @@ pub struct TestConfig {
     pub server: ServerConfig,
     /// Client configuration
     pub client: ClientConfig,
+    /// Put a chaos proxy between the client and the server
+    pub chaos: Option<ChaosConfig>,
 }
 
 /// An integration test fixture that manages server and client lifecycle
 pub struct IntegrationTest {
     server: ServerProcess,
+    proxy: Option<ChaosProxy>,
     client: FabricClient,
 }
 
@@ -63,10 +80,22 @@ impl IntegrationTest {
         // Give server a moment to fully initialize
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
 
+        let proxy = match config.chaos {
+            Some(chaos) => {
+                let upstream = ([127, 0, 0, 1], server.port()).into();
+                Some(ChaosProxy::start(upstream, chaos).await?)
+            }
+            None => None,
+        };
+
         // Start the client
         let client = FabricClient::spawn(config.client).await?;
 
-        Ok(Self { server, client })
+        Ok(Self {
+            server,
+            proxy,
+            client,
+        })
     }
 
     /// Get a reference to the server
@@ -75,6 +104,21 @@ impl IntegrationTest {
         &self.server
     }
 
+    /// Get the chaos proxy, if the test has one
+    #[must_use]
+    pub fn proxy(&self) -> Option<&ChaosProxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Get the port the client should connect to: the chaos proxy's if
+    /// there is one, the server's otherwise
+    #[must_use]
+    pub fn port(&self) -> u16 {
+        self.proxy
+            .as_ref()
+            .map_or_else(|| self.server.port(), ChaosProxy::port)
+    }
+
     /// Get a mutable reference to the client
     pub fn client(&mut self) -> &mut FabricClient {
         &mut self.client
@@ -9,6 +9,8 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
 use tracing::{debug, info};
 
+use crate::introspect::{DEFAULT_REST_PORT, Introspect};
+
 /// Configuration for spawning the Minecraft server
 pub struct ServerConfig {
     /// Path to the mc-server binary
@@ -149,6 +151,12 @@ impl ServerProcess {
         self.port
     }
 
+    /// The server's introspect channel, for assertions about its world
+    #[must_use]
+    pub fn introspect(&self) -> Introspect {
+        Introspect::new("127.0.0.1", DEFAULT_REST_PORT)
+    }
+
     /// Kill the server process
     ///
     /// # Errors
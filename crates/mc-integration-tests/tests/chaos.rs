@@ -0,0 +1,234 @@
+//! Tests for the chaos proxy.
+//!
+//! Most run against a local echo server and always run; the last one sends
+//! a status request to the real server through a fragmenting proxy.
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use mc_integration_tests::{
+    ChaosConfig, ChaosProxy, ServerConfig, ServerProcess, is_enabled, server_binary_path,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Start a server that echoes everything back
+async fn echo_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut read, mut write) = stream.into_split();
+                let _ = tokio::io::copy(&mut read, &mut write).await;
+            });
+        }
+    });
+    addr
+}
+
+async fn connect(proxy: &ChaosProxy) -> TcpStream {
+    TcpStream::connect(("127.0.0.1", proxy.port()))
+        .await
+        .expect("Failed to connect to the proxy")
+}
+
+#[tokio::test]
+async fn test_fragmented_jittery_traffic_arrives_intact() {
+    let config = ChaosConfig {
+        latency: Duration::from_millis(5),
+        jitter: Duration::from_millis(10),
+        max_segment: Some(7),
+        ..ChaosConfig::default()
+    };
+    let proxy = ChaosProxy::start(echo_server().await, config)
+        .await
+        .unwrap();
+    let mut stream = connect(&proxy).await;
+
+    let sent: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+    for chunk in sent.chunks(300) {
+        stream.write_all(chunk).await.unwrap();
+    }
+    let mut received = vec![0; sent.len()];
+    tokio::time::timeout(Duration::from_secs(10), stream.read_exact(&mut received))
+        .await
+        .expect("Echo timed out")
+        .unwrap();
+
+    assert_eq!(received, sent, "Bytes were lost or reordered");
+    let stats = proxy.stats();
+    assert_eq!(stats.bytes_up, sent.len() as u64);
+    assert_eq!(stats.bytes_down, sent.len() as u64);
+    // Each direction needs at least len / 7 segments
+    assert!(stats.segments >= 2 * 4096 / 7, "{stats:?}");
+}
+
+#[tokio::test]
+async fn test_latency_delays_both_directions() {
+    let config = ChaosConfig {
+        latency: Duration::from_millis(100),
+        ..ChaosConfig::default()
+    };
+    let proxy = ChaosProxy::start(echo_server().await, config)
+        .await
+        .unwrap();
+    let mut stream = connect(&proxy).await;
+
+    let start = Instant::now();
+    stream.write_all(b"ping").await.unwrap();
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await.unwrap();
+
+    assert_eq!(&reply, b"ping");
+    assert!(
+        start.elapsed() >= Duration::from_millis(200),
+        "{:?}",
+        start.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn test_disconnects() {
+    let config = ChaosConfig {
+        disconnect_after: Some(Duration::from_millis(100)),
+        ..ChaosConfig::default()
+    };
+    let proxy = ChaosProxy::start(echo_server().await, config)
+        .await
+        .unwrap();
+
+    // Cut after the configured time
+    let mut stream = connect(&proxy).await;
+    let mut buf = [0; 1];
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("Connection was never cut");
+    assert!(matches!(read, Ok(0) | Err(_)), "{read:?}");
+
+    // Cut on demand
+    let proxy = ChaosProxy::start(echo_server().await, ChaosConfig::default())
+        .await
+        .unwrap();
+    let mut stream = connect(&proxy).await;
+    stream.write_all(b"x").await.unwrap();
+    stream.read_exact(&mut buf).await.unwrap();
+    proxy.disconnect_all();
+    let read = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf))
+        .await
+        .expect("Connection was never cut");
+    assert!(matches!(read, Ok(0) | Err(_)), "{read:?}");
+    assert_eq!(proxy.stats().disconnects, 1);
+}
+
+#[tokio::test]
+async fn test_stall_holds_traffic_until_resumed() {
+    let proxy = ChaosProxy::start(echo_server().await, ChaosConfig::default())
+        .await
+        .unwrap();
+    let mut stream = connect(&proxy).await;
+
+    proxy.stall();
+    stream.write_all(b"held").await.unwrap();
+    let mut reply = [0; 4];
+    let early =
+        tokio::time::timeout(Duration::from_millis(200), stream.read_exact(&mut reply)).await;
+    assert!(early.is_err(), "Traffic went through while stalled");
+
+    proxy.resume();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_exact(&mut reply))
+        .await
+        .expect("Traffic never resumed")
+        .unwrap();
+    assert_eq!(&reply, b"held");
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+async fn read_varint(stream: &mut TcpStream) -> u32 {
+    let mut value = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = stream.read_u8().await.expect("Failed to read a varint");
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    value
+}
+
+/// Frame a packet with its length
+fn frame(packet: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::new();
+    write_varint(&mut framed, packet.len() as u32);
+    framed.extend_from_slice(packet);
+    framed
+}
+
+#[tokio::test]
+async fn test_status_through_fragmenting_proxy() {
+    if !is_enabled() {
+        eprintln!("Skipping integration test (set MC_INTEGRATION_TESTS=1 to enable)");
+        return;
+    }
+
+    let binary = server_binary_path();
+    if !binary.exists() {
+        eprintln!(
+            "Server binary not found at {:?}, run `cargo build -p mc-server --release` first",
+            binary
+        );
+        return;
+    }
+
+    let server = ServerProcess::spawn(ServerConfig::default())
+        .await
+        .expect("Failed to start server");
+    let config = ChaosConfig {
+        latency: Duration::from_millis(20),
+        jitter: Duration::from_millis(30),
+        max_segment: Some(1),
+        ..ChaosConfig::default()
+    };
+    let upstream = ([127, 0, 0, 1], server.port()).into();
+    let proxy = ChaosProxy::start(upstream, config).await.unwrap();
+    let mut stream = connect(&proxy).await;
+
+    // Handshake (next state: status) and status request in one write, so
+    // the server gets them split into single bytes
+    let mut handshake = vec![0x00];
+    write_varint(&mut handshake, 0);
+    write_varint(&mut handshake, 9);
+    handshake.extend_from_slice(b"127.0.0.1");
+    handshake.extend_from_slice(&proxy.port().to_be_bytes());
+    write_varint(&mut handshake, 1);
+    let mut request = frame(&handshake);
+    request.extend(frame(&[0x00]));
+    stream.write_all(&request).await.unwrap();
+
+    let response = tokio::time::timeout(Duration::from_secs(10), async {
+        let len = read_varint(&mut stream).await;
+        let mut packet = vec![0; len as usize];
+        stream.read_exact(&mut packet).await.unwrap();
+        packet
+    })
+    .await
+    .expect("No status response through the proxy");
+
+    assert_eq!(response.first(), Some(&0x00), "Expected a status response");
+    eprintln!(
+        "Status response of {} bytes in {:?}",
+        response.len(),
+        proxy.stats()
+    );
+}
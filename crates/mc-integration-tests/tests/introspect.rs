@@ -0,0 +1,127 @@
+//! Tests for the introspect assertion DSL against a stand-in REST API.
+//!
+//! These don't need a server binary and always run.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use mc_integration_tests::{Introspect, Position};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serve `TestPlayer` falling from y=80 to y=64 over the first requests
+async fn falling_player() -> (Introspect, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(AtomicUsize::new(0));
+    let count = requests.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = vec![0; 1024];
+            let len = stream.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).to_string();
+            let n = count.fetch_add(1, Ordering::SeqCst);
+
+            let (status, body) = if request.starts_with("GET /entity/players/TestPlayer?") {
+                let y = [80.0, 76.0, 72.0].get(n).copied().unwrap_or(64.0);
+                let body = json!({
+                    "parent": "players",
+                    "name": "TestPlayer",
+                    "components": {
+                        "mc_server_lib.Position": {"x": 0.5, "y": y, "z": 0.5},
+                        "Name": {"value": "TestPlayer"},
+                        "Inventory": {"slots": [{"item": "stone", "count": 64}]},
+                    },
+                });
+                ("200 OK", body.to_string())
+            } else {
+                (
+                    "404 Not Found",
+                    json!({"error": "entity not found"}).to_string(),
+                )
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    let introspect = Introspect::new("127.0.0.1", port)
+        .with_timeout(Duration::from_secs(2))
+        .with_interval(Duration::from_millis(10));
+    (introspect, requests)
+}
+
+#[tokio::test]
+async fn test_field_near_retries_until_it_holds() {
+    let (introspect, requests) = falling_player().await;
+
+    let y = introspect
+        .expect_entity("players::TestPlayer")
+        .component::<Position>()
+        .field("y")
+        .near(64.0)
+        .await
+        .expect("Player never landed");
+
+    assert!((y - 64.0).abs() < f64::EPSILON);
+    assert!(requests.load(Ordering::SeqCst) > 3, "Should have retried");
+}
+
+#[tokio::test]
+async fn test_field_paths_and_equality() {
+    let (introspect, _) = falling_player().await;
+    let player = introspect.expect_entity("players::TestPlayer");
+
+    player
+        .clone()
+        .component_named("Name")
+        .field("value")
+        .equals("TestPlayer")
+        .await
+        .expect("Wrong name");
+    player
+        .clone()
+        .component_named("Inventory")
+        .field("slots.0.count")
+        .equals(64)
+        .await
+        .expect("Wrong item count");
+    player.exists().await.expect("Player should exist");
+    introspect
+        .expect_entity("players::Nobody")
+        .missing()
+        .await
+        .expect("Nobody should not exist");
+}
+
+#[tokio::test]
+async fn test_timeout_reports_last_observation() {
+    let (introspect, _) = falling_player().await;
+
+    let err = introspect
+        .expect_entity("players::TestPlayer")
+        .timeout(Duration::from_millis(200))
+        .component::<Position>()
+        .field("x")
+        .near(10.0)
+        .await
+        .expect_err("x never gets near 10");
+    let message = err.to_string();
+    assert!(message.contains("Position.x"), "{message}");
+    assert!(message.contains("last saw 0.5"), "{message}");
+
+    let err = introspect
+        .expect_entity("players::TestPlayer")
+        .timeout(Duration::from_millis(200))
+        .component_named("Health")
+        .exists()
+        .await
+        .expect_err("There is no Health component");
+    assert!(err.to_string().contains("no Health component"), "{err}");
+}
@@ -10,7 +10,7 @@
 
 use std::time::Duration;
 
-use mc_integration_tests::{IntegrationTest, TestConfig, TestEvent, is_enabled};
+use mc_integration_tests::{IntegrationTest, Position, TestConfig, TestEvent, is_enabled};
 
 fn fabric_client_available() -> bool {
     // These tests require Fabric client setup which is complex
@@ -61,6 +61,16 @@ async fn test_login_flow() {
 
     if let Some(pos) = &state.position {
         eprintln!("Player position: ({}, {}, {})", pos.x, pos.y, pos.z);
+
+        // The server agrees on where the player is
+        test.server()
+            .introspect()
+            .expect_entity("players::TestPlayer")
+            .component::<Position>()
+            .field("y")
+            .near(pos.y)
+            .await
+            .expect("Server has a different position");
     }
 
     // Disconnect
@@ -0,0 +1,214 @@
+//! Bit sets and packed long arrays, used by light masks, heightmaps and
+//! paletted containers.
+//!
+//! ```text
+//! BitSet:    VarInt long count, longs (bit i is bit i % 64 of long i / 64)
+//! LongArray: VarInt long count, longs
+//! ```
+//!
+//! Packed longs hold fixed-width entries, lowest bits first, without
+//! spanning long boundaries (see [`pack_longs`]).
+
+use std::io::{Read, Write};
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Decode, Encode, Result, read_varint, write_varint};
+
+/// A growable set of bits, sent as its longs like Java's `BitSet`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BitSet(pub Vec<u64>);
+
+impl BitSet {
+    /// An empty set.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// A set with every bit in `range` set.
+    #[must_use]
+    pub fn from_range(range: Range<usize>) -> Self {
+        range.collect()
+    }
+
+    /// Set bit `index`.
+    pub fn set(&mut self, index: usize) {
+        let long = index / 64;
+        if long >= self.0.len() {
+            self.0.resize(long + 1, 0);
+        }
+        self.0[long] |= 1 << (index % 64);
+    }
+
+    /// Clear bit `index`.
+    pub fn clear(&mut self, index: usize) {
+        if let Some(long) = self.0.get_mut(index / 64) {
+            *long &= !(1 << (index % 64));
+        }
+    }
+
+    /// Whether bit `index` is set.
+    #[must_use]
+    pub fn get(&self, index: usize) -> bool {
+        self.0
+            .get(index / 64)
+            .is_some_and(|long| long & (1 << (index % 64)) != 0)
+    }
+
+    /// Number of set bits.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|long| long.count_ones() as usize).sum()
+    }
+
+    /// Whether no bit is set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&long| long == 0)
+    }
+
+    /// The longs as sent, without trailing zero longs.
+    fn trimmed(&self) -> &[u64] {
+        let len = self
+            .0
+            .iter()
+            .rposition(|&long| long != 0)
+            .map_or(0, |i| i + 1);
+        &self.0[..len]
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(indices: I) -> Self {
+        let mut set = Self::new();
+        for index in indices {
+            set.set(index);
+        }
+        set
+    }
+}
+
+impl Encode for BitSet {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let longs = self.trimmed();
+        write_varint(writer, longs.len() as i32)?;
+        for long in longs {
+            writer.write_all(&long.to_be_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Decode<'_> for BitSet {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self(LongArray::decode(reader)?.0))
+    }
+}
+
+/// A length-prefixed array of longs, e.g. a heightmap.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct LongArray(pub Vec<u64>);
+
+impl Encode for LongArray {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_varint(writer, self.0.len() as i32)?;
+        for long in &self.0 {
+            writer.write_all(&long.to_be_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl Decode<'_> for LongArray {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = read_varint(reader)? as usize;
+        let mut longs = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            longs.push(u64::from_be_bytes(bytes));
+        }
+        Ok(Self(longs))
+    }
+}
+
+/// Pack values of `bits` bits into longs, as many as fit without spanning
+/// two longs, low bits first. Values are masked to `bits`.
+#[must_use]
+pub fn pack_longs(values: impl ExactSizeIterator<Item = u64>, bits: u8) -> Vec<u64> {
+    let bits = usize::from(bits);
+    let per_long = 64 / bits;
+    let mask = u64::MAX >> (64 - bits);
+    let mut longs = vec![0u64; values.len().div_ceil(per_long)];
+    for (index, value) in values.enumerate() {
+        longs[index / per_long] |= (value & mask) << ((index % per_long) * bits);
+    }
+    longs
+}
+
+/// The first `count` values of `bits` bits packed by [`pack_longs`].
+#[must_use]
+pub fn unpack_longs(longs: &[u64], bits: u8, count: usize) -> Vec<u64> {
+    let bits = usize::from(bits);
+    let per_long = 64 / bits;
+    let mask = u64::MAX >> (64 - bits);
+    (0..count)
+        .map(|index| {
+            longs
+                .get(index / per_long)
+                .map_or(0, |long| (long >> ((index % per_long) * bits)) & mask)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut set = BitSet::from_range(5..26);
+        assert!(set.get(5) && set.get(25));
+        assert!(!set.get(4) && !set.get(26) && !set.get(1000));
+        assert_eq!(set.count(), 21);
+
+        set.set(70);
+        assert_eq!(set.0.len(), 2);
+        set.clear(70);
+        assert!(!set.get(70));
+
+        // Trailing zero longs aren't sent
+        let mut buf = Vec::new();
+        set.encode(&mut buf).unwrap();
+        assert_eq!(buf[0], 1);
+        assert_eq!(u64::from_be_bytes(buf[1..].try_into().unwrap()), 0x3FF_FFE0);
+        assert_eq!(BitSet::decode(&mut buf.as_slice()).unwrap().0, [0x3FF_FFE0]);
+
+        let mut empty = Vec::new();
+        BitSet::new().encode(&mut empty).unwrap();
+        assert_eq!(empty, [0]);
+    }
+
+    #[test]
+    fn test_pack_longs() {
+        // 9 bits: 7 per long, so 256 values take 37 longs
+        let values: Vec<u64> = (0..256).collect();
+        let longs = pack_longs(values.iter().copied(), 9);
+        assert_eq!(longs.len(), 37);
+        assert_eq!(longs[0] & 0x1FF, 0);
+        assert_eq!((longs[0] >> 9) & 0x1FF, 1);
+        assert_eq!(longs[1] & 0x1FF, 7);
+        assert_eq!(unpack_longs(&longs, 9, 256), values);
+    }
+
+    #[test]
+    fn test_long_array_roundtrip() {
+        let array = LongArray(vec![1, u64::MAX]);
+        let mut buf = Vec::new();
+        array.encode(&mut buf).unwrap();
+        assert_eq!(buf.len(), 1 + 16);
+        assert_eq!(LongArray::decode(&mut buf.as_slice()).unwrap(), array);
+    }
+}
@@ -0,0 +1,162 @@
+//! Item stacks in the slot wire format (1.20.5+).
+//!
+//! ```text
+//! count: VarInt
+//! if count > 0:
+//!     item: VarInt
+//!     added: VarInt, removed: VarInt
+//!     added × (component type: VarInt, data)
+//!     removed × (component type: VarInt)
+//! ```
+//!
+//! Component payloads have a per-type format, so they're kept as encoded
+//! bytes. Decoding a stack with added components is therefore unsupported.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Decode, Encode, ProtocolError, Result, read_varint, write_varint};
+
+/// A data component: its `minecraft:data_component_type` registry ID and
+/// its encoded value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemComponent {
+    pub id: i32,
+    pub data: Vec<u8>,
+}
+
+/// A stack of items, as sent in inventory, container and entity metadata
+/// packets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemStack {
+    /// `minecraft:item` registry ID
+    pub item: i32,
+    /// Number of items; zero or less is an empty slot
+    pub count: i32,
+    /// Components set on top of the item's defaults
+    pub added: Vec<ItemComponent>,
+    /// Default components removed from this stack
+    pub removed: Vec<i32>,
+}
+
+impl ItemStack {
+    /// An empty slot.
+    pub const EMPTY: Self = Self {
+        item: 0,
+        count: 0,
+        added: Vec::new(),
+        removed: Vec::new(),
+    };
+
+    #[must_use]
+    pub const fn new(item: i32, count: i32) -> Self {
+        Self {
+            item,
+            count,
+            added: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.count <= 0
+    }
+
+    /// Add a component from its registry ID and encoded value.
+    #[must_use]
+    pub fn with_component(mut self, id: i32, data: Vec<u8>) -> Self {
+        self.added.push(ItemComponent { id, data });
+        self
+    }
+
+    /// Remove one of the item's default components.
+    #[must_use]
+    pub fn without_component(mut self, id: i32) -> Self {
+        self.removed.push(id);
+        self
+    }
+}
+
+impl Encode for ItemStack {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.is_empty() {
+            return write_varint(writer, 0);
+        }
+        write_varint(writer, self.count)?;
+        write_varint(writer, self.item)?;
+        write_varint(writer, self.added.len() as i32)?;
+        write_varint(writer, self.removed.len() as i32)?;
+        for component in &self.added {
+            write_varint(writer, component.id)?;
+            writer.write_all(&component.data)?;
+        }
+        for &id in &self.removed {
+            write_varint(writer, id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decode<'_> for ItemStack {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        let count = read_varint(reader)?;
+        if count <= 0 {
+            return Ok(Self::EMPTY);
+        }
+        let item = read_varint(reader)?;
+        let added = read_varint(reader)?;
+        let removed = read_varint(reader)?;
+        if added > 0 {
+            // The payload length depends on the component type
+            return Err(ProtocolError::UnsupportedComponent(read_varint(reader)?));
+        }
+        let removed = (0..removed)
+            .map(|_| read_varint(reader))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            item,
+            count,
+            added: Vec::new(),
+            removed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(stack: &ItemStack) -> ItemStack {
+        let mut buf = Vec::new();
+        stack.encode(&mut buf).unwrap();
+        ItemStack::decode(&mut buf.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_empty_slot() {
+        let mut buf = Vec::new();
+        ItemStack::new(1, 0).encode(&mut buf).unwrap();
+        assert_eq!(buf, [0]);
+        assert_eq!(round_trip(&ItemStack::new(1, 0)), ItemStack::EMPTY);
+    }
+
+    #[test]
+    fn test_round_trip_with_removed_components() {
+        let stack = ItemStack::new(812, 64).without_component(3);
+        assert_eq!(round_trip(&stack), stack);
+    }
+
+    #[test]
+    fn test_encode_added_component() {
+        let stack = ItemStack::new(1, 1).with_component(2, vec![5]);
+        let mut buf = Vec::new();
+        stack.encode(&mut buf).unwrap();
+        assert_eq!(buf, [1, 1, 1, 0, 2, 5]);
+        assert!(matches!(
+            ItemStack::decode(&mut buf.as_slice()),
+            Err(ProtocolError::UnsupportedComponent(2))
+        ));
+    }
+}
@@ -5,7 +5,18 @@ use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+mod bits;
+mod item;
+mod movement;
 pub mod nbt;
+mod palette;
+
+pub use bits::{BitSet, LongArray, pack_longs, unpack_longs};
+pub use item::{ItemComponent, ItemStack};
+pub use movement::{Angle, FixedDelta};
+pub use palette::{
+    PaletteFormat, PaletteMode, index_bits, palettize, read_paletted, write_paletted,
+};
 
 #[cfg(feature = "derive")]
 pub use mc_protocol_derive::{Decode, Encode};
@@ -25,6 +36,14 @@ pub enum ProtocolError {
     InvalidEnumVariant(i32),
     #[error("UTF-8 error: {0}")]
     Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Cannot decode data component type {0}")]
+    UnsupportedComponent(i32),
+    #[error("Invalid palette length {0}")]
+    PaletteLength(i32),
+    #[error("Palette index {index} out of range for {len} entries")]
+    PaletteIndex { index: u64, len: usize },
+    #[error("NBT error: {0}")]
+    Nbt(#[from] nbt::NbtError),
 }
 
 pub type Result<T> = std::result::Result<T, ProtocolError>;
@@ -211,6 +230,19 @@ impl Decode<'_> for i32 {
     }
 }
 
+impl Encode for u32 {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<BigEndian>(*self)?;
+        Ok(())
+    }
+}
+
+impl Decode<'_> for u32 {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(reader.read_u32::<BigEndian>()?)
+    }
+}
+
 impl Encode for i64 {
     fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
         writer.write_i64::<BigEndian>(*self)?;
@@ -224,6 +256,32 @@ impl Decode<'_> for i64 {
     }
 }
 
+impl Encode for u64 {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u64::<BigEndian>(*self)?;
+        Ok(())
+    }
+}
+
+impl Decode<'_> for u64 {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(reader.read_u64::<BigEndian>()?)
+    }
+}
+
+impl Encode for i128 {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_i128::<BigEndian>(*self)?;
+        Ok(())
+    }
+}
+
+impl Decode<'_> for i128 {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(reader.read_i128::<BigEndian>()?)
+    }
+}
+
 impl Encode for f32 {
     fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
         writer.write_f32::<BigEndian>(*self)?;
@@ -398,7 +456,7 @@ pub struct Position {
     pub z: i32,
 }
 
-// NBT placeholder (raw bytes for now)
+// Network NBT, kept as its encoded bytes; see `nbt` for the document model
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Nbt(pub Vec<u8>);
 
@@ -414,10 +472,12 @@ impl Encode for Nbt {
 }
 
 impl Decode<'_> for Nbt {
-    fn decode<R: Read>(_reader: &mut R) -> Result<Self> {
-        // NBT decoding is complex - for now just return empty
-        // TODO: implement proper NBT parsing
-        Ok(Nbt(Vec::new()))
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        // Parse to find where the tag ends, then keep it re-encoded
+        Ok(match nbt::NbtValue::read_network(reader)? {
+            Some(value) => Nbt(value.to_network_bytes()),
+            None => Nbt(vec![0]),
+        })
     }
 }
 
@@ -0,0 +1,121 @@
+//! Entity movement types: rotations packed into a byte and fixed-point
+//! position deltas.
+//!
+//! ```text
+//! Angle:      u8, 256 steps per full turn
+//! FixedDelta: i16, (new * 4096 - old * 4096) per axis
+//! ```
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Decode, Encode, Result};
+
+/// A rotation in 1/256ths of a full turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    /// The nearest angle to `degrees`, wrapping around a full turn.
+    #[must_use]
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self((degrees.rem_euclid(360.0) * 256.0 / 360.0).round() as u32 as u8)
+    }
+
+    /// Degrees in `0.0..360.0`.
+    #[must_use]
+    pub fn to_degrees(self) -> f32 {
+        f32::from(self.0) * 360.0 / 256.0
+    }
+}
+
+impl Encode for Angle {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.encode(writer)
+    }
+}
+
+impl Decode<'_> for Angle {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self(u8::decode(reader)?))
+    }
+}
+
+/// A position change along one axis, in 1/4096ths of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FixedDelta(pub i16);
+
+impl FixedDelta {
+    /// Steps per block.
+    pub const SCALE: f64 = 4096.0;
+
+    /// The delta from `old` to `new`, or `None` if it's too far (8 blocks or
+    /// more) and the entity has to be teleported instead.
+    #[must_use]
+    pub fn between(old: f64, new: f64) -> Option<Self> {
+        let delta = (new * Self::SCALE).round() - (old * Self::SCALE).round();
+        (f64::from(i16::MIN)..=f64::from(i16::MAX))
+            .contains(&delta)
+            .then_some(Self(delta as i16))
+    }
+
+    /// The change in blocks.
+    #[must_use]
+    pub fn to_blocks(self) -> f64 {
+        f64::from(self.0) / Self::SCALE
+    }
+}
+
+impl Encode for FixedDelta {
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.encode(writer)
+    }
+}
+
+impl Decode<'_> for FixedDelta {
+    fn decode<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self(i16::decode(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angle_degrees() {
+        assert_eq!(Angle::from_degrees(0.0), Angle(0));
+        assert_eq!(Angle::from_degrees(90.0), Angle(64));
+        assert_eq!(Angle::from_degrees(-90.0), Angle(192));
+        assert_eq!(Angle::from_degrees(359.9), Angle(0));
+        assert_eq!(Angle::from_degrees(720.0 + 180.0), Angle(128));
+        assert!((Angle(64).to_degrees() - 90.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_fixed_delta() {
+        assert_eq!(FixedDelta::between(10.0, 10.5), Some(FixedDelta(2048)));
+        assert_eq!(FixedDelta::between(10.5, 10.0), Some(FixedDelta(-2048)));
+        assert_eq!(FixedDelta::between(0.0, 8.0), None);
+        assert!((FixedDelta(-2048).to_blocks() + 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut buf = Vec::new();
+        Angle(200).encode(&mut buf).unwrap();
+        FixedDelta(-3).encode(&mut buf).unwrap();
+        u32::MAX.encode(&mut buf).unwrap();
+        u64::MAX.encode(&mut buf).unwrap();
+        i128::MIN.encode(&mut buf).unwrap();
+        assert_eq!(buf.len(), 1 + 2 + 4 + 8 + 16);
+
+        let mut reader = buf.as_slice();
+        assert_eq!(Angle::decode(&mut reader).unwrap(), Angle(200));
+        assert_eq!(FixedDelta::decode(&mut reader).unwrap(), FixedDelta(-3));
+        assert_eq!(u32::decode(&mut reader).unwrap(), u32::MAX);
+        assert_eq!(u64::decode(&mut reader).unwrap(), u64::MAX);
+        assert_eq!(i128::decode(&mut reader).unwrap(), i128::MIN);
+    }
+}
@@ -1,9 +1,52 @@
-//! NBT (Named Binary Tag) serialization for Minecraft protocol.
+//! NBT (Named Binary Tag) document model for the Minecraft protocol.
 //!
-//! This module provides a minimal NBT implementation focused on network NBT,
-//! which uses nameless root compounds.
+//! [`NbtCompound`], [`NbtValue`] and [`NbtList`] hold a whole document; the
+//! [`nbt!`](crate::nbt!) macro builds one. Documents are read and written in
+//! three forms:
+//! - network NBT, whose root tag has no name ([`NbtCompound::to_network_bytes`],
+//!   [`NbtCompound::from_network_bytes`]); since 1.20.3 the root may be any
+//!   tag, see [`NbtValue::read_network`]
+//! - file NBT (region and level files), whose root compound is named
+//!   ([`NbtCompound::to_file_bytes`], [`NbtCompound::from_file_bytes`]), also
+//!   gzipped as in `level.dat` and player data
+//!   ([`NbtCompound::to_gzip_bytes`], [`NbtCompound::from_gzip_bytes`])
+//! - SNBT, the text form used in commands: values print as SNBT with
+//!   `Display` and parse with `FromStr`
+//!
+//! Strings are written as Java's modified UTF-8.
+
+mod read;
+mod snbt;
+
+use std::io::Read as _;
 
 use byteorder::{BigEndian, WriteBytesExt};
+use flate2::Compression;
+use flate2::read::{GzDecoder, GzEncoder, ZlibDecoder};
+use thiserror::Error;
+
+pub use read::MAX_DEPTH;
+
+/// Error reading NBT
+#[derive(Error, Debug)]
+pub enum NbtError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid tag type {0}")]
+    InvalidTag(u8),
+    #[error("Root tag is type {0}, not a compound")]
+    RootNotCompound(u8),
+    #[error("Negative length {0}")]
+    NegativeLength(i32),
+    #[error("Invalid modified UTF-8 string")]
+    InvalidString,
+    #[error("NBT nested deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+    #[error("{0} bytes after the root tag")]
+    TrailingBytes(usize),
+    #[error("Invalid SNBT at {position}: {message}")]
+    Snbt { position: usize, message: String },
+}
 
 /// NBT tag type IDs
 mod tag_type {
@@ -72,15 +115,63 @@ impl NbtCompound {
         }
     }
 
-    /// Insert a value into the compound
+    /// Insert a value into the compound, replacing (in place) any value
+    /// with the same key
     pub fn insert(&mut self, key: impl Into<String>, value: impl Into<NbtValue>) {
-        self.entries.push((key.into(), value.into()));
+        let key = key.into();
+        let value = value.into();
+        match self.get_mut(&key) {
+            Some(existing) => *existing = value,
+            None => self.entries.push((key, value)),
+        }
     }
 
-    /// Build a compound from entries
+    /// Build a compound from entries; later duplicates replace earlier ones
     #[must_use]
     pub fn from_entries(entries: Vec<(String, NbtValue)>) -> Self {
-        Self { entries }
+        let mut compound = Self::new();
+        for (key, value) in entries {
+            compound.insert(key, value);
+        }
+        compound
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&NbtValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut NbtValue> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Remove a value, keeping the order of the rest
+    pub fn remove(&mut self, key: &str) -> Option<NbtValue> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NbtValue)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
     }
 
     /// Serialize to network NBT format (type byte + content, no name)
@@ -94,6 +185,73 @@ impl NbtCompound {
         buf
     }
 
+    /// Serialize to file NBT format (type byte + root name + content)
+    #[must_use]
+    pub fn to_file_bytes(&self, name: &str) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.push(tag_type::COMPOUND);
+        write_nbt_string(&mut buf, name);
+        self.write_content(&mut buf);
+        buf
+    }
+
+    /// Parse network NBT whose root is a compound
+    ///
+    /// # Errors
+    /// Fails on malformed NBT, a root of another type or trailing bytes.
+    pub fn from_network_bytes(bytes: &[u8]) -> Result<Self, NbtError> {
+        let mut reader = bytes;
+        let compound = read::read_root(&mut reader, false)?.1;
+        if !reader.is_empty() {
+            return Err(NbtError::TrailingBytes(reader.len()));
+        }
+        Ok(compound)
+    }
+
+    /// Parse file NBT, returning the root's name and the root compound
+    ///
+    /// # Errors
+    /// Fails on malformed NBT, a root of another type or trailing bytes.
+    pub fn from_file_bytes(bytes: &[u8]) -> Result<(String, Self), NbtError> {
+        let mut reader = bytes;
+        let root = read::read_root(&mut reader, true)?;
+        if !reader.is_empty() {
+            return Err(NbtError::TrailingBytes(reader.len()));
+        }
+        Ok(root)
+    }
+
+    /// Serialize to gzipped file NBT, the format of `level.dat`
+    #[must_use]
+    pub fn to_gzip_bytes(&self, name: &str) -> Vec<u8> {
+        let file = self.to_file_bytes(name);
+        let mut out = Vec::new();
+        // Compressing from a slice into a Vec can't fail
+        let _ = GzEncoder::new(file.as_slice(), Compression::default()).read_to_end(&mut out);
+        out
+    }
+
+    /// Parse gzipped file NBT, returning the root's name and the root compound
+    ///
+    /// # Errors
+    /// Fails if the data isn't gzip or doesn't hold valid file NBT.
+    pub fn from_gzip_bytes(bytes: &[u8]) -> Result<(String, Self), NbtError> {
+        let mut data = Vec::new();
+        GzDecoder::new(bytes).read_to_end(&mut data)?;
+        Self::from_file_bytes(&data)
+    }
+
+    /// Parse zlib-compressed file NBT, the format region files store chunks
+    /// in by default, returning the root's name and the root compound
+    ///
+    /// # Errors
+    /// Fails if the data isn't zlib or doesn't hold valid file NBT.
+    pub fn from_zlib_bytes(bytes: &[u8]) -> Result<(String, Self), NbtError> {
+        let mut data = Vec::new();
+        ZlibDecoder::new(bytes).read_to_end(&mut data)?;
+        Self::from_file_bytes(&data)
+    }
+
     /// Write compound content (entries + end tag)
     fn write_content(&self, buf: &mut Vec<u8>) {
         for (name, value) in &self.entries {
@@ -104,8 +262,106 @@ impl NbtCompound {
 }
 
 impl NbtValue {
+    /// Serialize to network NBT with this value as the root tag
+    #[must_use]
+    pub fn to_network_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![self.type_id()];
+        self.write_content(&mut buf);
+        buf
+    }
+
+    /// Read network NBT with a root of any type, `None` for an empty
+    /// (`TAG_End`) root
+    ///
+    /// # Errors
+    /// Fails on malformed NBT or if the reader fails.
+    pub fn read_network(reader: &mut impl std::io::Read) -> Result<Option<Self>, NbtError> {
+        read::read_network_value(reader)
+    }
+
+    #[must_use]
+    pub const fn as_byte(&self) -> Option<i8> {
+        match self {
+            Self::Byte(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_short(&self) -> Option<i16> {
+        match self {
+            Self::Short(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_int(&self) -> Option<i32> {
+        match self {
+            Self::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_long(&self) -> Option<i64> {
+        match self {
+            Self::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_float(&self) -> Option<f32> {
+        match self {
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_double(&self) -> Option<f64> {
+        match self {
+            Self::Double(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// A byte as a boolean, the way NBT stores booleans
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Byte(v) => Some(*v != 0),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_list(&self) -> Option<&NbtList> {
+        match self {
+            Self::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            Self::Compound(v) => Some(v),
+            _ => None,
+        }
+    }
+
     /// Get the type ID for this value
-    fn type_id(&self) -> u8 {
+    const fn type_id(&self) -> u8 {
         match self {
             Self::Byte(_) => tag_type::BYTE,
             Self::Short(_) => tag_type::SHORT,
@@ -164,8 +420,68 @@ impl NbtValue {
 }
 
 impl NbtList {
+    /// Build a list from values, `None` if they aren't all of one type
+    #[must_use]
+    pub fn from_values(values: Vec<NbtValue>) -> Option<Self> {
+        macro_rules! collect {
+            ($variant:ident) => {
+                values
+                    .into_iter()
+                    .map(|v| match v {
+                        NbtValue::$variant(x) => Some(x),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(Self::$variant)
+            };
+        }
+        match values.first() {
+            None => Some(Self::Empty),
+            Some(NbtValue::Byte(_)) => collect!(Byte),
+            Some(NbtValue::Short(_)) => collect!(Short),
+            Some(NbtValue::Int(_)) => collect!(Int),
+            Some(NbtValue::Long(_)) => collect!(Long),
+            Some(NbtValue::Float(_)) => collect!(Float),
+            Some(NbtValue::Double(_)) => collect!(Double),
+            Some(NbtValue::ByteArray(_)) => collect!(ByteArray),
+            Some(NbtValue::String(_)) => collect!(String),
+            Some(NbtValue::List(_)) => collect!(List),
+            Some(NbtValue::Compound(_)) => collect!(Compound),
+            Some(NbtValue::IntArray(_)) => collect!(IntArray),
+            Some(NbtValue::LongArray(_)) => collect!(LongArray),
+        }
+    }
+
+    /// The elements as values
+    #[must_use]
+    pub fn to_values(&self) -> Vec<NbtValue> {
+        fn map<T: Clone>(v: &[T], f: fn(T) -> NbtValue) -> Vec<NbtValue> {
+            v.iter().cloned().map(f).collect()
+        }
+        match self {
+            Self::Empty => Vec::new(),
+            Self::Byte(v) => map(v, NbtValue::Byte),
+            Self::Short(v) => map(v, NbtValue::Short),
+            Self::Int(v) => map(v, NbtValue::Int),
+            Self::Long(v) => map(v, NbtValue::Long),
+            Self::Float(v) => map(v, NbtValue::Float),
+            Self::Double(v) => map(v, NbtValue::Double),
+            Self::ByteArray(v) => map(v, NbtValue::ByteArray),
+            Self::String(v) => map(v, NbtValue::String),
+            Self::List(v) => map(v, NbtValue::List),
+            Self::Compound(v) => map(v, NbtValue::Compound),
+            Self::IntArray(v) => map(v, NbtValue::IntArray),
+            Self::LongArray(v) => map(v, NbtValue::LongArray),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Get the element type ID
-    fn element_type_id(&self) -> u8 {
+    const fn element_type_id(&self) -> u8 {
         match self {
             Self::Empty => tag_type::END,
             Self::Byte(_) => tag_type::BYTE,
@@ -184,7 +500,8 @@ impl NbtList {
     }
 
     /// Get the length
-    fn len(&self) -> usize {
+    #[must_use]
+    pub fn len(&self) -> usize {
         match self {
             Self::Empty => 0,
             Self::Byte(v) => v.len(),
@@ -284,10 +601,36 @@ impl NbtList {
 
 /// Write an NBT string (u16 length + modified UTF-8)
 fn write_nbt_string(buf: &mut Vec<u8>, s: &str) {
-    // NBT uses modified UTF-8 with u16 length prefix
-    let bytes = s.as_bytes();
+    let bytes = to_modified_utf8(s);
     buf.write_u16::<BigEndian>(bytes.len() as u16).unwrap();
-    buf.extend_from_slice(bytes);
+    buf.extend_from_slice(&bytes);
+}
+
+/// Encode as Java's modified UTF-8: NUL is two bytes and characters outside
+/// the BMP are surrogate pairs of three bytes each. Anything else is the
+/// same as UTF-8.
+fn to_modified_utf8(s: &str) -> std::borrow::Cow<'_, [u8]> {
+    if !s.bytes().any(|b| b == 0 || b >= 0xf0) {
+        return s.as_bytes().into();
+    }
+    let mut out = Vec::with_capacity(s.len() + 2);
+    for c in s.chars() {
+        if c == '\0' {
+            out.extend_from_slice(&[0xc0, 0x80]);
+        } else if c.len_utf8() == 4 {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                let unit = *unit;
+                out.extend_from_slice(&[
+                    0xe0 | (unit >> 12) as u8,
+                    0x80 | ((unit >> 6) & 0x3f) as u8,
+                    0x80 | (unit & 0x3f) as u8,
+                ]);
+            }
+        } else {
+            out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+    }
+    out.into()
 }
 
 // Convenient From implementations
@@ -357,11 +700,94 @@ impl From<NbtList> for NbtValue {
     }
 }
 
+// Bytes, ints and longs have array tags, which vectors of them become; lists
+// of them are built with `NbtList` (`NbtList::Int(vec![..])` or
+// `NbtList::from`)
+impl From<Vec<i8>> for NbtValue {
+    fn from(v: Vec<i8>) -> Self {
+        Self::ByteArray(v)
+    }
+}
+
+impl From<Vec<i32>> for NbtValue {
+    fn from(v: Vec<i32>) -> Self {
+        Self::IntArray(v)
+    }
+}
+
+impl From<Vec<i64>> for NbtValue {
+    fn from(v: Vec<i64>) -> Self {
+        Self::LongArray(v)
+    }
+}
+
+/// Vectors of other types become lists
+macro_rules! list_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<Vec<$ty>> for NbtList {
+                fn from(v: Vec<$ty>) -> Self {
+                    Self::$variant(v)
+                }
+            }
+        )*
+    };
+}
+
+list_from! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    NbtList => List,
+    NbtCompound => Compound,
+}
+
+macro_rules! value_from_list {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl From<Vec<$ty>> for NbtValue {
+                fn from(v: Vec<$ty>) -> Self {
+                    Self::List(v.into())
+                }
+            }
+        )*
+    };
+}
+
+value_from_list!(i16, f32, f64, String, NbtList, NbtCompound);
+
+impl From<Vec<&str>> for NbtValue {
+    fn from(v: Vec<&str>) -> Self {
+        Self::List(NbtList::String(v.into_iter().map(str::to_string).collect()))
+    }
+}
+
+/// Arrays convert like vectors, so `[1, 2]` is an int array and
+/// `[nbt! {}, nbt! {}]` a list of compounds
+impl<T, const N: usize> From<[T; N]> for NbtValue
+where
+    Vec<T>: Into<Self>,
+{
+    fn from(v: [T; N]) -> Self {
+        Vec::from(v).into()
+    }
+}
+
 /// Macro for building NBT compounds ergonomically
 ///
+/// Values are anything convertible into [`NbtValue`](crate::nbt::NbtValue).
+/// A value in braces is a nested compound, and `[B; ..]`, `[I; ..]` and
+/// `[L; ..]` are byte, int and long arrays as in SNBT. Other lists are
+/// vectors or arrays of their elements, or an [`NbtList`](crate::nbt::NbtList).
+///
 /// # Example
 /// ```
 /// use mc_protocol::nbt;
+/// use mc_protocol::nbt::{NbtList, NbtValue};
 ///
 /// let compound = nbt! {
 ///     "byte" => 1i8,
@@ -370,7 +796,12 @@ impl From<NbtList> for NbtValue {
 ///     "nested" => nbt! {
 ///         "inner" => true,
 ///     },
+///     "braced" => { "inner" => 1.5f32 },
+///     "heights" => [L; 1, 2, 3],
+///     "pos" => NbtList::Double(vec![0.5, 64.0, 0.5]),
+///     "items" => [nbt! { "id" => "minecraft:stone" }],
 /// };
+/// assert_eq!(compound.get("heights"), Some(&NbtValue::LongArray(vec![1, 2, 3])));
 /// ```
 #[macro_export]
 macro_rules! nbt {
@@ -380,15 +811,45 @@ macro_rules! nbt {
     };
 
     // Compound with entries
-    ($($key:expr => $value:expr),* $(,)?) => {{
+    ($($body:tt)+) => {{
         let mut compound = $crate::nbt::NbtCompound::new();
-        $(
-            compound.insert($key, $value);
-        )*
+        $crate::__nbt_entries!(compound; $($body)+);
         compound
     }};
 }
 
+/// Entries of [`nbt!`], one at a time
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __nbt_entries {
+    ($compound:ident;) => {};
+
+    ($compound:ident; $key:expr => { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $compound.insert($key, $crate::nbt!($($inner)*));
+        $crate::__nbt_entries!($compound; $($($rest)*)?);
+    };
+
+    ($compound:ident; $key:expr => [B; $($v:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $compound.insert($key, $crate::nbt::NbtValue::ByteArray(::std::vec![$($v),*]));
+        $crate::__nbt_entries!($compound; $($($rest)*)?);
+    };
+
+    ($compound:ident; $key:expr => [I; $($v:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $compound.insert($key, $crate::nbt::NbtValue::IntArray(::std::vec![$($v),*]));
+        $crate::__nbt_entries!($compound; $($($rest)*)?);
+    };
+
+    ($compound:ident; $key:expr => [L; $($v:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $compound.insert($key, $crate::nbt::NbtValue::LongArray(::std::vec![$($v),*]));
+        $crate::__nbt_entries!($compound; $($($rest)*)?);
+    };
+
+    ($compound:ident; $key:expr => $value:expr $(, $($rest:tt)*)?) => {
+        $compound.insert($key, $value);
+        $crate::__nbt_entries!($compound; $($($rest)*)?);
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,4 +892,119 @@ mod tests {
         // Should be 1 for true
         assert!(bytes.contains(&1));
     }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut compound = nbt! { "a" => 1i32, "b" => 2i32 };
+        compound.insert("a", "one");
+        assert_eq!(compound.len(), 2);
+        assert_eq!(compound.iter().next(), Some(("a", &NbtValue::from("one"))));
+        assert_eq!(compound.remove("a"), Some(NbtValue::from("one")));
+        assert!(!compound.contains_key("a"));
+    }
+
+    #[test]
+    fn test_macro_arrays_and_nesting() {
+        let compound = nbt! {
+            "bytes" => [B; 1, -1],
+            "ints" => [I; 1, 2, 3],
+            "longs" => [L; i64::MAX],
+            "list" => NbtList::Int(vec![1, 2]),
+            "nested" => { "deeper" => { "flag" => true } },
+        };
+        assert_eq!(
+            compound.get("bytes"),
+            Some(&NbtValue::ByteArray(vec![1, -1]))
+        );
+        assert_eq!(
+            compound.get("ints"),
+            Some(&NbtValue::IntArray(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            compound.get("longs"),
+            Some(&NbtValue::LongArray(vec![i64::MAX]))
+        );
+        assert_eq!(
+            compound
+                .get("list")
+                .and_then(NbtValue::as_list)
+                .map(NbtList::len),
+            Some(2)
+        );
+        let flag = compound
+            .get("nested")
+            .and_then(NbtValue::as_compound)
+            .and_then(|c| c.get("deeper"))
+            .and_then(NbtValue::as_compound)
+            .and_then(|c| c.get("flag"))
+            .and_then(NbtValue::as_bool);
+        assert_eq!(flag, Some(true));
+    }
+
+    fn every_type() -> NbtCompound {
+        nbt! {
+            "byte" => 1i8,
+            "short" => -2i16,
+            "int" => 3i32,
+            "long" => -4i64,
+            "float" => 0.5f32,
+            "double" => -0.25f64,
+            "bytes" => [B; 1, 2],
+            "string" => "nul\0 and \u{1f600}",
+            "ints" => [I; -1],
+            "longs" => [L;],
+            "empty" => NbtList::Empty,
+            "lists" => [NbtList::Short(vec![1]), NbtList::Empty],
+            "arrays" => NbtList::LongArray(vec![vec![1], vec![]]),
+            "compounds" => [nbt! { "a" => 1i8 }, nbt! {}],
+            "nested" => { "deeper" => {} },
+        }
+    }
+
+    #[test]
+    fn test_network_round_trip() {
+        let compound = every_type();
+        let bytes = compound.to_network_bytes();
+        assert_eq!(NbtCompound::from_network_bytes(&bytes).unwrap(), compound);
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            NbtCompound::from_network_bytes(&trailing),
+            Err(NbtError::TrailingBytes(1))
+        ));
+        assert!(NbtCompound::from_network_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // Any root type, as in text components
+        let value = NbtValue::from("plain");
+        let decoded = NbtValue::read_network(&mut value.to_network_bytes().as_slice()).unwrap();
+        assert_eq!(decoded, Some(value));
+        assert_eq!(NbtValue::read_network(&mut [0u8].as_slice()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_file_and_gzip_round_trip() {
+        let compound = every_type();
+        let (name, parsed) = NbtCompound::from_file_bytes(&compound.to_file_bytes("Data")).unwrap();
+        assert_eq!((name.as_str(), &parsed), ("Data", &compound));
+
+        let gzipped = compound.to_gzip_bytes("");
+        assert_eq!(gzipped[..2], [0x1f, 0x8b]);
+        let (name, parsed) = NbtCompound::from_gzip_bytes(&gzipped).unwrap();
+        assert_eq!((name.as_str(), &parsed), ("", &compound));
+        assert!(NbtCompound::from_gzip_bytes(&compound.to_file_bytes("")).is_err());
+    }
+
+    #[test]
+    fn test_file_root_is_named() {
+        let compound = nbt! {
+            "int" => 42i32,
+        };
+
+        let network = compound.to_network_bytes();
+        let file = compound.to_file_bytes("");
+        // Same content, with an empty root name after the type byte
+        assert_eq!(file[..3], [tag_type::COMPOUND, 0, 0]);
+        assert_eq!(file[3..], network[1..]);
+    }
 }
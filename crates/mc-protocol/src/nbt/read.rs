@@ -0,0 +1,250 @@
+//! Reading binary NBT.
+//!
+//! Lengths come from the peer (or a possibly corrupt file), so nothing is
+//! pre-allocated past a small cap and nesting is limited to [`MAX_DEPTH`].
+
+use std::io::Read;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use super::{NbtCompound, NbtError, NbtList, NbtValue, tag_type};
+
+/// Deepest nesting of compounds and lists accepted, as in vanilla.
+pub const MAX_DEPTH: usize = 512;
+
+/// Most elements reserved up front for a length read from the input.
+const MAX_PREALLOCATE: usize = 1024;
+
+/// Read a root compound, with a name if `named` (file NBT)
+pub fn read_root(reader: &mut impl Read, named: bool) -> Result<(String, NbtCompound), NbtError> {
+    let ty = reader.read_u8()?;
+    if ty != tag_type::COMPOUND {
+        return Err(NbtError::RootNotCompound(ty));
+    }
+    let name = if named {
+        read_string(reader)?
+    } else {
+        String::new()
+    };
+    Ok((name, read_compound(reader, 0)?))
+}
+
+/// Read a nameless root of any type; `None` for `TAG_End`
+pub fn read_network_value(reader: &mut impl Read) -> Result<Option<NbtValue>, NbtError> {
+    match reader.read_u8()? {
+        tag_type::END => Ok(None),
+        ty => read_value(reader, ty, 0).map(Some),
+    }
+}
+
+fn read_value(reader: &mut impl Read, ty: u8, depth: usize) -> Result<NbtValue, NbtError> {
+    Ok(match ty {
+        tag_type::BYTE => NbtValue::Byte(reader.read_i8()?),
+        tag_type::SHORT => NbtValue::Short(reader.read_i16::<BigEndian>()?),
+        tag_type::INT => NbtValue::Int(reader.read_i32::<BigEndian>()?),
+        tag_type::LONG => NbtValue::Long(reader.read_i64::<BigEndian>()?),
+        tag_type::FLOAT => NbtValue::Float(reader.read_f32::<BigEndian>()?),
+        tag_type::DOUBLE => NbtValue::Double(reader.read_f64::<BigEndian>()?),
+        tag_type::BYTE_ARRAY => NbtValue::ByteArray(read_array(reader, |r| r.read_i8())?),
+        tag_type::STRING => NbtValue::String(read_string(reader)?),
+        tag_type::LIST => NbtValue::List(read_list(reader, depth + 1)?),
+        tag_type::COMPOUND => NbtValue::Compound(read_compound(reader, depth + 1)?),
+        tag_type::INT_ARRAY => {
+            NbtValue::IntArray(read_array(reader, ReadBytesExt::read_i32::<BigEndian>)?)
+        }
+        tag_type::LONG_ARRAY => {
+            NbtValue::LongArray(read_array(reader, ReadBytesExt::read_i64::<BigEndian>)?)
+        }
+        ty => return Err(NbtError::InvalidTag(ty)),
+    })
+}
+
+fn read_compound(reader: &mut impl Read, depth: usize) -> Result<NbtCompound, NbtError> {
+    if depth > MAX_DEPTH {
+        return Err(NbtError::TooDeep);
+    }
+    let mut compound = NbtCompound::new();
+    loop {
+        let ty = reader.read_u8()?;
+        if ty == tag_type::END {
+            return Ok(compound);
+        }
+        let name = read_string(reader)?;
+        let value = read_value(reader, ty, depth)?;
+        compound.insert(name, value);
+    }
+}
+
+fn read_list(reader: &mut impl Read, depth: usize) -> Result<NbtList, NbtError> {
+    if depth > MAX_DEPTH {
+        return Err(NbtError::TooDeep);
+    }
+    let ty = reader.read_u8()?;
+    let len = read_len(reader)?;
+    if len == 0 {
+        return Ok(NbtList::Empty);
+    }
+
+    Ok(match ty {
+        tag_type::BYTE => NbtList::Byte(elements(reader, len, |r| Ok(r.read_i8()?))?),
+        tag_type::SHORT => {
+            NbtList::Short(elements(reader, len, |r| Ok(r.read_i16::<BigEndian>()?))?)
+        }
+        tag_type::INT => NbtList::Int(elements(reader, len, |r| Ok(r.read_i32::<BigEndian>()?))?),
+        tag_type::LONG => NbtList::Long(elements(reader, len, |r| Ok(r.read_i64::<BigEndian>()?))?),
+        tag_type::FLOAT => {
+            NbtList::Float(elements(reader, len, |r| Ok(r.read_f32::<BigEndian>()?))?)
+        }
+        tag_type::DOUBLE => {
+            NbtList::Double(elements(reader, len, |r| Ok(r.read_f64::<BigEndian>()?))?)
+        }
+        tag_type::BYTE_ARRAY => {
+            NbtList::ByteArray(elements(reader, len, |r| read_array(r, |r| r.read_i8()))?)
+        }
+        tag_type::STRING => NbtList::String(elements(reader, len, read_string)?),
+        tag_type::LIST => NbtList::List(elements(reader, len, |r| read_list(r, depth + 1))?),
+        tag_type::COMPOUND => {
+            NbtList::Compound(elements(reader, len, |r| read_compound(r, depth + 1))?)
+        }
+        tag_type::INT_ARRAY => NbtList::IntArray(elements(reader, len, |r| {
+            read_array(r, ReadBytesExt::read_i32::<BigEndian>)
+        })?),
+        tag_type::LONG_ARRAY => NbtList::LongArray(elements(reader, len, |r| {
+            read_array(r, ReadBytesExt::read_i64::<BigEndian>)
+        })?),
+        ty => return Err(NbtError::InvalidTag(ty)),
+    })
+}
+
+/// Read `len` elements with `read`
+fn elements<R: Read, T>(
+    reader: &mut R,
+    len: usize,
+    mut read: impl FnMut(&mut R) -> Result<T, NbtError>,
+) -> Result<Vec<T>, NbtError> {
+    let mut out = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+    for _ in 0..len {
+        out.push(read(reader)?);
+    }
+    Ok(out)
+}
+
+/// Read an i32 length prefix
+fn read_len(reader: &mut impl Read) -> Result<usize, NbtError> {
+    let len = reader.read_i32::<BigEndian>()?;
+    usize::try_from(len).map_err(|_| NbtError::NegativeLength(len))
+}
+
+/// Read a length-prefixed array of numbers
+fn read_array<R: Read, T>(
+    reader: &mut R,
+    mut read: impl FnMut(&mut R) -> std::io::Result<T>,
+) -> Result<Vec<T>, NbtError> {
+    let len = read_len(reader)?;
+    let mut out = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+    for _ in 0..len {
+        out.push(read(reader)?);
+    }
+    Ok(out)
+}
+
+/// Read an NBT string (u16 length + modified UTF-8)
+fn read_string(reader: &mut impl Read) -> Result<String, NbtError> {
+    let len = reader.read_u16::<BigEndian>()?;
+    let mut bytes = vec![0; usize::from(len)];
+    reader.read_exact(&mut bytes)?;
+    from_modified_utf8(bytes)
+}
+
+/// Decode Java's modified UTF-8. Plain UTF-8 is accepted as is; otherwise
+/// the bytes are decoded to UTF-16 code units, which joins surrogate pairs
+/// and decodes the two-byte NUL.
+pub fn from_modified_utf8(bytes: Vec<u8>) -> Result<String, NbtError> {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(s) => return Ok(s),
+        Err(e) => e.into_bytes(),
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().copied();
+    while let Some(b) = iter.next() {
+        let unit = if b < 0x80 {
+            u16::from(b)
+        } else if b & 0xe0 == 0xc0 {
+            (u16::from(b & 0x1f) << 6) | continuation(iter.next())?
+        } else if b & 0xf0 == 0xe0 {
+            let high = continuation(iter.next())?;
+            let low = continuation(iter.next())?;
+            (u16::from(b & 0x0f) << 12) | (high << 6) | low
+        } else {
+            return Err(NbtError::InvalidString);
+        };
+        units.push(unit);
+    }
+    String::from_utf16(&units).map_err(|_| NbtError::InvalidString)
+}
+
+/// The low six bits of a continuation byte
+fn continuation(byte: Option<u8>) -> Result<u16, NbtError> {
+    match byte {
+        Some(b) if b & 0xc0 == 0x80 => Ok(u16::from(b & 0x3f)),
+        _ => Err(NbtError::InvalidString),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::to_modified_utf8;
+
+    #[test]
+    fn test_modified_utf8_round_trip() {
+        for s in ["plain", "caf\u{e9}", "nul\0byte", "emoji \u{1f600}"] {
+            let encoded = to_modified_utf8(s).into_owned();
+            assert!(!encoded.contains(&0), "{s:?} encoded with a NUL");
+            assert_eq!(from_modified_utf8(encoded).unwrap(), s);
+        }
+        // Surrogate pair, three bytes each
+        assert_eq!(
+            to_modified_utf8("\u{1f600}").as_ref(),
+            [0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]
+        );
+        // Unpaired surrogate
+        assert!(from_modified_utf8(vec![0xed, 0xa0, 0xbd]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_hostile_input() {
+        // A list claiming i32::MAX compounds with nothing behind it
+        let mut bytes = vec![tag_type::COMPOUND, tag_type::LIST, 0, 1, b'l'];
+        bytes.push(tag_type::COMPOUND);
+        bytes.extend_from_slice(&i32::MAX.to_be_bytes());
+        assert!(matches!(
+            NbtCompound::from_network_bytes(&bytes),
+            Err(NbtError::Io(_))
+        ));
+
+        // Negative array length
+        let mut bytes = vec![tag_type::COMPOUND, tag_type::INT_ARRAY, 0, 0];
+        bytes.extend_from_slice(&(-1i32).to_be_bytes());
+        assert!(matches!(
+            NbtCompound::from_network_bytes(&bytes),
+            Err(NbtError::NegativeLength(-1))
+        ));
+
+        // Compounds nested past the limit
+        let mut bytes = vec![tag_type::COMPOUND];
+        for _ in 0..=MAX_DEPTH {
+            bytes.extend_from_slice(&[tag_type::COMPOUND, 0, 0]);
+        }
+        assert!(matches!(
+            NbtCompound::from_network_bytes(&bytes),
+            Err(NbtError::TooDeep)
+        ));
+
+        assert!(matches!(
+            NbtCompound::from_network_bytes(&[tag_type::INT, 0, 0, 0, 1]),
+            Err(NbtError::RootNotCompound(tag_type::INT))
+        ));
+    }
+}
@@ -0,0 +1,511 @@
+//! SNBT, the text form of NBT used in commands.
+//!
+//! `Display` prints compact SNBT (`{id:"minecraft:stone",Count:1b}`) that
+//! `FromStr` parses back to the same value. Parsing follows vanilla:
+//! unquoted tokens are numbers when they look like one (suffixes `b`, `s`,
+//! `L`, `f` and `d`; no suffix is an int, or a double with a `.` or an
+//! exponent), `true` and `false` are bytes, and anything else is a string.
+//! Elements of a list must all have one type.
+//!
+//! Command arguments carry SNBT followed by more arguments
+//! (`diamond_sword{Damage:3} 2`), so the `parse_snbt_prefix` functions stop
+//! after the value and return the rest of the input.
+
+use std::fmt::{self, Display, Formatter, Write as _};
+use std::str::FromStr;
+
+use super::{NbtCompound, NbtError, NbtList, NbtValue};
+
+impl FromStr for NbtValue {
+    type Err = NbtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let value = parser.value(0)?;
+        parser.end()?;
+        Ok(value)
+    }
+}
+
+impl FromStr for NbtCompound {
+    type Err = NbtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        parser.skip_whitespace();
+        parser.expect('{')?;
+        let compound = parser.compound(0)?;
+        parser.end()?;
+        Ok(compound)
+    }
+}
+
+impl NbtValue {
+    /// Parse the SNBT value at the start of `input`, returning it and the
+    /// input after it
+    ///
+    /// # Errors
+    /// Fails if `input` doesn't start with a valid value.
+    pub fn parse_snbt_prefix(input: &str) -> Result<(Self, &str), NbtError> {
+        let mut parser = Parser::new(input);
+        let value = parser.value(0)?;
+        Ok((value, parser.rest()))
+    }
+}
+
+impl NbtCompound {
+    /// Parse the SNBT compound at the start of `input`, returning it and the
+    /// input after it
+    ///
+    /// # Errors
+    /// Fails if `input` doesn't start with a valid compound.
+    pub fn parse_snbt_prefix(input: &str) -> Result<(Self, &str), NbtError> {
+        let mut parser = Parser::new(input);
+        parser.skip_whitespace();
+        parser.expect('{')?;
+        let compound = parser.compound(0)?;
+        Ok((compound, parser.rest()))
+    }
+}
+
+/// Characters allowed in unquoted strings and keys
+const fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    const fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn error(&self, message: impl Into<String>) -> NbtError {
+        NbtError::Snbt {
+            position: self.pos,
+            message: message.into(),
+        }
+    }
+
+    /// The input not yet parsed
+    fn rest(&self) -> &'a str {
+        self.input.get(self.pos..).unwrap_or_default()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), NbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("expected '{expected}', found '{c}'"))),
+            None => Err(self.error(format!("expected '{expected}', found end of input"))),
+        }
+    }
+
+    /// Fail unless only whitespace is left
+    fn end(&mut self) -> Result<(), NbtError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(format!("unexpected '{c}' after value"))),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<NbtValue, NbtError> {
+        if depth > super::MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                Ok(NbtValue::Compound(self.compound(depth + 1)?))
+            }
+            Some('[') => {
+                self.bump();
+                self.list_or_array(depth + 1)
+            }
+            Some('"' | '\'') => Ok(NbtValue::String(self.quoted()?)),
+            Some(c) if is_unquoted(c) => Ok(token_value(self.unquoted())),
+            Some(c) => Err(self.error(format!("unexpected '{c}'"))),
+            None => Err(self.error("expected a value, found end of input")),
+        }
+    }
+
+    /// Entries of a compound whose `{` has been read
+    fn compound(&mut self, depth: usize) -> Result<NbtCompound, NbtError> {
+        let mut compound = NbtCompound::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(compound);
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                Some(c) if is_unquoted(c) => self.unquoted().to_string(),
+                _ => return Err(self.error("expected a key")),
+            };
+            self.expect(':')?;
+            let value = self.value(depth)?;
+            compound.insert(key, value);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => {}
+                Some('}') => return Ok(compound),
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    /// A list or typed array whose `[` has been read
+    fn list_or_array(&mut self, depth: usize) -> Result<NbtValue, NbtError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let array = ['B', 'I', 'L'].into_iter().find(|&ty| {
+            rest.strip_prefix(ty)
+                .is_some_and(|r| r.trim_start().starts_with(';'))
+        });
+        let Some(ty) = array else {
+            let start = self.pos;
+            let values = self.elements(depth)?;
+            return NbtList::from_values(values)
+                .map(NbtValue::List)
+                .ok_or_else(|| NbtError::Snbt {
+                    position: start,
+                    message: "list elements must all have one type".to_string(),
+                });
+        };
+
+        self.bump();
+        self.expect(';')?;
+        let start = self.pos;
+        let values = self.elements(depth)?;
+        let mismatch = || NbtError::Snbt {
+            position: start,
+            message: format!("[{ty};] array elements must all be of its type"),
+        };
+        Ok(match ty {
+            'B' => NbtValue::ByteArray(
+                values
+                    .iter()
+                    .map(NbtValue::as_byte)
+                    .collect::<Option<_>>()
+                    .ok_or_else(mismatch)?,
+            ),
+            'I' => NbtValue::IntArray(
+                values
+                    .iter()
+                    .map(NbtValue::as_int)
+                    .collect::<Option<_>>()
+                    .ok_or_else(mismatch)?,
+            ),
+            _ => NbtValue::LongArray(
+                values
+                    .iter()
+                    .map(NbtValue::as_long)
+                    .collect::<Option<_>>()
+                    .ok_or_else(mismatch)?,
+            ),
+        })
+    }
+
+    /// Comma-separated values up to and including `]`
+    fn elements(&mut self, depth: usize) -> Result<Vec<NbtValue>, NbtError> {
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value(depth)?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(values),
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn unquoted(&mut self) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(is_unquoted) {
+            self.bump();
+        }
+        self.input.get(start..self.pos).unwrap_or_default()
+    }
+
+    /// A string in single or double quotes
+    fn quoted(&mut self) -> Result<String, NbtError> {
+        let Some(quote) = self.bump() else {
+            return Err(self.error("expected a string"));
+        };
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => return Ok(out),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('s') => ' ',
+                        Some('u') => self.unicode_escape()?,
+                        Some(c @ ('\\' | '"' | '\'')) => c,
+                        Some(c) => return Err(self.error(format!("invalid escape '\\{c}'"))),
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    out.push(escaped);
+                }
+                Some(c) => out.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// The four hex digits of a `\u` escape
+    fn unicode_escape(&mut self) -> Result<char, NbtError> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("expected four hex digits after \\u"))?;
+        let c = u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(format!("invalid escape '\\u{digits}'")))?;
+        self.pos += 4;
+        Ok(c)
+    }
+}
+
+/// The value of an unquoted token: a number or boolean if it reads as one,
+/// otherwise a string
+fn token_value(token: &str) -> NbtValue {
+    if token.eq_ignore_ascii_case("true") {
+        return NbtValue::Byte(1);
+    }
+    if token.eq_ignore_ascii_case("false") {
+        return NbtValue::Byte(0);
+    }
+
+    let (body, suffix) = token.split_at(token.len() - 1);
+    let number = match suffix {
+        "b" | "B" => body.parse().ok().map(NbtValue::Byte),
+        "s" | "S" => body.parse().ok().map(NbtValue::Short),
+        "l" | "L" => body.parse().ok().map(NbtValue::Long),
+        "f" | "F" => parse_float(body).map(NbtValue::Float),
+        "d" | "D" => parse_float(body).map(NbtValue::Double),
+        _ => token.parse().ok().map(NbtValue::Int).or_else(|| {
+            token
+                .contains(['.', 'e', 'E'])
+                .then(|| parse_float(token))
+                .flatten()
+                .map(NbtValue::Double)
+        }),
+    };
+    number.unwrap_or_else(|| NbtValue::String(token.to_string()))
+}
+
+/// A decimal float; unlike Rust's parser, no `inf` or `NaN`
+fn parse_float<T: FromStr>(s: &str) -> Option<T> {
+    if s.is_empty() || s.contains(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E') {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Write a string in double quotes
+fn write_quoted(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\t' => f.write_str("\\t")?,
+            '\r' => f.write_str("\\r")?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Write comma-separated items between `open` and `]`
+fn write_seq<T>(
+    f: &mut Formatter<'_>,
+    open: &str,
+    items: &[T],
+    mut write: impl FnMut(&mut Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    f.write_str(open)?;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_char(',')?;
+        }
+        write(f, item)?;
+    }
+    f.write_char(']')
+}
+
+impl Display for NbtValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Byte(v) => write!(f, "{v}b"),
+            Self::Short(v) => write!(f, "{v}s"),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::Long(v) => write!(f, "{v}L"),
+            Self::Float(v) => write!(f, "{v}f"),
+            Self::Double(v) => write!(f, "{v}d"),
+            Self::ByteArray(v) => write_seq(f, "[B;", v, |f, b| write!(f, "{b}b")),
+            Self::String(v) => write_quoted(f, v),
+            Self::List(list) => list.fmt(f),
+            Self::Compound(compound) => compound.fmt(f),
+            Self::IntArray(v) => write_seq(f, "[I;", v, |f, i| write!(f, "{i}")),
+            Self::LongArray(v) => write_seq(f, "[L;", v, |f, l| write!(f, "{l}L")),
+        }
+    }
+}
+
+impl Display for NbtList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_seq(f, "[", &self.to_values(), |f, v| v.fmt(f))
+    }
+}
+
+impl Display for NbtCompound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('{')?;
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            if !key.is_empty() && key.chars().all(is_unquoted) {
+                f.write_str(key)?;
+            } else {
+                write_quoted(f, key)?;
+            }
+            write!(f, ":{value}")?;
+        }
+        f.write_char('}')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt;
+
+    #[test]
+    fn test_parse_snbt() {
+        let parsed: NbtCompound = r#"{
+            id: "minecraft:diamond_sword",
+            count: 1b,
+            'custom name': 'It\'s "sharp"',
+            damage: 3s, seed: -5L, ratio: 0.5f, speed: 1.5, big: 2e3d,
+            flags: [true, false],
+            pos: [0.5d, 64.0d, -0.5d],
+            heights: [L; 1L, 2L],
+            bytes: [B;],
+            ints: [I; 1, -2],
+            name: stone_bricks,
+            tags: {}
+        }"#
+        .parse()
+        .unwrap();
+
+        let expected = nbt! {
+            "id" => "minecraft:diamond_sword",
+            "count" => 1i8,
+            "custom name" => "It's \"sharp\"",
+            "damage" => 3i16,
+            "seed" => -5i64,
+            "ratio" => 0.5f32,
+            "speed" => 1.5f64,
+            "big" => 2000.0f64,
+            "flags" => NbtList::Byte(vec![1, 0]),
+            "pos" => [0.5f64, 64.0, -0.5],
+            "heights" => [L; 1, 2],
+            "bytes" => [B;],
+            "ints" => [I; 1, -2],
+            "name" => "stone_bricks",
+            "tags" => {},
+        };
+        assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn test_snbt_round_trip() {
+        let compound = nbt! {
+            "text" => "line\nbreak \\ \"quoted\"",
+            "needs quotes" => 1i32,
+            "" => 2i64,
+            "nested" => {
+                "lists" => [NbtList::Int(vec![1]), NbtList::Empty],
+                "items" => [nbt! { "f" => 1.0f32 }, nbt! { "f" => -0.25f32 }],
+            },
+            "bytes" => [B; -1, 2],
+        };
+        let printed = compound.to_string();
+        assert!(printed.starts_with(r#"{text:"line\nbreak \\ \"quoted\"","needs quotes":1,"#));
+        assert_eq!(printed.parse::<NbtCompound>().unwrap(), compound);
+    }
+
+    #[test]
+    fn test_parse_snbt_prefix() {
+        let (compound, rest) =
+            NbtCompound::parse_snbt_prefix("{display:{Name:'\"Sword\"'}} 2").unwrap();
+        assert_eq!(rest, " 2");
+        assert_eq!(compound, nbt! { "display" => { "Name" => "\"Sword\"" } });
+
+        let (value, rest) = NbtValue::parse_snbt_prefix("12b,x").unwrap();
+        assert_eq!((value, rest), (NbtValue::Byte(12), ",x"));
+        assert!(NbtCompound::parse_snbt_prefix("stone").is_err());
+    }
+
+    #[test]
+    fn test_snbt_errors() {
+        for (input, position) in [
+            ("{a:1,", 5),
+            ("{a:[1,2b]}", 4),
+            ("{a:[I;1,2L]}", 6),
+            ("{a: 1} extra", 7),
+            ("{a:\"open}", 9),
+        ] {
+            match input.parse::<NbtCompound>() {
+                Err(NbtError::Snbt { position: p, .. }) => assert_eq!(p, position, "{input}"),
+                other => panic!("{input}: {other:?}"),
+            }
+        }
+        // A value, but not a compound
+        assert!("[1, 2]".parse::<NbtCompound>().is_err());
+        assert_eq!(
+            "2147483648".parse::<NbtValue>().unwrap().as_str(),
+            Some("2147483648")
+        );
+        assert_eq!("infd".parse::<NbtValue>().unwrap().as_str(), Some("infd"));
+    }
+}
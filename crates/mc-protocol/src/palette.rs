@@ -0,0 +1,365 @@
+//! Paletted containers, the storage format of chunk section blocks and
+//! biomes (1.21.5+, no data array length prefix).
+//!
+//! ```text
+//! bits per entry: u8
+//! 0 bits:   value: VarInt                        (single-valued)
+//! indirect: palette length: VarInt, palette × VarInt, packed indices
+//! direct:   packed registry IDs
+//! ```
+//!
+//! Entries are packed into big-endian longs, lowest bits first, without
+//! spanning long boundaries. The data array has no length; it's implied by
+//! the entry count and bits per entry. [`PaletteFormat::mode`] and
+//! [`PaletteFormat::mode_for_bits`] pick the same mode on both sides, so
+//! [`write_paletted`] and [`read_paletted`] round-trip.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::{ProtocolError, Result, pack_longs, read_varint, unpack_longs, write_varint};
+
+/// Size and bit limits of one kind of paletted container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteFormat {
+    /// Number of entries (`16³` blocks or `4³` biomes)
+    pub entries: usize,
+    /// Smallest bits per entry of an indirect palette
+    pub min_bits: u8,
+    /// Largest bits per entry before switching to direct IDs
+    pub max_indirect_bits: u8,
+    /// Bits per entry of direct IDs, `ceil(log2(registry size))`
+    pub direct_bits: u8,
+}
+
+impl PaletteFormat {
+    /// Block states of a chunk section, indexed `(y * 16 + z) * 16 + x`.
+    #[must_use]
+    pub const fn blocks(direct_bits: u8) -> Self {
+        Self {
+            entries: 4096,
+            min_bits: 4,
+            max_indirect_bits: 8,
+            direct_bits,
+        }
+    }
+
+    /// Biomes of a chunk section in 4×4×4 cells, indexed `(y * 4 + z) * 4 + x`.
+    #[must_use]
+    pub const fn biomes(direct_bits: u8) -> Self {
+        Self {
+            entries: 64,
+            min_bits: 1,
+            max_indirect_bits: 3,
+            direct_bits,
+        }
+    }
+}
+
+/// How a paletted container stores its entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteMode {
+    /// One value, no data
+    Single,
+    /// Palette indices of the given bits
+    Indirect(u8),
+    /// Registry IDs of the given bits
+    Direct(u8),
+}
+
+impl PaletteMode {
+    /// Bits per entry as sent.
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::Single => 0,
+            Self::Indirect(bits) | Self::Direct(bits) => bits,
+        }
+    }
+}
+
+impl PaletteFormat {
+    /// Mode used to write a palette of `len` distinct values.
+    #[must_use]
+    pub const fn mode(&self, len: usize) -> PaletteMode {
+        if len <= 1 {
+            return PaletteMode::Single;
+        }
+        let bits = index_bits(len);
+        let bits = if bits < self.min_bits {
+            self.min_bits
+        } else {
+            bits
+        };
+        if bits > self.max_indirect_bits {
+            PaletteMode::Direct(self.direct_bits)
+        } else {
+            PaletteMode::Indirect(bits)
+        }
+    }
+
+    /// Mode of a container read with `bits` bits per entry. Like the client,
+    /// indirect bits are raised to `min_bits` and direct containers always
+    /// use `direct_bits`.
+    #[must_use]
+    pub const fn mode_for_bits(&self, bits: u8) -> PaletteMode {
+        if bits == 0 {
+            PaletteMode::Single
+        } else if bits <= self.max_indirect_bits {
+            PaletteMode::Indirect(if bits < self.min_bits {
+                self.min_bits
+            } else {
+                bits
+            })
+        } else {
+            PaletteMode::Direct(self.direct_bits)
+        }
+    }
+
+    /// Number of longs holding `entries` values of `bits` bits.
+    #[must_use]
+    pub const fn data_longs(&self, bits: u8) -> usize {
+        if bits == 0 {
+            return 0;
+        }
+        self.entries.div_ceil(64 / bits as usize)
+    }
+}
+
+/// Bits needed to index a palette of `len` entries (`0` for one entry).
+#[must_use]
+pub const fn index_bits(len: usize) -> u8 {
+    (usize::BITS - len.saturating_sub(1).leading_zeros()) as u8
+}
+
+/// Distinct values in order of first appearance, and the palette index of
+/// every value. Shared with region file storage, whose palettes hold names.
+#[must_use]
+pub fn palettize<T: Copy + Eq + Hash>(values: &[T]) -> (Vec<T>, Vec<u64>) {
+    let mut palette = Vec::new();
+    let mut index_of = HashMap::new();
+    let indices = values
+        .iter()
+        .map(|&value| {
+            *index_of.entry(value).or_insert_with(|| {
+                palette.push(value);
+                palette.len() as u64 - 1
+            })
+        })
+        .collect();
+    (palette, indices)
+}
+
+/// Write `values` (registry IDs, one per entry) as a paletted container.
+///
+/// # Panics
+///
+/// If `values` doesn't have exactly `format.entries` entries.
+pub fn write_paletted<W: Write>(
+    writer: &mut W,
+    format: &PaletteFormat,
+    values: &[i32],
+) -> Result<()> {
+    assert_eq!(
+        values.len(),
+        format.entries,
+        "wrong paletted container size"
+    );
+
+    let (palette, indices) = palettize(values);
+    let mode = format.mode(palette.len());
+    writer.write_all(&[mode.bits()])?;
+
+    let data = match mode {
+        PaletteMode::Single => return write_varint(writer, palette[0]),
+        PaletteMode::Indirect(bits) => {
+            write_varint(writer, palette.len() as i32)?;
+            for &value in &palette {
+                write_varint(writer, value)?;
+            }
+            pack_longs(indices.into_iter(), bits)
+        }
+        PaletteMode::Direct(bits) => pack_longs(values.iter().map(|&v| v as u64), bits),
+    };
+    for long in data {
+        writer.write_all(&long.to_be_bytes())?;
+    }
+    Ok(())
+}
+
+/// Read a paletted container written by [`write_paletted`] or the vanilla
+/// server, returning one registry ID per entry.
+pub fn read_paletted<R: Read>(reader: &mut R, format: &PaletteFormat) -> Result<Vec<i32>> {
+    let mode = format.mode_for_bits(reader.read_u8()?);
+
+    let palette = match mode {
+        PaletteMode::Single => return Ok(vec![read_varint(reader)?; format.entries]),
+        PaletteMode::Indirect(_) => {
+            let len = read_varint(reader)?;
+            // Checked before allocating: a larger palette can't be indexed
+            if len <= 0 || len > 1 << format.max_indirect_bits {
+                return Err(ProtocolError::PaletteLength(len));
+            }
+            (0..len)
+                .map(|_| read_varint(reader))
+                .collect::<Result<Vec<_>>>()?
+        }
+        PaletteMode::Direct(_) => Vec::new(),
+    };
+
+    let bits = mode.bits();
+    let mut data = vec![0u64; format.data_longs(bits)];
+    for long in &mut data {
+        *long = reader.read_u64::<BigEndian>()?;
+    }
+    let entries = unpack_longs(&data, bits, format.entries);
+
+    if let PaletteMode::Direct(_) = mode {
+        return Ok(entries.into_iter().map(|id| id as i32).collect());
+    }
+    entries
+        .into_iter()
+        .map(|index| {
+            palette
+                .get(index as usize)
+                .copied()
+                .ok_or(ProtocolError::PaletteIndex {
+                    index,
+                    len: palette.len(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(format: &PaletteFormat, values: &[i32]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_paletted(&mut buf, format, values).unwrap();
+        buf
+    }
+
+    fn roundtrip(format: &PaletteFormat, values: &[i32]) -> PaletteMode {
+        let buf = encode(format, values);
+        let mut reader = buf.as_slice();
+        assert_eq!(read_paletted(&mut reader, format).unwrap(), values);
+        assert!(reader.is_empty(), "trailing bytes");
+        format.mode_for_bits(buf[0])
+    }
+
+    #[test]
+    fn test_single_valued() {
+        assert_eq!(encode(&PaletteFormat::biomes(6), &[3; 64]), [0, 3]);
+    }
+
+    #[test]
+    fn test_indirect_biomes() {
+        let mut values = [7; 64];
+        values[1] = 2;
+        let buf = encode(&PaletteFormat::biomes(6), &values);
+
+        // 1 bit, palette [7, 2], 64 entries in one long
+        assert_eq!(buf[..4], [1, 2, 7, 2]);
+        assert_eq!(buf.len(), 4 + 8);
+        assert_eq!(u64::from_be_bytes(buf[4..].try_into().unwrap()), 0b10);
+    }
+
+    #[test]
+    fn test_indirect_blocks_use_min_bits() {
+        let mut values = [0; 4096];
+        values[4095] = 1;
+        let buf = encode(&PaletteFormat::blocks(15), &values);
+
+        // 4 bits, 16 entries per long, 256 longs
+        assert_eq!(buf[..4], [4, 2, 0, 1]);
+        assert_eq!(buf.len(), 4 + 256 * 8);
+        assert_eq!(buf[buf.len() - 8..], (1u64 << 60).to_be_bytes());
+    }
+
+    #[test]
+    fn test_direct_biomes() {
+        let values: Vec<i32> = (0..64).map(|i| i % 9).collect();
+        let buf = encode(&PaletteFormat::biomes(4), &values);
+
+        // No palette; 16 IDs of 4 bits per long
+        assert_eq!(buf[0], 4);
+        assert_eq!(buf.len(), 1 + 4 * 8);
+        assert_eq!(
+            u64::from_be_bytes(buf[1..9].try_into().unwrap()) & 0xFF,
+            0x10
+        );
+    }
+
+    #[test]
+    fn test_mode_thresholds() {
+        let blocks = PaletteFormat::blocks(15);
+        assert_eq!(blocks.mode(1), PaletteMode::Single);
+        assert_eq!(blocks.mode(2), PaletteMode::Indirect(4));
+        assert_eq!(blocks.mode(16), PaletteMode::Indirect(4));
+        assert_eq!(blocks.mode(17), PaletteMode::Indirect(5));
+        assert_eq!(blocks.mode(256), PaletteMode::Indirect(8));
+        assert_eq!(blocks.mode(257), PaletteMode::Direct(15));
+
+        let biomes = PaletteFormat::biomes(6);
+        assert_eq!(biomes.mode(2), PaletteMode::Indirect(1));
+        assert_eq!(biomes.mode(8), PaletteMode::Indirect(3));
+        assert_eq!(biomes.mode(9), PaletteMode::Direct(6));
+
+        // The client reads 1..=3 bit block palettes as 4 bits
+        assert_eq!(blocks.mode_for_bits(2), PaletteMode::Indirect(4));
+        assert_eq!(blocks.mode_for_bits(9), PaletteMode::Direct(15));
+        assert_eq!(blocks.data_longs(15), 1024);
+    }
+
+    #[test]
+    fn test_roundtrip_every_mode() {
+        let blocks = PaletteFormat::blocks(15);
+        let biomes = PaletteFormat::biomes(6);
+
+        assert_eq!(roundtrip(&blocks, &[9; 4096]), PaletteMode::Single);
+        let two: Vec<i32> = (0..4096).map(|i| i % 2 * 100).collect();
+        assert_eq!(roundtrip(&blocks, &two), PaletteMode::Indirect(4));
+        let many: Vec<i32> = (0..4096).map(|i| i % 200 * 7).collect();
+        assert_eq!(roundtrip(&blocks, &many), PaletteMode::Indirect(8));
+        let direct: Vec<i32> = (0..4096).map(|i| i * 5).collect();
+        assert_eq!(roundtrip(&blocks, &direct), PaletteMode::Direct(15));
+
+        let cells: Vec<i32> = (0..64).map(|i| i % 5).collect();
+        assert_eq!(roundtrip(&biomes, &cells), PaletteMode::Indirect(3));
+        let cells: Vec<i32> = (0..64).collect();
+        assert_eq!(roundtrip(&biomes, &cells), PaletteMode::Direct(6));
+    }
+
+    #[test]
+    fn test_read_rejects_bad_index() {
+        // 1 bit, palette [5], index 1 in the first entry
+        let mut buf = vec![1, 1, 5];
+        buf.extend_from_slice(&1u64.to_be_bytes());
+        let err = read_paletted(&mut buf.as_slice(), &PaletteFormat::biomes(6)).unwrap_err();
+        assert!(matches!(
+            err,
+            ProtocolError::PaletteIndex { index: 1, len: 1 }
+        ));
+    }
+
+    #[test]
+    fn test_read_rejects_oversized_palette() {
+        // 2 bits, a palette claiming far more entries than 3 bits can index
+        let mut buf = vec![2];
+        write_varint(&mut buf, i32::MAX).unwrap();
+        let err = read_paletted(&mut buf.as_slice(), &PaletteFormat::biomes(6)).unwrap_err();
+        assert!(matches!(err, ProtocolError::PaletteLength(i32::MAX)));
+    }
+
+    #[test]
+    fn test_palettize_in_first_appearance_order() {
+        let (palette, indices) = palettize(&[7, 3, 7, 9, 3]);
+        assert_eq!(palette, [7, 3, 9]);
+        assert_eq!(indices, [0, 1, 0, 2, 1]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Anvil region files
+//!
+//! A region file `r.{x}.{z}.mca` holds the 32x32 chunks of one region. It
+//! starts with two 4 KiB tables, chunk locations (offset and length in 4 KiB
+//! sectors) and modification times, followed by the chunks' sectors. Each
+//! chunk is its byte length, a compression type and the chunk's NBT.
+//!
+//! Chunks are stored uncompressed (compression type 3), which the game reads
+//! since 1.15.1. A rewritten chunk stays in place if it still fits, and
+//! otherwise moves to the first free run of sectors. Reading also takes the
+//! gzip (1) and zlib (2) chunks the game writes, so worlds saved by it load.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flecs_ecs::prelude::*;
+use mc_protocol::nbt::NbtCompound;
+
+const SECTOR_SIZE: usize = 4096;
+/// Sectors taken by the location and timestamp tables
+const HEADER_SECTORS: usize = 2;
+/// Longest chunk a location entry can describe, in sectors
+const MAX_CHUNK_SECTORS: usize = 255;
+const COMPRESSION_GZIP: u8 = 1;
+const COMPRESSION_ZLIB: u8 = 2;
+const COMPRESSION_NONE: u8 = 3;
+
+/// Global: Region files under a directory
+#[derive(Component, Debug, Clone)]
+pub struct RegionStore {
+    dir: PathBuf,
+}
+
+impl RegionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn region_path(&self, chunk_x: i32, chunk_z: i32) -> PathBuf {
+        self.dir
+            .join(format!("r.{}.{}.mca", chunk_x >> 5, chunk_z >> 5))
+    }
+
+    /// Write a chunk to its region file, creating the file if needed.
+    /// Returns the number of bytes written.
+    pub fn write_chunk(&self, chunk_x: i32, chunk_z: i32, nbt: &NbtCompound) -> io::Result<usize> {
+        let nbt = nbt.to_file_bytes("");
+        let mut payload = Vec::with_capacity(5 + nbt.len());
+        payload.extend_from_slice(&(nbt.len() as u32 + 1).to_be_bytes());
+        payload.push(COMPRESSION_NONE);
+        payload.extend_from_slice(&nbt);
+
+        let sectors = payload.len().div_ceil(SECTOR_SIZE);
+        if sectors > MAX_CHUNK_SECTORS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("chunk {chunk_x}, {chunk_z} takes {sectors} sectors"),
+            ));
+        }
+        payload.resize(sectors * SECTOR_SIZE, 0);
+
+        fs::create_dir_all(&self.dir)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.region_path(chunk_x, chunk_z))?;
+        let mut header = read_header(&mut file)?;
+
+        let slot = slot(chunk_x, chunk_z);
+        let (old_offset, old_sectors) = location(&header, slot);
+        let offset = if old_offset >= HEADER_SECTORS && sectors <= old_sectors {
+            old_offset
+        } else {
+            free_run(&header, slot, sectors)
+        };
+
+        file.seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        file.write_all(&payload)?;
+
+        let location = ((offset as u32) << 8) | sectors as u32;
+        header[slot * 4..slot * 4 + 4].copy_from_slice(&location.to_be_bytes());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as u32);
+        let timestamp_at = SECTOR_SIZE + slot * 4;
+        header[timestamp_at..timestamp_at + 4].copy_from_slice(&timestamp.to_be_bytes());
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&header)?;
+
+        Ok(payload.len())
+    }
+
+    /// Read a chunk from its region file, `None` if it was never saved
+    pub fn read_chunk(&self, chunk_x: i32, chunk_z: i32) -> io::Result<Option<NbtCompound>> {
+        let mut file = match File::open(self.region_path(chunk_x, chunk_z)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let header = read_header(&mut file)?;
+        let (offset, sectors) = location(&header, slot(chunk_x, chunk_z));
+        if offset < HEADER_SECTORS || sectors == 0 {
+            return Ok(None);
+        }
+
+        let mut payload = vec![0; sectors * SECTOR_SIZE];
+        file.seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        file.read_exact(&mut payload)?;
+
+        // The length counts the compression type byte
+        let length = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+        let Some(data) = payload.get(5..4 + length.max(1)) else {
+            return Err(invalid_data(format!(
+                "chunk {chunk_x}, {chunk_z} is longer than its sectors"
+            )));
+        };
+        let nbt = match payload[4] {
+            COMPRESSION_GZIP => NbtCompound::from_gzip_bytes(data),
+            COMPRESSION_ZLIB => NbtCompound::from_zlib_bytes(data),
+            COMPRESSION_NONE => NbtCompound::from_file_bytes(data),
+            other => {
+                return Err(invalid_data(format!(
+                    "chunk {chunk_x}, {chunk_z} uses unknown compression type {other}"
+                )));
+            }
+        };
+        nbt.map(|(_, chunk)| Some(chunk))
+            .map_err(|e| invalid_data(format!("chunk {chunk_x}, {chunk_z}: {e}")))
+    }
+}
+
+/// Index of a chunk in its region's tables
+const fn slot(chunk_x: i32, chunk_z: i32) -> usize {
+    ((chunk_z & 31) * 32 + (chunk_x & 31)) as usize
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The location and timestamp tables, empty for a new file
+fn read_header(file: &mut File) -> io::Result<Vec<u8>> {
+    let mut header = vec![0; HEADER_SECTORS * SECTOR_SIZE];
+    if file.metadata()?.len() >= header.len() as u64 {
+        file.read_exact(&mut header)?;
+    }
+    Ok(header)
+}
+
+/// Sector offset and count of a chunk slot, zero if the chunk isn't stored
+fn location(header: &[u8], slot: usize) -> (usize, usize) {
+    let entry = u32::from_be_bytes(
+        header[slot * 4..slot * 4 + 4]
+            .try_into()
+            .unwrap_or_default(),
+    );
+    ((entry >> 8) as usize, (entry & 0xFF) as usize)
+}
+
+/// First run of `sectors` free sectors, ignoring the space held by `slot`
+/// (which is being rewritten)
+fn free_run(header: &[u8], slot: usize, sectors: usize) -> usize {
+    let mut used: Vec<(usize, usize)> = (0..SECTOR_SIZE / 4)
+        .filter(|&other| other != slot)
+        .map(|other| location(header, other))
+        .filter(|&(offset, count)| offset >= HEADER_SECTORS && count > 0)
+        .collect();
+    used.sort_unstable();
+
+    let mut start = HEADER_SECTORS;
+    for (offset, count) in used {
+        if offset >= start + sectors {
+            break;
+        }
+        start = start.max(offset + count);
+    }
+    start
+}
@@ -0,0 +1,190 @@
+//! Per-player audit log
+//!
+//! Security-relevant actions are recorded in an append-only log per player:
+//! logins (with the address they came from), logouts, commands run, blocks
+//! broken or placed and items received. [`record`] queues an [`AuditAction`]
+//! for a player and [`AuditLog::flush`] appends the tick's entries to a
+//! [`PersistDb`] under `<world>/audit` in one transaction at the end of the
+//! tick.
+//!
+//! `/audit <player> [count]` shows a player's latest entries, and the
+//! dashboard serves them at `/api/players/{uuid}/audit`. Players are looked
+//! up by name, which in offline mode is all their UUID depends on, so the
+//! logs of players who aren't online can be read too.
+//!
+//! Nothing is recorded without a world directory or while replaying. The
+//! dashboard can't change entities yet, so it has no edits to record.
+
+use core::fmt;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flecs_ecs::prelude::*;
+use persist::PersistDb;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::components::{BlockPos, Uuid, WorldTime};
+
+/// Log name in the database
+const LOG: &str = "audit";
+
+/// Something a player did, or had done to them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditAction {
+    /// `addr` is the address they connected from, if known
+    Login {
+        addr: Option<String>,
+    },
+    Logout,
+    /// A command line, without the leading `/`
+    Command {
+        command: String,
+    },
+    BreakBlock {
+        pos: BlockPos,
+        block: String,
+    },
+    PlaceBlock {
+        pos: BlockPos,
+        block: String,
+    },
+    /// `from` is who gave them, e.g. by running `/give`
+    ReceiveItems {
+        item: String,
+        count: i32,
+        from: String,
+    },
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Login { addr: Some(addr) } => write!(f, "logged in from {addr}"),
+            Self::Login { addr: None } => write!(f, "logged in"),
+            Self::Logout => write!(f, "logged out"),
+            Self::Command { command } => write!(f, "ran /{command}"),
+            Self::BreakBlock { pos, block } => {
+                write!(f, "broke {block} at {} {} {}", pos.x, pos.y, pos.z)
+            }
+            Self::PlaceBlock { pos, block } => {
+                write!(f, "placed {block} at {} {} {}", pos.x, pos.y, pos.z)
+            }
+            Self::ReceiveItems { item, count, from } => {
+                write!(f, "received {count} {item} from {from}")
+            }
+        }
+    }
+}
+
+/// An action as recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch
+    pub time_ms: u64,
+    /// World age
+    pub tick: i64,
+    pub action: AuditAction,
+}
+
+/// An entry read back with its position in the player's log
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// Sequence number, counting from 0 for each player
+    pub seq: u64,
+    #[serde(flatten)]
+    pub entry: AuditEntry,
+}
+
+/// Global: The audit log and this tick's unwritten entries
+#[derive(Component)]
+pub struct AuditLog {
+    db: PersistDb,
+    pending: Vec<(u128, AuditEntry)>,
+}
+
+impl AuditLog {
+    /// Open the log stored at `path`
+    pub fn open(path: &Path) -> eyre::Result<Self> {
+        Ok(Self {
+            db: PersistDb::open(path)?,
+            pending: Vec::new(),
+        })
+    }
+
+    /// A player's newest entries, newest first, optionally only those
+    /// before sequence number `before`
+    pub fn read(
+        &self,
+        uuid: u128,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AuditRecord>, String> {
+        let entries = self
+            .db
+            .read_log(LOG, uuid, before, limit)
+            .map_err(|e| e.to_string())?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(seq, bytes)| {
+                let entry = serde_json::from_slice(&bytes).ok()?;
+                Some(AuditRecord { seq, entry })
+            })
+            .collect())
+    }
+
+    /// Append this tick's entries
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let encoded: Vec<_> = self
+            .pending
+            .drain(..)
+            .filter_map(|(uuid, entry)| Some((uuid, serde_json::to_vec(&entry).ok()?)))
+            .collect();
+        let entries = encoded
+            .iter()
+            .map(|(uuid, bytes)| (*uuid, bytes.as_slice()));
+        if let Err(e) = self.db.append_log(LOG, entries) {
+            warn!("Failed to write {} audit entries: {e}", encoded.len());
+        }
+    }
+}
+
+/// Queue `action` for `player`'s log, if they have a UUID and the log is
+/// open
+pub fn record(world: &WorldRef<'_>, player: EntityView<'_>, action: AuditAction) {
+    let Some(uuid) = player.try_get::<&Uuid>(|u| u.0) else {
+        return;
+    };
+    let tick = world.get::<&WorldTime>(|t| t.world_age);
+    world.try_get::<&mut AuditLog>(|log| {
+        log.pending.push((
+            uuid,
+            AuditEntry {
+                time_ms: now_ms(),
+                tick,
+                action,
+            },
+        ));
+    });
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// How long ago a recorded time was, e.g. `5m`
+pub fn age(time_ms: u64) -> String {
+    let seconds = now_ms().saturating_sub(time_ms) / 1000;
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86_400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86_400),
+    }
+}
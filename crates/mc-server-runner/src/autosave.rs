@@ -0,0 +1,117 @@
+//! Saving modified chunks
+//!
+//! [`chunk::set_block`] marks a changed chunk [`Unsaved`]. Each tick,
+//! [`autosave`] writes a few of the unsaved chunks to their region files:
+//! first chunks no player sees (the next to be unloaded), then those unsaved
+//! the longest. A chunk is only saved once it's been unsaved for a while,
+//! so a chunk that keeps changing isn't rewritten every tick.
+
+use flecs_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::anvil::RegionStore;
+use crate::chunk::{ChunkBlocks, chunk_viewers};
+use crate::components::{ChunkPos, WorldTime};
+
+/// Most chunks written per tick
+pub const SAVE_BUDGET: usize = 2;
+/// Ticks a chunk someone sees stays unsaved before it's written
+pub const AUTOSAVE_DELAY: i64 = 600;
+/// Ticks a chunk no one sees stays unsaved before it's written
+pub const UNWATCHED_DELAY: i64 = 20;
+
+/// Component: The chunk has changes not yet written to its region file
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Unsaved {
+    /// World age of the first unsaved change
+    pub since: i64,
+}
+
+/// Global: Autosave backlog and throughput
+#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SaveStats {
+    /// Unsaved chunks, as of the last autosave
+    pub backlog: usize,
+    /// Ticks the longest-unsaved chunk has waited
+    pub oldest_unsaved_ticks: i64,
+    /// Chunks written
+    pub saved: u64,
+    /// Writes that failed (the chunk is retried after the delay)
+    pub failed: u64,
+    pub bytes_written: u64,
+}
+
+/// Mark a chunk as changed since it was last saved
+pub fn mark_unsaved(world: &WorldRef<'_>, chunk: EntityView<'_>) {
+    if !chunk.has::<Unsaved>() {
+        let since = world.get::<&WorldTime>(|t| t.world_age);
+        chunk.set(Unsaved { since });
+    }
+}
+
+/// Write a chunk to its region file and clear [`Unsaved`]. Returns `false`
+/// if the write failed.
+pub fn save_chunk(
+    world: &WorldRef<'_>,
+    store: &RegionStore,
+    stats: &mut SaveStats,
+    chunk: EntityView<'_>,
+) -> bool {
+    let now = world.get::<&WorldTime>(|t| t.world_age);
+    let Some((pos, nbt)) = chunk.try_get::<(&ChunkBlocks, &ChunkPos)>(|(blocks, pos)| {
+        (*pos, blocks.to_nbt(pos.x, pos.z, now))
+    }) else {
+        return false;
+    };
+
+    match store.write_chunk(pos.x, pos.z, &nbt) {
+        Ok(bytes) => {
+            chunk.remove::<Unsaved>();
+            stats.saved += 1;
+            stats.bytes_written += bytes as u64;
+            true
+        }
+        Err(e) => {
+            tracing::warn!("Failed to save chunk {}, {}: {e}", pos.x, pos.z);
+            chunk.set(Unsaved { since: now });
+            stats.failed += 1;
+            false
+        }
+    }
+}
+
+/// Update the backlog and save up to [`SAVE_BUDGET`] due chunks
+pub fn autosave(world: &WorldRef<'_>, store: &RegionStore, stats: &mut SaveStats) {
+    let now = world.get::<&WorldTime>(|t| t.world_age);
+
+    let mut unsaved = Vec::new();
+    world
+        .query::<&Unsaved>()
+        .build()
+        .each_entity(|chunk, unsaved_since| unsaved.push((chunk.id(), unsaved_since.since)));
+    stats.backlog = unsaved.len();
+    stats.oldest_unsaved_ticks = unsaved
+        .iter()
+        .map(|&(_, since)| now - since)
+        .max()
+        .unwrap_or(0);
+
+    // Unwatched chunks first, then the longest unsaved
+    let mut due: Vec<_> = unsaved
+        .into_iter()
+        .map(|(chunk, since)| (!chunk_viewers(world, chunk).is_empty(), since, chunk))
+        .filter(|&(watched, since, _)| {
+            let delay = if watched {
+                AUTOSAVE_DELAY
+            } else {
+                UNWATCHED_DELAY
+            };
+            now - since >= delay
+        })
+        .collect();
+    due.sort_by_key(|&(watched, since, _)| (watched, since));
+
+    for (_, _, chunk) in due.into_iter().take(SAVE_BUDGET) {
+        save_chunk(world, store, stats, world.entity_from_id(chunk));
+    }
+}
@@ -0,0 +1,140 @@
+//! Scheduled block ticks
+//!
+//! Blocks that change on a delay (fluids, and later crops and redstone
+//! components) schedule a tick at their position. Each chunk keeps its
+//! pending ticks in [`ScheduledTicks`], ordered by due time and then
+//! priority; the component is serializable, so pending ticks are saved with
+//! the chunk and resume after a restart.
+//!
+//! A tick only fires if the block it was scheduled for is still there, and
+//! each chunk fires at most [`TICK_BUDGET`] ticks per game tick, leaving the
+//! rest for the next.
+//!
+//! Chunks fire in nine phases by [`phase`], like the RGB simulation: a phase
+//! computes all of its chunks' changes before applying any, and chunks in a
+//! phase are three chunks apart, so the one-block reach of a block tick
+//! never lets two chunks of a phase touch the same blocks.
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{self, chunk_name};
+use crate::components::{BlockPos, ChunkPos, WorldTime};
+use crate::fluid::{self, Fluid};
+
+/// Most scheduled ticks a chunk fires per game tick
+pub const TICK_BUDGET: usize = 64;
+
+/// A pending tick of one block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledTick {
+    pub pos: BlockPos,
+    /// Default state of the block the tick is for
+    pub block: u16,
+    /// World age the tick is due at
+    pub due: i64,
+    /// Among ticks due at once, lower fires first
+    pub priority: i8,
+}
+
+/// Pending ticks of a chunk, in firing order
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduledTicks {
+    pub entries: Vec<ScheduledTick>,
+}
+
+impl ScheduledTicks {
+    /// Add a tick after those due at the same time with the same or higher
+    /// priority. A block already waiting for a tick isn't scheduled twice.
+    pub fn insert(&mut self, tick: ScheduledTick) {
+        let pending = self
+            .entries
+            .iter()
+            .any(|t| t.pos == tick.pos && t.block == tick.block);
+        if pending {
+            return;
+        }
+        let index = self
+            .entries
+            .partition_point(|t| (t.due, t.priority) <= (tick.due, tick.priority));
+        self.entries.insert(index, tick);
+    }
+
+    /// Remove and return up to `budget` ticks due by `now`
+    pub fn take_due(&mut self, now: i64, budget: usize) -> Vec<ScheduledTick> {
+        let ready = self
+            .entries
+            .iter()
+            .take(budget)
+            .take_while(|t| t.due <= now)
+            .count();
+        self.entries.drain(..ready).collect()
+    }
+}
+
+/// Phase (0-8) a chunk fires its ticks in; neighboring chunks never share one
+pub const fn phase(pos: ChunkPos) -> usize {
+    (pos.x.rem_euclid(3) + pos.z.rem_euclid(3) * 3) as usize
+}
+
+fn block_id(state: BlockState) -> u16 {
+    state.default_state().unwrap_or(state).id()
+}
+
+/// Schedule a tick for the block at `pos`, `delay` game ticks from now.
+/// Does nothing if its chunk isn't loaded.
+pub fn schedule_tick(world: &WorldRef<'_>, pos: BlockPos, delay: i64, priority: i8) {
+    let Some(state) = chunk::block_at(world, pos) else {
+        return;
+    };
+    let Some(chunk) = world.try_lookup_recursive(&chunk_name(pos.x >> 4, pos.z >> 4)) else {
+        return;
+    };
+    let tick = ScheduledTick {
+        pos,
+        block: block_id(state),
+        due: world.get::<&WorldTime>(|t| t.world_age) + delay,
+        priority,
+    };
+    chunk.try_get::<&mut ScheduledTicks>(|ticks| ticks.insert(tick));
+}
+
+/// Changes made by a block's tick
+fn run_tick(world: &WorldRef<'_>, pos: BlockPos, state: BlockState) -> Vec<(BlockPos, BlockState)> {
+    if Fluid::of(state).is_some() {
+        return fluid::tick(world, pos);
+    }
+    Vec::new()
+}
+
+/// Fire the due ticks of every chunk, phase by phase
+pub fn fire_due_ticks(world: &WorldRef<'_>) {
+    let now = world.get::<&WorldTime>(|t| t.world_age);
+
+    for current_phase in 0..9 {
+        let mut due = Vec::new();
+        world
+            .query::<(&ChunkPos, &mut ScheduledTicks)>()
+            .build()
+            .each(|(pos, ticks)| {
+                if phase(*pos) == current_phase {
+                    due.extend(ticks.take_due(now, TICK_BUDGET));
+                }
+            });
+
+        // Compute the whole phase before changing anything
+        let mut changes = Vec::new();
+        for tick in due {
+            let Some(state) = chunk::block_at(world, tick.pos) else {
+                continue;
+            };
+            if block_id(state) == tick.block {
+                changes.extend(run_tick(world, tick.pos, state));
+            }
+        }
+        for (pos, state) in changes {
+            chunk::set_block(world, pos.x, pos.y, pos.z, state);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Bots: players driven by code instead of a connection
+//!
+//! A bot has the components of a logged-in player (name, UUID, entity ID,
+//! position, rotation, game mode) plus [`Bot`], but no connection or packet
+//! buffer, so it is listed, journaled and shown on the dashboard like anyone
+//! else while the network systems skip it. A [`BotController`] spawns one
+//! and drives it, for scripted experiments and integration tests that
+//! shouldn't need a client:
+//!
+//! ```ignore
+//! let bot = BotController::spawn(&world, "alex")?;
+//! bot.move_to(&world, Position::new(10.0, 80.0, 10.0));
+//! bot.chat(&world, "on my way");
+//! ```
+//!
+//! The server has no pathfinding yet, so [`BotController::move_to`] walks a
+//! straight line at walking speed, through blocks and without gravity.
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use mc_text::Text;
+
+use crate::audit::{self, AuditAction};
+use crate::chunk;
+use crate::components::{
+    BlockPos, ChunkPosition, EntityId, GameMode, Name, Player, Position, Rotation, Uuid,
+};
+use crate::entity_ids::EntityIdAllocator;
+use crate::protection::{self, Action};
+use crate::protocol::offline_uuid;
+use crate::systems::{broadcast_chat, run_command, spawn_position};
+
+/// Blocks a bot walks per tick (vanilla walking speed, 4.317 blocks/s)
+const WALK_SPEED: f64 = 0.215_85;
+
+/// Height of a player's eyes above their feet
+const EYE_HEIGHT: f64 = 1.62;
+
+/// Furthest a bot can break a block, from its eyes to the block's center
+/// (the distance vanilla servers allow players)
+const REACH: f64 = 6.0;
+
+/// Tag: Player entity driven by a [`BotController`]
+#[derive(Component)]
+pub struct Bot;
+
+/// Where a bot is walking to, removed once it arrives
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MoveTarget(pub Position);
+
+/// Handle to a bot entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BotController {
+    entity: Entity,
+}
+
+impl BotController {
+    /// Spawn a bot named `name` at the world spawn
+    pub fn spawn(world: &WorldRef<'_>, name: &str) -> Result<Self, String> {
+        if Self::find(world, name).is_some() {
+            return Err(format!("There is already a bot named {name}"));
+        }
+        let entity_id = world
+            .get::<&EntityIdAllocator>(EntityIdAllocator::allocate)
+            .ok_or_else(|| "No free entity IDs".to_string())?;
+        let position = spawn_position(world);
+        let (chunk_x, chunk_z) = position.chunk_pos();
+        let entity = world
+            .entity_named(&bot_name(name))
+            .add(Player)
+            .add(Bot)
+            .set(Name {
+                value: name.to_string(),
+            })
+            .set(Uuid(offline_uuid(name)))
+            .set(EntityId { value: entity_id })
+            .set(ChunkPosition::new(chunk_x, chunk_z))
+            .set(position)
+            .set(Rotation::new(0.0, 0.0))
+            .set(GameMode::CREATIVE)
+            .id();
+        Ok(Self { entity })
+    }
+
+    /// The bot named `name`, if one is spawned
+    pub fn find(world: &WorldRef<'_>, name: &str) -> Option<Self> {
+        let entity = world.try_lookup_recursive(&bot_name(name))?;
+        entity.has(Bot).then(|| Self {
+            entity: entity.id(),
+        })
+    }
+
+    fn position(&self, world: &WorldRef<'_>) -> Position {
+        world
+            .entity_from_id(self.entity)
+            .get::<&Position>(|pos| *pos)
+    }
+
+    /// Start walking to `target`, replacing any previous target
+    pub fn move_to(&self, world: &WorldRef<'_>, target: Position) {
+        world.entity_from_id(self.entity).set(MoveTarget(target));
+    }
+
+    /// Turn to look at `target`
+    pub fn look_at(&self, world: &WorldRef<'_>, target: Position) {
+        let entity = world.entity_from_id(self.entity);
+        let rotation = entity.get::<&Position>(|pos| facing(eyes(*pos), target));
+        entity.set(rotation);
+    }
+
+    /// Look at and break the block at `pos`, returning what it was
+    pub fn break_block(&self, world: &WorldRef<'_>, pos: BlockPos) -> Result<BlockState, String> {
+        let center = Position::new(
+            f64::from(pos.x) + 0.5,
+            f64::from(pos.y) + 0.5,
+            f64::from(pos.z) + 0.5,
+        );
+        if distance(eyes(self.position(world)), center) > REACH {
+            return Err(format!("{pos:?} is out of reach"));
+        }
+        match chunk::block_at(world, pos) {
+            None => return Err(format!("The chunk of {pos:?} isn't loaded")),
+            Some(state) if state.is_air() => return Err(format!("There is no block at {pos:?}")),
+            Some(_) => {}
+        }
+        let entity = world.entity_from_id(self.entity);
+        if !protection::allowed(world, entity, pos, Action::Break) {
+            return Err(format!("{pos:?} is protected"));
+        }
+        self.look_at(world, center);
+        let old = chunk::set_block(world, pos.x, pos.y, pos.z, BlockState::AIR)
+            .ok_or_else(|| format!("The chunk of {pos:?} isn't loaded"))?;
+        let block = old.block_name().unwrap_or_default().to_string();
+        audit::record(world, entity, AuditAction::BreakBlock { pos, block });
+        Ok(old)
+    }
+
+    /// Say `message` in chat, or run it as a command if it starts with `/`,
+    /// returning the command's response
+    pub fn chat(&self, world: &WorldRef<'_>, message: &str) -> Option<Text> {
+        let entity = world.entity_from_id(self.entity);
+        if let Some(command) = message.strip_prefix('/') {
+            return run_command(world, entity, command);
+        }
+        let name = entity.get::<&Name>(|name| name.value.clone());
+        broadcast_chat(
+            world,
+            &Text::translate_with(
+                "chat.type.text",
+                vec![Text::literal(name), Text::literal(message)],
+            ),
+        );
+        None
+    }
+
+    /// Remove the bot from the world
+    pub fn despawn(self, world: &WorldRef<'_>) {
+        world.entity_from_id(self.entity).destruct();
+    }
+}
+
+fn bot_name(name: &str) -> String {
+    format!("bot:{name}")
+}
+
+fn eyes(pos: Position) -> Position {
+    Position::new(pos.x, pos.y + EYE_HEIGHT, pos.z)
+}
+
+fn distance(a: Position, b: Position) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y).hypot(b.z - a.z)
+}
+
+/// Rotation of someone at `from` looking at `to`
+fn facing(from: Position, to: Position) -> Rotation {
+    let (dx, dy, dz) = (to.x - from.x, to.y - from.y, to.z - from.z);
+    let yaw = -dx.atan2(dz).to_degrees();
+    let pitch = -dy.atan2(dx.hypot(dz)).to_degrees();
+    Rotation::new(yaw as f32, pitch as f32)
+}
+
+/// Walk a bot one tick towards its target, facing where it goes. Returns
+/// `true` once it has arrived.
+pub fn walk(pos: &mut Position, rot: &mut Rotation, target: &MoveTarget) -> bool {
+    let target = target.0;
+    let remaining = distance(*pos, target);
+    if remaining <= WALK_SPEED {
+        *pos = target;
+        return true;
+    }
+    *rot = facing(*pos, target);
+    let step = WALK_SPEED / remaining;
+    pos.x += (target.x - pos.x) * step;
+    pos.y += (target.y - pos.y) * step;
+    pos.z += (target.z - pos.z) * step;
+    false
+}
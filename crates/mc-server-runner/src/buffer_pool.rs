@@ -0,0 +1,140 @@
+//! Buffer pooling for outgoing packets
+//!
+//! Building a packet used to allocate a `Vec` for its body, two more for the
+//! length and ID varints and a `BytesMut` for the frame. With 100 players
+//! that's thousands of allocations per tick for keep-alives, time updates,
+//! block updates and the tab list alone.
+//!
+//! [`frame`] instead writes the body into a scratch `Vec` kept per thread,
+//! and frames it into a per-thread arena the returned `Bytes` is split off
+//! of. Frames share the arena's allocation, and once the network task has
+//! written and dropped all of them the arena reclaims it, so a steady
+//! stream of packets settles into no allocations at all.
+//!
+//! Frames that live long, like cached chunk packets, would pin an arena
+//! block, so they keep using [`encode_packet`], which allocates exactly
+//! once. Frames over [`MAX_POOLED_FRAME`] bytes bypass the arena too.
+//!
+//! [`stats`] counts how often the pool was reused, shown in the dashboard
+//! metrics as `egress_buffers`.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use mc_protocol::write_varint;
+use serde::Serialize;
+
+use crate::protocol::{encode_packet, varint_len};
+
+/// Size of a fresh arena block
+const ARENA_SIZE: usize = 64 * 1024;
+
+/// Largest frame taken from the arena
+pub const MAX_POOLED_FRAME: usize = 8 * 1024;
+
+/// Scratch buffers that grew past this are dropped rather than kept
+const MAX_SCRATCH: usize = 256 * 1024;
+
+thread_local! {
+    static POOL: RefCell<BufferPool> = RefCell::new(BufferPool::default());
+}
+
+static FRAMES_REUSED: AtomicU64 = AtomicU64::new(0);
+static FRAMES_ALLOCATED: AtomicU64 = AtomicU64::new(0);
+static SCRATCH_REUSED: AtomicU64 = AtomicU64::new(0);
+static SCRATCH_ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct BufferPool {
+    arena: BytesMut,
+    scratch: Vec<u8>,
+}
+
+impl BufferPool {
+    fn frame(&mut self, packet_id: i32, body: &[u8]) -> Bytes {
+        let len = varint_len(packet_id) + body.len();
+        let frame_len = varint_len(len as i32) + len;
+        if frame_len > MAX_POOLED_FRAME {
+            FRAMES_ALLOCATED.fetch_add(1, Ordering::Relaxed);
+            return encode_packet(packet_id, body);
+        }
+
+        if self.arena.capacity() >= frame_len || self.arena.try_reclaim(frame_len) {
+            FRAMES_REUSED.fetch_add(1, Ordering::Relaxed);
+        } else {
+            // Frames still in flight hold the old block; it's freed with them
+            FRAMES_ALLOCATED.fetch_add(1, Ordering::Relaxed);
+            self.arena = BytesMut::with_capacity(ARENA_SIZE);
+        }
+
+        let mut writer = (&mut self.arena).writer();
+        write_varint(&mut writer, len as i32).expect("varint write");
+        write_varint(&mut writer, packet_id).expect("varint write");
+        self.arena.put_slice(body);
+        self.arena.split().freeze()
+    }
+}
+
+/// Build a packet whose body `body` writes, framed with its length and ID
+///
+/// Uses this thread's pooled buffers; see the module docs.
+pub fn frame(
+    packet_id: i32,
+    body: impl FnOnce(&mut Vec<u8>) -> eyre::Result<()>,
+) -> eyre::Result<Bytes> {
+    // Taken out of the pool so `body` may build packets itself
+    let mut scratch = POOL.with_borrow_mut(|pool| std::mem::take(&mut pool.scratch));
+    scratch.clear();
+    let capacity = scratch.capacity();
+
+    let result = body(&mut scratch);
+    if scratch.capacity() == capacity && capacity > 0 {
+        SCRATCH_REUSED.fetch_add(1, Ordering::Relaxed);
+    } else {
+        SCRATCH_ALLOCATED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let packet = result.map(|()| POOL.with_borrow_mut(|pool| pool.frame(packet_id, &scratch)));
+    if scratch.capacity() <= MAX_SCRATCH {
+        POOL.with_borrow_mut(|pool| pool.scratch = scratch);
+    }
+    packet
+}
+
+/// How well the egress buffer pool is doing, across all threads
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct BufferPoolStats {
+    /// Frames split off an arena that had room
+    pub frames_reused: u64,
+    /// Frames that needed a new arena block, or were too big for one
+    pub frames_allocated: u64,
+    /// Packet bodies that fit the scratch buffer
+    pub scratch_reused: u64,
+    /// Packet bodies that had to grow the scratch buffer
+    pub scratch_allocated: u64,
+    /// Share of buffers served without allocating, from 0 to 1
+    pub reuse_rate: f64,
+}
+
+/// Pool counters since startup
+pub fn stats() -> BufferPoolStats {
+    let frames_reused = FRAMES_REUSED.load(Ordering::Relaxed);
+    let frames_allocated = FRAMES_ALLOCATED.load(Ordering::Relaxed);
+    let scratch_reused = SCRATCH_REUSED.load(Ordering::Relaxed);
+    let scratch_allocated = SCRATCH_ALLOCATED.load(Ordering::Relaxed);
+
+    let reused = frames_reused + scratch_reused;
+    let total = reused + frames_allocated + scratch_allocated;
+    BufferPoolStats {
+        frames_reused,
+        frames_allocated,
+        scratch_reused,
+        scratch_allocated,
+        reuse_rate: if total == 0 {
+            0.0
+        } else {
+            reused as f64 / total as f64
+        },
+    }
+}
@@ -0,0 +1,808 @@
+//! Chunk block storage, heightmaps and network encoding
+//!
+//! Each chunk entity keeps its blocks and biomes in [`ChunkBlocks`]; its
+//! `Level Chunk With Light` packet is cached in [`ChunkData`], dropped by
+//! [`set_block`] and rebuilt by [`chunk_packet`] on the next send. Its
+//! dashboard [map tile](crate::map) is cached and dropped the same way.
+//! Players track the chunks their client has loaded as `(Sees, chunk)`
+//! pairs, kept up to date by [`update_interest`], and each chunk keeps the
+//! reverse in [`ChunkViewers`].
+//!
+//! Block coordinates are taken modulo 16 on X and Z, so world coordinates
+//! can be passed as-is.
+
+use std::collections::HashSet;
+
+use byteorder::{BigEndian, WriteBytesExt};
+use bytes::Bytes;
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use mc_protocol::nbt::{NbtCompound, NbtList, NbtValue};
+use mc_protocol::{
+    BitSet, Encode, LongArray, PaletteFormat, index_bits, pack_longs, palettize, unpack_longs,
+    write_paletted, write_varint,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    BlockPos, ChunkData, ChunkLoaded, ChunkPos, ChunkPosition, PacketBuffer, Player, Position, Sees,
+};
+use crate::journal::Journal;
+use crate::map::MapTile;
+use crate::protocol::{
+    encode_packet, packet_ids, send_block_update, send_chunks_to_buffer, send_forget_chunk,
+    send_set_center_chunk,
+};
+use crate::{autosave, fluid, redstone, view_distance, world_gen};
+
+/// Lowest block Y of the overworld
+pub const MIN_Y: i32 = -64;
+/// Number of 16-block sections in a column
+pub const SECTION_COUNT: usize = 24;
+
+const SECTION_VOLUME: usize = 16 * 16 * 16;
+const BIOME_VOLUME: usize = 4 * 4 * 4;
+/// Bits per heightmap entry, `ceil(log2(384 + 1))`
+const HEIGHTMAP_BITS: u8 = 9;
+/// World data version written with saved chunks (1.21.10; the game
+/// upgrades older data on load)
+const DATA_VERSION: i32 = 4556;
+
+/// Heightmaps the client uses, with their network IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightmapKind {
+    /// Highest non-air block
+    WorldSurface = 1,
+    /// Highest block with collision or fluid (rain, spawn selection)
+    MotionBlocking = 4,
+    /// Like `MotionBlocking`, ignoring leaves
+    MotionBlockingNoLeaves = 5,
+}
+
+impl HeightmapKind {
+    pub const ALL: [Self; 3] = [
+        Self::WorldSurface,
+        Self::MotionBlocking,
+        Self::MotionBlockingNoLeaves,
+    ];
+
+    /// Key of the heightmap in a region file's `Heightmaps`
+    pub const fn nbt_name(self) -> &'static str {
+        match self {
+            Self::WorldSurface => "WORLD_SURFACE",
+            Self::MotionBlocking => "MOTION_BLOCKING",
+            Self::MotionBlockingNoLeaves => "MOTION_BLOCKING_NO_LEAVES",
+        }
+    }
+
+    /// Whether a block counts towards this heightmap
+    pub fn matches(self, state: BlockState) -> bool {
+        if state.is_air() {
+            return false;
+        }
+        match self {
+            Self::WorldSurface => true,
+            Self::MotionBlocking => state.is_solid() || state.has_fluid(),
+            Self::MotionBlockingNoLeaves => {
+                (state.is_solid() || state.has_fluid())
+                    && !state
+                        .block_name()
+                        .is_some_and(|name| name.ends_with("_leaves"))
+            }
+        }
+    }
+}
+
+/// Component: Block and biome data of a loaded chunk
+#[derive(Component)]
+pub struct ChunkBlocks {
+    /// Block states per section (y, z, x order); `None` is all air
+    sections: Vec<Option<Box<[u16; SECTION_VOLUME]>>>,
+    /// Non-air blocks per section; a section is freed when it drops to zero
+    block_counts: [u16; SECTION_COUNT],
+    /// Biome IDs per section in 4x4x4 cells (y, z, x order)
+    biomes: Vec<[i32; BIOME_VOLUME]>,
+    /// Per heightmap and column (`z * 16 + x`), the highest matching Y
+    heightmaps: [[i32; 256]; HeightmapKind::ALL.len()],
+}
+
+impl ChunkBlocks {
+    /// Build a chunk from block and biome functions of local `(x, y, z)`.
+    ///
+    /// Biomes are sampled once per 4x4x4 cell at the cell's lowest corner.
+    pub fn from_fn(
+        mut block: impl FnMut(usize, i32, usize) -> BlockState,
+        mut biome: impl FnMut(usize, i32, usize) -> i32,
+    ) -> Self {
+        let mut sections = Vec::with_capacity(SECTION_COUNT);
+        let mut biomes = Vec::with_capacity(SECTION_COUNT);
+
+        for section in 0..SECTION_COUNT {
+            let min_y = MIN_Y + section as i32 * 16;
+
+            let mut blocks = Box::new([0u16; SECTION_VOLUME]);
+            for (index, state) in blocks.iter_mut().enumerate() {
+                let (y, z, x) = (index / 256, (index / 16) % 16, index % 16);
+                *state = block(x, min_y + y as i32, z).id();
+            }
+            sections.push(blocks.iter().any(|&id| id != 0).then_some(blocks));
+
+            biomes.push(std::array::from_fn(|index| {
+                let (y, z, x) = (index / 16, (index / 4) % 4, index % 4);
+                biome(x * 4, min_y + y as i32 * 4, z * 4)
+            }));
+        }
+
+        Self::with_heightmaps(sections, biomes)
+    }
+
+    /// Read a chunk saved by [`to_nbt`](Self::to_nbt) or by the game, `None`
+    /// if it's malformed. Blocks the server doesn't know read as air.
+    pub fn from_nbt(nbt: &NbtCompound) -> Option<Self> {
+        let mut sections = vec![None; SECTION_COUNT];
+        let mut biomes = vec![[0; BIOME_VOLUME]; SECTION_COUNT];
+
+        let NbtList::Compound(saved) = nbt.get("sections")?.as_list()? else {
+            return None;
+        };
+        for section in saved {
+            let y = i32::from(section.get("Y")?.as_byte()?);
+            let Some(index) = usize::try_from(y - MIN_Y / 16)
+                .ok()
+                .filter(|&index| index < SECTION_COUNT)
+            else {
+                // Sections above and below the world hold only light
+                continue;
+            };
+
+            if let Some(states) = section.get("block_states").and_then(NbtValue::as_compound) {
+                let NbtList::Compound(palette) = states.get("palette")?.as_list()? else {
+                    return None;
+                };
+                let palette: Vec<u16> = palette
+                    .iter()
+                    .map(|entry| palette_state(entry).id())
+                    .collect();
+                let ids = unpaletted(&palette, states.get("data"), SECTION_VOLUME, 4)?;
+                let blocks: Box<[u16; SECTION_VOLUME]> = ids.into_boxed_slice().try_into().ok()?;
+                sections[index] = blocks.iter().any(|&id| id != 0).then_some(blocks);
+            }
+
+            if let Some(saved) = section.get("biomes").and_then(NbtValue::as_compound) {
+                let NbtList::String(palette) = saved.get("palette")?.as_list()? else {
+                    return None;
+                };
+                let palette: Vec<i32> = palette
+                    .iter()
+                    .map(|name| world_gen::biome_id(name))
+                    .collect();
+                let ids = unpaletted(&palette, saved.get("data"), BIOME_VOLUME, 1)?;
+                biomes[index] = ids.try_into().ok()?;
+            }
+        }
+
+        Some(Self::with_heightmaps(sections, biomes))
+    }
+
+    /// A chunk of the given sections and biomes, with its heightmaps
+    /// scanned
+    fn with_heightmaps(
+        sections: Vec<Option<Box<[u16; SECTION_VOLUME]>>>,
+        biomes: Vec<[i32; BIOME_VOLUME]>,
+    ) -> Self {
+        let block_counts = std::array::from_fn(|section| {
+            sections[section].as_ref().map_or(0, |blocks| {
+                blocks.iter().filter(|&&id| id != 0).count() as u16
+            })
+        });
+        let mut chunk = Self {
+            sections,
+            block_counts,
+            biomes,
+            heightmaps: [[MIN_Y - 1; 256]; HeightmapKind::ALL.len()],
+        };
+        for z in 0..16 {
+            for x in 0..16 {
+                for kind in 0..HeightmapKind::ALL.len() {
+                    chunk.rescan_column(kind, x, z, MIN_Y + 16 * SECTION_COUNT as i32 - 1);
+                }
+            }
+        }
+        chunk
+    }
+
+    fn index(x: i32, y: i32, z: i32) -> Option<(usize, usize)> {
+        let section = usize::try_from((y - MIN_Y) >> 4).ok()?;
+        if section >= SECTION_COUNT {
+            return None;
+        }
+        let index = (((y & 15) * 16 + (z & 15)) * 16 + (x & 15)) as usize;
+        Some((section, index))
+    }
+
+    /// Block state at a position (air outside the build height)
+    pub fn block(&self, x: i32, y: i32, z: i32) -> BlockState {
+        Self::index(x, y, z)
+            .and_then(|(section, index)| {
+                self.sections[section]
+                    .as_ref()
+                    .map(|blocks| BlockState(blocks[index]))
+            })
+            .unwrap_or(BlockState::AIR)
+    }
+
+    /// Replace a block, keeping heightmaps up to date. Returns the old state,
+    /// or `None` if `y` is outside the build height.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, state: BlockState) -> Option<BlockState> {
+        let (section, index) = Self::index(x, y, z)?;
+        let blocks = self.sections[section].get_or_insert_with(|| Box::new([0; SECTION_VOLUME]));
+        let old = BlockState(std::mem::replace(&mut blocks[index], state.id()));
+        let count = &mut self.block_counts[section];
+        *count = *count + u16::from(state.id() != 0) - u16::from(old.id() != 0);
+        if *count == 0 {
+            self.sections[section] = None;
+        }
+
+        let column = ((z & 15) * 16 + (x & 15)) as usize;
+        for (kind, heightmap) in HeightmapKind::ALL.into_iter().enumerate() {
+            let top = self.heightmaps[kind][column];
+            if heightmap.matches(state) {
+                self.heightmaps[kind][column] = top.max(y);
+            } else if y == top {
+                self.rescan_column(kind, (x & 15) as usize, (z & 15) as usize, y - 1);
+            }
+        }
+        Some(old)
+    }
+
+    /// Find the highest matching block at or below `from_y`.
+    fn rescan_column(&mut self, kind: usize, x: usize, z: usize, from_y: i32) {
+        let heightmap = HeightmapKind::ALL[kind];
+        let top = (MIN_Y..=from_y)
+            .rev()
+            .find(|&y| heightmap.matches(self.block(x as i32, y, z as i32)))
+            .unwrap_or(MIN_Y - 1);
+        self.heightmaps[kind][z * 16 + x] = top;
+    }
+
+    /// Y of the highest block in a heightmap, `None` for an empty column
+    pub fn height(&self, kind: HeightmapKind, x: i32, z: i32) -> Option<i32> {
+        let index = HeightmapKind::ALL.iter().position(|&k| k == kind)?;
+        let top = self.heightmaps[index][((z & 15) * 16 + (x & 15)) as usize];
+        (top >= MIN_Y).then_some(top)
+    }
+
+    /// Y of the highest motion-blocking block (something to stand on or
+    /// swim in), `None` for a void column
+    pub fn highest_block_at(&self, x: i32, z: i32) -> Option<i32> {
+        self.height(HeightmapKind::MotionBlocking, x, z)
+    }
+
+    /// Biome network ID at a position
+    pub fn biome(&self, x: i32, y: i32, z: i32) -> i32 {
+        let section = ((y - MIN_Y) >> 4).clamp(0, SECTION_COUNT as i32 - 1) as usize;
+        let index = ((((y & 15) >> 2) * 4 + ((z & 15) >> 2)) * 4 + ((x & 15) >> 2)) as usize;
+        self.biomes[section][index]
+    }
+
+    /// Encode the `Level Chunk With Light` payload (without packet ID)
+    pub fn encode(&self, chunk_x: i32, chunk_z: i32) -> eyre::Result<Bytes> {
+        let mut data = Vec::new();
+
+        // Chunk X, Z (Int)
+        data.write_i32::<BigEndian>(chunk_x)?;
+        data.write_i32::<BigEndian>(chunk_z)?;
+
+        // Heightmaps: (type, long array) pairs, entries packed by column
+        write_varint(&mut data, HeightmapKind::ALL.len() as i32)?;
+        for (kind, heights) in HeightmapKind::ALL.into_iter().zip(&self.heightmaps) {
+            write_varint(&mut data, kind as i32)?;
+            LongArray(packed_heightmap(heights)).encode(&mut data)?;
+        }
+
+        // Chunk section data
+        let sections = self.encode_sections()?;
+        write_varint(&mut data, sections.len() as i32)?;
+        data.extend_from_slice(&sections);
+
+        // Block Entities - empty list
+        write_varint(&mut data, 0)?;
+
+        // Light masks: bit index is section index + 1 (because of the extra
+        // section below). Sections 4-24 (Y 0 to Y 320) have sky light,
+        // sections 0-4 (below Y 0) have none, and no section has block light.
+        let sky_mask = BitSet::from_range(5..26);
+        sky_mask.encode(&mut data)?;
+        BitSet::new().encode(&mut data)?;
+        BitSet::from_range(0..5).encode(&mut data)?;
+        BitSet::from_range(0..26).encode(&mut data)?;
+
+        // Sky Light Arrays - full light for each section in the sky mask
+        let sky_section_count = sky_mask.count();
+        write_varint(&mut data, sky_section_count as i32)?;
+
+        let full_light = vec![0xFFu8; 2048];
+        for _ in 0..sky_section_count {
+            write_varint(&mut data, 2048)?;
+            data.extend_from_slice(&full_light);
+        }
+
+        // Block Light Arrays - empty list
+        write_varint(&mut data, 0)?;
+
+        Ok(Bytes::from(data))
+    }
+
+    fn encode_sections(&self) -> eyre::Result<Vec<u8>> {
+        let block_format = PaletteFormat::blocks(BlockState::direct_bits());
+        let biome_format = PaletteFormat::biomes(
+            mc_data::registry("minecraft:worldgen/biome")
+                .map_or(0, mc_data::RegistryData::direct_bits),
+        );
+        let mut data = Vec::new();
+
+        for (section, (blocks, biomes)) in self.sections.iter().zip(&self.biomes).enumerate() {
+            match blocks {
+                Some(blocks) => {
+                    let block_count = self.block_counts[section] as i16;
+                    data.extend_from_slice(&block_count.to_be_bytes());
+                    let states: Vec<i32> = blocks.iter().map(|&id| i32::from(id)).collect();
+                    write_paletted(&mut data, &block_format, &states)?;
+                }
+                None => {
+                    data.extend_from_slice(&0i16.to_be_bytes());
+                    write_paletted(&mut data, &block_format, &[0; SECTION_VOLUME])?;
+                }
+            }
+            write_paletted(&mut data, &biome_format, biomes)?;
+        }
+
+        Ok(data)
+    }
+
+    /// The chunk in Anvil region file format
+    pub fn to_nbt(&self, chunk_x: i32, chunk_z: i32, last_update: i64) -> NbtCompound {
+        let biome_names = mc_data::registry("minecraft:worldgen/biome").map(|r| r.entries);
+        let biome_name = |id: i32| {
+            biome_names
+                .and_then(|names| names.get(usize::try_from(id).ok()?).copied())
+                .unwrap_or("minecraft:plains")
+        };
+
+        let mut sections = Vec::with_capacity(SECTION_COUNT);
+        for (section, (blocks, biomes)) in self.sections.iter().zip(&self.biomes).enumerate() {
+            let ids = blocks
+                .as_deref()
+                .map_or(&[0; SECTION_VOLUME], |blocks| blocks);
+            let (palette, data) = paletted(ids, 4);
+            let palette = palette
+                .into_iter()
+                .map(|id| {
+                    let state = BlockState(id);
+                    let mut entry = NbtCompound::new();
+                    entry.insert("Name", state.block_name().unwrap_or("minecraft:air"));
+                    let properties: Vec<_> = state
+                        .properties()
+                        .map(|(name, value)| (name.to_string(), NbtValue::from(value)))
+                        .collect();
+                    if !properties.is_empty() {
+                        entry.insert("Properties", NbtCompound::from_entries(properties));
+                    }
+                    entry
+                })
+                .collect();
+            let mut block_states = NbtCompound::new();
+            block_states.insert("palette", NbtList::Compound(palette));
+            if let Some(data) = data {
+                block_states.insert("data", nbt_longs(data));
+            }
+
+            let (palette, data) = paletted(biomes, 1);
+            let mut biomes = NbtCompound::new();
+            let palette = palette.into_iter().map(|id| biome_name(id).to_string());
+            biomes.insert("palette", NbtList::String(palette.collect()));
+            if let Some(data) = data {
+                biomes.insert("data", nbt_longs(data));
+            }
+
+            let mut entry = NbtCompound::new();
+            entry.insert("Y", (MIN_Y / 16 + section as i32) as i8);
+            entry.insert("block_states", block_states);
+            entry.insert("biomes", biomes);
+            sections.push(entry);
+        }
+
+        let mut heightmaps = NbtCompound::new();
+        for (kind, heights) in HeightmapKind::ALL.into_iter().zip(&self.heightmaps) {
+            heightmaps.insert(kind.nbt_name(), nbt_longs(packed_heightmap(heights)));
+        }
+
+        let mut chunk = NbtCompound::new();
+        chunk.insert("DataVersion", DATA_VERSION);
+        chunk.insert("xPos", chunk_x);
+        chunk.insert("yPos", MIN_Y / 16);
+        chunk.insert("zPos", chunk_z);
+        chunk.insert("Status", "minecraft:full");
+        chunk.insert("LastUpdate", last_update);
+        chunk.insert("sections", NbtList::Compound(sections));
+        chunk.insert("Heightmaps", heightmaps);
+        // No light is stored, so the game relights the chunk on load
+        chunk.insert("isLightOn", false);
+        chunk
+    }
+}
+
+/// Packed longs as an NBT long array
+fn nbt_longs(longs: Vec<u64>) -> NbtValue {
+    NbtValue::LongArray(longs.into_iter().map(|long| long as i64).collect())
+}
+
+/// A heightmap as sent and stored: heights above `MIN_Y - 1`, packed
+fn packed_heightmap(heights: &[i32; 256]) -> Vec<u64> {
+    let heights = heights.iter().map(|&top| (top + 1 - MIN_Y) as u64);
+    pack_longs(heights, HEIGHTMAP_BITS)
+}
+
+/// Values of a region file paletted container, the inverse of [`paletted`].
+/// `None` if an index is outside the palette.
+fn unpaletted<T: Copy>(
+    palette: &[T],
+    data: Option<&NbtValue>,
+    count: usize,
+    min_bits: u8,
+) -> Option<Vec<T>> {
+    let Some(data) = data else {
+        return palette.first().map(|&value| vec![value; count]);
+    };
+    let NbtValue::LongArray(longs) = data else {
+        return None;
+    };
+    let longs: Vec<u64> = longs.iter().map(|&long| long as u64).collect();
+    let bits = index_bits(palette.len()).max(min_bits);
+    unpack_longs(&longs, bits, count)
+        .into_iter()
+        .map(|index| palette.get(usize::try_from(index).ok()?).copied())
+        .collect()
+}
+
+/// Block state of a saved palette entry, air if the server doesn't know it
+fn palette_state(entry: &NbtCompound) -> BlockState {
+    let Some(mut state) = entry
+        .get("Name")
+        .and_then(NbtValue::as_str)
+        .and_then(|name| BlockState::by_name(name.strip_prefix("minecraft:").unwrap_or(name)))
+    else {
+        return BlockState::AIR;
+    };
+    if let Some(properties) = entry.get("Properties").and_then(NbtValue::as_compound) {
+        for (name, value) in properties.iter() {
+            if let Some(changed) = value
+                .as_str()
+                .and_then(|value| state.with_property(name, value))
+            {
+                state = changed;
+            }
+        }
+    }
+    state
+}
+
+/// Palette and packed indices of a region file paletted container, with
+/// at least `min_bits` per index. A single-entry palette has no data.
+fn paletted<T: Copy + Eq + core::hash::Hash>(
+    values: &[T],
+    min_bits: u8,
+) -> (Vec<T>, Option<Vec<u64>>) {
+    let (palette, indices) = palettize(values);
+    if palette.len() == 1 {
+        return (palette, None);
+    }
+    let bits = index_bits(palette.len()).max(min_bits);
+    (palette, Some(pack_longs(indices.into_iter(), bits)))
+}
+
+// ============================================================================
+// Chunk entities
+// ============================================================================
+
+/// Chunks sent around each player until their view distance changes
+/// (matches the view distance in Login)
+pub const VIEW_DISTANCE: i32 = 8;
+
+/// Global: Accounting for cached chunk encodings ([`ChunkData`])
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ChunkCache {
+    /// Evict encodings of chunks no player can see above this size
+    pub budget_bytes: usize,
+    /// Encoded chunks and their total size, as of the last sweep
+    pub cached_chunks: usize,
+    pub cached_bytes: usize,
+    /// Chunk sends served from the cache
+    pub hits: u64,
+    /// Chunk sends that had to encode first
+    pub misses: u64,
+    /// Encodings dropped by sweeps
+    pub evictions: u64,
+}
+
+impl Default for ChunkCache {
+    fn default() -> Self {
+        Self {
+            budget_bytes: 64 * 1024 * 1024,
+            cached_chunks: 0,
+            cached_bytes: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+}
+
+/// Entity name of the chunk at chunk coordinates
+pub fn chunk_name(chunk_x: i32, chunk_z: i32) -> String {
+    format!("chunk:{}:{}", chunk_x, chunk_z)
+}
+
+/// Framed `Level Chunk With Light` packet of a chunk entity, encoding and
+/// caching it in [`ChunkData`] if needed
+pub fn chunk_packet(chunk: EntityView<'_>) -> Option<Bytes> {
+    let world = chunk.world();
+    if let Some(packet) = chunk.try_get::<&ChunkData>(|data| Bytes::clone(&data.encoded)) {
+        world.get::<&mut ChunkCache>(|cache| cache.hits += 1);
+        return Some(packet);
+    }
+
+    let payload = chunk
+        .try_get::<(&ChunkBlocks, &ChunkPos)>(|(blocks, pos)| blocks.encode(pos.x, pos.z))?
+        .ok()?;
+    let packet = encode_packet(packet_ids::LEVEL_CHUNK, &payload);
+    chunk.set(ChunkData::new(Bytes::clone(&packet)));
+    world.get::<&mut ChunkCache>(|cache| cache.misses += 1);
+    Some(packet)
+}
+
+/// Change a block in a loaded chunk, invalidating the chunk's cached
+/// encoding and map tile, marking the chunk unsaved, journaling the change,
+/// sending the change to everyone who sees the chunk, updating the block's
+/// redstone entity and scheduling fluid ticks around it.
+/// Returns the old state, or `None` if the chunk isn't loaded or `y` is
+/// outside the build height.
+pub fn set_block(
+    world: &WorldRef<'_>,
+    x: i32,
+    y: i32,
+    z: i32,
+    state: BlockState,
+) -> Option<BlockState> {
+    let (chunk, old) = replace_block(world, x, y, z, state)?;
+    if old != state {
+        let pos = BlockPos::new(x, y, z);
+        autosave::mark_unsaved(world, chunk);
+        world.try_get::<&mut Journal>(|journal| journal.record_block(pos, state));
+        redstone::track_block(world, pos, state);
+        fluid::on_block_changed(world, pos);
+    }
+    Some(old)
+}
+
+/// Change a block like [`set_block`] without saving, journaling or
+/// simulating the change, only showing it to viewers. Replays apply
+/// recorded changes this way.
+pub fn show_block(
+    world: &WorldRef<'_>,
+    x: i32,
+    y: i32,
+    z: i32,
+    state: BlockState,
+) -> Option<BlockState> {
+    replace_block(world, x, y, z, state).map(|(_, old)| old)
+}
+
+/// Write a block, invalidate the chunk's cached encoding and map tile and
+/// send the change to its viewers. Returns the chunk and the old state.
+fn replace_block<'a>(
+    world: &WorldRef<'a>,
+    x: i32,
+    y: i32,
+    z: i32,
+    state: BlockState,
+) -> Option<(EntityView<'a>, BlockState)> {
+    let chunk = world.try_lookup_recursive(&chunk_name(x >> 4, z >> 4))?;
+    let old = chunk
+        .try_get::<&mut ChunkBlocks>(|blocks| blocks.set_block(x, y, z, state))
+        .flatten()?;
+    if old != state {
+        chunk.remove::<ChunkData>();
+        chunk.remove::<MapTile>();
+        for viewer in chunk_viewers(world, chunk.id()) {
+            world
+                .entity_from_id(viewer)
+                .try_get::<&mut PacketBuffer>(|buffer| {
+                    send_block_update(buffer, x, y, z, i32::from(state.id()));
+                });
+        }
+    }
+    Some((chunk, old))
+}
+
+/// Singleton: block changes requested by systems holding a player's packet
+/// buffer (which [`set_block`] may write to), applied by
+/// [`apply_block_changes`]
+#[derive(Component, Default)]
+pub struct PendingBlockChanges {
+    pub changes: Vec<(BlockPos, BlockState)>,
+}
+
+/// Queue a block change for the next [`apply_block_changes`]. Returns
+/// `false` if the chunk isn't loaded or `y` is outside the build height.
+pub fn queue_block_change(world: &WorldRef<'_>, pos: BlockPos, state: BlockState) -> bool {
+    let in_height = (MIN_Y..MIN_Y + 16 * SECTION_COUNT as i32).contains(&pos.y);
+    if !in_height || block_at(world, pos).is_none() {
+        return false;
+    }
+    world.get::<&mut PendingBlockChanges>(|pending| pending.changes.push((pos, state)));
+    true
+}
+
+/// Apply queued block changes in order
+pub fn apply_block_changes(world: &WorldRef<'_>, pending: &mut PendingBlockChanges) {
+    for (pos, state) in pending.changes.drain(..) {
+        set_block(world, pos.x, pos.y, pos.z, state);
+    }
+}
+
+/// Block state at a world position, if its chunk is loaded
+pub fn block_at(world: &WorldRef<'_>, pos: BlockPos) -> Option<BlockState> {
+    world
+        .try_lookup_recursive(&chunk_name(pos.x >> 4, pos.z >> 4))?
+        .try_get::<&ChunkBlocks>(|blocks| blocks.block(pos.x, pos.y, pos.z))
+}
+
+/// Highest motion-blocking block at a world column, if its chunk is loaded
+pub fn highest_block_at(world: &WorldRef<'_>, x: i32, z: i32) -> Option<i32> {
+    world
+        .try_lookup_recursive(&chunk_name(x >> 4, z >> 4))?
+        .try_get::<&ChunkBlocks>(|blocks| blocks.highest_block_at(x, z))
+        .flatten()
+}
+
+/// Recount the cached encodings and, when over budget, drop those of chunks
+/// no player sees (they're re-encoded on their next send)
+pub fn sweep_chunk_cache(world: &WorldRef<'_>, cache: &mut ChunkCache) {
+    let mut seen = HashSet::new();
+    world
+        .query::<()>()
+        .with(Player)
+        .build()
+        .each_entity(|player, ()| {
+            player.each_target(Sees, |chunk| {
+                seen.insert(chunk.id());
+            });
+        });
+
+    let mut cached = Vec::new();
+    world
+        .query::<&ChunkData>()
+        .with(ChunkLoaded)
+        .build()
+        .each_entity(|entity, data| {
+            cached.push((entity.id(), data.encoded.len()));
+        });
+
+    cache.cached_chunks = cached.len();
+    cache.cached_bytes = cached.iter().map(|&(_, len)| len).sum();
+    if cache.cached_bytes <= cache.budget_bytes {
+        return;
+    }
+
+    for (id, len) in cached {
+        if !seen.contains(&id) {
+            world.entity_from_id(id).remove::<ChunkData>();
+            cache.cached_chunks -= 1;
+            cache.cached_bytes -= len;
+            cache.evictions += 1;
+        }
+    }
+}
+
+// ============================================================================
+// Interest management
+// ============================================================================
+
+/// Component: Players whose client has this chunk loaded, the reverse of
+/// their `(Sees, chunk)` pairs, so block changes find their viewers
+/// without a query
+#[derive(Component, Default)]
+pub struct ChunkViewers {
+    players: Vec<Entity>,
+}
+
+/// Players whose client has a chunk loaded
+pub fn chunk_viewers(world: &WorldRef<'_>, chunk: Entity) -> Vec<Entity> {
+    world
+        .entity_from_id(chunk)
+        .try_get::<&ChunkViewers>(|viewers| viewers.players.clone())
+        .unwrap_or_default()
+}
+
+/// Record that a player's client has a chunk loaded
+fn see_chunk(player: EntityView<'_>, chunk: EntityView<'_>) {
+    player.add((Sees, chunk));
+    chunk.try_get::<&mut ChunkViewers>(|viewers| viewers.players.push(player.id()));
+}
+
+/// Record that a player's client unloaded a chunk
+fn forget_chunk(player: EntityView<'_>, chunk: EntityView<'_>) {
+    player.remove((Sees, chunk));
+    chunk.try_get::<&mut ChunkViewers>(|viewers| {
+        viewers.players.retain(|&viewer| viewer != player.id());
+    });
+}
+
+/// Drop a leaving player from the viewers of every chunk they see
+pub fn forget_all_chunks(player: EntityView<'_>) {
+    player.each_target(Sees, |chunk| {
+        chunk.try_get::<&mut ChunkViewers>(|viewers| {
+            viewers.players.retain(|&viewer| viewer != player.id());
+        });
+    });
+}
+
+/// Keep a player's loaded chunks in sync with their position: send chunks
+/// entering their view distance and forget those leaving it, tracking each
+/// as a `(Sees, chunk)` pair. Does nothing while the player stays in the
+/// same chunk, unless `force` is set.
+pub fn update_interest(
+    player: EntityView<'_>,
+    buffer: &mut PacketBuffer,
+    pos: &Position,
+    center: &mut ChunkPosition,
+    force: bool,
+) {
+    let (cx, cz) = pos.chunk_pos();
+    if !force && (center.x, center.z) == (cx, cz) {
+        return;
+    }
+    *center = ChunkPosition::new(cx, cz);
+    send_set_center_chunk(buffer, cx, cz);
+
+    let world = player.world();
+    let radius = view_distance::sent(player);
+    let in_view = |x: i32, z: i32| (x - cx).abs() <= radius && (z - cz).abs() <= radius;
+
+    // Forget chunks that left the view
+    let mut seen = HashSet::new();
+    player.each_target(Sees, |chunk| {
+        seen.insert(chunk.id());
+    });
+    for &chunk in &seen {
+        let chunk = world.entity_from_id(chunk);
+        let Some(chunk_pos) = chunk.try_get::<&ChunkPos>(|p| *p) else {
+            continue;
+        };
+        if !in_view(chunk_pos.x, chunk_pos.z) {
+            send_forget_chunk(buffer, chunk_pos.x, chunk_pos.z);
+            forget_chunk(player, chunk);
+        }
+    }
+
+    // Send chunks that entered it
+    let mut packets = Vec::new();
+    for x in cx - radius..=cx + radius {
+        for z in cz - radius..=cz + radius {
+            let Some(chunk) = world.try_lookup_recursive(&chunk_name(x, z)) else {
+                continue;
+            };
+            if seen.contains(&chunk.id()) {
+                continue;
+            }
+            if let Some(packet) = chunk_packet(chunk) {
+                packets.push(packet);
+                see_chunk(player, chunk);
+            }
+        }
+    }
+    if !packets.is_empty() {
+        send_chunks_to_buffer(buffer, &packets);
+    }
+}
@@ -0,0 +1,127 @@
+//! Server-side collision between players and entities
+//!
+//! Vanilla leaves pushing to the client, so players pass through each other
+//! unless their client says otherwise. Minigames often need the server to
+//! decide, so two game rules turn server-side resolution on, both off by
+//! default:
+//!
+//! - `playerEntityCollision`: players are pushed out of entities with a
+//!   [`Collider`]
+//! - `playerCollision`: overlapping players are pushed apart, each half the
+//!   way
+//!
+//! Hitboxes are [`Aabb`]s standing on the entity's position, separated along
+//! the horizontal axis they overlap least on ([`Aabb::push_out`]). A pushed
+//! player gets the push as a relative Player Position packet, which keeps
+//! their rotation and velocity; bots are just moved.
+
+use flecs_ecs::prelude::*;
+use mc_data::Aabb;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Bot;
+use crate::components::{InPlayState, PacketBuffer, Player, Position};
+use crate::game_rules::{GameRuleValue, GameRules};
+use crate::protocol::send_player_push;
+
+/// Whether players are pushed out of entities with a [`Collider`]
+pub const PLAYER_ENTITY_COLLISION: &str = "playerEntityCollision";
+/// Whether overlapping players are pushed apart
+pub const PLAYER_COLLISION: &str = "playerCollision";
+
+/// Player hitbox width in blocks
+pub const PLAYER_WIDTH: f64 = 0.6;
+/// Player hitbox height in blocks
+pub const PLAYER_HEIGHT: f64 = 1.8;
+
+/// Entity: Hitbox players can't walk into, standing on its position
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Collider {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Register the collision game rules
+pub fn register_rules(rules: &mut GameRules) {
+    rules.register(PLAYER_ENTITY_COLLISION, GameRuleValue::Bool(false));
+    rules.register(PLAYER_COLLISION, GameRuleValue::Bool(false));
+}
+
+fn player_box(pos: &Position) -> Aabb {
+    Aabb::standing([pos.x, pos.y, pos.z], PLAYER_WIDTH, PLAYER_HEIGHT)
+}
+
+/// Push players out of colliders and each other, as the game rules say
+pub fn resolve(world: &WorldRef<'_>) {
+    let (with_entities, with_players) = world.get::<&GameRules>(|rules| {
+        (
+            rules.bool(PLAYER_ENTITY_COLLISION),
+            rules.bool(PLAYER_COLLISION),
+        )
+    });
+    if !with_entities && !with_players {
+        return;
+    }
+
+    // Players in the world: in play, or bots
+    let mut players: Vec<(Entity, Aabb)> = Vec::new();
+    world
+        .query::<&Position>()
+        .with(Player)
+        .build()
+        .each_entity(|entity, pos| {
+            if entity.has(InPlayState) || entity.has(Bot) {
+                players.push((entity.id(), player_box(pos)));
+            }
+        });
+    // Horizontal push of each player
+    let mut pushes = vec![(0.0, 0.0); players.len()];
+
+    if with_players {
+        for i in 0..players.len() {
+            for j in i + 1..players.len() {
+                if let Some([x, _, z]) = players[i].1.push_out(&players[j].1) {
+                    pushes[i].0 += x / 2.0;
+                    pushes[i].1 += z / 2.0;
+                    pushes[j].0 -= x / 2.0;
+                    pushes[j].1 -= z / 2.0;
+                }
+            }
+        }
+    }
+
+    if with_entities {
+        let mut colliders = Vec::new();
+        world
+            .query::<(&Position, &Collider)>()
+            .without(Player)
+            .build()
+            .each(|(pos, collider)| {
+                colliders.push(Aabb::standing(
+                    [pos.x, pos.y, pos.z],
+                    collider.width,
+                    collider.height,
+                ));
+            });
+        for ((_, hitbox), total) in players.iter().zip(&mut pushes) {
+            for collider in &colliders {
+                if let Some([x, _, z]) = hitbox.push_out(collider) {
+                    total.0 += x;
+                    total.1 += z;
+                }
+            }
+        }
+    }
+
+    for ((player, _), (x, z)) in players.into_iter().zip(pushes) {
+        if x == 0.0 && z == 0.0 {
+            continue;
+        }
+        let player = world.entity_from_id(player);
+        player.get::<&mut Position>(|pos| {
+            pos.x += x;
+            pos.z += z;
+        });
+        player.try_get::<&mut PacketBuffer>(|buffer| send_player_push(buffer, x, z));
+    }
+}
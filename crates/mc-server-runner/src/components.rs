@@ -1,12 +1,18 @@
 //! All ECS components for the Minecraft server
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use bytes::Bytes;
 use crossbeam_channel::{Receiver, Sender};
 use flecs_ecs::prelude::*;
+use persist::Schema;
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -17,6 +23,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug)]
 pub struct IncomingPacket {
     pub connection_id: u64,
+    /// Address the connection comes from
+    pub addr: SocketAddr,
     pub packet_id: i32,
     pub data: Bytes,
 }
@@ -27,11 +35,14 @@ pub struct DisconnectEvent {
     pub connection_id: u64,
 }
 
-/// Packet to send via async network layer
+/// Packets to send via async network layer
 #[derive(Debug)]
 pub struct OutgoingPacket {
     pub connection_id: u64,
-    pub data: Bytes,
+    /// A tick's length-prefixed packets, written with one vectored write
+    pub packets: Vec<Bytes>,
+    /// Close the connection once `packets` are written
+    pub close: bool,
 }
 
 /// Global: Receiver for incoming packets from async layer
@@ -73,6 +84,10 @@ impl ConnectionId {
     }
 }
 
+/// Address a connection comes from
+#[derive(Component, Debug, Clone, Copy)]
+pub struct RemoteAddr(pub SocketAddr);
+
 /// Current protocol state of the connection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash, Serialize, Deserialize)]
 #[repr(u8)]
@@ -93,6 +108,8 @@ pub struct ProtocolState(pub ConnectionState);
 pub struct PacketBuffer {
     pub incoming: VecDeque<(i32, Bytes)>,
     pub outgoing: VecDeque<Bytes>,
+    /// Close the connection after `outgoing` is sent
+    pub closing: bool,
 }
 
 impl PacketBuffer {
@@ -116,6 +133,11 @@ impl PacketBuffer {
     pub fn pop_outgoing(&mut self) -> Option<Bytes> {
         self.outgoing.pop_front()
     }
+
+    /// Close the connection once the packets queued so far are sent
+    pub fn close(&mut self) {
+        self.closing = true;
+    }
 }
 
 /// Global: Temporary buffer for packets arriving before connection entity is ready.
@@ -151,6 +173,12 @@ pub struct Name {
 #[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Uuid(pub u128);
 
+impl From<Uuid> for u128 {
+    fn from(uuid: Uuid) -> Self {
+        uuid.0
+    }
+}
+
 /// Entity ID assigned by server (for protocol)
 #[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct EntityId {
@@ -158,7 +186,7 @@ pub struct EntityId {
 }
 
 /// Player position in world
-#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Schema)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -180,12 +208,65 @@ impl Position {
 
     #[must_use]
     pub fn chunk_pos(&self) -> (i32, i32) {
-        ((self.x as i32) >> 4, (self.z as i32) >> 4)
+        ((self.x.floor() as i32) >> 4, (self.z.floor() as i32) >> 4)
+    }
+}
+
+/// Entity velocity in blocks per tick
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Velocity {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Velocity {
+    #[must_use]
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Integer block coordinates
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    #[must_use]
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    #[must_use]
+    pub const fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// Pack into a protocol position: X (26 bits), Z (26 bits), Y (12 bits)
+    #[must_use]
+    pub const fn packed(self) -> i64 {
+        ((self.x as i64 & 0x3FF_FFFF) << 38)
+            | ((self.z as i64 & 0x3FF_FFFF) << 12)
+            | (self.y as i64 & 0xFFF)
+    }
+
+    /// Unpack a protocol position: X (26 bits), Z (26 bits), Y (12 bits)
+    #[must_use]
+    pub const fn from_packed(packed: i64) -> Self {
+        Self::new(
+            (packed >> 38) as i32,
+            (packed << 52 >> 52) as i32,
+            (packed << 26 >> 38) as i32,
+        )
     }
 }
 
 /// Player rotation
-#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Schema)]
 pub struct Rotation {
     pub yaw: f32,
     pub pitch: f32,
@@ -225,6 +306,107 @@ impl GameMode {
     pub const SPECTATOR: Self = Self { value: 3 };
 }
 
+/// Client brand reported on the `minecraft:brand` channel (e.g. "vanilla", "fabric")
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct ClientBrand {
+    pub value: String,
+}
+
+/// Client language (e.g. "en_us") reported in Client Information
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct ClientLocale {
+    pub value: String,
+}
+
+/// Round-trip time measured from keep-alive responses
+#[derive(Component, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Latency {
+    /// Smoothed RTT in milliseconds (0 until the first keep-alive returns)
+    pub ms: i32,
+    /// Number of keep-alive round-trips measured
+    pub samples: u32,
+}
+
+impl Latency {
+    /// Fold in a new RTT sample, weighting it 1/4 like vanilla
+    pub fn record(&mut self, rtt_ms: i32) {
+        self.ms = if self.samples == 0 {
+            rtt_ms
+        } else {
+            (self.ms * 3 + rtt_ms) / 4
+        };
+        self.samples += 1;
+    }
+}
+
+/// One source's bid for the action bar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HudEntry {
+    pub text: String,
+    /// Highest priority is shown
+    pub priority: i32,
+    /// World age at which the entry disappears (`None` = until cleared)
+    pub expires_at: Option<i64>,
+}
+
+/// Action bar text for a player, keyed by source (e.g. "debug", "combat")
+///
+/// Any system can write an entry; the play systems render the highest-priority
+/// live entry, breaking ties by source name.
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HudText {
+    pub entries: BTreeMap<String, HudEntry>,
+}
+
+impl HudText {
+    /// Priority of the built-in position/TPS display
+    pub const PRIORITY_DEBUG: i32 = 0;
+
+    /// Set `source`'s text, replacing its previous entry.
+    ///
+    /// `ttl` is in ticks from `now`; `None` keeps the entry until cleared.
+    pub fn set(
+        &mut self,
+        source: impl Into<String>,
+        text: impl Into<String>,
+        priority: i32,
+        ttl: Option<i64>,
+        now: i64,
+    ) {
+        self.entries.insert(
+            source.into(),
+            HudEntry {
+                text: text.into(),
+                priority,
+                expires_at: ttl.map(|ttl| now + ttl),
+            },
+        );
+    }
+
+    /// Remove `source`'s entry.
+    pub fn clear(&mut self, source: &str) {
+        self.entries.remove(source);
+    }
+
+    /// Drop entries that expired at or before `now`.
+    pub fn prune(&mut self, now: i64) {
+        self.entries
+            .retain(|_, entry| entry.expires_at.is_none_or(|at| at > now));
+    }
+
+    /// Text to show at `now`: the highest-priority live entry.
+    #[must_use]
+    pub fn current(&self, now: i64) -> Option<&str> {
+        self.entries
+            .values()
+            .filter(|entry| entry.expires_at.is_none_or(|at| at > now))
+            // max_by_key keeps the last maximum, so reverse to prefer the first source
+            .rev()
+            .max_by_key(|entry| entry.priority)
+            .map(|entry| entry.text.as_str())
+    }
+}
+
 /// Tag: Player needs initial spawn chunks sent
 #[derive(Component, Default)]
 pub struct NeedsSpawnChunks;
@@ -233,21 +415,9 @@ pub struct NeedsSpawnChunks;
 #[derive(Component, Default)]
 pub struct InPlayState;
 
-/// Global: Entity ID counter for protocol
-#[derive(Component)]
-pub struct EntityIdCounter(pub Arc<AtomicI64>);
-
-impl Default for EntityIdCounter {
-    fn default() -> Self {
-        Self(Arc::new(AtomicI64::new(1)))
-    }
-}
-
-impl EntityIdCounter {
-    pub fn next(&self) -> i32 {
-        self.0.fetch_add(1, Ordering::Relaxed) as i32
-    }
-}
+/// Tag: Player has been added to everyone's tab list
+#[derive(Component, Default)]
+pub struct InTabList;
 
 // ============================================================================
 // Chunk Components
@@ -279,9 +449,13 @@ impl ChunkPos {
     }
 }
 
-/// Pre-encoded chunk data for network transmission
+/// Cached network encoding of a chunk
+///
+/// Removed when the chunk's blocks change or the cache is swept; see
+/// `chunk::chunk_packet`.
 #[derive(Component)]
 pub struct ChunkData {
+    /// Framed `Level Chunk With Light` packet, ready to send
     pub encoded: Bytes,
 }
 
@@ -296,10 +470,74 @@ impl ChunkData {
 #[derive(Component, Default)]
 pub struct ChunkLoaded;
 
+/// Relation tag: player's client has this chunk loaded.
+/// Used as: player.add((Sees, chunk_entity))
+#[derive(Component, Default)]
+pub struct Sees;
+
 // ============================================================================
 // Server Config (Global)
 // ============================================================================
 
+/// Environment variable naming the world directory
+pub const WORLD_DIR_ENV: &str = "RGB_WORLD_DIR";
+/// Environment variable setting the RCON password
+pub const RCON_PASSWORD_ENV: &str = "RGB_RCON_PASSWORD";
+/// Environment variable setting the RCON port
+pub const RCON_PORT_ENV: &str = "RCON_PORT";
+/// Environment variable setting the spawn protection radius
+pub const SPAWN_PROTECTION_ENV: &str = "RGB_SPAWN_PROTECTION";
+/// Environment variable setting the catch-up policy, e.g. `burst:40`
+pub const CATCH_UP_ENV: &str = "RGB_CATCH_UP";
+/// Environment variable naming the skript directory
+#[cfg(feature = "reload")]
+pub const SKRIPT_DIR_ENV: &str = "RGB_SKRIPT_DIR";
+/// Environment variable naming the dynamic modules directory
+#[cfg(feature = "reload")]
+pub const MODULES_DIR_ENV: &str = "RGB_MODULES_DIR";
+/// Environment variable listing operators, comma-separated
+#[cfg(feature = "reload")]
+pub const OPERATORS_ENV: &str = "RGB_OPERATORS";
+
+/// What the game loop does with ticks it missed by falling behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CatchUpPolicy {
+    /// Run up to `max_ticks` missed ticks back to back without sleeping
+    /// and drop the rest (`burst:N`); `burst:0` never catches up
+    Burst { max_ticks: u32 },
+    /// Run one tick that moves the world time forward by up to
+    /// `max_ticks` missed ticks too and drop the rest (`skip:N`)
+    Skip { max_ticks: u32 },
+}
+
+impl FromStr for CatchUpPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mode, max_ticks) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected burst:<ticks> or skip:<ticks>, got {s}"))?;
+        let max_ticks = max_ticks
+            .parse()
+            .map_err(|_| format!("invalid tick count {max_ticks}"))?;
+        match mode {
+            "burst" => Ok(Self::Burst { max_ticks }),
+            "skip" => Ok(Self::Skip { max_ticks }),
+            _ => Err(format!("unknown catch-up mode {mode}")),
+        }
+    }
+}
+
+impl fmt::Display for CatchUpPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Burst { max_ticks } => write!(f, "burst:{max_ticks}"),
+            Self::Skip { max_ticks } => write!(f, "skip:{max_ticks}"),
+        }
+    }
+}
+
 /// Global: Server configuration
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -307,6 +545,115 @@ pub struct ServerConfig {
     pub max_players: i32,
     /// Server description shown in server list
     pub motd: String,
+    /// Server brand sent on the `minecraft:brand` channel (shown in the F3 screen)
+    pub brand: String,
+    /// Hide player names from the server list (the online count is still shown)
+    pub hide_player_sample: bool,
+    /// Record all packets to this NDJSON file (overridden by `RGB_PACKET_LOG`)
+    pub packet_log: Option<String>,
+    /// Save changed chunks to this world directory (overridden by
+    /// `RGB_WORLD_DIR`); chunks aren't saved if unset
+    pub world_dir: Option<String>,
+    /// Port of the RCON server (overridden by `RCON_PORT`)
+    pub rcon_port: u16,
+    /// RCON password (overridden by `RGB_RCON_PASSWORD`); RCON is off if
+    /// unset
+    pub rcon_password: Option<String>,
+    /// Blocks around spawn only claim owners may build in (overridden by
+    /// `RGB_SPAWN_PROTECTION`); 0 turns spawn protection off
+    pub spawn_protection: u32,
+    /// What to do with missed ticks when the server falls behind
+    /// (overridden by `RGB_CATCH_UP`)
+    pub catch_up: CatchUpPolicy,
+    /// Load `.sk` skripts from this directory (overridden by
+    /// `RGB_SKRIPT_DIR`); none are loaded if unset
+    pub skript_dir: Option<String>,
+    /// Load dynamic modules from this directory (overridden by
+    /// `RGB_MODULES_DIR`); none are loaded if unset
+    pub modules_dir: Option<String>,
+    /// Names of players who may run operator commands like `/reload`
+    /// (overridden by `RGB_OPERATORS`)
+    pub operators: Vec<String>,
+}
+
+impl ServerConfig {
+    /// World directory from `RGB_WORLD_DIR` or [`Self::world_dir`]
+    pub fn world_dir_path(&self) -> Option<PathBuf> {
+        std::env::var(WORLD_DIR_ENV)
+            .ok()
+            .or_else(|| self.world_dir.clone())
+            .map(PathBuf::from)
+    }
+
+    /// RCON port from `RCON_PORT` or [`Self::rcon_port`]
+    pub fn rcon_port(&self) -> u16 {
+        std::env::var(RCON_PORT_ENV)
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(self.rcon_port)
+    }
+
+    /// RCON password from `RGB_RCON_PASSWORD` or [`Self::rcon_password`],
+    /// if RCON is enabled
+    pub fn rcon_password(&self) -> Option<String> {
+        std::env::var(RCON_PASSWORD_ENV)
+            .ok()
+            .or_else(|| self.rcon_password.clone())
+            .filter(|password| !password.is_empty())
+    }
+
+    /// Spawn protection radius from `RGB_SPAWN_PROTECTION` or
+    /// [`Self::spawn_protection`]
+    pub fn spawn_protection(&self) -> u32 {
+        std::env::var(SPAWN_PROTECTION_ENV)
+            .ok()
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(self.spawn_protection)
+    }
+
+    /// Catch-up policy from `RGB_CATCH_UP` or [`Self::catch_up`]
+    pub fn catch_up(&self) -> CatchUpPolicy {
+        let Ok(policy) = std::env::var(CATCH_UP_ENV) else {
+            return self.catch_up;
+        };
+        policy.parse().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring {CATCH_UP_ENV}: {e}");
+            self.catch_up
+        })
+    }
+
+    /// Skript directory from `RGB_SKRIPT_DIR` or [`Self::skript_dir`]
+    #[cfg(feature = "reload")]
+    pub fn skript_dir(&self) -> Option<PathBuf> {
+        std::env::var(SKRIPT_DIR_ENV)
+            .ok()
+            .or_else(|| self.skript_dir.clone())
+            .map(PathBuf::from)
+    }
+
+    /// Modules directory from `RGB_MODULES_DIR` or [`Self::modules_dir`]
+    #[cfg(feature = "reload")]
+    pub fn modules_dir(&self) -> Option<PathBuf> {
+        std::env::var(MODULES_DIR_ENV)
+            .ok()
+            .or_else(|| self.modules_dir.clone())
+            .map(PathBuf::from)
+    }
+
+    /// Whether the player `name` is an operator, from `RGB_OPERATORS` or
+    /// [`Self::operators`]. Names match case-insensitively, like logins.
+    #[cfg(feature = "reload")]
+    pub fn is_operator(&self, name: &str) -> bool {
+        match std::env::var(OPERATORS_ENV) {
+            Ok(operators) => operators
+                .split(',')
+                .any(|operator| operator.trim().eq_ignore_ascii_case(name)),
+            Err(_) => self
+                .operators
+                .iter()
+                .any(|operator| operator.eq_ignore_ascii_case(name)),
+        }
+    }
 }
 
 impl Default for ServerConfig {
@@ -314,6 +661,18 @@ impl Default for ServerConfig {
         Self {
             max_players: 20_000,
             motd: "A Rust Minecraft Server (Flecs ECS)".to_string(),
+            brand: "rgb".to_string(),
+            hide_player_sample: false,
+            packet_log: None,
+            world_dir: Some("world".to_string()),
+            rcon_port: 25575,
+            rcon_password: None,
+            spawn_protection: 0,
+            // Two seconds, after which vanilla gives up catching up too
+            catch_up: CatchUpPolicy::Burst { max_ticks: 40 },
+            skript_dir: None,
+            modules_dir: None,
+            operators: Vec::new(),
         }
     }
 }
@@ -339,10 +698,19 @@ impl Default for WorldTime {
 }
 
 impl WorldTime {
-    /// Tick the world time forward
-    pub fn tick(&mut self) {
-        self.world_age += 1;
-        self.time_of_day = (self.time_of_day + 1) % 24000;
+    /// Tick the world time forward; the time of day only advances with the
+    /// daylight cycle
+    pub fn tick(&mut self, daylight_cycle: bool) {
+        self.skip(1, daylight_cycle);
+    }
+
+    /// Move the world time forward by several ticks at once
+    pub fn skip(&mut self, ticks: u32, daylight_cycle: bool) {
+        let ticks = i64::from(ticks);
+        self.world_age += ticks;
+        if daylight_cycle {
+            self.time_of_day = (self.time_of_day + ticks) % 24000;
+        }
     }
 }
 
@@ -355,6 +723,16 @@ pub struct TpsTracker {
     pub tps_15s: f32,
     /// TPS with 1-minute smoothing
     pub tps_1m: f32,
+    /// Ticks the game loop was behind when this tick started
+    pub behind: u32,
+    /// Missed ticks run back to back since startup
+    pub caught_up: u64,
+    /// Missed ticks the world time was moved past since startup
+    pub skipped: u64,
+    /// Missed ticks given up on since startup
+    pub dropped: u64,
+    /// Tick time percentiles over the last minute
+    pub mspt: Mspt,
 }
 
 impl Default for TpsTracker {
@@ -363,6 +741,11 @@ impl Default for TpsTracker {
             tps_5s: 20.0,
             tps_15s: 20.0,
             tps_1m: 20.0,
+            behind: 0,
+            caught_up: 0,
+            skipped: 0,
+            dropped: 0,
+            mspt: Mspt::default(),
         }
     }
 }
@@ -386,6 +769,107 @@ impl TpsTracker {
     }
 }
 
+/// Milliseconds per tick at a few percentiles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mspt {
+    pub p50: f32,
+    pub p95: f32,
+    pub p99: f32,
+}
+
+/// Global: How long recent ticks took, as a sliding histogram
+///
+/// TPS averages hide the odd slow tick; tick time percentiles don't. Ticks
+/// are counted in 0.1 ms buckets over the last [`TickTimes::WINDOW`] ticks,
+/// and ticks over [`TickTimes::MAX_MS`] count as that long.
+#[derive(Component, Debug, Clone)]
+pub struct TickTimes {
+    /// Bucket of each tick in the window, oldest first
+    window: VecDeque<u16>,
+    /// Ticks in the window per bucket
+    counts: Vec<u32>,
+}
+
+impl Default for TickTimes {
+    fn default() -> Self {
+        Self {
+            window: VecDeque::with_capacity(Self::WINDOW),
+            counts: vec![0; Self::BUCKETS],
+        }
+    }
+}
+
+impl TickTimes {
+    /// One minute at 20 TPS
+    pub const WINDOW: usize = 1200;
+    /// Longest tick time told apart
+    pub const MAX_MS: f32 = 250.0;
+    const BUCKET_MS: f32 = 0.1;
+    const BUCKETS: usize = (Self::MAX_MS / Self::BUCKET_MS) as usize;
+
+    /// Add a tick, dropping the oldest once the window is full
+    pub fn record(&mut self, tick: Duration) {
+        if self.window.len() == Self::WINDOW
+            && let Some(oldest) = self.window.pop_front()
+        {
+            self.counts[usize::from(oldest)] -= 1;
+        }
+        let ms = tick.as_secs_f32() * 1000.0;
+        let bucket = ((ms / Self::BUCKET_MS) as usize).min(Self::BUCKETS - 1);
+        self.counts[bucket] += 1;
+        self.window.push_back(bucket as u16);
+    }
+
+    /// Tick time in milliseconds that `p` (0 to 1) of the window's ticks
+    /// stayed within, rounded up to the bucket
+    pub fn percentile(&self, p: f32) -> f32 {
+        let rank = ((self.window.len() as f32 * p).ceil() as u32).max(1);
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return (bucket + 1) as f32 * Self::BUCKET_MS;
+            }
+        }
+        0.0
+    }
+
+    /// Median, 95th and 99th percentile tick times
+    pub fn mspt(&self) -> Mspt {
+        Mspt {
+            p50: self.percentile(0.50),
+            p95: self.percentile(0.95),
+            p99: self.percentile(0.99),
+        }
+    }
+}
+
 /// Global: Delta time for current tick
 #[derive(Component, Debug, Clone, Copy, Default)]
 pub struct DeltaTime(pub f32);
+
+/// Global: Whether the game loop keeps running, shared with the signal
+/// handler. Cleared to shut the server down after the current tick.
+#[derive(Component, Debug, Clone)]
+pub struct Running(Arc<AtomicBool>);
+
+impl Running {
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn stop(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Default for Running {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,363 @@
+//! Admin console on stdin
+//!
+//! A thread reads stdin lines into a channel. The game loop runs them with
+//! [`Console::process`] between ticks, when no system is running, so a
+//! command sees and changes the world like a system would.
+//!
+//! Besides the chat commands (run as a `console` entity), the console has:
+//! - `stop`: shut the server down
+//! - `list`: online players
+//! - `query <terms>`: entities matching a query DSL expression, with the
+//!   values of the named serializable components
+//! - `bot spawn <name>`, `bot <name> move|look|break <x> <y> <z>`,
+//!   `bot <name> chat <message>`, `bot <name> despawn`: drive a
+//!   [`BotController`]
+//! - `viewdistance`, `viewdistance <player> <chunks>|reset`: show the view
+//!   distance, or override it for a player
+//! - `reload skripts|modules|config` (with the `reload` feature): see
+//!   [`reload`](crate::reload)
+//! - `more`: the next page of the last long output
+//!
+//! Each command runs in a [`history_scope`] labelled with the command line,
+//! so everything it changes shows up as one transaction in history.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::io::BufRead;
+use std::thread;
+
+use crossbeam_channel::{Receiver, unbounded};
+use flecs_ecs::prelude::*;
+use flecs_history::{SerializeInfo, history_scope};
+use query_dsl::{Operator, TermKind, parse_query};
+use tracing::error;
+
+use crate::bot::BotController;
+use crate::components::{BlockPos, Name, Player, Position};
+#[cfg(feature = "reload")]
+use crate::reload::Reloadables;
+use crate::systems::run_command;
+use crate::view_distance::{
+    MAX_VIEW_DISTANCE, MIN_VIEW_DISTANCE, ViewDistance, ViewDistanceOverride,
+};
+
+/// Lines printed before the console waits for `more`
+pub const PAGE_SIZE: usize = 20;
+
+/// What the game loop should do after processing console input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleAction {
+    Continue,
+    Stop,
+}
+
+pub struct Console {
+    lines: Receiver<String>,
+    /// Output lines past the current page
+    pending: VecDeque<String>,
+    /// Entity chat commands run as
+    executor: Entity,
+    /// What `reload` reloads
+    #[cfg(feature = "reload")]
+    reloadables: Reloadables,
+}
+
+impl Console {
+    /// Start reading stdin on a thread
+    pub fn start(world: &World, #[cfg(feature = "reload")] reloadables: Reloadables) -> Self {
+        let (tx, lines) = unbounded();
+        thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                match line {
+                    Ok(line) => {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to read console input: {e}");
+                        break;
+                    }
+                }
+            }
+        });
+
+        let executor = world
+            .entity_named("console")
+            .set(Name {
+                value: "Console".to_string(),
+            })
+            .id();
+        Self {
+            lines,
+            pending: VecDeque::new(),
+            executor,
+            #[cfg(feature = "reload")]
+            reloadables,
+        }
+    }
+
+    /// Run every line entered since the last call, then the `reload`s
+    /// operators queued during the tick
+    pub fn process(&mut self, world: &World) -> ConsoleAction {
+        #[cfg(feature = "reload")]
+        self.reloadables.run_queued(world);
+        while let Ok(line) = self.lines.try_recv() {
+            match line.trim() {
+                "" => continue,
+                "more" => {
+                    self.print_page();
+                    continue;
+                }
+                _ => {}
+            }
+            let (output, action) = self.execute(world, &line);
+            self.print(output);
+            if action == ConsoleAction::Stop {
+                return action;
+            }
+        }
+        ConsoleAction::Continue
+    }
+
+    /// Run a console command, returning its output lines
+    pub fn execute(&mut self, world: &World, line: &str) -> (Vec<String>, ConsoleAction) {
+        let line = line.trim();
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        if command == "stop" {
+            return (vec!["Stopping the server".to_string()], ConsoleAction::Stop);
+        }
+        // Reloading isn't a change to the world's entities, so it's not
+        // recorded as a transaction
+        #[cfg(feature = "reload")]
+        if command == "reload" {
+            let output = self
+                .reloadables
+                .run(world, rest)
+                .unwrap_or_else(|e| vec![e]);
+            return (output, ConsoleAction::Continue);
+        }
+        let output = history_scope(world, format!("/{line}"), || match command {
+            "" => Vec::new(),
+            "list" => {
+                let players = online_players(world);
+                vec![format!(
+                    "There are {} players online: {}",
+                    players.len(),
+                    players.join(", ")
+                )]
+            }
+            "query" => run_query(world, rest).unwrap_or_else(|e| vec![e]),
+            "bot" => run_bot(&world.world(), rest).unwrap_or_else(|e| vec![e]),
+            "viewdistance" => run_view_distance(&world.world(), rest).unwrap_or_else(|e| vec![e]),
+            _ => {
+                let executor = world.entity_from_id(self.executor);
+                run_command(&world.world(), executor, line).map_or_else(Vec::new, |response| {
+                    response.to_plain().lines().map(str::to_string).collect()
+                })
+            }
+        });
+        (output, ConsoleAction::Continue)
+    }
+
+    /// Print output a page at a time, replacing any unread pages
+    fn print(&mut self, output: Vec<String>) {
+        self.pending = output.into();
+        self.print_page();
+    }
+
+    fn print_page(&mut self) {
+        let page = self.pending.len().min(PAGE_SIZE);
+        for line in self.pending.drain(..page) {
+            println!("{line}");
+        }
+        if !self.pending.is_empty() {
+            println!("-- {} more lines, type `more` --", self.pending.len());
+        }
+    }
+}
+
+fn online_players(world: &World) -> Vec<String> {
+    let mut players = Vec::new();
+    world
+        .query::<&Name>()
+        .with(Player)
+        .build()
+        .each(|name| players.push(name.value.clone()));
+    players
+}
+
+/// Run a `bot` console command
+fn run_bot(world: &WorldRef<'_>, input: &str) -> Result<Vec<String>, String> {
+    const USAGE: &str = "Usage: bot spawn <name> | bot <name> move|look|break <x> <y> <z> | bot <name> chat <message> | bot <name> despawn";
+
+    let mut words = input.split_whitespace();
+    let (Some(name), Some(action)) = (words.next(), words.next()) else {
+        return Err(USAGE.to_string());
+    };
+    if name == "spawn" {
+        BotController::spawn(world, action)?;
+        return Ok(vec![format!("Spawned bot {action}")]);
+    }
+    let bot = BotController::find(world, name).ok_or_else(|| format!("No bot named {name}"))?;
+    let coordinates = |words: std::str::SplitWhitespace<'_>| -> Result<[f64; 3], String> {
+        let coordinates: Vec<f64> = words
+            .map(|word| {
+                word.parse()
+                    .map_err(|_| format!("Invalid coordinate {word}"))
+            })
+            .collect::<Result<_, _>>()?;
+        coordinates.try_into().map_err(|_| USAGE.to_string())
+    };
+    let output = match action {
+        "move" => {
+            let [x, y, z] = coordinates(words)?;
+            bot.move_to(world, Position::new(x, y, z));
+            format!("{name} is walking to {x} {y} {z}")
+        }
+        "look" => {
+            let [x, y, z] = coordinates(words)?;
+            bot.look_at(world, Position::new(x, y, z));
+            format!("{name} is looking at {x} {y} {z}")
+        }
+        "break" => {
+            let [x, y, z] = coordinates(words)?;
+            let pos = BlockPos::new(x.floor() as i32, y.floor() as i32, z.floor() as i32);
+            let old = bot.break_block(world, pos)?;
+            format!(
+                "{name} broke {} at {} {} {}",
+                old.block_name().unwrap_or("unknown"),
+                pos.x,
+                pos.y,
+                pos.z
+            )
+        }
+        "chat" => {
+            let message = words.collect::<Vec<_>>().join(" ");
+            if message.is_empty() {
+                return Err(USAGE.to_string());
+            }
+            return Ok(bot.chat(world, &message).map_or_else(Vec::new, |response| {
+                response.to_plain().lines().map(str::to_string).collect()
+            }));
+        }
+        "despawn" => {
+            bot.despawn(world);
+            format!("Despawned bot {name}")
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok(vec![output])
+}
+
+/// Run a `viewdistance` console command
+fn run_view_distance(world: &WorldRef<'_>, input: &str) -> Result<Vec<String>, String> {
+    const USAGE: &str = "Usage: viewdistance | viewdistance <player> <chunks>|reset";
+
+    let mut words = input.split_whitespace();
+    let Some(name) = words.next() else {
+        let distance = world.get::<&ViewDistance>(|d| *d);
+        return Ok(vec![format!(
+            "View distance is {} (between {} and {})",
+            distance.current, distance.min, distance.max
+        )]);
+    };
+    let Some(value) = words.next() else {
+        return Err(USAGE.to_string());
+    };
+    let mut player = None;
+    world
+        .query::<&Name>()
+        .with(Player)
+        .build()
+        .each_entity(|entity, player_name| {
+            if player_name.value == name {
+                player = Some(entity.id());
+            }
+        });
+    let player = world.entity_from_id(player.ok_or_else(|| format!("No player named {name}"))?);
+
+    if value == "reset" {
+        player.remove::<ViewDistanceOverride>();
+        return Ok(vec![format!(
+            "{name} follows the server view distance again"
+        )]);
+    }
+    let chunks: i32 = value
+        .parse()
+        .ok()
+        .filter(|chunks| (MIN_VIEW_DISTANCE..=MAX_VIEW_DISTANCE).contains(chunks))
+        .ok_or_else(|| {
+            format!("View distance must be between {MIN_VIEW_DISTANCE} and {MAX_VIEW_DISTANCE}")
+        })?;
+    player.set(ViewDistanceOverride(chunks));
+    Ok(vec![format!("{name} has a view distance of {chunks}")])
+}
+
+/// Component entities by name: every serializable component, then
+/// anything else the world can find by that name
+fn resolve_component(
+    world: &World,
+    serializable: &HashMap<String, (Entity, SerializeInfo)>,
+    name: &str,
+) -> Result<Entity, String> {
+    serializable
+        .get(name)
+        .map(|(id, _)| *id)
+        .or_else(|| world.try_lookup_recursive(name).map(|e| e.id()))
+        .ok_or_else(|| format!("Unknown component {name}"))
+}
+
+/// Matching entities, one per line, with their queried component values
+fn run_query(world: &World, input: &str) -> Result<Vec<String>, String> {
+    let query = parse_query(input).map_err(|e| e.to_string())?;
+    if query.terms.iter().any(|term| term.operator == Operator::Or) {
+        return Err("The console doesn't support || terms".to_string());
+    }
+    if query
+        .terms
+        .iter()
+        .any(|term| matches!(term.kind, TermKind::Pair(_)))
+    {
+        return Err("The console doesn't support pair terms".to_string());
+    }
+
+    let mut serializable = HashMap::new();
+    world
+        .query::<&SerializeInfo>()
+        .build()
+        .each_entity(|component, info| {
+            serializable.insert(component.name(), (component.id(), info.clone()));
+        });
+
+    let mut builder = world.query::<()>();
+    if query.is_wildcard_only() {
+        builder.with(flecs::Wildcard::id());
+    }
+    for name in query.required_components() {
+        builder.with(resolve_component(world, &serializable, name)?);
+    }
+    for name in query.excluded_components() {
+        builder.without(resolve_component(world, &serializable, name)?);
+    }
+    let shown: Vec<_> = query
+        .required_components()
+        .chain(query.optional_components())
+        .filter_map(|name| serializable.get(name).map(|(id, info)| (name, *id, info)))
+        .collect();
+
+    let mut output = Vec::new();
+    builder.build().each_entity(|entity, ()| {
+        let mut line = format!("#{} {}", entity.id().0, entity.name());
+        for &(name, id, info) in &shown {
+            if entity.has(id) {
+                let value = (info.to_json)(entity.get_untyped(id));
+                let _ = write!(line, " {name}={value}");
+            }
+        }
+        output.push(line);
+    });
+    output.push(format!("{} entities", output.len()));
+    Ok(output)
+}
@@ -0,0 +1,190 @@
+//! Melee damage: attack cooldown, invulnerability frames and knockback
+//!
+//! A player's hit lands at a strength set by how far their attack cooldown
+//! has recharged since their last swing, scaling the damage like vanilla
+//! (`0.2 + 0.8 * strength²`), so spam-clicking deals a fifth of a full hit.
+//! An entity hurt in the last [`INVULNERABLE_TICKS`] ticks ignores further
+//! damage.
+//!
+//! A hit that lands knocks the target away from the attacker. The knockback
+//! goes into the target's [`Velocity`] and is sent as Set Entity Motion to
+//! everyone who sees the target's chunk, the target included: players move
+//! themselves, bots are moved by [`drift`].
+//!
+//! Health is only tracked server-side for now; nothing dies at zero.
+
+use flecs_ecs::prelude::*;
+use persist::Schema;
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Bot;
+use crate::chunk::{self, chunk_name};
+use crate::components::{EntityId, PacketBuffer, Position, Velocity, WorldTime};
+use crate::protocol::send_set_entity_motion;
+
+/// Ticks after taking damage during which an entity can't be hurt again
+pub const INVULNERABLE_TICKS: i64 = 10;
+
+/// Ticks for an empty hand's attack cooldown to recharge (attack speed 4)
+pub const ATTACK_COOLDOWN_TICKS: f32 = 5.0;
+
+/// Damage of a fully charged punch
+pub const FIST_DAMAGE: f32 = 1.0;
+
+/// Horizontal speed a hit knocks its target back with (blocks per tick)
+pub const KNOCKBACK: f64 = 0.4;
+
+/// Horizontal speed kept each tick by a knocked back bot (ground friction)
+const GROUND_DRAG: f64 = 0.546;
+
+/// Horizontal speed below which a knocked back bot stops
+const STOP_SPEED: f64 = 0.003;
+
+/// Entity: Hit points, full until first hurt
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize, Schema)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            current: 20.0,
+            max: 20.0,
+        }
+    }
+}
+
+/// Entity: World age of the last damage taken
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LastHurt(pub i64);
+
+/// Player: World age of the last melee swing
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LastAttack(pub i64);
+
+/// Entity: Velocity changed this tick, to be sent as Set Entity Motion
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct MotionChanged;
+
+/// How far the attack cooldown has recharged `ticks` after the last swing,
+/// from 0 to 1
+pub fn attack_strength(ticks: i64) -> f32 {
+    ((ticks as f32 + 0.5) / ATTACK_COOLDOWN_TICKS).clamp(0.0, 1.0)
+}
+
+/// Damage of a punch at an attack strength
+pub fn melee_damage(strength: f32) -> f32 {
+    FIST_DAMAGE * (0.2 + strength * strength * 0.8)
+}
+
+/// Velocity of an entity at `target` knocked away from `source`: half its
+/// old velocity plus `strength` away from the source, and a hop
+pub fn knockback(
+    velocity: Velocity,
+    source: &Position,
+    target: &Position,
+    strength: f64,
+) -> Velocity {
+    let (dx, dz) = (target.x - source.x, target.z - source.z);
+    let distance = dx.hypot(dz);
+    // Straight up when the two stand on the same spot
+    let (away_x, away_z) = if distance < 1.0e-4 {
+        (0.0, 0.0)
+    } else {
+        (dx / distance * strength, dz / distance * strength)
+    };
+    Velocity::new(
+        velocity.x / 2.0 + away_x,
+        (velocity.y / 2.0 + strength).min(0.4),
+        velocity.z / 2.0 + away_z,
+    )
+}
+
+/// Damage `target` unless it's invulnerable, knocking it away from `source`
+/// if given. Returns whether the damage landed.
+pub fn hurt(
+    world: &WorldRef<'_>,
+    target: EntityView<'_>,
+    amount: f32,
+    source: Option<&Position>,
+) -> bool {
+    let now = world.get::<&WorldTime>(|t| t.world_age);
+    let invulnerable = target
+        .try_get::<&LastHurt>(|last| now - last.0 < INVULNERABLE_TICKS)
+        .unwrap_or(false);
+    if invulnerable {
+        return false;
+    }
+    target.set(LastHurt(now));
+
+    let mut health = target.try_get::<&Health>(|h| *h).unwrap_or_default();
+    health.current = (health.current - amount).max(0.0);
+    target.set(health);
+
+    if let Some(source) = source
+        && let Some(pos) = target.try_get::<&Position>(|p| *p)
+    {
+        let velocity = target.try_get::<&Velocity>(|v| *v).unwrap_or_default();
+        target.set(knockback(velocity, source, &pos, KNOCKBACK));
+        target.add(MotionChanged);
+    }
+    true
+}
+
+/// Punch `target`, at the strength the attacker's cooldown allows. Returns
+/// the damage dealt, or `None` if the target was invulnerable.
+pub fn melee(
+    world: &WorldRef<'_>,
+    attacker: EntityView<'_>,
+    target: EntityView<'_>,
+) -> Option<f32> {
+    let now = world.get::<&WorldTime>(|t| t.world_age);
+    let strength = attacker
+        .try_get::<&LastAttack>(|last| attack_strength(now - last.0))
+        .unwrap_or(1.0);
+    attacker.set(LastAttack(now));
+
+    let damage = melee_damage(strength);
+    let source = attacker.try_get::<&Position>(|p| *p);
+    hurt(world, target, damage, source.as_ref()).then_some(damage)
+}
+
+/// Send an entity's changed velocity to everyone who sees its chunk.
+/// Players simulate their own motion, so theirs is forgotten once sent.
+pub fn send_motion(
+    world: &WorldRef<'_>,
+    entity: EntityView<'_>,
+    id: &EntityId,
+    pos: &Position,
+    velocity: &Velocity,
+) {
+    entity.remove::<MotionChanged>();
+    if !entity.has(Bot) {
+        entity.remove::<Velocity>();
+    }
+    let (chunk_x, chunk_z) = pos.chunk_pos();
+    let Some(chunk) = world.try_lookup_recursive(&chunk_name(chunk_x, chunk_z)) else {
+        return;
+    };
+    for viewer in chunk::chunk_viewers(world, chunk.id()) {
+        world
+            .entity_from_id(viewer)
+            .try_get::<&mut PacketBuffer>(|buffer| {
+                send_set_entity_motion(buffer, id.value, velocity.x, velocity.y, velocity.z);
+            });
+    }
+}
+
+/// Move a knocked back bot for a tick, slowing it down on the ground. Bots
+/// don't fall, so only the horizontal part moves them. Returns `true` once
+/// it has stopped.
+pub fn drift(pos: &mut Position, velocity: &mut Velocity) -> bool {
+    pos.x += velocity.x;
+    pos.z += velocity.z;
+    velocity.x *= GROUND_DRAG;
+    velocity.z *= GROUND_DRAG;
+    velocity.y = 0.0;
+    velocity.x.hypot(velocity.z) < STOP_SPEED
+}
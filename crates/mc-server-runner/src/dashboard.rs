@@ -9,13 +9,21 @@ use axum::{
     Json, Router,
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
 };
 use crossbeam_channel::{Receiver, Sender, bounded};
 use serde::{Deserialize, Serialize};
 use tower_http::cors::CorsLayer;
 
+use crate::audit::AuditRecord;
+use crate::autosave::SaveStats;
+use crate::buffer_pool::BufferPoolStats;
+use crate::components::Mspt;
+use crate::map::MapTile;
+use crate::replay::{ReplayControl, ReplayStatus};
+use crate::sniffer::PacketRecord;
+
 // ============================================================================
 // Request/Response Types for Channel Communication
 // ============================================================================
@@ -40,6 +48,14 @@ pub enum DashboardRequest {
     ListChunks {
         response: Sender<Vec<ChunkInfo>>,
     },
+    ListSystems {
+        response: Sender<Vec<SystemInfo>>,
+    },
+    GetMapTiles {
+        min: (i32, i32),
+        max: (i32, i32),
+        response: Sender<Result<Vec<MapTile>, String>>,
+    },
     GetEntityHistory {
         id: u64,
         limit: usize,
@@ -49,6 +65,41 @@ pub enum DashboardRequest {
         spec: QuerySpec,
         response: Sender<QueryResponse>,
     },
+    GetMetrics {
+        response: Sender<MetricsInfo>,
+    },
+    ListPackets {
+        connection: Option<u64>,
+        limit: usize,
+        response: Sender<Vec<PacketRecord>>,
+    },
+    ListSavedQueries {
+        response: Sender<Vec<SavedQuery>>,
+    },
+    SaveQuery {
+        query: SavedQuery,
+        response: Sender<SavedQueryResponse>,
+    },
+    DeleteSavedQuery {
+        name: String,
+        response: Sender<SavedQueryResponse>,
+    },
+    RunSavedQuery {
+        name: String,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        response: Sender<Result<QueryResponse, String>>,
+    },
+    Replay {
+        control: Option<ReplayControl>,
+        response: Sender<Result<ReplayStatus, String>>,
+    },
+    GetAuditLog {
+        uuid: u128,
+        before: Option<u64>,
+        limit: usize,
+        response: Sender<Result<Vec<AuditRecord>, String>>,
+    },
 }
 
 /// Query specification for filtering entities.
@@ -82,6 +133,29 @@ pub struct QueryResponse {
     pub execution_time_us: u64,
 }
 
+/// A named query DSL expression.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedQuery {
+    pub name: String,
+    pub query: String,
+}
+
+/// Result of saving or deleting a query.
+#[derive(Serialize, Clone)]
+pub struct SavedQueryResponse {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl From<Result<(), String>> for SavedQueryResponse {
+    fn from(result: Result<(), String>) -> Self {
+        Self {
+            success: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
 #[derive(Serialize, Clone)]
 pub struct WorldInfo {
     pub entity_count: usize,
@@ -130,6 +204,18 @@ pub struct ChunkInfo {
     pub loaded: bool,
 }
 
+/// A registered system and the phase it runs in.
+#[derive(Serialize, Clone)]
+pub struct SystemInfo {
+    pub id: u64,
+    pub name: String,
+    /// Module the system was registered in, if any
+    pub module: Option<String>,
+    /// The system's phase followed by every phase it depends on, so the
+    /// last entry is a builtin phase such as `OnUpdate`
+    pub phases: Vec<String>,
+}
+
 #[derive(Serialize, Clone)]
 pub struct PlayerInfo {
     pub entity_id: u64,
@@ -137,6 +223,7 @@ pub struct PlayerInfo {
     pub uuid: Option<String>,
     pub position: Option<PositionInfo>,
     pub game_mode: Option<String>,
+    pub latency_ms: Option<i32>,
 }
 
 #[derive(Serialize, Clone)]
@@ -146,6 +233,55 @@ pub struct PositionInfo {
     pub z: f64,
 }
 
+#[derive(Serialize, Clone)]
+pub struct MetricsInfo {
+    pub tps_5s: f32,
+    pub tps_15s: f32,
+    pub tps_1m: f32,
+    /// Ticks the game loop was behind at the start of the last tick
+    pub ticks_behind: u32,
+    /// Missed ticks run back to back, skipped over and dropped since
+    /// startup
+    pub ticks_caught_up: u64,
+    pub ticks_skipped: u64,
+    pub ticks_dropped: u64,
+    /// Tick time percentiles over the last minute
+    pub mspt: Mspt,
+    pub players: usize,
+    pub latency: LatencyMetrics,
+    /// How often outgoing packets reused pooled buffers
+    pub egress_buffers: BufferPoolStats,
+    /// Protocol entity IDs currently allocated
+    pub entity_ids: usize,
+    /// Unsaved chunks and autosave throughput
+    pub saves: SaveStats,
+}
+
+/// Latency across all players with at least one keep-alive measured.
+#[derive(Serialize, Clone, Default)]
+pub struct LatencyMetrics {
+    pub measured: usize,
+    pub avg_ms: f64,
+    pub min_ms: i32,
+    pub max_ms: i32,
+}
+
+impl LatencyMetrics {
+    /// Aggregate per-player latencies.
+    pub fn from_samples(samples: &[i32]) -> Self {
+        let (Some(&min_ms), Some(&max_ms)) = (samples.iter().min(), samples.iter().max()) else {
+            return Self::default();
+        };
+        let sum: i64 = samples.iter().map(|&ms| i64::from(ms)).sum();
+        Self {
+            measured: samples.len(),
+            avg_ms: sum as f64 / samples.len() as f64,
+            min_ms,
+            max_ms,
+        }
+    }
+}
+
 #[derive(Serialize, Clone)]
 pub struct HistoryEntryInfo {
     pub id: u64,
@@ -154,7 +290,9 @@ pub struct HistoryEntryInfo {
     pub component: String,
     pub old_value: Option<serde_json::Value>,
     pub new_value: Option<serde_json::Value>,
+    /// Label of the transaction the change was part of, or `"system"`
     pub source: String,
+    pub transaction: Option<u64>,
 }
 
 #[derive(Serialize, Clone)]
@@ -231,17 +369,33 @@ pub fn create_router(state: DashboardState) -> Router {
         .route("/api/entities/{id}", get(get_entity))
         // Players (convenience endpoint)
         .route("/api/players", get(list_players))
+        .route("/api/players/{uuid}/audit", get(get_audit_log))
         // Chunks
         .route("/api/chunks", get(list_chunks))
+        // Systems and their pipeline phases
+        .route("/api/systems", get(list_systems))
+        // Top-down map tiles
+        .route("/api/map", get(get_map_tiles))
         // Query
         .route("/api/query", post(query_entities))
+        // Saved queries
+        .route("/api/queries", get(list_saved_queries).post(save_query))
+        .route("/api/queries/{name}", delete(delete_saved_query))
+        .route("/api/queries/{name}/run", post(run_saved_query))
         // History
         .route("/api/history/entity/{id}", get(get_entity_history))
+        // Metrics
+        .route("/api/metrics", get(get_metrics))
+        // Packet sniffer (empty unless RGB_PACKET_LOG is set)
+        .route("/api/packets", get(list_packets))
+        // Replay playback (only with --replay)
+        .route("/api/replay", get(get_replay).post(control_replay))
         .with_state(state)
         .layer(cors)
 }
 
 /// Start the dashboard server on the given port.
+#[cfg(not(feature = "dashboard-tls"))]
 pub async fn start_server(state: DashboardState, port: u16) {
     let app = create_router(state);
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}"))
@@ -255,6 +409,52 @@ pub async fn start_server(state: DashboardState, port: u16) {
         .expect("Dashboard server failed");
 }
 
+/// Start the dashboard server on the given port.
+///
+/// With TLS configured, the plaintext port only listens on loopback and
+/// remote clients have to use the TLS port.
+#[cfg(feature = "dashboard-tls")]
+pub async fn start_server(state: DashboardState, port: u16) {
+    use crate::dashboard_tls::{PeerAddr, TlsListener, TlsSettings};
+
+    let app = create_router(state);
+    let Some(settings) = TlsSettings::from_env() else {
+        let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{port}"))
+            .await
+            .expect("Failed to bind dashboard server");
+        tracing::info!("Dashboard server listening on http://localhost:{port}");
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<PeerAddr>(),
+        )
+        .await
+        .expect("Dashboard server failed");
+        return;
+    };
+
+    let tls = TlsListener::bind(&settings)
+        .await
+        .expect("Failed to start dashboard TLS");
+    let plain = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}"))
+        .await
+        .expect("Failed to bind dashboard server");
+
+    tracing::info!(
+        "Dashboard server listening on https://localhost:{} (http://127.0.0.1:{port} locally)",
+        settings.port
+    );
+
+    let local = axum::serve(
+        plain,
+        app.clone()
+            .into_make_service_with_connect_info::<PeerAddr>(),
+    );
+    let remote = axum::serve(tls, app.into_make_service_with_connect_info::<PeerAddr>());
+    let (local, remote) = tokio::join!(local.into_future(), remote.into_future());
+    local.expect("Dashboard server failed");
+    remote.expect("Dashboard TLS server failed");
+}
+
 // ============================================================================
 // Handlers
 // ============================================================================
@@ -281,6 +481,28 @@ async fn get_world(State(state): State<DashboardState>) -> impl IntoResponse {
     }
 }
 
+async fn get_metrics(State(state): State<DashboardState>) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::GetMetrics { response: tx };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(metrics) => Json(metrics).into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
 #[derive(Deserialize)]
 struct ListParams {
     limit: Option<usize>,
@@ -365,6 +587,55 @@ async fn list_players(State(state): State<DashboardState>) -> impl IntoResponse
     }
 }
 
+#[derive(Deserialize)]
+struct AuditParams {
+    before: Option<u64>,
+    limit: Option<usize>,
+}
+
+async fn get_audit_log(
+    State(state): State<DashboardState>,
+    Path(uuid): Path<String>,
+    axum::extract::Query(params): axum::extract::Query<AuditParams>,
+) -> impl IntoResponse {
+    let Ok(uuid) = u128::from_str_radix(&uuid.replace('-', ""), 16) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "Invalid UUID"})),
+        )
+            .into_response();
+    };
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::GetAuditLog {
+        uuid,
+        before: params.before,
+        limit: params.limit.unwrap_or(100),
+        response: tx,
+    };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(Ok(entries)) => Json(entries).into_response(),
+        Ok(Err(error)) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "error": error })),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
 async fn list_chunks(State(state): State<DashboardState>) -> impl IntoResponse {
     let (tx, rx) = bounded(1);
     let request = DashboardRequest::ListChunks { response: tx };
@@ -387,6 +658,28 @@ async fn list_chunks(State(state): State<DashboardState>) -> impl IntoResponse {
     }
 }
 
+async fn list_systems(State(state): State<DashboardState>) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::ListSystems { response: tx };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(systems) => Json(systems).into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
 #[derive(Deserialize)]
 struct HistoryParams {
     limit: Option<usize>,
@@ -446,3 +739,232 @@ async fn query_entities(
             .into_response(),
     }
 }
+
+#[derive(Deserialize)]
+struct PacketParams {
+    connection: Option<u64>,
+    limit: Option<usize>,
+}
+
+async fn list_packets(
+    State(state): State<DashboardState>,
+    axum::extract::Query(params): axum::extract::Query<PacketParams>,
+) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::ListPackets {
+        connection: params.connection,
+        limit: params.limit.unwrap_or(200),
+        response: tx,
+    };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(packets) => Json(packets).into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
+async fn list_saved_queries(State(state): State<DashboardState>) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::ListSavedQueries { response: tx };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(queries) => Json(queries).into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
+async fn save_query(
+    State(state): State<DashboardState>,
+    Json(query): Json<SavedQuery>,
+) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::SaveQuery {
+        query,
+        response: tx,
+    };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(result) => Json(result).into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
+async fn delete_saved_query(
+    State(state): State<DashboardState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::DeleteSavedQuery { name, response: tx };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(result) => Json(result).into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
+async fn run_saved_query(
+    State(state): State<DashboardState>,
+    Path(name): Path<String>,
+    axum::extract::Query(params): axum::extract::Query<ListParams>,
+) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::RunSavedQuery {
+        name,
+        limit: params.limit,
+        offset: params.offset,
+        response: tx,
+    };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(Ok(result)) => Json(result).into_response(),
+        Ok(Err(error)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": error })),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_replay(State(state): State<DashboardState>) -> impl IntoResponse {
+    replay_request(&state, None)
+}
+
+async fn control_replay(
+    State(state): State<DashboardState>,
+    Json(control): Json<ReplayControl>,
+) -> impl IntoResponse {
+    replay_request(&state, Some(control))
+}
+
+fn replay_request(state: &DashboardState, control: Option<ReplayControl>) -> Response {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::Replay {
+        control,
+        response: tx,
+    };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(Ok(status)) => Json(status).into_response(),
+        Ok(Err(error)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": error })),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct MapParams {
+    min_x: i32,
+    min_z: i32,
+    max_x: i32,
+    max_z: i32,
+}
+
+async fn get_map_tiles(
+    State(state): State<DashboardState>,
+    axum::extract::Query(params): axum::extract::Query<MapParams>,
+) -> impl IntoResponse {
+    let (tx, rx) = bounded(1);
+    let request = DashboardRequest::GetMapTiles {
+        min: (params.min_x, params.min_z),
+        max: (params.max_x, params.max_z),
+        response: tx,
+    };
+
+    if state.request_tx.send(request).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "Game loop not available"})),
+        )
+            .into_response();
+    }
+
+    match rx.recv_timeout(REQUEST_TIMEOUT) {
+        Ok(Ok(tiles)) => Json(tiles).into_response(),
+        Ok(Err(error)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": error })),
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::REQUEST_TIMEOUT,
+            Json(serde_json::json!({"error": "Request timeout"})),
+        )
+            .into_response(),
+    }
+}
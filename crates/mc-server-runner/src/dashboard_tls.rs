@@ -0,0 +1,178 @@
+//! TLS for the dashboard
+//!
+//! With both [`CERT_ENV`] and [`KEY_ENV`] pointing to PEM files, the
+//! dashboard's own axum server accepts TLS on [`PORT_ENV`] (8443 by
+//! default), and its plaintext port only listens on loopback, so remote
+//! clients can't skip TLS. WebSocket upgrades are plain HTTP/1.1 inside the
+//! TLS stream and work unchanged.
+//!
+//! Handlers see the real client address either way, as
+//! `ConnectInfo<PeerAddr>`.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::connect_info::Connected;
+use axum::serve::{IncomingStream, Listener};
+use eyre::WrapErr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::server::TlsStream;
+use tracing::debug;
+
+/// Environment variable with the certificate chain (PEM)
+pub const CERT_ENV: &str = "RGB_DASHBOARD_TLS_CERT";
+/// Environment variable with the private key (PEM)
+pub const KEY_ENV: &str = "RGB_DASHBOARD_TLS_KEY";
+/// Environment variable with the port to accept TLS on
+pub const PORT_ENV: &str = "RGB_DASHBOARD_TLS_PORT";
+
+const DEFAULT_PORT: u16 = 8443;
+/// Handshakes not finished by then are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Finished handshakes waiting for axum to pick them up
+const ACCEPT_BACKLOG: usize = 64;
+
+/// Where to accept TLS and with which certificate
+#[derive(Debug, Clone)]
+pub struct TlsSettings {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// Port to accept TLS connections on
+    pub port: u16,
+}
+
+impl TlsSettings {
+    /// Read the settings from the environment, or `None` if no certificate
+    /// and key are configured
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            cert: std::env::var_os(CERT_ENV)?.into(),
+            key: std::env::var_os(KEY_ENV)?.into(),
+            port: std::env::var(PORT_ENV)
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(DEFAULT_PORT),
+        })
+    }
+
+    fn acceptor(&self) -> eyre::Result<TlsAcceptor> {
+        let certs = load_certs(&self.cert)?;
+        let key = load_key(&self.key)?;
+        let mut config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .wrap_err("invalid TLS certificate or key")?;
+        // The dashboard only speaks HTTP/1.1
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+fn load_certs(path: &Path) -> eyre::Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(path).wrap_err_with(|| format!("failed to open {}", path.display()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err_with(|| format!("failed to read certificates from {}", path.display()))?;
+    if certs.is_empty() {
+        eyre::bail!("no certificates in {}", path.display());
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> eyre::Result<PrivateKeyDer<'static>> {
+    let file = File::open(path).wrap_err_with(|| format!("failed to open {}", path.display()))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .wrap_err_with(|| format!("failed to read the private key from {}", path.display()))?
+        .ok_or_else(|| eyre::eyre!("no private key in {}", path.display()))
+}
+
+/// Address of the client on the other end of a dashboard connection
+#[derive(Debug, Clone, Copy)]
+pub struct PeerAddr(pub SocketAddr);
+
+impl Connected<IncomingStream<'_, TcpListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+/// Accepts TLS connections for `axum::serve`
+///
+/// Handshakes run on their own tasks, so a client that stalls one doesn't
+/// hold up the others.
+pub struct TlsListener {
+    handshaken: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    /// Start accepting TLS on every interface on the configured port
+    pub async fn bind(settings: &TlsSettings) -> eyre::Result<Self> {
+        let acceptor = settings.acceptor()?;
+        let listener = TcpListener::bind(("0.0.0.0", settings.port))
+            .await
+            .wrap_err_with(|| format!("failed to bind port {}", settings.port))?;
+        let local_addr = listener.local_addr()?;
+        let (tx, handshaken) = mpsc::channel(ACCEPT_BACKLOG);
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        debug!("Dashboard TLS accept failed: {e}");
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(tls)) => {
+                            let _ = tx.send((tls, addr)).await;
+                        }
+                        Ok(Err(e)) => debug!("Dashboard TLS handshake with {addr} failed: {e}"),
+                        Err(_) => debug!("Dashboard TLS handshake with {addr} timed out"),
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            handshaken,
+            local_addr,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.handshaken.recv().await {
+            Some(accepted) => accepted,
+            // The accept loop only ends with the runtime
+            None => core::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
@@ -0,0 +1,90 @@
+//! Protocol entity ID allocation
+//!
+//! Every entity a client can see needs an `i32` ID that is unique among live
+//! entities. IDs are released when the entity's [`EntityId`](crate::components::EntityId)
+//! is removed (including on despawn) and recycled, so long-running servers never
+//! overflow. A released ID is only handed out again after [`RECYCLE_DELAY`] other
+//! IDs have been released since, so late packets for a despawned entity can't
+//! land on its successor.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use flecs_ecs::prelude::*;
+
+/// First ID handed out (0 is avoided since some clients treat it as "none")
+const FIRST_ID: i32 = 1;
+
+/// Released IDs that must queue up before the oldest one is reused
+const RECYCLE_DELAY: usize = 256;
+
+#[derive(Debug)]
+struct EntityIdPool {
+    /// Next never-used ID, `None` once the `i32` range is exhausted
+    next: Option<i32>,
+    /// Released IDs, oldest first
+    free: VecDeque<i32>,
+    /// IDs currently held by an entity
+    live: HashSet<i32>,
+}
+
+impl Default for EntityIdPool {
+    fn default() -> Self {
+        Self {
+            next: Some(FIRST_ID),
+            free: VecDeque::new(),
+            live: HashSet::new(),
+        }
+    }
+}
+
+impl EntityIdPool {
+    fn allocate(&mut self) -> Option<i32> {
+        let id = if self.free.len() > RECYCLE_DELAY {
+            self.free.pop_front()
+        } else if let Some(id) = self.next {
+            self.next = id.checked_add(1);
+            Some(id)
+        } else {
+            // Fresh IDs are exhausted, so even recently released ones are fair game
+            self.free.pop_front()
+        }?;
+        self.live.insert(id);
+        Some(id)
+    }
+
+    fn release(&mut self, id: i32) {
+        if self.live.remove(&id) {
+            self.free.push_back(id);
+        }
+    }
+}
+
+/// Global: Allocator for protocol entity IDs
+///
+/// Cloning shares the same pool.
+#[derive(Component, Clone, Default)]
+pub struct EntityIdAllocator(Arc<Mutex<EntityIdPool>>);
+
+impl EntityIdAllocator {
+    fn pool(&self) -> MutexGuard<'_, EntityIdPool> {
+        self.0.lock().expect("entity ID pool poisoned")
+    }
+
+    /// Allocate an ID, or `None` if every `i32` ID is live.
+    #[must_use]
+    pub fn allocate(&self) -> Option<i32> {
+        self.pool().allocate()
+    }
+
+    /// Return `id` to the pool. Unknown or already released IDs are ignored.
+    pub fn release(&self, id: i32) {
+        self.pool().release(id);
+    }
+
+    /// Number of IDs currently held by entities
+    #[must_use]
+    pub fn live(&self) -> usize {
+        self.pool().live.len()
+    }
+}
@@ -0,0 +1,188 @@
+//! Water and lava
+//!
+//! Fluid blocks only change when ticked. Changing a block schedules a
+//! [block tick](crate::block_tick) for it and its neighbors that hold fluid,
+//! after the fluid's delay.
+//!
+//! A tick recomputes the block's level from its neighbors (draining it if
+//! nothing feeds it any more), turns water between two sources into a
+//! source, and spreads the fluid down or, failing that, sideways.
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+
+use crate::components::BlockPos;
+use crate::{block_tick, chunk};
+
+/// `level` of a source block
+const SOURCE: u8 = 0;
+/// `level` of falling fluid (flowing levels are `1..=7`)
+const FALLING: u8 = 8;
+/// Weakest flowing level
+const MAX_FLOWING: u8 = 7;
+
+/// Horizontal neighbors, in `(dx, dz)`
+const HORIZONTAL: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    /// The fluid of a water or lava block (waterlogged blocks don't flow)
+    pub fn of(state: BlockState) -> Option<Self> {
+        match state.block_name()? {
+            "minecraft:water" => Some(Self::Water),
+            "minecraft:lava" => Some(Self::Lava),
+            _ => None,
+        }
+    }
+
+    /// Game ticks between a change and the resulting fluid tick
+    pub const fn tick_delay(self) -> i64 {
+        match self {
+            Self::Water => 5,
+            Self::Lava => 30,
+        }
+    }
+
+    /// How much the level weakens per block of horizontal flow
+    const fn drop_off(self) -> u8 {
+        match self {
+            Self::Water => 1,
+            Self::Lava => 2,
+        }
+    }
+
+    /// The fluid block with a `level`
+    pub fn state(self, level: u8) -> BlockState {
+        let block = match self {
+            Self::Water => BlockState::by_name("water"),
+            Self::Lava => BlockState::by_name("lava"),
+        };
+        block
+            .and_then(|b| b.with_property("level", &level.to_string()))
+            .unwrap_or(BlockState::AIR)
+    }
+}
+
+fn level(state: BlockState) -> u8 {
+    state
+        .property("level")
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(SOURCE)
+}
+
+/// Flow distance a block feeds its horizontal neighbors from (falling
+/// fluid feeds like a source)
+const fn distance(level: u8) -> u8 {
+    if level >= FALLING { SOURCE } else { level }
+}
+
+/// Schedule a tick for a fluid block after its fluid's delay
+fn schedule_tick(world: &WorldRef<'_>, pos: BlockPos) {
+    if let Some(fluid) = chunk::block_at(world, pos).and_then(Fluid::of) {
+        block_tick::schedule_tick(world, pos, fluid.tick_delay(), 0);
+    }
+}
+
+/// Schedule ticks for a changed block and its neighbors
+pub fn on_block_changed(world: &WorldRef<'_>, pos: BlockPos) {
+    schedule_tick(world, pos);
+    schedule_tick(world, pos.offset(0, 1, 0));
+    schedule_tick(world, pos.offset(0, -1, 0));
+    for (dx, dz) in HORIZONTAL {
+        schedule_tick(world, pos.offset(dx, 0, dz));
+    }
+}
+
+/// Whether fluid can flow into a block, washing it away
+fn is_replaceable(state: BlockState) -> bool {
+    !state.is_solid() && !state.has_fluid()
+}
+
+/// Level a flowing block should have, or `None` if nothing feeds it
+fn expected_level(world: &WorldRef<'_>, fluid: Fluid, pos: BlockPos) -> Option<u8> {
+    let block = |pos| chunk::block_at(world, pos).unwrap_or(BlockState::AIR);
+
+    if Fluid::of(block(pos.offset(0, 1, 0))) == Some(fluid) {
+        return Some(FALLING);
+    }
+
+    let mut sources = 0;
+    let mut nearest = None::<u8>;
+    for (dx, dz) in HORIZONTAL {
+        let neighbor = block(pos.offset(dx, 0, dz));
+        if Fluid::of(neighbor) != Some(fluid) {
+            continue;
+        }
+        let level = level(neighbor);
+        if level == SOURCE {
+            sources += 1;
+        }
+        let distance = distance(level);
+        nearest = Some(nearest.map_or(distance, |n| n.min(distance)));
+    }
+
+    // Water between two sources, on something it can't drain into, refills
+    let below = block(pos.offset(0, -1, 0));
+    let supported = below.is_solid() || (Fluid::of(below) == Some(fluid) && level(below) == SOURCE);
+    if fluid == Fluid::Water && sources >= 2 && supported {
+        return Some(SOURCE);
+    }
+
+    let level = nearest? + fluid.drop_off();
+    (level <= MAX_FLOWING).then_some(level)
+}
+
+/// Changes caused by ticking the fluid block at `pos`
+pub fn tick(world: &WorldRef<'_>, pos: BlockPos) -> Vec<(BlockPos, BlockState)> {
+    let mut changes = Vec::new();
+    let Some(state) = chunk::block_at(world, pos) else {
+        return changes;
+    };
+    let Some(fluid) = Fluid::of(state) else {
+        return changes;
+    };
+
+    let mut current = level(state);
+    if current != SOURCE {
+        match expected_level(world, fluid, pos) {
+            Some(expected) if expected != current => {
+                changes.push((pos, fluid.state(expected)));
+                current = expected;
+            }
+            Some(_) => {}
+            None => {
+                changes.push((pos, BlockState::AIR));
+                return changes;
+            }
+        }
+    }
+
+    let block = |pos| chunk::block_at(world, pos);
+    let below = pos.offset(0, -1, 0);
+    if block(below).is_some_and(is_replaceable) {
+        changes.push((below, fluid.state(FALLING)));
+        return changes;
+    }
+
+    let spread = distance(current) + fluid.drop_off();
+    if spread > MAX_FLOWING || block(below).is_some_and(|b| Fluid::of(b) == Some(fluid)) {
+        return changes;
+    }
+    for (dx, dz) in HORIZONTAL {
+        let side = pos.offset(dx, 0, dz);
+        let weaker = |b: BlockState| {
+            Fluid::of(b) == Some(fluid)
+                && (SOURCE + 1..FALLING).contains(&level(b))
+                && level(b) > spread
+        };
+        if block(side).is_some_and(|b| is_replaceable(b) || weaker(b)) {
+            changes.push((side, fluid.state(spread)));
+        }
+    }
+    changes
+}
@@ -0,0 +1,280 @@
+//! Game rules
+//!
+//! [`GameRules`] holds the world's game rules by their vanilla names, each
+//! a boolean or an integer. The built-in rules are registered at startup;
+//! other code registers its own with [`GameRules::register`] before the
+//! game loop starts. `/gamerule` queries and sets them.
+//!
+//! Setting a rule to a new value records a [`GameRuleChange`]. Systems that
+//! react to a rule look for it with [`GameRules::changed`]: changes made by a
+//! command are seen the same tick, those made by the console or RCON
+//! (between ticks) the next one. [`finish_tick`] clears them at the end of
+//! the tick.
+//!
+//! With a world directory, the rules are saved to `<world>/gamerules.json`
+//! whenever one changes and loaded at startup. Saved values of rules no one
+//! registered are kept, so a rule registered later still gets its value.
+//!
+//! Of the vanilla rules only `doDaylightCycle` has an effect so far: players
+//! can't die (`keepInventory`) and there are no mobs (`mobGriefing`). The
+//! collision rules are registered by [`crate::collision`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::WrapErr;
+use flecs_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::systems::broadcast_time;
+
+/// File in the world directory the rules are saved to
+pub const FILE: &str = "gamerules.json";
+
+/// Whether the time of day advances
+pub const DO_DAYLIGHT_CYCLE: &str = "doDaylightCycle";
+/// Whether players keep their inventory when they die
+pub const KEEP_INVENTORY: &str = "keepInventory";
+/// Whether mobs can change blocks
+pub const MOB_GRIEFING: &str = "mobGriefing";
+
+/// Value of a game rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl GameRuleValue {
+    /// Parse `input` as a value of the same type as `self`
+    fn parse_like(self, input: &str) -> Option<Self> {
+        match self {
+            Self::Bool(_) => input.parse().ok().map(Self::Bool),
+            Self::Int(_) => input.parse().ok().map(Self::Int),
+        }
+    }
+
+    const fn same_type(self, other: Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Bool(_), Self::Bool(_)) | (Self::Int(_), Self::Int(_))
+        )
+    }
+}
+
+impl fmt::Display for GameRuleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A rule set to a new value this tick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRuleChange {
+    pub rule: String,
+    pub old: GameRuleValue,
+    pub new: GameRuleValue,
+}
+
+/// Why a rule couldn't be set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameRuleError {
+    UnknownRule,
+    /// The value isn't of the rule's type
+    InvalidValue,
+}
+
+/// Global: The world's game rules
+#[derive(Component, Debug, Default)]
+pub struct GameRules {
+    rules: BTreeMap<String, GameRuleValue>,
+    /// Loaded values of rules not registered (yet)
+    unregistered: BTreeMap<String, GameRuleValue>,
+    changes: Vec<GameRuleChange>,
+    /// Where changes are saved, if anywhere
+    path: Option<PathBuf>,
+}
+
+impl GameRules {
+    /// The built-in rules at their vanilla defaults
+    pub fn builtin() -> Self {
+        let mut rules = Self::default();
+        rules.register(DO_DAYLIGHT_CYCLE, GameRuleValue::Bool(true));
+        rules.register(KEEP_INVENTORY, GameRuleValue::Bool(false));
+        rules.register(MOB_GRIEFING, GameRuleValue::Bool(true));
+        rules
+    }
+
+    /// Add a rule, set to its loaded value if one of the same type was
+    /// loaded and to `default` otherwise. Returns `false` if a rule with
+    /// the name already exists.
+    pub fn register(&mut self, name: &str, default: GameRuleValue) -> bool {
+        if self.rules.contains_key(name) {
+            return false;
+        }
+        let value = self
+            .unregistered
+            .remove(name)
+            .filter(|loaded| loaded.same_type(default))
+            .unwrap_or(default);
+        self.rules.insert(name.to_string(), value);
+        true
+    }
+
+    /// Load the values saved at `path`, if it exists
+    pub fn load(&mut self, path: &Path) {
+        let Ok(bytes) = fs::read(path) else {
+            return;
+        };
+        let saved: BTreeMap<String, GameRuleValue> = match serde_json::from_slice(&bytes) {
+            Ok(saved) => saved,
+            Err(e) => {
+                warn!("Ignoring unreadable game rules {}: {e}", path.display());
+                return;
+            }
+        };
+        for (name, value) in saved {
+            match self.rules.get_mut(&name) {
+                Some(current) if current.same_type(value) => *current = value,
+                Some(_) => warn!("Ignoring saved game rule {name} of the wrong type"),
+                None => {
+                    self.unregistered.insert(name, value);
+                }
+            }
+        }
+    }
+
+    /// Read the rules saved at the save path again, setting each one that
+    /// changed. Returns how many changed.
+    #[cfg(feature = "reload")]
+    pub fn reload(&mut self) -> eyre::Result<usize> {
+        let path = self
+            .path
+            .clone()
+            .ok_or_else(|| eyre::eyre!("the game rules aren't saved anywhere"))?;
+        let bytes =
+            fs::read(&path).wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        let saved: BTreeMap<String, GameRuleValue> = serde_json::from_slice(&bytes)?;
+        let before = self.changes.len();
+        for (name, value) in saved {
+            match self.set(&name, value) {
+                Ok(()) => {}
+                Err(GameRuleError::UnknownRule) => {
+                    self.unregistered.insert(name, value);
+                }
+                Err(GameRuleError::InvalidValue) => {
+                    warn!("Ignoring saved game rule {name} of the wrong type");
+                }
+            }
+        }
+        Ok(self.changes.len() - before)
+    }
+
+    /// Save the rules to `path` whenever one changes
+    pub fn save_to(&mut self, path: PathBuf) {
+        self.path = Some(path);
+    }
+
+    /// Every rule's name, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.keys().map(String::as_str)
+    }
+
+    pub fn get(&self, name: &str) -> Option<GameRuleValue> {
+        self.rules.get(name).copied()
+    }
+
+    /// A boolean rule's value; `false` if there's no such boolean rule
+    pub fn bool(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(GameRuleValue::Bool(true)))
+    }
+
+    pub fn do_daylight_cycle(&self) -> bool {
+        self.bool(DO_DAYLIGHT_CYCLE)
+    }
+
+    /// Set a rule, recording the change if the value is new
+    pub fn set(&mut self, name: &str, value: GameRuleValue) -> Result<(), GameRuleError> {
+        let current = self.rules.get_mut(name).ok_or(GameRuleError::UnknownRule)?;
+        if !current.same_type(value) {
+            return Err(GameRuleError::InvalidValue);
+        }
+        if *current != value {
+            self.changes.push(GameRuleChange {
+                rule: name.to_string(),
+                old: *current,
+                new: value,
+            });
+            *current = value;
+        }
+        Ok(())
+    }
+
+    /// Set a rule from its value as typed in a command, returning the value
+    pub fn set_str(&mut self, name: &str, input: &str) -> Result<GameRuleValue, GameRuleError> {
+        let value = self
+            .get(name)
+            .ok_or(GameRuleError::UnknownRule)?
+            .parse_like(input)
+            .ok_or(GameRuleError::InvalidValue)?;
+        self.set(name, value)?;
+        Ok(value)
+    }
+
+    /// Rules set to a new value this tick, in order
+    pub fn changes(&self) -> &[GameRuleChange] {
+        &self.changes
+    }
+
+    /// The last change to `name` this tick
+    pub fn changed(&self, name: &str) -> Option<&GameRuleChange> {
+        self.changes.iter().rev().find(|change| change.rule == name)
+    }
+
+    fn save(&self, path: &Path) -> eyre::Result<()> {
+        let mut saved = self.unregistered.clone();
+        saved.extend(
+            self.rules
+                .iter()
+                .map(|(name, value)| (name.clone(), *value)),
+        );
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(&saved)?)?;
+        fs::rename(&temp, path).wrap_err_with(|| format!("failed to save {}", path.display()))
+    }
+}
+
+/// React to this tick's changes, save them and clear them
+pub fn finish_tick(world: &WorldRef<'_>, rules: &mut GameRules) {
+    if rules.changes().is_empty() {
+        return;
+    }
+    for change in rules.changes() {
+        info!(
+            "Game rule {} changed from {} to {}",
+            change.rule, change.old, change.new
+        );
+    }
+
+    // Clients advance the time of day themselves while it's increasing
+    if rules.changed(DO_DAYLIGHT_CYCLE).is_some() {
+        broadcast_time(world, rules.do_daylight_cycle());
+    }
+
+    if let Some(path) = &rules.path
+        && let Err(e) = rules.save(path)
+    {
+        warn!("Failed to save the game rules: {e:#}");
+    }
+    rules.changes.clear();
+}
@@ -0,0 +1,45 @@
+//! Server message translations
+//!
+//! Lang files live in `lang/<locale>.json` and are embedded at build time.
+//! Messages are translated per recipient using their [`ClientLocale`], e.g.
+//! `tr!(translations.catalog, &locale, "command.tps", ..)`.
+//!
+//! [`ClientLocale`]: crate::components::ClientLocale
+
+use flecs_ecs::prelude::*;
+use mc_text::lang::{Catalog, DEFAULT_LOCALE};
+use tracing::error;
+
+use crate::components::ClientLocale;
+
+/// Lang files shipped with the server
+const BUILTIN: &[(&str, &str)] = &[
+    ("en_us", include_str!("../lang/en_us.json")),
+    ("de_de", include_str!("../lang/de_de.json")),
+];
+
+/// Global: Server-side translation catalog
+#[derive(Component, Debug, Clone)]
+pub struct Translations {
+    pub catalog: Catalog,
+}
+
+impl Translations {
+    /// Catalog with every builtin lang file loaded
+    pub fn builtin() -> Self {
+        let mut catalog = Catalog::new();
+        for (locale, json) in BUILTIN {
+            if let Err(e) = catalog.load_json(locale, json) {
+                error!("Invalid lang file {}: {}", locale, e);
+            }
+        }
+        Self { catalog }
+    }
+}
+
+/// Locale of a recipient, or the default if the client never reported one
+pub fn locale_of(entity: EntityView<'_>) -> String {
+    entity
+        .try_get::<&ClientLocale>(|l| l.value.clone())
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
@@ -0,0 +1,96 @@
+//! Player inventories
+//!
+//! The server only knows what it put into a player's [`Inventory`]: items
+//! given by `/give` land in matching stacks first, then in empty slots,
+//! hotbar first. Changed slots are sent with Set Player Inventory once per
+//! tick. Slot clicks and creative-mode edits aren't read back yet, so
+//! anything the player moves around is invisible to the server.
+
+use std::collections::BTreeSet;
+
+use flecs_ecs::prelude::*;
+use mc_data::Item;
+use mc_data::play::clientbound::SetPlayerInventory;
+use mc_protocol::{Encode, ItemStack, Packet, write_varint};
+
+use crate::buffer_pool;
+use crate::components::PacketBuffer;
+
+/// Clientbound Set Player Inventory packet ID
+const SET_PLAYER_INVENTORY_PACKET_ID: i32 = SetPlayerInventory::ID;
+
+/// Hotbar (0-8) and main inventory (9-35) slots
+pub const MAIN_SLOTS: usize = 36;
+
+/// A player's hotbar and main inventory
+#[derive(Component, Debug, Clone)]
+pub struct Inventory {
+    slots: Vec<ItemStack>,
+    /// Slots not yet sent to the client
+    changed: BTreeSet<usize>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            slots: vec![ItemStack::EMPTY; MAIN_SLOTS],
+            changed: BTreeSet::new(),
+        }
+    }
+}
+
+impl Inventory {
+    /// Add `stack`, splitting it into stacks of at most the item's max stack
+    /// size. Returns how many items didn't fit.
+    pub fn insert(&mut self, stack: &ItemStack) -> i32 {
+        let max = Item::new(stack.item).max_stack_size();
+        let mut left = stack.count.max(0);
+
+        let same_item = |slot: &ItemStack| {
+            !slot.is_empty()
+                && slot.item == stack.item
+                && slot.added == stack.added
+                && slot.removed == stack.removed
+        };
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if left == 0 {
+                break;
+            }
+            if same_item(slot) && slot.count < max {
+                let moved = left.min(max - slot.count);
+                slot.count += moved;
+                left -= moved;
+                self.changed.insert(index);
+            }
+        }
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if left == 0 {
+                break;
+            }
+            if slot.is_empty() {
+                let moved = left.min(max);
+                *slot = ItemStack {
+                    count: moved,
+                    ..stack.clone()
+                };
+                left -= moved;
+                self.changed.insert(index);
+            }
+        }
+        left
+    }
+
+    /// Send every changed slot
+    pub fn send_changes(&mut self, buffer: &mut PacketBuffer) {
+        for index in std::mem::take(&mut self.changed) {
+            let packet = buffer_pool::frame(SET_PLAYER_INVENTORY_PACKET_ID, |data| {
+                write_varint(data, index as i32)?;
+                self.slots[index].encode(data)?;
+                Ok(())
+            });
+            if let Ok(packet) = packet {
+                buffer.push_outgoing(packet);
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Write-ahead journal and crash recovery
+//!
+//! Every tick, the state needed to resume the world is committed to an
+//! rgb-storage [`VersionedWorld`] under `<world>/journal`: the world time,
+//! every changed block and, once a second, each player's position, rotation
+//! and game mode. Each player's record gets a slot of its own, so players
+//! are told apart by their full UUID.
+//!
+//! Chunks are loaded from the region files at startup, and blocks are
+//! journaled as their latest state, so replaying the journal over them is
+//! always safe. A shutdown that saves every chunk checkpoints the journal
+//! into the region files: it writes the last journal tick to
+//! `<world>/journal.checkpoint`, and the next start deletes the journal if
+//! nothing was committed after it. Otherwise the next start replays the
+//! journal up to its last committed tick.
+//!
+//! `<world>/session.lock` exists while the server runs; finding it at
+//! startup means the last run didn't shut down cleanly. `--recover-to-tick
+//! <tick>` replays up to an earlier tick instead, as far back as the
+//! journal's oldest checkpoint. Blocks first changed after that tick keep
+//! the state the region files hold.
+//!
+//! Recovered players get their position back when they next log in.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{WrapErr, bail};
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use rgb_storage::{ComponentKey, Mutation, TickId, VersionedWorld};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::chunk;
+use crate::components::{BlockPos, GameMode, Player, Position, Rotation, Uuid, WorldTime};
+
+/// Journal component IDs (entity bits are 0 for the world, the packed
+/// position for blocks and the player's slot for players)
+const WORLD_TIME: u32 = 1;
+const BLOCK: u32 = 2;
+const PLAYER: u32 = 3;
+
+/// Ticks between journaling every player
+const PLAYER_INTERVAL: i64 = 20;
+
+/// File holding the journal tick whose changes are all in the region files
+const CHECKPOINT_FILE: &str = "journal.checkpoint";
+
+/// Where a player was, as journaled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub uuid: u128,
+    pub position: Position,
+    pub rotation: Rotation,
+    pub game_mode: GameMode,
+}
+
+/// One journaled value
+#[derive(Debug, Clone, Copy)]
+pub enum Record {
+    WorldTime(WorldTime),
+    Block(BlockPos, BlockState),
+    Player(PlayerRecord),
+}
+
+impl Record {
+    /// Decode the value journaled under `key`
+    pub fn decode(key: &ComponentKey, data: &[u8]) -> Option<Self> {
+        match key.component_raw() {
+            WORLD_TIME => serde_json::from_slice(data).ok().map(Self::WorldTime),
+            BLOCK => {
+                let id = data.try_into().map(u16::from_le_bytes).ok()?;
+                Some(Self::Block(block_pos(key)?, BlockState(id)))
+            }
+            PLAYER => serde_json::from_slice(data).ok().map(Self::Player),
+            _ => None,
+        }
+    }
+}
+
+/// Position of the block journaled under `key`, if it is a block key
+pub fn block_pos(key: &ComponentKey) -> Option<BlockPos> {
+    (key.component_raw() == BLOCK).then(|| BlockPos::from_packed(key.entity_bits() as i64))
+}
+
+/// World state replayed from the journal
+#[derive(Debug, Default)]
+pub struct Recovered {
+    pub tick: TickId,
+    pub world_time: Option<WorldTime>,
+    pub blocks: Vec<(BlockPos, BlockState)>,
+    pub players: HashMap<u128, PlayerRecord>,
+}
+
+impl Recovered {
+    pub fn from_state(tick: TickId, state: &BTreeMap<ComponentKey, Vec<u8>>) -> Self {
+        let mut recovered = Self {
+            tick,
+            ..Self::default()
+        };
+        for (key, data) in state {
+            match Record::decode(key, data) {
+                Some(Record::WorldTime(time)) => recovered.world_time = Some(time),
+                Some(Record::Block(pos, state)) => recovered.blocks.push((pos, state)),
+                Some(Record::Player(record)) => {
+                    recovered.players.insert(record.uuid, record);
+                }
+                None => {}
+            }
+        }
+        recovered
+    }
+}
+
+/// Global: The journal of the running session
+#[derive(Component)]
+pub struct Journal {
+    storage: VersionedWorld,
+    lock: PathBuf,
+    checkpoint: PathBuf,
+    /// Journal slot of every player journaled so far
+    player_slots: HashMap<u128, u64>,
+    /// Players recovered at startup who haven't logged in since
+    recovered_players: HashMap<u128, PlayerRecord>,
+}
+
+impl Journal {
+    /// Open the journal of a world directory, replaying it unless the last
+    /// run checkpointed it into the region files or `recover_to` is given,
+    /// and take the session lock.
+    pub fn open(
+        world_dir: &Path,
+        recover_to: Option<TickId>,
+    ) -> eyre::Result<(Self, Option<Recovered>)> {
+        let lock = world_dir.join("session.lock");
+        let journal_dir = world_dir.join("journal");
+        let checkpoint = world_dir.join(CHECKPOINT_FILE);
+        let unclean = lock.exists();
+
+        fs::create_dir_all(world_dir)?;
+        let mut storage =
+            VersionedWorld::open(&journal_dir).wrap_err("failed to open the journal")?;
+
+        let checkpointed = fs::read_to_string(&checkpoint)
+            .ok()
+            .and_then(|tick| tick.trim().parse::<TickId>().ok());
+        if !unclean && recover_to.is_none() && checkpointed == Some(storage.current_tick()) {
+            // Every journaled change is in the region files
+            drop(storage);
+            fs::remove_dir_all(&journal_dir)
+                .wrap_err("failed to delete the checkpointed journal")?;
+            storage = VersionedWorld::open(&journal_dir).wrap_err("failed to open the journal")?;
+        }
+        // Stale once the journal moves on
+        if checkpointed.is_some() {
+            fs::remove_file(&checkpoint).wrap_err("failed to remove the journal checkpoint")?;
+        }
+
+        let latest = storage.current_tick();
+        let target = match recover_to {
+            Some(tick) if tick > latest => {
+                bail!("can't recover to tick {tick}, the journal ends at tick {latest}")
+            }
+            Some(tick) => Some(tick),
+            None if unclean => {
+                warn!("The server didn't shut down cleanly");
+                Some(latest)
+            }
+            None if latest > 0 => {
+                warn!("The last shutdown didn't save every chunk");
+                Some(latest)
+            }
+            None => None,
+        };
+
+        let mut player_slots = HashMap::new();
+        let recovered = match target {
+            Some(tick) => {
+                info!("Recovering the world as of journal tick {tick}");
+                let state = if tick < latest {
+                    storage.restore_tick(tick)?
+                } else {
+                    storage.replay(tick)?
+                };
+                player_slots = journaled_player_slots(&state);
+                Some(Recovered::from_state(tick, &state))
+            }
+            None => None,
+        };
+
+        fs::write(&lock, std::process::id().to_string())
+            .wrap_err("failed to take the session lock")?;
+        let journal = Self {
+            storage,
+            lock,
+            checkpoint,
+            player_slots,
+            recovered_players: HashMap::new(),
+        };
+        Ok((journal, recovered))
+    }
+
+    /// Journal a block change
+    pub fn record_block(&mut self, pos: BlockPos, state: BlockState) {
+        self.storage.push(Mutation::Set {
+            key: ComponentKey::from_raw(pos.packed() as u64, BLOCK),
+            data: state.id().to_le_bytes().to_vec(),
+        });
+    }
+
+    fn record_json(&mut self, entity_bits: u64, component: u32, value: &impl Serialize) {
+        match serde_json::to_vec(value) {
+            Ok(data) => self.storage.push(Mutation::Set {
+                key: ComponentKey::from_raw(entity_bits, component),
+                data,
+            }),
+            Err(e) => warn!("Failed to journal component {component}: {e}"),
+        }
+    }
+
+    /// Journal slot of a player, taking the next free one for a new player
+    fn player_slot(&mut self, uuid: u128) -> u64 {
+        if let Some(&slot) = self.player_slots.get(&uuid) {
+            return slot;
+        }
+        let slot = self.player_slots.values().max().map_or(0, |&last| last + 1);
+        self.player_slots.insert(uuid, slot);
+        slot
+    }
+
+    /// Write every committed tick to disk and release the session lock,
+    /// marking the shutdown as clean. If `saved`, every chunk is in the
+    /// region files and the journal is checkpointed into them.
+    pub fn close(&self, saved: bool) {
+        if let Err(e) = self.storage.flush() {
+            // Keeping the lock replays what made it to disk on the next start
+            warn!("Failed to write the journal: {e}");
+            return;
+        }
+        if saved
+            && let Err(e) = fs::write(&self.checkpoint, self.storage.current_tick().to_string())
+        {
+            warn!("Failed to checkpoint the journal: {e}");
+        }
+        if let Err(e) = fs::remove_file(&self.lock) {
+            warn!("Failed to release the session lock: {e}");
+        }
+    }
+}
+
+/// Journal slots of the players in a replayed state
+fn journaled_player_slots(state: &BTreeMap<ComponentKey, Vec<u8>>) -> HashMap<u128, u64> {
+    state
+        .iter()
+        .filter_map(|(key, data)| match Record::decode(key, data)? {
+            Record::Player(record) => Some((record.uuid, key.entity_bits())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The tick passed as `--recover-to-tick <tick>` or `--recover-to-tick=<tick>`
+pub fn recover_to_tick_arg(mut args: impl Iterator<Item = String>) -> eyre::Result<Option<TickId>> {
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--recover-to-tick") {
+            Some("") => args.next(),
+            Some(value) => value.strip_prefix('=').map(str::to_string),
+            None => continue,
+        };
+        let Some(value) = value else {
+            bail!("--recover-to-tick needs a tick");
+        };
+        let tick = value
+            .parse()
+            .wrap_err_with(|| format!("invalid tick {value:?}"))?;
+        return Ok(Some(tick));
+    }
+    Ok(None)
+}
+
+/// Apply recovered state to the world loaded at startup
+pub fn apply_recovered(world: &WorldRef<'_>, recovered: Recovered) {
+    if let Some(time) = recovered.world_time {
+        world.set(time);
+    }
+    let blocks = recovered.blocks.len();
+    for (pos, state) in recovered.blocks {
+        chunk::set_block(world, pos.x, pos.y, pos.z, state);
+    }
+    let players = recovered.players.len();
+    world.get::<&mut Journal>(|journal| journal.recovered_players = recovered.players);
+    info!(
+        "Recovered tick {}: {blocks} changed blocks, {players} players",
+        recovered.tick
+    );
+}
+
+/// The recovered record of a player logging in, if there is one
+pub fn take_recovered_player(world: &WorldRef<'_>, uuid: u128) -> Option<PlayerRecord> {
+    world
+        .try_get::<&mut Journal>(|journal| journal.recovered_players.remove(&uuid))
+        .flatten()
+}
+
+/// Journal every player's position, rotation and game mode
+pub fn record_players(world: &WorldRef<'_>, journal: &mut Journal) {
+    world
+        .query::<(&Uuid, &Position, &Rotation, &GameMode)>()
+        .with(Player)
+        .build()
+        .each(|(uuid, position, rotation, game_mode)| {
+            let record = PlayerRecord {
+                uuid: uuid.0,
+                position: *position,
+                rotation: *rotation,
+                game_mode: *game_mode,
+            };
+            let slot = journal.player_slot(uuid.0);
+            journal.record_json(slot, PLAYER, &record);
+        });
+}
+
+/// Journal the world time (and periodically every player), then commit the
+/// tick
+pub fn commit_tick(world: &WorldRef<'_>, journal: &mut Journal) {
+    let time = world.get::<&WorldTime>(|t| *t);
+    journal.record_json(0, WORLD_TIME, &time);
+
+    if time.world_age % PLAYER_INTERVAL == 0 {
+        record_players(world, journal);
+    }
+
+    if let Err(e) = journal.storage.commit_tick() {
+        warn!("Failed to commit journal tick: {e}");
+    }
+}
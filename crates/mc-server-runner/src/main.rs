@@ -11,25 +11,56 @@
 //!
 //! This server uses Flecs ECS with a pipeline-based system architecture.
 
+mod anvil;
+mod audit;
 // mod audio;
+mod autosave;
+mod block_tick;
+mod bot;
+mod buffer_pool;
+mod chunk;
+mod collision;
 mod components;
+mod console;
+mod damage;
 #[cfg(feature = "dashboard")]
 mod dashboard;
+#[cfg(feature = "dashboard-tls")]
+mod dashboard_tls;
+mod entity_ids;
+mod fluid;
+mod game_rules;
+mod i18n;
+mod inventory;
+mod journal;
+mod map;
 mod network;
+mod pacing;
+mod player_data;
+mod protection;
 mod protocol;
-mod registry;
+mod protocol_state;
+mod rcon;
+mod redstone;
+#[cfg(feature = "reload")]
+mod reload;
+mod replay;
+#[cfg(feature = "dashboard")]
+mod saved_queries;
+mod shutdown;
+mod sniffer;
+mod stats;
 mod systems;
+mod view_distance;
 mod world_gen;
 
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
 use std::time::{Duration, Instant};
 
 use flecs_ecs::prelude::*;
 use tracing::info;
 
 use crate::components::*;
+use crate::entity_ids::EntityIdAllocator;
 use crate::network::NetworkChannels;
 
 fn main() -> eyre::Result<()> {
@@ -46,6 +77,9 @@ fn main() -> eyre::Result<()> {
     // Create ECS world
     let world = World::new();
 
+    // Redstone components live in their module's scope
+    world.import::<module_redstone::RedstoneModule>();
+
     // Initialize history tracking (must be before systems so hooks are set up)
     let history = systems::history::init_history_tracking(&world);
 
@@ -75,11 +109,74 @@ fn main() -> eyre::Result<()> {
     };
 
     // Set singletons
-    world.set(ServerConfig::default());
+    let config = ServerConfig::default();
+    if let Some(sniffer) = sniffer::PacketSniffer::from_env(config.packet_log.as_deref()) {
+        world.set(sniffer);
+    }
+    let recover_to = journal::recover_to_tick_arg(std::env::args().skip(1))?;
+    let replay = replay::replay_arg(std::env::args().skip(1));
+    if replay && recover_to.is_some() {
+        eyre::bail!("--replay and --recover-to-tick can't be combined");
+    }
+    let mut recovered = None;
+    let mut regions = None;
+    let mut game_rules = game_rules::GameRules::builtin();
+    collision::register_rules(&mut game_rules);
+    if let Some(world_dir) = config.world_dir_path() {
+        game_rules.load(&world_dir.join(game_rules::FILE));
+        let store = anvil::RegionStore::new(world_dir.join("region"));
+        if replay {
+            info!("Replaying the journal of {}", world_dir.display());
+            world.set(replay::Replay::open(&world_dir)?);
+        } else {
+            info!("Saving the world to {}", world_dir.display());
+            world.set(store.clone());
+            let (journal, state) = journal::Journal::open(&world_dir, recover_to)?;
+            world.set(journal);
+            recovered = state;
+            world.set(stats::StatsStore::new(world_dir.join("stats")));
+            match persist::PersistDb::open(world_dir.join("players")) {
+                Ok(db) => player_data::init(&world, db),
+                Err(e) => tracing::warn!("Failed to open the player data: {e}"),
+            }
+            match audit::AuditLog::open(&world_dir.join("audit")) {
+                Ok(log) => {
+                    world.set(log);
+                }
+                Err(e) => tracing::warn!("Failed to open the audit log: {e}"),
+            }
+            game_rules.save_to(world_dir.join(game_rules::FILE));
+        }
+        regions = Some(store);
+        #[cfg(feature = "dashboard")]
+        match saved_queries::SavedQueries::open(&world_dir.join("dashboard")) {
+            Ok(saved) => {
+                world.set(saved);
+            }
+            Err(e) => tracing::warn!("Failed to open saved queries: {e}"),
+        }
+    } else if recover_to.is_some() {
+        eyre::bail!("--recover-to-tick needs a world directory");
+    } else if replay {
+        eyre::bail!("--replay needs a world directory");
+    }
+    let rcon = config
+        .rcon_password()
+        .map(|password| rcon::RconServer::start(config.rcon_port(), password));
+    world.set(config);
     world.set(WorldTime::default());
+    world.set(game_rules);
     world.set(TpsTracker::default());
+    world.set(TickTimes::default());
+    world.set(view_distance::ViewDistance::default());
+    world.set(chunk::ChunkCache::default());
+    world.set(chunk::PendingBlockChanges::default());
+    world.set(protection::ProtectionLog::default());
+    world.set(protocol_state::StateTransitions::default());
+    world.set(autosave::SaveStats::default());
     world.set(DeltaTime::default());
-    world.set(EntityIdCounter::default());
+    world.set(EntityIdAllocator::default());
+    world.set(i18n::Translations::builtin());
     world.set(PendingPackets::default());
     world.set(ConnectionIndex::default());
     world.set(NetworkIngress {
@@ -93,23 +190,41 @@ fn main() -> eyre::Result<()> {
     });
 
     // Start network thread
-    network::start_network_thread(
+    let network = network::start_network_thread(
         channels.ingress_tx,
         channels.egress_rx,
         channels.disconnect_tx,
     );
 
-    // Generate spawn chunks
-    world_gen::generate_spawn_chunks(&world, 8);
+    // Load or generate spawn chunks
+    world_gen::generate_spawn_chunks(
+        &world,
+        &world_gen::DuneGenerator::default(),
+        regions.as_ref(),
+        8,
+    );
+    if let Some(recovered) = recovered {
+        journal::apply_recovered(&world.world(), recovered);
+    }
 
     info!("Server initialized");
+    // Modules change the world as they load, so not while the config is borrowed
+    #[cfg(feature = "reload")]
+    let reloadables = {
+        let config = world.get::<&ServerConfig>(ServerConfig::clone);
+        reload::Reloadables::load(&world, &config)
+    };
+    let mut console = console::Console::start(
+        &world,
+        #[cfg(feature = "reload")]
+        reloadables,
+    );
 
-    // Set up Ctrl+C handler
-    let running = Arc::new(AtomicBool::new(true));
+    // Shut down on SIGINT/SIGTERM
+    let running = Running::new();
+    world.set(running.clone());
     let r = running.clone();
-    ctrlc::set_handler(move || {
-        r.store(false, Ordering::SeqCst);
-    })?;
+    ctrlc::set_handler(move || r.stop())?;
 
     // Run game loop at 20 TPS
     let target_fps: f32 = std::env::var("TARGET_FPS")
@@ -117,10 +232,16 @@ fn main() -> eyre::Result<()> {
         .and_then(|p| p.parse().ok())
         .unwrap_or(20.0);
     let target_delta = Duration::from_secs_f32(1.0 / target_fps);
+    let catch_up = world.get::<&ServerConfig>(ServerConfig::catch_up);
+    info!("Catching up on missed ticks with {catch_up}");
+    let mut pacer = pacing::TickPacer::new(catch_up, target_delta);
     let mut last_tick = Instant::now();
 
-    while running.load(Ordering::SeqCst) {
-        let start = Instant::now();
+    while running.get() {
+        // Sleep until the tick is due, or decide how to catch up
+        let pace = pacer.wait();
+        let tick_start = Instant::now();
+        pacing::begin_tick(&world.world(), pace);
 
         // Calculate delta time
         let delta_time = last_tick.elapsed().as_secs_f32();
@@ -131,21 +252,34 @@ fn main() -> eyre::Result<()> {
 
         // Run all systems via Flecs pipeline
         world.progress();
+        player_data::flush_due(&world);
+
+        // Run console and RCON commands between ticks
+        if console.process(&world) == console::ConsoleAction::Stop {
+            running.stop();
+        }
+        if let Some(rcon) = &rcon
+            && rcon.process(&world, &mut console) == console::ConsoleAction::Stop
+        {
+            running.stop();
+        }
 
         // Process dashboard requests
         #[cfg(feature = "dashboard")]
         systems::dashboard::system_process_dashboard(&world, &dashboard_channels, &history);
 
         // Advance history tick
-        history.advance_tick();
+        history.advance_tick(&world);
+        systems::history::compact_periodically(&world, &history);
 
-        // Sleep to maintain target FPS
-        let elapsed = start.elapsed();
-        if elapsed < target_delta {
-            thread::sleep(target_delta - elapsed);
-        }
+        // Everything above counts towards the tick's time
+        let tick_time = tick_start.elapsed();
+        world.get::<(&mut TickTimes, &mut TpsTracker)>(|(times, tps)| {
+            times.record(tick_time);
+            tps.mspt = times.mspt();
+        });
     }
 
-    info!("Shutting down...");
+    shutdown::shutdown(&world, network);
     Ok(())
 }
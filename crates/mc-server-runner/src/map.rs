@@ -0,0 +1,118 @@
+//! Top-down map tiles for the dashboard
+//!
+//! A tile is a chunk seen from above: the color and height of each column's
+//! highest block. Tiles are rendered on request, cached on the chunk entity
+//! as a [`MapTile`] and dropped by [`chunk::set_block`] when a block changes.
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use serde::Serialize;
+
+use crate::chunk::{self, ChunkBlocks, HeightmapKind};
+use crate::components::ChunkPos;
+
+/// Most tiles one request may render
+pub const MAX_TILES: usize = 1024;
+
+/// Color of a column with no blocks
+const VOID: u32 = 0x00_00_00;
+
+/// Component: Cached map tile of a chunk
+#[derive(Component, Debug, Clone, Serialize)]
+pub struct MapTile {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    /// Top block color (`0xRRGGBB`) per column, `z * 16 + x`
+    pub colors: Vec<u32>,
+    /// Top block Y per column, `None` for an empty column
+    pub heights: Vec<Option<i32>>,
+}
+
+impl MapTile {
+    pub fn render(blocks: &ChunkBlocks, chunk_x: i32, chunk_z: i32) -> Self {
+        let mut colors = Vec::with_capacity(256);
+        let mut heights = Vec::with_capacity(256);
+        for z in 0..16 {
+            for x in 0..16 {
+                let height = blocks.height(HeightmapKind::WorldSurface, x, z);
+                colors.push(height.map_or(VOID, |y| block_color(blocks.block(x, y, z))));
+                heights.push(height);
+            }
+        }
+        Self {
+            chunk_x,
+            chunk_z,
+            colors,
+            heights,
+        }
+    }
+}
+
+/// Approximate map color of a block, by name
+pub fn block_color(state: BlockState) -> u32 {
+    let name = state.block_name().unwrap_or_default();
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    match name {
+        "water" | "bubble_column" | "kelp" | "kelp_plant" | "seagrass" | "tall_seagrass" => {
+            0x40_40_FF
+        }
+        "lava" | "magma_block" => 0xFF_60_00,
+        "grass_block" | "short_grass" | "tall_grass" | "fern" | "large_fern" => 0x7F_B2_38,
+        "sand" | "sandstone" | "birch_planks" => 0xF7_E9_A3,
+        "red_sand" | "red_sandstone" | "terracotta" => 0xD8_7F_33,
+        "snow" | "snow_block" | "powder_snow" => 0xFF_FF_FF,
+        "ice" | "packed_ice" | "blue_ice" => 0xA0_A0_FF,
+        "dirt" | "coarse_dirt" | "rooted_dirt" | "farmland" | "dirt_path" | "mud" => 0x97_6D_4D,
+        "gravel" | "clay" => 0xA4_A8_B8,
+        "netherrack" => 0x70_02_00,
+        "obsidian" => 0x19_19_19,
+        _ if name.ends_with("_leaves") => 0x00_7C_00,
+        _ if name.ends_with("_log") || name.ends_with("_wood") || name.ends_with("_planks") => {
+            0x8F_77_48
+        }
+        _ if name.ends_with("_wool") || name.ends_with("_concrete") => 0xC7_C7_C7,
+        _ if name.contains("flower") || name.ends_with("_tulip") => 0xFF_00_FF,
+        _ => 0x70_70_70,
+    }
+}
+
+/// Tiles of the loaded chunks in a chunk coordinate range (inclusive),
+/// rendering those not cached
+pub fn tiles(
+    world: &WorldRef<'_>,
+    min: (i32, i32),
+    max: (i32, i32),
+) -> Result<Vec<MapTile>, String> {
+    let width = i64::from(max.0) - i64::from(min.0) + 1;
+    let depth = i64::from(max.1) - i64::from(min.1) + 1;
+    if width <= 0 || depth <= 0 {
+        return Err("The chunk range is empty".to_string());
+    }
+    if width * depth > MAX_TILES as i64 {
+        return Err(format!(
+            "At most {MAX_TILES} tiles can be requested at once"
+        ));
+    }
+
+    let mut tiles = Vec::new();
+    for chunk_z in min.1..=max.1 {
+        for chunk_x in min.0..=max.0 {
+            let Some(chunk) = world.try_lookup_recursive(&chunk::chunk_name(chunk_x, chunk_z))
+            else {
+                continue;
+            };
+            if let Some(tile) = chunk.try_get::<&MapTile>(MapTile::clone) {
+                tiles.push(tile);
+                continue;
+            }
+            let rendered = chunk.try_get::<(&ChunkBlocks, &ChunkPos)>(|(blocks, pos)| {
+                MapTile::render(blocks, pos.x, pos.z)
+            });
+            if let Some(tile) = rendered {
+                chunk.set(tile.clone());
+                tiles.push(tile);
+            }
+        }
+    }
+    Ok(tiles)
+}
@@ -1,22 +1,29 @@
 //! Network layer - async TCP server bridging to ECS
 
 use std::collections::HashMap;
-use std::io::Cursor;
+use std::io::{Cursor, IoSlice};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
 use bytes::Bytes;
 use crossbeam_channel::{Receiver, Sender};
 use mc_protocol::read_varint;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tokio::sync::{Notify, RwLock};
+use tracing::{debug, error, info, warn};
 
 use crate::components::{DisconnectEvent, IncomingPacket, OutgoingPacket};
 
-/// Active connections map (connection_id -> sender for that connection)
-type ConnectionMap = Arc<RwLock<HashMap<u64, tokio::sync::mpsc::Sender<Bytes>>>>;
+/// Longest the network thread waits at shutdown for queued packets to be
+/// sent and clients to disconnect
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Active connections map (connection_id -> sender of packet batches for
+/// that connection)
+type ConnectionMap = Arc<RwLock<HashMap<u64, tokio::sync::mpsc::Sender<Vec<Bytes>>>>>;
 
 /// Channels for network I/O between async Tokio runtime and sync ECS world
 pub struct NetworkChannels {
@@ -57,13 +64,38 @@ impl Default for NetworkChannels {
     }
 }
 
+/// Handle to the network thread, used to shut it down
+pub struct NetworkHandle {
+    shutdown: Arc<Notify>,
+    thread: thread::JoinHandle<()>,
+}
+
+impl NetworkHandle {
+    /// Stop accepting connections. Existing connections stay open.
+    pub fn stop_accepting(&self) {
+        self.shutdown.notify_one();
+    }
+
+    /// Wait for the network thread to send every queued packet and for
+    /// clients to disconnect (up to [`SHUTDOWN_TIMEOUT`]), closing what's
+    /// left
+    pub fn join(self) {
+        self.stop_accepting();
+        if self.thread.join().is_err() {
+            error!("Network thread panicked");
+        }
+    }
+}
+
 /// Start the network thread with async TCP server
 pub fn start_network_thread(
     ingress_tx: Sender<IncomingPacket>,
     egress_rx: Receiver<OutgoingPacket>,
     disconnect_tx: Sender<DisconnectEvent>,
-) {
-    thread::spawn(move || {
+) -> NetworkHandle {
+    let shutdown = Arc::new(Notify::new());
+    let notified = shutdown.clone();
+    let thread = thread::spawn(move || {
         let rt = tokio::runtime::Builder::new_multi_thread()
             .worker_threads(2)
             .enable_all()
@@ -71,25 +103,28 @@ pub fn start_network_thread(
             .expect("Failed to create Tokio runtime");
 
         rt.block_on(async move {
-            if let Err(e) = run_network(ingress_tx, egress_rx, disconnect_tx).await {
+            if let Err(e) = run_network(ingress_tx, egress_rx, disconnect_tx, &notified).await {
                 error!("Network error: {}", e);
             }
         });
     });
 
     info!("Network thread started - TCP server starting on port 25565");
+    NetworkHandle { shutdown, thread }
 }
 
 async fn run_network(
     ingress_tx: Sender<IncomingPacket>,
     egress_rx: Receiver<OutgoingPacket>,
     disconnect_tx: Sender<DisconnectEvent>,
+    shutdown: &Notify,
 ) -> eyre::Result<()> {
     // Connection map for routing outgoing packets
     let connections: ConnectionMap = Arc::new(RwLock::new(HashMap::new()));
 
     // Spawn egress handler (routes packets from ECS to connections)
     let connections_for_egress = connections.clone();
+    let queued = egress_rx.clone();
     tokio::spawn(async move {
         loop {
             let egress_rx = egress_rx.clone();
@@ -104,12 +139,15 @@ async fn run_network(
                 break;
             };
 
-            let conn_id = packet.connection_id;
-            let data = packet.data;
-
             let conns = connections.read().await;
-            if let Some(tx) = conns.get(&conn_id) {
-                let _ = tx.send(data).await;
+            if let Some(tx) = conns.get(&packet.connection_id) {
+                let _ = tx.send(packet.packets).await;
+            }
+            drop(conns);
+            // Without its sender, the connection's writer ends after what's
+            // queued, and the connection closes with it
+            if packet.close {
+                connections.write().await.remove(&packet.connection_id);
             }
         }
     });
@@ -129,7 +167,10 @@ async fn run_network(
     let mut next_conn_id: u64 = 1;
 
     loop {
-        let (stream, addr) = listener.accept().await?;
+        let (stream, addr) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            () = shutdown.notified() => break,
+        };
         info!("Connection from {}", addr);
 
         let conn_id = next_conn_id;
@@ -141,7 +182,7 @@ async fn run_network(
 
         tokio::spawn(async move {
             // Create channel for this connection's outgoing packets
-            let (tx, rx) = tokio::sync::mpsc::channel::<Bytes>(256);
+            let (tx, rx) = tokio::sync::mpsc::channel::<Vec<Bytes>>(256);
 
             // Register connection
             {
@@ -150,7 +191,7 @@ async fn run_network(
             }
 
             // Handle connection
-            let result = handle_connection(stream, conn_id, ingress_tx, rx).await;
+            let result = handle_connection(stream, conn_id, addr, ingress_tx, rx).await;
 
             // Unregister connection
             {
@@ -169,57 +210,104 @@ async fn run_network(
             }
         });
     }
+
+    drop(listener);
+    info!("Stopped accepting connections");
+
+    // Clients leave once they get their disconnect packet
+    let drained = async {
+        while !queued.is_empty() || !connections.read().await.is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, drained)
+        .await
+        .is_err()
+    {
+        warn!(
+            "Closing {} connections that didn't disconnect",
+            connections.read().await.len()
+        );
+    }
+    Ok(())
 }
 
 async fn handle_connection(
     stream: TcpStream,
     conn_id: u64,
+    addr: SocketAddr,
     ingress_tx: Sender<IncomingPacket>,
-    mut egress_rx: tokio::sync::mpsc::Receiver<Bytes>,
+    mut egress_rx: tokio::sync::mpsc::Receiver<Vec<Bytes>>,
 ) -> eyre::Result<()> {
     let (mut reader, mut writer) = stream.into_split();
 
     // Spawn writer task
-    let writer_handle = tokio::spawn(async move {
-        while let Some(data) = egress_rx.recv().await {
-            if writer.write_all(&data).await.is_err() {
-                break;
+    let mut writer_handle = tokio::spawn(async move {
+        while let Some(packets) = egress_rx.recv().await {
+            if write_batch(&mut writer, &packets).await.is_err() {
+                return;
             }
             if writer.flush().await.is_err() {
-                break;
+                return;
             }
         }
+        // The server closed the connection
+        let _ = writer.shutdown().await;
     });
 
     // Read packets and send to ECS
-    loop {
-        let Ok(length) = read_varint_async(&mut reader).await else {
-            break;
-        };
+    let read = async move {
+        loop {
+            let Ok(length) = read_varint_async(&mut reader).await else {
+                break;
+            };
 
-        if length <= 0 {
-            continue;
-        }
+            if length <= 0 {
+                continue;
+            }
 
-        let mut data = vec![0u8; length as usize];
-        if reader.read_exact(&mut data).await.is_err() {
-            break;
-        }
+            let mut data = vec![0u8; length as usize];
+            if reader.read_exact(&mut data).await.is_err() {
+                break;
+            }
 
-        let mut cursor = Cursor::new(&data);
-        let Ok(packet_id) = read_varint(&mut cursor) else {
-            break;
-        };
-        let remaining = data[cursor.position() as usize..].to_vec();
+            let mut cursor = Cursor::new(&data);
+            let Ok(packet_id) = read_varint(&mut cursor) else {
+                break;
+            };
+            let remaining = data[cursor.position() as usize..].to_vec();
 
-        let _ = ingress_tx.send(IncomingPacket {
-            connection_id: conn_id,
-            packet_id,
-            data: remaining.into(),
-        });
+            let _ = ingress_tx.send(IncomingPacket {
+                connection_id: conn_id,
+                addr,
+                packet_id,
+                data: remaining.into(),
+            });
+        }
+    };
+
+    // Whichever side finishes first ends the connection
+    tokio::select! {
+        () = read => writer_handle.abort(),
+        _ = &mut writer_handle => {}
     }
+    Ok(())
+}
 
-    writer_handle.abort();
+/// Write all of `packets` with as few vectored writes as the socket allows
+async fn write_batch<W: AsyncWriteExt + Unpin>(
+    writer: &mut W,
+    packets: &[Bytes],
+) -> std::io::Result<()> {
+    let mut slices: Vec<IoSlice<'_>> = packets.iter().map(|data| IoSlice::new(data)).collect();
+    let mut remaining = &mut slices[..];
+    while !remaining.is_empty() {
+        let written = writer.write_vectored(remaining).await?;
+        if written == 0 {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        IoSlice::advance_slices(&mut remaining, written);
+    }
     Ok(())
 }
 
@@ -0,0 +1,137 @@
+//! Tick pacing
+//!
+//! [`TickPacer`] decides when the next tick starts. Ticks are due at a fixed
+//! interval; when one runs long, the following ones are late and the server
+//! is *behind*. What happens to those missed ticks is the
+//! [`CatchUpPolicy`]:
+//!
+//! - `burst:N` runs up to `N` of them back to back without sleeping, like
+//!   vanilla, so the world keeps the same number of ticks per wall-clock
+//!   second once the load passes.
+//! - `skip:N` runs a single tick and moves the world time forward by up to
+//!   `N` missed ticks too, for servers that would rather stay responsive
+//!   than grind through a backlog.
+//!
+//! Missed ticks beyond `N` are dropped and the schedule restarts from now,
+//! with a warning at most every [`WARN_INTERVAL`]. [`begin_tick`] records
+//! each decision on [`TpsTracker`], where `/tps` and the dashboard metrics
+//! show it.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use flecs_ecs::prelude::*;
+use tracing::warn;
+
+use crate::components::{CatchUpPolicy, TpsTracker, WorldTime};
+use crate::game_rules::GameRules;
+use crate::systems::broadcast_time;
+
+/// Least time between two "can't keep up" warnings
+const WARN_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How the next tick runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pace {
+    /// Whole ticks the loop was behind when the tick started
+    pub behind: u32,
+    /// The tick runs without sleeping to catch up
+    pub burst: bool,
+    /// Extra ticks the world time moves forward by
+    pub skipped: u32,
+    /// Missed ticks given up on
+    pub dropped: u32,
+}
+
+/// Schedules ticks at a fixed interval according to a [`CatchUpPolicy`]
+pub struct TickPacer {
+    policy: CatchUpPolicy,
+    interval: Duration,
+    /// When the next tick is due
+    due: Instant,
+    last_warning: Option<Instant>,
+}
+
+impl TickPacer {
+    pub fn new(policy: CatchUpPolicy, interval: Duration) -> Self {
+        Self {
+            policy,
+            interval,
+            due: Instant::now(),
+            last_warning: None,
+        }
+    }
+
+    /// Sleep until the next tick is due, or decide how to catch up if it
+    /// is overdue
+    pub fn wait(&mut self) -> Pace {
+        let mut now = Instant::now();
+        if now < self.due {
+            thread::sleep(self.due - now);
+            now = self.due;
+        }
+        let behind = self.ticks_behind(now);
+
+        let pace = match self.policy {
+            CatchUpPolicy::Burst { max_ticks } => {
+                let dropped = behind.saturating_sub(max_ticks);
+                if dropped > 0 {
+                    // Only the last `max_ticks` missed ticks stay overdue
+                    self.due = now.checked_sub(self.interval * max_ticks).unwrap_or(now);
+                }
+                self.due += self.interval;
+                Pace {
+                    behind,
+                    burst: behind > dropped,
+                    skipped: 0,
+                    dropped,
+                }
+            }
+            CatchUpPolicy::Skip { max_ticks } => {
+                let skipped = behind.min(max_ticks);
+                self.due = now + self.interval;
+                Pace {
+                    behind,
+                    burst: false,
+                    skipped,
+                    dropped: behind - skipped,
+                }
+            }
+        };
+
+        if pace.dropped > 0
+            && self
+                .last_warning
+                .is_none_or(|t| t.elapsed() >= WARN_INTERVAL)
+        {
+            self.last_warning = Some(now);
+            warn!(
+                "Can't keep up! {behind} ticks behind, dropping {} ({})",
+                pace.dropped, self.policy
+            );
+        }
+        pace
+    }
+
+    fn ticks_behind(&self, now: Instant) -> u32 {
+        let late = now.saturating_duration_since(self.due);
+        u32::try_from(late.as_nanos() / self.interval.as_nanos().max(1)).unwrap_or(u32::MAX)
+    }
+}
+
+/// Record how this tick runs and move the world time past skipped ticks
+pub fn begin_tick(world: &WorldRef<'_>, pace: Pace) {
+    world.get::<&mut TpsTracker>(|tps| {
+        tps.behind = pace.behind;
+        tps.caught_up += u64::from(pace.burst);
+        tps.skipped += u64::from(pace.skipped);
+        tps.dropped += u64::from(pace.dropped);
+    });
+
+    if pace.skipped > 0 {
+        let daylight_cycle = world.get::<&GameRules>(GameRules::do_daylight_cycle);
+        world.get::<&mut WorldTime>(|time| time.skip(pace.skipped, daylight_cycle));
+        // Clients only predict the time one tick at a time
+        broadcast_time(world, daylight_cycle);
+    }
+}
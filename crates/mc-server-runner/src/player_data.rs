@@ -0,0 +1,66 @@
+//! Player state kept across sessions
+//!
+//! With a world directory, a player's position, rotation and health are
+//! stored by UUID in `<world>/players` and restored when they log in again
+//! (a journal recovery still wins over them). Position and rotation change
+//! every tick, so while playing they're written at most every
+//! [`SAVE_INTERVAL`]. When the player leaves or the server shuts down, all
+//! of them are written together in one transaction, so a crash can't leave
+//! the saved position and health from different moments.
+
+use std::time::Duration;
+
+use flecs_ecs::prelude::*;
+use persist::{PersistDb, PersistDbSingleton, PersistExt};
+use tracing::{debug, error};
+
+use crate::components::{Player, Position, Rotation, Uuid};
+use crate::damage::Health;
+
+/// Shortest time between two writes of a moving player's position
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Restore player state from `db` whenever a [`Uuid`] is set, and save it
+/// as it changes
+pub fn init(world: &World, db: PersistDb) {
+    persist::init_with_db::<Uuid>(world, db);
+    world
+        .component::<Position>()
+        .persist_throttled::<Uuid>(SAVE_INTERVAL);
+    world
+        .component::<Rotation>()
+        .persist_throttled::<Uuid>(SAVE_INTERVAL);
+    world.component::<Health>().persist::<Uuid>();
+}
+
+/// Write the positions and rotations held back for longer than
+/// [`SAVE_INTERVAL`]
+pub fn flush_due(world: &World) {
+    if world.try_get::<&PersistDbSingleton>(|_| ()).is_some() {
+        persist::flush_due(world);
+    }
+}
+
+/// Write all of a leaving player's state in one transaction
+pub fn save(player: EntityView<'_>) {
+    if player
+        .world()
+        .try_get::<&PersistDbSingleton>(|_| ())
+        .is_none()
+    {
+        return;
+    }
+    match persist::persist_transaction::<Uuid>(player) {
+        Ok(saved) => debug!("Saved {saved} player components"),
+        Err(e) => error!("Failed to save player data: {e}"),
+    }
+}
+
+/// Write every online player's state, e.g. at shutdown
+pub fn save_all(world: &WorldRef<'_>) {
+    world
+        .query::<()>()
+        .with(Player)
+        .build()
+        .each_entity(|player, ()| save(player));
+}
@@ -0,0 +1,195 @@
+//! Spawn and region protection
+//!
+//! Players can't break or use (flip levers on) blocks where they may not
+//! build:
+//! - within [`ServerConfig::spawn_protection`] blocks of the spawn column,
+//!   measured like vanilla as the larger of the X and Z distances
+//! - inside a [`Claim`] they don't own
+//!
+//! A claim is an entity with a [`Claim`]: a box of blocks and the UUIDs of
+//! the players who may build in it. `/claim` claims a box for the player
+//! running it. Claims aren't saved yet, so they're gone after a restart.
+//!
+//! A denied action is undone on the client by acknowledging it without
+//! changing the block. The player is told why on the action bar, and a
+//! [`Denial`] is queued on [`ProtectionLog`], which [`log_denials`] logs
+//! and clears at the end of the tick.
+//!
+//! [`ServerConfig::spawn_protection`]: crate::components::ServerConfig::spawn_protection
+
+use flecs_ecs::prelude::*;
+use mc_text::tr;
+use tracing::info;
+
+use crate::components::{BlockPos, HudText, Name, Position, ServerConfig, Uuid, WorldTime};
+use crate::i18n::{Translations, locale_of};
+
+/// HUD source of the denial message
+const HUD_PROTECTION: &str = "protection";
+/// Above the position/TPS display
+const HUD_PRIORITY: i32 = 10;
+/// Ticks the denial message stays on the action bar
+const HUD_TTL: i64 = 40;
+
+/// Component: A claimed box of blocks, inclusive, only its owners may
+/// build in
+#[derive(Component, Debug, Clone)]
+pub struct Claim {
+    pub min: BlockPos,
+    pub max: BlockPos,
+    /// UUIDs of the players who may build
+    pub owners: Vec<u128>,
+}
+
+impl Claim {
+    /// The box between two corners
+    pub fn new(a: BlockPos, b: BlockPos, owners: Vec<u128>) -> Self {
+        Self {
+            min: BlockPos::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: BlockPos::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+            owners,
+        }
+    }
+
+    pub fn contains(&self, pos: BlockPos) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x)
+            && (self.min.y..=self.max.y).contains(&pos.y)
+            && (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+            && self.min.z <= other.max.z
+            && other.min.z <= self.max.z
+    }
+}
+
+/// What a player tried to do to a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Break,
+    Use,
+}
+
+/// Why a player may not build somewhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denied {
+    SpawnProtection,
+    /// Inside this claim entity
+    Claim(Entity),
+}
+
+/// An action protection prevented
+#[derive(Debug, Clone)]
+pub struct Denial {
+    pub player: Entity,
+    pub pos: BlockPos,
+    pub action: Action,
+    pub reason: Denied,
+}
+
+/// Global: Actions denied this tick
+#[derive(Component, Debug, Default)]
+pub struct ProtectionLog {
+    pub denials: Vec<Denial>,
+}
+
+/// Whether `player` may build at `pos`
+pub fn check(world: &WorldRef<'_>, player: EntityView<'_>, pos: BlockPos) -> Result<(), Denied> {
+    let radius = world.get::<&ServerConfig>(ServerConfig::spawn_protection);
+    if radius > 0 {
+        let spawn = Position::SPAWN;
+        let dx = (pos.x - spawn.x.floor() as i32).unsigned_abs();
+        let dz = (pos.z - spawn.z.floor() as i32).unsigned_abs();
+        if dx.max(dz) <= radius {
+            return Err(Denied::SpawnProtection);
+        }
+    }
+
+    let uuid = player.try_get::<&Uuid>(|u| u.0);
+    let claim = world.query::<&Claim>().build().find(|claim| {
+        claim.contains(pos) && !uuid.is_some_and(|uuid| claim.owners.contains(&uuid))
+    });
+    match claim {
+        Some(claim) => Err(Denied::Claim(claim.id())),
+        None => Ok(()),
+    }
+}
+
+/// Check that `player` may `action` the block at `pos`; if not, tell them
+/// why and queue the denial
+pub fn allowed(
+    world: &WorldRef<'_>,
+    player: EntityView<'_>,
+    pos: BlockPos,
+    action: Action,
+) -> bool {
+    let Err(reason) = check(world, player, pos) else {
+        return true;
+    };
+
+    let key = match reason {
+        Denied::SpawnProtection => "protection.spawn",
+        Denied::Claim(_) => "protection.claim",
+    };
+    let locale = locale_of(player);
+    let message = world.get::<&Translations>(|t| tr!(t.catalog, &locale, key).to_plain());
+    let now = world.get::<&WorldTime>(|t| t.world_age);
+    player.try_get::<&mut HudText>(|hud| {
+        hud.set(HUD_PROTECTION, message, HUD_PRIORITY, Some(HUD_TTL), now);
+    });
+
+    world.get::<&mut ProtectionLog>(|log| {
+        log.denials.push(Denial {
+            player: player.id(),
+            pos,
+            action,
+            reason,
+        });
+    });
+    false
+}
+
+/// Claim a box for `owners`, unless it overlaps a claim they don't all own
+pub fn claim(world: &WorldRef<'_>, claim: Claim) -> Option<Entity> {
+    let overlaps = world
+        .query::<&Claim>()
+        .build()
+        .find(|other| {
+            other.intersects(&claim)
+                && !claim
+                    .owners
+                    .iter()
+                    .all(|owner| other.owners.contains(owner))
+        })
+        .is_some();
+    if overlaps {
+        return None;
+    }
+    Some(world.entity().set(claim).id())
+}
+
+/// Log and clear this tick's denials
+pub fn log_denials(world: &WorldRef<'_>, log: &mut ProtectionLog) {
+    for denial in log.denials.drain(..) {
+        let player = world
+            .entity_from_id(denial.player)
+            .try_get::<&Name>(|n| n.value.clone())
+            .unwrap_or_default();
+        let action = match denial.action {
+            Action::Break => "break",
+            Action::Use => "use",
+        };
+        let reason = match denial.reason {
+            Denied::SpawnProtection => "spawn protection".to_string(),
+            Denied::Claim(claim) => format!("claim {claim}"),
+        };
+        info!(
+            "{player} may not {action} the block at {:?} ({reason})",
+            denial.pos
+        );
+    }
+}
@@ -2,31 +2,43 @@
 
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use bytes::{BufMut, Bytes, BytesMut};
-use mc_protocol::{Decode, Encode, nbt, write_varint};
+use mc_data::{configuration, login, play};
+use mc_protocol::{Decode, Encode, Packet, write_varint};
+use mc_text::Text;
 use serde::Serialize;
 
-use crate::components::PacketBuffer;
+use crate::buffer_pool;
+use crate::components::{ConnectionState, PacketBuffer};
 
 // ============================================================================
 // Packet encoding
 // ============================================================================
 
 /// Encode a packet with ID and data into a length-prefixed packet
+///
+/// Allocates exactly once, for frames that are kept around (such as cached
+/// chunks); short-lived packets go through [`buffer_pool::frame`].
 pub fn encode_packet(packet_id: i32, data: &[u8]) -> Bytes {
-    let mut packet_id_bytes = Vec::new();
-    write_varint(&mut packet_id_bytes, packet_id).expect("varint write");
-
-    let length = packet_id_bytes.len() + data.len();
-    let mut length_bytes = Vec::new();
-    write_varint(&mut length_bytes, length as i32).expect("varint write");
-
-    let mut buf = BytesMut::with_capacity(length_bytes.len() + packet_id_bytes.len() + data.len());
-    buf.put_slice(&length_bytes);
-    buf.put_slice(&packet_id_bytes);
+    let length = varint_len(packet_id) + data.len();
+    let mut buf = BytesMut::with_capacity(varint_len(length as i32) + length);
+    let mut writer = (&mut buf).writer();
+    write_varint(&mut writer, length as i32).expect("varint write");
+    write_varint(&mut writer, packet_id).expect("varint write");
     buf.put_slice(data);
     buf.freeze()
 }
 
+/// Bytes a VarInt takes on the wire
+pub const fn varint_len(value: i32) -> usize {
+    match value as u32 {
+        0..0x80 => 1,
+        0x80..0x4000 => 2,
+        0x4000..0x20_0000 => 3,
+        0x20_0000..0x1000_0000 => 4,
+        _ => 5,
+    }
+}
+
 // ============================================================================
 // Handshake packets
 // ============================================================================
@@ -41,13 +53,42 @@ pub fn parse_handshake(data: &[u8]) -> eyre::Result<(i32, i32)> {
     Ok((protocol_version, next_state))
 }
 
-/// Create status response JSON
-pub fn create_status_response(max_players: i32, motd: &str) -> eyre::Result<Vec<u8>> {
+/// Maximum number of players listed in the status response (matches vanilla)
+pub const STATUS_SAMPLE_SIZE: usize = 12;
+
+/// Online players reported in the status response
+#[derive(Debug, Clone, Default)]
+pub struct OnlinePlayers {
+    /// Number of players online
+    pub online: i32,
+    /// Players shown in the server list tooltip as (name, uuid)
+    pub sample: Vec<(String, u128)>,
+}
+
+/// Format a UUID in the hyphenated form used by the status response
+pub fn format_uuid(uuid: u128) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        uuid >> 96,
+        (uuid >> 80) & 0xFFFF,
+        (uuid >> 64) & 0xFFFF,
+        (uuid >> 48) & 0xFFFF,
+        uuid & 0xFFFF_FFFF_FFFF
+    )
+}
+
+/// Write the status response JSON
+pub fn write_status_response(
+    data: &mut Vec<u8>,
+    max_players: i32,
+    motd: &str,
+    players: &OnlinePlayers,
+) -> eyre::Result<()> {
     #[derive(Serialize)]
     struct ServerStatus {
         version: Version,
         players: Players,
-        description: Description,
+        description: Text,
         #[serde(rename = "enforcesSecureChat")]
         enforces_secure_chat: bool,
     }
@@ -71,11 +112,6 @@ pub fn create_status_response(max_players: i32, motd: &str) -> eyre::Result<Vec<
         id: String,
     }
 
-    #[derive(Serialize)]
-    struct Description {
-        text: String,
-    }
-
     let status = ServerStatus {
         version: Version {
             name: mc_data::PROTOCOL_NAME.to_string(),
@@ -83,19 +119,23 @@ pub fn create_status_response(max_players: i32, motd: &str) -> eyre::Result<Vec<
         },
         players: Players {
             max: max_players,
-            online: 0,
-            sample: vec![],
-        },
-        description: Description {
-            text: motd.to_string(),
+            online: players.online,
+            sample: players
+                .sample
+                .iter()
+                .map(|(name, uuid)| PlayerSample {
+                    name: name.clone(),
+                    id: format_uuid(*uuid),
+                })
+                .collect(),
         },
+        description: Text::literal(motd),
         enforces_secure_chat: false,
     };
 
     let json = serde_json::to_string(&status)?;
-    let mut data = Vec::new();
-    json.encode(&mut data)?;
-    Ok(data)
+    json.encode(data)?;
+    Ok(())
 }
 
 // ============================================================================
@@ -126,58 +166,98 @@ pub fn parse_login_start(data: &[u8]) -> eyre::Result<(String, u128)> {
     Ok((name, uuid.0))
 }
 
-pub fn create_login_success(uuid: u128, name: &str) -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
-    mc_protocol::Uuid(uuid).encode(&mut data)?;
-    name.to_string().encode(&mut data)?;
-    write_varint(&mut data, 0)?; // 0 properties
-    Ok(data)
+pub fn write_login_success(data: &mut Vec<u8>, uuid: u128, name: &str) -> eyre::Result<()> {
+    mc_protocol::Uuid(uuid).encode(data)?;
+    name.to_string().encode(data)?;
+    write_varint(data, 0)?; // 0 properties
+    Ok(())
 }
 
-pub fn create_known_packs() -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
-    write_varint(&mut data, 1)?;
-    "minecraft".to_string().encode(&mut data)?;
-    "core".to_string().encode(&mut data)?;
-    "1.21".to_string().encode(&mut data)?;
-    Ok(data)
+pub fn write_known_packs(data: &mut Vec<u8>) -> eyre::Result<()> {
+    write_varint(data, 1)?;
+    "minecraft".to_string().encode(data)?;
+    "core".to_string().encode(data)?;
+    "1.21".to_string().encode(data)?;
+    Ok(())
 }
 
 // ============================================================================
-// Play packets
+// Custom payloads (plugin messages)
 // ============================================================================
 
-pub fn create_play_login(entity_id: i32, max_players: i32) -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
+/// Channel used by client and server to report their brand
+pub const BRAND_CHANNEL: &str = "minecraft:brand";
+
+/// Split a custom payload into its channel and remaining payload bytes
+pub fn parse_custom_payload(data: &[u8]) -> eyre::Result<(String, &[u8])> {
+    let mut cursor = std::io::Cursor::new(data);
+    let channel = String::decode(&mut cursor)?;
+    let rest = &data[cursor.position() as usize..];
+    Ok((channel, rest))
+}
+
+/// Parse the brand string from a `minecraft:brand` payload
+pub fn parse_brand(payload: &[u8]) -> eyre::Result<String> {
+    let mut cursor = std::io::Cursor::new(payload);
+    Ok(String::decode(&mut cursor)?)
+}
 
+/// Locale from a Client Information packet (configuration or play); the
+/// remaining settings are ignored
+pub fn parse_client_locale(data: &[u8]) -> eyre::Result<String> {
+    let mut cursor = std::io::Cursor::new(data);
+    Ok(String::decode(&mut cursor)?.to_ascii_lowercase())
+}
+
+pub fn write_brand_payload(data: &mut Vec<u8>, brand: &str) -> eyre::Result<()> {
+    BRAND_CHANNEL.to_string().encode(data)?;
+    brand.to_string().encode(data)?;
+    Ok(())
+}
+
+// ============================================================================
+// Play packets
+// ============================================================================
+
+pub fn write_play_login(
+    data: &mut Vec<u8>,
+    entity_id: i32,
+    max_players: i32,
+    game_mode: u8,
+) -> eyre::Result<()> {
     data.write_i32::<BigEndian>(entity_id)?;
-    false.encode(&mut data)?; // is_hardcore
-    write_varint(&mut data, 1)?; // 1 dimension
-    "minecraft:overworld".to_string().encode(&mut data)?;
-    write_varint(&mut data, max_players)?; // max_players
-    write_varint(&mut data, 8)?; // view_distance
-    write_varint(&mut data, 8)?; // simulation_distance
-    false.encode(&mut data)?; // reduced_debug_info
-    true.encode(&mut data)?; // enable_respawn_screen
-    false.encode(&mut data)?; // do_limited_crafting
-    write_varint(&mut data, 0)?; // dimension_type (registry ID)
-    "minecraft:overworld".to_string().encode(&mut data)?; // dimension
+    false.encode(data)?; // is_hardcore
+    write_varint(data, 1)?; // 1 dimension
+    "minecraft:overworld".to_string().encode(data)?;
+    write_varint(data, max_players)?; // max_players
+    write_varint(data, 8)?; // view_distance
+    write_varint(data, 8)?; // simulation_distance
+    false.encode(data)?; // reduced_debug_info
+    true.encode(data)?; // enable_respawn_screen
+    false.encode(data)?; // do_limited_crafting
+    write_varint(data, 0)?; // dimension_type (registry ID)
+    "minecraft:overworld".to_string().encode(data)?; // dimension
     data.write_i64::<BigEndian>(0)?; // hashed_seed
-    data.write_u8(1)?; // game_mode (creative)
+    data.write_u8(game_mode)?;
     data.write_i8(-1)?; // previous_game_mode
-    false.encode(&mut data)?; // is_debug
-    true.encode(&mut data)?; // is_flat
-    false.encode(&mut data)?; // has_death_location
-    write_varint(&mut data, 0)?; // portal_cooldown
-    write_varint(&mut data, 63)?; // sea_level
-    false.encode(&mut data)?; // enforces_secure_chat
-
-    Ok(data)
+    false.encode(data)?; // is_debug
+    true.encode(data)?; // is_flat
+    false.encode(data)?; // has_death_location
+    write_varint(data, 0)?; // portal_cooldown
+    write_varint(data, 63)?; // sea_level
+    false.encode(data)?; // enforces_secure_chat
+
+    Ok(())
 }
 
-pub fn create_player_position(x: f64, y: f64, z: f64, teleport_id: i32) -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
-    write_varint(&mut data, teleport_id)?;
+pub fn write_player_position(
+    data: &mut Vec<u8>,
+    x: f64,
+    y: f64,
+    z: f64,
+    teleport_id: i32,
+) -> eyre::Result<()> {
+    write_varint(data, teleport_id)?;
     data.write_f64::<BigEndian>(x)?;
     data.write_f64::<BigEndian>(y)?;
     data.write_f64::<BigEndian>(z)?;
@@ -187,52 +267,229 @@ pub fn create_player_position(x: f64, y: f64, z: f64, teleport_id: i32) -> eyre:
     data.write_f32::<BigEndian>(0.0)?; // yaw
     data.write_f32::<BigEndian>(0.0)?; // pitch
     data.write_i32::<BigEndian>(0)?; // flags
-    Ok(data)
+    Ok(())
 }
 
-pub fn create_game_event_start_waiting() -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
+/// Player Position relative to where the client is: moved by `(dx, dz)`,
+/// with rotation and velocity kept
+pub fn write_player_push(data: &mut Vec<u8>, dx: f64, dz: f64) -> eyre::Result<()> {
+    write_varint(data, 0)?; // teleport_id
+    data.write_f64::<BigEndian>(dx)?;
+    data.write_f64::<BigEndian>(0.0)?;
+    data.write_f64::<BigEndian>(dz)?;
+    data.write_f64::<BigEndian>(0.0)?; // vel_x
+    data.write_f64::<BigEndian>(0.0)?; // vel_y
+    data.write_f64::<BigEndian>(0.0)?; // vel_z
+    data.write_f32::<BigEndian>(0.0)?; // yaw
+    data.write_f32::<BigEndian>(0.0)?; // pitch
+    data.write_i32::<BigEndian>(0xFF)?; // flags: everything relative
+    Ok(())
+}
+
+/// Velocity as the protocol's low precision vector: the largest component
+/// sets a scale, and each is packed into 15 bits relative to it
+pub fn write_lp_vec3(data: &mut Vec<u8>, x: f64, y: f64, z: f64) -> eyre::Result<()> {
+    const LIMIT: f64 = 1.717_986_918_3e10;
+    const MIN_SCALE: f64 = 3.051_944_088_384_301e-5;
+    let sanitize = |v: f64| {
+        if v.is_nan() {
+            0.0
+        } else {
+            v.clamp(-LIMIT, LIMIT)
+        }
+    };
+    let (x, y, z) = (sanitize(x), sanitize(y), sanitize(z));
+    let largest = x.abs().max(y.abs()).max(z.abs());
+    if largest < MIN_SCALE {
+        data.write_u8(0)?;
+        return Ok(());
+    }
+
+    let scale = largest.ceil() as u64;
+    let big = scale & 3 != scale;
+    let markers = if big { (scale & 3) | 4 } else { scale };
+    let pack = |v: f64| ((v / scale as f64 * 0.5 + 0.5) * 32766.0).round() as u64;
+    let packed = markers | (pack(x) << 3) | (pack(y) << 18) | (pack(z) << 33);
+    data.write_u8(packed as u8)?;
+    data.write_u8((packed >> 8) as u8)?;
+    data.write_u32::<BigEndian>((packed >> 16) as u32)?;
+    if big {
+        write_varint(data, (scale >> 2) as i32)?;
+    }
+    Ok(())
+}
+
+pub fn write_set_entity_motion(
+    data: &mut Vec<u8>,
+    entity_id: i32,
+    x: f64,
+    y: f64,
+    z: f64,
+) -> eyre::Result<()> {
+    write_varint(data, entity_id)?;
+    write_lp_vec3(data, x, y, z)
+}
+
+pub fn write_game_event_start_waiting(data: &mut Vec<u8>) -> eyre::Result<()> {
     data.write_u8(13)?;
     data.write_f32::<BigEndian>(0.0)?;
-    Ok(data)
+    Ok(())
+}
+
+pub fn write_set_center_chunk(data: &mut Vec<u8>, x: i32, z: i32) -> eyre::Result<()> {
+    write_varint(data, x)?;
+    write_varint(data, z)?;
+    Ok(())
+}
+
+pub fn write_set_chunk_cache_radius(data: &mut Vec<u8>, radius: i32) -> eyre::Result<()> {
+    write_varint(data, radius)?;
+    Ok(())
+}
+
+/// Chunk position as one long: Z in the high half, X in the low half
+pub fn write_forget_chunk(data: &mut Vec<u8>, x: i32, z: i32) -> eyre::Result<()> {
+    data.write_i32::<BigEndian>(z)?;
+    data.write_i32::<BigEndian>(x)?;
+    Ok(())
 }
 
-pub fn create_set_center_chunk(x: i32, z: i32) -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
-    write_varint(&mut data, x)?;
-    write_varint(&mut data, z)?;
-    Ok(data)
+pub fn write_block_update(
+    data: &mut Vec<u8>,
+    x: i32,
+    y: i32,
+    z: i32,
+    state: i32,
+) -> eyre::Result<()> {
+    // Position: X (26 bits), Z (26 bits), Y (12 bits)
+    let position = ((i64::from(x) & 0x3FF_FFFF) << 38)
+        | ((i64::from(z) & 0x3FF_FFFF) << 12)
+        | (i64::from(y) & 0xFFF);
+    data.write_i64::<BigEndian>(position)?;
+    write_varint(data, state)?;
+    Ok(())
 }
 
-pub fn create_set_time(world_age: i64, time_of_day: i64) -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
+pub fn write_block_changed_ack(data: &mut Vec<u8>, sequence: i32) -> eyre::Result<()> {
+    write_varint(data, sequence)?;
+    Ok(())
+}
+
+/// `increasing`: whether the client advances the time of day itself
+pub fn write_set_time(
+    data: &mut Vec<u8>,
+    world_age: i64,
+    time_of_day: i64,
+    increasing: bool,
+) -> eyre::Result<()> {
     data.write_i64::<BigEndian>(world_age)?;
     data.write_i64::<BigEndian>(time_of_day)?;
-    false.encode(&mut data)?;
-    Ok(data)
+    increasing.encode(data)?;
+    Ok(())
 }
 
-pub fn create_keepalive() -> eyre::Result<Vec<u8>> {
-    let timestamp = std::time::SystemTime::now()
+fn unix_millis() -> i64 {
+    std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .expect("time")
-        .as_millis() as i64;
-    let mut data = Vec::new();
-    data.write_i64::<BigEndian>(timestamp)?;
-    Ok(data)
+        .as_millis() as i64
 }
 
-pub fn create_chunk_batch_finished(count: i32) -> eyre::Result<Vec<u8>> {
-    let mut data = Vec::new();
-    write_varint(&mut data, count)?;
-    Ok(data)
+/// Keep-alive IDs are the send time in Unix milliseconds, so the response
+/// carries everything needed to measure the round-trip.
+pub fn write_keepalive(data: &mut Vec<u8>) -> eyre::Result<()> {
+    data.write_i64::<BigEndian>(unix_millis())?;
+    Ok(())
 }
 
-pub fn create_action_bar_text(text: &str) -> eyre::Result<Vec<u8>> {
-    let compound = nbt! {
-        "text" => text,
-    };
-    Ok(compound.to_network_bytes())
+/// Longest round-trip accepted from a keep-alive response
+const MAX_KEEPALIVE_RTT_MS: i64 = 30_000;
+
+/// Round-trip time for a keep-alive response with `id`, if plausible.
+///
+/// IDs from the future or older than the keep-alive timeout are rejected.
+pub fn keepalive_rtt(id: i64) -> Option<i32> {
+    let rtt = unix_millis() - id;
+    (0..=MAX_KEEPALIVE_RTT_MS)
+        .contains(&rtt)
+        .then_some(rtt as i32)
+}
+
+/// `PlayerInfoUpdate` action bits (1.21.4+: 8 actions, one byte)
+const PLAYER_INFO_ADD_PLAYER: u8 = 0x01;
+const PLAYER_INFO_UPDATE_LISTED: u8 = 0x08;
+const PLAYER_INFO_UPDATE_LATENCY: u8 = 0x10;
+
+/// One row of the tab list
+#[derive(Debug, Clone)]
+pub struct PlayerInfoEntry {
+    pub uuid: u128,
+    pub name: String,
+    pub latency_ms: i32,
+}
+
+/// `PlayerInfoUpdate` adding `entries` to the tab list with their latency
+pub fn write_player_info_update(
+    data: &mut Vec<u8>,
+    entries: &[PlayerInfoEntry],
+) -> eyre::Result<()> {
+    data.push(PLAYER_INFO_ADD_PLAYER | PLAYER_INFO_UPDATE_LISTED | PLAYER_INFO_UPDATE_LATENCY);
+    write_varint(data, entries.len() as i32)?;
+    for entry in entries {
+        mc_protocol::Uuid(entry.uuid).encode(data)?;
+        // ADD_PLAYER: name + no properties (offline mode)
+        entry.name.encode(data)?;
+        write_varint(data, 0)?;
+        // UPDATE_LISTED
+        true.encode(data)?;
+        // UPDATE_LATENCY
+        write_varint(data, entry.latency_ms)?;
+    }
+    Ok(())
+}
+
+/// `PlayerInfoUpdate` refreshing the latency of players already listed
+pub fn write_player_latency_update(
+    data: &mut Vec<u8>,
+    entries: &[PlayerInfoEntry],
+) -> eyre::Result<()> {
+    data.push(PLAYER_INFO_UPDATE_LATENCY);
+    write_varint(data, entries.len() as i32)?;
+    for entry in entries {
+        mc_protocol::Uuid(entry.uuid).encode(data)?;
+        write_varint(data, entry.latency_ms)?;
+    }
+    Ok(())
+}
+
+/// `PlayerInfoRemove` dropping `uuids` from the tab list
+pub fn write_player_info_remove(data: &mut Vec<u8>, uuids: &[u128]) -> eyre::Result<()> {
+    write_varint(data, uuids.len() as i32)?;
+    for &uuid in uuids {
+        mc_protocol::Uuid(uuid).encode(data)?;
+    }
+    Ok(())
+}
+
+pub fn write_chunk_batch_finished(data: &mut Vec<u8>, count: i32) -> eyre::Result<()> {
+    write_varint(data, count)?;
+    Ok(())
+}
+
+pub fn write_action_bar_text(data: &mut Vec<u8>, text: &str) -> eyre::Result<()> {
+    data.extend_from_slice(&Text::literal(text).to_network_bytes());
+    Ok(())
+}
+
+/// `AwardStats` with `(stat type ID, stat ID, value)` entries
+pub fn write_award_stats(data: &mut Vec<u8>, entries: &[(i32, i32, i32)]) -> eyre::Result<()> {
+    write_varint(data, entries.len() as i32)?;
+    for &(stat_type, stat, value) in entries {
+        write_varint(data, stat_type)?;
+        write_varint(data, stat)?;
+        write_varint(data, value)?;
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -241,104 +498,257 @@ pub fn create_action_bar_text(text: &str) -> eyre::Result<Vec<u8>> {
 
 pub mod packet_ids {
     use mc_data::play::clientbound::{
-        ChunkBatchFinished, ChunkBatchStart, GameEvent, KeepAlive as ClientboundKeepAlive,
-        LevelChunkWithLight, Login as PlayLogin, PlayerPosition, SetActionBarText,
-        SetChunkCacheCenter, SetTime,
+        AwardStats, BlockChangedAck, BlockUpdate, ChunkBatchFinished, ChunkBatchStart,
+        CustomPayload, ForgetLevelChunk, GameEvent, KeepAlive as ClientboundKeepAlive,
+        LevelChunkWithLight, Login as PlayLogin, PlayerInfoRemove, PlayerInfoUpdate,
+        PlayerPosition, SetActionBarText, SetChunkCacheCenter, SetChunkCacheRadius,
+        SetEntityMotion, SetTime,
     };
     use mc_protocol::Packet;
 
     pub const PLAY_LOGIN: i32 = PlayLogin::ID;
+    pub const CUSTOM_PAYLOAD: i32 = CustomPayload::ID;
     pub const GAME_EVENT: i32 = GameEvent::ID;
     pub const SET_CHUNK_CENTER: i32 = SetChunkCacheCenter::ID;
+    pub const SET_CHUNK_RADIUS: i32 = SetChunkCacheRadius::ID;
     pub const SET_TIME: i32 = SetTime::ID;
     pub const PLAYER_POSITION: i32 = PlayerPosition::ID;
+    pub const SET_ENTITY_MOTION: i32 = SetEntityMotion::ID;
     pub const KEEPALIVE: i32 = ClientboundKeepAlive::ID;
     pub const CHUNK_BATCH_START: i32 = ChunkBatchStart::ID;
     pub const CHUNK_BATCH_FINISHED: i32 = ChunkBatchFinished::ID;
     pub const LEVEL_CHUNK: i32 = LevelChunkWithLight::ID;
+    pub const FORGET_CHUNK: i32 = ForgetLevelChunk::ID;
+    pub const BLOCK_UPDATE: i32 = BlockUpdate::ID;
+    pub const BLOCK_CHANGED_ACK: i32 = BlockChangedAck::ID;
     pub const ACTION_BAR: i32 = SetActionBarText::ID;
+    pub const PLAYER_INFO_UPDATE: i32 = PlayerInfoUpdate::ID;
+    pub const PLAYER_INFO_REMOVE: i32 = PlayerInfoRemove::ID;
+    pub const AWARD_STATS: i32 = AwardStats::ID;
 }
 
 // ============================================================================
 // Buffer helpers
 // ============================================================================
 
-pub fn send_status_response(buffer: &mut PacketBuffer, max_players: i32, motd: &str) {
-    if let Ok(response_data) = create_status_response(max_players, motd) {
-        let packet = encode_packet(0, &response_data);
+/// The disconnect packet for a connection's state, if it has one
+pub fn disconnect_packet(state: ConnectionState, reason: &Text) -> Option<Bytes> {
+    match state {
+        ConnectionState::Handshaking | ConnectionState::Status => None,
+        ConnectionState::Login => {
+            let mut data = Vec::new();
+            reason.to_json().encode(&mut data).ok()?;
+            Some(encode_packet(
+                login::clientbound::LoginDisconnect::ID,
+                &data,
+            ))
+        }
+        ConnectionState::Configuration => Some(encode_packet(
+            configuration::clientbound::Disconnect::ID,
+            &reason.to_network_bytes(),
+        )),
+        ConnectionState::Play => Some(encode_packet(
+            play::clientbound::Disconnect::ID,
+            &reason.to_network_bytes(),
+        )),
+    }
+}
+
+/// Disconnect a client with `reason`, closing the connection once it's sent
+pub fn send_disconnect(buffer: &mut PacketBuffer, state: ConnectionState, reason: &Text) {
+    if let Some(packet) = disconnect_packet(state, reason) {
+        buffer.push_outgoing(packet);
+    }
+    buffer.close();
+}
+
+pub fn send_status_response(
+    buffer: &mut PacketBuffer,
+    max_players: i32,
+    motd: &str,
+    players: &OnlinePlayers,
+) {
+    if let Ok(packet) = buffer_pool::frame(0, |data| {
+        write_status_response(data, max_players, motd, players)
+    }) {
         buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_login_success(buffer: &mut PacketBuffer, uuid: u128, name: &str) {
-    if let Ok(response_data) = create_login_success(uuid, name) {
-        let packet = encode_packet(2, &response_data);
+    if let Ok(packet) = buffer_pool::frame(2, |data| write_login_success(data, uuid, name)) {
         buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_known_packs(buffer: &mut PacketBuffer) {
-    if let Ok(data) = create_known_packs() {
-        let packet = encode_packet(14, &data);
+    if let Ok(packet) = buffer_pool::frame(14, write_known_packs) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_play_login(buffer: &mut PacketBuffer, entity_id: i32, max_players: i32, game_mode: u8) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::PLAY_LOGIN, |data| {
+        write_play_login(data, entity_id, max_players, game_mode)
+    }) {
         buffer.push_outgoing(packet);
     }
 }
 
-pub fn send_play_login(buffer: &mut PacketBuffer, entity_id: i32, max_players: i32) {
-    if let Ok(data) = create_play_login(entity_id, max_players) {
-        buffer.push_outgoing(encode_packet(packet_ids::PLAY_LOGIN, &data));
+pub fn send_brand(buffer: &mut PacketBuffer, brand: &str) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::CUSTOM_PAYLOAD, |data| {
+        write_brand_payload(data, brand)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_player_position(buffer: &mut PacketBuffer, x: f64, y: f64, z: f64, teleport_id: i32) {
-    if let Ok(data) = create_player_position(x, y, z, teleport_id) {
-        buffer.push_outgoing(encode_packet(packet_ids::PLAYER_POSITION, &data));
+    if let Ok(packet) = buffer_pool::frame(packet_ids::PLAYER_POSITION, |data| {
+        write_player_position(data, x, y, z, teleport_id)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_player_push(buffer: &mut PacketBuffer, dx: f64, dz: f64) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::PLAYER_POSITION, |data| {
+        write_player_push(data, dx, dz)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_set_entity_motion(buffer: &mut PacketBuffer, entity_id: i32, x: f64, y: f64, z: f64) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::SET_ENTITY_MOTION, |data| {
+        write_set_entity_motion(data, entity_id, x, y, z)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_game_event_start_waiting(buffer: &mut PacketBuffer) {
-    if let Ok(data) = create_game_event_start_waiting() {
-        buffer.push_outgoing(encode_packet(packet_ids::GAME_EVENT, &data));
+    if let Ok(packet) = buffer_pool::frame(packet_ids::GAME_EVENT, write_game_event_start_waiting) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_set_center_chunk(buffer: &mut PacketBuffer, x: i32, z: i32) {
-    if let Ok(data) = create_set_center_chunk(x, z) {
-        buffer.push_outgoing(encode_packet(packet_ids::SET_CHUNK_CENTER, &data));
+    if let Ok(packet) = buffer_pool::frame(packet_ids::SET_CHUNK_CENTER, |data| {
+        write_set_center_chunk(data, x, z)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_set_chunk_cache_radius(buffer: &mut PacketBuffer, radius: i32) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::SET_CHUNK_RADIUS, |data| {
+        write_set_chunk_cache_radius(data, radius)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
-pub fn send_set_time(buffer: &mut PacketBuffer, world_age: i64, time_of_day: i64) {
-    if let Ok(data) = create_set_time(world_age, time_of_day) {
-        buffer.push_outgoing(encode_packet(packet_ids::SET_TIME, &data));
+pub fn send_forget_chunk(buffer: &mut PacketBuffer, x: i32, z: i32) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::FORGET_CHUNK, |data| {
+        write_forget_chunk(data, x, z)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_block_update(buffer: &mut PacketBuffer, x: i32, y: i32, z: i32, state: i32) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::BLOCK_UPDATE, |data| {
+        write_block_update(data, x, y, z, state)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_award_stats(buffer: &mut PacketBuffer, entries: &[(i32, i32, i32)]) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::AWARD_STATS, |data| {
+        write_award_stats(data, entries)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_block_changed_ack(buffer: &mut PacketBuffer, sequence: i32) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::BLOCK_CHANGED_ACK, |data| {
+        write_block_changed_ack(data, sequence)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_set_time(
+    buffer: &mut PacketBuffer,
+    world_age: i64,
+    time_of_day: i64,
+    increasing: bool,
+) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::SET_TIME, |data| {
+        write_set_time(data, world_age, time_of_day, increasing)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_keepalive(buffer: &mut PacketBuffer) {
-    if let Ok(data) = create_keepalive() {
-        buffer.push_outgoing(encode_packet(packet_ids::KEEPALIVE, &data));
+    if let Ok(packet) = buffer_pool::frame(packet_ids::KEEPALIVE, write_keepalive) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_player_info_update(buffer: &mut PacketBuffer, entries: &[PlayerInfoEntry]) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::PLAYER_INFO_UPDATE, |data| {
+        write_player_info_update(data, entries)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_player_latency_update(buffer: &mut PacketBuffer, entries: &[PlayerInfoEntry]) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::PLAYER_INFO_UPDATE, |data| {
+        write_player_latency_update(data, entries)
+    }) {
+        buffer.push_outgoing(packet);
+    }
+}
+
+pub fn send_player_info_remove(buffer: &mut PacketBuffer, uuids: &[u128]) {
+    if let Ok(packet) = buffer_pool::frame(packet_ids::PLAYER_INFO_REMOVE, |data| {
+        write_player_info_remove(data, uuids)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_chunk_batch_finished(buffer: &mut PacketBuffer, count: i32) {
-    if let Ok(data) = create_chunk_batch_finished(count) {
-        buffer.push_outgoing(encode_packet(packet_ids::CHUNK_BATCH_FINISHED, &data));
+    if let Ok(packet) = buffer_pool::frame(packet_ids::CHUNK_BATCH_FINISHED, |data| {
+        write_chunk_batch_finished(data, count)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_action_bar(buffer: &mut PacketBuffer, text: &str) {
-    if let Ok(data) = create_action_bar_text(text) {
-        buffer.push_outgoing(encode_packet(packet_ids::ACTION_BAR, &data));
+    if let Ok(packet) = buffer_pool::frame(packet_ids::ACTION_BAR, |data| {
+        write_action_bar_text(data, text)
+    }) {
+        buffer.push_outgoing(packet);
     }
 }
 
 pub fn send_chunks_to_buffer(buffer: &mut PacketBuffer, chunks: &[Bytes]) {
-    buffer.push_outgoing(encode_packet(packet_ids::CHUNK_BATCH_START, &[]));
-
-    for chunk_data in chunks {
-        let packet = encode_packet(packet_ids::LEVEL_CHUNK, chunk_data);
+    if let Ok(packet) = buffer_pool::frame(packet_ids::CHUNK_BATCH_START, |_| Ok(())) {
         buffer.push_outgoing(packet);
     }
 
+    // Chunk packets are cached already framed
+    for packet in chunks {
+        buffer.push_outgoing(Bytes::clone(packet));
+    }
+
     send_chunk_batch_finished(buffer, chunks.len() as i32);
 }
@@ -0,0 +1,184 @@
+//! Connection protocol state machine
+//!
+//! A connection goes from handshaking to status, or from handshaking to
+//! login, configuration and play, and never back. Handlers change a
+//! connection's [`ProtocolState`] only through [`transition`], which refuses
+//! any other move as [`ProtocolError::InvalidTransition`] and queues a
+//! [`StateTransition`] on [`StateTransitions`] for systems later in the tick;
+//! [`log_transitions`] logs and clears it at the end of the tick.
+//!
+//! Packets are checked against the state they arrive in. A packet ID the
+//! client can't send in that state, like a play packet before configuration
+//! finished, is dropped as [`ProtocolError::UnexpectedPacket`] instead of
+//! being read as whatever has that ID in the current state. For the same
+//! reason a handler stops reading a connection's packets once it changed
+//! state, leaving the rest to the next state's handler.
+
+use core::fmt;
+
+use bytes::Bytes;
+use flecs_ecs::prelude::*;
+use tracing::{debug, warn};
+
+use crate::components::{ConnectionState, PacketBuffer, ProtocolState};
+
+impl ConnectionState {
+    /// Whether a connection may go from this state to `next`
+    #[must_use]
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Handshaking, Self::Status | Self::Login)
+                | (Self::Login, Self::Configuration)
+                | (Self::Configuration, Self::Play)
+        )
+    }
+
+    /// Name of the serverbound packet `packet_id` in this state, if there is one
+    #[must_use]
+    pub fn serverbound_packet(self, packet_id: i32) -> Option<&'static str> {
+        match self {
+            Self::Handshaking => mc_data::handshake::serverbound::packet_name(packet_id),
+            Self::Status => mc_data::status::serverbound::packet_name(packet_id),
+            Self::Login => mc_data::login::serverbound::packet_name(packet_id),
+            Self::Configuration => mc_data::configuration::serverbound::packet_name(packet_id),
+            Self::Play => mc_data::play::serverbound::packet_name(packet_id),
+        }
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Handshaking => "handshaking",
+            Self::Status => "status",
+            Self::Login => "login",
+            Self::Configuration => "configuration",
+            Self::Play => "play",
+        })
+    }
+}
+
+impl ProtocolState {
+    /// Whether the client may send `packet_id` in this state
+    pub fn check_packet(self, packet_id: i32) -> Result<(), ProtocolError> {
+        match self.0.serverbound_packet(packet_id) {
+            Some(_) => Ok(()),
+            None => Err(ProtocolError::UnexpectedPacket {
+                state: self.0,
+                packet_id,
+            }),
+        }
+    }
+}
+
+/// A protocol rule a connection or handler broke
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// A handler tried to move a connection between these states
+    InvalidTransition {
+        from: ConnectionState,
+        to: ConnectionState,
+    },
+    /// The client sent a packet ID that doesn't exist in its state
+    UnexpectedPacket {
+        state: ConnectionState,
+        packet_id: i32,
+    },
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidTransition { from, to } => {
+                write!(f, "invalid state transition from {from} to {to}")
+            }
+            Self::UnexpectedPacket { state, packet_id } => {
+                write!(f, "unexpected packet 0x{packet_id:02X} in {state} state")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+/// A connection's protocol state changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateTransition {
+    pub connection: Entity,
+    pub from: ConnectionState,
+    pub to: ConnectionState,
+}
+
+/// Global: Protocol state changes this tick
+#[derive(Component, Debug, Default)]
+pub struct StateTransitions {
+    pub transitions: Vec<StateTransition>,
+}
+
+/// Move `connection` to the `to` state, if that's the next state from its
+/// current one
+pub fn transition(
+    connection: EntityView<'_>,
+    state: &mut ProtocolState,
+    to: ConnectionState,
+) -> Result<(), ProtocolError> {
+    let from = state.0;
+    if !from.can_transition_to(to) {
+        return Err(ProtocolError::InvalidTransition { from, to });
+    }
+    state.0 = to;
+    connection
+        .world()
+        .try_get::<&mut StateTransitions>(|queue| {
+            queue.transitions.push(StateTransition {
+                connection: connection.id(),
+                from,
+                to,
+            });
+        });
+    Ok(())
+}
+
+/// Next packet in `buffer` the client may send in its current state
+///
+/// Packets it may not send are dropped with a warning.
+pub fn next_packet(
+    connection: EntityView<'_>,
+    buffer: &mut PacketBuffer,
+    state: ProtocolState,
+) -> Option<(i32, Bytes)> {
+    while let Some((packet_id, data)) = buffer.pop_incoming() {
+        match state.check_packet(packet_id) {
+            Ok(()) => return Some((packet_id, data)),
+            Err(err) => warn!("{}: dropping packet: {err}", connection.name()),
+        }
+    }
+    None
+}
+
+/// Drop the packets in `buffer` the client may not send in its current state
+pub fn drop_unexpected(
+    connection: EntityView<'_>,
+    buffer: &mut PacketBuffer,
+    state: ProtocolState,
+) {
+    buffer.incoming.retain(|&(packet_id, _)| {
+        state
+            .check_packet(packet_id)
+            .inspect_err(|err| warn!("{}: dropping packet: {err}", connection.name()))
+            .is_ok()
+    });
+}
+
+/// Log this tick's state changes and clear them
+pub fn log_transitions(world: &WorldRef<'_>, queue: &mut StateTransitions) {
+    for transition in queue.transitions.drain(..) {
+        debug!(
+            "{}: {} -> {}",
+            world.entity_from_id(transition.connection).name(),
+            transition.from,
+            transition.to
+        );
+    }
+}
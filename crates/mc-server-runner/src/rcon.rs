@@ -0,0 +1,321 @@
+//! RCON server
+//!
+//! The vanilla remote console protocol, so existing admin tools can run
+//! commands. It listens on its own port and is off unless a password is set
+//! (see [`ServerConfig::rcon_password`]).
+//!
+//! Every packet is its length, a request ID, a type and a NUL-terminated
+//! body, little-endian. A client logs in with the password, then sends
+//! commands; a wrong password closes the connection, so each guess costs a
+//! reconnect. The game loop runs commands between ticks like
+//! [console](crate::console) input, and the output comes back split over
+//! packets of at most [`MAX_RESPONSE_BODY`] bytes. A client can send an
+//! empty response packet after a command and wait for its echo to know the
+//! output is complete.
+//!
+//! [`ServerConfig::rcon_password`]: crate::components::ServerConfig::rcon_password
+
+use std::io;
+use std::sync::Arc;
+use std::thread;
+
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use flecs_ecs::prelude::*;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tracing::{debug, error, info};
+
+use crate::console::{Console, ConsoleAction};
+
+/// Packet types
+const LOGIN: i32 = 3;
+const EXEC_COMMAND: i32 = 2;
+const AUTH_RESPONSE: i32 = 2;
+const RESPONSE_VALUE: i32 = 0;
+
+/// Request ID of a failed login
+const AUTH_FAILED: i32 = -1;
+/// Longest request body the game accepts
+const MAX_REQUEST_BODY: usize = 1446;
+/// Longest body of one response packet
+pub const MAX_RESPONSE_BODY: usize = 4096;
+/// Request ID, type and the two NUL bytes
+const HEADER_SIZE: usize = 10;
+
+/// A command from an RCON client, answered with its output
+pub struct RconRequest {
+    pub command: String,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// Commands from RCON clients, waiting for the game loop
+pub struct RconServer {
+    requests: Receiver<RconRequest>,
+}
+
+impl RconServer {
+    /// Listen for RCON clients on a thread
+    pub fn start(port: u16, password: String) -> Self {
+        let (tx, requests) = unbounded();
+        thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create Tokio runtime");
+
+            rt.block_on(async move {
+                if let Err(e) = listen(port, password.into(), tx).await {
+                    error!("RCON error: {}", e);
+                }
+            });
+        });
+        Self { requests }
+    }
+
+    /// Run every command received since the last call as the console
+    pub fn process(&self, world: &World, console: &mut Console) -> ConsoleAction {
+        while let Ok(request) = self.requests.try_recv() {
+            info!("RCON executed command: {}", request.command);
+            let (output, action) = console.execute(world, &request.command);
+            let _ = request.reply.send(output.join("\n"));
+            if action == ConsoleAction::Stop {
+                return action;
+            }
+        }
+        ConsoleAction::Continue
+    }
+}
+
+async fn listen(port: u16, password: Arc<str>, requests: Sender<RconRequest>) -> io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    info!(
+        "RCON listening on 0.0.0.0:{}",
+        listener.local_addr()?.port()
+    );
+
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        info!("RCON connection from {}", addr);
+        let password = password.clone();
+        let requests = requests.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, &password, &requests).await {
+                debug!("RCON connection {} closed: {}", addr, e);
+            }
+        });
+    }
+}
+
+async fn handle_client(
+    mut stream: TcpStream,
+    password: &str,
+    requests: &Sender<RconRequest>,
+) -> io::Result<()> {
+    let mut authenticated = false;
+    while let Some((id, kind, body)) = read_packet(&mut stream).await? {
+        match kind {
+            LOGIN => {
+                authenticated = password_matches(&body, password);
+                if !authenticated {
+                    write_packet(&mut stream, AUTH_FAILED, AUTH_RESPONSE, "").await?;
+                    debug!("RCON login failed, closing the connection");
+                    break;
+                }
+                write_packet(&mut stream, id, AUTH_RESPONSE, "").await?;
+            }
+            EXEC_COMMAND if authenticated => {
+                let (reply, output) = oneshot::channel();
+                let request = RconRequest {
+                    command: body,
+                    reply,
+                };
+                if requests.send(request).is_err() {
+                    break;
+                }
+                // Dropped if the server stops first
+                let Ok(output) = output.await else {
+                    break;
+                };
+                for part in split_response(&output) {
+                    write_packet(&mut stream, id, RESPONSE_VALUE, part).await?;
+                }
+            }
+            EXEC_COMMAND => write_packet(&mut stream, AUTH_FAILED, AUTH_RESPONSE, "").await?,
+            RESPONSE_VALUE => write_packet(&mut stream, id, RESPONSE_VALUE, "").await?,
+            _ => {
+                let message = format!("Unknown request {kind:x}");
+                write_packet(&mut stream, id, RESPONSE_VALUE, &message).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compare a login attempt with the password in time that doesn't depend on
+/// how much of it was right
+fn password_matches(attempt: &str, password: &str) -> bool {
+    let attempt = attempt.as_bytes();
+    let mut diff = u8::from(attempt.len() != password.len());
+    for (i, &byte) in password.as_bytes().iter().enumerate() {
+        diff |= byte ^ attempt.get(i).copied().unwrap_or(0);
+    }
+    core::hint::black_box(diff) == 0
+}
+
+/// Next packet's request ID, type and body, or `None` once the client has
+/// disconnected
+async fn read_packet(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> io::Result<Option<(i32, i32, String)>> {
+    let length = match stream.read_i32_le().await {
+        Ok(length) => length as usize,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if !(HEADER_SIZE..=HEADER_SIZE + MAX_REQUEST_BODY).contains(&length) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid packet length {length}"),
+        ));
+    }
+
+    let mut data = vec![0; length];
+    stream.read_exact(&mut data).await?;
+    let id = i32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let kind = i32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    let body = data[8..].split(|&b| b == 0).next().unwrap_or_default();
+    Ok(Some((id, kind, String::from_utf8_lossy(body).into_owned())))
+}
+
+async fn write_packet(
+    stream: &mut (impl AsyncWrite + Unpin),
+    id: i32,
+    kind: i32,
+    body: &str,
+) -> io::Result<()> {
+    let mut packet = Vec::with_capacity(4 + HEADER_SIZE + body.len());
+    packet.extend_from_slice(&((HEADER_SIZE + body.len()) as i32).to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&kind.to_le_bytes());
+    packet.extend_from_slice(body.as_bytes());
+    packet.extend_from_slice(&[0, 0]);
+    stream.write_all(&packet).await
+}
+
+/// Split output into response bodies of at most [`MAX_RESPONSE_BODY`] bytes,
+/// on character boundaries. Empty output is one empty body.
+fn split_response(output: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = output;
+    while rest.len() > MAX_RESPONSE_BODY {
+        let mut end = MAX_RESPONSE_BODY;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (part, tail) = rest.split_at(end);
+        parts.push(part);
+        rest = tail;
+    }
+    parts.push(rest);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(id: i32, kind: i32, body: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&((HEADER_SIZE + body.len()) as i32).to_le_bytes());
+        data.extend_from_slice(&id.to_le_bytes());
+        data.extend_from_slice(&kind.to_le_bytes());
+        data.extend_from_slice(body);
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    #[tokio::test]
+    async fn test_read_packet() {
+        let data = packet(7, EXEC_COMMAND, b"list");
+        let packet = read_packet(&mut data.as_slice()).await.unwrap();
+        assert_eq!(packet, Some((7, EXEC_COMMAND, "list".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_read_packet_eof() {
+        let packet = read_packet(&mut [].as_slice()).await.unwrap();
+        assert_eq!(packet, None);
+    }
+
+    #[tokio::test]
+    async fn test_read_packet_length_limits() {
+        let longest = packet(1, EXEC_COMMAND, &[b'a'; MAX_REQUEST_BODY]);
+        let (_, _, body) = read_packet(&mut longest.as_slice()).await.unwrap().unwrap();
+        assert_eq!(body.len(), MAX_REQUEST_BODY);
+
+        let too_long = packet(1, EXEC_COMMAND, &[b'a'; MAX_REQUEST_BODY + 1]);
+        let err = read_packet(&mut too_long.as_slice()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let too_short = ((HEADER_SIZE - 1) as i32).to_le_bytes();
+        let err = read_packet(&mut too_short.as_slice()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let negative = (-1i32).to_le_bytes();
+        let err = read_packet(&mut negative.as_slice()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn test_read_packet_truncated() {
+        let mut data = packet(1, EXEC_COMMAND, b"list");
+        data.truncate(data.len() - 3);
+        let err = read_packet(&mut data.as_slice()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_write_packet_framing() {
+        let mut out = Vec::new();
+        write_packet(&mut out, 42, RESPONSE_VALUE, "hi")
+            .await
+            .unwrap();
+        assert_eq!(out, packet(42, RESPONSE_VALUE, b"hi"));
+
+        let read = read_packet(&mut out.as_slice()).await.unwrap();
+        assert_eq!(read, Some((42, RESPONSE_VALUE, "hi".to_string())));
+    }
+
+    #[test]
+    fn test_split_response() {
+        assert_eq!(split_response(""), vec![""]);
+
+        let exact = "a".repeat(MAX_RESPONSE_BODY);
+        assert_eq!(split_response(&exact), vec![exact.as_str()]);
+
+        let long = "a".repeat(MAX_RESPONSE_BODY + 1);
+        let lengths: Vec<_> = split_response(&long).iter().map(|p| p.len()).collect();
+        assert_eq!(lengths, [MAX_RESPONSE_BODY, 1]);
+    }
+
+    #[test]
+    fn test_split_response_char_boundary() {
+        // The 3-byte character straddles the limit and moves to the next part
+        let output = format!("{}\u{20ac}tail", "a".repeat(MAX_RESPONSE_BODY - 1));
+        let parts = split_response(&output);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].len(), MAX_RESPONSE_BODY - 1);
+        assert_eq!(parts[1], "\u{20ac}tail");
+        assert_eq!(parts.concat(), output);
+    }
+
+    #[test]
+    fn test_password_matches() {
+        assert!(password_matches("hunter2", "hunter2"));
+        assert!(!password_matches("hunter3", "hunter2"));
+        assert!(!password_matches("hunter", "hunter2"));
+        assert!(!password_matches("hunter22", "hunter2"));
+        assert!(!password_matches("", "hunter2"));
+    }
+}
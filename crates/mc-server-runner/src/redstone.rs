@@ -0,0 +1,108 @@
+//! Redstone blocks in the world
+//!
+//! Levels are decided by [`module_redstone`]; this keeps its entities in
+//! sync with the blocks and schedules it. Every redstone block has an entity
+//! named `redstone:{x}:{y}:{z}` holding its [`BlockPos`], [`Redstone`] kind
+//! and current [`SignalLevel`]. [`chunk::set_block`] keeps these entities in
+//! sync through [`track_block`].
+//!
+//! Signals advance one block per tick. [`tick_redstone`] steps the chunks in
+//! nine phases by [`block_tick::phase`], like scheduled block ticks: a phase
+//! computes all of its changes before applying any, and its chunks are three
+//! chunks apart, well beyond the two-block reach of a step.
+
+use std::collections::HashMap;
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use module_redstone::{BlockOffset, Redstone, RedstoneBlock, RedstoneKind, SignalLevel};
+
+use crate::components::{BlockPos, ChunkPos};
+use crate::{block_tick, chunk};
+
+impl BlockOffset for BlockPos {
+    fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+}
+
+pub fn redstone_name(pos: BlockPos) -> String {
+    format!("redstone:{}:{}:{}", pos.x, pos.y, pos.z)
+}
+
+/// Create, update or remove the redstone entity of a changed block
+pub fn track_block(world: &WorldRef<'_>, pos: BlockPos, state: BlockState) {
+    let name = redstone_name(pos);
+    match RedstoneKind::of(state) {
+        Some(kind) => {
+            world
+                .entity_named(&name)
+                .set(pos)
+                .set(Redstone { kind })
+                .set(SignalLevel(kind.level(state)));
+        }
+        None => {
+            if let Some(entity) = world.try_lookup_recursive(&name) {
+                entity.destruct();
+            }
+        }
+    }
+}
+
+/// Whether there's a lever at `pos`
+pub fn is_lever(world: &WorldRef<'_>, pos: BlockPos) -> bool {
+    chunk::block_at(world, pos).and_then(RedstoneKind::of) == Some(RedstoneKind::Lever)
+}
+
+/// Queue flipping a lever. Returns `false` if there's no lever at `pos`.
+pub fn toggle_lever(world: &WorldRef<'_>, pos: BlockPos) -> bool {
+    let Some(state) = chunk::block_at(world, pos) else {
+        return false;
+    };
+    if RedstoneKind::of(state) != Some(RedstoneKind::Lever) {
+        return false;
+    }
+    let powered = if state.property("powered") == Some("true") {
+        "false"
+    } else {
+        "true"
+    };
+    state
+        .with_property("powered", powered)
+        .is_some_and(|toggled| chunk::queue_block_change(world, pos, toggled))
+}
+
+/// Advance every redstone signal by one block, phase by phase
+pub fn tick_redstone(world: &WorldRef<'_>) {
+    let mut blocks = HashMap::new();
+    let mut phases: [Vec<BlockPos>; 9] = Default::default();
+    world
+        .query::<(&BlockPos, &Redstone, &SignalLevel)>()
+        .build()
+        .each(|(pos, redstone, level)| {
+            if let Some(state) = chunk::block_at(world, *pos) {
+                let block = RedstoneBlock {
+                    kind: redstone.kind,
+                    state,
+                    level: level.0,
+                };
+                blocks.insert(*pos, block);
+                phases[block_tick::phase(ChunkPos::new(pos.x >> 4, pos.z >> 4))].push(*pos);
+            }
+        });
+
+    for positions in phases {
+        // Compute the whole phase before changing anything
+        for change in module_redstone::step(&blocks, positions) {
+            if let Some(block) = blocks.get_mut(&change.pos) {
+                block.state = change.state;
+                block.level = change.level;
+            }
+            if let Some(entity) = world.try_lookup_recursive(&redstone_name(change.pos)) {
+                entity.set(SignalLevel(change.level));
+            }
+            let pos = change.pos;
+            chunk::set_block(world, pos.x, pos.y, pos.z, change.state);
+        }
+    }
+}
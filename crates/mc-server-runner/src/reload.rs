@@ -0,0 +1,163 @@
+//! `/reload`: re-read skripts, modules and config without a restart
+//!
+//! - `reload skripts`: the `.sk` files in [`ServerConfig::skript_dir`],
+//!   through module-skript's [`ScriptRegistry`]
+//! - `reload modules`: every dynamic module loaded from
+//!   [`ServerConfig::modules_dir`], through module-loader; a build that
+//!   fails its pre-flight check leaves the old one running
+//! - `reload config`: the game rules saved in the world directory
+//!
+//! Each replies with a summary of what was reloaded. Reloading swaps out
+//! code and state systems run on, so it only runs between ticks. The
+//! console and RCON run it right away; operators (see
+//! [`ServerConfig::is_operator`]) queue it in [`ReloadQueue`] and get the
+//! summary in chat once it has run. Other players are refused.
+
+use flecs_ecs::prelude::*;
+use mc_text::Text;
+use module_loader::ModuleLoader;
+use module_skript::{LoadSummary, ScriptRegistry};
+use tracing::{info, warn};
+
+use crate::components::{PacketBuffer, ServerConfig};
+use crate::game_rules::GameRules;
+use crate::systems::send_chat_message;
+
+pub const USAGE: &str = "Usage: reload skripts|modules|config";
+
+/// Global: `/reload`s run by operators, waiting for the end of the tick
+#[derive(Component, Default)]
+pub struct ReloadQueue {
+    /// Invoker and target of each request
+    requests: Vec<(Entity, String)>,
+}
+
+impl ReloadQueue {
+    /// Queue `reload <target>` for `invoker`
+    pub fn push(&mut self, invoker: Entity, target: &str) {
+        self.requests.push((invoker, target.trim().to_string()));
+    }
+}
+
+/// Skripts and modules loaded at startup, reloaded by `reload`
+pub struct Reloadables {
+    skripts: Option<ScriptRegistry>,
+    modules: Option<ModuleLoader>,
+}
+
+impl Reloadables {
+    /// Load the skripts and modules from the directories `config` names,
+    /// and set up the [`ReloadQueue`]
+    pub fn load(world: &World, config: &ServerConfig) -> Self {
+        let skripts = config.skript_dir().map(|dir| {
+            let mut registry = ScriptRegistry::new(dir);
+            match registry.reload() {
+                Ok(summary) => info!("{}", skripts_summary(&summary).join("; ")),
+                Err(e) => warn!(
+                    "Failed to load skripts from {}: {e}",
+                    registry.dir().display()
+                ),
+            }
+            registry
+        });
+        let modules = config.modules_dir().map(|dir| {
+            let mut loader = ModuleLoader::new(dir);
+            loader.set_preflight(true);
+            if let Err(e) = loader.load_all(world) {
+                warn!("Failed to load modules: {e}");
+            }
+            loader
+        });
+        world.set(ReloadQueue::default());
+        Self { skripts, modules }
+    }
+
+    /// Run `reload <target>`, returning the summary sent to the invoker
+    pub fn run(&mut self, world: &World, target: &str) -> Result<Vec<String>, String> {
+        match target.trim() {
+            "skripts" => self.reload_skripts(),
+            "modules" => self.reload_modules(world),
+            "config" => reload_config(world),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+
+    /// Run the operators' queued `reload`s, sending each summary to its
+    /// invoker if they're still online
+    pub fn run_queued(&mut self, world: &World) {
+        let Some(requests) =
+            world.try_get::<&mut ReloadQueue>(|queue| core::mem::take(&mut queue.requests))
+        else {
+            return;
+        };
+        for (invoker, target) in requests {
+            info!("Running queued reload {target}");
+            let output = self.run(world, &target).unwrap_or_else(|e| vec![e]);
+            let invoker = world.entity_from_id(invoker);
+            if !invoker.is_alive() {
+                continue;
+            }
+            let message = Text::literal(output.join("\n"));
+            invoker.try_get::<&mut PacketBuffer>(|buffer| send_chat_message(buffer, &message));
+        }
+    }
+
+    fn reload_skripts(&mut self) -> Result<Vec<String>, String> {
+        let registry = self
+            .skripts
+            .as_mut()
+            .ok_or("No skript directory is set (RGB_SKRIPT_DIR)")?;
+        let summary = registry.reload().map_err(|e| {
+            format!(
+                "Failed to read skripts from {}: {e}",
+                registry.dir().display()
+            )
+        })?;
+        Ok(skripts_summary(&summary))
+    }
+
+    fn reload_modules(&mut self, world: &World) -> Result<Vec<String>, String> {
+        let loader = self
+            .modules
+            .as_mut()
+            .ok_or("No modules directory is set (RGB_MODULES_DIR)")?;
+        let total = loader.loaded_modules().len();
+        let reloaded = loader.reload_all(world);
+        let mut output = vec![format!("Reloaded {reloaded} of {total} modules")];
+        if reloaded < total {
+            output.push(format!("{} failed, see the server log", total - reloaded));
+        }
+        output.extend(
+            loader
+                .loaded_modules()
+                .into_iter()
+                .map(|module| format!("  {module}")),
+        );
+        Ok(output)
+    }
+}
+
+/// Read the saved game rules again; changed rules take effect on the next
+/// tick
+fn reload_config(world: &World) -> Result<Vec<String>, String> {
+    let changed = world
+        .get::<&mut GameRules>(GameRules::reload)
+        .map_err(|e| format!("Failed to reload the game rules: {e:#}"))?;
+    Ok(vec![format!("Reloaded the game rules, {changed} changed")])
+}
+
+fn skripts_summary(summary: &LoadSummary) -> Vec<String> {
+    let mut output = vec![format!(
+        "Loaded {} skripts, {} failed, {} removed",
+        summary.loaded,
+        summary.failed.len(),
+        summary.removed
+    )];
+    output.extend(
+        summary
+            .failed
+            .iter()
+            .map(|(name, error)| format!("  {name}: {error}")),
+    );
+    output
+}
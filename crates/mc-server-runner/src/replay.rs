@@ -0,0 +1,265 @@
+//! Replay mode: a recorded journal played back to spectators
+//!
+//! `--replay` starts the server on the journal of its world directory (see
+//! [`crate::journal`]) instead of a new session. The world is loaded as
+//! usual, then the journaled ticks are applied in order, one per server
+//! tick: block changes are shown to players without being saved, journaled
+//! or simulated, the world time follows the recording and player records
+//! are kept for the dashboard. Everyone who joins is a spectator; while a
+//! recorded player is followed, spectators are teleported along with it.
+//!
+//! The dashboard controls playback through `/api/replay`: pause, resume,
+//! seek to any tick since the journal's oldest checkpoint and follow a
+//! recorded player. Nothing is
+//! written to the world directory while replaying.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use eyre::{WrapErr, bail};
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use rgb_storage::{Mutation, TickId, VersionedWorld};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::chunk;
+use crate::components::{
+    BlockPos, GameMode, InPlayState, PacketBuffer, Player, Position, Rotation,
+};
+use crate::journal::{self, PlayerRecord, Record, Recovered};
+use crate::protocol::send_player_position;
+
+/// Global: The journal being replayed
+#[derive(Component)]
+pub struct Replay {
+    storage: VersionedWorld,
+    /// Last applied journal tick (0 is the world as loaded)
+    tick: TickId,
+    paused: bool,
+    /// Loaded state of every block the replay has changed
+    originals: HashMap<BlockPos, BlockState>,
+    /// Latest record of each player, as of `tick`
+    players: HashMap<u128, PlayerRecord>,
+    following: Option<u128>,
+}
+
+/// Playback state, for the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStatus {
+    pub tick: TickId,
+    /// Last journaled tick
+    pub end: TickId,
+    pub paused: bool,
+    /// UUID of the followed player
+    pub following: Option<String>,
+    pub players: Vec<ReplayPlayer>,
+}
+
+/// A recorded player as of the current tick
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayPlayer {
+    pub uuid: String,
+    pub position: Position,
+    pub rotation: Rotation,
+    pub game_mode: GameMode,
+}
+
+/// A playback command from the dashboard
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ReplayControl {
+    Pause,
+    Resume,
+    Seek {
+        tick: TickId,
+    },
+    /// Follow a recorded player by UUID, or stop following
+    Follow {
+        uuid: Option<String>,
+    },
+}
+
+impl Replay {
+    /// Open the journal of a world directory for playback, paused at the
+    /// loaded world
+    pub fn open(world_dir: &Path) -> eyre::Result<Self> {
+        let journal_dir = world_dir.join("journal");
+        if !journal_dir.exists() {
+            bail!("{} has no journal to replay", world_dir.display());
+        }
+        let storage = VersionedWorld::open(&journal_dir).wrap_err("failed to open the journal")?;
+        info!("Replaying {} journaled ticks", storage.current_tick());
+        Ok(Self {
+            storage,
+            tick: 0,
+            paused: true,
+            originals: HashMap::new(),
+            players: HashMap::new(),
+            following: None,
+        })
+    }
+
+    /// Last journaled tick
+    pub fn end(&self) -> TickId {
+        self.storage.current_tick()
+    }
+
+    pub fn status(&self) -> ReplayStatus {
+        let mut players: Vec<_> = self
+            .players
+            .values()
+            .map(|record| ReplayPlayer {
+                uuid: format!("{:032x}", record.uuid),
+                position: record.position,
+                rotation: record.rotation,
+                game_mode: record.game_mode,
+            })
+            .collect();
+        players.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+        ReplayStatus {
+            tick: self.tick,
+            end: self.end(),
+            paused: self.paused,
+            following: self.following.map(|uuid| format!("{uuid:032x}")),
+            players,
+        }
+    }
+
+    /// Apply a playback command
+    pub fn control(&mut self, world: &WorldRef<'_>, control: ReplayControl) -> Result<(), String> {
+        match control {
+            ReplayControl::Pause => self.paused = true,
+            ReplayControl::Resume => self.paused = false,
+            ReplayControl::Seek { tick } => self.seek(world, tick).map_err(|e| e.to_string())?,
+            ReplayControl::Follow { uuid: None } => self.following = None,
+            ReplayControl::Follow { uuid: Some(uuid) } => {
+                let uuid = u128::from_str_radix(&uuid.replace('-', ""), 16)
+                    .map_err(|_| format!("Invalid UUID {uuid:?}"))?;
+                if !self.players.contains_key(&uuid) {
+                    return Err(format!(
+                        "No player {uuid:032x} is recorded at tick {}",
+                        self.tick
+                    ));
+                }
+                self.following = Some(uuid);
+                self.teleport_spectators(world);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the journal tick after the current one
+    fn advance(&mut self, world: &WorldRef<'_>) -> eyre::Result<()> {
+        // Ticks before the oldest checkpoint are only kept as its state
+        let first = self.storage.first_tick()?;
+        if self.tick < first {
+            return self.seek(world, first);
+        }
+        let tick = self.tick + 1;
+        let mut followed_moved = false;
+        for mutation in self.storage.log(tick)? {
+            match mutation {
+                Mutation::Set { key, data } => match Record::decode(&key, &data) {
+                    Some(Record::WorldTime(time)) => {
+                        world.set(time);
+                    }
+                    Some(Record::Block(pos, state)) => self.show_block(world, pos, state),
+                    Some(Record::Player(record)) => {
+                        followed_moved |= self.following == Some(record.uuid);
+                        self.players.insert(record.uuid, record);
+                    }
+                    None => {}
+                },
+                // Removed blocks are back to their loaded state
+                Mutation::Remove { key } => {
+                    let original = journal::block_pos(&key)
+                        .and_then(|pos| Some((pos, *self.originals.get(&pos)?)));
+                    if let Some((pos, state)) = original {
+                        self.show_block(world, pos, state);
+                    }
+                }
+            }
+        }
+        self.tick = tick;
+        if followed_moved {
+            self.teleport_spectators(world);
+        }
+        Ok(())
+    }
+
+    /// Jump to the state as of `target`
+    fn seek(&mut self, world: &WorldRef<'_>, target: TickId) -> eyre::Result<()> {
+        if target > self.end() {
+            bail!("The journal ends at tick {}", self.end());
+        }
+        let recovered = Recovered::from_state(target, &self.storage.replay(target)?);
+
+        // Blocks changed since are back to their loaded state
+        let mut blocks = self.originals.clone();
+        blocks.extend(recovered.blocks);
+        for (pos, state) in blocks {
+            self.show_block(world, pos, state);
+        }
+        if let Some(time) = recovered.world_time {
+            world.set(time);
+        }
+        self.players = recovered.players;
+        self.tick = target;
+        self.teleport_spectators(world);
+        Ok(())
+    }
+
+    fn show_block(&mut self, world: &WorldRef<'_>, pos: BlockPos, state: BlockState) {
+        if let Some(old) = chunk::show_block(world, pos.x, pos.y, pos.z, state) {
+            self.originals.entry(pos).or_insert(old);
+        }
+    }
+
+    /// Move every spectator to the followed player
+    fn teleport_spectators(&self, world: &WorldRef<'_>) {
+        let Some(record) = self.following.and_then(|uuid| self.players.get(&uuid)) else {
+            return;
+        };
+        let target = record.position;
+        world
+            .query::<(&mut PacketBuffer, &mut Position)>()
+            .with(Player)
+            .with(InPlayState)
+            .build()
+            .each(|(buffer, position)| {
+                *position = target;
+                send_player_position(buffer, target.x, target.y, target.z, 0);
+            });
+    }
+}
+
+/// Whether `--replay` was passed
+pub fn replay_arg(mut args: impl Iterator<Item = String>) -> bool {
+    args.any(|arg| arg == "--replay")
+}
+
+/// Whether the server is replaying a journal
+pub fn is_replaying(world: &WorldRef<'_>) -> bool {
+    world.try_get::<&Replay>(|_| ()).is_some()
+}
+
+/// Apply the next journal tick unless playback is paused, pausing at the
+/// end of the journal
+pub fn step(world: &WorldRef<'_>, replay: &mut Replay) {
+    if replay.paused {
+        return;
+    }
+    if replay.tick >= replay.end() {
+        info!(
+            "The replay reached the end of the journal at tick {}",
+            replay.tick
+        );
+        replay.paused = true;
+        return;
+    }
+    if let Err(e) = replay.advance(world) {
+        warn!("Failed to replay journal tick {}: {e}", replay.tick + 1);
+        replay.paused = true;
+    }
+}
@@ -0,0 +1,113 @@
+//! Saved dashboard queries
+//!
+//! Operators name query DSL expressions to build a library of diagnostics
+//! the dashboard can rerun. The library is a single record in a
+//! [`PersistDb`] under the world directory, so it survives restarts.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use flecs_ecs::prelude::*;
+use persist::PersistDb;
+use query_dsl::{Operator, TermKind, parse_query};
+
+use crate::dashboard::{QuerySpec, SavedQuery};
+
+/// Record key of the library
+const LIBRARY_UUID: u128 = 0;
+const LIBRARY: &str = "saved_queries";
+
+/// Global: Named query DSL expressions
+#[derive(Component)]
+pub struct SavedQueries {
+    db: PersistDb,
+    queries: BTreeMap<String, String>,
+}
+
+impl SavedQueries {
+    /// Open the library stored at `path`
+    pub fn open(path: &Path) -> eyre::Result<Self> {
+        let db = PersistDb::open(path)?;
+        let queries = match db.load_bytes(LIBRARY_UUID, LIBRARY)? {
+            Some(bytes) => serde_json::from_slice(&bytes)?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self { db, queries })
+    }
+
+    /// Every saved query, by name
+    pub fn list(&self) -> Vec<SavedQuery> {
+        self.queries
+            .iter()
+            .map(|(name, query)| SavedQuery {
+                name: name.clone(),
+                query: query.clone(),
+            })
+            .collect()
+    }
+
+    /// Save a query, replacing any with the same name
+    pub fn save(&mut self, query: SavedQuery) -> Result<(), String> {
+        if query.name.trim().is_empty() {
+            return Err("A saved query needs a name".to_string());
+        }
+        to_spec(&query.query)?;
+        let previous = self.queries.insert(query.name.clone(), query.query);
+        self.write().inspect_err(|_| {
+            match previous {
+                Some(previous) => self.queries.insert(query.name, previous),
+                None => self.queries.remove(&query.name),
+            };
+        })
+    }
+
+    /// Delete a query. Returns whether it existed.
+    pub fn delete(&mut self, name: &str) -> Result<bool, String> {
+        let Some(query) = self.queries.remove(name) else {
+            return Ok(false);
+        };
+        self.write().inspect_err(|_| {
+            self.queries.insert(name.to_string(), query);
+        })?;
+        Ok(true)
+    }
+
+    /// The query spec of a saved query
+    pub fn spec(&self, name: &str) -> Result<QuerySpec, String> {
+        let query = self
+            .queries
+            .get(name)
+            .ok_or_else(|| format!("No saved query named {name}"))?;
+        to_spec(query)
+    }
+
+    fn write(&self) -> Result<(), String> {
+        let bytes = serde_json::to_vec(&self.queries).map_err(|e| e.to_string())?;
+        self.db
+            .save_bytes(LIBRARY_UUID, LIBRARY, &bytes)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// The dashboard query spec of a query DSL expression
+pub fn to_spec(input: &str) -> Result<QuerySpec, String> {
+    let query = parse_query(input).map_err(|e| e.to_string())?;
+    if query.terms.iter().any(|term| term.operator == Operator::Or) {
+        return Err("Saved queries don't support || terms".to_string());
+    }
+    if query
+        .terms
+        .iter()
+        .any(|term| matches!(term.kind, TermKind::Pair(_)))
+    {
+        return Err("Saved queries don't support pair terms".to_string());
+    }
+
+    Ok(QuerySpec {
+        with: query.required_components().map(str::to_string).collect(),
+        optional: query.optional_components().map(str::to_string).collect(),
+        without: query.excluded_components().map(str::to_string).collect(),
+        limit: None,
+        offset: None,
+    })
+}
@@ -0,0 +1,93 @@
+//! Graceful shutdown
+//!
+//! The game loop stops once [`Running`] is cleared, by SIGINT/SIGTERM,
+//! `/stop` or the console's `stop`. [`shutdown`] then, between ticks:
+//! 1. stops accepting connections
+//! 2. sends every client a disconnect packet and flushes the packet buffers
+//! 3. journals every player and saves every unsaved chunk, and every
+//!    player's stats and state
+//! 4. commits the last journal tick, checkpoints the journal if every chunk
+//!    was saved and releases the session lock
+//! 5. waits for the network thread to send what's queued
+//!
+//! The runner loads no modules, so there are none to unload.
+//!
+//! [`Running`]: crate::components::Running
+
+use flecs_ecs::prelude::*;
+use mc_text::Text;
+use tracing::info;
+
+use crate::anvil::RegionStore;
+use crate::autosave::{self, SaveStats, Unsaved};
+use crate::components::{Connection, ConnectionId, NetworkEgress, PacketBuffer, ProtocolState};
+use crate::journal::{self, Journal};
+use crate::network::{self, NetworkHandle};
+use crate::protocol::disconnect_packet;
+use crate::{player_data, stats};
+
+/// Disconnect reason, translated by the client
+const SERVER_CLOSED: &str = "multiplayer.disconnect.server_shutdown";
+
+/// Shut the server down after the game loop has stopped
+pub fn shutdown(world: &World, network: NetworkHandle) {
+    info!("Shutting down...");
+    network.stop_accepting();
+
+    let world = world.world();
+    disconnect_all(&world);
+    let saved = save_all(&world);
+    stats::save_all(&world);
+    player_data::save_all(&world);
+    world.try_get::<&mut Journal>(|journal| {
+        journal::record_players(&world, journal);
+        journal::commit_tick(&world, journal);
+        journal.close(saved);
+    });
+
+    network.join();
+    info!("Shutdown complete");
+}
+
+/// Send every connection a disconnect packet and flush its packet buffer
+fn disconnect_all(world: &WorldRef<'_>) {
+    let reason = Text::translate(SERVER_CLOSED);
+    let mut disconnected = 0;
+    world.get::<&NetworkEgress>(|egress| {
+        world
+            .query::<(&mut PacketBuffer, &ConnectionId, &ProtocolState)>()
+            .with(Connection)
+            .build()
+            .each(|(buffer, conn_id, state)| {
+                if let Some(packet) = disconnect_packet(state.0, &reason) {
+                    buffer.push_outgoing(packet);
+                    disconnected += 1;
+                }
+                network::record_outgoing(world, buffer, conn_id, state);
+                network::handle_egress(buffer, conn_id, egress);
+            });
+    });
+    info!("Disconnected {disconnected} clients");
+}
+
+/// Save every unsaved chunk. Returns whether they all were.
+fn save_all(world: &WorldRef<'_>) -> bool {
+    let mut unsaved = Vec::new();
+    world
+        .query::<&Unsaved>()
+        .build()
+        .each_entity(|chunk, _| unsaved.push(chunk.id()));
+
+    world
+        .try_get::<(&RegionStore, &mut SaveStats)>(|(store, stats)| {
+            let saved = unsaved
+                .iter()
+                .filter(|&&chunk| {
+                    autosave::save_chunk(world, store, stats, world.entity_from_id(chunk))
+                })
+                .count();
+            info!("Saved {saved} of {} unsaved chunks", unsaved.len());
+            saved == unsaved.len()
+        })
+        .unwrap_or(false)
+}
@@ -0,0 +1,226 @@
+//! Packet sniffer - records every packet for protocol debugging
+//!
+//! Enabled by setting `RGB_PACKET_LOG` (or `ServerConfig::packet_log`) to a file path.
+//! Every inbound and outbound packet is appended to that file as one JSON object
+//! per line (NDJSON):
+//!
+//! ```json
+//! {"tick":120,"connection":3,"direction":"outbound","state":"Play","packet_id":43,"len":5,"hex":"2b 00 00 00 01"}
+//! ```
+//!
+//! Inbound packets are recorded when the ECS receives them, with the connection's
+//! state at that moment. Outbound packets are recorded at egress, with the state at
+//! the end of the tick.
+//!
+//! The file is written from a background thread through a queue of
+//! [`WRITE_QUEUE_CAPACITY`] records. If the disk can't keep up, records that
+//! don't fit are left out of the file (and counted in the log) rather than
+//! piling up in memory or stalling the tick.
+//!
+//! The most recent records are also kept in memory and served by the dashboard at
+//! `/api/packets`. For the file, `jq` works well:
+//!
+//! ```text
+//! jq -c 'select(.connection == 3 and .direction == "inbound")' packets.ndjson
+//! ```
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crossbeam_channel::{Sender, TrySendError, bounded};
+use flecs_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::ConnectionState;
+
+/// Environment variable that enables the sniffer and names its output file
+pub const PACKET_LOG_ENV: &str = "RGB_PACKET_LOG";
+
+/// Number of records kept in memory for the dashboard
+const RECENT_CAPACITY: usize = 1024;
+
+/// Records waiting for the writer thread before new ones are dropped
+pub const WRITE_QUEUE_CAPACITY: usize = 16 * 1024;
+
+/// Which way a packet travelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PacketDirection {
+    Inbound,
+    Outbound,
+}
+
+/// A single recorded packet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PacketRecord {
+    /// World age when the packet was recorded
+    pub tick: i64,
+    /// Connection the packet belongs to
+    pub connection: u64,
+    pub direction: PacketDirection,
+    /// Protocol state used to interpret `packet_id`
+    pub state: ConnectionState,
+    pub packet_id: i32,
+    /// Payload length in bytes (excluding length prefix and packet ID)
+    pub len: usize,
+    /// Space-separated hex dump of the payload
+    pub hex: String,
+}
+
+/// Global: Packet sniffer, only present when enabled
+#[derive(Component)]
+pub struct PacketSniffer {
+    /// Records for the writer thread
+    tx: Sender<PacketRecord>,
+    /// Records left out of the file because the writer fell behind
+    dropped: u64,
+    /// Most recent records, oldest first
+    recent: VecDeque<PacketRecord>,
+}
+
+impl PacketSniffer {
+    /// Start a sniffer writing NDJSON to `path` from a background thread.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let (tx, rx) = bounded::<PacketRecord>(WRITE_QUEUE_CAPACITY);
+
+        std::thread::Builder::new()
+            .name("packet-sniffer".to_string())
+            .spawn(move || {
+                while let Ok(record) = rx.recv() {
+                    let written = serde_json::to_writer(&mut writer, &record)
+                        .map_err(std::io::Error::from)
+                        .and_then(|()| writer.write_all(b"\n"));
+                    if let Err(e) = written {
+                        tracing::error!("Packet sniffer write failed: {e}");
+                        return;
+                    }
+                    // Flush whenever we catch up so the file is readable live
+                    if rx.is_empty() {
+                        let _ = writer.flush();
+                    }
+                }
+                let _ = writer.flush();
+            })?;
+
+        Ok(Self {
+            tx,
+            dropped: 0,
+            recent: VecDeque::with_capacity(RECENT_CAPACITY),
+        })
+    }
+
+    /// Create a sniffer if `RGB_PACKET_LOG` or `configured` names a file.
+    ///
+    /// The environment variable takes precedence over the config.
+    pub fn from_env(configured: Option<&str>) -> Option<Self> {
+        let path = std::env::var(PACKET_LOG_ENV)
+            .ok()
+            .or_else(|| configured.map(str::to_string))?;
+
+        match Self::create(&path) {
+            Ok(sniffer) => {
+                tracing::info!("Recording packets to {path}");
+                Some(sniffer)
+            }
+            Err(e) => {
+                tracing::error!("Failed to open packet log {path}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Record a packet payload.
+    pub fn record(
+        &mut self,
+        tick: i64,
+        connection: u64,
+        direction: PacketDirection,
+        state: ConnectionState,
+        packet_id: i32,
+        payload: &[u8],
+    ) {
+        let record = PacketRecord {
+            tick,
+            connection,
+            direction,
+            state,
+            packet_id,
+            len: payload.len(),
+            hex: hexdump(payload),
+        };
+
+        if let Err(TrySendError::Full(_)) = self.tx.try_send(record.clone()) {
+            self.dropped += 1;
+            // Logged at 1, 2, 4, 8, ... drops so a slow disk doesn't flood the log
+            if self.dropped.is_power_of_two() {
+                tracing::warn!(
+                    "Packet sniffer can't keep up, {} records left out of the file",
+                    self.dropped
+                );
+            }
+        }
+
+        if self.recent.len() == RECENT_CAPACITY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(record);
+    }
+
+    /// Record an outgoing frame (`length ++ packet_id ++ payload`).
+    pub fn record_outgoing(
+        &mut self,
+        tick: i64,
+        connection: u64,
+        state: ConnectionState,
+        frame: &[u8],
+    ) {
+        if let Some((packet_id, payload)) = split_frame(frame) {
+            self.record(
+                tick,
+                connection,
+                PacketDirection::Outbound,
+                state,
+                packet_id,
+                payload,
+            );
+        }
+    }
+
+    /// Most recent records, newest last, optionally for one connection.
+    pub fn recent(&self, connection: Option<u64>, limit: usize) -> Vec<PacketRecord> {
+        let mut records: Vec<PacketRecord> = self
+            .recent
+            .iter()
+            .rev()
+            .filter(|r| connection.is_none_or(|c| r.connection == c))
+            .take(limit)
+            .cloned()
+            .collect();
+        records.reverse();
+        records
+    }
+}
+
+/// Format bytes as lowercase space-separated hex (`"0a ff 00"`).
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 3);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+/// Split a length-prefixed frame into its packet ID and payload.
+fn split_frame(frame: &[u8]) -> Option<(i32, &[u8])> {
+    let mut cursor = std::io::Cursor::new(frame);
+    let _length = mc_protocol::read_varint(&mut cursor).ok()?;
+    let packet_id = mc_protocol::read_varint(&mut cursor).ok()?;
+    let payload = frame.get(cursor.position() as usize..)?;
+    Some((packet_id, payload))
+}
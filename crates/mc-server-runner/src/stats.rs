@@ -0,0 +1,200 @@
+//! Player statistics
+//!
+//! Every player has [`Stats`]: vanilla statistics as entries of a stat type
+//! and a stat, e.g. `minecraft:mined` / `minecraft:stone` or
+//! `minecraft:custom` / `minecraft:walk_one_cm`. Tracked so far are blocks
+//! mined, distance walked (in centimeters), play time (in ticks) and games
+//! quit; players can't die yet, so there are no deaths to count.
+//!
+//! Opening the statistics screen sends Client Command (request stats), which
+//! is answered with Award Stats. Entries whose network IDs aren't known are
+//! left out of it; the built-in stat registries are empty until `mc-gen` has
+//! extracted the registry reports.
+//!
+//! With a world directory, a player's stats are loaded when they log in and
+//! saved to `<world>/stats/<uuid>.json`, vanilla's layout, when they leave or
+//! the server shuts down.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use eyre::WrapErr;
+use flecs_ecs::prelude::*;
+use mc_data::play::serverbound::ClientCommand;
+use mc_data::{BlockState, builtin_registry};
+use mc_protocol::{Packet, read_varint};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::components::{PacketBuffer, Position};
+use crate::protocol::{format_uuid, send_award_stats};
+
+/// Stat type of blocks mined, by block name
+pub const MINED: &str = "minecraft:mined";
+
+/// Stat type of the named statistics (`minecraft:custom_stat` registry)
+const CUSTOM: &str = "minecraft:custom";
+
+const WALK_ONE_CM: &str = "minecraft:walk_one_cm";
+const PLAY_TIME: &str = "minecraft:play_time";
+const LEAVE_GAME: &str = "minecraft:leave_game";
+
+/// Serverbound Client Command packet ID (Play state)
+const CLIENT_COMMAND_PACKET_ID: i32 = ClientCommand::ID;
+
+/// Client Command action sent when the statistics screen opens
+const REQUEST_STATS: i32 = 1;
+
+/// Longest horizontal move in one tick counted as walking; longer moves are
+/// teleports
+const MAX_STEP: f64 = 10.0;
+
+/// A player's statistics
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    /// Value by stat type, then stat
+    stats: BTreeMap<String, BTreeMap<String, i32>>,
+    /// Position the next tick's walking is measured from
+    #[serde(skip)]
+    walked_from: Option<Position>,
+}
+
+impl Stats {
+    /// Add `amount` to a stat
+    pub fn award(&mut self, stat_type: &str, stat: &str, amount: i32) {
+        let value = self
+            .stats
+            .entry(stat_type.to_string())
+            .or_default()
+            .entry(stat.to_string())
+            .or_default();
+        *value = value.saturating_add(amount);
+    }
+
+    /// Entries with known network IDs, as `(stat type, stat, value)`
+    fn network_entries(&self) -> Vec<(i32, i32, i32)> {
+        let stat_types = builtin_registry("minecraft:stat_type");
+        let custom_stats = builtin_registry("minecraft:custom_stat");
+        let mut entries = Vec::new();
+        for (stat_type, stats) in &self.stats {
+            let Some(type_id) = stat_types.and_then(|registry| registry.entry_id(stat_type)) else {
+                continue;
+            };
+            for (stat, &value) in stats {
+                let stat_id = match stat_type.as_str() {
+                    MINED => BlockState::by_name(stat).and_then(BlockState::block_id),
+                    CUSTOM => custom_stats.and_then(|registry| registry.entry_id(stat)),
+                    _ => None,
+                };
+                if let Some(stat_id) = stat_id {
+                    entries.push((type_id, stat_id, value));
+                }
+            }
+        }
+        entries
+    }
+}
+
+/// Global: Directory player stats are saved in
+#[derive(Component)]
+pub struct StatsStore {
+    dir: PathBuf,
+}
+
+impl StatsStore {
+    pub const fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, uuid: u128) -> PathBuf {
+        self.dir.join(format!("{}.json", format_uuid(uuid)))
+    }
+
+    /// A player's saved stats, or empty stats if none were saved
+    fn load(&self, uuid: u128) -> Stats {
+        let path = self.path(uuid);
+        let Ok(bytes) = fs::read(&path) else {
+            return Stats::default();
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable stats {}: {e}", path.display());
+            Stats::default()
+        })
+    }
+
+    fn save(&self, uuid: u128, stats: &Stats) -> eyre::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(uuid);
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec(stats)?)?;
+        fs::rename(&temp, &path).wrap_err_with(|| format!("failed to save {}", path.display()))
+    }
+}
+
+/// Stats of a player logging in
+pub fn load(world: &WorldRef<'_>, uuid: u128) -> Stats {
+    world
+        .try_get::<&StatsStore>(|store| store.load(uuid))
+        .unwrap_or_default()
+}
+
+/// Save the stats of a player whose stats are being removed (on leaving or
+/// shutdown), counting the departure
+pub fn save(world: &WorldRef<'_>, uuid: u128, stats: &Stats) {
+    let mut stats = stats.clone();
+    stats.award(CUSTOM, LEAVE_GAME, 1);
+    world.try_get::<&StatsStore>(|store| {
+        if let Err(e) = store.save(uuid, &stats) {
+            warn!("Failed to save the stats of {}: {e:#}", format_uuid(uuid));
+        }
+    });
+}
+
+/// Remove every player's stats, which saves them
+pub fn save_all(world: &WorldRef<'_>) {
+    let mut players = Vec::new();
+    world
+        .query::<&Stats>()
+        .build()
+        .each_entity(|player, _| players.push(player.id()));
+    for player in players {
+        world.entity_from_id(player).remove::<Stats>();
+    }
+}
+
+/// Count a tick of play time and the distance walked since the last tick
+pub fn tick(stats: &mut Stats, pos: &Position) {
+    stats.award(CUSTOM, PLAY_TIME, 1);
+    if let Some(from) = stats.walked_from.replace(*pos) {
+        let step = (pos.x - from.x).hypot(pos.z - from.z);
+        let centimeters = (step * 100.0).round() as i32;
+        if step <= MAX_STEP && centimeters > 0 {
+            stats.award(CUSTOM, WALK_ONE_CM, centimeters);
+        }
+    }
+}
+
+/// Answer statistics requests with the player's stats
+pub fn handle_stats_requests(buffer: &mut PacketBuffer, stats: &Stats) {
+    let mut requested = false;
+    let mut remaining = Vec::new();
+
+    while let Some((packet_id, data)) = buffer.pop_incoming() {
+        if packet_id == CLIENT_COMMAND_PACKET_ID
+            && read_varint(&mut &data[..]).ok() == Some(REQUEST_STATS)
+        {
+            requested = true;
+        } else {
+            remaining.push((packet_id, data));
+        }
+    }
+
+    for (id, data) in remaining {
+        buffer.push_incoming(id, data);
+    }
+
+    if requested {
+        send_award_stats(buffer, &stats.network_entries());
+    }
+}
@@ -3,6 +3,7 @@
 //! Uses Flecs ECS with pipeline phases.
 
 mod attack;
+mod block;
 mod command;
 mod config;
 #[cfg(feature = "dashboard")]
@@ -14,11 +15,34 @@ mod network;
 mod play;
 mod time;
 
-pub use command::send_commands_to_player;
+#[cfg(feature = "reload")]
+pub use command::send_chat_message;
+pub use command::{broadcast_chat, run_command, send_commands_to_player};
+pub use login::spawn_position;
+pub use time::broadcast_time;
 
 use flecs_ecs::prelude::*;
 
+use crate::anvil::RegionStore;
+use crate::audit::AuditLog;
+use crate::autosave::{self, SaveStats};
+use crate::bot::{self, Bot, MoveTarget};
+use crate::chunk::{self, ChunkCache, PendingBlockChanges};
 use crate::components::*;
+use crate::damage::{self, MotionChanged};
+use crate::entity_ids::EntityIdAllocator;
+use crate::game_rules::{self, GameRules};
+use crate::inventory::Inventory;
+use crate::journal::{self, Journal};
+use crate::protection::{self, ProtectionLog};
+use crate::protocol_state::{self, StateTransitions};
+use crate::replay::{self, Replay};
+use crate::stats::{self, Stats};
+use crate::view_distance::{self, ViewDistance};
+use crate::{block_tick, collision, player_data, redstone};
+
+/// Ticks between chunk cache sweeps (5 seconds at 20 TPS)
+const CHUNK_CACHE_SWEEP_INTERVAL: i64 = 100;
 
 /// Initialize all systems for the server
 pub fn init_systems(world: &World) {
@@ -39,6 +63,34 @@ pub fn init_systems(world: &World) {
             network::system_handle_disconnects(&it.world());
         });
 
+    // Recycle protocol entity IDs when their holder despawns
+    // (try_get: the allocator may already be gone during world teardown)
+    world
+        .observer::<flecs::OnRemove, &EntityId>()
+        .each_entity(|entity, entity_id| {
+            entity
+                .world()
+                .try_get::<&EntityIdAllocator>(|ids| ids.release(entity_id.value));
+        });
+
+    // Drop a player from chunk viewers and save their state when they leave
+    world
+        .observer::<flecs::OnRemove, ()>()
+        .with(Player)
+        .each_entity(|entity, ()| {
+            chunk::forget_all_chunks(entity);
+            player_data::save(entity);
+        });
+
+    // Save a player's stats when they leave (or at shutdown)
+    world
+        .observer::<flecs::OnRemove, &Stats>()
+        .each_entity(|entity, player_stats| {
+            if let Some(uuid) = entity.try_get::<&Uuid>(|u| u.0) {
+                stats::save(&entity.world(), uuid, player_stats);
+            }
+        });
+
     // ============================================================
     // PROTOCOL HANDLING - PreUpdate phase
     // ============================================================
@@ -54,10 +106,10 @@ pub fn init_systems(world: &World) {
         .system::<(&mut PacketBuffer, &ProtocolState)>()
         .with(Connection)
         .kind(id::<flecs::pipeline::PreUpdate>())
-        .each_iter(|it, _i, (buffer, state)| {
+        .each_iter(|it, i, (buffer, state)| {
             let world = it.world();
             let config = world.get::<&ServerConfig>(|c| c.clone());
-            handshake::handle_status(buffer, state, &config);
+            handshake::handle_status(&world, it.entity(i), buffer, state, &config);
         });
 
     world
@@ -66,9 +118,9 @@ pub fn init_systems(world: &World) {
         .kind(id::<flecs::pipeline::PreUpdate>())
         .each_iter(|it, i, (buffer, state)| {
             let world = it.world();
-            let entity_counter = world.get::<&EntityIdCounter>(|c| EntityIdCounter(c.0.clone()));
+            let entity_ids = world.get::<&EntityIdAllocator>(EntityIdAllocator::clone);
             let entity = it.entity(i);
-            login::handle_login(entity, buffer, state, &entity_counter);
+            login::handle_login(entity, buffer, state, &entity_ids);
         });
 
     world
@@ -83,21 +135,53 @@ pub fn init_systems(world: &World) {
     // PLAY STATE - OnUpdate phase
     // ============================================================
     world
-        .system::<(&mut PacketBuffer, &Position, &EntityId)>()
+        .system::<(&mut PacketBuffer, &Position, &mut ChunkPosition, &EntityId)>()
         .with(NeedsSpawnChunks)
         .kind(id::<flecs::pipeline::OnUpdate>())
-        .each_iter(|it, i, (buffer, pos, entity_id)| {
+        .each_iter(|it, i, (buffer, pos, center, entity_id)| {
             let world = it.world();
             let entity = it.entity(i);
-            play::send_spawn_data(&world, entity, buffer, pos, entity_id);
+            play::send_spawn_data(&world, entity, buffer, pos, center, entity_id);
         });
 
     world
-        .system::<(&mut PacketBuffer, &mut Position, &mut Rotation)>()
+        .system::<&mut PacketBuffer>()
         .with(InPlayState)
         .kind(id::<flecs::pipeline::OnUpdate>())
-        .each(|(buffer, pos, rot)| {
-            play::handle_movement(buffer, pos, rot);
+        .each_entity(|entity, buffer| {
+            play::handle_client_information(entity, buffer);
+        });
+
+    world
+        .system::<(
+            &mut PacketBuffer,
+            &mut Position,
+            &mut Rotation,
+            &mut Latency,
+        )>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each(|(buffer, pos, rot, latency)| {
+            play::handle_movement(buffer, pos, rot, latency);
+        });
+
+    // Stream chunks as players cross chunk borders
+    world
+        .system::<(&mut PacketBuffer, &Position, &mut ChunkPosition)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_entity(|entity, (buffer, pos, center)| {
+            chunk::update_interest(entity, buffer, pos, center, false);
+        });
+
+    // Resend or forget chunks when a player's view distance changes
+    world
+        .system::<(&mut PacketBuffer, &Position, &mut ChunkPosition)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_entity(|entity, (buffer, pos, center)| {
+            let distance = entity.world().get::<&ViewDistance>(|d| *d);
+            view_distance::apply(entity, buffer, pos, center, &distance);
         });
 
     world
@@ -110,15 +194,36 @@ pub fn init_systems(world: &World) {
             play::send_keepalive(buffer, &world_time);
         });
 
+    // Tab list: newcomers once on join, everyone's latency periodically
+    world
+        .system::<()>()
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, _i, _| {
+            let world = it.world();
+            let world_time = world.get::<&WorldTime>(|t| *t);
+            play::update_tab_list(&world, &world_time);
+        });
+
     world
-        .system::<(&mut PacketBuffer, &Position)>()
+        .system::<(&mut HudText, &Position)>()
         .with(InPlayState)
         .kind(id::<flecs::pipeline::OnUpdate>())
-        .each_iter(|it, _i, (buffer, pos)| {
+        .each_iter(|it, _i, (hud, pos)| {
             let world = it.world();
             let world_time = world.get::<&WorldTime>(|t| *t);
             let tps = world.get::<&TpsTracker>(|t| *t);
-            play::send_position_action_bar(buffer, pos, &world_time, &tps);
+            play::update_debug_hud(hud, pos, &world_time, &tps);
+        });
+
+    // Runs after every OnUpdate HUD writer
+    world
+        .system::<(&mut PacketBuffer, &mut HudText)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, (buffer, hud)| {
+            let world = it.world();
+            let world_time = world.get::<&WorldTime>(|t| *t);
+            play::render_hud(buffer, hud, &world_time);
         });
 
     world
@@ -141,14 +246,122 @@ pub fn init_systems(world: &World) {
             command::handle_commands(&world, entity, buffer);
         });
 
+    // Slots filled by /give
+    world
+        .system::<(&mut Inventory, &mut PacketBuffer)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each(|(inventory, buffer)| {
+            inventory.send_changes(buffer);
+        });
+
+    // Statistics: play time, distance walked and the statistics screen
+    world
+        .system::<(&mut Stats, &Position)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each(|(player_stats, pos)| {
+            stats::tick(player_stats, pos);
+        });
+
+    world
+        .system::<(&mut PacketBuffer, &Stats)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each(|(buffer, player_stats)| {
+            stats::handle_stats_requests(buffer, player_stats);
+        });
+
+    // ============================================================
+    // BLOCKS - OnUpdate phase
+    // ============================================================
+    world
+        .system::<(&mut PacketBuffer, &GameMode)>()
+        .with(InPlayState)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, i, (buffer, game_mode)| {
+            let world = it.world();
+            let entity = it.entity(i);
+            block::handle_block_interactions(&world, entity, buffer, game_mode);
+        });
+
+    // Block changes requested above, applied once no player buffer is borrowed
+    world
+        .system::<&mut PendingBlockChanges>()
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, _i, pending| {
+            chunk::apply_block_changes(&it.world(), pending);
+        });
+
+    world
+        .system::<()>()
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, _i, _| {
+            redstone::tick_redstone(&it.world());
+        });
+
+    // Replay mode: the next journaled tick
+    world
+        .system::<&mut Replay>()
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, _i, replay| {
+            replay::step(&it.world(), replay);
+        });
+
+    world
+        .system::<()>()
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, _i, _| {
+            block_tick::fire_due_ticks(&it.world());
+        });
+
+    // Bots walking to their move target
+    world
+        .system::<(&mut Position, &mut Rotation, &MoveTarget)>()
+        .with(Bot)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_entity(|entity, (pos, rot, target)| {
+            if bot::walk(pos, rot, target) {
+                entity.remove::<MoveTarget>();
+            }
+        });
+
+    // Bots sliding from knockback
+    world
+        .system::<(&mut Position, &mut Velocity)>()
+        .with(Bot)
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_entity(|entity, (pos, velocity)| {
+            if damage::drift(pos, velocity) {
+                entity.remove::<Velocity>();
+            }
+        });
+
+    // Push players apart once everyone has moved
+    world
+        .system::<()>()
+        .kind(id::<flecs::pipeline::OnUpdate>())
+        .each_iter(|it, _i, _| {
+            collision::resolve(&it.world());
+        });
+
+    // Knockback dealt this tick
+    world
+        .system::<(&EntityId, &Position, &Velocity)>()
+        .with(MotionChanged)
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_entity(|entity, (id, pos, velocity)| {
+            damage::send_motion(&entity.world(), entity, id, pos, velocity);
+        });
+
     // ============================================================
     // TIME - PostUpdate phase
     // ============================================================
     world
-        .system::<&mut WorldTime>()
+        .system::<(&mut WorldTime, &GameRules)>()
         .kind(id::<flecs::pipeline::PostUpdate>())
-        .each(|time| {
-            time.tick();
+        .each(|(time, rules)| {
+            time.tick(rules.do_daylight_cycle());
         });
 
     world
@@ -160,15 +373,79 @@ pub fn init_systems(world: &World) {
             tps.update(delta);
         });
 
+    world
+        .system::<&mut ViewDistance>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, distance| {
+            view_distance::adjust(&it.world(), distance);
+        });
+
+    // ============================================================
+    // CHUNKS - PostUpdate phase
+    // ============================================================
+    world
+        .system::<&mut ChunkCache>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, cache| {
+            let world = it.world();
+            let world_age = world.get::<&WorldTime>(|t| t.world_age);
+            if world_age % CHUNK_CACHE_SWEEP_INTERVAL == 0 {
+                chunk::sweep_chunk_cache(&world, cache);
+            }
+        });
+
+    world
+        .system::<(&RegionStore, &mut SaveStats)>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, (store, stats)| {
+            autosave::autosave(&it.world(), store, stats);
+        });
+
+    world
+        .system::<&mut ProtectionLog>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, log| {
+            protection::log_denials(&it.world(), log);
+        });
+
+    world
+        .system::<&mut StateTransitions>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, queue| {
+            protocol_state::log_transitions(&it.world(), queue);
+        });
+
+    world
+        .system::<&mut AuditLog>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each(|log| log.flush());
+
+    // After every system that reacts to game rule changes
+    world
+        .system::<&mut GameRules>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, rules| {
+            game_rules::finish_tick(&it.world(), rules);
+        });
+
+    // Last of the tick's changes, so the journal commits them all
+    world
+        .system::<&mut Journal>()
+        .kind(id::<flecs::pipeline::PostUpdate>())
+        .each_iter(|it, _i, journal| {
+            journal::commit_tick(&it.world(), journal);
+        });
+
     // ============================================================
     // NETWORK EGRESS - OnStore phase (last)
     // ============================================================
     world
-        .system::<(&mut PacketBuffer, &ConnectionId)>()
+        .system::<(&mut PacketBuffer, &ConnectionId, &ProtocolState)>()
         .with(Connection)
         .kind(id::<flecs::pipeline::OnStore>())
-        .each_iter(|it, _i, (buffer, conn_id)| {
+        .each_iter(|it, _i, (buffer, conn_id, state)| {
             let world = it.world();
+            network::record_outgoing(&world, buffer, conn_id, state);
             world.get::<&NetworkEgress>(|egress| {
                 network::handle_egress(buffer, conn_id, egress);
             });
@@ -1,6 +1,7 @@
 //! Attack/Combat systems
 //!
-//! Handles player attacks on entities via the Interact packet (action type = ATTACK).
+//! Handles player attacks on entities via the Interact packet (action type = ATTACK),
+//! dealing melee damage through [`damage::melee`].
 
 use flecs_ecs::prelude::*;
 use mc_data::play::serverbound::Interact;
@@ -8,6 +9,7 @@ use mc_protocol::{Decode, Packet};
 use tracing::{debug, info};
 
 use crate::components::{EntityId, Name, PacketBuffer, Position};
+use crate::damage;
 
 /// Interaction action types from the protocol
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -127,10 +129,13 @@ pub fn handle_attacks(
                 .try_get::<&Name>(|n| n.value.clone())
                 .unwrap_or_else(|| format!("Entity#{}", attack.target_entity_id));
 
-            info!(
-                "{} attacked {} (sneaking: {})",
-                attacker_name, target_name, attack.sneaking
-            );
+            match damage::melee(world, attacker_entity, target) {
+                Some(damage) => info!(
+                    "{} attacked {} for {:.1} (sneaking: {})",
+                    attacker_name, target_name, damage, attack.sneaking
+                ),
+                None => debug!("{} attacked invulnerable {}", attacker_name, target_name),
+            }
         } else {
             debug!(
                 "{} attacked unknown entity ID {} at {:?}",
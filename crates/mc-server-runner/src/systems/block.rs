@@ -0,0 +1,142 @@
+//! Block interaction systems
+//!
+//! Handles Use Item On packets (right-clicking a lever flips it) and Player
+//! Action packets: creative players break a block as soon as they start
+//! digging it, survival players once they finish. Blocks the player may not
+//! build at (see [`protection`]) are left alone.
+//!
+//! [`protection`]: crate::protection
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use mc_data::play::serverbound::{PlayerAction, UseItemOn};
+use mc_protocol::{Decode, Packet, read_varint};
+use tracing::debug;
+
+use crate::audit::{self, AuditAction};
+use crate::chunk;
+use crate::components::{BlockPos, GameMode, PacketBuffer};
+use crate::protection::{self, Action};
+use crate::protocol::send_block_changed_ack;
+use crate::redstone;
+use crate::stats::{self, Stats};
+
+/// Serverbound Use Item On packet ID in Play state
+const USE_ITEM_ON_PACKET_ID: i32 = UseItemOn::ID;
+
+/// Serverbound Player Action packet ID in Play state
+const PLAYER_ACTION_PACKET_ID: i32 = PlayerAction::ID;
+
+/// Player Action statuses
+const START_DESTROY_BLOCK: i32 = 0;
+const STOP_DESTROY_BLOCK: i32 = 2;
+
+/// Parsed Use Item On packet data
+#[derive(Debug, Clone, Copy)]
+struct UseItemOnPacket {
+    pos: BlockPos,
+    sequence: i32,
+}
+
+fn parse_use_item_on(data: &[u8]) -> Option<UseItemOnPacket> {
+    let mut cursor = std::io::Cursor::new(data);
+
+    let _hand = read_varint(&mut cursor).ok()?;
+    let pos = BlockPos::from_packed(i64::decode(&mut cursor).ok()?);
+    let _face = read_varint(&mut cursor).ok()?;
+    // Cursor position on the face
+    for _ in 0..3 {
+        f32::decode(&mut cursor).ok()?;
+    }
+    let _inside_block = bool::decode(&mut cursor).ok()?;
+    let _world_border_hit = bool::decode(&mut cursor).ok()?;
+    let sequence = read_varint(&mut cursor).ok()?;
+
+    Some(UseItemOnPacket { pos, sequence })
+}
+
+/// Parsed Player Action packet data
+#[derive(Debug, Clone, Copy)]
+struct PlayerActionPacket {
+    status: i32,
+    pos: BlockPos,
+    sequence: i32,
+}
+
+fn parse_player_action(data: &[u8]) -> Option<PlayerActionPacket> {
+    let mut cursor = std::io::Cursor::new(data);
+
+    let status = read_varint(&mut cursor).ok()?;
+    let pos = BlockPos::from_packed(i64::decode(&mut cursor).ok()?);
+    let _face = u8::decode(&mut cursor).ok()?;
+    let sequence = read_varint(&mut cursor).ok()?;
+
+    Some(PlayerActionPacket {
+        status,
+        pos,
+        sequence,
+    })
+}
+
+/// Handle block right-clicks and digging
+pub fn handle_block_interactions(
+    world: &WorldRef<'_>,
+    player: EntityView<'_>,
+    buffer: &mut PacketBuffer,
+    game_mode: &GameMode,
+) {
+    let mut uses = Vec::new();
+    let mut actions = Vec::new();
+    let mut remaining = Vec::new();
+
+    while let Some((packet_id, data)) = buffer.pop_incoming() {
+        match packet_id {
+            USE_ITEM_ON_PACKET_ID => uses.extend(parse_use_item_on(&data)),
+            PLAYER_ACTION_PACKET_ID => actions.extend(parse_player_action(&data)),
+            _ => remaining.push((packet_id, data)),
+        }
+    }
+
+    for (id, data) in remaining {
+        buffer.push_incoming(id, data);
+    }
+
+    for packet in uses {
+        if redstone::is_lever(world, packet.pos)
+            && protection::allowed(world, player, packet.pos, Action::Use)
+            && redstone::toggle_lever(world, packet.pos)
+        {
+            debug!("Toggled lever at {:?}", packet.pos);
+        }
+        // Ends the client's prediction of the interaction
+        send_block_changed_ack(buffer, packet.sequence);
+    }
+
+    for packet in actions {
+        let breaks = match packet.status {
+            START_DESTROY_BLOCK => game_mode.value == GameMode::CREATIVE.value,
+            STOP_DESTROY_BLOCK => game_mode.value == GameMode::SURVIVAL.value,
+            // Other actions (dropping items, swapping hands, ...) only end
+            // the client's prediction
+            _ => false,
+        };
+        if breaks && protection::allowed(world, player, packet.pos, Action::Break) {
+            break_block(world, player, packet.pos);
+        }
+        send_block_changed_ack(buffer, packet.sequence);
+    }
+}
+
+/// Break a block a player dug, counting it in their stats and audit log
+fn break_block(world: &WorldRef<'_>, player: EntityView<'_>, pos: BlockPos) {
+    let Some(state) = chunk::block_at(world, pos).filter(|state| !state.is_air()) else {
+        return;
+    };
+    if chunk::queue_block_change(world, pos, BlockState::AIR) {
+        let block = state.block_name().unwrap_or("minecraft:air");
+        player.try_get::<&mut Stats>(|stats| stats.award(stats::MINED, block, 1));
+        let block = block.to_string();
+        debug!("Broke {block} at {pos:?}");
+        audit::record(world, player, AuditAction::BreakBlock { pos, block });
+    }
+}
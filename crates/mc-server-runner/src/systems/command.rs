@@ -2,19 +2,35 @@
 //!
 //! Handles incoming chat commands and generates Minecraft command tree packets.
 
+use std::hash::{BuildHasher, RandomState};
+
 use bytes::{BufMut, Bytes, BytesMut};
 use flecs_ecs::prelude::*;
 use mc_protocol::{Decode, Encode};
 use tracing::{debug, info};
 
+use crate::audit::{self, AuditAction, AuditLog};
+use crate::buffer_pool;
+use crate::chunk::{ChunkBlocks, chunk_name, chunk_viewers, queue_block_change};
 use crate::components::{
-    EntityId, InPlayState, Name, PacketBuffer, Position, Rotation, TpsTracker,
+    BlockPos, ClientLocale, Connection, EntityId, InPlayState, Name, PacketBuffer, Player,
+    Position, Rotation, Running, TpsTracker, Uuid,
 };
-use crate::protocol::encode_packet;
+use crate::game_rules::{GameRuleError, GameRules};
+use crate::i18n::{Translations, locale_of};
+use crate::inventory::Inventory;
+use crate::protection::{self, Claim};
+use crate::protocol::{encode_packet, offline_uuid};
+#[cfg(feature = "reload")]
+use crate::reload::ReloadQueue;
+use crate::systems::spawn_position;
 
 use mc_data::play::clientbound::{Commands, SystemChat};
 use mc_data::play::serverbound::ChatCommand;
+use mc_data::{BlockState, Candidate, EntitySelector, ItemArgument};
 use mc_protocol::Packet;
+use mc_text::lang::Catalog;
+use mc_text::{Text, tr};
 
 /// Serverbound Chat Command packet ID (Play state)
 const CHAT_COMMAND_PACKET_ID: i32 = ChatCommand::ID;
@@ -25,6 +41,9 @@ const COMMANDS_PACKET_ID: i32 = Commands::ID;
 /// Clientbound System Chat packet ID
 const SYSTEM_CHAT_PACKET_ID: i32 = SystemChat::ID;
 
+/// Audit entries `/audit` shows without a count
+const AUDIT_ENTRIES: usize = 10;
+
 /// Command node flags
 const NODE_TYPE_ROOT: u8 = 0;
 const NODE_TYPE_LITERAL: u8 = 1;
@@ -33,8 +52,12 @@ const FLAG_EXECUTABLE: u8 = 0x04;
 
 /// Argument parser IDs from Minecraft registry
 mod parser_ids {
+    pub const INTEGER: i32 = 3;
     pub const STRING_SINGLE_WORD: i32 = 5;
     pub const ENTITY: i32 = 6;
+    pub const BLOCK_POS: i32 = 8;
+    pub const BLOCK_STATE: i32 = 12;
+    pub const ITEM_STACK: i32 = 14;
 }
 
 /// Command definition for building command trees
@@ -49,6 +72,8 @@ pub struct ArgDef {
     pub name: &'static str,
     pub parser_id: i32,
     pub parser_data: Option<Vec<u8>>,
+    /// The command can be run without this and later arguments
+    pub optional: bool,
 }
 
 /// All registered commands
@@ -60,6 +85,7 @@ pub fn registered_commands() -> Vec<CommandDef> {
                 name: "entity",
                 parser_id: parser_ids::ENTITY,
                 parser_data: Some(vec![0x01]),
+                optional: false,
             }],
         },
         CommandDef {
@@ -74,6 +100,118 @@ pub fn registered_commands() -> Vec<CommandDef> {
             name: "entities",
             args: vec![],
         },
+        CommandDef {
+            name: "chunk",
+            args: vec![],
+        },
+        CommandDef {
+            name: "setblock",
+            args: vec![
+                ArgDef {
+                    name: "pos",
+                    parser_id: parser_ids::BLOCK_POS,
+                    parser_data: None,
+                    optional: false,
+                },
+                ArgDef {
+                    name: "block",
+                    parser_id: parser_ids::BLOCK_STATE,
+                    parser_data: None,
+                    optional: false,
+                },
+            ],
+        },
+        CommandDef {
+            name: "give",
+            args: vec![
+                ArgDef {
+                    name: "targets",
+                    // Players only
+                    parser_id: parser_ids::ENTITY,
+                    parser_data: Some(vec![0x02]),
+                    optional: false,
+                },
+                ArgDef {
+                    name: "item",
+                    parser_id: parser_ids::ITEM_STACK,
+                    parser_data: None,
+                    optional: false,
+                },
+                ArgDef {
+                    name: "count",
+                    // Minimum of 1
+                    parser_id: parser_ids::INTEGER,
+                    parser_data: Some(vec![0x01, 0, 0, 0, 1]),
+                    optional: true,
+                },
+            ],
+        },
+        CommandDef {
+            name: "claim",
+            args: vec![
+                ArgDef {
+                    name: "from",
+                    parser_id: parser_ids::BLOCK_POS,
+                    parser_data: None,
+                    optional: false,
+                },
+                ArgDef {
+                    name: "to",
+                    parser_id: parser_ids::BLOCK_POS,
+                    parser_data: None,
+                    optional: false,
+                },
+            ],
+        },
+        CommandDef {
+            name: "gamerule",
+            args: vec![
+                ArgDef {
+                    name: "rule",
+                    parser_id: parser_ids::STRING_SINGLE_WORD,
+                    parser_data: Some(vec![0x00]),
+                    optional: false,
+                },
+                ArgDef {
+                    name: "value",
+                    parser_id: parser_ids::STRING_SINGLE_WORD,
+                    parser_data: Some(vec![0x00]),
+                    optional: true,
+                },
+            ],
+        },
+        CommandDef {
+            name: "audit",
+            args: vec![
+                ArgDef {
+                    name: "player",
+                    parser_id: parser_ids::STRING_SINGLE_WORD,
+                    parser_data: Some(vec![0x00]),
+                    optional: false,
+                },
+                ArgDef {
+                    name: "count",
+                    // Minimum of 1
+                    parser_id: parser_ids::INTEGER,
+                    parser_data: Some(vec![0x01, 0, 0, 0, 1]),
+                    optional: true,
+                },
+            ],
+        },
+        CommandDef {
+            name: "stop",
+            args: vec![],
+        },
+        #[cfg(feature = "reload")]
+        CommandDef {
+            name: "reload",
+            args: vec![ArgDef {
+                name: "target",
+                parser_id: parser_ids::STRING_SINGLE_WORD,
+                parser_data: Some(vec![0x00]),
+                optional: false,
+            }],
+        },
     ]
 }
 
@@ -151,7 +289,8 @@ pub fn build_commands_packet() -> eyre::Result<Bytes> {
                     vec![nodes.len() as i32 + 1]
                 };
 
-                let flags = NODE_TYPE_ARGUMENT | if is_last { FLAG_EXECUTABLE } else { 0 };
+                let executable = is_last || cmd.args[i + 1].optional;
+                let flags = NODE_TYPE_ARGUMENT | if executable { FLAG_EXECUTABLE } else { 0 };
 
                 nodes.push(CommandNode {
                     flags,
@@ -209,103 +348,515 @@ struct CommandNode {
     parser_data: Option<Vec<u8>>,
 }
 
-fn send_chat_message(buffer: &mut PacketBuffer, message: &str) {
-    let mut data = BytesMut::new();
-    let nbt = mc_protocol::nbt! {
-        "text" => message,
-    };
-    data.extend_from_slice(&nbt.to_network_bytes());
-    data.put_u8(0);
-    buffer.push_outgoing(encode_packet(SYSTEM_CHAT_PACKET_ID, &data));
+/// Send a system chat message to one player
+pub fn send_chat_message(buffer: &mut PacketBuffer, message: &Text) {
+    let packet = buffer_pool::frame(SYSTEM_CHAT_PACKET_ID, |data| {
+        data.extend_from_slice(&message.to_network_bytes());
+        data.push(0); // overlay: false
+        Ok(())
+    });
+    if let Ok(packet) = packet {
+        buffer.push_outgoing(packet);
+    }
+}
+
+/// Send a chat message to every player in play
+pub fn broadcast_chat(world: &WorldRef<'_>, message: &Text) {
+    world
+        .query::<&mut PacketBuffer>()
+        .with(InPlayState)
+        .build()
+        .each(|buffer| send_chat_message(buffer, message));
 }
 
-fn parse_command(input: &str) -> Option<(&str, Vec<&str>)> {
+/// Split a command line into its name and the rest, which arguments with
+/// spaces in them (like SNBT) are parsed from
+fn parse_command(input: &str) -> Option<(&str, &str)> {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return None;
     }
-    let mut parts = trimmed.split_whitespace();
-    let cmd_name = parts.next()?;
-    let args: Vec<&str> = parts.collect();
-    Some((cmd_name, args))
+    Some(
+        trimmed
+            .split_once(char::is_whitespace)
+            .map_or((trimmed, ""), |(cmd, rest)| (cmd, rest.trim_start())),
+    )
+}
+
+/// Entities matching `selector` as seen from `executor`: those with a
+/// protocol entity ID, plus the executor itself (e.g. the console). The
+/// origin is the executor's position, or the spawn point if it has none.
+fn select_entities<'a>(
+    world: &WorldRef<'a>,
+    executor: EntityView<'a>,
+    selector: &EntitySelector,
+) -> Vec<EntityView<'a>> {
+    let origin = executor
+        .try_get::<&Position>(|p| *p)
+        .unwrap_or_else(|| spawn_position(world));
+
+    let mut entities = Vec::new();
+    world
+        .query::<(&EntityId, &Position)>()
+        .build()
+        .each_entity(|entity, (_, pos)| {
+            // Connections still logging in aren't in the world yet
+            if entity.has(Connection) && !entity.has(InPlayState) {
+                return;
+            }
+            entities.push((entity.id(), *pos));
+        });
+    if !entities.iter().any(|(entity, _)| *entity == executor.id()) {
+        entities.push((executor.id(), origin));
+    }
+
+    let names: Vec<Option<String>> = entities
+        .iter()
+        .map(|(entity, _)| {
+            world
+                .entity_from_id(*entity)
+                .try_get::<&Name>(|n| n.value.clone())
+        })
+        .collect();
+    let candidates: Vec<Candidate<'_>> = entities
+        .iter()
+        .zip(&names)
+        .map(|((entity, pos), name)| Candidate {
+            position: [pos.x, pos.y, pos.z],
+            entity_type: if world.entity_from_id(*entity).has(Player) {
+                "minecraft:player"
+            } else {
+                "minecraft:unknown"
+            },
+            name: name.as_deref(),
+            executor: *entity == executor.id(),
+        })
+        .collect();
+
+    let seed = RandomState::new().hash_one(executor.id().0);
+    selector
+        .select([origin.x, origin.y, origin.z], &candidates, seed)
+        .into_iter()
+        .map(|index| world.entity_from_id(entities[index].0))
+        .collect()
+}
+
+/// Parse a coordinate, where `~` and `~n` are relative to `base`
+fn parse_coordinate(arg: &str, base: f64) -> Option<i32> {
+    match arg.strip_prefix('~') {
+        Some("") => Some(base.floor() as i32),
+        Some(offset) => Some(base.floor() as i32 + offset.parse::<i32>().ok()?),
+        None => arg.parse().ok(),
+    }
+}
+
+/// Parse a block state like `lever` or `minecraft:lever[face=floor,powered=true]`
+fn parse_block_state(arg: &str) -> Option<BlockState> {
+    let (name, properties) = match arg.split_once('[') {
+        Some((name, rest)) => (name, rest.strip_suffix(']')?),
+        None => (arg, ""),
+    };
+    let mut state = BlockState::by_name(name.strip_prefix("minecraft:").unwrap_or(name))?;
+    for property in properties.split(',').filter(|p| !p.is_empty()) {
+        let (key, value) = property.split_once('=')?;
+        state = state.with_property(key, value)?;
+    }
+    Some(state)
 }
 
 fn execute_command(
     cmd: &str,
+    rest: &str,
     args: &[&str],
     executor: EntityView<'_>,
     world: &WorldRef<'_>,
-) -> Result<String, String> {
+    lang: &Catalog,
+    locale: &str,
+) -> Result<Text, Text> {
     match cmd {
         "tps" => {
             let tps = world.get::<&TpsTracker>(|t| *t);
-            Ok(format!(
-                "TPS: {:.1} (5s) {:.1} (15s) {:.1} (1m)",
-                tps.tps_5s, tps.tps_15s, tps.tps_1m
-            ))
+            let text = tr!(
+                lang,
+                locale,
+                "command.tps",
+                format!("{:.1}", tps.tps_5s),
+                format!("{:.1}", tps.tps_15s),
+                format!("{:.1}", tps.tps_1m)
+            )
+            .append("\n")
+            .append(tr!(
+                lang,
+                locale,
+                "command.tps.mspt",
+                format!("{:.1}", tps.mspt.p50),
+                format!("{:.1}", tps.mspt.p95),
+                format!("{:.1}", tps.mspt.p99)
+            ));
+            if tps.behind == 0 && tps.caught_up == 0 && tps.skipped == 0 && tps.dropped == 0 {
+                return Ok(text);
+            }
+            Ok(text.append("\n").append(tr!(
+                lang,
+                locale,
+                "command.tps.behind",
+                tps.behind.to_string(),
+                tps.caught_up.to_string(),
+                tps.skipped.to_string(),
+                tps.dropped.to_string()
+            )))
         }
         "pos" => {
             let pos = executor
                 .try_get::<&Position>(|p| *p)
-                .ok_or("Position not found")?;
+                .ok_or_else(|| tr!(lang, locale, "command.pos.missing"))?;
+            let [x, y, z] = [pos.x, pos.y, pos.z].map(|v| format!("{:.2}", v));
             let rot = executor.try_get::<&Rotation>(|r| *r);
             if let Some(rot) = rot {
-                Ok(format!(
-                    "Position: {:.2}, {:.2}, {:.2} | Yaw: {:.1} Pitch: {:.1}",
-                    pos.x, pos.y, pos.z, rot.yaw, rot.pitch
+                Ok(tr!(
+                    lang,
+                    locale,
+                    "command.pos.rotation",
+                    x,
+                    y,
+                    z,
+                    format!("{:.1}", rot.yaw),
+                    format!("{:.1}", rot.pitch)
                 ))
             } else {
-                Ok(format!(
-                    "Position: {:.2}, {:.2}, {:.2}",
-                    pos.x, pos.y, pos.z
-                ))
+                Ok(tr!(lang, locale, "command.pos", x, y, z))
             }
         }
         "entities" => {
             let mut count = 0;
             world.query::<&EntityId>().build().each(|_| count += 1);
-            Ok(format!("Total entities with EntityId: {}", count))
+            Ok(tr!(lang, locale, "command.entities", count.to_string()))
+        }
+        "chunk" => {
+            let pos = executor
+                .try_get::<&Position>(|p| *p)
+                .ok_or_else(|| tr!(lang, locale, "command.pos.missing"))?;
+            let (cx, cz) = pos.chunk_pos();
+            let unloaded = || {
+                tr!(
+                    lang,
+                    locale,
+                    "command.chunk.unloaded",
+                    cx.to_string(),
+                    cz.to_string()
+                )
+            };
+            let chunk = world
+                .try_lookup_recursive(&chunk_name(cx, cz))
+                .ok_or_else(unloaded)?;
+
+            let [x, y, z] = [pos.x, pos.y, pos.z].map(|v| v.floor() as i32);
+            let biome = chunk
+                .try_get::<&ChunkBlocks>(|blocks| blocks.biome(x, y, z))
+                .ok_or_else(unloaded)?;
+            let biome = mc_data::registry("minecraft:worldgen/biome")
+                .and_then(|registry| registry.entries.get(biome as usize))
+                .map_or_else(|| biome.to_string(), ToString::to_string);
+
+            let viewers: Vec<String> = chunk_viewers(world, chunk.id())
+                .into_iter()
+                .map(|viewer| {
+                    world
+                        .entity_from_id(viewer)
+                        .try_get::<&Name>(|n| n.value.clone())
+                        .unwrap_or_else(|| viewer.to_string())
+                })
+                .collect();
+            Ok(tr!(
+                lang,
+                locale,
+                "command.chunk",
+                cx.to_string(),
+                cz.to_string(),
+                biome,
+                viewers.len().to_string(),
+                viewers.join(", ")
+            ))
+        }
+        "setblock" => {
+            let [x, y, z, block] = args else {
+                return Err(tr!(lang, locale, "command.setblock.usage"));
+            };
+            let pos = executor
+                .try_get::<&Position>(|p| *p)
+                .unwrap_or(Position::SPAWN);
+            let (Some(x), Some(y), Some(z)) = (
+                parse_coordinate(x, pos.x),
+                parse_coordinate(y, pos.y),
+                parse_coordinate(z, pos.z),
+            ) else {
+                return Err(tr!(lang, locale, "command.setblock.usage"));
+            };
+            let state = parse_block_state(block)
+                .ok_or_else(|| tr!(lang, locale, "command.setblock.invalid_block", *block))?;
+
+            let pos = BlockPos::new(x, y, z);
+            let placed = queue_block_change(world, pos, state);
+            let (x, y, z) = (x.to_string(), y.to_string(), z.to_string());
+            if placed {
+                let block = state.block_name().unwrap_or_default().to_string();
+                audit::record(world, executor, AuditAction::PlaceBlock { pos, block });
+                Ok(tr!(lang, locale, "command.setblock", x, y, z))
+            } else {
+                Err(tr!(lang, locale, "command.setblock.failed", x, y, z))
+            }
+        }
+        "give" => {
+            let usage = || tr!(lang, locale, "command.give.usage");
+            let (selector, rest) = EntitySelector::parse(rest)
+                .map_err(|e| tr!(lang, locale, "command.selector.invalid", e.to_string()))?;
+            if rest.trim().is_empty() {
+                return Err(usage());
+            }
+            let (item, rest) = ItemArgument::parse(rest)
+                .map_err(|e| tr!(lang, locale, "command.give.invalid_item", e.to_string()))?;
+            let count = match rest.trim() {
+                "" => 1,
+                count => count
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|&count| count > 0)
+                    .ok_or_else(usage)?,
+            };
+
+            let targets = select_entities(world, executor, &selector);
+            if targets.is_empty() {
+                return Err(tr!(lang, locale, "command.selector.none"));
+            }
+            let stack = item.stack(count);
+            let item = item.item.name().unwrap_or_default().to_string();
+            let from = executor
+                .try_get::<&Name>(|n| n.value.clone())
+                .unwrap_or_else(|| executor.name());
+            let mut given = 0;
+            let mut names = Vec::new();
+            for target in targets {
+                let left = target
+                    .try_get::<&mut Inventory>(|inventory| inventory.insert(&stack))
+                    .unwrap_or(count);
+                if left < count {
+                    given += count - left;
+                    names.push(
+                        target
+                            .try_get::<&Name>(|n| n.value.clone())
+                            .unwrap_or_default(),
+                    );
+                    let action = AuditAction::ReceiveItems {
+                        item: item.clone(),
+                        count: count - left,
+                        from: from.clone(),
+                    };
+                    audit::record(world, target, action);
+                }
+            }
+            if names.is_empty() {
+                return Err(tr!(lang, locale, "command.give.full"));
+            }
+            Ok(tr!(
+                lang,
+                locale,
+                "command.give",
+                given.to_string(),
+                item,
+                names.join(", ")
+            ))
         }
         "inspect" => {
             if args.is_empty() {
-                return Err("Usage: /inspect <entity>".to_string());
+                return Err(tr!(lang, locale, "command.inspect.usage"));
+            }
+            let (selector, _) = EntitySelector::parse(rest)
+                .map_err(|e| tr!(lang, locale, "command.selector.invalid", e.to_string()))?;
+            if !selector.single() {
+                return Err(tr!(lang, locale, "command.selector.not_single"));
             }
+            let target = select_entities(world, executor, &selector)
+                .into_iter()
+                .next()
+                .ok_or_else(|| tr!(lang, locale, "command.selector.none"))?;
 
-            if args[0] == "@s" {
-                let mut components = Vec::new();
+            let mut components = Vec::new();
 
-                if let Some(name) = executor.try_get::<&Name>(|n| n.value.clone()) {
-                    components.push(format!("Name: {}", name));
-                }
-                if let Some(pos) = executor.try_get::<&Position>(|p| *p) {
-                    components.push(format!(
-                        "Position: {:.2}, {:.2}, {:.2}",
-                        pos.x, pos.y, pos.z
-                    ));
-                }
-                if let Some(rot) = executor.try_get::<&Rotation>(|r| *r) {
-                    components.push(format!(
-                        "Rotation: yaw={:.1} pitch={:.1}",
-                        rot.yaw, rot.pitch
-                    ));
-                }
-                if let Some(eid) = executor.try_get::<&EntityId>(|e| e.value) {
-                    components.push(format!("EntityId: {}", eid));
+            if let Some(name) = target.try_get::<&Name>(|n| n.value.clone()) {
+                components.push(format!("Name: {}", name));
+            }
+            if let Some(pos) = target.try_get::<&Position>(|p| *p) {
+                components.push(format!(
+                    "Position: {:.2}, {:.2}, {:.2}",
+                    pos.x, pos.y, pos.z
+                ));
+            }
+            if let Some(rot) = target.try_get::<&Rotation>(|r| *r) {
+                components.push(format!(
+                    "Rotation: yaw={:.1} pitch={:.1}",
+                    rot.yaw, rot.pitch
+                ));
+            }
+            if let Some(eid) = target.try_get::<&EntityId>(|e| e.value) {
+                components.push(format!("EntityId: {}", eid));
+            }
+            if let Some(locale) = target.try_get::<&ClientLocale>(|l| l.value.clone()) {
+                components.push(format!("ClientLocale: {}", locale));
+            }
+            if target.has(InPlayState) {
+                components.push("InPlayState: true".to_string());
+            }
+
+            if components.is_empty() {
+                Ok(tr!(lang, locale, "command.inspect.none"))
+            } else {
+                // Component dumps are debug output and stay untranslated
+                Ok(tr!(lang, locale, "command.inspect.header")
+                    .append(format!("\n{}", components.join("\n"))))
+            }
+        }
+        "claim" => {
+            let usage = || tr!(lang, locale, "command.claim.usage");
+            let [x1, y1, z1, x2, y2, z2] = args else {
+                return Err(usage());
+            };
+            let owner = executor
+                .try_get::<&Uuid>(|u| u.0)
+                .ok_or_else(|| tr!(lang, locale, "command.claim.no_owner"))?;
+            let pos = executor
+                .try_get::<&Position>(|p| *p)
+                .unwrap_or(Position::SPAWN);
+            let corner = |x, y, z| {
+                Some(BlockPos::new(
+                    parse_coordinate(x, pos.x)?,
+                    parse_coordinate(y, pos.y)?,
+                    parse_coordinate(z, pos.z)?,
+                ))
+            };
+            let (Some(from), Some(to)) = (corner(x1, y1, z1), corner(x2, y2, z2)) else {
+                return Err(usage());
+            };
+
+            let claim = Claim::new(from, to, vec![owner]);
+            let (min, max) = (claim.min, claim.max);
+            protection::claim(world, claim)
+                .ok_or_else(|| tr!(lang, locale, "command.claim.overlap"))?;
+            Ok(tr!(
+                lang,
+                locale,
+                "command.claim",
+                min.x.to_string(),
+                min.y.to_string(),
+                min.z.to_string(),
+                max.x.to_string(),
+                max.y.to_string(),
+                max.z.to_string()
+            ))
+        }
+        "gamerule" => {
+            let (rule, value) = match args {
+                [rule] => (*rule, None),
+                [rule, value] => (*rule, Some(*value)),
+                _ => {
+                    let names = world
+                        .get::<&GameRules>(|rules| rules.names().collect::<Vec<_>>().join(", "));
+                    return Err(tr!(lang, locale, "command.gamerule.usage", names));
                 }
-                if executor.has(InPlayState) {
-                    components.push("InPlayState: true".to_string());
+            };
+            let result = world.get::<&mut GameRules>(|rules| match value {
+                Some(value) => rules.set_str(rule, value),
+                None => rules.get(rule).ok_or(GameRuleError::UnknownRule),
+            });
+            match result {
+                Ok(current) if value.is_none() => Ok(tr!(
+                    lang,
+                    locale,
+                    "command.gamerule.query",
+                    rule,
+                    current.to_string()
+                )),
+                Ok(new) => Ok(tr!(
+                    lang,
+                    locale,
+                    "command.gamerule.set",
+                    rule,
+                    new.to_string()
+                )),
+                Err(GameRuleError::UnknownRule) => {
+                    Err(tr!(lang, locale, "command.gamerule.unknown", rule))
                 }
+                Err(GameRuleError::InvalidValue) => Err(tr!(
+                    lang,
+                    locale,
+                    "command.gamerule.invalid_value",
+                    value.unwrap_or_default(),
+                    rule
+                )),
+            }
+        }
+        "audit" => {
+            let usage = || tr!(lang, locale, "command.audit.usage");
+            let (name, count) = match args {
+                [name] => (*name, AUDIT_ENTRIES),
+                [name, count] => (
+                    *name,
+                    count
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&count| count > 0)
+                        .ok_or_else(usage)?,
+                ),
+                _ => return Err(usage()),
+            };
+            // Offline mode UUIDs only depend on the name
+            let records = world
+                .try_get::<&AuditLog>(|log| log.read(offline_uuid(name), None, count))
+                .ok_or_else(|| tr!(lang, locale, "command.audit.unavailable"))?
+                .map_err(|e| tr!(lang, locale, "command.audit.failed", e))?;
+            if records.is_empty() {
+                return Err(tr!(lang, locale, "command.audit.none", name));
+            }
 
-                if components.is_empty() {
-                    Ok("No known components found".to_string())
-                } else {
-                    Ok(format!("Components:\n{}", components.join("\n")))
-                }
-            } else {
-                Err("Invalid entity selector. Use @s".to_string())
+            // Entries are debug output and stay untranslated
+            let lines: Vec<String> = records
+                .iter()
+                .rev()
+                .map(|record| {
+                    format!(
+                        "#{} ({} ago) {}",
+                        record.seq,
+                        audit::age(record.entry.time_ms),
+                        record.entry.action
+                    )
+                })
+                .collect();
+            Ok(tr!(lang, locale, "command.audit.header", name)
+                .append(format!("\n{}", lines.join("\n"))))
+        }
+        "stop" => {
+            world.get::<&Running>(Running::stop);
+            Ok(tr!(lang, locale, "command.stop"))
+        }
+        // Reloading only runs between ticks, so it's queued for then
+        #[cfg(feature = "reload")]
+        "reload" => {
+            let is_operator = executor
+                .try_get::<&Name>(|name| {
+                    world.get::<&crate::components::ServerConfig>(|config| {
+                        config.is_operator(&name.value)
+                    })
+                })
+                .unwrap_or(false);
+            if !is_operator {
+                return Err(tr!(lang, locale, "command.reload.permission"));
             }
+            if !matches!(rest.trim(), "skripts" | "modules" | "config") {
+                return Err(tr!(lang, locale, "command.reload.usage"));
+            }
+            world.get::<&mut ReloadQueue>(|queue| queue.push(executor.id(), rest));
+            Ok(tr!(lang, locale, "command.reload.queued", rest.trim()))
         }
-        _ => Err(format!("Unknown command: /{}", cmd)),
+        _ => Err(tr!(lang, locale, "command.unknown", cmd)),
     }
 }
 
@@ -336,16 +887,29 @@ pub fn handle_commands(world: &WorldRef<'_>, executor: EntityView<'_>, buffer: &
 
         info!("{} executed command: /{}", executor_name, command_str);
 
-        if let Some((cmd, args)) = parse_command(&command_str) {
-            let response = match execute_command(cmd, &args, executor, world) {
-                Ok(msg) => msg,
-                Err(err) => err,
-            };
+        if let Some(response) = run_command(world, executor, &command_str) {
             send_chat_message(buffer, &response);
         }
     }
 }
 
+/// Run a command line (without the leading `/`) as `executor`, returning
+/// its response in the executor's locale. Returns `None` for a blank line.
+pub fn run_command(world: &WorldRef<'_>, executor: EntityView<'_>, input: &str) -> Option<Text> {
+    let (cmd, rest) = parse_command(input)?;
+    let command = input.trim().to_string();
+    audit::record(world, executor, AuditAction::Command { command });
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    let locale = locale_of(executor);
+    let response = world.get::<&Translations>(|t| {
+        match execute_command(cmd, rest, &args, executor, world, &t.catalog, &locale) {
+            Ok(msg) => msg,
+            Err(err) => err,
+        }
+    });
+    Some(response)
+}
+
 /// Send command tree to new players
 pub fn send_commands_to_player(buffer: &mut PacketBuffer) {
     match build_commands_packet() {
@@ -1,18 +1,15 @@
 //! Configuration phase system
 
 use flecs_ecs::prelude::*;
-use mc_protocol::Decode;
-use tracing::debug;
+use tracing::{debug, info, warn};
 
-use crate::components::{ConnectionState, NeedsSpawnChunks, PacketBuffer, ProtocolState};
-use crate::protocol::encode_packet;
-use crate::registry::{
-    create_biome_registry, create_cat_variant_registry, create_chicken_variant_registry,
-    create_cow_variant_registry, create_damage_type_registry, create_dimension_type_registry,
-    create_frog_variant_registry, create_painting_variant_registry, create_pig_variant_registry,
-    create_wolf_sound_variant_registry, create_wolf_variant_registry,
-    create_zombie_nautilus_variant_registry,
+use crate::components::{
+    ClientBrand, ClientLocale, ConnectionState, NeedsSpawnChunks, PacketBuffer, ProtocolState,
 };
+use crate::protocol::{
+    BRAND_CHANNEL, encode_packet, parse_brand, parse_client_locale, parse_custom_payload,
+};
+use crate::protocol_state::{drop_unexpected, next_packet, transition};
 
 /// Handle configuration packets for a single entity
 pub fn handle_configuration(
@@ -24,24 +21,38 @@ pub fn handle_configuration(
         return;
     }
 
-    while let Some((packet_id, data)) = buffer.pop_incoming() {
+    while let Some((packet_id, data)) = next_packet(entity, buffer, *state) {
         match packet_id {
             0 => {
                 // Client Information
-                debug!("Got Client Information");
+                if let Ok(locale) = parse_client_locale(&data) {
+                    debug!("Client locale: {}", locale);
+                    entity.set(ClientLocale { value: locale });
+                }
             }
             2 => {
                 // Custom Payload (plugin message)
-                let mut cursor = std::io::Cursor::new(&data[..]);
-                if let Ok(channel) = String::decode(&mut cursor) {
+                if let Ok((channel, payload)) = parse_custom_payload(&data) {
                     debug!("Plugin message on channel: {}", channel);
+                    if channel == BRAND_CHANNEL {
+                        if let Ok(brand) = parse_brand(payload) {
+                            debug!("Client brand: {}", brand);
+                            entity.set(ClientBrand { value: brand });
+                        }
+                    }
                 }
             }
             3 => {
                 // Finish Configuration (Acknowledge)
-                tracing::info!("Client acknowledged configuration, transitioning to Play");
-                state.0 = ConnectionState::Play;
+                if let Err(err) = transition(entity, state, ConnectionState::Play) {
+                    warn!("Configuration: {err}");
+                    continue;
+                }
+                info!("Client acknowledged configuration, transitioning to Play");
                 entity.add(NeedsSpawnChunks);
+                // The rest should be play packets, left for the play systems
+                drop_unexpected(entity, buffer, *state);
+                break;
             }
             7 => {
                 // Select Known Packs response
@@ -62,51 +73,14 @@ pub fn handle_configuration(
     }
 }
 
-fn send_registry(buffer: &mut PacketBuffer, data: Vec<u8>) {
-    let mut cursor = std::io::Cursor::new(&data);
-    if let Ok(name) = <String as Decode>::decode(&mut cursor) {
-        debug!("Sending registry: {} ({} bytes)", name, data.len());
-    }
-    let packet = encode_packet(7, &data);
-    buffer.push_outgoing(packet);
-}
-
 fn send_registry_data(buffer: &mut PacketBuffer) {
-    if let Ok(data) = create_dimension_type_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_biome_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_damage_type_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_cat_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_chicken_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_cow_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_frog_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_pig_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_wolf_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_wolf_sound_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_zombie_nautilus_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_painting_variant_registry() {
-        send_registry(buffer, data);
+    for registry in mc_data::all_registries() {
+        debug!(
+            "Sending registry: {} ({} bytes)",
+            registry.name,
+            registry.payload.len()
+        );
+        buffer.push_outgoing(encode_packet(7, registry.payload));
     }
 
     debug!("Sent all registry data");
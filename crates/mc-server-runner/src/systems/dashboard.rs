@@ -9,15 +9,33 @@ use std::time::Instant;
 use flecs_ecs::prelude::*;
 use flecs_history::HistoryTracker;
 
+use crate::audit::AuditLog;
+use crate::autosave::SaveStats;
+use crate::buffer_pool;
 use crate::components::{
-    ChunkPos, Connection, ConnectionId, EntityId, GameMode, Player, Position, ProtocolState,
-    Rotation, Uuid,
+    ChunkPos, Connection, ConnectionId, EntityId, GameMode, Latency, Player, Position,
+    ProtocolState, Rotation, TpsTracker, Uuid,
 };
 use crate::dashboard::{
     ChunkInfo, ComponentValue, DashboardChannels, DashboardRequest, EntityDetails, EntitySummary,
-    HistoryEntryInfo, HistoryResponse, ListEntitiesResponse, PlayerInfo, PositionInfo,
-    QueryResponse, QueryResultRow, WorldInfo,
+    HistoryEntryInfo, HistoryResponse, LatencyMetrics, ListEntitiesResponse, MetricsInfo,
+    PlayerInfo, PositionInfo, QueryResponse, QueryResultRow, QuerySpec, SavedQueryResponse,
+    SystemInfo, WorldInfo,
 };
+use crate::entity_ids::EntityIdAllocator;
+use crate::map;
+use crate::replay::Replay;
+use crate::saved_queries::SavedQueries;
+use crate::sniffer::PacketSniffer;
+
+/// Error for saved query requests when the world has no directory to keep them in.
+const NO_SAVED_QUERIES: &str = "Saved queries need a world directory";
+
+/// Error for audit log requests when nothing is being recorded.
+const NO_AUDIT_LOG: &str = "The audit log needs a world directory and isn't kept while replaying";
+
+/// Error for replay requests when the server isn't replaying a journal.
+const NOT_REPLAYING: &str = "The server isn't replaying a journal; start it with --replay";
 
 /// Get entity name, returning None if empty.
 fn get_entity_name(entity: &EntityView<'_>) -> Option<String> {
@@ -37,6 +55,22 @@ fn user_entities_query(world: &World) -> Query<()> {
         .build()
 }
 
+/// Names of a system's phase and every phase it depends on, nearest first.
+fn system_phases(system: &EntityView<'_>) -> Vec<String> {
+    let mut phases: Vec<String> = Vec::new();
+    let mut current = system.target(flecs::DependsOn, 0);
+    while let Some(phase) = current {
+        let name = phase.name();
+        // DependsOn cycles are a registration bug; don't loop on them
+        if phases.contains(&name) {
+            break;
+        }
+        phases.push(name);
+        current = phase.target(flecs::DependsOn, 0);
+    }
+    phases
+}
+
 /// Helper to create a ComponentValue with full details.
 fn make_component_value(name: &str, value: serde_json::Value) -> ComponentValue {
     ComponentValue {
@@ -198,6 +232,122 @@ fn chunk_color(x: i32, z: i32) -> &'static str {
     }
 }
 
+/// Run a dashboard query.
+fn run_query(world: &World, spec: &QuerySpec) -> QueryResponse {
+    let start = Instant::now();
+    let limit = spec.limit.unwrap_or(100);
+    let offset = spec.offset.unwrap_or(0);
+
+    let mut entities = Vec::new();
+    let mut total = 0;
+    let mut skipped = 0;
+
+    // For now, we support filtering by "Position" component for the map
+    let has_position_filter = spec.with.iter().any(|c| c == "Position");
+
+    if has_position_filter {
+        world
+            .query::<&Position>()
+            .build()
+            .each_entity(|entity, pos| {
+                total += 1;
+
+                if skipped < offset {
+                    skipped += 1;
+                    return;
+                }
+                if entities.len() >= limit {
+                    return;
+                }
+
+                let name = get_entity_name(&entity);
+                let mut components = HashMap::new();
+                components.insert(
+                    "Position".to_string(),
+                    serde_json::json!({"x": pos.x, "y": pos.y, "z": pos.z}),
+                );
+
+                // Add other requested components
+                let all_comps = get_entity_components_map(&entity);
+                for comp_name in &spec.with {
+                    if let Some(val) = all_comps.get(comp_name) {
+                        components.insert(comp_name.clone(), val.clone());
+                    }
+                }
+                for comp_name in &spec.optional {
+                    if let Some(val) = all_comps.get(comp_name) {
+                        components.insert(comp_name.clone(), val.clone());
+                    }
+                }
+
+                entities.push(QueryResultRow {
+                    entity: entity.id().0,
+                    name,
+                    components,
+                });
+            });
+    } else {
+        // Generic query - return all user entities
+        user_entities_query(world).each_entity(|entity, _| {
+            total += 1;
+
+            if skipped < offset {
+                skipped += 1;
+                return;
+            }
+            if entities.len() >= limit {
+                return;
+            }
+
+            let name = get_entity_name(&entity);
+            let all_comps = get_entity_components_map(&entity);
+
+            // Filter by required components
+            let has_all_required = spec.with.iter().all(|c| all_comps.contains_key(c));
+            if !has_all_required {
+                return;
+            }
+
+            // Filter by without
+            let has_excluded = spec.without.iter().any(|c| all_comps.contains_key(c));
+            if has_excluded {
+                return;
+            }
+
+            let mut components = HashMap::new();
+            for comp_name in &spec.with {
+                if let Some(val) = all_comps.get(comp_name) {
+                    components.insert(comp_name.clone(), val.clone());
+                }
+            }
+            for comp_name in &spec.optional {
+                if let Some(val) = all_comps.get(comp_name) {
+                    components.insert(comp_name.clone(), val.clone());
+                }
+            }
+
+            // If no specific components requested, return all
+            if spec.with.is_empty() && spec.optional.is_empty() {
+                components = all_comps;
+            }
+
+            entities.push(QueryResultRow {
+                entity: entity.id().0,
+                name,
+                components,
+            });
+        });
+    }
+
+    let execution_time_us = start.elapsed().as_micros() as u64;
+
+    QueryResponse {
+        entities,
+        total,
+        execution_time_us,
+    }
+}
+
 /// Process all pending dashboard requests.
 pub fn system_process_dashboard(
     world: &World,
@@ -303,6 +453,7 @@ pub fn system_process_dashboard(
                             z: p.z,
                         });
                         let game_mode = entity.try_get::<&GameMode>(|g| format!("{:?}", g));
+                        let latency_ms = entity.try_get::<&Latency>(|l| l.ms);
 
                         players.push(PlayerInfo {
                             entity_id: entity.id().0,
@@ -310,6 +461,7 @@ pub fn system_process_dashboard(
                             uuid,
                             position,
                             game_mode,
+                            latency_ms,
                         });
                     });
 
@@ -331,6 +483,29 @@ pub fn system_process_dashboard(
                 let _ = response.send(chunks);
             }
 
+            DashboardRequest::ListSystems { response } => {
+                let mut systems = Vec::new();
+
+                world
+                    .query::<()>()
+                    .with(flecs::system::System::id())
+                    .build()
+                    .each_entity(|entity, ()| {
+                        systems.push(SystemInfo {
+                            id: entity.id().0,
+                            name: entity.name(),
+                            module: entity.parent().and_then(|p| get_entity_name(&p)),
+                            phases: system_phases(&entity),
+                        });
+                    });
+
+                let _ = response.send(systems);
+            }
+
+            DashboardRequest::GetMapTiles { min, max, response } => {
+                let _ = response.send(map::tiles(&world.world(), min, max));
+            }
+
             DashboardRequest::GetEntityHistory {
                 id,
                 limit,
@@ -361,7 +536,11 @@ pub fn system_process_dashboard(
                             component: format!("component_{}", e.component_id),
                             old_value: None,
                             new_value,
-                            source: "system".to_string(),
+                            source: e
+                                .transaction
+                                .and_then(|id| history.get_transaction(world, id))
+                                .map_or_else(|| "system".to_string(), |t| t.label),
+                            transaction: e.transaction,
                         }
                     })
                     .collect();
@@ -373,118 +552,120 @@ pub fn system_process_dashboard(
             }
 
             DashboardRequest::Query { spec, response } => {
-                let start = Instant::now();
-                let limit = spec.limit.unwrap_or(100);
-                let offset = spec.offset.unwrap_or(0);
-
-                let mut entities = Vec::new();
-                let mut total = 0;
-                let mut skipped = 0;
+                let _ = response.send(run_query(world, &spec));
+            }
 
-                // For now, we support filtering by "Position" component for the map
-                let has_position_filter = spec.with.iter().any(|c| c == "Position");
-
-                if has_position_filter {
-                    world
-                        .query::<&Position>()
-                        .build()
-                        .each_entity(|entity, pos| {
-                            total += 1;
-
-                            if skipped < offset {
-                                skipped += 1;
-                                return;
-                            }
-                            if entities.len() >= limit {
-                                return;
-                            }
-
-                            let name = get_entity_name(&entity);
-                            let mut components = HashMap::new();
-                            components.insert(
-                                "Position".to_string(),
-                                serde_json::json!({"x": pos.x, "y": pos.y, "z": pos.z}),
-                            );
-
-                            // Add other requested components
-                            let all_comps = get_entity_components_map(&entity);
-                            for comp_name in &spec.with {
-                                if let Some(val) = all_comps.get(comp_name) {
-                                    components.insert(comp_name.clone(), val.clone());
-                                }
-                            }
-                            for comp_name in &spec.optional {
-                                if let Some(val) = all_comps.get(comp_name) {
-                                    components.insert(comp_name.clone(), val.clone());
-                                }
-                            }
-
-                            entities.push(QueryResultRow {
-                                entity: entity.id().0,
-                                name,
-                                components,
-                            });
-                        });
-                } else {
-                    // Generic query - return all user entities
-                    user_entities_query(world).each_entity(|entity, _| {
-                        total += 1;
-
-                        if skipped < offset {
-                            skipped += 1;
-                            return;
-                        }
-                        if entities.len() >= limit {
-                            return;
+            DashboardRequest::GetMetrics { response } => {
+                let tps = world.get::<&TpsTracker>(|t| *t);
+                let mut players = 0;
+                let mut latencies = Vec::new();
+                world
+                    .query::<&Latency>()
+                    .with(Player)
+                    .build()
+                    .each(|latency| {
+                        players += 1;
+                        if latency.samples > 0 {
+                            latencies.push(latency.ms);
                         }
+                    });
 
-                        let name = get_entity_name(&entity);
-                        let all_comps = get_entity_components_map(&entity);
+                let _ = response.send(MetricsInfo {
+                    tps_5s: tps.tps_5s,
+                    tps_15s: tps.tps_15s,
+                    tps_1m: tps.tps_1m,
+                    ticks_behind: tps.behind,
+                    ticks_caught_up: tps.caught_up,
+                    ticks_skipped: tps.skipped,
+                    ticks_dropped: tps.dropped,
+                    mspt: tps.mspt,
+                    players,
+                    latency: LatencyMetrics::from_samples(&latencies),
+                    egress_buffers: buffer_pool::stats(),
+                    entity_ids: world.get::<&EntityIdAllocator>(EntityIdAllocator::live),
+                    saves: world.get::<&SaveStats>(|stats| *stats),
+                });
+            }
 
-                        // Filter by required components
-                        let has_all_required = spec.with.iter().all(|c| all_comps.contains_key(c));
-                        if !has_all_required {
-                            return;
-                        }
+            DashboardRequest::ListPackets {
+                connection,
+                limit,
+                response,
+            } => {
+                let packets = world
+                    .try_get::<&PacketSniffer>(|sniffer| sniffer.recent(connection, limit))
+                    .unwrap_or_default();
+                let _ = response.send(packets);
+            }
 
-                        // Filter by without
-                        let has_excluded = spec.without.iter().any(|c| all_comps.contains_key(c));
-                        if has_excluded {
-                            return;
-                        }
+            DashboardRequest::ListSavedQueries { response } => {
+                let queries = world
+                    .try_get::<&SavedQueries>(SavedQueries::list)
+                    .unwrap_or_default();
+                let _ = response.send(queries);
+            }
 
-                        let mut components = HashMap::new();
-                        for comp_name in &spec.with {
-                            if let Some(val) = all_comps.get(comp_name) {
-                                components.insert(comp_name.clone(), val.clone());
-                            }
-                        }
-                        for comp_name in &spec.optional {
-                            if let Some(val) = all_comps.get(comp_name) {
-                                components.insert(comp_name.clone(), val.clone());
-                            }
-                        }
+            DashboardRequest::SaveQuery { query, response } => {
+                let result = world
+                    .try_get::<&mut SavedQueries>(|saved| saved.save(query))
+                    .unwrap_or_else(|| Err(NO_SAVED_QUERIES.to_string()));
+                let _ = response.send(result.into());
+            }
 
-                        // If no specific components requested, return all
-                        if spec.with.is_empty() && spec.optional.is_empty() {
-                            components = all_comps;
+            DashboardRequest::DeleteSavedQuery { name, response } => {
+                let result = world
+                    .try_get::<&mut SavedQueries>(|saved| saved.delete(&name))
+                    .unwrap_or_else(|| Err(NO_SAVED_QUERIES.to_string()))
+                    .and_then(|existed| {
+                        if existed {
+                            Ok(())
+                        } else {
+                            Err(format!("No saved query named {name}"))
                         }
-
-                        entities.push(QueryResultRow {
-                            entity: entity.id().0,
-                            name,
-                            components,
-                        });
                     });
-                }
-
-                let execution_time_us = start.elapsed().as_micros() as u64;
+                let _ = response.send(result.into());
+            }
 
-                let _ = response.send(QueryResponse {
-                    entities,
-                    total,
-                    execution_time_us,
-                });
+            DashboardRequest::RunSavedQuery {
+                name,
+                limit,
+                offset,
+                response,
+            } => {
+                let result = world
+                    .try_get::<&SavedQueries>(|saved| saved.spec(&name))
+                    .unwrap_or_else(|| Err(NO_SAVED_QUERIES.to_string()))
+                    .map(|spec| {
+                        let spec = QuerySpec {
+                            limit,
+                            offset,
+                            ..spec
+                        };
+                        run_query(world, &spec)
+                    });
+                let _ = response.send(result);
+            }
+            DashboardRequest::Replay { control, response } => {
+                let result = world
+                    .try_get::<&mut Replay>(|replay| {
+                        if let Some(control) = control {
+                            replay.control(&world.world(), control)?;
+                        }
+                        Ok(replay.status())
+                    })
+                    .unwrap_or_else(|| Err(NOT_REPLAYING.to_string()));
+                let _ = response.send(result);
+            }
+            DashboardRequest::GetAuditLog {
+                uuid,
+                before,
+                limit,
+                response,
+            } => {
+                let result = world
+                    .try_get::<&AuditLog>(|log| log.read(uuid, before, limit))
+                    .unwrap_or_else(|| Err(NO_AUDIT_LOG.to_string()));
+                let _ = response.send(result);
             }
         }
     }
@@ -1,14 +1,19 @@
 //! Handshake and status systems
 
 use flecs_ecs::prelude::*;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::components::{ConnectionState, PacketBuffer, ProtocolState, ServerConfig};
-use crate::protocol::{encode_packet, parse_handshake, send_status_response};
+use crate::components::{
+    ConnectionIndex, ConnectionState, Name, PacketBuffer, Player, ProtocolState, ServerConfig, Uuid,
+};
+use crate::protocol::{
+    OnlinePlayers, STATUS_SAMPLE_SIZE, encode_packet, parse_handshake, send_status_response,
+};
+use crate::protocol_state::{next_packet, transition};
 
 /// Handle handshake for a single entity
 pub fn handle_handshake(
-    _entity: EntityView<'_>,
+    entity: EntityView<'_>,
     buffer: &mut PacketBuffer,
     state: &mut ProtocolState,
 ) {
@@ -18,7 +23,7 @@ pub fn handle_handshake(
 
     debug!("HandleHandshake: checking for packets");
 
-    if let Some((packet_id, data)) = buffer.pop_incoming() {
+    if let Some((packet_id, data)) = next_packet(entity, buffer, *state) {
         debug!("HandleHandshake: got packet_id={}", packet_id);
         if packet_id == 0 {
             // Handshake packet
@@ -32,29 +37,38 @@ pub fn handle_handshake(
                     1 => ConnectionState::Status,
                     2 => ConnectionState::Login,
                     _ => {
-                        tracing::warn!("Unknown next state: {}", next_state);
+                        warn!("Unknown next state: {}", next_state);
                         return;
                     }
                 };
 
-                state.0 = new_state;
+                if let Err(err) = transition(entity, state, new_state) {
+                    warn!("Handshake: {err}");
+                }
             }
         }
     }
 }
 
 /// Handle status request packets
-pub fn handle_status(buffer: &mut PacketBuffer, state: &ProtocolState, config: &ServerConfig) {
+pub fn handle_status(
+    world: &WorldRef<'_>,
+    entity: EntityView<'_>,
+    buffer: &mut PacketBuffer,
+    state: &ProtocolState,
+    config: &ServerConfig,
+) {
     if state.0 != ConnectionState::Status {
         return;
     }
 
-    while let Some((packet_id, data)) = buffer.pop_incoming() {
+    while let Some((packet_id, data)) = next_packet(entity, buffer, *state) {
         match packet_id {
             0 => {
                 // Status Request
                 info!("Status request");
-                send_status_response(buffer, config.max_players, &config.motd);
+                let players = online_players(world, config);
+                send_status_response(buffer, config.max_players, &config.motd, &players);
             }
             1 => {
                 // Ping - echo back the same data
@@ -65,3 +79,34 @@ pub fn handle_status(buffer: &mut PacketBuffer, state: &ProtocolState, config: &
         }
     }
 }
+
+/// Collect the online count and player sample for a status response.
+///
+/// Only connections that finished login (have `Player`) count as online.
+fn online_players(world: &WorldRef<'_>, config: &ServerConfig) -> OnlinePlayers {
+    let online = world.get::<&ConnectionIndex>(|index| {
+        index
+            .map
+            .values()
+            .filter(|&&entity| world.entity_from_id(entity).has(Player))
+            .count()
+    });
+
+    let mut sample = Vec::new();
+    if !config.hide_player_sample {
+        world
+            .query::<(&Name, &Uuid)>()
+            .with(Player)
+            .build()
+            .each(|(name, uuid)| {
+                if sample.len() < STATUS_SAMPLE_SIZE {
+                    sample.push((name.value.clone(), uuid.0));
+                }
+            });
+    }
+
+    OnlinePlayers {
+        online: i32::try_from(online).unwrap_or(i32::MAX),
+        sample,
+    }
+}
@@ -4,12 +4,20 @@
 
 use flecs_ecs::prelude::*;
 use flecs_history::prelude::*;
+use module_redstone::{Redstone, SignalLevel};
 
+use crate::autosave::SaveStats;
+use crate::block_tick::ScheduledTicks;
+use crate::chunk::ChunkCache;
 use crate::components::{
-    ChunkPos, ChunkPosition, ConnectionId, EntityId, GameMode, Name, Position, ProtocolState,
-    Rotation, ServerConfig, TpsTracker, Uuid, WorldTime,
+    BlockPos, ChunkPos, ChunkPosition, ClientBrand, ClientLocale, ConnectionId, EntityId, GameMode,
+    HudText, Latency, Name, Position, ProtocolState, Rotation, ServerConfig, TpsTracker, Uuid,
+    WorldTime,
 };
 
+/// Ticks between history compactions (one minute at 20 TPS)
+const COMPACT_INTERVAL: u64 = 20 * 60;
+
 /// Initialize history tracking for all serializable components.
 ///
 /// This should be called after the world is created and before any entities are spawned.
@@ -22,10 +30,28 @@ pub fn init_history_tracking(world: &World) -> HistoryTracker {
     world.component::<Uuid>().serializable::<Uuid>();
     world.component::<EntityId>().serializable::<EntityId>();
     world.component::<GameMode>().serializable::<GameMode>();
+    world.component::<Latency>().serializable::<Latency>();
+    world.component::<HudText>().serializable::<HudText>();
+    world
+        .component::<ClientBrand>()
+        .serializable::<ClientBrand>();
+    world
+        .component::<ClientLocale>()
+        .serializable::<ClientLocale>();
     world
         .component::<ChunkPosition>()
         .serializable::<ChunkPosition>();
     world.component::<ChunkPos>().serializable::<ChunkPos>();
+    world.component::<ChunkCache>().serializable::<ChunkCache>();
+    world.component::<SaveStats>().serializable::<SaveStats>();
+    world
+        .component::<ScheduledTicks>()
+        .serializable::<ScheduledTicks>();
+    world.component::<BlockPos>().serializable::<BlockPos>();
+    world.component::<Redstone>().serializable::<Redstone>();
+    world
+        .component::<SignalLevel>()
+        .serializable::<SignalLevel>();
     world
         .component::<ConnectionId>()
         .serializable::<ConnectionId>();
@@ -42,13 +68,31 @@ pub fn init_history_tracking(world: &World) -> HistoryTracker {
     let history = HistoryTracker::new(world);
 
     // Enable tracking for important components
-    // These will record changes automatically via OnSet hooks
-    history.track_component::<Position>(world);
-    history.track_component::<Rotation>(world);
-    history.track_component::<Name>(world);
-    history.track_component::<Uuid>(world);
-    history.track_component::<EntityId>(world);
-    history.track_component::<GameMode>(world);
+    // These will record changes automatically via OnSet hooks.
+    // Position and Rotation are set on every movement packet, so they are
+    // recorded at most every 5 ticks (4 entries per second at 20 TPS), with
+    // the last value before a player stops moving recorded once it's due.
+    history.track_component_with::<Position>(world, SamplingPolicy::Throttled(5));
+    history.track_component_with::<Rotation>(world, SamplingPolicy::Throttled(5));
+    history.track_component_with::<Name>(world, SamplingPolicy::OnChange);
+    history.track_component_with::<Uuid>(world, SamplingPolicy::OnChange);
+    history.track_component_with::<EntityId>(world, SamplingPolicy::OnChange);
+    history.track_component_with::<GameMode>(world, SamplingPolicy::OnChange);
+
+    // A player standing still keeps sending the same position and rotation
+    history.compact_on_equal::<Position>(world);
+    history.compact_on_equal::<Rotation>(world);
 
     history
 }
+
+/// Drop repeated history entries once every [`COMPACT_INTERVAL`] ticks
+pub fn compact_periodically(world: &World, history: &HistoryTracker) {
+    if !history.current_tick(world).is_multiple_of(COMPACT_INTERVAL) {
+        return;
+    }
+    let dropped = history.compact(world);
+    if dropped > 0 {
+        tracing::debug!("Compacted {dropped} repeated history entries");
+    }
+}
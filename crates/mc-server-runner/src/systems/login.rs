@@ -1,26 +1,38 @@
 //! Login system
 
 use flecs_ecs::prelude::*;
-use tracing::{debug, info};
+use mc_text::Text;
+use tracing::{debug, error, info, warn};
 
+use crate::audit::{self, AuditAction};
+use crate::chunk::highest_block_at;
 use crate::components::{
-    ChunkPosition, ConnectionState, EntityId, EntityIdCounter, GameMode, Name, PacketBuffer,
-    Player, Position, ProtocolState, Rotation, Uuid,
+    ChunkPosition, ConnectionState, EntityId, GameMode, HudText, Name, PacketBuffer, Player,
+    Position, ProtocolState, RemoteAddr, Rotation, Uuid,
 };
-use crate::protocol::{offline_uuid, parse_login_start, send_known_packs, send_login_success};
+use crate::entity_ids::EntityIdAllocator;
+use crate::inventory::Inventory;
+use crate::protocol::{
+    offline_uuid, parse_login_start, send_disconnect, send_known_packs, send_login_success,
+};
+use crate::protocol_state::{next_packet, transition};
+use crate::{journal, replay, stats};
+
+/// Disconnect reason when no entity ID is free, translated by the client
+const SERVER_FULL: &str = "multiplayer.disconnect.server_full";
 
 /// Handle login packets for a single entity
 pub fn handle_login(
     entity: EntityView<'_>,
     buffer: &mut PacketBuffer,
     state: &mut ProtocolState,
-    entity_counter: &EntityIdCounter,
+    entity_ids: &EntityIdAllocator,
 ) {
     if state.0 != ConnectionState::Login {
         return;
     }
 
-    while let Some((packet_id, data)) = buffer.pop_incoming() {
+    while let Some((packet_id, data)) = next_packet(entity, buffer, *state) {
         debug!("HandleLogin: got packet_id={}", packet_id);
         match packet_id {
             0 => {
@@ -29,22 +41,45 @@ pub fn handle_login(
                     let player_uuid = offline_uuid(&name);
                     info!("Login from: {} (uuid: {:032x})", &name, player_uuid);
 
-                    let new_entity_id = entity_counter.next();
+                    let Some(new_entity_id) = entity_ids.allocate() else {
+                        error!("No free entity IDs, rejecting login from {}", &name);
+                        send_disconnect(buffer, state.0, &Text::translate(SERVER_FULL));
+                        return;
+                    };
 
-                    // Add player components
+                    // Add player components. The spawn defaults go first:
+                    // setting the Uuid restores saved player data over them
+                    // (see player_data), and a journal recovery wins over both.
                     entity
                         .add(Player)
                         .set(Name {
                             value: name.clone(),
                         })
+                        .set(spawn_position(&entity.world()))
+                        .set(Rotation::new(0.0, 0.0))
+                        .set(GameMode::CREATIVE)
                         .set(Uuid(player_uuid))
                         .set(EntityId {
                             value: new_entity_id,
                         })
-                        .set(Position::SPAWN)
-                        .set(Rotation::new(0.0, 0.0))
                         .set(ChunkPosition::new(0, 0))
-                        .set(GameMode::CREATIVE);
+                        .set(HudText::default())
+                        .set(Inventory::default())
+                        .set(stats::load(&entity.world(), player_uuid));
+                    if let Some(record) =
+                        journal::take_recovered_player(&entity.world(), player_uuid)
+                    {
+                        entity
+                            .set(record.position)
+                            .set(record.rotation)
+                            .set(record.game_mode);
+                    }
+                    if replay::is_replaying(&entity.world()) {
+                        entity.set(GameMode::SPECTATOR);
+                    }
+
+                    let addr = entity.try_get::<&RemoteAddr>(|a| a.0.to_string());
+                    audit::record(&entity.world(), entity, AuditAction::Login { addr });
 
                     send_login_success(buffer, player_uuid, &name);
                     info!("Sent Login Success, waiting for Login Acknowledged");
@@ -52,10 +87,15 @@ pub fn handle_login(
             }
             3 => {
                 // Login Acknowledged
+                if let Err(err) = transition(entity, state, ConnectionState::Configuration) {
+                    warn!("Login: {err}");
+                    continue;
+                }
                 info!("Login Acknowledged, transitioning to Configuration");
-                state.0 = ConnectionState::Configuration;
                 send_known_packs(buffer);
                 debug!("Sent Known Packs");
+                // The rest are configuration packets
+                break;
             }
             _ => {
                 debug!("Unknown login packet: {}", packet_id);
@@ -63,3 +103,10 @@ pub fn handle_login(
         }
     }
 }
+
+/// Default spawn column, on top of the terrain if its chunk is loaded
+pub fn spawn_position(world: &WorldRef<'_>) -> Position {
+    let spawn = Position::SPAWN;
+    highest_block_at(world, spawn.x.floor() as i32, spawn.z.floor() as i32)
+        .map_or(spawn, |y| Position::new(spawn.x, f64::from(y + 1), spawn.z))
+}
@@ -1,16 +1,22 @@
 //! Network systems - packet ingress/egress
 
 use flecs_ecs::prelude::*;
+use tracing::warn;
 
+use crate::audit::{self, AuditAction};
 use crate::components::{
-    Connection, ConnectionId, ConnectionIndex, DisconnectIngress, NetworkEgress, NetworkIngress,
-    OutgoingPacket, PacketBuffer, PendingPackets, ProtocolState,
+    Connection, ConnectionId, ConnectionIndex, ConnectionState, DisconnectIngress, InPlayState,
+    IncomingPacket, Latency, NetworkEgress, NetworkIngress, OutgoingPacket, PacketBuffer,
+    PendingPackets, ProtocolState, RemoteAddr, Uuid, WorldTime,
 };
+use crate::protocol::send_player_info_remove;
+use crate::sniffer::{PacketDirection, PacketSniffer};
 
 /// System: Receive packets from network thread and route to connection entities
 pub fn system_network_ingress(world: &World) {
     // Get singletons
     let ingress_rx = world.get::<&NetworkIngress>(|i| i.rx.clone());
+    let tick = world.get::<&WorldTime>(|t| t.world_age);
 
     world.get::<(&mut PendingPackets, &mut ConnectionIndex)>(|(pending, conn_index)| {
         // Process pending packets from last tick
@@ -27,6 +33,7 @@ pub fn system_network_ingress(world: &World) {
         // Drain all packets from the channel
         while let Ok(packet) = ingress_rx.try_recv() {
             let conn_id = packet.connection_id;
+            record_inbound(world, conn_index, tick, &packet);
 
             if !conn_index.map.contains_key(&conn_id) {
                 // New connection - create entity
@@ -35,8 +42,10 @@ pub fn system_network_ingress(world: &World) {
                     .entity_named(&name)
                     .add(Connection)
                     .set(ConnectionId(conn_id))
+                    .set(RemoteAddr(packet.addr))
                     .set(PacketBuffer::new())
                     .set(ProtocolState::default())
+                    .set(Latency::default())
                     .id();
                 conn_index.map.insert(conn_id, entity);
 
@@ -49,6 +58,17 @@ pub fn system_network_ingress(world: &World) {
                 let entity = conn_index.map[&conn_id];
                 let entity_view = world.entity_from_id(entity);
                 let packet_id = packet.packet_id;
+                // A connection in play stays there, so its packets can be
+                // checked on arrival; earlier states check them as they're
+                // handled, since handling one may change the state for the next
+                let state = entity_view.try_get::<&ProtocolState>(|state| *state);
+                if let Some(Err(err)) = state
+                    .filter(|state| state.0 == ConnectionState::Play)
+                    .map(|state| state.check_packet(packet_id))
+                {
+                    warn!("{}: dropping packet: {err}", entity_view.name());
+                    continue;
+                }
                 let data = packet.data;
                 let data_clone = data.clone();
                 let routed = entity_view.try_get::<&mut PacketBuffer>(|buffer| {
@@ -62,26 +82,88 @@ pub fn system_network_ingress(world: &World) {
     });
 }
 
+/// Mirror an inbound packet to the sniffer, if enabled
+fn record_inbound(world: &World, conn_index: &ConnectionIndex, tick: i64, packet: &IncomingPacket) {
+    world.try_get::<&mut PacketSniffer>(|sniffer| {
+        // New connections have no entity yet and are still handshaking
+        let state = conn_index
+            .map
+            .get(&packet.connection_id)
+            .and_then(|&entity| {
+                world
+                    .entity_from_id(entity)
+                    .try_get::<&ProtocolState>(|s| s.0)
+            })
+            .unwrap_or_default();
+        sniffer.record(
+            tick,
+            packet.connection_id,
+            PacketDirection::Inbound,
+            state,
+            packet.packet_id,
+            &packet.data,
+        );
+    });
+}
+
+/// Mirror a connection's outgoing packets to the sniffer, if enabled
+pub fn record_outgoing(
+    world: &World,
+    buffer: &PacketBuffer,
+    conn_id: &ConnectionId,
+    state: &ProtocolState,
+) {
+    world.try_get::<&mut PacketSniffer>(|sniffer| {
+        let tick = world.get::<&WorldTime>(|t| t.world_age);
+        for frame in &buffer.outgoing {
+            sniffer.record_outgoing(tick, conn_id.0, state.0, frame);
+        }
+    });
+}
+
 /// System: Handle disconnect events
 pub fn system_handle_disconnects(world: &World) {
     let disconnect_rx = world.get::<&DisconnectIngress>(|d| d.rx.clone());
 
+    let mut departed = Vec::new();
     world.get::<&mut ConnectionIndex>(|conn_index| {
         while let Ok(event) = disconnect_rx.try_recv() {
             let conn_id = event.connection_id;
             if let Some(entity) = conn_index.map.remove(&conn_id) {
-                world.entity_from_id(entity).destruct();
+                let entity = world.entity_from_id(entity);
+                audit::record(&entity.world(), entity, AuditAction::Logout);
+                if entity.has(InPlayState)
+                    && let Some(uuid) = entity.try_get::<&Uuid>(|u| u.0)
+                {
+                    departed.push(uuid);
+                }
+                entity.destruct();
             }
         }
     });
+
+    // Drop departed players from everyone's tab list
+    if !departed.is_empty() {
+        world
+            .query::<&mut PacketBuffer>()
+            .with(InPlayState)
+            .build()
+            .each(|buffer| send_player_info_remove(buffer, &departed));
+    }
 }
 
 /// Handle egress for a single connection
+///
+/// The tick's packets go to the network task as one batch, without being
+/// copied, and the connection's writer hands them to the kernel with
+/// vectored writes rather than one write per packet.
 pub fn handle_egress(buffer: &mut PacketBuffer, conn_id: &ConnectionId, egress: &NetworkEgress) {
-    while let Some(data) = buffer.pop_outgoing() {
-        let _ = egress.tx.send(OutgoingPacket {
-            connection_id: conn_id.0,
-            data,
-        });
+    if buffer.outgoing.is_empty() && !buffer.closing {
+        return;
     }
+    let _ = egress.tx.send(OutgoingPacket {
+        connection_id: conn_id.0,
+        packets: buffer.outgoing.drain(..).collect(),
+        close: core::mem::take(&mut buffer.closing),
+    });
 }
@@ -1,18 +1,21 @@
 //! Play state systems
 
-use bytes::Bytes;
 use flecs_ecs::prelude::*;
-use mc_protocol::Decode;
+use mc_data::play::serverbound::{ClientInformation, KeepAlive};
+use mc_protocol::{Decode, Packet};
 use tracing::debug;
 
+use crate::chunk;
 use crate::components::{
-    ChunkData, ChunkPos, EntityId, InPlayState, NeedsSpawnChunks, PacketBuffer, Position, Rotation,
-    ServerConfig, TpsTracker, WorldTime,
+    ChunkPosition, ClientLocale, EntityId, GameMode, HudText, InPlayState, InTabList, Latency,
+    Name, NeedsSpawnChunks, PacketBuffer, Player, Position, Rotation, ServerConfig, TpsTracker,
+    Uuid, WorldTime,
 };
+use crate::game_rules::GameRules;
 use crate::protocol::{
-    send_action_bar, send_chunks_to_buffer, send_game_event_start_waiting,
-    send_keepalive as protocol_send_keepalive, send_play_login, send_player_position,
-    send_set_center_chunk, send_set_time,
+    PlayerInfoEntry, keepalive_rtt, parse_client_locale, send_action_bar, send_brand,
+    send_game_event_start_waiting, send_keepalive as protocol_send_keepalive, send_play_login,
+    send_player_info_update, send_player_latency_update, send_player_position, send_set_time,
 };
 use crate::systems::send_commands_to_player;
 
@@ -22,22 +25,29 @@ pub fn send_spawn_data(
     entity: EntityView<'_>,
     buffer: &mut PacketBuffer,
     pos: &Position,
+    center: &mut ChunkPosition,
     entity_id: &EntityId,
 ) {
     // Get singletons
     let config = world.get::<&ServerConfig>(|c| c.clone());
     let world_time = world.get::<&WorldTime>(|t| *t);
 
-    send_play_login(buffer, entity_id.value, config.max_players);
+    let game_mode = entity
+        .try_get::<&GameMode>(|g| *g)
+        .unwrap_or(GameMode::CREATIVE);
+    send_play_login(buffer, entity_id.value, config.max_players, game_mode.value);
+    send_brand(buffer, &config.brand);
     send_game_event_start_waiting(buffer);
 
-    let (cx, cz) = pos.chunk_pos();
-    send_set_center_chunk(buffer, cx, cz);
+    chunk::update_interest(entity, buffer, pos, center, true);
 
-    let chunks = collect_chunks_for_player(8, world);
-    send_chunks_to_buffer(buffer, &chunks);
-
-    send_set_time(buffer, world_time.world_age, world_time.time_of_day);
+    let daylight_cycle = world.get::<&GameRules>(GameRules::do_daylight_cycle);
+    send_set_time(
+        buffer,
+        world_time.world_age,
+        world_time.time_of_day,
+        daylight_cycle,
+    );
     send_player_position(buffer, pos.x, pos.y, pos.z, 1);
     protocol_send_keepalive(buffer);
 
@@ -51,8 +61,43 @@ pub fn send_spawn_data(
     tracing::info!("Player entered play state");
 }
 
-/// Handle movement for a single entity
-pub fn handle_movement(buffer: &mut PacketBuffer, pos: &mut Position, rot: &mut Rotation) {
+/// Serverbound KeepAlive packet ID in Play state
+const KEEPALIVE_PACKET_ID: i32 = KeepAlive::ID;
+
+/// Serverbound Client Information packet ID in Play state
+const CLIENT_INFORMATION_PACKET_ID: i32 = ClientInformation::ID;
+
+/// Ticks between tab list refreshes (5 seconds at 20 TPS)
+const TAB_LIST_INTERVAL: i64 = 100;
+
+/// Track locale changes sent while in play (the client resends its settings
+/// when the player switches language)
+pub fn handle_client_information(entity: EntityView<'_>, buffer: &mut PacketBuffer) {
+    let mut remaining = Vec::new();
+
+    while let Some((packet_id, data)) = buffer.pop_incoming() {
+        if packet_id == CLIENT_INFORMATION_PACKET_ID {
+            if let Ok(locale) = parse_client_locale(&data) {
+                debug!("Client locale changed: {}", locale);
+                entity.set(ClientLocale { value: locale });
+            }
+        } else {
+            remaining.push((packet_id, data));
+        }
+    }
+
+    for (id, data) in remaining {
+        buffer.push_incoming(id, data);
+    }
+}
+
+/// Handle movement and keep-alive responses for a single entity
+pub fn handle_movement(
+    buffer: &mut PacketBuffer,
+    pos: &mut Position,
+    rot: &mut Rotation,
+    latency: &mut Latency,
+) {
     // Collect unhandled packets to put back after processing
     let mut unhandled = Vec::new();
 
@@ -103,10 +148,13 @@ pub fn handle_movement(buffer: &mut PacketBuffer, pos: &mut Position, rot: &mut
                     debug!("Client accepted teleport: {}", teleport_id);
                 }
             }
-            0x1A => {
-                // KeepAlive response
+            KEEPALIVE_PACKET_ID => {
+                // KeepAlive response - the ID is the send time
                 if let Ok(ka_id) = i64::decode(&mut cursor) {
-                    debug!("Keep alive response: {}", ka_id);
+                    if let Some(rtt) = keepalive_rtt(ka_id) {
+                        latency.record(rtt);
+                    }
+                    debug!("Keep alive response: {} (latency {}ms)", ka_id, latency.ms);
                 }
             }
             _ => {
@@ -132,39 +180,115 @@ pub fn send_keepalive(buffer: &mut PacketBuffer, world_time: &WorldTime) {
     protocol_send_keepalive(buffer);
 }
 
-/// Send position and TPS to action bar
-pub fn send_position_action_bar(
-    buffer: &mut PacketBuffer,
+/// Collect the tab list entries for every player in play
+fn collect_player_info(world: &WorldRef<'_>) -> Vec<PlayerInfoEntry> {
+    let mut entries = Vec::new();
+    world
+        .query::<(&Name, &Uuid, &Latency)>()
+        .with(Player)
+        .with(InPlayState)
+        .build()
+        .each(|(name, uuid, latency)| {
+            entries.push(PlayerInfoEntry {
+                uuid: uuid.0,
+                name: name.value.clone(),
+                latency_ms: latency.ms,
+            });
+        });
+    entries
+}
+
+/// Global: Add players who entered play to everyone's tab list, and
+/// periodically send everyone's latency. The list is collected once per tick,
+/// not per player.
+pub fn update_tab_list(world: &WorldRef<'_>, world_time: &WorldTime) {
+    let mut joined = Vec::new();
+    let mut joined_entries = Vec::new();
+    world
+        .query::<(&Name, &Uuid, &Latency)>()
+        .with(Player)
+        .with(InPlayState)
+        .without(InTabList)
+        .build()
+        .each_entity(|entity, (name, uuid, latency)| {
+            joined.push(entity.id());
+            joined_entries.push(PlayerInfoEntry {
+                uuid: uuid.0,
+                name: name.value.clone(),
+                latency_ms: latency.ms,
+            });
+        });
+
+    let refresh = world_time.world_age % TAB_LIST_INTERVAL == 0;
+    if joined.is_empty() && !refresh {
+        return;
+    }
+
+    let listed = world.query::<&mut PacketBuffer>().with(InTabList).build();
+    // Players already listed learn about the newcomers
+    if !joined_entries.is_empty() {
+        listed.each(|buffer| send_player_info_update(buffer, &joined_entries));
+    }
+
+    // Newcomers get the whole list, themselves included
+    let entries = collect_player_info(world);
+    for &id in &joined {
+        let entity = world.entity_from_id(id);
+        entity.try_get::<&mut PacketBuffer>(|buffer| send_player_info_update(buffer, &entries));
+        entity.add(InTabList);
+    }
+
+    if refresh && !entries.is_empty() {
+        listed.each(|buffer| send_player_latency_update(buffer, &entries));
+    }
+}
+
+/// Ticks between action bar refreshes (0.5 seconds at 20 TPS)
+const HUD_INTERVAL: i64 = 10;
+
+/// HUD source name of the position/TPS display
+const HUD_DEBUG: &str = "debug";
+
+/// Write the position and TPS display to the HUD
+pub fn update_debug_hud(
+    hud: &mut HudText,
     pos: &Position,
     world_time: &WorldTime,
     tps: &TpsTracker,
 ) {
-    // Only send every 10 ticks (0.5 seconds at 20 TPS)
-    if world_time.world_age % 10 != 0 {
+    if world_time.world_age % HUD_INTERVAL != 0 {
         return;
     }
 
     let text = format!(
-        "X: {:.1} Y: {:.1} Z: {:.1} | TPS: {:.1}:{:.1}:{:.1}",
-        pos.x, pos.y, pos.z, tps.tps_5s, tps.tps_15s, tps.tps_1m
+        "X: {:.1} Y: {:.1} Z: {:.1} | TPS: {:.1}:{:.1}:{:.1} | MSPT: {:.1}/{:.1}/{:.1}",
+        pos.x,
+        pos.y,
+        pos.z,
+        tps.tps_5s,
+        tps.tps_15s,
+        tps.tps_1m,
+        tps.mspt.p50,
+        tps.mspt.p95,
+        tps.mspt.p99
+    );
+    hud.set(
+        HUD_DEBUG,
+        text,
+        HudText::PRIORITY_DEBUG,
+        Some(HUD_INTERVAL * 2),
+        world_time.world_age,
     );
-    send_action_bar(buffer, &text);
 }
 
-fn collect_chunks_for_player(view_distance: i32, world: &WorldRef<'_>) -> Vec<Bytes> {
-    let mut chunks = Vec::new();
-
-    for cx in -view_distance..=view_distance {
-        for cz in -view_distance..=view_distance {
-            let pos = ChunkPos::new(cx, cz);
-            let name = format!("chunk:{}:{}", pos.x, pos.z);
-            if let Some(chunk_entity) = world.try_lookup_recursive(&name) {
-                chunk_entity.try_get::<&ChunkData>(|chunk_data| {
-                    chunks.push(Bytes::clone(&chunk_data.encoded));
-                });
-            }
-        }
+/// Send the highest-priority HUD entry to the action bar
+pub fn render_hud(buffer: &mut PacketBuffer, hud: &mut HudText, world_time: &WorldTime) {
+    if world_time.world_age % HUD_INTERVAL != 0 {
+        return;
     }
 
-    chunks
+    hud.prune(world_time.world_age);
+    if let Some(text) = hud.current(world_time.world_age) {
+        send_action_bar(buffer, text);
+    }
 }
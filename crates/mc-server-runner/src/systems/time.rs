@@ -3,3 +3,22 @@
 //! The actual time update logic is done directly in the system definitions:
 //! - TickWorldTime: calls WorldTime::tick()
 //! - UpdateTps: calls TpsTracker::update(delta_time)
+
+use flecs_ecs::prelude::*;
+
+use crate::components::{InPlayState, PacketBuffer, WorldTime};
+use crate::protocol::send_set_time;
+
+/// Send the current time to every player, e.g. after it jumped or the
+/// daylight cycle was switched; `increasing` is whether clients advance
+/// the time of day themselves
+pub fn broadcast_time(world: &WorldRef<'_>, increasing: bool) {
+    let time = world.get::<&WorldTime>(|t| *t);
+    world
+        .query::<&mut PacketBuffer>()
+        .with(InPlayState)
+        .build()
+        .each(|buffer| {
+            send_set_time(buffer, time.world_age, time.time_of_day, increasing);
+        });
+}
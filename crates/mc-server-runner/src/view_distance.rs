@@ -0,0 +1,161 @@
+//! Load-aware view distance
+//!
+//! Every chunk in view is sent to a player, and every change in it goes to
+//! each player that sees it, so the view distance sets most of the server's
+//! outgoing traffic. [`ViewDistance`] shrinks it as players join or ticks
+//! get slow, and grows it back when load drops:
+//!
+//! - Past [`ViewDistance::players_full`] players, every
+//!   [`ViewDistance::players_per_chunk`] more take a chunk off the distance.
+//! - Every [`ViewDistance::interval`] ticks, the distance shrinks by a chunk
+//!   while the 95th percentile tick time is above
+//!   [`ViewDistance::shrink_above_mspt`], and grows by one while it's below
+//!   [`ViewDistance::grow_below_mspt`]. The gap between the two keeps it from
+//!   bouncing.
+//!
+//! A player with a [`ViewDistanceOverride`] keeps that distance regardless
+//! of load. When a player's distance changes, they get a Set Chunk Cache
+//! Radius packet and their chunks are resent or forgotten to match; block
+//! changes and other per-chunk broadcasts follow, since they go to whoever
+//! sees the chunk.
+
+use flecs_ecs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::{self, VIEW_DISTANCE};
+use crate::components::{ChunkPosition, PacketBuffer, Player, Position, TpsTracker, WorldTime};
+use crate::protocol::send_set_chunk_cache_radius;
+
+/// Closest view distance the client accepts
+pub const MIN_VIEW_DISTANCE: i32 = 2;
+
+/// Farthest view distance the client accepts
+pub const MAX_VIEW_DISTANCE: i32 = 32;
+
+/// Global: View distance given to players without an override
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ViewDistance {
+    /// Distance with no load
+    pub max: i32,
+    /// Distance never gone below
+    pub min: i32,
+    /// Players online before the distance starts shrinking
+    pub players_full: u32,
+    /// Players past `players_full` that take one chunk off the distance
+    pub players_per_chunk: u32,
+    /// Shrink by a chunk while the 95th percentile tick time is above this
+    /// (ms)
+    pub shrink_above_mspt: f32,
+    /// Grow by a chunk while the 95th percentile tick time is below this
+    /// (ms)
+    pub grow_below_mspt: f32,
+    /// Ticks between adjustments
+    pub interval: i64,
+    /// Current distance
+    pub current: i32,
+}
+
+impl Default for ViewDistance {
+    fn default() -> Self {
+        Self {
+            max: VIEW_DISTANCE,
+            min: 4,
+            players_full: 20,
+            players_per_chunk: 10,
+            shrink_above_mspt: 40.0,
+            grow_below_mspt: 25.0,
+            interval: 200,
+            current: VIEW_DISTANCE,
+        }
+    }
+}
+
+impl ViewDistance {
+    /// Distance `players` online players allow
+    pub fn for_players(&self, players: u32) -> i32 {
+        let over = players.saturating_sub(self.players_full) / self.players_per_chunk.max(1);
+        self.max - i32::try_from(over).unwrap_or(i32::MAX)
+    }
+
+    /// Next distance for `players` online and a 95th percentile tick time
+    /// of `p95` ms
+    pub fn next(&self, players: u32, p95: f32) -> i32 {
+        let stepped = if p95 > self.shrink_above_mspt {
+            self.current - 1
+        } else if p95 < self.grow_below_mspt {
+            self.current + 1
+        } else {
+            self.current
+        };
+        let lowest = self.min.max(MIN_VIEW_DISTANCE);
+        let highest = self.max.min(MAX_VIEW_DISTANCE).max(lowest);
+        stepped
+            .min(self.for_players(players))
+            .clamp(lowest, highest)
+    }
+}
+
+/// Player: View distance kept regardless of load (e.g. for a streamer or a
+/// spectator)
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ViewDistanceOverride(pub i32);
+
+/// Player: View distance last sent to the client; chunks are streamed within
+/// it. Missing until it first changes from the distance sent at login
+/// ([`VIEW_DISTANCE`]).
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SentViewDistance(pub i32);
+
+/// View distance chunks are streamed to `player` within
+pub fn sent(player: EntityView<'_>) -> i32 {
+    player
+        .try_get::<&SentViewDistance>(|d| d.0)
+        .unwrap_or(VIEW_DISTANCE)
+}
+
+/// View distance `player` should have now
+pub fn wanted(player: EntityView<'_>, distance: &ViewDistance) -> i32 {
+    player
+        .try_get::<&ViewDistanceOverride>(|d| d.0)
+        .map_or(distance.current, |d| {
+            d.clamp(MIN_VIEW_DISTANCE, MAX_VIEW_DISTANCE)
+        })
+}
+
+/// Move the global view distance towards what the current load allows, once
+/// per interval
+pub fn adjust(world: &WorldRef<'_>, distance: &mut ViewDistance) {
+    let world_age = world.get::<&WorldTime>(|t| t.world_age);
+    if world_age % distance.interval.max(1) != 0 {
+        return;
+    }
+    let players = world.query::<()>().with(Player).build().count();
+    let p95 = world.get::<&TpsTracker>(|t| t.mspt.p95);
+    let next = distance.next(u32::try_from(players).unwrap_or(0), p95);
+    if next != distance.current {
+        tracing::info!(
+            "View distance {} -> {} ({players} players, {p95:.1} ms p95)",
+            distance.current,
+            next
+        );
+        distance.current = next;
+    }
+}
+
+/// Tell `player` about a change of their view distance, and resend or forget
+/// chunks to match
+pub fn apply(
+    player: EntityView<'_>,
+    buffer: &mut PacketBuffer,
+    pos: &Position,
+    center: &mut ChunkPosition,
+    distance: &ViewDistance,
+) {
+    let wanted = wanted(player, distance);
+    if wanted == sent(player) {
+        return;
+    }
+    send_set_chunk_cache_radius(buffer, wanted);
+    player.set(SentViewDistance(wanted));
+    chunk::update_interest(player, buffer, pos, center, true);
+}
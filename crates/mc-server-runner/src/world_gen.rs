@@ -1,12 +1,13 @@
-//! World generation - dune terrain
+//! World generation - dune terrain with biomes
 
-use byteorder::{BigEndian, WriteBytesExt};
-use bytes::Bytes;
 use flecs_ecs::prelude::*;
-use mc_protocol::write_varint;
-use tracing::info;
+use mc_data::BlockState;
+use tracing::{info, warn};
 
-use crate::components::{ChunkData, ChunkLoaded, ChunkPos};
+use crate::anvil::RegionStore;
+use crate::block_tick::ScheduledTicks;
+use crate::chunk::{ChunkBlocks, ChunkViewers, chunk_name, chunk_packet};
+use crate::components::{ChunkLoaded, ChunkPos};
 
 // ============================================================================
 // Noise Implementation (Simplex-like)
@@ -121,6 +122,32 @@ fn ridged_noise(x: f64, y: f64, octaves: u32) -> f64 {
     value
 }
 
+// ============================================================================
+// World Generators
+// ============================================================================
+
+/// Terrain and biome source for chunk generation
+pub trait WorldGenerator {
+    /// Y of the topmost block in a column
+    fn surface_height(&self, world_x: i32, world_z: i32) -> i32;
+
+    /// Block state at a position, given its column's surface height
+    fn block_at(&self, world_x: i32, world_y: i32, world_z: i32, surface_height: i32) -> u16;
+
+    /// Biome network ID at a position, given its column's surface height.
+    ///
+    /// Sampled once per 4x4x4 cell at the cell's lowest corner.
+    fn biome_at(&self, world_x: i32, world_y: i32, world_z: i32, surface_height: i32) -> i32;
+}
+
+/// Network ID of a synced biome (IDs follow registry order, so they can't be
+/// hard-coded)
+pub fn biome_id(name: &str) -> i32 {
+    mc_data::registry("minecraft:worldgen/biome")
+        .and_then(|registry| registry.entry_id(name))
+        .unwrap_or(0)
+}
+
 // ============================================================================
 // Dune Terrain Generation
 // ============================================================================
@@ -132,6 +159,10 @@ struct DuneConfig {
     ridge_scale: f64,
     wind_angle: f64,
     wind_stretch: f64,
+    /// Dune troughs at most this far above the base height are beach
+    beach_height: i32,
+    /// Scale of the badlands patches
+    badlands_scale: f64,
 }
 
 impl Default for DuneConfig {
@@ -142,6 +173,8 @@ impl Default for DuneConfig {
             ridge_scale: 0.015,
             wind_angle: 0.4,
             wind_stretch: 2.5,
+            beach_height: 14,
+            badlands_scale: 0.004,
         }
     }
 }
@@ -188,208 +221,130 @@ fn get_block_at(_world_x: i32, world_y: i32, _world_z: i32, surface_height: i32)
     }
 }
 
-// ============================================================================
-// Chunk Encoding
-// ============================================================================
-
-fn create_dune_chunk(chunk_x: i32, chunk_z: i32) -> eyre::Result<Bytes> {
-    let mut data = Vec::new();
-
-    data.write_i32::<BigEndian>(chunk_x)?;
-    data.write_i32::<BigEndian>(chunk_z)?;
-
-    write_varint(&mut data, 0)?;
-
-    let chunk_data = create_dune_sections(chunk_x, chunk_z);
-    write_varint(&mut data, chunk_data.len() as i32)?;
-    data.extend_from_slice(&chunk_data);
-
-    write_varint(&mut data, 0)?;
+/// Sand dunes: desert, with badlands patches and beaches in the troughs
+pub struct DuneGenerator {
+    config: DuneConfig,
+    desert: i32,
+    badlands: i32,
+    beach: i32,
+}
 
-    let mut sky_mask: u64 = 0;
-    for i in 5..=25 {
-        sky_mask |= 1u64 << i;
+impl Default for DuneGenerator {
+    fn default() -> Self {
+        Self {
+            config: DuneConfig::default(),
+            desert: biome_id("minecraft:desert"),
+            badlands: biome_id("minecraft:badlands"),
+            beach: biome_id("minecraft:beach"),
+        }
     }
-    write_varint(&mut data, 1)?;
-    data.write_i64::<BigEndian>(sky_mask as i64)?;
-
-    write_varint(&mut data, 0)?;
+}
 
-    let mut empty_sky_mask: u64 = 0;
-    for i in 0..5 {
-        empty_sky_mask |= 1u64 << i;
+impl WorldGenerator for DuneGenerator {
+    fn surface_height(&self, world_x: i32, world_z: i32) -> i32 {
+        get_dune_height(world_x, world_z, &self.config)
     }
-    write_varint(&mut data, 1)?;
-    data.write_i64::<BigEndian>(empty_sky_mask as i64)?;
 
-    let mut empty_block_mask: u64 = 0;
-    for i in 0..26 {
-        empty_block_mask |= 1u64 << i;
+    fn block_at(&self, world_x: i32, world_y: i32, world_z: i32, surface_height: i32) -> u16 {
+        get_block_at(world_x, world_y, world_z, surface_height)
     }
-    write_varint(&mut data, 1)?;
-    data.write_i64::<BigEndian>(empty_block_mask as i64)?;
 
-    let sky_section_count = (5..=25).count();
-    write_varint(&mut data, sky_section_count as i32)?;
+    fn biome_at(&self, world_x: i32, _world_y: i32, world_z: i32, surface_height: i32) -> i32 {
+        let config = &self.config;
+        if surface_height <= config.base_height + config.beach_height {
+            return self.beach;
+        }
 
-    let full_light = vec![0xFFu8; 2048];
-    for _ in 0..sky_section_count {
-        write_varint(&mut data, 2048)?;
-        data.extend_from_slice(&full_light);
+        // Offset so the patches don't line up with the dune noise
+        let patch = fbm(
+            world_x as f64 * config.badlands_scale + 100.0,
+            world_z as f64 * config.badlands_scale - 100.0,
+            2,
+            2.0,
+            0.5,
+        );
+        if patch > 0.25 {
+            self.badlands
+        } else {
+            self.desert
+        }
     }
-
-    write_varint(&mut data, 0)?;
-
-    Ok(Bytes::from(data))
 }
 
-fn create_dune_sections(chunk_x: i32, chunk_z: i32) -> Vec<u8> {
-    use mc_data::blocks;
-
-    let config = DuneConfig::default();
-    let mut data = Vec::new();
+// ============================================================================
+// Chunk Generation
+// ============================================================================
 
+/// Generate the blocks and biomes of one chunk
+pub fn generate_chunk(generator: &impl WorldGenerator, chunk_x: i32, chunk_z: i32) -> ChunkBlocks {
     let mut heights = [[0i32; 16]; 16];
     for lz in 0..16 {
         for lx in 0..16 {
             let world_x = chunk_x * 16 + lx as i32;
             let world_z = chunk_z * 16 + lz as i32;
-            heights[lz][lx] = get_dune_height(world_x, world_z, &config);
+            heights[lz][lx] = generator.surface_height(world_x, world_z);
         }
     }
 
-    for section_y in 0..24 {
-        let section_min_y = (section_y as i32 - 4) * 16;
-
-        let mut block_count: i16 = 0;
-        let mut blocks_in_section = [[[0u16; 16]; 16]; 16];
-
-        for local_y in 0..16 {
-            let world_y = section_min_y + local_y as i32;
-            for local_z in 0..16 {
-                for local_x in 0..16 {
-                    let surface_height = heights[local_z][local_x];
-                    let world_x = chunk_x * 16 + local_x as i32;
-                    let world_z = chunk_z * 16 + local_z as i32;
-
-                    let block_id = get_block_at(world_x, world_y, world_z, surface_height);
-                    blocks_in_section[local_y][local_z][local_x] = block_id;
-
-                    if block_id != blocks::AIR.id() {
-                        block_count += 1;
-                    }
-                }
-            }
-        }
-
-        data.extend_from_slice(&block_count.to_be_bytes());
-
-        if block_count == 0 {
-            data.push(0);
-            write_varint_vec(&mut data, blocks::AIR.id() as i32);
-        } else {
-            let mut palette: Vec<u16> = vec![blocks::AIR.id()];
-            let mut palette_map = std::collections::HashMap::new();
-            palette_map.insert(blocks::AIR.id(), 0u8);
-
-            for local_y in 0..16 {
-                for local_z in 0..16 {
-                    for local_x in 0..16 {
-                        let block_id = blocks_in_section[local_y][local_z][local_x];
-                        if !palette_map.contains_key(&block_id) {
-                            palette_map.insert(block_id, palette.len() as u8);
-                            palette.push(block_id);
-                        }
-                    }
-                }
-            }
-
-            let bits_per_entry = match palette.len() {
-                1 => 0,
-                2..=2 => 1,
-                3..=4 => 2,
-                5..=8 => 3,
-                9..=16 => 4,
-                17..=32 => 5,
-                33..=64 => 6,
-                65..=128 => 7,
-                _ => 8,
-            };
-
-            if bits_per_entry == 0 {
-                data.push(0);
-                write_varint_vec(&mut data, palette[0] as i32);
-            } else {
-                let bits = bits_per_entry.max(4);
-                data.push(bits);
-
-                write_varint_vec(&mut data, palette.len() as i32);
-                for block_id in &palette {
-                    write_varint_vec(&mut data, *block_id as i32);
-                }
-
-                let entries_per_long = 64 / bits as usize;
-                let mask = (1u64 << bits) - 1;
-
-                let mut bit_buffer: u64 = 0;
-                let mut entries_in_long = 0;
-
-                for local_y in 0..16 {
-                    for local_z in 0..16 {
-                        for local_x in 0..16 {
-                            let block_id = blocks_in_section[local_y][local_z][local_x];
-                            let palette_idx = palette_map[&block_id] as u64;
-
-                            let bit_offset = entries_in_long * bits as usize;
-                            bit_buffer |= (palette_idx & mask) << bit_offset;
-                            entries_in_long += 1;
-
-                            if entries_in_long == entries_per_long {
-                                data.extend_from_slice(&bit_buffer.to_be_bytes());
-                                bit_buffer = 0;
-                                entries_in_long = 0;
-                            }
-                        }
-                    }
-                }
-
-                if entries_in_long > 0 {
-                    data.extend_from_slice(&bit_buffer.to_be_bytes());
-                }
-            }
-        }
-
-        data.push(0);
-        write_varint_vec(&mut data, 0);
-    }
-
-    data
-}
-
-fn write_varint_vec(buf: &mut Vec<u8>, value: i32) {
-    write_varint(buf, value).expect("varint write");
+    ChunkBlocks::from_fn(
+        |x, y, z| {
+            let world_x = chunk_x * 16 + x as i32;
+            let world_z = chunk_z * 16 + z as i32;
+            BlockState::new(generator.block_at(world_x, y, world_z, heights[z][x]))
+        },
+        |x, y, z| {
+            let world_x = chunk_x * 16 + x as i32;
+            let world_z = chunk_z * 16 + z as i32;
+            generator.biome_at(world_x, y, world_z, heights[z][x])
+        },
+    )
 }
 
-/// Generate spawn chunks around origin
-pub fn generate_spawn_chunks(world: &World, view_distance: i32) {
+/// Load the spawn chunks around origin from `regions`, generating the ones
+/// that were never saved
+pub fn generate_spawn_chunks(
+    world: &World,
+    generator: &impl WorldGenerator,
+    regions: Option<&RegionStore>,
+    view_distance: i32,
+) {
+    let mut loaded = 0;
     for cx in -view_distance..=view_distance {
         for cz in -view_distance..=view_distance {
             let pos = ChunkPos::new(cx, cz);
+            let saved = regions.and_then(|regions| load_chunk(regions, cx, cz));
+            loaded += usize::from(saved.is_some());
+
+            // Use readable string name for dashboard visibility
+            let chunk = world
+                .entity_named(&chunk_name(cx, cz))
+                .set(pos)
+                .set(saved.unwrap_or_else(|| generate_chunk(generator, cx, cz)))
+                .set(ScheduledTicks::default())
+                .set(ChunkViewers::default())
+                .add(ChunkLoaded);
+            // Warm the encoding cache
+            chunk_packet(chunk);
+        }
+    }
+
+    let total = ((view_distance * 2 + 1) * (view_distance * 2 + 1)) as usize;
+    info!("Loaded {loaded} spawn chunks, generated {}", total - loaded);
+}
 
-            if let Ok(data) = create_dune_chunk(cx, cz) {
-                // Use readable string name for dashboard visibility
-                let name = format!("chunk:{}:{}", cx, cz);
-                world
-                    .entity_named(&name)
-                    .set(pos)
-                    .set(ChunkData::new(data))
-                    .add(ChunkLoaded);
+/// A saved chunk, `None` if it was never saved or can't be read
+fn load_chunk(regions: &RegionStore, chunk_x: i32, chunk_z: i32) -> Option<ChunkBlocks> {
+    match regions.read_chunk(chunk_x, chunk_z) {
+        Ok(nbt) => {
+            let blocks = ChunkBlocks::from_nbt(&nbt?);
+            if blocks.is_none() {
+                warn!("Chunk {chunk_x}, {chunk_z} is malformed, generating it again");
             }
+            blocks
+        }
+        Err(e) => {
+            warn!("Failed to read chunk {chunk_x}, {chunk_z}, generating it again: {e}");
+            None
         }
     }
-
-    info!(
-        "Generated {} spawn chunks",
-        (view_distance * 2 + 1) * (view_distance * 2 + 1)
-    );
 }
@@ -0,0 +1,70 @@
+//! Click and hover events.
+
+use mc_protocol::nbt::NbtCompound;
+use serde::Serialize;
+
+use crate::Text;
+
+/// What happens when the text is clicked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClickEvent {
+    OpenUrl { url: String },
+    RunCommand { command: String },
+    SuggestCommand { command: String },
+    ChangePage { page: i32 },
+    CopyToClipboard { value: String },
+}
+
+impl ClickEvent {
+    /// Network NBT form.
+    #[must_use]
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        match self {
+            Self::OpenUrl { url } => {
+                compound.insert("action", "open_url");
+                compound.insert("url", url.as_str());
+            }
+            Self::RunCommand { command } => {
+                compound.insert("action", "run_command");
+                compound.insert("command", command.as_str());
+            }
+            Self::SuggestCommand { command } => {
+                compound.insert("action", "suggest_command");
+                compound.insert("command", command.as_str());
+            }
+            Self::ChangePage { page } => {
+                compound.insert("action", "change_page");
+                compound.insert("page", *page);
+            }
+            Self::CopyToClipboard { value } => {
+                compound.insert("action", "copy_to_clipboard");
+                compound.insert("value", value.as_str());
+            }
+        }
+        compound
+    }
+}
+
+/// What is shown when the text is hovered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum HoverEvent {
+    ShowText { value: Box<Text> },
+}
+
+impl HoverEvent {
+    /// Network NBT form.
+    #[must_use]
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+        match self {
+            Self::ShowText { value } => {
+                compound.insert("action", "show_text");
+                compound.insert("value", value.to_nbt());
+            }
+        }
+        compound
+    }
+}
@@ -0,0 +1,212 @@
+//! Server-side translations.
+//!
+//! A [`Catalog`] holds message templates per locale, loaded from Minecraft-style
+//! flat JSON lang files:
+//!
+//! ```json
+//! { "command.tps": "TPS: %s (5s) %s (15s) %s (1m)" }
+//! ```
+//!
+//! Templates use the same placeholders as vanilla: `%s` for the next argument,
+//! `%1$s` for a specific one and `%%` for a literal percent sign. Use [`tr!`]
+//! to translate for a recipient:
+//!
+//! ```
+//! use mc_text::{lang::Catalog, tr};
+//!
+//! let mut catalog = Catalog::new();
+//! catalog.load_json("en_us", r#"{ "greet": "Hello, %s!" }"#).unwrap();
+//!
+//! let text = tr!(catalog, "en_us", "greet", "Steve");
+//! assert_eq!(text.to_plain(), "Hello, Steve!");
+//! ```
+//!
+//! [`tr!`]: crate::tr
+
+use std::collections::HashMap;
+
+use crate::Text;
+
+/// Locale used when the recipient's locale has no entry for a key.
+pub const DEFAULT_LOCALE: &str = "en_us";
+
+/// Message templates keyed by locale, then by translation key.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    locales: HashMap<String, HashMap<String, String>>,
+}
+
+impl Catalog {
+    /// Create an empty catalog.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge a flat JSON lang file into `locale`, replacing existing keys.
+    pub fn load_json(&mut self, locale: &str, json: &str) -> serde_json::Result<()> {
+        let entries: HashMap<String, String> = serde_json::from_str(json)?;
+        self.locales
+            .entry(locale.to_ascii_lowercase())
+            .or_default()
+            .extend(entries);
+        Ok(())
+    }
+
+    /// Iterate over the loaded locales.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.locales.keys().map(String::as_str)
+    }
+
+    /// Template for `key` in `locale`, falling back to [`DEFAULT_LOCALE`].
+    #[must_use]
+    pub fn template(&self, locale: &str, key: &str) -> Option<&str> {
+        let lookup = |locale: &str| self.locales.get(locale)?.get(key).map(String::as_str);
+        lookup(&locale.to_ascii_lowercase()).or_else(|| lookup(DEFAULT_LOCALE))
+    }
+
+    /// Translate `key` for `locale`.
+    ///
+    /// Keys the server doesn't know become a `translate` component, so the
+    /// client can still resolve vanilla keys itself.
+    #[must_use]
+    pub fn translate(&self, locale: &str, key: &str, args: Vec<Text>) -> Text {
+        match self.template(locale, key) {
+            Some(template) => format(template, &args),
+            None => Text::translate_with(key, args),
+        }
+    }
+}
+
+/// Substitute `args` into a template.
+///
+/// Placeholders without a matching argument are dropped.
+#[must_use]
+pub fn format(template: &str, args: &[Text]) -> Text {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut next_arg = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+
+        // %% -> %
+        if chars.next_if_eq(&'%').is_some() {
+            literal.push('%');
+            continue;
+        }
+
+        // %s or %N$s
+        let mut digits = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            digits.push(digit);
+        }
+        let index = if digits.is_empty() {
+            let index = next_arg;
+            next_arg += 1;
+            Some(index)
+        } else if chars.next_if_eq(&'$').is_some() {
+            digits.parse::<usize>().ok().and_then(|n| n.checked_sub(1))
+        } else {
+            // Not a placeholder after all
+            literal.push('%');
+            literal.push_str(&digits);
+            continue;
+        };
+        if chars.next_if_eq(&'s').is_none() {
+            literal.push('%');
+            literal.push_str(&digits);
+            continue;
+        }
+
+        if !literal.is_empty() {
+            parts.push(Text::literal(std::mem::take(&mut literal)));
+        }
+        if let Some(arg) = index.and_then(|i| args.get(i)) {
+            parts.push(arg.clone());
+        }
+    }
+
+    if parts.is_empty() {
+        return Text::literal(literal);
+    }
+    if !literal.is_empty() {
+        parts.push(Text::literal(literal));
+    }
+    parts
+        .into_iter()
+        .fold(Text::literal(""), |text, part| text.append(part))
+}
+
+/// Translate a message for a recipient's locale.
+///
+/// `tr!(catalog, locale, key, args...)` where each argument converts into a
+/// [`Text`](crate::Text).
+#[macro_export]
+macro_rules! tr {
+    ($catalog:expr, $locale:expr, $key:expr $(, $arg:expr)* $(,)?) => {
+        $catalog.translate($locale, $key, vec![$($crate::Text::from($arg)),*])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::new();
+        catalog
+            .load_json(
+                "en_us",
+                r#"{ "greet": "Hello, %s!", "swap": "%2$s before %1$s", "pct": "100%% %s" }"#,
+            )
+            .unwrap();
+        catalog
+            .load_json("de_de", r#"{ "greet": "Hallo, %s!" }"#)
+            .unwrap();
+        catalog
+    }
+
+    #[test]
+    fn test_translate_per_locale() {
+        let catalog = catalog();
+        assert_eq!(
+            tr!(catalog, "de_de", "greet", "Steve").to_plain(),
+            "Hallo, Steve!"
+        );
+        assert_eq!(
+            tr!(catalog, "EN_US", "greet", "Alex").to_plain(),
+            "Hello, Alex!"
+        );
+    }
+
+    #[test]
+    fn test_fallback_to_default_locale() {
+        let catalog = catalog();
+        assert_eq!(
+            tr!(catalog, "de_de", "swap", "a", "b").to_plain(),
+            "b before a"
+        );
+        assert_eq!(tr!(catalog, "fr_fr", "pct", "sure").to_plain(), "100% sure");
+    }
+
+    #[test]
+    fn test_unknown_key_is_client_translated() {
+        let text = tr!(catalog(), "en_us", "multiplayer.player.joined", "Steve");
+        assert_eq!(
+            text,
+            Text::translate_with("multiplayer.player.joined", vec!["Steve".into()])
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_argument_style() {
+        let text = format("Hi %s", &[Text::literal("Steve").bold()]);
+        assert_eq!(text.extra[1].bold, Some(true));
+        assert_eq!(format("no args", &[]), Text::literal("no args"));
+    }
+}
@@ -0,0 +1,341 @@
+//! Minecraft text components.
+//!
+//! A typed builder for chat/action bar/MOTD text that serializes to both the
+//! JSON form (status response, legacy APIs) and the network NBT form used by
+//! play packets since 1.20.3:
+//!
+//! ```
+//! use mc_text::{ClickEvent, Color, Text};
+//!
+//! let text = Text::literal("Welcome, ")
+//!     .color(Color::Gray)
+//!     .append(Text::literal("Steve").color(Color::Gold).bold())
+//!     .append(
+//!         Text::literal(" [help]")
+//!             .underlined()
+//!             .on_click(ClickEvent::RunCommand { command: "/help".into() })
+//!             .on_hover(Text::literal("Show commands")),
+//!     );
+//!
+//! let json = text.to_json();
+//! let nbt = text.to_network_bytes();
+//! # assert!(json.contains("\"bold\":true"));
+//! # assert!(!nbt.is_empty());
+//! ```
+//!
+//! Field names follow the 1.21.5+ format (`click_event`, `hover_event`).
+
+mod color;
+mod event;
+pub mod lang;
+
+use mc_protocol::nbt::{NbtCompound, NbtList};
+use serde::Serialize;
+
+pub use color::Color;
+pub use event::{ClickEvent, HoverEvent};
+
+/// What a text component displays.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Content {
+    /// Literal text.
+    Text { text: String },
+    /// A translation key, with arguments substituted for `%s`.
+    Translate {
+        translate: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        with: Vec<Text>,
+    },
+    /// The key bound to a control (e.g. `key.jump`).
+    Keybind { keybind: String },
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Self::Text {
+            text: String::new(),
+        }
+    }
+}
+
+/// A text component with style and children.
+///
+/// Unset style fields are inherited from the parent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Text {
+    #[serde(flatten)]
+    pub content: Content,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub obfuscated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<Text>,
+}
+
+impl Text {
+    /// Literal text.
+    #[must_use]
+    pub fn literal(text: impl Into<String>) -> Self {
+        Self::with_content(Content::Text { text: text.into() })
+    }
+
+    /// A translation key without arguments.
+    #[must_use]
+    pub fn translate(key: impl Into<String>) -> Self {
+        Self::translate_with(key, Vec::new())
+    }
+
+    /// A translation key with arguments.
+    #[must_use]
+    pub fn translate_with(key: impl Into<String>, with: Vec<Self>) -> Self {
+        Self::with_content(Content::Translate {
+            translate: key.into(),
+            with,
+        })
+    }
+
+    /// The key bound to a control.
+    #[must_use]
+    pub fn keybind(key: impl Into<String>) -> Self {
+        Self::with_content(Content::Keybind {
+            keybind: key.into(),
+        })
+    }
+
+    fn with_content(content: Content) -> Self {
+        Self {
+            content,
+            ..Self::default()
+        }
+    }
+
+    /// Set the color.
+    #[must_use]
+    pub const fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Make the text bold.
+    #[must_use]
+    pub const fn bold(mut self) -> Self {
+        self.bold = Some(true);
+        self
+    }
+
+    /// Make the text italic.
+    #[must_use]
+    pub const fn italic(mut self) -> Self {
+        self.italic = Some(true);
+        self
+    }
+
+    /// Underline the text.
+    #[must_use]
+    pub const fn underlined(mut self) -> Self {
+        self.underlined = Some(true);
+        self
+    }
+
+    /// Strike the text through.
+    #[must_use]
+    pub const fn strikethrough(mut self) -> Self {
+        self.strikethrough = Some(true);
+        self
+    }
+
+    /// Render the text as constantly changing random characters.
+    #[must_use]
+    pub const fn obfuscated(mut self) -> Self {
+        self.obfuscated = Some(true);
+        self
+    }
+
+    /// Set the click action.
+    #[must_use]
+    pub fn on_click(mut self, event: ClickEvent) -> Self {
+        self.click_event = Some(event);
+        self
+    }
+
+    /// Show `text` as a tooltip on hover.
+    #[must_use]
+    pub fn on_hover(mut self, text: impl Into<Self>) -> Self {
+        self.hover_event = Some(HoverEvent::ShowText {
+            value: Box::new(text.into()),
+        });
+        self
+    }
+
+    /// Append a child component, which inherits this component's style.
+    #[must_use]
+    pub fn append(mut self, child: impl Into<Self>) -> Self {
+        self.extra.push(child.into());
+        self
+    }
+
+    /// The displayed text without styling, for logs.
+    ///
+    /// Translate and keybind components render as their key.
+    #[must_use]
+    pub fn to_plain(&self) -> String {
+        let mut out = String::new();
+        self.write_plain(&mut out);
+        out
+    }
+
+    fn write_plain(&self, out: &mut String) {
+        match &self.content {
+            Content::Text { text } => out.push_str(text),
+            Content::Translate { translate, .. } => out.push_str(translate),
+            Content::Keybind { keybind } => out.push_str(keybind),
+        }
+        for child in &self.extra {
+            child.write_plain(out);
+        }
+    }
+
+    /// Serialize to JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Convert to an NBT compound.
+    #[must_use]
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut compound = NbtCompound::new();
+
+        match &self.content {
+            Content::Text { text } => compound.insert("text", text.as_str()),
+            Content::Translate { translate, with } => {
+                compound.insert("translate", translate.as_str());
+                if !with.is_empty() {
+                    compound.insert("with", Self::nbt_list(with));
+                }
+            }
+            Content::Keybind { keybind } => compound.insert("keybind", keybind.as_str()),
+        }
+
+        if let Some(color) = self.color {
+            compound.insert("color", color.to_string());
+        }
+        let flags = [
+            ("bold", self.bold),
+            ("italic", self.italic),
+            ("underlined", self.underlined),
+            ("strikethrough", self.strikethrough),
+            ("obfuscated", self.obfuscated),
+        ];
+        for (name, value) in flags {
+            if let Some(value) = value {
+                compound.insert(name, value);
+            }
+        }
+        if let Some(event) = &self.click_event {
+            compound.insert("click_event", event.to_nbt());
+        }
+        if let Some(event) = &self.hover_event {
+            compound.insert("hover_event", event.to_nbt());
+        }
+        if !self.extra.is_empty() {
+            compound.insert("extra", Self::nbt_list(&self.extra));
+        }
+
+        compound
+    }
+
+    /// Serialize to network NBT (nameless root compound), as sent in play packets.
+    #[must_use]
+    pub fn to_network_bytes(&self) -> Vec<u8> {
+        self.to_nbt().to_network_bytes()
+    }
+
+    fn nbt_list(texts: &[Self]) -> NbtList {
+        NbtList::Compound(texts.iter().map(Self::to_nbt).collect())
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Self::literal(text)
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Self::literal(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mc_protocol::nbt;
+
+    use super::*;
+
+    #[test]
+    fn test_plain_text_json() {
+        assert_eq!(Text::literal("hi").to_json(), r#"{"text":"hi"}"#);
+    }
+
+    #[test]
+    fn test_plain_text_nbt_matches_macro() {
+        let expected = nbt! { "text" => "hi" }.to_network_bytes();
+        assert_eq!(Text::literal("hi").to_network_bytes(), expected);
+    }
+
+    #[test]
+    fn test_styled_json() {
+        let text = Text::literal("Hello")
+            .color(Color::Rgb(0xff, 0x80, 0x00))
+            .bold()
+            .on_click(ClickEvent::OpenUrl {
+                url: "https://example.com".into(),
+            })
+            .on_hover("tip")
+            .append(Text::literal("!").color(Color::Red));
+
+        let json: serde_json::Value = serde_json::from_str(&text.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "text": "Hello",
+                "color": "#ff8000",
+                "bold": true,
+                "click_event": { "action": "open_url", "url": "https://example.com" },
+                "hover_event": { "action": "show_text", "value": { "text": "tip" } },
+                "extra": [{ "text": "!", "color": "red" }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_translate_nbt() {
+        let text =
+            Text::translate_with("chat.type.text", vec!["Steve".into(), "hi".into()]).italic();
+        let expected = nbt! {
+            "translate" => "chat.type.text",
+            "with" => NbtList::Compound(vec![
+                nbt! { "text" => "Steve" },
+                nbt! { "text" => "hi" },
+            ]),
+            "italic" => true,
+        };
+        assert_eq!(text.to_nbt(), expected);
+    }
+}
@@ -8,8 +8,16 @@
 //! Each module dylib must export these Rust ABI symbols:
 //! - `module_load(world: &World)` - Called to load/register the module
 //! - `module_unload(world: &World)` - Called before unloading to cleanup
-//! - `module_name() -> &'static str` - Returns the module name
+//! - `module_name() -> &'static str` - Returns the module name, which must be
+//!   unique among loaded modules
 //! - `module_version() -> u32` - (optional) Returns the module version
+//! - `module_path() -> &'static str` - (optional) Returns the path of the
+//!   module's Flecs scope, used for [resource accounting](ModuleStats)
+//! - `module_components() -> &'static [&'static str]` - (optional) Returns
+//!   the names of the components the module registers
+//!
+//! [`ModuleLoader::inspect`] reads these from a module without loading it
+//! into a world, for tools listing the modules available.
 //!
 //! # Using the `register_module!` macro
 //!
@@ -23,9 +31,16 @@
 //!     version: 1,
 //!     module: MyModule,
 //!     path: "::my_module",
+//!     components: [Position, Velocity],
 //! }
 //! ```
 //!
+//! # Sharing data between modules
+//!
+//! Besides components, modules can publish [services](services) that other
+//! modules look up by name: message channels and request/response queues.
+//! A module's services are withdrawn when it's unloaded.
+//!
 //! # Safety
 //!
 //! Modules use Rust ABI which requires the same compiler version.
@@ -33,10 +48,12 @@
 
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
 
-use flecs_ecs::prelude::World;
+use flecs_ecs::prelude::*;
 #[cfg(unix)]
 use libloading::os::unix::{Library, Symbol};
 #[cfg(windows)]
@@ -45,6 +62,8 @@ use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
+pub mod services;
+
 /// Ensure flecs_ecs shared library is loaded with RTLD_GLOBAL on Unix.
 /// This must be called before loading any modules that depend on flecs_ecs.
 #[cfg(unix)]
@@ -108,6 +127,8 @@ type ModuleLoadFn = fn(&World);
 type ModuleUnloadFn = fn(&World);
 type ModuleNameFn = fn() -> &'static str;
 type ModuleVersionFn = fn() -> u32;
+type ModulePathFn = fn() -> &'static str;
+type ModuleComponentsFn = fn() -> &'static [&'static str];
 
 /// Errors that can occur during module operations
 #[derive(Error, Debug)]
@@ -126,6 +147,70 @@ pub enum ModuleError {
 
     #[error("Watch error: {0}")]
     Watch(#[from] notify::Error),
+
+    #[error("Pre-flight check failed: {0}")]
+    Preflight(String),
+
+    #[error("Module '{name}' is already loaded from {}", loaded.display())]
+    DuplicateName { name: String, loaded: PathBuf },
+}
+
+/// What a module dylib exports about itself, read by [`ModuleLoader::inspect`]
+#[derive(Debug, Clone)]
+pub struct ModuleInfo {
+    /// Path to the module file
+    pub path: PathBuf,
+    /// Module name
+    pub name: String,
+    /// Module version, if exported
+    pub version: Option<u32>,
+    /// Path of the module's Flecs scope, if exported
+    pub scope: Option<String>,
+    /// Components the module registers, empty if it doesn't say
+    pub components: Vec<String>,
+    /// Hash of the file's contents, which tells builds apart even when
+    /// their version is the same
+    pub fingerprint: u64,
+}
+
+/// Name, version and scope exports of a module
+struct Exports {
+    name: String,
+    version: Option<u32>,
+    scope: Option<String>,
+}
+
+impl Exports {
+    fn read(library: &Library) -> Result<Self, ModuleError> {
+        let name_fn: Symbol<ModuleNameFn> = unsafe {
+            library
+                .get(b"module_name")
+                .map_err(|_| ModuleError::MissingSymbol {
+                    symbol: "module_name",
+                })?
+        };
+
+        // Optional exports
+        let version = unsafe { library.get::<ModuleVersionFn>(b"module_version") }
+            .ok()
+            .map(|f| f());
+        let scope = unsafe { library.get::<ModulePathFn>(b"module_path") }
+            .ok()
+            .map(|f| f().to_string());
+
+        Ok(Self {
+            name: name_fn().to_string(),
+            version,
+            scope,
+        })
+    }
+}
+
+/// 64-bit FNV-1a, stable across builds of the loader unlike `DefaultHasher`
+fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 /// A loaded module instance
@@ -138,6 +223,62 @@ struct LoadedModule {
     name: String,
     /// Module version (optional, from module_version())
     version: Option<u32>,
+    /// Path of the module's Flecs scope (optional, from module_path())
+    scope: Option<String>,
+}
+
+/// Resources used by a loaded module
+///
+/// Counted over everything in the module's Flecs scope, so modules without
+/// a `module_path` export report nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleStats {
+    /// Module name
+    pub name: String,
+    /// Module version, if exported
+    pub version: Option<u32>,
+    /// Entities registered that are neither components nor systems
+    pub entities: usize,
+    /// Components registered
+    pub components: usize,
+    /// Systems registered
+    pub systems: usize,
+    /// Bytes used by the columns of the module's components
+    pub column_bytes: usize,
+    /// Time spent running the module's systems since they were registered
+    pub system_time: Duration,
+    /// Services published
+    pub services: usize,
+}
+
+impl ModuleStats {
+    /// Add up the resources in a scope and its nested scopes
+    fn count_scope(&mut self, world: &World, scope: EntityView<'_>) {
+        scope.each_child(|child| {
+            if child.has(flecs::system::System::ID) {
+                self.systems += 1;
+                self.system_time += system_time(world, child);
+            } else if let Some(size) = child.try_get::<&flecs::Component>(|c| c.size) {
+                self.components += 1;
+                self.column_bytes += size as usize * world.count(child) as usize;
+            } else {
+                self.entities += 1;
+            }
+            self.count_scope(world, child);
+        });
+    }
+}
+
+/// Total time a system has spent running
+///
+/// Only measured while system time measurement is on, which loading a module
+/// turns on.
+fn system_time(world: &World, system: EntityView<'_>) -> Duration {
+    let system = unsafe { flecs_ecs::sys::ecs_system_get(world.ptr_mut(), system.id().0) };
+    if system.is_null() {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f32(unsafe { (*system).time_spent }.max(0.0))
 }
 
 impl LoadedModule {
@@ -171,20 +312,11 @@ impl LoadedModule {
 
     /// Common loading logic after library is opened
     fn load_inner(library: Library, path: &Path) -> Result<Self, ModuleError> {
-        // Get module name
-        let name_fn: Symbol<ModuleNameFn> = unsafe {
-            library
-                .get(b"module_name")
-                .map_err(|_| ModuleError::MissingSymbol {
-                    symbol: "module_name",
-                })?
-        };
-        let name = name_fn();
-
-        // Try to get version (optional)
-        let version = unsafe { library.get::<ModuleVersionFn>(b"module_version") }
-            .ok()
-            .map(|f| f());
+        let Exports {
+            name,
+            version,
+            scope,
+        } = Exports::read(&library)?;
 
         if let Some(v) = version {
             info!("Loaded module '{}' v{} from {}", name, v, path.display());
@@ -195,8 +327,9 @@ impl LoadedModule {
         Ok(Self {
             library,
             path: path.to_path_buf(),
-            name: name.to_string(),
+            name,
             version,
+            scope,
         })
     }
 
@@ -212,7 +345,13 @@ impl LoadedModule {
                 })?
         };
 
+        // Needed for per-module system time
+        unsafe { flecs_ecs::sys::ecs_measure_system_time(world.ptr_mut(), true) };
+
+        // Services published while loading belong to this module
+        services::set_loading(world, Some(&self.name));
         load_fn(world);
+        services::set_loading(world, None);
         info!("Initialized module '{}'", self.name);
         Ok(())
     }
@@ -241,10 +380,14 @@ pub struct ModuleLoader {
     modules_dir: PathBuf,
     /// Currently loaded modules (keyed by file path)
     modules: HashMap<PathBuf, LoadedModule>,
+    /// Path each loaded module name was loaded from
+    names: HashMap<String, PathBuf>,
     /// File watcher for hot-reload
     watcher: Option<RecommendedWatcher>,
     /// Channel for file change events
     watch_rx: Option<mpsc::Receiver<Result<Event, notify::Error>>>,
+    /// Whether reloads are tried in a scratch world first
+    preflight: bool,
 }
 
 impl ModuleLoader {
@@ -253,11 +396,60 @@ impl ModuleLoader {
         Self {
             modules_dir: modules_dir.into(),
             modules: HashMap::new(),
+            names: HashMap::new(),
             watcher: None,
             watch_rx: None,
+            preflight: false,
         }
     }
 
+    /// Try each reloaded module in a scratch world before swapping it in
+    ///
+    /// The new build is loaded into an empty [`World`], its `module_load`
+    /// run and one tick progressed. Only if that succeeds is the live module
+    /// unloaded and replaced, so a broken build leaves the old one running.
+    pub fn set_preflight(&mut self, preflight: bool) {
+        self.preflight = preflight;
+    }
+
+    /// Read a module's exports without loading it into a world
+    ///
+    /// The library is opened and closed again without calling `module_load`,
+    /// though its static initializers still run. Its symbols aren't made
+    /// available to other modules.
+    pub fn inspect(path: &Path) -> Result<ModuleInfo, ModuleError> {
+        if !path.exists() {
+            return Err(ModuleError::NotFound(path.into()));
+        }
+        // Modules link against flecs_ecs even when nothing calls into them
+        ensure_flecs_global();
+
+        #[cfg(unix)]
+        let library = unsafe { Library::open(Some(path), libc::RTLD_LAZY | libc::RTLD_LOCAL)? };
+        #[cfg(windows)]
+        let library = unsafe { Library::new(path)? };
+
+        let Exports {
+            name,
+            version,
+            scope,
+        } = Exports::read(&library)?;
+        let components = unsafe { library.get::<ModuleComponentsFn>(b"module_components") }
+            .ok()
+            .map(|f| f().iter().map(ToString::to_string).collect())
+            .unwrap_or_default();
+        drop(library);
+
+        Ok(ModuleInfo {
+            path: path.to_path_buf(),
+            name,
+            version,
+            scope,
+            components,
+            fingerprint: fingerprint(&std::fs::read(path)?),
+        })
+    }
+
     /// Get the platform-specific dynamic library extension
     fn dylib_extension() -> &'static str {
         if cfg!(target_os = "macos") {
@@ -291,14 +483,21 @@ impl ModuleLoader {
             return Ok(());
         }
 
-        let entries = std::fs::read_dir(&self.modules_dir)?;
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.modules_dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(OsStr::new(ext)))
+            .collect();
+        // Newest first, so older builds of a module are the ones skipped
+        paths.sort_by_key(|path| std::cmp::Reverse(modified(path)));
 
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension() == Some(OsStr::new(ext))
-                && let Err(e) = self.load_module(&path, world)
-            {
-                error!("Failed to load module {}: {}", path.display(), e);
+        for path in paths {
+            match self.load_module(&path, world) {
+                Ok(()) => {}
+                Err(e @ ModuleError::DuplicateName { .. }) => {
+                    warn!("Skipping module {}: {}", path.display(), e);
+                }
+                Err(e) => error!("Failed to load module {}: {}", path.display(), e),
             }
         }
 
@@ -306,6 +505,10 @@ impl ModuleLoader {
     }
 
     /// Load a single module from the given path
+    ///
+    /// If a module with the same name is loaded from another path, whichever
+    /// file was modified last wins: an older file is refused with
+    /// [`ModuleError::DuplicateName`], a newer one replaces the loaded module.
     pub fn load_module(&mut self, path: &Path, world: &World) -> Result<(), ModuleError> {
         // Unload existing module at this path if any
         if self.modules.contains_key(path) {
@@ -313,7 +516,24 @@ impl ModuleLoader {
         }
 
         let module = unsafe { LoadedModule::load(path)? };
+        if let Some(loaded) = self.names.get(&module.name).cloned() {
+            if modified(path) <= modified(&loaded) {
+                return Err(ModuleError::DuplicateName {
+                    name: module.name,
+                    loaded,
+                });
+            }
+            warn!(
+                "Module '{}' from {} is newer than the one loaded from {}, replacing it",
+                module.name,
+                path.display(),
+                loaded.display()
+            );
+            self.unload_module(&loaded, world)?;
+        }
+
         module.init(world)?;
+        self.names.insert(module.name.clone(), path.to_path_buf());
         self.modules.insert(path.to_path_buf(), module);
 
         Ok(())
@@ -322,25 +542,75 @@ impl ModuleLoader {
     /// Unload a module at the given path
     pub fn unload_module(&mut self, path: &Path, world: &World) -> Result<(), ModuleError> {
         if let Some(module) = self.modules.remove(path) {
-            module.cleanup(world)?;
+            self.names.remove(&module.name);
+            let cleanup = module.cleanup(world);
+            // Endpoints are released by the module's code, so before it's closed
+            let withdrawn = services::withdraw_module(world, &module.name);
+            if withdrawn > 0 {
+                debug!("Withdrew {} services of '{}'", withdrawn, module.name);
+            }
+            cleanup?;
             // Library is dropped here, unloading the dylib
             info!("Unloaded module '{}' from {}", module.name, path.display());
         }
         Ok(())
     }
 
-    /// Reload a module (unload then load)
+    /// Reload a module (unload then load), after a
+    /// [pre-flight check](Self::set_preflight) if enabled
     pub fn reload_module(&mut self, path: &Path, world: &World) -> Result<(), ModuleError> {
         info!("Reloading module: {}", path.display());
-        self.unload_module(path, world)?;
 
         // Small delay to ensure file is fully written
         std::thread::sleep(std::time::Duration::from_millis(100));
 
+        if self.preflight {
+            Self::preflight(path)?;
+        }
+
+        self.unload_module(path, world)?;
         self.load_module(path, world)?;
         Ok(())
     }
 
+    /// Load a module into a scratch world and run one tick
+    fn preflight(path: &Path) -> Result<(), ModuleError> {
+        // A library already loaded from `path` would be reused, so test a copy
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| ModuleError::NotFound(path.into()))?;
+        let copy = std::env::temp_dir().join(format!(
+            "preflight-{}-{}",
+            std::process::id(),
+            file_name.to_string_lossy()
+        ));
+        std::fs::copy(path, &copy)?;
+
+        let result = unsafe { LoadedModule::load(&copy) }.and_then(|module| {
+            debug!("Pre-flight checking module '{}'", module.name);
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                let scratch = World::new();
+                module.init(&scratch)?;
+                scratch.progress();
+                Ok(())
+            }))
+            .unwrap_or_else(|_| {
+                Err(ModuleError::Preflight(format!(
+                    "module '{}' panicked",
+                    module.name
+                )))
+            });
+            // The scratch world is dropped before the library it ran
+            drop(module);
+            result
+        });
+
+        if let Err(e) = std::fs::remove_file(&copy) {
+            warn!("Failed to remove {}: {}", copy.display(), e);
+        }
+        result
+    }
+
     /// Start watching the modules directory for changes
     pub fn start_watching(&mut self) -> Result<(), ModuleError> {
         let (tx, rx) = mpsc::channel();
@@ -462,6 +732,36 @@ impl ModuleLoader {
             })
             .collect()
     }
+
+    /// Resources used by each loaded module
+    pub fn stats(&self, world: &World) -> Vec<ModuleStats> {
+        self.modules
+            .values()
+            .map(|module| {
+                let mut stats = ModuleStats {
+                    name: module.name.clone(),
+                    version: module.version,
+                    ..Default::default()
+                };
+                if let Some(scope) = module
+                    .scope
+                    .as_deref()
+                    .and_then(|path| world.try_lookup(path))
+                {
+                    stats.count_scope(world, scope);
+                }
+                stats.services = services::with_registry(world, |registry| {
+                    registry.published_by(&module.name).len()
+                });
+                stats
+            })
+            .collect()
+    }
+}
+
+/// When the file at `path` was last modified, if it can be read
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
 impl Drop for ModuleLoader {
@@ -504,8 +804,30 @@ impl Drop for ModuleLoader {
 ///     path: "::my_module",
 /// }
 /// ```
+///
+/// An optional trailing `components: [A, B]` lists the module's components
+/// for [`ModuleLoader::inspect`].
 #[macro_export]
 macro_rules! register_module {
+    {
+        name: $name:literal,
+        version: $version:expr,
+        module: $module:ty,
+        path: $path:literal,
+        components: [$($component:ty),* $(,)?] $(,)?
+    } => {
+        $crate::register_module! {
+            name: $name,
+            version: $version,
+            module: $module,
+            path: $path,
+        }
+
+        #[unsafe(no_mangle)]
+        pub fn module_components() -> &'static [&'static str] {
+            &[$(stringify!($component)),*]
+        }
+    };
     {
         name: $name:literal,
         version: $version:expr,
@@ -533,5 +855,10 @@ macro_rules! register_module {
         pub fn module_version() -> u32 {
             $version
         }
+
+        #[unsafe(no_mangle)]
+        pub fn module_path() -> &'static str {
+            $path
+        }
     };
 }
@@ -0,0 +1,480 @@
+//! Services shared between modules
+//!
+//! Modules can only share components they both compile against. A service
+//! lets one module hand others an endpoint, looked up by name at runtime:
+//! - a [`Channel`], a queue of messages any module can send to and drain
+//! - a [`RequestQueue`], where clients queue requests and the publishing
+//!   module answers them
+//!
+//! ```ignore
+//! use module_loader::services::{self, Channel, Message, schema_hash};
+//!
+//! pub struct ChatLine {
+//!     pub player: u64,
+//!     pub text: String,
+//! }
+//!
+//! impl Message for ChatLine {
+//!     const SCHEMA_HASH: u64 = schema_hash("ChatLine { player: u64, text: String }");
+//! }
+//!
+//! // In the publishing module's `module_load`
+//! let lines = Channel::<ChatLine>::new();
+//! services::publish(world, "chat.lines", lines.clone())?;
+//!
+//! // In any other module
+//! if let Some(lines) = services::lookup::<Channel<ChatLine>>(world, "chat.lines") {
+//!     lines.send(ChatLine { player: 1, text: "hi".into() });
+//! }
+//! ```
+//!
+//! Services are keyed by their interned name and the [schema hash] of the
+//! endpoint, which covers the message types. A module built against an
+//! older message layout doesn't find the service rather than reading it
+//! with the wrong layout, and two versions can be published side by side
+//! under the same name.
+//!
+//! Endpoints are plain data behind an `Arc`, with no trait objects: a
+//! vtable lives in the code of the module that made it, and would dangle
+//! once that module is unloaded. Each module works with an endpoint using
+//! its own copy of the code.
+//!
+//! A service published while a module is loading belongs to that module
+//! and is withdrawn when it's unloaded, before its library is closed.
+//! Withdrawing closes the endpoint: copies other modules still hold stop
+//! accepting messages, so they know to look the service up again after a
+//! reload. Services published by the host live as long as the world.
+//!
+//! [schema hash]: Endpoint::SCHEMA_HASH
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use flecs_ecs::prelude::*;
+use thiserror::Error;
+
+/// A message type that can cross module boundaries
+pub trait Message: Send + 'static {
+    /// Hash of the type's layout, usually [`schema_hash`] of its definition.
+    /// Change it whenever the fields change.
+    const SCHEMA_HASH: u64;
+}
+
+/// FNV-1a hash of a schema description, for [`Message::SCHEMA_HASH`]
+pub const fn schema_hash(schema: &str) -> u64 {
+    let bytes = schema.as_bytes();
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = combine(hash, bytes[i] as u64);
+        i += 1;
+    }
+    hash
+}
+
+const fn combine(hash: u64, value: u64) -> u64 {
+    (hash ^ value).wrapping_mul(0x0000_0100_0000_01b3)
+}
+
+/// State shared by every copy of an endpoint
+struct Shared<Q> {
+    open: AtomicBool,
+    state: Mutex<Q>,
+}
+
+impl<Q> Shared<Q> {
+    fn new(state: Q) -> Arc<Self> {
+        Arc::new(Self {
+            open: AtomicBool::new(true),
+            state: Mutex::new(state),
+        })
+    }
+
+    fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Q> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Close the endpoint behind a registry's reference and drop the reference
+///
+/// # Safety
+/// `ptr` must come from `Arc::<Shared<Q>>::into_raw`.
+unsafe fn release<Q>(ptr: *const ()) {
+    let shared = unsafe { Arc::from_raw(ptr.cast::<Shared<Q>>()) };
+    shared.open.store(false, Ordering::Release);
+}
+
+/// Another reference to the endpoint behind a registry's reference
+///
+/// # Safety
+/// `ptr` must come from `Arc::<Shared<Q>>::into_raw` and still be held.
+unsafe fn clone_raw<Q>(ptr: *const ()) -> Arc<Shared<Q>> {
+    let ptr = ptr.cast::<Shared<Q>>();
+    unsafe {
+        Arc::increment_strong_count(ptr);
+        Arc::from_raw(ptr)
+    }
+}
+
+/// Something a module can publish as a service
+pub trait Endpoint: Clone + Sized + 'static {
+    /// Hash of the endpoint kind and its message types
+    const SCHEMA_HASH: u64;
+
+    #[doc(hidden)]
+    fn into_raw(self) -> *const ();
+
+    /// # Safety
+    /// `ptr` must come from [`Endpoint::into_raw`] of the same type.
+    #[doc(hidden)]
+    unsafe fn from_raw(ptr: *const ()) -> Self;
+
+    /// Closes the endpoint and drops the reference `ptr` holds.
+    ///
+    /// # Safety
+    /// `ptr` must come from [`Endpoint::into_raw`] of the same type.
+    #[doc(hidden)]
+    unsafe fn release(ptr: *const ());
+}
+
+/// A queue of messages
+///
+/// Clones share the queue.
+pub struct Channel<T> {
+    shared: Arc<Shared<VecDeque<T>>>,
+}
+
+impl<T: Message> Channel<T> {
+    pub fn new() -> Self {
+        Self {
+            shared: Shared::new(VecDeque::new()),
+        }
+    }
+
+    /// Queue a message. Returns `false`, dropping it, if the channel was
+    /// withdrawn.
+    pub fn send(&self, message: T) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+        self.shared.lock().push_back(message);
+        true
+    }
+
+    /// Take every queued message, oldest first
+    pub fn drain(&self) -> Vec<T> {
+        self.shared.lock().drain(..).collect()
+    }
+
+    /// Whether the channel is still published (or was never published)
+    pub fn is_open(&self) -> bool {
+        self.shared.is_open()
+    }
+}
+
+impl<T: Message> Default for Channel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T: Message> Endpoint for Channel<T> {
+    const SCHEMA_HASH: u64 = combine(schema_hash("channel"), T::SCHEMA_HASH);
+
+    fn into_raw(self) -> *const () {
+        Arc::into_raw(self.shared).cast()
+    }
+
+    unsafe fn from_raw(ptr: *const ()) -> Self {
+        Self {
+            shared: unsafe { clone_raw(ptr) },
+        }
+    }
+
+    unsafe fn release(ptr: *const ()) {
+        unsafe { release::<VecDeque<T>>(ptr) };
+    }
+}
+
+/// Identifies a request sent to a [`RequestQueue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ticket(u64);
+
+struct Requests<Req, Resp> {
+    next: u64,
+    pending: VecDeque<(Ticket, Req)>,
+    responses: HashMap<Ticket, Resp>,
+}
+
+/// Requests answered by the module that published the queue
+///
+/// Clients [`request`](Self::request) and later
+/// [`take_response`](Self::take_response); the publisher answers
+/// everything queued with [`serve`](Self::serve), typically once per tick.
+/// Clones share the queue.
+pub struct RequestQueue<Req, Resp> {
+    shared: Arc<Shared<Requests<Req, Resp>>>,
+}
+
+impl<Req: Message, Resp: Message> RequestQueue<Req, Resp> {
+    pub fn new() -> Self {
+        Self {
+            shared: Shared::new(Requests {
+                next: 0,
+                pending: VecDeque::new(),
+                responses: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Queue a request. Returns `None`, dropping it, if the queue was
+    /// withdrawn.
+    pub fn request(&self, request: Req) -> Option<Ticket> {
+        if !self.is_open() {
+            return None;
+        }
+        let mut requests = self.shared.lock();
+        let ticket = Ticket(requests.next);
+        requests.next += 1;
+        requests.pending.push_back((ticket, request));
+        drop(requests);
+        Some(ticket)
+    }
+
+    /// Answer every queued request in order. Returns how many were answered.
+    pub fn serve(&self, mut answer: impl FnMut(Req) -> Resp) -> usize {
+        let pending: Vec<_> = self.shared.lock().pending.drain(..).collect();
+        let answered = pending.len();
+        // Answered outside the lock so `answer` may queue requests itself
+        let responses: Vec<_> = pending
+            .into_iter()
+            .map(|(ticket, request)| (ticket, answer(request)))
+            .collect();
+        self.shared.lock().responses.extend(responses);
+        answered
+    }
+
+    /// The response to a request, once it's been answered
+    pub fn take_response(&self, ticket: Ticket) -> Option<Resp> {
+        self.shared.lock().responses.remove(&ticket)
+    }
+
+    /// Whether the queue is still published (or was never published)
+    pub fn is_open(&self) -> bool {
+        self.shared.is_open()
+    }
+}
+
+impl<Req: Message, Resp: Message> Default for RequestQueue<Req, Resp> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Req, Resp> Clone for RequestQueue<Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<Req: Message, Resp: Message> Endpoint for RequestQueue<Req, Resp> {
+    const SCHEMA_HASH: u64 = combine(
+        combine(schema_hash("request"), Req::SCHEMA_HASH),
+        Resp::SCHEMA_HASH,
+    );
+
+    fn into_raw(self) -> *const () {
+        Arc::into_raw(self.shared).cast()
+    }
+
+    unsafe fn from_raw(ptr: *const ()) -> Self {
+        Self {
+            shared: unsafe { clone_raw(ptr) },
+        }
+    }
+
+    unsafe fn release(ptr: *const ()) {
+        unsafe { release::<Requests<Req, Resp>>(ptr) };
+    }
+}
+
+/// Errors that can occur publishing a service
+#[derive(Error, Debug)]
+pub enum ServiceError {
+    #[error("Service '{name}' is already published by {}", owner.as_deref().unwrap_or("the host"))]
+    AlreadyPublished { name: String, owner: Option<String> },
+}
+
+/// Interned service name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ServiceName(u32);
+
+/// A published endpoint
+struct Service {
+    /// Module that published it, `None` for the host
+    owner: Option<String>,
+    ptr: *const (),
+    /// [`Endpoint::release`] of the endpoint's type, in the publisher's code
+    release: unsafe fn(*const ()),
+}
+
+// Endpoints are an `Arc` of a `Mutex`, and their messages are `Send`
+unsafe impl Send for Service {}
+unsafe impl Sync for Service {}
+
+/// Singleton: Services published to the world
+#[derive(Component, Default)]
+pub struct ServiceRegistry {
+    names: Vec<Box<str>>,
+    ids: HashMap<Box<str>, ServiceName>,
+    services: HashMap<(ServiceName, u64), Service>,
+    /// Module whose `module_load` is running, set by the loader
+    loading: Option<String>,
+}
+
+impl ServiceRegistry {
+    /// The interned id of a name
+    ///
+    /// # Panics
+    /// If more than `u32::MAX` names are interned.
+    pub fn intern(&mut self, name: &str) -> ServiceName {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = ServiceName(u32::try_from(self.names.len()).expect("too many service names"));
+        self.names.push(name.into());
+        self.ids.insert(name.into(), id);
+        id
+    }
+
+    pub fn name(&self, id: ServiceName) -> &str {
+        &self.names[id.0 as usize]
+    }
+
+    /// Publish an endpoint, owned by the module being loaded if any
+    pub fn publish<E: Endpoint>(&mut self, name: &str, endpoint: E) -> Result<(), ServiceError> {
+        let id = self.intern(name);
+        if let Some(existing) = self.services.get(&(id, E::SCHEMA_HASH)) {
+            return Err(ServiceError::AlreadyPublished {
+                name: name.to_string(),
+                owner: existing.owner.clone(),
+            });
+        }
+        let service = Service {
+            owner: self.loading.clone(),
+            ptr: endpoint.into_raw(),
+            release: E::release,
+        };
+        self.services.insert((id, E::SCHEMA_HASH), service);
+        Ok(())
+    }
+
+    /// The endpoint published under `name` with `E`'s schema
+    pub fn lookup<E: Endpoint>(&self, name: &str) -> Option<E> {
+        self.lookup_id(*self.ids.get(name)?)
+    }
+
+    /// [`lookup`](Self::lookup) by interned name
+    pub fn lookup_id<E: Endpoint>(&self, id: ServiceName) -> Option<E> {
+        let service = self.services.get(&(id, E::SCHEMA_HASH))?;
+        // The schema hash matches, so it was published as an `E`
+        Some(unsafe { E::from_raw(service.ptr) })
+    }
+
+    /// Withdraw the service published under `name` with `E`'s schema,
+    /// closing it. Returns `false` if there was none.
+    pub fn withdraw<E: Endpoint>(&mut self, name: &str) -> bool {
+        let Some(&id) = self.ids.get(name) else {
+            return false;
+        };
+        self.services
+            .remove(&(id, E::SCHEMA_HASH))
+            .map(|service| unsafe { (service.release)(service.ptr) })
+            .is_some()
+    }
+
+    /// Names of the services `module` published
+    pub fn published_by(&self, module: &str) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .services
+            .iter()
+            .filter(|(_, service)| service.owner.as_deref() == Some(module))
+            .map(|(&(id, _), _)| self.name(id))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Withdraw every service `module` published. Returns how many there
+    /// were.
+    fn withdraw_module(&mut self, module: &str) -> usize {
+        let before = self.services.len();
+        self.services.retain(|_, service| {
+            if service.owner.as_deref() != Some(module) {
+                return true;
+            }
+            unsafe { (service.release)(service.ptr) };
+            false
+        });
+        before - self.services.len()
+    }
+}
+
+impl Drop for ServiceRegistry {
+    fn drop(&mut self) {
+        for (_, service) in self.services.drain() {
+            unsafe { (service.release)(service.ptr) };
+        }
+    }
+}
+
+/// Run `f` on the world's registry, creating it if needed
+pub fn with_registry<R>(world: &World, f: impl FnOnce(&mut ServiceRegistry) -> R) -> R {
+    if world.try_get::<&ServiceRegistry>(|_| ()).is_none() {
+        world.set(ServiceRegistry::default());
+    }
+    world.get::<&mut ServiceRegistry>(f)
+}
+
+/// Publish an endpoint to the world, see [`ServiceRegistry::publish`]
+pub fn publish<E: Endpoint>(world: &World, name: &str, endpoint: E) -> Result<(), ServiceError> {
+    with_registry(world, |registry| registry.publish(name, endpoint))
+}
+
+/// Look up an endpoint in the world, see [`ServiceRegistry::lookup`]
+pub fn lookup<E: Endpoint>(world: &World, name: &str) -> Option<E> {
+    world
+        .try_get::<&ServiceRegistry>(|registry| registry.lookup(name))
+        .flatten()
+}
+
+/// Mark `module` as loading, so services published meanwhile are its own
+pub(crate) fn set_loading(world: &World, module: Option<&str>) {
+    with_registry(world, |registry| {
+        registry.loading = module.map(str::to_string)
+    });
+}
+
+/// Withdraw the services `module` published
+pub(crate) fn withdraw_module(world: &World, module: &str) -> usize {
+    world
+        .try_get::<&mut ServiceRegistry>(|registry| registry.withdraw_module(module))
+        .unwrap_or(0)
+}
@@ -0,0 +1,393 @@
+//! Redstone signal propagation: levers, torches and wire.
+//!
+//! Every redstone block is an entity holding its [`Redstone`] kind and
+//! current [`SignalLevel`], so scripts and systems can read signal levels
+//! like any other component. [`RedstoneModule`] registers both.
+//!
+//! This crate decides levels; the server owns the blocks. Each tick it
+//! snapshots its redstone blocks into a map of [`RedstoneBlock`]s and calls
+//! [`step`] once per region, applying the region's [`SignalChange`]s before
+//! stepping the next one. A block's next level only depends on blocks at
+//! most two away (a torch reads the block it's attached to, which reads its
+//! own neighbors), so regions that far apart never read each other's blocks
+//! and can be stepped in any order.
+//!
+//! The rules are simplified from vanilla:
+//! - a lever outputs 15 while `powered`
+//! - a torch outputs 15 unless the block it's attached to is powered
+//! - wire is 15 next to a powered lever or lit torch, otherwise one less
+//!   than the strongest wire beside it (level, one up or one down)
+//! - a block is powered by a lever attached to it or by powered wire on top
+
+use core::hash::{BuildHasher, Hash};
+use std::collections::HashMap;
+
+use flecs_ecs::prelude::*;
+use mc_data::BlockState;
+use serde::{Deserialize, Serialize};
+
+/// Strongest signal level.
+pub const MAX_SIGNAL: u8 = 15;
+
+/// A step from a block to one of its neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Offset {
+    dx: i32,
+    dy: i32,
+    dz: i32,
+}
+
+impl Offset {
+    const fn new(dx: i32, dy: i32, dz: i32) -> Self {
+        Self { dx, dy, dz }
+    }
+
+    /// The step in the opposite direction.
+    const fn reverse(self) -> Self {
+        Self::new(-self.dx, -self.dy, -self.dz)
+    }
+
+    /// The block this step leads to from `pos`.
+    fn apply<P: BlockOffset>(self, pos: P) -> P {
+        pos.offset(self.dx, self.dy, self.dz)
+    }
+}
+
+const UP: Offset = Offset::new(0, 1, 0);
+const DOWN: Offset = Offset::new(0, -1, 0);
+
+/// Horizontal neighbors.
+const HORIZONTAL: [Offset; 4] = [
+    Offset::new(0, 0, -1),
+    Offset::new(0, 0, 1),
+    Offset::new(-1, 0, 0),
+    Offset::new(1, 0, 0),
+];
+
+/// All six face neighbors.
+const ADJACENT: [Offset; 6] = [
+    DOWN,
+    UP,
+    HORIZONTAL[0],
+    HORIZONTAL[1],
+    HORIZONTAL[2],
+    HORIZONTAL[3],
+];
+
+/// Block coordinates the server keys its redstone blocks by.
+pub trait BlockOffset: Copy + Eq + Hash {
+    /// The position `dx`, `dy`, `dz` blocks away.
+    #[must_use]
+    fn offset(self, dx: i32, dy: i32, dz: i32) -> Self;
+}
+
+/// Kind of redstone block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedstoneKind {
+    Wire,
+    Torch,
+    Lever,
+}
+
+impl RedstoneKind {
+    /// The kind of a block state, if it takes part in redstone.
+    pub fn of(state: BlockState) -> Option<Self> {
+        match state.block_name()? {
+            "minecraft:redstone_wire" => Some(Self::Wire),
+            "minecraft:redstone_torch" | "minecraft:redstone_wall_torch" => Some(Self::Torch),
+            "minecraft:lever" => Some(Self::Lever),
+            _ => None,
+        }
+    }
+
+    /// Signal level encoded in a block state's properties.
+    pub fn level(self, state: BlockState) -> u8 {
+        let on = |property| state.property(property) == Some("true");
+        match self {
+            Self::Wire => state
+                .property("power")
+                .and_then(|power| power.parse().ok())
+                .unwrap_or(0),
+            Self::Torch if on("lit") => MAX_SIGNAL,
+            Self::Lever if on("powered") => MAX_SIGNAL,
+            Self::Torch | Self::Lever => 0,
+        }
+    }
+
+    /// The block state showing a signal level.
+    pub fn with_level(self, state: BlockState, level: u8) -> BlockState {
+        let changed = match self {
+            Self::Wire => state.with_property("power", &level.to_string()),
+            Self::Torch => state.with_property("lit", if level > 0 { "true" } else { "false" }),
+            // Levers are only switched by players
+            Self::Lever => None,
+        };
+        changed.unwrap_or(state)
+    }
+}
+
+/// Redstone block marker.
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Redstone {
+    pub kind: RedstoneKind,
+}
+
+/// Signal level of a redstone block (0-15).
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignalLevel(pub u8);
+
+/// A redstone block as seen at the start of a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedstoneBlock {
+    pub kind: RedstoneKind,
+    pub state: BlockState,
+    pub level: u8,
+}
+
+impl RedstoneBlock {
+    /// A block at the level its state shows, if it takes part in redstone.
+    pub fn from_state(state: BlockState) -> Option<Self> {
+        let kind = RedstoneKind::of(state)?;
+        Some(Self {
+            kind,
+            state,
+            level: kind.level(state),
+        })
+    }
+}
+
+/// A redstone block whose level changed, with the state showing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalChange<P> {
+    pub pos: P,
+    pub state: BlockState,
+    pub level: u8,
+}
+
+/// Direction of a `facing` property value.
+fn facing(state: BlockState) -> Option<Offset> {
+    match state.property("facing")? {
+        "north" => Some(HORIZONTAL[0]),
+        "south" => Some(HORIZONTAL[1]),
+        "west" => Some(HORIZONTAL[2]),
+        "east" => Some(HORIZONTAL[3]),
+        _ => None,
+    }
+}
+
+/// The block a torch or lever is attached to.
+pub fn attached_to<P: BlockOffset>(kind: RedstoneKind, state: BlockState, pos: P) -> Option<P> {
+    let behind = || facing(state).map(|facing| facing.reverse().apply(pos));
+    match kind {
+        RedstoneKind::Wire => None,
+        RedstoneKind::Torch if state.block_name() == Some("minecraft:redstone_torch") => {
+            Some(DOWN.apply(pos))
+        }
+        RedstoneKind::Torch => behind(),
+        RedstoneKind::Lever => match state.property("face")? {
+            "floor" => Some(DOWN.apply(pos)),
+            "ceiling" => Some(UP.apply(pos)),
+            _ => behind(),
+        },
+    }
+}
+
+/// Whether a solid block is powered by an attached lever or wire on top.
+fn is_powered<P: BlockOffset, S: BuildHasher>(
+    blocks: &HashMap<P, RedstoneBlock, S>,
+    pos: P,
+) -> bool {
+    let wire_on_top = blocks
+        .get(&UP.apply(pos))
+        .is_some_and(|b| b.kind == RedstoneKind::Wire && b.level > 0);
+    wire_on_top
+        || ADJACENT.iter().any(|offset| {
+            let lever_pos = offset.apply(pos);
+            blocks.get(&lever_pos).is_some_and(|b| {
+                b.kind == RedstoneKind::Lever
+                    && b.level > 0
+                    && attached_to(b.kind, b.state, lever_pos) == Some(pos)
+            })
+        })
+}
+
+fn wire_level<P: BlockOffset, S: BuildHasher>(blocks: &HashMap<P, RedstoneBlock, S>, pos: P) -> u8 {
+    let next_to_source = ADJACENT.iter().any(|offset| {
+        blocks.get(&offset.apply(pos)).is_some_and(|b| {
+            matches!(b.kind, RedstoneKind::Torch | RedstoneKind::Lever) && b.level > 0
+        })
+    });
+    if next_to_source {
+        return MAX_SIGNAL;
+    }
+
+    HORIZONTAL
+        .iter()
+        .flat_map(|offset| (-1..=1).map(move |dy| offset.apply(pos).offset(0, dy, 0)))
+        .filter_map(|wire_pos| blocks.get(&wire_pos))
+        .filter(|b| b.kind == RedstoneKind::Wire)
+        .map(|b| b.level.saturating_sub(1))
+        .max()
+        .unwrap_or(0)
+}
+
+/// The level the block at `pos` has after one step, from its neighbors'
+/// current levels.
+pub fn next_level<P: BlockOffset, S: BuildHasher>(
+    blocks: &HashMap<P, RedstoneBlock, S>,
+    pos: P,
+    block: RedstoneBlock,
+) -> u8 {
+    match block.kind {
+        RedstoneKind::Lever => block.kind.level(block.state),
+        RedstoneKind::Torch => {
+            let attached = attached_to(block.kind, block.state, pos);
+            if attached.is_some_and(|attached| is_powered(blocks, attached)) {
+                0
+            } else {
+                MAX_SIGNAL
+            }
+        }
+        RedstoneKind::Wire => wire_level(blocks, pos),
+    }
+}
+
+/// Advance the blocks at `positions` by one step.
+///
+/// Every new level is computed from `blocks` before any is applied, so the
+/// result doesn't depend on the order of `positions`. Positions missing from
+/// `blocks` are skipped.
+pub fn step<P: BlockOffset, S: BuildHasher>(
+    blocks: &HashMap<P, RedstoneBlock, S>,
+    positions: impl IntoIterator<Item = P>,
+) -> Vec<SignalChange<P>> {
+    positions
+        .into_iter()
+        .filter_map(|pos| {
+            let block = blocks.get(&pos)?;
+            let level = next_level(blocks, pos, *block);
+            (level != block.level).then(|| SignalChange {
+                pos,
+                state: block.kind.with_level(block.state, level),
+                level,
+            })
+        })
+        .collect()
+}
+
+/// Redstone module for Flecs.
+#[derive(Component)]
+pub struct RedstoneModule;
+
+impl Module for RedstoneModule {
+    fn module(world: &World) {
+        world.module::<RedstoneModule>("redstone");
+
+        world.component::<Redstone>();
+        world.component::<SignalLevel>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mc_data::blocks;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct Pos {
+        x: i32,
+        y: i32,
+        z: i32,
+    }
+
+    impl BlockOffset for Pos {
+        fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+            Self {
+                x: self.x + dx,
+                y: self.y + dy,
+                z: self.z + dz,
+            }
+        }
+    }
+
+    const fn pos(x: i32, y: i32, z: i32) -> Pos {
+        Pos { x, y, z }
+    }
+
+    fn block(state: BlockState) -> RedstoneBlock {
+        RedstoneBlock::from_state(state).unwrap()
+    }
+
+    /// Step until nothing changes, applying each step's changes.
+    fn settle(blocks: &mut HashMap<Pos, RedstoneBlock>) {
+        for _ in 0..32 {
+            let changes = step(blocks, blocks.keys().copied());
+            if changes.is_empty() {
+                return;
+            }
+            for change in changes {
+                let block = blocks.get_mut(&change.pos).unwrap();
+                block.state = change.state;
+                block.level = change.level;
+            }
+        }
+        panic!("redstone didn't settle");
+    }
+
+    #[test]
+    fn test_wire_fades_from_a_lever() {
+        let lever = blocks::LEVER
+            .with_property("face", "floor")
+            .and_then(|s| s.with_property("powered", "true"))
+            .unwrap();
+        let mut blocks = HashMap::new();
+        blocks.insert(pos(0, 0, 0), block(lever));
+        for x in 1..=3 {
+            blocks.insert(pos(x, 0, 0), block(blocks::REDSTONE_WIRE));
+        }
+
+        settle(&mut blocks);
+        let levels: Vec<u8> = (1..=3).map(|x| blocks[&pos(x, 0, 0)].level).collect();
+        assert_eq!(levels, [15, 14, 13]);
+        assert_eq!(
+            blocks[&pos(3, 0, 0)].state.property("power"),
+            Some("13"),
+            "the state shows the level"
+        );
+    }
+
+    #[test]
+    fn test_powered_block_turns_its_torch_off() {
+        let lever = blocks::LEVER
+            .with_property("face", "ceiling")
+            .and_then(|s| s.with_property("powered", "true"))
+            .unwrap();
+        let mut blocks = HashMap::new();
+        // Torch on top of the block at the origin, lever hanging below it
+        blocks.insert(pos(0, 1, 0), block(blocks::REDSTONE_TORCH));
+        blocks.insert(pos(0, -1, 0), block(lever));
+
+        let changes = step(&blocks, [pos(0, 1, 0), pos(0, -1, 0)]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].pos, pos(0, 1, 0));
+        assert_eq!(changes[0].level, 0);
+        assert_eq!(changes[0].state.property("lit"), Some("false"));
+    }
+
+    #[test]
+    fn test_step_ignores_position_order() {
+        let mut blocks = HashMap::new();
+        blocks.insert(pos(0, 0, 0), block(blocks::REDSTONE_TORCH));
+        for x in 1..=2 {
+            blocks.insert(pos(x, 0, 0), block(blocks::REDSTONE_WIRE));
+        }
+
+        let forward = step(&blocks, [pos(1, 0, 0), pos(2, 0, 0)]);
+        let mut backward = step(&blocks, [pos(2, 0, 0), pos(1, 0, 0)]);
+        backward.reverse();
+        assert_eq!(forward, backward);
+        // Only the wire beside the torch changes in the first step
+        assert_eq!(forward.len(), 1);
+    }
+}
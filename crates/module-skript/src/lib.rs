@@ -9,8 +9,10 @@
 //!
 //! See `plan/overview.md` for the implementation roadmap.
 
+mod registry;
 mod value;
 
+pub use registry::{LoadSummary, LoadedScript, ScriptRegistry};
 pub use value::Value;
 
 use flecs_ecs::prelude::*;
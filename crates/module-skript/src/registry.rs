@@ -0,0 +1,155 @@
+//! Skripts loaded from a directory, reloadable at runtime.
+//!
+//! Every `.sk` file in the directory is read and parsed; the ones that parse
+//! are kept by file name. [`ScriptRegistry::reload`] reads the directory
+//! again: a script that no longer parses keeps its last good version, so a
+//! typo doesn't unload a script that was running.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File extension of scripts.
+const EXTENSION: &str = "sk";
+
+/// A script that parsed.
+#[derive(Debug, Clone)]
+pub struct LoadedScript {
+    /// Source text, parsed again by whatever runs the script.
+    pub source: String,
+    /// Number of top-level items (events, commands, functions, options).
+    pub items: usize,
+}
+
+/// Outcome of loading a directory of scripts.
+#[derive(Debug, Default)]
+pub struct LoadSummary {
+    /// Scripts that were read and parsed.
+    pub loaded: usize,
+    /// Scripts that were loaded before but whose file is gone.
+    pub removed: usize,
+    /// Scripts that could not be read or parsed, with why.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Every script in a directory, by file name.
+#[derive(Debug)]
+pub struct ScriptRegistry {
+    dir: PathBuf,
+    scripts: BTreeMap<String, LoadedScript>,
+}
+
+impl ScriptRegistry {
+    /// An empty registry for the scripts in `dir`; call
+    /// [`reload`](Self::reload) to load them.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            scripts: BTreeMap::new(),
+        }
+    }
+
+    /// Directory scripts are loaded from.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Read every script in the directory again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory can't be listed; individual scripts
+    /// that fail end up in [`LoadSummary::failed`].
+    pub fn reload(&mut self) -> std::io::Result<LoadSummary> {
+        let mut summary = LoadSummary::default();
+        let mut found = BTreeMap::new();
+
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != EXTENSION) {
+                continue;
+            }
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match load(&path) {
+                Ok(script) => {
+                    summary.loaded += 1;
+                    found.insert(name, script);
+                }
+                Err(error) => {
+                    // Keep the last version that parsed
+                    if let Some(previous) = self.scripts.remove(&name) {
+                        found.insert(name.clone(), previous);
+                    }
+                    summary.failed.push((name, error));
+                }
+            }
+        }
+
+        summary.removed = self
+            .scripts
+            .keys()
+            .filter(|name| !found.contains_key(*name))
+            .count();
+        self.scripts = found;
+        Ok(summary)
+    }
+
+    /// A loaded script by file name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&LoadedScript> {
+        self.scripts.get(name)
+    }
+
+    /// File names of every loaded script, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scripts.keys().map(String::as_str)
+    }
+}
+
+/// Read and parse one script.
+fn load(path: &Path) -> Result<LoadedScript, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let items = skript_lang::parse(&source)
+        .map_err(|errors| errors.join("; "))?
+        .items
+        .len();
+    Ok(LoadedScript { source, items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOIN: &str = "on join:\n    send \"Hello!\" to player\n";
+
+    #[test]
+    fn test_reload_keeps_last_good_version() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("join.sk"), JOIN).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a script").unwrap();
+
+        let mut registry = ScriptRegistry::new(dir.path());
+        let summary = registry.reload().unwrap();
+        assert_eq!(summary.loaded, 1);
+        assert!(summary.failed.is_empty());
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["join.sk"]);
+
+        std::fs::write(
+            dir.path().join("join.sk"),
+            "on join:\n    send \"unterminated\n",
+        )
+        .unwrap();
+        let summary = registry.reload().unwrap();
+        assert_eq!(summary.loaded, 0);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(registry.get("join.sk").unwrap().source, JOIN);
+
+        std::fs::remove_file(dir.path().join("join.sk")).unwrap();
+        let summary = registry.reload().unwrap();
+        assert_eq!(summary.removed, 1);
+        assert_eq!(registry.names().count(), 0);
+    }
+}
@@ -1,6 +1,8 @@
 //! Runtime value types for Skript.
 
 use flecs_ecs::core::Entity;
+use mc_data::{Item, ItemArgument};
+use mc_protocol::ItemStack;
 
 /// A runtime value in Skript.
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -18,6 +20,8 @@ pub enum Value {
     Player(Entity),
     /// Generic entity.
     Entity(Entity),
+    /// Item stack.
+    Item(ItemStack),
     /// List of values.
     List(Vec<Value>),
 }
@@ -32,6 +36,7 @@ impl Value {
             Self::Number(n) => *n != 0.0,
             Self::Text(s) => !s.is_empty(),
             Self::List(l) => !l.is_empty(),
+            Self::Item(stack) => !stack.is_empty(),
             Self::Player(_) | Self::Entity(_) => true,
         }
     }
@@ -51,6 +56,7 @@ impl Value {
             Self::Number(n) => *n,
             Self::Text(s) => s.parse().unwrap_or(0.0),
             Self::List(l) => l.len() as f64,
+            Self::Item(stack) => f64::from(stack.count),
             Self::Player(_) | Self::Entity(_) => 0.0,
         }
     }
@@ -65,6 +71,22 @@ impl Value {
             Self::Text(s) => s.clone(),
             Self::Player(e) | Self::Entity(e) => format!("entity:{}", e.0),
             Self::List(l) => l.iter().map(Self::as_text).collect::<Vec<_>>().join(", "),
+            Self::Item(stack) => {
+                let name = Item::new(stack.item).name().unwrap_or("unknown");
+                format!("{} of {name}", stack.count)
+            }
+        }
+    }
+
+    /// Convert to an item stack. Text is read as an item literal with an
+    /// optional SNBT tag, e.g. `"diamond_sword{display:{Name:'\"Excalibur\"'}}"`,
+    /// giving a stack of one.
+    #[must_use]
+    pub fn as_item(&self) -> Option<ItemStack> {
+        match self {
+            Self::Item(stack) => Some(stack.clone()),
+            Self::Text(s) => s.parse::<ItemArgument>().ok().map(|item| item.stack(1)),
+            _ => None,
         }
     }
 
@@ -77,6 +99,7 @@ impl Value {
             (Self::Number(a), Self::Number(b)) => (a - b).abs() < f64::EPSILON,
             (Self::Text(a), Self::Text(b)) => a.eq_ignore_ascii_case(b),
             (Self::Player(a), Self::Player(b)) | (Self::Entity(a), Self::Entity(b)) => a == b,
+            (Self::Item(a), Self::Item(b)) => a == b,
             // Cross-type: try numeric comparison
             (Self::Number(_), Self::Text(_)) | (Self::Text(_), Self::Number(_)) => {
                 (self.as_number() - other.as_number()).abs() < f64::EPSILON
@@ -125,4 +148,16 @@ mod tests {
         assert!(Value::Text("Hello".to_string()).equals(&Value::Text("hello".to_string())));
         assert!(Value::Number(42.0).equals(&Value::Text("42".to_string())));
     }
+
+    #[test]
+    fn test_item_conversion() {
+        let stack = ItemStack::new(1, 3);
+        let value = Value::Item(stack.clone());
+        assert!(value.as_boolean());
+        assert!((value.as_number() - 3.0).abs() < f64::EPSILON);
+        assert_eq!(value.as_item(), Some(stack));
+        assert!(!Value::Item(ItemStack::EMPTY).as_boolean());
+        assert_eq!(Value::Text("no_such_item".to_string()).as_item(), None);
+        assert_eq!(Value::Number(1.0).as_item(), None);
+    }
 }
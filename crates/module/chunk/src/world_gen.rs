@@ -7,7 +7,7 @@
 
 use byteorder::{BigEndian, WriteBytesExt};
 use bytes::Bytes;
-use mc_protocol::write_varint;
+use mc_protocol::{BitSet, Encode, PaletteFormat, write_paletted, write_varint};
 
 // ============================================================================
 // Noise Implementation (Simplex-like)
@@ -236,38 +236,23 @@ pub fn create_dune_chunk(chunk_x: i32, chunk_z: i32) -> eyre::Result<Bytes> {
     write_varint(&mut data, 0)?;
 
     // Light Data - 4 BitSets + 2 lists of byte arrays
-    // Format: each BitSet is varint(num_longs) + longs as big-endian i64s
+    // BitSet bit index is section index + 1 (because of the extra section below)
 
     // Sky light mask - sections 4-24 (Y 0 to Y 320) have sky light
-    // BitSet bit index corresponds to section index + 1 (because of the extra section below)
-    let mut sky_mask: u64 = 0;
-    for i in 5..=25 {
-        sky_mask |= 1u64 << i;
-    }
-    write_varint(&mut data, 1)?; // 1 long in the bitset
-    data.write_i64::<BigEndian>(sky_mask as i64)?;
+    let sky_mask = BitSet::from_range(5..26);
+    sky_mask.encode(&mut data)?;
 
     // Block light mask - empty (no block light)
-    write_varint(&mut data, 0)?;
+    BitSet::new().encode(&mut data)?;
 
     // Empty sky light mask - sections 0-4 are empty (below Y 0)
-    let mut empty_sky_mask: u64 = 0;
-    for i in 0..5 {
-        empty_sky_mask |= 1u64 << i;
-    }
-    write_varint(&mut data, 1)?;
-    data.write_i64::<BigEndian>(empty_sky_mask as i64)?;
+    BitSet::from_range(0..5).encode(&mut data)?;
 
     // Empty block light mask - all sections have empty block light
-    let mut empty_block_mask: u64 = 0;
-    for i in 0..26 {
-        empty_block_mask |= 1u64 << i;
-    }
-    write_varint(&mut data, 1)?;
-    data.write_i64::<BigEndian>(empty_block_mask as i64)?;
+    BitSet::from_range(0..26).encode(&mut data)?;
 
     // Sky Light Arrays - list of byte arrays for sections with sky light
-    let sky_section_count = (5..=25).count();
+    let sky_section_count = sky_mask.count();
     write_varint(&mut data, sky_section_count as i32)?;
 
     let full_light = vec![0xFFu8; 2048];
@@ -288,6 +273,11 @@ fn create_dune_sections(chunk_x: i32, chunk_z: i32) -> Vec<u8> {
     let config = DuneConfig::default();
     let mut data = Vec::new();
 
+    // Biome IDs follow synced registry order
+    let plains = mc_data::registry("minecraft:worldgen/biome")
+        .and_then(|registry| registry.entry_id("minecraft:plains"))
+        .unwrap_or(0);
+
     // Pre-calculate heightmap for this chunk
     let mut heights = [[0i32; 16]; 16];
     for lz in 0..16 {
@@ -298,13 +288,16 @@ fn create_dune_sections(chunk_x: i32, chunk_z: i32) -> Vec<u8> {
         }
     }
 
+    let block_format = PaletteFormat::blocks(mc_data::BlockState::direct_bits());
+    let biome_format = PaletteFormat::biomes(
+        mc_data::registry("minecraft:worldgen/biome").map_or(0, mc_data::RegistryData::direct_bits),
+    );
+
     for section_y in 0..24 {
         let section_min_y = (section_y as i32 - 4) * 16; // Section 0 = Y -64, Section 4 = Y 0
 
-        // Count non-air blocks in this section
-        let mut block_count: i16 = 0;
-        let mut blocks_in_section = [[[0u16; 16]; 16]; 16];
-
+        // Blocks indexed (y * 16 + z) * 16 + x
+        let mut states = Vec::with_capacity(4096);
         for local_y in 0..16 {
             let world_y = section_min_y + local_y as i32;
             for local_z in 0..16 {
@@ -314,119 +307,25 @@ fn create_dune_sections(chunk_x: i32, chunk_z: i32) -> Vec<u8> {
                     let world_z = chunk_z * 16 + local_z as i32;
 
                     let block_id = get_block_at(world_x, world_y, world_z, surface_height);
-                    blocks_in_section[local_y][local_z][local_x] = block_id;
-
-                    if block_id != blocks::AIR.id() {
-                        block_count += 1;
-                    }
+                    states.push(i32::from(block_id));
                 }
             }
         }
 
         // Write block count
+        let air = i32::from(blocks::AIR.id());
+        let block_count = states.iter().filter(|&&id| id != air).count() as i16;
         data.extend_from_slice(&block_count.to_be_bytes());
 
-        if block_count == 0 {
-            // Empty section - single value palette (air)
-            data.push(0); // bits per entry = 0
-            write_varint_vec(&mut data, blocks::AIR.id() as i32);
-            // No data array for single-value palette (ZeroBitStorage)
-        } else {
-            // Build palette
-            let mut palette: Vec<u16> = vec![blocks::AIR.id()];
-            let mut palette_map = std::collections::HashMap::new();
-            palette_map.insert(blocks::AIR.id(), 0u8);
-
-            for local_y in 0..16 {
-                for local_z in 0..16 {
-                    for local_x in 0..16 {
-                        let block_id = blocks_in_section[local_y][local_z][local_x];
-                        if !palette_map.contains_key(&block_id) {
-                            palette_map.insert(block_id, palette.len() as u8);
-                            palette.push(block_id);
-                        }
-                    }
-                }
-            }
-
-            let bits_per_entry = match palette.len() {
-                1 => 0,
-                2..=2 => 1,
-                3..=4 => 2,
-                5..=8 => 3,
-                9..=16 => 4,
-                17..=32 => 5,
-                33..=64 => 6,
-                65..=128 => 7,
-                _ => 8,
-            };
-
-            if bits_per_entry == 0 {
-                // Single block type - no data array needed
-                data.push(0);
-                write_varint_vec(&mut data, palette[0] as i32);
-                // No data array for single-value palette (ZeroBitStorage)
-            } else {
-                let bits = bits_per_entry.max(4); // Minecraft requires minimum 4 bits
-                data.push(bits);
-
-                // Write palette
-                write_varint_vec(&mut data, palette.len() as i32);
-                for block_id in &palette {
-                    write_varint_vec(&mut data, *block_id as i32);
-                }
-
-                // Write data array (fixed size - NO VarInt prefix!)
-                // The client calculates the array size from bits_per_entry:
-                // entries_per_long = 64 / bits (integer division)
-                // longs = ceil(4096 / entries_per_long)
-                let entries_per_long = 64 / bits as usize;
-                let mask = (1u64 << bits) - 1;
-
-                let mut bit_buffer: u64 = 0;
-                let mut entries_in_long = 0;
-
-                for local_y in 0..16 {
-                    for local_z in 0..16 {
-                        for local_x in 0..16 {
-                            let block_id = blocks_in_section[local_y][local_z][local_x];
-                            let palette_idx = palette_map[&block_id] as u64;
-
-                            // Pack entry at bit position (entries_in_long * bits)
-                            let bit_offset = entries_in_long * bits as usize;
-                            bit_buffer |= (palette_idx & mask) << bit_offset;
-                            entries_in_long += 1;
-
-                            // Flush when we've filled a long
-                            if entries_in_long == entries_per_long {
-                                data.extend_from_slice(&bit_buffer.to_be_bytes());
-                                bit_buffer = 0;
-                                entries_in_long = 0;
-                            }
-                        }
-                    }
-                }
-
-                // Flush remaining entries (partial long)
-                if entries_in_long > 0 {
-                    data.extend_from_slice(&bit_buffer.to_be_bytes());
-                }
-            }
-        }
+        write_paletted(&mut data, &block_format, &states).expect("paletted write");
 
         // Biomes - plains biome (single value)
-        // ID 0 corresponds to the first biome in the synced registry ("minecraft:plains")
-        data.push(0); // bits per entry = 0
-        write_varint_vec(&mut data, 0); // plains biome ID (first in registry)
+        write_paletted(&mut data, &biome_format, &[plains; 64]).expect("paletted write");
     }
 
     data
 }
 
-fn write_varint_vec(buf: &mut Vec<u8>, value: i32) {
-    write_varint(buf, value).expect("varint write");
-}
-
 // ============================================================================
 // Legacy superflat (kept for reference)
 // ============================================================================
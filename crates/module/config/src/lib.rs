@@ -1,7 +1,5 @@
 //! Configuration module - handles configuration phase
 
-mod registry;
-
 use bytes::{BufMut, Bytes, BytesMut};
 use flecs_ecs::prelude::*;
 use mc_protocol::{Decode, write_varint};
@@ -10,13 +8,6 @@ use module_login_components::{LoginComponentsModule, NeedsSpawnChunks};
 use module_network_components::{
     Connection, ConnectionState, NetworkComponentsModule, PacketBuffer, ProtocolState,
 };
-use registry::{
-    create_biome_registry, create_cat_variant_registry, create_chicken_variant_registry,
-    create_cow_variant_registry, create_damage_type_registry, create_dimension_type_registry,
-    create_frog_variant_registry, create_painting_variant_registry, create_pig_variant_registry,
-    create_wolf_sound_variant_registry, create_wolf_variant_registry,
-    create_zombie_nautilus_variant_registry,
-};
 use tracing::{debug, info};
 
 fn encode_packet(packet_id: i32, data: &[u8]) -> Bytes {
@@ -96,51 +87,14 @@ impl Module for ConfigurationModule {
     }
 }
 
-fn send_registry(buffer: &mut PacketBuffer, data: Vec<u8>) {
-    let mut cursor = std::io::Cursor::new(&data);
-    if let Ok(name) = <String as Decode>::decode(&mut cursor) {
-        debug!("Sending registry: {} ({} bytes)", name, data.len());
-    }
-    let packet = encode_packet(7, &data);
-    buffer.push_outgoing(packet);
-}
-
 fn send_registry_data(buffer: &mut PacketBuffer) {
-    if let Ok(data) = create_dimension_type_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_biome_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_damage_type_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_cat_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_chicken_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_cow_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_frog_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_pig_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_wolf_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_wolf_sound_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_zombie_nautilus_variant_registry() {
-        send_registry(buffer, data);
-    }
-    if let Ok(data) = create_painting_variant_registry() {
-        send_registry(buffer, data);
+    for registry in mc_data::all_registries() {
+        debug!(
+            "Sending registry: {} ({} bytes)",
+            registry.name,
+            registry.payload.len()
+        );
+        buffer.push_outgoing(encode_packet(7, registry.payload));
     }
 
     debug!("Sent all registry data");
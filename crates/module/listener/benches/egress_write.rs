@@ -0,0 +1,95 @@
+//! Connection writer backends sending a burst of chunk packets.
+//!
+//! A player joining gets a few hundred chunks at once, each packet tens of
+//! kilobytes, with small packets in between. This queues such a burst on a
+//! connection's channel and times how fast each `module-listener` writer
+//! backend gets it through a loopback socket to a reader that discards it.
+
+use std::hint::black_box;
+
+use bytes::Bytes;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use module_listener::writer::WriteBackend;
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+/// Chunks in a burst (view distance 8 is 289)
+const CHUNKS: usize = 289;
+
+/// Size of one framed chunk packet
+const CHUNK_SIZE: usize = 24 * 1024;
+
+/// Small packets (entity moves, block updates) sent after each chunk
+const SMALL_PER_CHUNK: usize = 4;
+
+fn burst() -> Vec<Bytes> {
+    let chunk = Bytes::from(vec![0xAB; CHUNK_SIZE]);
+    let small = Bytes::from(vec![0x01; 24]);
+    let mut packets = Vec::with_capacity(CHUNKS * (1 + SMALL_PER_CHUNK));
+    for _ in 0..CHUNKS {
+        packets.push(chunk.clone());
+        packets.extend(std::iter::repeat_n(small.clone(), SMALL_PER_CHUNK));
+    }
+    packets
+}
+
+/// Send `packets` over a fresh loopback connection and wait until the
+/// reader got all of it
+async fn send_burst(listener: &TcpListener, backend: WriteBackend, packets: &[Bytes]) -> usize {
+    let addr = listener.local_addr().unwrap();
+    let (client, accepted) = tokio::join!(TcpStream::connect(addr), listener.accept());
+    let mut client = client.unwrap();
+    let (server, _) = accepted.unwrap();
+
+    let reader = tokio::spawn(async move {
+        let mut buf = vec![0; 256 * 1024];
+        let mut total = 0;
+        loop {
+            match client.read(&mut buf).await {
+                Ok(0) | Err(_) => return total,
+                Ok(n) => total += n,
+            }
+        }
+    });
+
+    let (tx, mut rx) = mpsc::channel(packets.len());
+    for packet in packets {
+        tx.try_send(packet.clone()).unwrap();
+    }
+    drop(tx);
+
+    let (_read, mut write) = server.into_split();
+    backend.run(&mut write, &mut rx).await;
+    drop(write);
+    reader.await.unwrap()
+}
+
+fn bench_egress_write(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let listener = rt.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+    let packets = burst();
+    let bytes: usize = packets.iter().map(Bytes::len).sum();
+
+    let mut group = c.benchmark_group("egress_write/chunk_burst");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.sample_size(20);
+    for backend in [WriteBackend::WriteAll, WriteBackend::Vectored] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(backend),
+            &backend,
+            |b, &backend| {
+                b.iter(|| {
+                    let received = rt.block_on(send_burst(&listener, backend, &packets));
+                    assert_eq!(received, bytes);
+                    black_box(received)
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_egress_write);
+criterion_main!(benches);
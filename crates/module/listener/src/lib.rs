@@ -4,6 +4,11 @@
 //! 1. Creates network channels for ECS <-> async communication
 //! 2. Spawns a Tokio runtime with TCP listener
 //! 3. Routes packets between network and ECS
+//!
+//! How connections write their packets is chosen at runtime; see
+//! [`writer`].
+
+pub mod writer;
 
 use std::collections::HashMap;
 use std::io::Cursor;
@@ -19,10 +24,11 @@ use module_network_components::{
     DisconnectEvent, DisconnectIngress, IncomingPacket, NetworkChannels, NetworkComponentsModule,
     NetworkEgress, NetworkIngress, OutgoingPacket,
 };
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncReadExt;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info};
+use writer::WriteBackend;
 
 /// Active connections map (connection_id -> sender for that connection)
 type ConnectionMap = Arc<RwLock<HashMap<u64, tokio::sync::mpsc::Sender<Bytes>>>>;
@@ -127,6 +133,9 @@ async fn run_network(
 
     info!("Minecraft server listening on 0.0.0.0:{}", actual_port);
 
+    let backend = WriteBackend::from_env();
+    info!("Writing packets with the {backend} backend");
+
     let mut next_conn_id: u64 = 1;
 
     loop {
@@ -151,7 +160,7 @@ async fn run_network(
             }
 
             // Handle connection
-            let result = handle_connection(stream, conn_id, ingress_tx, rx).await;
+            let result = handle_connection(stream, conn_id, ingress_tx, rx, backend).await;
 
             // Unregister connection
             {
@@ -177,19 +186,13 @@ async fn handle_connection(
     conn_id: u64,
     ingress_tx: Sender<IncomingPacket>,
     mut egress_rx: tokio::sync::mpsc::Receiver<Bytes>,
+    backend: WriteBackend,
 ) -> eyre::Result<()> {
     let (mut reader, mut writer) = stream.into_split();
 
     // Spawn writer task
     let writer_handle = tokio::spawn(async move {
-        while let Some(data) = egress_rx.recv().await {
-            if writer.write_all(&data).await.is_err() {
-                break;
-            }
-            if writer.flush().await.is_err() {
-                break;
-            }
-        }
+        backend.run(&mut writer, &mut egress_rx).await;
     });
 
     // Read packets and send to ECS
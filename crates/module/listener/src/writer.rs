@@ -0,0 +1,123 @@
+//! Connection writer backends
+//!
+//! Each connection has a writer task draining the buffers the ECS queued
+//! for it. How it writes them is picked at startup with `RGB_WRITE_BACKEND`:
+//!
+//! - `write_all` (default): one `write_all` per buffer.
+//! - `vectored`: takes every buffer already queued (up to [`MAX_IOVECS`])
+//!   and hands them to the kernel in one `writev`, which saves a syscall per
+//!   buffer during chunk bursts. Worth it mostly on Linux, where tokio's
+//!   `TcpStream` writes vectored natively; elsewhere it still works.
+//!
+//! The `egress_write` benchmark in `rgb-benches` compares the two for mass
+//! chunk sends.
+
+use std::fmt;
+use std::io::IoSlice;
+use std::str::FromStr;
+
+use bytes::Bytes;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Environment variable selecting the backend
+pub const WRITE_BACKEND_ENV: &str = "RGB_WRITE_BACKEND";
+
+/// Most buffers passed to one vectored write (Linux's `UIO_MAXIOV` is 1024)
+pub const MAX_IOVECS: usize = 64;
+
+/// How a connection's writer task writes its buffers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteBackend {
+    /// One `write_all` per buffer
+    #[default]
+    WriteAll,
+    /// Queued buffers batched into vectored writes
+    Vectored,
+}
+
+impl WriteBackend {
+    /// The backend `RGB_WRITE_BACKEND` selects, or the default
+    pub fn from_env() -> Self {
+        let Ok(value) = std::env::var(WRITE_BACKEND_ENV) else {
+            return Self::default();
+        };
+        value.parse().unwrap_or_else(|e| {
+            warn!("Ignoring {WRITE_BACKEND_ENV}: {e}");
+            Self::default()
+        })
+    }
+
+    /// Write everything received on `rx` to `writer` until either closes
+    pub async fn run<W: AsyncWrite + Unpin>(self, writer: &mut W, rx: &mut mpsc::Receiver<Bytes>) {
+        match self {
+            Self::WriteAll => write_all_loop(writer, rx).await,
+            Self::Vectored => vectored_loop(writer, rx).await,
+        }
+    }
+}
+
+impl fmt::Display for WriteBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::WriteAll => "write_all",
+            Self::Vectored => "vectored",
+        })
+    }
+}
+
+impl FromStr for WriteBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "write_all" => Ok(Self::WriteAll),
+            "vectored" => Ok(Self::Vectored),
+            _ => Err(format!(
+                "unknown write backend {s:?}, expected write_all or vectored"
+            )),
+        }
+    }
+}
+
+async fn write_all_loop<W: AsyncWrite + Unpin>(writer: &mut W, rx: &mut mpsc::Receiver<Bytes>) {
+    while let Some(data) = rx.recv().await {
+        if writer.write_all(&data).await.is_err() {
+            break;
+        }
+        if writer.flush().await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn vectored_loop<W: AsyncWrite + Unpin>(writer: &mut W, rx: &mut mpsc::Receiver<Bytes>) {
+    let mut batch = Vec::with_capacity(MAX_IOVECS);
+    while rx.recv_many(&mut batch, MAX_IOVECS).await > 0 {
+        if write_batch(writer, &batch).await.is_err() {
+            break;
+        }
+        batch.clear();
+        if writer.flush().await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Write all of `batch` with as few vectored writes as the socket allows
+async fn write_batch<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    batch: &[Bytes],
+) -> std::io::Result<()> {
+    let mut slices: Vec<IoSlice<'_>> = batch.iter().map(|data| IoSlice::new(data)).collect();
+    let mut remaining = &mut slices[..];
+    while !remaining.is_empty() {
+        let written = writer.write_vectored(remaining).await?;
+        if written == 0 {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        IoSlice::advance_slices(&mut remaining, written);
+    }
+    Ok(())
+}
@@ -4,10 +4,11 @@
 //! Systems that operate on these components are in `module-login`.
 
 use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
 
 use flecs_ecs::prelude::*;
 use module_loader::register_module;
-use persist::PersistExt;
+use persist::{PersistExt, Schema};
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -51,7 +52,7 @@ pub struct EntityId {
 }
 
 /// Player position in world
-#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[derive(Component, Serialize, Deserialize, Schema, Debug, Clone, Copy, Default)]
 #[flecs(meta)]
 pub struct Position {
     pub x: f64,
@@ -166,7 +167,11 @@ impl Module for LoginComponentsModule {
         world.component::<Name>();
         world.component::<Uuid>();
         world.component::<EntityId>();
-        world.component::<Position>().persist::<Uuid>();
+        // Position changes on every movement packet; save it at most once a
+        // second, and whatever is held back when the player disconnects
+        world
+            .component::<Position>()
+            .persist_throttled::<Uuid>(Duration::from_secs(1));
         world.component::<Rotation>();
         world.component::<ChunkPosition>();
         world.component::<GameMode>();
@@ -178,6 +183,16 @@ impl Module for LoginComponentsModule {
             .component::<EntityIdCounter>()
             .add_trait::<flecs::Singleton>();
         world.set(EntityIdCounter::default());
+
+        // Save throttled positions that stopped changing
+        world
+            .system_named::<()>("PersistFlushDue")
+            .kind(id::<flecs::pipeline::OnStore>())
+            .run(|mut it| {
+                while it.next() {
+                    persist::flush_due(&it.world());
+                }
+            });
     }
 }
 
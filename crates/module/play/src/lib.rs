@@ -8,7 +8,9 @@ use mc_data::play::clientbound::{
     LevelChunkWithLight, Login as PlayLogin, PlayerPosition, SetActionBarText, SetChunkCacheCenter,
     SetTime,
 };
-use mc_protocol::{Decode, Encode, Packet, nbt, write_varint};
+use mc_data::play::serverbound::KeepAlive as ServerboundKeepAlive;
+use mc_protocol::{Decode, Encode, Packet, write_varint};
+use mc_text::Text;
 use module_chunk_components::{ChunkComponentsModule, ChunkData, ChunkIndex, ChunkPos};
 use module_loader::register_module;
 use module_login_components::{
@@ -119,10 +121,7 @@ fn create_chunk_batch_finished(count: i32) -> eyre::Result<Vec<u8>> {
 }
 
 fn create_action_bar_text(text: &str) -> eyre::Result<Vec<u8>> {
-    let compound = nbt! {
-        "text" => text,
-    };
-    Ok(compound.to_network_bytes())
+    Ok(Text::literal(text).to_network_bytes())
 }
 
 fn send_play_login(buffer: &mut PacketBuffer, entity_id: i32) {
@@ -173,6 +172,35 @@ fn send_action_bar(buffer: &mut PacketBuffer, text: &str) {
     }
 }
 
+// ============================================================================
+// Pipeline
+// ============================================================================
+//
+// OnLoad           (network ingress fills PacketBuffer)
+// PostLoad         HandleMovement
+// OnUpdate         SendKeepAlive, SendPositionActionBar
+//  └ PlaySpawnLogin    SendSpawnLogin
+//     └ PlaySpawnChunks   SendSpawnChunks
+//        └ PlaySpawnPosition SendSpawnPosition
+//           └ PlaySpawnFinish   EnterPlayState
+// OnStore          (network egress flushes PacketBuffer)
+//
+// Each step of the spawn sequence gets its own phase so its dependency is
+// declared with `DependsOn` instead of relying on declaration order. The
+// dashboard's `/api/systems` lists the registered systems with their phases.
+
+/// Phase: first spawn packets (login, center chunk)
+pub const PHASE_SPAWN_LOGIN: &str = "PlaySpawnLogin";
+/// Phase: spawn chunks around the player
+pub const PHASE_SPAWN_CHUNKS: &str = "PlaySpawnChunks";
+/// Phase: time and position, once the chunks are sent
+pub const PHASE_SPAWN_POSITION: &str = "PlaySpawnPosition";
+/// Phase: move the player into the play state
+pub const PHASE_SPAWN_FINISH: &str = "PlaySpawnFinish";
+
+/// Serverbound KeepAlive packet ID in Play state
+const KEEPALIVE_PACKET_ID: i32 = ServerboundKeepAlive::ID;
+
 // ============================================================================
 // Module
 // ============================================================================
@@ -191,70 +219,55 @@ impl Module for PlayModule {
         world.import::<TimeComponentsModule>();
         world.import::<NetworkComponentsModule>();
 
-        // Send spawn data to new players
+        // Spawn phases, each depending on the previous one
+        let spawn_login = world
+            .entity_named(PHASE_SPAWN_LOGIN)
+            .add(flecs::pipeline::Phase)
+            .depends_on(id::<flecs::pipeline::OnUpdate>());
+        let spawn_chunks = world
+            .entity_named(PHASE_SPAWN_CHUNKS)
+            .add(flecs::pipeline::Phase)
+            .depends_on(spawn_login);
+        let spawn_position = world
+            .entity_named(PHASE_SPAWN_POSITION)
+            .add(flecs::pipeline::Phase)
+            .depends_on(spawn_chunks);
+        let spawn_finish = world
+            .entity_named(PHASE_SPAWN_FINISH)
+            .add(flecs::pipeline::Phase)
+            .depends_on(spawn_position);
+
+        // --------------------------------------------------------------------
+        // PostLoad: consume play packets once network ingress has run
+        // --------------------------------------------------------------------
         world
-            .system_named::<(
-                &mut PacketBuffer,
-                &Position,
-                &EntityId,
-                &ChunkIndex,
-                &WorldTime,
-            )>("SendSpawnData")
-            .with(NeedsSpawnChunks)
+            .system_named::<(&mut PacketBuffer, &mut Position, &mut Rotation)>("HandleMovement")
             .with(Connection)
-            .run(|mut it| {
-                while it.next() {
-                    let mut buffer = it.field_mut::<PacketBuffer>(0);
-                    let positions = it.field::<Position>(1);
-                    let entity_ids = it.field::<EntityId>(2);
-                    let chunk_index = &it.field::<ChunkIndex>(3)[0];
-                    let world_time = &it.field::<WorldTime>(4)[0];
-
-                    for i in it.iter() {
-                        let pos = &positions[i];
-                        let entity_id = &entity_ids[i];
-                        let buf = &mut buffer[i];
-
-                        send_play_login(buf, entity_id.value);
-                        send_game_event_start_waiting(buf);
-
-                        let (cx, cz) = pos.chunk_pos();
-                        send_set_center_chunk(buf, cx, cz);
-
-                        let chunks = collect_chunks_for_player(chunk_index, 8, it.world());
-                        send_chunks_to_buffer(buf, &chunks);
-
-                        send_set_time(buf, world_time.world_age, world_time.time_of_day);
-                        send_player_position(buf, pos.x, pos.y, pos.z, 1);
-                        send_keepalive(buf);
-
-                        let entity = it.entity(i);
-                        entity.remove(NeedsSpawnChunks);
-                        entity.add(InPlayState);
-
-                        tracing::info!("Player entered play state");
-                    }
-                }
-            });
+            .with(InPlayState)
+            .kind(id::<flecs::pipeline::PostLoad>())
+            .each(|(buffer, pos, rot)| handle_movement(buffer, pos, rot));
 
-        // Periodic keepalive
+        // --------------------------------------------------------------------
+        // OnUpdate: periodic packets for players in play
+        // --------------------------------------------------------------------
         world
             .system_named::<(&mut PacketBuffer, &WorldTime)>("SendKeepAlive")
             .with(Connection)
             .with(InPlayState)
+            .kind(id::<flecs::pipeline::OnUpdate>())
             .each(|(buffer, world_time)| {
                 if world_time.world_age % 300 == 0 {
                     send_keepalive(buffer);
                 }
             });
 
-        // Send position and TPS to action bar
         world
             .system_named::<(&mut PacketBuffer, &Position, &WorldTime, &TpsTracker)>(
                 "SendPositionActionBar",
             )
             .with(Connection)
             .with(InPlayState)
+            .kind(id::<flecs::pipeline::OnUpdate>())
             .each(|(buffer, pos, world_time, tps)| {
                 if world_time.world_age % 10 == 0 {
                     let text = format!(
@@ -265,75 +278,118 @@ impl Module for PlayModule {
                 }
             });
 
-        // Handle player movement packets directly (without packet dispatch)
+        // --------------------------------------------------------------------
+        // Spawn sequence for players that just finished configuration
+        // --------------------------------------------------------------------
         world
-            .system_named::<(&mut PacketBuffer, &mut Position, &mut Rotation)>("HandleMovement")
+            .system_named::<(&mut PacketBuffer, &Position, &EntityId)>("SendSpawnLogin")
+            .with(NeedsSpawnChunks)
             .with(Connection)
-            .with(InPlayState)
-            .each(|(buffer, pos, rot)| {
-                while let Some((packet_id, data)) = buffer.pop_incoming() {
-                    let mut cursor = std::io::Cursor::new(&data[..]);
-                    match packet_id {
-                        0x1D => {
-                            // MovePlayerPos
-                            if let (Ok(x), Ok(y), Ok(z)) = (
-                                f64::decode(&mut cursor),
-                                f64::decode(&mut cursor),
-                                f64::decode(&mut cursor),
-                            ) {
-                                pos.x = x;
-                                pos.y = y;
-                                pos.z = z;
-                            }
-                        }
-                        0x1E => {
-                            // MovePlayerPosRot
-                            if let (Ok(x), Ok(y), Ok(z), Ok(yaw), Ok(pitch)) = (
-                                f64::decode(&mut cursor),
-                                f64::decode(&mut cursor),
-                                f64::decode(&mut cursor),
-                                f32::decode(&mut cursor),
-                                f32::decode(&mut cursor),
-                            ) {
-                                pos.x = x;
-                                pos.y = y;
-                                pos.z = z;
-                                rot.yaw = yaw;
-                                rot.pitch = pitch;
-                            }
-                        }
-                        0x1F => {
-                            // MovePlayerRot
-                            if let (Ok(yaw), Ok(pitch)) =
-                                (f32::decode(&mut cursor), f32::decode(&mut cursor))
-                            {
-                                rot.yaw = yaw;
-                                rot.pitch = pitch;
-                            }
-                        }
-                        0x20 => {
-                            // MovePlayerStatusOnly - just on_ground, ignore
-                        }
-                        0x00 => {
-                            // AcceptTeleportation
-                            if let Ok(teleport_id) = mc_protocol::read_varint(&mut cursor) {
-                                debug!("Client accepted teleport: {}", teleport_id);
-                            }
-                        }
-                        0x1A => {
-                            // KeepAlive response
-                            if let Ok(ka_id) = i64::decode(&mut cursor) {
-                                debug!("Keep alive response: {}", ka_id);
-                            }
-                        }
-                        _ => {
-                            // Unknown packet, put it back
-                            buffer.push_incoming(packet_id, Bytes::from(data.to_vec()));
-                            break;
-                        }
-                    }
-                }
+            .kind(spawn_login)
+            .each(|(buffer, pos, entity_id)| {
+                send_play_login(buffer, entity_id.value);
+                send_game_event_start_waiting(buffer);
+
+                let (cx, cz) = pos.chunk_pos();
+                send_set_center_chunk(buffer, cx, cz);
+            });
+
+        world
+            .system_named::<(&mut PacketBuffer, &ChunkIndex)>("SendSpawnChunks")
+            .with(NeedsSpawnChunks)
+            .with(Connection)
+            .kind(spawn_chunks)
+            .each_iter(|it, _i, (buffer, chunk_index)| {
+                let chunks = collect_chunks_for_player(chunk_index, 8, it.world());
+                send_chunks_to_buffer(buffer, &chunks);
+            });
+
+        world
+            .system_named::<(&mut PacketBuffer, &Position, &WorldTime)>("SendSpawnPosition")
+            .with(NeedsSpawnChunks)
+            .with(Connection)
+            .kind(spawn_position)
+            .each(|(buffer, pos, world_time)| {
+                send_set_time(buffer, world_time.world_age, world_time.time_of_day);
+                send_player_position(buffer, pos.x, pos.y, pos.z, 1);
+                send_keepalive(buffer);
             });
+
+        world
+            .system_named::<()>("EnterPlayState")
+            .with(NeedsSpawnChunks)
+            .with(Connection)
+            .kind(spawn_finish)
+            .each_entity(|entity, ()| {
+                entity.remove(NeedsSpawnChunks);
+                entity.add(InPlayState);
+
+                tracing::info!("Player entered play state");
+            });
+    }
+}
+
+/// Handle player movement packets directly (without packet dispatch)
+fn handle_movement(buffer: &mut PacketBuffer, pos: &mut Position, rot: &mut Rotation) {
+    while let Some((packet_id, data)) = buffer.pop_incoming() {
+        let mut cursor = std::io::Cursor::new(&data[..]);
+        match packet_id {
+            0x1D => {
+                // MovePlayerPos
+                if let (Ok(x), Ok(y), Ok(z)) = (
+                    f64::decode(&mut cursor),
+                    f64::decode(&mut cursor),
+                    f64::decode(&mut cursor),
+                ) {
+                    pos.x = x;
+                    pos.y = y;
+                    pos.z = z;
+                }
+            }
+            0x1E => {
+                // MovePlayerPosRot
+                if let (Ok(x), Ok(y), Ok(z), Ok(yaw), Ok(pitch)) = (
+                    f64::decode(&mut cursor),
+                    f64::decode(&mut cursor),
+                    f64::decode(&mut cursor),
+                    f32::decode(&mut cursor),
+                    f32::decode(&mut cursor),
+                ) {
+                    pos.x = x;
+                    pos.y = y;
+                    pos.z = z;
+                    rot.yaw = yaw;
+                    rot.pitch = pitch;
+                }
+            }
+            0x1F => {
+                // MovePlayerRot
+                if let (Ok(yaw), Ok(pitch)) = (f32::decode(&mut cursor), f32::decode(&mut cursor)) {
+                    rot.yaw = yaw;
+                    rot.pitch = pitch;
+                }
+            }
+            0x20 => {
+                // MovePlayerStatusOnly - just on_ground, ignore
+            }
+            0x00 => {
+                // AcceptTeleportation
+                if let Ok(teleport_id) = mc_protocol::read_varint(&mut cursor) {
+                    debug!("Client accepted teleport: {}", teleport_id);
+                }
+            }
+            KEEPALIVE_PACKET_ID => {
+                // KeepAlive response
+                if let Ok(ka_id) = i64::decode(&mut cursor) {
+                    debug!("Keep alive response: {}", ka_id);
+                }
+            }
+            _ => {
+                // Unknown packet, put it back
+                buffer.push_incoming(packet_id, Bytes::from(data.to_vec()));
+                break;
+            }
+        }
     }
 }
 
@@ -3,7 +3,15 @@
 //! This module provides:
 //! - `WorldTime` - tracks world age and time of day
 //! - `TpsTracker` - tracks ticks per second with EMAs
+//! - `FixedTimestep` - turns frame time into a fixed number of game ticks
 //! - Systems for ticking time forward
+//!
+//! Game logic runs at exactly 20 TPS however often `world.progress()` is
+//! called: each frame adds its delta time to [`FixedTimestep`], which says
+//! how many whole ticks are due. Systems that advance the game run their
+//! work [`FixedTimestep::steps`] times per frame (possibly zero), and
+//! anything smoothing between ticks, like rendering, interpolates by
+//! [`FixedTimestep::alpha`].
 
 use flecs_ecs::prelude::*;
 
@@ -75,6 +83,64 @@ impl TpsTracker {
     }
 }
 
+/// Singleton: Fixed timestep accumulator driving game ticks
+#[derive(Component, Debug)]
+pub struct FixedTimestep {
+    /// Seconds per game tick
+    pub step: f32,
+    /// Most ticks run in one frame; time beyond that is dropped so a long
+    /// stall doesn't make the world race to catch up
+    pub max_steps: u32,
+    /// Frame time not yet spent on a tick
+    accumulator: f32,
+    /// Ticks due this frame
+    steps: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(Self::TPS)
+    }
+}
+
+impl FixedTimestep {
+    /// Vanilla tick rate
+    pub const TPS: u32 = 20;
+
+    /// Ticks at `tps` per second, catching up at most a second at a time
+    pub fn new(tps: u32) -> Self {
+        Self {
+            step: 1.0 / tps as f32,
+            max_steps: tps,
+            accumulator: 0.0,
+            steps: 0,
+        }
+    }
+
+    /// Add a frame's delta time and work out how many ticks are due
+    pub fn advance(&mut self, delta_time: f32) -> u32 {
+        self.accumulator += delta_time.max(0.0);
+        let due = (self.accumulator / self.step) as u32;
+        self.steps = due.min(self.max_steps);
+        if due > self.max_steps {
+            self.accumulator = 0.0;
+        } else {
+            self.accumulator -= due as f32 * self.step;
+        }
+        self.steps
+    }
+
+    /// Ticks to run this frame
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// How far the world is between the last tick and the next, from 0 to 1
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
+
 // ============================================================================
 // Module
 // ============================================================================
@@ -90,20 +156,41 @@ impl Module for TimeModule {
         // Register and set up singletons
         world.component::<WorldTime>();
         world.component::<TpsTracker>();
+        world.component::<FixedTimestep>();
         world
             .component::<WorldTime>()
             .add_trait::<flecs::Singleton>();
         world
             .component::<TpsTracker>()
             .add_trait::<flecs::Singleton>();
+        world
+            .component::<FixedTimestep>()
+            .add_trait::<flecs::Singleton>();
         world.set(WorldTime::default());
         world.set(TpsTracker::default());
+        world.set(FixedTimestep::default());
+
+        // Work out the ticks due before anything runs them
+        world
+            .system_named::<&mut FixedTimestep>("AdvanceFixedTimestep")
+            .kind(id::<flecs::pipeline::OnLoad>())
+            .run(|mut it| {
+                while it.next() {
+                    let delta_time = it.delta_time();
+                    let mut timestep = it.field_mut::<FixedTimestep>(0);
+                    for i in it.iter() {
+                        timestep[i].advance(delta_time);
+                    }
+                }
+            });
 
-        // Tick world time each frame
+        // Tick world time once per game tick
         world
-            .system_named::<&mut WorldTime>("TickWorldTime")
-            .each(|time| {
-                time.tick();
+            .system_named::<(&mut WorldTime, &FixedTimestep)>("TickWorldTime")
+            .each(|(time, timestep)| {
+                for _ in 0..timestep.steps() {
+                    time.tick();
+                }
             });
 
         // Update TPS tracker each frame
@@ -1,15 +1,105 @@
 //! Derive macros for the persist crate.
 //!
-//! This crate is currently a placeholder. The persistence system now uses
-//! the `PersistExt` trait which doesn't require a derive macro.
+//! `#[derive(Schema)]` describes a persisted component's layout so the
+//! database can tell when stored data no longer matches it:
 //!
-//! Usage:
 //! ```ignore
-//! use persist::PersistExt;
+//! use persist::{PersistExt, Schema};
+//!
+//! #[derive(Component, Serialize, Deserialize, Schema)]
+//! #[schema(version = 2)]
+//! struct Position { x: f64, y: f64, z: f64 }
 //!
-//! // Register component for persistence
 //! world.component::<Position>().persist::<Uuid>();
 //! ```
+//!
+//! Every field is recorded with its type as written. `version` (default 1)
+//! is bumped together with a migration when the layout changes on purpose.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, LitInt};
+
+/// Parse `#[schema(version = N)]`, defaulting to version 1.
+fn parse_version(attrs: &[Attribute]) -> syn::Result<u32> {
+    let mut version = 1;
+    for attr in attrs {
+        if !attr.path().is_ident("schema") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("version") {
+                version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown schema attribute; expected `version = N`"))
+            }
+        })?;
+    }
+    Ok(version)
+}
+
+/// `(name, type)` of each field; tuple fields are named by index.
+fn field_list(fields: &Fields, prefix: &str) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| index.to_string(), ToString::to_string);
+            let ty = &field.ty;
+            let ty = quote!(#ty).to_string().replace(' ', "");
+            (format!("{prefix}{name}"), ty)
+        })
+        .collect()
+}
+
+#[proc_macro_derive(Schema, attributes(schema))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let version = match parse_version(&input.attrs) {
+        Ok(version) => version,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    // Enum variants are recorded as `Variant` (with an empty type) followed
+    // by their fields as `Variant.field`, so reordering or renaming variants
+    // changes the schema too
+    let fields = match &input.data {
+        Data::Struct(data) => field_list(&data.fields, ""),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| {
+                let variant_name = variant.ident.to_string();
+                let prefix = format!("{variant_name}.");
+                std::iter::once((variant_name, String::new()))
+                    .chain(field_list(&variant.fields, &prefix))
+            })
+            .collect(),
+        Data::Union(_) => {
+            return syn::Error::new_spanned(name, "unions cannot derive Schema")
+                .to_compile_error()
+                .into();
+        }
+    };
+    let fields = fields.iter().map(|(field, ty)| {
+        quote! { ::persist::FieldSchema::new(#field, #ty) }
+    });
+
+    quote! {
+        impl #impl_generics ::persist::Schema for #name #ty_generics #where_clause {
+            const VERSION: u32 = #version;
 
-// No derive macros needed for the new design.
-// The crate is kept for potential future use.
+            fn fields() -> ::std::vec::Vec<::persist::FieldSchema> {
+                ::std::vec![#(#fields),*]
+            }
+        }
+    }
+    .into()
+}
@@ -1,15 +1,44 @@
 //! LMDB database wrapper for component persistence.
 
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use heed::{Database, Env, EnvOpenOptions, types::Bytes};
+use heed::{Database, Env, EnvOpenOptions, RwTxn, types::Bytes};
+
+use crate::maintenance::{MaintenanceReport, NamespacePolicy};
+use crate::schema::{ComponentSchema, SchemaError};
 
 /// LMDB database wrapper for persisting components.
 ///
 /// Uses the key format `"{uuid}.{component_name}"` for storage.
+///
+/// Every row also has an entry in a `meta` database keyed by
+/// `"{component_name}/{uuid}"`, recording when it was last touched and how
+/// large it is. Namespaces (component names) with a [`NamespacePolicy`] use
+/// this for TTL expiry and LRU eviction in [`maintain`](Self::maintain).
+/// Reads of those namespaces count as use, but [`load_bytes`](Self::load_bytes)
+/// only opens a read transaction: it queues the touch in memory, and the queue
+/// is written by [`flush_reads`](Self::flush_reads) or at the start of
+/// maintenance.
+///
+/// The `schemas` database records the [`ComponentSchema`] of each component
+/// name, checked by [`register_schema`](Self::register_schema).
+///
+/// The `logs` database holds append-only per-UUID logs, keyed by
+/// `"{log_name}/{uuid}/{seq}"` with the sequence number as 16 hex digits so
+/// keys sort in append order. Logs can't be overwritten or deleted, and
+/// maintenance doesn't touch them.
 pub struct PersistDb {
     env: Env,
     db: Database<Bytes, Bytes>,
+    meta: Database<Bytes, Bytes>,
+    schemas: Database<Bytes, Bytes>,
+    logs: Database<Bytes, Bytes>,
+    policies: HashMap<String, NamespacePolicy>,
+    /// Reads of policed rows not yet written to `meta`, by metadata key.
+    pending_reads: Mutex<HashMap<String, u64>>,
 }
 
 impl PersistDb {
@@ -35,15 +64,45 @@ impl PersistDb {
         let env = unsafe {
             EnvOpenOptions::new()
                 .map_size(1024 * 1024 * 1024) // 1GB max
-                .max_dbs(1)
+                .max_dbs(4)
                 .open(path)?
         };
 
         let mut wtxn = env.write_txn()?;
         let db = env.create_database(&mut wtxn, Some("components"))?;
+        let meta = env.create_database(&mut wtxn, Some("meta"))?;
+        let schemas = env.create_database(&mut wtxn, Some("schemas"))?;
+        let logs = env.create_database(&mut wtxn, Some("logs"))?;
         wtxn.commit()?;
 
-        Ok(Self { env, db })
+        Ok(Self {
+            env,
+            db,
+            meta,
+            schemas,
+            logs,
+            policies: HashMap::new(),
+            pending_reads: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Set the retention policy for a component namespace.
+    ///
+    /// Policies are enforced by [`maintain`](Self::maintain), not on write.
+    #[must_use]
+    pub fn with_policy(
+        mut self,
+        component_name: impl Into<String>,
+        policy: NamespacePolicy,
+    ) -> Self {
+        self.policies.insert(component_name.into(), policy);
+        self
+    }
+
+    /// Get the retention policy for a component namespace, if any.
+    #[must_use]
+    pub fn policy(&self, component_name: &str) -> Option<&NamespacePolicy> {
+        self.policies.get(component_name)
     }
 
     /// Save raw bytes for a given UUID and component name.
@@ -53,32 +112,96 @@ impl PersistDb {
     /// # Errors
     /// Returns an error if database write fails.
     pub fn save_bytes(&self, uuid: u128, component_name: &str, bytes: &[u8]) -> heed::Result<()> {
+        self.save_bytes_at(uuid, component_name, bytes, now_ms())
+    }
+
+    /// [`save_bytes`](Self::save_bytes), touching the row at `now_ms`.
+    fn save_bytes_at(
+        &self,
+        uuid: u128,
+        component_name: &str,
+        bytes: &[u8],
+        now_ms: u64,
+    ) -> heed::Result<()> {
         let key = format_key(uuid, component_name);
 
         let mut wtxn = self.env.write_txn()?;
         self.db.put(&mut wtxn, key.as_bytes(), bytes)?;
+        self.touch(&mut wtxn, uuid, component_name, bytes.len(), now_ms)?;
         wtxn.commit()?;
 
         tracing::trace!("Persisted {component_name} for {uuid:032x}");
         Ok(())
     }
 
+    /// Save several components for one UUID in a single write transaction.
+    ///
+    /// Either every entry is written or none are, so related components
+    /// (e.g. a player's position, inventory and health) can't end up out of
+    /// sync after a crash mid-save.
+    ///
+    /// # Errors
+    /// Returns an error if any write fails; the transaction is then aborted.
+    pub fn save_many<'a>(
+        &self,
+        uuid: u128,
+        entries: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+    ) -> heed::Result<usize> {
+        let now = now_ms();
+        let mut wtxn = self.env.write_txn()?;
+        let mut written = 0;
+        for (component_name, bytes) in entries {
+            let key = format_key(uuid, component_name);
+            self.db.put(&mut wtxn, key.as_bytes(), bytes)?;
+            self.touch(&mut wtxn, uuid, component_name, bytes.len(), now)?;
+            written += 1;
+        }
+        wtxn.commit()?;
+
+        tracing::trace!("Persisted {written} components for {uuid:032x} in one transaction");
+        Ok(written)
+    }
+
     /// Load raw bytes for a given UUID and component name.
     ///
     /// Returns `None` if no data exists for this UUID/component combination.
+    /// Reads of namespaces with a policy are queued for
+    /// [`flush_reads`](Self::flush_reads) rather than written here.
     ///
     /// # Errors
     /// Returns an error if database read fails.
     pub fn load_bytes(&self, uuid: u128, component_name: &str) -> heed::Result<Option<Vec<u8>>> {
+        self.load_bytes_at(uuid, component_name, now_ms())
+    }
+
+    /// [`load_bytes`](Self::load_bytes), recording the read at `now_ms`.
+    fn load_bytes_at(
+        &self,
+        uuid: u128,
+        component_name: &str,
+        now_ms: u64,
+    ) -> heed::Result<Option<Vec<u8>>> {
         let key = format_key(uuid, component_name);
 
         let rtxn = self.env.read_txn()?;
         let Some(bytes) = self.db.get(&rtxn, key.as_bytes())? else {
             return Ok(None);
         };
+        let bytes = bytes.to_vec();
+        drop(rtxn);
+
+        // Reads count as use for LRU, but only where a policy will look at it
+        if self.policies.contains_key(component_name) {
+            self.pending_reads
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(format_meta_key(uuid, component_name))
+                .and_modify(|touched| *touched = (*touched).max(now_ms))
+                .or_insert(now_ms);
+        }
 
         tracing::trace!("Loaded {component_name} for {uuid:032x}");
-        Ok(Some(bytes.to_vec()))
+        Ok(Some(bytes))
     }
 
     /// Delete a component for a given UUID.
@@ -90,6 +213,8 @@ impl PersistDb {
 
         let mut wtxn = self.env.write_txn()?;
         let deleted = self.db.delete(&mut wtxn, key.as_bytes())?;
+        self.meta
+            .delete(&mut wtxn, format_meta_key(uuid, component_name).as_bytes())?;
         wtxn.commit()?;
 
         if deleted {
@@ -97,6 +222,364 @@ impl PersistDb {
         }
         Ok(deleted)
     }
+
+    /// The schema recorded for a component, if any.
+    ///
+    /// # Errors
+    /// Returns an error if database read fails.
+    pub fn load_schema(&self, component_name: &str) -> heed::Result<Option<ComponentSchema>> {
+        let rtxn = self.env.read_txn()?;
+        Ok(self
+            .schemas
+            .get(&rtxn, component_name.as_bytes())?
+            .and_then(ComponentSchema::decode))
+    }
+
+    /// Check a component's compiled schema against the recorded one before
+    /// its rows are used, recording it if they're compatible.
+    ///
+    /// Nothing recorded (a new component, or rows written before schemas
+    /// were recorded) or no stored rows: `schema` is recorded. Same hash:
+    /// nothing to do. An older recorded version with a `migrate` function:
+    /// every stored row is passed through it, rows it returns `None` for are
+    /// dropped, and `schema` is recorded, all in one transaction.
+    ///
+    /// # Errors
+    /// Returns a [`SchemaError`] if the stored rows can't be read with
+    /// `schema`; nothing is changed then.
+    pub fn register_schema(
+        &self,
+        schema: &ComponentSchema,
+        migrate: Option<fn(&[u8]) -> Option<Vec<u8>>>,
+    ) -> Result<(), SchemaError> {
+        let name = schema.name.as_str();
+        let mut wtxn = self.env.write_txn()?;
+        let stored = self
+            .schemas
+            .get(&wtxn, name.as_bytes())?
+            .and_then(ComponentSchema::decode);
+
+        let rows = self.component_rows(&wtxn, name)?;
+        match stored {
+            Some(stored) if stored.hash == schema.hash => return Ok(()),
+            Some(stored) if !rows.is_empty() => {
+                let error = if stored.version == schema.version {
+                    SchemaError::Changed {
+                        stored,
+                        current: schema.clone(),
+                    }
+                } else if stored.version > schema.version {
+                    SchemaError::Newer {
+                        stored,
+                        current: schema.clone(),
+                    }
+                } else if let Some(migrate) = migrate {
+                    let now = now_ms();
+                    for (uuid, bytes) in &rows {
+                        let key = format_key(*uuid, name);
+                        if let Some(migrated) = migrate(bytes) {
+                            self.db.put(&mut wtxn, key.as_bytes(), &migrated)?;
+                            self.touch(&mut wtxn, *uuid, name, migrated.len(), now)?;
+                        } else {
+                            self.db.delete(&mut wtxn, key.as_bytes())?;
+                            self.meta
+                                .delete(&mut wtxn, format_meta_key(*uuid, name).as_bytes())?;
+                        }
+                    }
+                    tracing::info!(
+                        "Migrated {} rows of {name} from version {} to {}",
+                        rows.len(),
+                        stored.version,
+                        schema.version
+                    );
+                    self.schemas
+                        .put(&mut wtxn, name.as_bytes(), &schema.encode())?;
+                    wtxn.commit()?;
+                    return Ok(());
+                } else {
+                    SchemaError::NoMigration {
+                        stored,
+                        current: schema.clone(),
+                    }
+                };
+                return Err(error);
+            }
+            _ => {}
+        }
+
+        self.schemas
+            .put(&mut wtxn, name.as_bytes(), &schema.encode())?;
+        wtxn.commit()?;
+        tracing::debug!("Recorded schema of {name} (version {})", schema.version);
+        Ok(())
+    }
+
+    /// Every stored row of a component, as `(uuid, bytes)`.
+    fn component_rows(
+        &self,
+        txn: &heed::RoTxn<'_>,
+        component_name: &str,
+    ) -> heed::Result<Vec<(u128, Vec<u8>)>> {
+        let suffix = format!(".{component_name}");
+        let mut rows = Vec::new();
+        for entry in self.db.iter(txn)? {
+            let (key, value) = entry?;
+            let uuid = core::str::from_utf8(key)
+                .ok()
+                .and_then(|key| key.strip_suffix(&suffix))
+                .and_then(|uuid| uuid::Uuid::parse_str(uuid).ok());
+            if let Some(uuid) = uuid {
+                rows.push((uuid.as_u128(), value.to_vec()));
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Append entries to UUIDs' logs in a single write transaction.
+    ///
+    /// Each entry gets the next sequence number of its UUID's log, starting
+    /// at 0.
+    ///
+    /// # Errors
+    /// Returns an error if any write fails; the transaction is then aborted.
+    pub fn append_log<'a>(
+        &self,
+        log_name: &str,
+        entries: impl IntoIterator<Item = (u128, &'a [u8])>,
+    ) -> heed::Result<usize> {
+        let mut wtxn = self.env.write_txn()?;
+        let mut next: HashMap<u128, u64> = HashMap::new();
+        let mut written = 0;
+        for (uuid, bytes) in entries {
+            let seq = match next.get(&uuid) {
+                Some(&seq) => seq,
+                None => self.next_log_seq(&wtxn, log_name, uuid)?,
+            };
+            self.logs.put(
+                &mut wtxn,
+                format_log_key(log_name, uuid, seq).as_bytes(),
+                bytes,
+            )?;
+            next.insert(uuid, seq + 1);
+            written += 1;
+        }
+        wtxn.commit()?;
+
+        tracing::trace!("Appended {written} entries to {log_name} logs");
+        Ok(written)
+    }
+
+    /// The newest entries of a UUID's log, newest first, as `(seq, bytes)`.
+    ///
+    /// Only entries with a sequence number below `before` are returned, if
+    /// given, so a long log can be paged through.
+    ///
+    /// # Errors
+    /// Returns an error if database read fails.
+    pub fn read_log(
+        &self,
+        log_name: &str,
+        uuid: u128,
+        before: Option<u64>,
+        limit: usize,
+    ) -> heed::Result<Vec<(u64, Vec<u8>)>> {
+        let prefix = format_log_prefix(log_name, uuid);
+        let rtxn = self.env.read_txn()?;
+        let mut entries = Vec::new();
+        for entry in self.logs.rev_prefix_iter(&rtxn, prefix.as_bytes())? {
+            if entries.len() >= limit {
+                break;
+            }
+            let (key, value) = entry?;
+            let Some(seq) = parse_log_seq(key, &prefix) else {
+                continue;
+            };
+            if before.is_none_or(|before| seq < before) {
+                entries.push((seq, value.to_vec()));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The sequence number the next entry of a UUID's log gets.
+    fn next_log_seq(&self, txn: &heed::RoTxn<'_>, log_name: &str, uuid: u128) -> heed::Result<u64> {
+        let prefix = format_log_prefix(log_name, uuid);
+        let Some(entry) = self.logs.rev_prefix_iter(txn, prefix.as_bytes())?.next() else {
+            return Ok(0);
+        };
+        let (key, _) = entry?;
+        Ok(parse_log_seq(key, &prefix).map_or(0, |seq| seq + 1))
+    }
+
+    /// Write queued reads to the `meta` database in one write transaction.
+    ///
+    /// A read only moves a row's touch time forward, and rows deleted since
+    /// the read are skipped. Returns the number of rows updated.
+    ///
+    /// # Errors
+    /// Returns an error if the write fails; the queued reads are then dropped.
+    pub fn flush_reads(&self) -> heed::Result<usize> {
+        let mut wtxn = self.env.write_txn()?;
+        let flushed = self.apply_reads(&mut wtxn)?;
+        wtxn.commit()?;
+        Ok(flushed)
+    }
+
+    /// Enforce every namespace policy as of now.
+    ///
+    /// # Errors
+    /// Returns an error if reading or deleting rows fails.
+    pub fn maintain(&self) -> heed::Result<MaintenanceReport> {
+        self.maintain_at(now_ms())
+    }
+
+    /// Enforce every namespace policy as of `now_ms` (milliseconds since the
+    /// Unix epoch).
+    ///
+    /// Queued reads are flushed first, in the same transaction.
+    ///
+    /// For each namespace with a policy, rows not touched within the TTL are
+    /// deleted first. If the namespace is still over its byte quota, the least
+    /// recently touched rows are evicted until it fits. Rows written before
+    /// metadata existed have no `meta` entry and are never removed.
+    ///
+    /// # Errors
+    /// Returns an error if reading or deleting rows fails.
+    pub fn maintain_at(&self, now_ms: u64) -> heed::Result<MaintenanceReport> {
+        let mut report = MaintenanceReport::default();
+        let mut wtxn = self.env.write_txn()?;
+        self.apply_reads(&mut wtxn)?;
+
+        for (component_name, policy) in &self.policies {
+            let prefix = format!("{component_name}/");
+            let mut rows: Vec<(String, RowMeta)> = Vec::new();
+            for entry in self.meta.prefix_iter(&wtxn, prefix.as_bytes())? {
+                let (key, value) = entry?;
+                let uuid = key.strip_prefix(prefix.as_bytes()).unwrap_or(key);
+                let (Ok(uuid), Some(meta)) = (core::str::from_utf8(uuid), RowMeta::decode(value))
+                else {
+                    continue;
+                };
+                rows.push((uuid.to_string(), meta));
+            }
+
+            // Oldest first, so both passes remove the least recently used rows
+            rows.sort_by(|(a_uuid, a), (b_uuid, b)| {
+                a.touched_ms
+                    .cmp(&b.touched_ms)
+                    .then_with(|| a_uuid.cmp(b_uuid))
+            });
+
+            let mut total: u64 = rows.iter().map(|(_, meta)| meta.size).sum();
+            for (uuid, meta) in &rows {
+                let expired = policy
+                    .ttl
+                    .is_some_and(|ttl| now_ms.saturating_sub(meta.touched_ms) > ttl_ms(ttl));
+                let over_quota = policy.max_bytes.is_some_and(|max| total > max);
+                if !expired && !over_quota {
+                    continue;
+                }
+
+                self.db
+                    .delete(&mut wtxn, format!("{uuid}.{component_name}").as_bytes())?;
+                self.meta
+                    .delete(&mut wtxn, format!("{prefix}{uuid}").as_bytes())?;
+                total -= meta.size;
+                report.bytes_freed += meta.size;
+                if expired {
+                    report.expired += 1;
+                } else {
+                    report.evicted += 1;
+                }
+            }
+        }
+
+        wtxn.commit()?;
+
+        if report.expired + report.evicted > 0 {
+            tracing::debug!(
+                "Persist maintenance: {} expired, {} evicted, {} bytes freed",
+                report.expired,
+                report.evicted,
+                report.bytes_freed
+            );
+        }
+        Ok(report)
+    }
+
+    /// Write queued reads into `wtxn`, clearing the queue.
+    fn apply_reads(&self, wtxn: &mut RwTxn<'_>) -> heed::Result<usize> {
+        let reads = core::mem::take(
+            &mut *self
+                .pending_reads
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        let mut applied = 0;
+        for (key, touched_ms) in reads {
+            let Some(meta) = self
+                .meta
+                .get(wtxn, key.as_bytes())?
+                .and_then(RowMeta::decode)
+            else {
+                continue;
+            };
+            if meta.touched_ms >= touched_ms {
+                continue;
+            }
+            let meta = RowMeta {
+                touched_ms,
+                size: meta.size,
+            };
+            self.meta.put(wtxn, key.as_bytes(), &meta.encode())?;
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    /// Record that a row was written.
+    fn touch(
+        &self,
+        wtxn: &mut RwTxn<'_>,
+        uuid: u128,
+        component_name: &str,
+        size: usize,
+        now_ms: u64,
+    ) -> heed::Result<()> {
+        let meta = RowMeta {
+            touched_ms: now_ms,
+            size: size as u64,
+        };
+        self.meta.put(
+            wtxn,
+            format_meta_key(uuid, component_name).as_bytes(),
+            &meta.encode(),
+        )
+    }
+}
+
+/// Per-row metadata stored in the `meta` database.
+#[derive(Debug, Clone, Copy)]
+struct RowMeta {
+    /// Last write or flushed (policed) read, in milliseconds since the Unix epoch.
+    touched_ms: u64,
+    /// Size of the stored value in bytes.
+    size: u64,
+}
+
+impl RowMeta {
+    fn encode(self) -> [u8; 16] {
+        let mut out = [0; 16];
+        out[..8].copy_from_slice(&self.touched_ms.to_le_bytes());
+        out[8..].copy_from_slice(&self.size.to_le_bytes());
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let touched_ms = u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?);
+        let size = u64::from_le_bytes(bytes.get(8..16)?.try_into().ok()?);
+        Some(Self { touched_ms, size })
+    }
 }
 
 /// Format the database key as `"{uuid}.{component_name}"`.
@@ -105,6 +588,43 @@ fn format_key(uuid: u128, component_name: &str) -> String {
     format!("{uuid}.{component_name}")
 }
 
+/// Format the metadata key as `"{component_name}/{uuid}"`.
+///
+/// Component-first so a namespace can be scanned with a prefix iterator.
+fn format_meta_key(uuid: u128, component_name: &str) -> String {
+    let uuid = uuid::Uuid::from_u128(uuid);
+    format!("{component_name}/{uuid}")
+}
+
+/// Format the prefix of a UUID's log keys as `"{log_name}/{uuid}/"`.
+fn format_log_prefix(log_name: &str, uuid: u128) -> String {
+    let uuid = uuid::Uuid::from_u128(uuid);
+    format!("{log_name}/{uuid}/")
+}
+
+/// Format a log key as `"{log_name}/{uuid}/{seq:016x}"`.
+fn format_log_key(log_name: &str, uuid: u128, seq: u64) -> String {
+    format!("{}{seq:016x}", format_log_prefix(log_name, uuid))
+}
+
+/// The sequence number of a log key.
+fn parse_log_seq(key: &[u8], prefix: &str) -> Option<u64> {
+    let seq = core::str::from_utf8(key.strip_prefix(prefix.as_bytes())?).ok()?;
+    u64::from_str_radix(seq, 16).ok()
+}
+
+/// Current time in milliseconds since the Unix epoch.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, ttl_ms)
+}
+
+/// Convert a duration to whole milliseconds, saturating.
+fn ttl_ms(duration: core::time::Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,4 +686,177 @@ mod tests {
         let loaded = db.load_bytes(uuid, "Position").unwrap();
         assert_eq!(loaded, None);
     }
+
+    #[test]
+    fn test_save_many() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersistDb::open(dir.path()).unwrap();
+
+        let uuid = 0x550e8400_e29b_41d4_a716_446655440000u128;
+        let written = db
+            .save_many(uuid, [("Position", &[1, 2][..]), ("Health", &[20][..])])
+            .unwrap();
+        assert_eq!(written, 2);
+
+        assert_eq!(db.load_bytes(uuid, "Position").unwrap(), Some(vec![1, 2]));
+        assert_eq!(db.load_bytes(uuid, "Health").unwrap(), Some(vec![20]));
+    }
+
+    #[test]
+    fn test_register_schema() {
+        use crate::schema::FieldSchema;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersistDb::open(dir.path()).unwrap();
+        let v1 = ComponentSchema::new("Health", 1, vec![FieldSchema::new("value", "i32")]);
+        let changed = ComponentSchema::new("Health", 1, vec![FieldSchema::new("value", "f32")]);
+        let v2 = ComponentSchema::new("Health", 2, vec![FieldSchema::new("value", "i64")]);
+
+        // Without stored rows, any schema is simply recorded
+        db.register_schema(&v1, None).unwrap();
+        db.register_schema(&changed, None).unwrap();
+        db.register_schema(&v1, None).unwrap();
+        assert_eq!(db.load_schema("Health").unwrap(), Some(v1.clone()));
+
+        db.save_bytes(1, "Health", &20_i32.to_le_bytes()).unwrap();
+        db.save_bytes(2, "Health", &[0xff]).unwrap();
+        db.register_schema(&v1, None).unwrap();
+        assert!(matches!(
+            db.register_schema(&changed, None),
+            Err(SchemaError::Changed { .. })
+        ));
+        assert!(matches!(
+            db.register_schema(&v2, None),
+            Err(SchemaError::NoMigration { .. })
+        ));
+        assert_eq!(db.load_schema("Health").unwrap(), Some(v1.clone()));
+
+        // Widen to i64, dropping rows that don't decode
+        db.register_schema(
+            &v2,
+            Some(|bytes| {
+                let value = i32::from_le_bytes(bytes.try_into().ok()?);
+                Some(i64::from(value).to_le_bytes().to_vec())
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            db.load_bytes(1, "Health").unwrap(),
+            Some(20_i64.to_le_bytes().to_vec())
+        );
+        assert_eq!(db.load_bytes(2, "Health").unwrap(), None);
+        assert_eq!(db.load_schema("Health").unwrap(), Some(v2));
+
+        assert!(matches!(
+            db.register_schema(&v1, None),
+            Err(SchemaError::Newer { .. })
+        ));
+    }
+
+    #[test]
+    fn test_append_and_read_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersistDb::open(dir.path()).unwrap();
+
+        let (a, b) = (1_u128, 2_u128);
+        let written = db
+            .append_log("audit", [(a, &b"a0"[..]), (b, b"b0"), (a, b"a1")])
+            .unwrap();
+        assert_eq!(written, 3);
+        db.append_log("audit", [(a, &b"a2"[..])]).unwrap();
+
+        let entries = db.read_log("audit", a, None, 10).unwrap();
+        assert_eq!(
+            entries,
+            [
+                (2, b"a2".to_vec()),
+                (1, b"a1".to_vec()),
+                (0, b"a0".to_vec())
+            ]
+        );
+        assert_eq!(
+            db.read_log("audit", b, None, 10).unwrap(),
+            [(0, b"b0".to_vec())]
+        );
+
+        // Paging from the newest
+        assert_eq!(
+            db.read_log("audit", a, None, 1).unwrap(),
+            [(2, b"a2".to_vec())]
+        );
+        assert_eq!(
+            db.read_log("audit", a, Some(2), 1).unwrap(),
+            [(1, b"a1".to_vec())]
+        );
+
+        // Other logs and components are separate
+        assert!(db.read_log("chat", a, None, 10).unwrap().is_empty());
+        assert_eq!(db.load_bytes(a, "audit").unwrap(), None);
+    }
+
+    #[test]
+    fn test_ttl_expires_stale_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = core::time::Duration::from_hours(24);
+        let db = PersistDb::open(dir.path())
+            .unwrap()
+            .with_policy("Visit", NamespacePolicy::new().ttl(day));
+
+        let uuid = 0x550e8400_e29b_41d4_a716_446655440000u128;
+        db.save_bytes(uuid, "Visit", &[1]).unwrap();
+        db.save_bytes(uuid, "Position", &[2]).unwrap();
+
+        assert_eq!(db.maintain().unwrap(), MaintenanceReport::default());
+
+        let later = now_ms() + ttl_ms(day * 2);
+        let report = db.maintain_at(later).unwrap();
+        assert_eq!(report.expired, 1);
+        assert_eq!(db.load_bytes(uuid, "Visit").unwrap(), None);
+
+        // Namespaces without a policy are untouched
+        assert_eq!(db.load_bytes(uuid, "Position").unwrap(), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_quota_evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersistDb::open(dir.path())
+            .unwrap()
+            .with_policy("Stats", NamespacePolicy::new().max_bytes(8));
+
+        for uuid in 1..=3_u128 {
+            let at = u64::try_from(uuid).unwrap() * 1000;
+            db.save_bytes_at(uuid, "Stats", &[0; 4], at).unwrap();
+        }
+        // Reading the oldest row makes it the most recently used once flushed
+        db.load_bytes_at(1, "Stats", 4000).unwrap();
+        assert_eq!(db.flush_reads().unwrap(), 1);
+        assert_eq!(db.flush_reads().unwrap(), 0);
+
+        let report = db.maintain_at(5000).unwrap();
+        assert_eq!(report.evicted, 1);
+        assert_eq!(report.bytes_freed, 4);
+        assert!(db.load_bytes(1, "Stats").unwrap().is_some());
+        assert_eq!(db.load_bytes(2, "Stats").unwrap(), None);
+        assert!(db.load_bytes(3, "Stats").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_flush_reads_skips_stale_and_deleted_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersistDb::open(dir.path())
+            .unwrap()
+            .with_policy("Stats", NamespacePolicy::new().max_bytes(8));
+
+        db.save_bytes_at(1, "Stats", &[0; 4], 1000).unwrap();
+        db.save_bytes_at(2, "Stats", &[0; 4], 1000).unwrap();
+        db.load_bytes_at(1, "Stats", 2000).unwrap();
+        db.load_bytes_at(2, "Stats", 2000).unwrap();
+
+        // A newer write wins over an older queued read, and a deleted row
+        // isn't brought back
+        db.save_bytes_at(1, "Stats", &[0; 4], 3000).unwrap();
+        db.delete(2, "Stats").unwrap();
+        assert_eq!(db.flush_reads().unwrap(), 0);
+    }
 }
@@ -6,7 +6,10 @@
 //!
 //! 1. Register a component for persistence:
 //! ```ignore
-//! use persist::PersistExt;
+//! use persist::{PersistExt, Schema};
+//!
+//! #[derive(Component, Serialize, Deserialize, Schema)]
+//! struct Position { x: f64, y: f64, z: f64 }
 //!
 //! world.component::<Position>().persist::<Uuid>();
 //! ```
@@ -18,14 +21,44 @@
 //!
 //! 3. When `Uuid` is set on an entity, all persisted components are automatically loaded.
 //! 4. When a persisted component is set on an entity with `Uuid`, it's automatically saved.
+//!
+//! Components saved by the observer are written one at a time. When several
+//! components form one logical state (e.g. on player quit), save them together:
+//! ```ignore
+//! persist::persist_transaction::<Uuid>(player)?;
+//! ```
+//!
+//! Components that change every tick (`Position`) can be written at most
+//! once per interval per entity instead, with
+//! [`persist_throttled`](PersistExt::persist_throttled). The latest value
+//! held back is written by [`flush_due`] once the interval passed, and right
+//! away when the entity loses its `UuidComponent` or is deleted (e.g. on
+//! disconnect), or on [`flush_entity`].
+//!
+//! Component namespaces can be bounded by age or size with a
+//! [`NamespacePolicy`]; see the [`maintenance`] module.
+//!
+//! Each persisted component's [`ComponentSchema`] is recorded with its data,
+//! so a component whose layout changed is refused (or migrated) instead of
+//! misread; see the [`schema`] module.
+
+// Lets `#[derive(Schema)]` refer to `::persist` inside this crate's tests
+extern crate self as persist;
 
 mod db;
+pub mod maintenance;
+pub mod schema;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use change_throttle::Throttle;
 use flecs_ecs::prelude::*;
 
 pub use db::PersistDb;
+pub use maintenance::{MaintenanceReport, MaintenanceTask, NamespacePolicy};
+pub use persist_derive::Schema;
+pub use schema::{ComponentSchema, FieldSchema, Schema, SchemaError};
 
 /// Tag component added to component entities to mark them as persistent.
 #[derive(Component, Default)]
@@ -42,6 +75,29 @@ pub struct PersistLoader {
     pub save: fn(EntityView<'_>) -> Option<Vec<u8>>,
 }
 
+/// Limits how often a component is written per entity, set on component
+/// entities registered with [`PersistExt::persist_throttled`].
+#[derive(Component, Clone)]
+pub struct PersistThrottle {
+    /// Held serialized values by UUID, timed in milliseconds since `started`.
+    throttle: Arc<Mutex<Throttle<u128, Vec<u8>>>>,
+    started: Instant,
+}
+
+impl PersistThrottle {
+    fn new(min_interval: Duration) -> Self {
+        Self {
+            throttle: Arc::new(Mutex::new(Throttle::new(min_interval.as_millis() as u64))),
+            started: Instant::now(),
+        }
+    }
+
+    /// Milliseconds since the throttle was created.
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+}
+
 /// Wrapper around `PersistDb` for use as a Flecs singleton.
 #[derive(Component)]
 pub struct PersistDbSingleton(pub Arc<PersistDb>);
@@ -56,6 +112,7 @@ impl Module for PersistModule {
 
         world.component::<Persist>();
         world.component::<PersistLoader>();
+        world.component::<PersistThrottle>();
         world
             .component::<PersistDbSingleton>()
             .add_trait::<flecs::Singleton>();
@@ -67,6 +124,7 @@ impl Module for PersistModule {
 /// This:
 /// 1. Opens the database at `db_path`
 /// 2. Sets up an observer on `UuidComponent` to load persisted components when UUID is set
+/// 3. Sets up an observer on `UuidComponent` to write held throttled values when UUID is removed
 ///
 /// # Panics
 /// Panics if the database cannot be opened.
@@ -74,9 +132,19 @@ pub fn init<UuidComponent>(world: &World, db_path: &str)
 where
     UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
 {
-    world.import::<PersistModule>();
-
     let db = PersistDb::open(db_path).expect("Failed to open persist database");
+    init_with_db::<UuidComponent>(world, db);
+}
+
+/// Initialize the persistence system with an already opened database.
+///
+/// Use this to configure the database first, e.g. with
+/// [`PersistDb::with_policy`].
+pub fn init_with_db<UuidComponent>(world: &World, db: PersistDb)
+where
+    UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
+{
+    world.import::<PersistModule>();
     world.set(PersistDbSingleton(Arc::new(db)));
 
     // When Uuid is set on an entity, load all persisted components
@@ -86,6 +154,84 @@ where
             let uuid_val: u128 = (*uuid).into();
             load_all_components(entity, uuid_val);
         });
+
+    // When Uuid is removed (or the entity deleted), write what throttling held back
+    world
+        .observer::<flecs::OnRemove, &UuidComponent>()
+        .each_entity(|entity, uuid| {
+            flush_uuid(&entity.world(), (*uuid).into());
+        });
+}
+
+/// Write held values of throttled components whose interval has passed.
+///
+/// Call this periodically (e.g. once per tick), so the last value of a
+/// component that stopped changing is written. Returns the number of values
+/// written.
+pub fn flush_due(world: &World) -> usize {
+    let mut written = 0;
+    for_each_throttle(world, |name, throttle| {
+        let now = throttle.now();
+        let due = throttle.throttle.lock().unwrap().take_due(now);
+        for (uuid, bytes) in due {
+            save_held(world, uuid, name, &bytes);
+            written += 1;
+        }
+    });
+    written
+}
+
+/// Write the held values of an entity's throttled components right away.
+///
+/// Happens on its own when the entity loses its `UuidComponent`; call this
+/// to save an entity that keeps it, e.g. before a checkpoint.
+pub fn flush_entity<UuidComponent>(entity: EntityView<'_>)
+where
+    UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
+{
+    if let Some(uuid) = entity.try_get::<&UuidComponent>(|uuid| (*uuid).into()) {
+        flush_uuid(&entity.world(), uuid);
+    }
+}
+
+/// Write the held values of every throttled component for `uuid`.
+fn flush_uuid(world: &World, uuid: u128) {
+    for_each_throttle(world, |name, throttle| {
+        let held = throttle.throttle.lock().unwrap().flush(uuid);
+        if let Some(bytes) = held {
+            save_held(world, uuid, name, &bytes);
+        }
+    });
+}
+
+/// Run `f` with the name and throttle of every throttled component.
+fn for_each_throttle(world: &World, mut f: impl FnMut(&str, &PersistThrottle)) {
+    world
+        .query::<&PersistThrottle>()
+        .with(Persist::id())
+        .build()
+        .each_entity(|component_entity, throttle| {
+            f(&component_entity.name(), throttle);
+        });
+}
+
+/// Write a value throttling held back.
+fn save_held(world: &World, uuid: u128, name: &str, bytes: &[u8]) {
+    world.get::<&PersistDbSingleton>(|db| {
+        if let Err(e) = db.0.save_bytes(uuid, name, bytes) {
+            tracing::error!("Failed to persist {name}: {e}");
+        }
+    });
+}
+
+/// Enforce namespace policies on the world's database now.
+///
+/// For periodic maintenance off the game thread, see [`MaintenanceTask`].
+///
+/// # Errors
+/// Returns an error if the maintenance pass fails.
+pub fn run_maintenance(world: &World) -> heed::Result<MaintenanceReport> {
+    world.get::<&PersistDbSingleton>(|db| db.0.maintain())
 }
 
 /// Load all persisted components for an entity.
@@ -119,6 +265,45 @@ fn load_all_components(entity: EntityView<'_>, uuid: u128) {
         });
 }
 
+/// Save every persisted component of an entity in a single transaction.
+///
+/// Gathers all components registered with [`PersistExt::persist`] that the
+/// entity currently has and writes them atomically. Returns the number of
+/// components written (0 if the entity has no `UuidComponent`).
+///
+/// # Errors
+/// Returns an error if the write transaction fails; nothing is written then.
+pub fn persist_transaction<UuidComponent>(entity: EntityView<'_>) -> heed::Result<usize>
+where
+    UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
+{
+    let Some(uuid) = entity.try_get::<&UuidComponent>(|uuid| (*uuid).into()) else {
+        return Ok(0);
+    };
+    let world = entity.world();
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    world
+        .query::<&PersistLoader>()
+        .with(Persist::id())
+        .with(flecs::Component::id())
+        .build()
+        .each_entity(|component_entity, loader| {
+            if let Some(bytes) = (loader.save)(entity) {
+                entries.push((component_entity.name(), bytes));
+            }
+        });
+
+    world.get::<&PersistDbSingleton>(|db| {
+        db.0.save_many(
+            uuid,
+            entries
+                .iter()
+                .map(|(name, bytes)| (name.as_str(), bytes.as_slice())),
+        )
+    })
+}
+
 /// Extension trait for registering persistent components.
 pub trait PersistExt<T: ComponentId> {
     /// Mark this component as persistent.
@@ -129,60 +314,147 @@ pub trait PersistExt<T: ComponentId> {
     /// 3. Sets up an `OnSet` observer to save when the component changes
     ///
     /// The component will only be persisted if the entity also has a `UuidComponent`.
+    ///
+    /// Call after [`init`], so the component's schema can be checked against
+    /// the one recorded with its stored data.
+    ///
+    /// # Panics
+    /// Panics if the stored data was written with an incompatible schema
+    /// (see [`PersistDb::register_schema`]), rather than misreading it.
     fn persist<UuidComponent>(self) -> Self
     where
-        T: serde::Serialize + serde::de::DeserializeOwned,
+        T: serde::Serialize + serde::de::DeserializeOwned + Schema,
+        UuidComponent: ComponentId + DataComponent + Copy + Into<u128>;
+
+    /// Like [`persist`](Self::persist), but stored data of an older schema
+    /// version is converted with `migrate` (old bytes to new bytes, `None` to
+    /// drop the row) instead of being refused.
+    ///
+    /// # Panics
+    /// Panics if the stored data was written with an incompatible schema of
+    /// the same or a newer version.
+    fn persist_migrating<UuidComponent>(self, migrate: fn(&[u8]) -> Option<Vec<u8>>) -> Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Schema,
+        UuidComponent: ComponentId + DataComponent + Copy + Into<u128>;
+
+    /// Like [`persist`](Self::persist), but an entity's component is written
+    /// at most once per `min_interval`.
+    ///
+    /// Values set in between are held back, and only the latest is written,
+    /// by [`flush_due`] once the interval passed, or when the entity loses its
+    /// `UuidComponent`.
+    ///
+    /// # Panics
+    /// Panics if the stored data was written with an incompatible schema.
+    fn persist_throttled<UuidComponent>(self, min_interval: Duration) -> Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Schema,
         UuidComponent: ComponentId + DataComponent + Copy + Into<u128>;
 }
 
 impl<'a, T: ComponentId + DataComponent> PersistExt<T> for Component<'a, T> {
     fn persist<UuidComponent>(self) -> Self
     where
-        T: serde::Serialize + serde::de::DeserializeOwned,
+        T: serde::Serialize + serde::de::DeserializeOwned + Schema,
+        UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
+    {
+        register::<T, UuidComponent>(self, None, None)
+    }
+
+    fn persist_migrating<UuidComponent>(self, migrate: fn(&[u8]) -> Option<Vec<u8>>) -> Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Schema,
         UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
     {
-        let world = self.world();
-        let component_name = self.name();
+        register::<T, UuidComponent>(self, Some(migrate), None)
+    }
 
-        tracing::info!("Registered persistent component: {component_name}");
+    fn persist_throttled<UuidComponent>(self, min_interval: Duration) -> Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Schema,
+        UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
+    {
+        register::<T, UuidComponent>(self, None, Some(PersistThrottle::new(min_interval)))
+    }
+}
 
-        // Add Persist tag and PersistLoader to the component entity
-        self.entity().add(Persist).set(PersistLoader {
-            load: |bytes, entity| match bincode::deserialize::<T>(bytes) {
-                Ok(component) => {
-                    entity.set(component);
-                }
-                Err(e) => {
-                    tracing::error!("Failed to deserialize component: {e}");
+/// Shared body of [`PersistExt::persist`], [`PersistExt::persist_migrating`]
+/// and [`PersistExt::persist_throttled`].
+fn register<'a, T, UuidComponent>(
+    component: Component<'a, T>,
+    migrate: Option<fn(&[u8]) -> Option<Vec<u8>>>,
+    throttle: Option<PersistThrottle>,
+) -> Component<'a, T>
+where
+    T: ComponentId + DataComponent + serde::Serialize + serde::de::DeserializeOwned + Schema,
+    UuidComponent: ComponentId + DataComponent + Copy + Into<u128>,
+{
+    let world = component.world();
+    let component_name = component.name();
+
+    let schema = ComponentSchema::of::<T>(component_name.as_str());
+    let registered = world.get::<&PersistDbSingleton>(|db| db.0.register_schema(&schema, migrate));
+    if let Err(e) = registered {
+        panic!("Refusing to load persisted {component_name}: {e}");
+    }
+
+    tracing::info!("Registered persistent component: {component_name}");
+
+    // Add Persist tag and PersistLoader to the component entity
+    component.entity().add(Persist).set(PersistLoader {
+        load: |bytes, entity| match bincode::deserialize::<T>(bytes) {
+            Ok(component) => {
+                entity.set(component);
+            }
+            Err(e) => {
+                tracing::error!("Failed to deserialize component: {e}");
+            }
+        },
+        save: |entity| {
+            entity
+                .try_get::<&T>(|c| bincode::serialize(c).ok())
+                .flatten()
+        },
+    });
+    if let Some(throttle) = &throttle {
+        component.entity().set(throttle.clone());
+    }
+
+    // Create OnSet observer - fires when T is set on an entity that has UuidComponent
+    world
+        .observer::<flecs::OnSet, (&T, &UuidComponent)>()
+        .each_entity(move |entity, (component, uuid)| {
+            let uuid_val: u128 = (*uuid).into();
+
+            let Ok(bytes) = bincode::serialize(component) else {
+                tracing::error!("Failed to serialize {component_name}");
+                return;
+            };
+            let bytes = match &throttle {
+                Some(throttle) => {
+                    let now = throttle.now();
+                    let offered = throttle
+                        .throttle
+                        .lock()
+                        .unwrap()
+                        .offer(uuid_val, bytes, now);
+                    let Some(bytes) = offered else {
+                        return;
+                    };
+                    bytes
                 }
-            },
-            save: |entity| {
-                entity
-                    .try_get::<&T>(|c| bincode::serialize(c).ok())
-                    .flatten()
-            },
-        });
+                None => bytes,
+            };
 
-        // Create OnSet observer - fires when T is set on an entity that has UuidComponent
-        world
-            .observer::<flecs::OnSet, (&T, &UuidComponent)>()
-            .each_entity(move |entity, (component, uuid)| {
-                let uuid_val: u128 = (*uuid).into();
-
-                let Ok(bytes) = bincode::serialize(component) else {
-                    tracing::error!("Failed to serialize {component_name}");
-                    return;
-                };
-
-                entity.world().get::<&PersistDbSingleton>(|db| {
-                    if let Err(e) = db.0.save_bytes(uuid_val, &component_name, &bytes) {
-                        tracing::error!("Failed to persist {component_name}: {e}");
-                    }
-                });
+            entity.world().get::<&PersistDbSingleton>(|db| {
+                if let Err(e) = db.0.save_bytes(uuid_val, &component_name, &bytes) {
+                    tracing::error!("Failed to persist {component_name}: {e}");
+                }
             });
+        });
 
-        self
-    }
+    component
 }
 
 /// Load a specific persisted component for an entity.
@@ -233,7 +505,7 @@ mod tests {
     }
 
     /// Test position component
-    #[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+    #[derive(Component, Serialize, Deserialize, Schema, Debug, Clone, Copy, PartialEq)]
     struct TestPosition {
         x: f64,
         y: f64,
@@ -241,11 +513,22 @@ mod tests {
     }
 
     /// Test health component
-    #[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+    #[derive(Component, Serialize, Deserialize, Schema, Debug, Clone, Copy, PartialEq)]
     struct TestHealth {
         value: i32,
     }
 
+    /// `TestHealth` as a later build declares it
+    mod v2 {
+        use super::*;
+
+        #[derive(Component, Serialize, Deserialize, Schema, Debug, Clone, Copy, PartialEq)]
+        #[schema(version = 2)]
+        pub struct TestHealth {
+            pub value: i64,
+        }
+    }
+
     #[test]
     fn test_persist_saves_on_component_set() {
         let dir = tempfile::tempdir().unwrap();
@@ -420,4 +703,117 @@ mod tests {
             // This test just ensures no panic occurs
         });
     }
+
+    #[test]
+    fn test_persist_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let world = World::new();
+        let uuid = 0x5555_6666_7777_8888_u128;
+
+        init::<TestUuid>(&world, dir.path().to_str().unwrap());
+        world.component::<TestPosition>().persist::<TestUuid>();
+        world.component::<TestHealth>().persist::<TestUuid>();
+
+        let entity = world
+            .entity()
+            .set(TestPosition {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0,
+            })
+            .set(TestHealth { value: 7 });
+
+        // No UUID yet, so there is nothing to save under
+        assert_eq!(persist_transaction::<TestUuid>(entity).unwrap(), 0);
+
+        entity.set(TestUuid(uuid));
+        assert_eq!(persist_transaction::<TestUuid>(entity).unwrap(), 2);
+
+        world.get::<&PersistDbSingleton>(|db| {
+            let bytes = db.0.load_bytes(uuid, "TestHealth").unwrap().unwrap();
+            let loaded: TestHealth = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(loaded.value, 7);
+            assert!(db.0.load_bytes(uuid, "TestPosition").unwrap().is_some());
+        });
+    }
+
+    #[test]
+    fn test_persist_refuses_changed_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let uuid = 0x9999_aaaa_bbbb_cccc_u128;
+
+        {
+            let world = World::new();
+            init::<TestUuid>(&world, dir.path().to_str().unwrap());
+            world.component::<TestHealth>().persist::<TestUuid>();
+            world
+                .entity()
+                .set(TestUuid(uuid))
+                .set(TestHealth { value: 42 });
+        }
+
+        // Version 2 without a migration is refused
+        let refused = std::panic::catch_unwind(|| {
+            let world = World::new();
+            init::<TestUuid>(&world, dir.path().to_str().unwrap());
+            world.component::<v2::TestHealth>().persist::<TestUuid>();
+        });
+        assert!(refused.is_err());
+
+        // With one, stored rows are converted and load as version 2
+        let world = World::new();
+        init::<TestUuid>(&world, dir.path().to_str().unwrap());
+        world
+            .component::<v2::TestHealth>()
+            .persist_migrating::<TestUuid>(|bytes| {
+                let old: TestHealth = bincode::deserialize(bytes).ok()?;
+                bincode::serialize(&v2::TestHealth {
+                    value: i64::from(old.value),
+                })
+                .ok()
+            });
+
+        let entity = world.entity().set(TestUuid(uuid));
+        entity.get::<&v2::TestHealth>(|health| assert_eq!(health.value, 42));
+    }
+
+    #[test]
+    fn test_persist_throttled() {
+        let dir = tempfile::tempdir().unwrap();
+        let world = World::new();
+        let uuid = 0x4242_4242_4242_4242_u128;
+
+        init::<TestUuid>(&world, dir.path().to_str().unwrap());
+        world
+            .component::<TestHealth>()
+            .persist_throttled::<TestUuid>(Duration::from_secs(3600));
+
+        let stored = || {
+            world.get::<&PersistDbSingleton>(|db| {
+                let bytes = db.0.load_bytes(uuid, "TestHealth").unwrap()?;
+                Some(bincode::deserialize::<TestHealth>(&bytes).unwrap().value)
+            })
+        };
+
+        // The first value is written, the rest of the hour is held back
+        let entity = world
+            .entity()
+            .set(TestUuid(uuid))
+            .set(TestHealth { value: 1 });
+        for value in 2..=10 {
+            entity.set(TestHealth { value });
+        }
+        assert_eq!(stored(), Some(1));
+        assert_eq!(flush_due(&world), 0);
+
+        flush_entity::<TestUuid>(entity);
+        assert_eq!(stored(), Some(10));
+
+        // Losing the UUID writes what's held
+        entity.set(TestHealth { value: 11 });
+        entity.set(TestHealth { value: 12 });
+        assert_eq!(stored(), Some(11));
+        entity.remove(TestUuid::id());
+        assert_eq!(stored(), Some(12));
+    }
 }
@@ -0,0 +1,121 @@
+//! Retention policies and background maintenance for [`PersistDb`].
+//!
+//! Analytics-style components are written often and rarely read back, so
+//! without limits they grow the LMDB map file forever. A [`NamespacePolicy`]
+//! bounds one component namespace by age, total size, or both:
+//!
+//! ```ignore
+//! let db = PersistDb::open("data/persist")?
+//!     .with_policy("ChunkVisit", NamespacePolicy::new().ttl(Duration::from_secs(30 * 86_400)))
+//!     .with_policy("PlayerStats", NamespacePolicy::new().max_bytes(64 * 1024 * 1024));
+//! let db = Arc::new(db);
+//!
+//! // Enforce policies every 10 minutes on a background thread
+//! let task = MaintenanceTask::spawn(Arc::clone(&db), Duration::from_secs(600));
+//! ```
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::PersistDb;
+
+/// Retention rules for one component namespace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NamespacePolicy {
+    /// Delete rows not written or read for this long.
+    pub ttl: Option<Duration>,
+    /// Evict least recently used rows while the namespace exceeds this many bytes.
+    pub max_bytes: Option<u64>,
+}
+
+impl NamespacePolicy {
+    /// A policy with no limits.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            ttl: None,
+            max_bytes: None,
+        }
+    }
+
+    /// Expire rows not touched within `ttl`.
+    #[must_use]
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Cap the namespace at `max_bytes` of stored values.
+    #[must_use]
+    pub const fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+}
+
+/// What a maintenance pass removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    /// Rows deleted because their TTL ran out.
+    pub expired: usize,
+    /// Rows evicted to bring a namespace under its byte quota.
+    pub evicted: usize,
+    /// Total size of removed values.
+    pub bytes_freed: u64,
+}
+
+/// Runs [`PersistDb::maintain`] periodically on a background thread.
+///
+/// The thread stops when the task is dropped.
+pub struct MaintenanceTask {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MaintenanceTask {
+    /// How often the thread checks whether it should stop.
+    const POLL: Duration = Duration::from_millis(100);
+
+    /// Start maintaining `db` every `interval`.
+    ///
+    /// # Panics
+    /// Panics if the thread cannot be spawned.
+    #[must_use]
+    pub fn spawn(db: Arc<PersistDb>, interval: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = std::thread::Builder::new()
+            .name("persist-maintenance".to_string())
+            .spawn(move || {
+                let mut next = Instant::now() + interval;
+                while !thread_stop.load(Ordering::Relaxed) {
+                    if Instant::now() < next {
+                        std::thread::sleep(Self::POLL.min(interval));
+                        continue;
+                    }
+                    if let Err(e) = db.maintain() {
+                        tracing::error!("Persist maintenance failed: {e}");
+                    }
+                    next = Instant::now() + interval;
+                }
+            })
+            .expect("failed to spawn persist maintenance thread");
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for MaintenanceTask {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
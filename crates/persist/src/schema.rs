@@ -0,0 +1,194 @@
+//! Component schemas.
+//!
+//! Every persisted component records its [`ComponentSchema`] (name, version,
+//! fields and a hash of all three) in the database next to its rows. When the
+//! component is registered again, e.g. on the next startup, the recorded
+//! schema is compared with the compiled one before anything is loaded:
+//!
+//! - no recorded schema, or no stored rows: the compiled schema is recorded
+//! - same hash: nothing changed
+//! - older recorded version and a migration registered with
+//!   [`PersistExt::persist_migrating`](crate::PersistExt::persist_migrating):
+//!   every stored row is migrated and the new schema recorded, in one
+//!   transaction
+//! - anything else: the stored bytes can't be trusted to deserialize
+//!   correctly, so registration fails with a [`SchemaError`]
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Describes the layout of a persisted component; see `#[derive(Schema)]`.
+pub trait Schema {
+    /// Bumped, together with a migration, when the layout changes on purpose.
+    const VERSION: u32;
+
+    /// Every field and its type, in declaration order.
+    fn fields() -> Vec<FieldSchema>;
+}
+
+/// One field of a component schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    pub name: String,
+    /// The field's type as written, without whitespace.
+    pub ty: String,
+}
+
+impl FieldSchema {
+    #[must_use]
+    pub fn new(name: impl Into<String>, ty: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ty: ty.into(),
+        }
+    }
+}
+
+/// The recorded layout of a persisted component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentSchema {
+    pub name: String,
+    pub version: u32,
+    pub fields: Vec<FieldSchema>,
+    /// Stable hash of the name, version and fields.
+    pub hash: u64,
+}
+
+impl ComponentSchema {
+    /// Build a schema, computing its hash.
+    #[must_use]
+    pub fn new(name: impl Into<String>, version: u32, fields: Vec<FieldSchema>) -> Self {
+        let name = name.into();
+        let hash = schema_hash(&name, version, &fields);
+        Self {
+            name,
+            version,
+            fields,
+            hash,
+        }
+    }
+
+    /// The compiled schema of `T`, stored under `name`.
+    #[must_use]
+    pub fn of<T: Schema>(name: impl Into<String>) -> Self {
+        Self::new(name, T::VERSION, T::fields())
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap_or_default()
+    }
+
+    pub(crate) fn decode(bytes: &[u8]) -> Option<Self> {
+        bincode::deserialize(bytes).ok()
+    }
+}
+
+/// FNV-1a over every part, each followed by a separator byte so that
+/// `("ab", "c")` and `("a", "bc")` differ. Unlike `DefaultHasher`, this is
+/// stable across Rust releases, which a hash kept on disk needs.
+fn schema_hash(name: &str, version: u32, fields: &[FieldSchema]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes.iter().chain(&[0xff]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+    feed(name.as_bytes());
+    feed(&version.to_le_bytes());
+    for field in fields {
+        feed(field.name.as_bytes());
+        feed(field.ty.as_bytes());
+    }
+    hash
+}
+
+/// Why a component's stored data can't be used with its compiled schema.
+#[derive(Debug)]
+pub enum SchemaError {
+    /// The layout changed without a version bump.
+    Changed {
+        stored: ComponentSchema,
+        current: ComponentSchema,
+    },
+    /// The stored version is newer than the compiled one (a downgrade).
+    Newer {
+        stored: ComponentSchema,
+        current: ComponentSchema,
+    },
+    /// The version was bumped, but no migration was registered.
+    NoMigration {
+        stored: ComponentSchema,
+        current: ComponentSchema,
+    },
+    /// The database failed while checking or migrating.
+    Db(heed::Error),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Changed { stored, current } => write!(
+                f,
+                "{} changed without a version bump (stored hash {:016x}, now {:016x}, both version {})",
+                current.name, stored.hash, current.hash, current.version
+            ),
+            Self::Newer { stored, current } => write!(
+                f,
+                "{} is stored at version {}, newer than this build's version {}",
+                current.name, stored.version, current.version
+            ),
+            Self::NoMigration { stored, current } => write!(
+                f,
+                "{} needs a migration from version {} to {}",
+                current.name, stored.version, current.version
+            ),
+            Self::Db(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<heed::Error> for SchemaError {
+    fn from(e: heed::Error) -> Self {
+        Self::Db(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_hash_is_stable_and_sensitive() {
+        let fields = vec![FieldSchema::new("x", "f64"), FieldSchema::new("y", "f64")];
+        let schema = ComponentSchema::new("Position", 1, fields.clone());
+        assert_eq!(
+            schema.hash,
+            ComponentSchema::new("Position", 1, fields).hash
+        );
+
+        let renamed = ComponentSchema::new(
+            "Position",
+            1,
+            vec![FieldSchema::new("x", "f64"), FieldSchema::new("z", "f64")],
+        );
+        let retyped = ComponentSchema::new(
+            "Position",
+            1,
+            vec![FieldSchema::new("x", "f64"), FieldSchema::new("y", "f32")],
+        );
+        let bumped = ComponentSchema::new("Position", 2, schema.fields.clone());
+        for other in [renamed, retyped, bumped] {
+            assert_ne!(schema.hash, other.hash);
+        }
+
+        let decoded = ComponentSchema::decode(&schema.encode()).unwrap();
+        assert_eq!(decoded, schema);
+    }
+}
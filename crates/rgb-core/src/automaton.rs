@@ -0,0 +1,303 @@
+//! Double-buffered cellular automata over chunk cells
+//!
+//! Each step reads every chunk's [`CellData`], plus a one-cell border taken
+//! from its neighbor chunks, and writes the next generation to its
+//! [`NextCellData`]. Buffers are swapped only once every chunk has been
+//! stepped, so no chunk sees a neighbor's next generation early.
+//!
+//! Chunks are stepped in [`SimColor`] batches, each spread across threads.
+//! Reads come from the current buffers and every chunk writes only its own
+//! next buffer, so chunks in a batch never race.
+//!
+//! Only chunks that are [`Active`] or border an active chunk are stepped, so
+//! rules must keep a neighborhood without live cells dead.
+
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::thread;
+
+use flecs_ecs::prelude::*;
+
+use crate::chunk_manager::get_neighbor;
+use crate::color::Color;
+use crate::components::{Active, CellData, Direction, Dirty, NextCellData, SimColor};
+use crate::pos::CHUNK_SIZE;
+
+/// Cells of one chunk, indexed `[y][x]`
+pub type CellGrid = [[bool; CHUNK_SIZE]; CHUNK_SIZE];
+
+const PADDED_SIZE: usize = CHUNK_SIZE + 2;
+
+/// Batches smaller than this are stepped on the calling thread
+const PARALLEL_THRESHOLD: usize = 4;
+
+/// A cell and its eight neighbors, indexed `[1 + dy][1 + dx]`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Neighborhood {
+    pub cells: [[bool; 3]; 3],
+}
+
+impl Neighborhood {
+    pub const fn center(&self) -> bool {
+        self.cells[1][1]
+    }
+
+    /// The cell at an offset of `-1..=1` on each axis
+    pub const fn get(&self, dx: i32, dy: i32) -> bool {
+        self.cells[(1 + dy) as usize][(1 + dx) as usize]
+    }
+
+    /// Number of live cells around the center
+    pub fn alive_neighbors(&self) -> u8 {
+        let alive = self.cells.iter().flatten().filter(|&&c| c).count() as u8;
+        alive - u8::from(self.center())
+    }
+}
+
+/// Transition function of a cellular automaton
+pub trait Rule: Send + Sync {
+    /// Next state of the neighborhood's center cell
+    fn next(&self, neighborhood: &Neighborhood) -> bool;
+}
+
+impl<F: Fn(&Neighborhood) -> bool + Send + Sync> Rule for F {
+    fn next(&self, neighborhood: &Neighborhood) -> bool {
+        self(neighborhood)
+    }
+}
+
+/// A chunk's cells surrounded by a one-cell border from its neighbors,
+/// indexed `[y + 1][x + 1]`
+#[derive(Clone, Debug)]
+pub struct PaddedCells {
+    cells: [[bool; PADDED_SIZE]; PADDED_SIZE],
+}
+
+impl PaddedCells {
+    /// Cells with a dead border
+    pub fn new(cells: &CellGrid) -> Self {
+        let mut padded = [[false; PADDED_SIZE]; PADDED_SIZE];
+        for (y, row) in cells.iter().enumerate() {
+            padded[y + 1][1..=CHUNK_SIZE].copy_from_slice(row);
+        }
+        Self { cells: padded }
+    }
+
+    /// Cells with the border filled in through the chunk's neighbor
+    /// relationships; missing neighbors count as dead
+    pub fn gather(world: &World, chunk: Entity, cells: &CellData) -> Self {
+        let mut padded = Self::new(&cells.cells);
+        for dir in Direction::ALL {
+            let neighbor = get_neighbor(world, chunk, dir).and_then(|neighbor| {
+                world
+                    .entity_from_id(neighbor)
+                    .try_get::<&CellData>(|cells| cells.cells)
+            });
+            if let Some(neighbor) = neighbor {
+                padded.fill_border(dir, &neighbor);
+            }
+        }
+        padded
+    }
+
+    /// Copy the cells bordering this chunk from its neighbor in `dir`
+    pub fn fill_border(&mut self, dir: Direction, neighbor: &CellGrid) {
+        let span = |offset: i32| match offset {
+            -1 => 0..1,
+            0 => 1..CHUNK_SIZE + 1,
+            _ => CHUNK_SIZE + 1..PADDED_SIZE,
+        };
+        let (dx, dy) = dir.offset();
+        for y in span(dy) {
+            for x in span(dx) {
+                // Padded 0 is the neighbor's last row/column, padded 17 its first
+                let nx = (x + CHUNK_SIZE - 1) % CHUNK_SIZE;
+                let ny = (y + CHUNK_SIZE - 1) % CHUNK_SIZE;
+                self.cells[y][x] = neighbor[ny][nx];
+            }
+        }
+    }
+
+    /// Whether the chunk and its border have no live cells
+    pub fn is_dead(&self) -> bool {
+        self.cells.iter().flatten().all(|&c| !c)
+    }
+
+    /// The neighborhood of the chunk cell at `(x, y)`
+    pub fn neighborhood(&self, x: usize, y: usize) -> Neighborhood {
+        Neighborhood {
+            cells: std::array::from_fn(|dy| std::array::from_fn(|dx| self.cells[y + dy][x + dx])),
+        }
+    }
+
+    /// Apply `rule` to every cell of the chunk
+    pub fn step(&self, rule: &impl Rule) -> CellGrid {
+        std::array::from_fn(|y| std::array::from_fn(|x| rule.next(&self.neighborhood(x, y))))
+    }
+}
+
+/// Step a batch of chunks, spread across threads if it's large enough
+///
+/// # Panics
+///
+/// If `rule` panics.
+pub fn step_batch(batch: &[PaddedCells], rule: &impl Rule) -> Vec<CellGrid> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    if batch.len() < PARALLEL_THRESHOLD || threads == 1 {
+        return batch.iter().map(|cells| cells.step(rule)).collect();
+    }
+
+    let per_thread = batch.len().div_ceil(threads);
+    thread::scope(|scope| {
+        let handles: Vec<_> = batch
+            .chunks(per_thread)
+            .map(|part| {
+                scope.spawn(move || {
+                    part.iter()
+                        .map(|cells| cells.step(rule))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        // Spawn every thread before joining any
+        let mut next = Vec::with_capacity(batch.len());
+        for handle in handles {
+            next.extend(handle.join().expect("automaton rule panicked"));
+        }
+        next
+    })
+}
+
+/// Advance every active chunk, and every chunk bordering one, by one
+/// generation. Changed chunks are marked [`Dirty`]; [`Active`] is updated to
+/// match whether a chunk has live cells.
+pub fn step_automaton(world: &World, rule: &impl Rule) {
+    let mut candidates = HashSet::new();
+    world
+        .query::<()>()
+        .with(Active)
+        .build()
+        .each_entity(|chunk, ()| {
+            candidates.insert(chunk.id());
+            for dir in Direction::ALL {
+                candidates.extend(get_neighbor(world, chunk.id(), dir));
+            }
+        });
+
+    // Gather every neighborhood before any buffer is written
+    let mut batches: [(Vec<Entity>, Vec<PaddedCells>); Color::ALL.len()] = Default::default();
+    for chunk in candidates {
+        let Some((cells, color)) = world
+            .entity_from_id(chunk)
+            .try_get::<(&CellData, &SimColor)>(|(cells, color)| (cells.clone(), color.0))
+        else {
+            continue;
+        };
+        let padded = PaddedCells::gather(world, chunk, &cells);
+        if padded.is_dead() {
+            continue;
+        }
+        let (entities, grids) = &mut batches[usize::from(color) % Color::ALL.len()];
+        entities.push(chunk);
+        grids.push(padded);
+    }
+
+    for (entities, grids) in &batches {
+        for (&chunk, next) in entities.iter().zip(step_batch(grids, rule)) {
+            world
+                .entity_from_id(chunk)
+                .try_get::<&mut NextCellData>(|buffer| buffer.cells = next);
+        }
+    }
+
+    // Swap buffers
+    for &chunk in batches.iter().flat_map(|(entities, _)| entities) {
+        let chunk = world.entity_from_id(chunk);
+        let Some(changed) = chunk.try_get::<(&mut CellData, &NextCellData)>(|(cells, next)| {
+            let changed = cells.cells != next.cells;
+            cells.cells = next.cells;
+            changed
+        }) else {
+            continue;
+        };
+        if changed {
+            chunk.add(Dirty);
+        }
+        if chunk.try_get::<&CellData>(CellData::is_empty) == Some(true) {
+            chunk.remove(Active);
+        } else {
+            chunk.add(Active);
+        }
+    }
+}
+
+/// Register a system that steps the automaton once per frame
+pub fn register_automaton<R: Rule + 'static>(world: &World, name: &str, rule: R) {
+    world.system_named::<()>(name).each_iter(move |it, _, ()| {
+        let world = it.world();
+        step_automaton(&world, &rule);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(n: &Neighborhood) -> bool {
+        matches!(
+            (n.center(), n.alive_neighbors()),
+            (true, 2 | 3) | (false, 3)
+        )
+    }
+
+    fn grid(cells: &[(usize, usize)]) -> CellGrid {
+        let mut grid = [[false; CHUNK_SIZE]; CHUNK_SIZE];
+        for &(x, y) in cells {
+            grid[y][x] = true;
+        }
+        grid
+    }
+
+    #[test]
+    fn blinker_oscillates() {
+        let horizontal = grid(&[(7, 7), (8, 7), (9, 7)]);
+        let vertical = grid(&[(8, 6), (8, 7), (8, 8)]);
+
+        let next = PaddedCells::new(&horizontal).step(&life);
+        assert_eq!(next, vertical);
+        assert_eq!(PaddedCells::new(&next).step(&life), horizontal);
+    }
+
+    #[test]
+    fn border_comes_from_neighbors() {
+        let mut padded = PaddedCells::new(&grid(&[]));
+        // Neighbor to the east: its first column borders our last
+        padded.fill_border(Direction::E, &grid(&[(0, 4), (1, 5)]));
+        // Neighbor to the southwest: only its top-right corner borders us
+        padded.fill_border(Direction::SW, &grid(&[(15, 15), (14, 15)]));
+
+        assert!(padded.neighborhood(15, 4).get(1, 0));
+        assert!(!padded.neighborhood(15, 5).get(1, 0));
+        assert!(padded.neighborhood(0, 0).get(-1, -1));
+        assert!(!padded.neighborhood(1, 0).get(-1, -1));
+        assert!(!padded.is_dead());
+    }
+
+    #[test]
+    fn births_across_chunk_edges() {
+        // A vertical blinker in the east neighbor's first column births a
+        // cell in our last column
+        let mut padded = PaddedCells::new(&grid(&[]));
+        padded.fill_border(Direction::E, &grid(&[(0, 6), (0, 7), (0, 8)]));
+        assert_eq!(padded.step(&life), grid(&[(15, 7)]));
+    }
+
+    #[test]
+    fn parallel_batch_matches_serial() {
+        let batch: Vec<_> = (0..16)
+            .map(|i| PaddedCells::new(&grid(&[(i, 3), (i, 4), (i, 5)])))
+            .collect();
+        let serial: Vec<_> = batch.iter().map(|cells| cells.step(&life)).collect();
+        assert_eq!(step_batch(&batch, &life), serial);
+    }
+}
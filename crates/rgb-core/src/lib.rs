@@ -1,8 +1,12 @@
+mod automaton;
 mod chunk_manager;
 mod color;
 mod components;
 mod pos;
 
+pub use automaton::{
+    CellGrid, Neighborhood, PaddedCells, Rule, register_automaton, step_automaton, step_batch,
+};
 pub use chunk_manager::{
     ChunkIndex, get_neighbor, link_chunk_neighbors, spawn_chunk, unlink_chunk_neighbors,
 };
@@ -35,6 +35,40 @@
 //! - Cannot be persisted to storage
 //! - Should be used sparingly (prefer relations for per-entity data)
 //!
+//! ## Relations and Tags
+//!
+//! `#[component(relation)]` marks a fieldless struct as a relation, generating
+//! an `rgb_ecs::Relation` impl (so `Follows::pair(target)` builds a
+//! `Pair<Follows>`). Add `target = T` to require that targets have component
+//! `T`; `World::insert_pair` checks this in debug builds. Add `one_to_one` to
+//! allow at most one source per target (`rgb_ecs::Cardinality::OneToOne`).
+//!
+//! `#[component(tag)]` marks a fieldless marker component.
+//!
+//! ```ignore
+//! #[derive(Component, Clone, Default)]
+//! #[component(relation, target = Player)]
+//! struct Follows;
+//! ```
+//!
+//! ## Requirements
+//!
+//! `#[component(requires = T)]` (repeatable) declares that the component
+//! can't be inserted on an entity without `T`. By default the missing `T` is
+//! inserted with `T::default()`; add `on_missing = reject` to reject the
+//! insert instead. The derive implements `rgb_ecs::ComponentRequires`, so
+//! `world.register_requirements::<Self>()` turns the declarations on.
+//!
+//! ```ignore
+//! #[derive(Component, Clone)]
+//! #[component(requires = Position, requires = Velocity)]
+//! struct Projectile { damage: f32 }
+//!
+//! #[derive(Component, Clone)]
+//! #[component(requires = Player, on_missing = reject)]
+//! struct Gamemode(u8);
+//! ```
+//!
 //! # Forbidden Types (for non-opaque)
 //!
 //! - `Vec<T>` - Use relations: spawn child entities with `(Data, ChildOf(parent))`
@@ -52,11 +86,19 @@
 //! - `Option<T>` where T is allowed
 //! - Other `#[derive(Component)]` structs
 //! - `Entity` (entity references)
+//!
+//! # Reflection
+//!
+//! Every derived component also gets an `rgb_ecs::ComponentReflect` impl
+//! listing each struct field's name, type tag, source type, and byte offset.
+//! Offsets come from `offset_of!` and tags from the field's type through
+//! `rgb_ecs::TagOf`, which is what makes the impl sound. Enums and opaque
+//! components report no fields.
 
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::{
-    Attribute, Data, DeriveInput, Fields, GenericArgument, Meta, Path, PathArguments, Type,
+    Attribute, Data, DeriveInput, Fields, GenericArgument, Path, PathArguments, Type,
     spanned::Spanned,
 };
 
@@ -174,19 +216,119 @@ const FORBIDDEN_TYPES: &[(&str, &str)] = &[
     ),
 ];
 
-/// Check if the derive has `#[component(opaque)]` attribute
-fn is_opaque(attrs: &[Attribute]) -> bool {
+/// Options parsed from `#[component(...)]` attributes.
+#[derive(Default)]
+struct ComponentAttrs {
+    /// `#[component(opaque)]` - skip field validation.
+    opaque: bool,
+    /// `#[component(relation)]` - usable as the `R` in `Pair<R>`.
+    relation: bool,
+    /// `#[component(tag)]` - zero-sized marker component.
+    tag: bool,
+    /// `#[component(relation, target = T)]` - targets must have component `T`.
+    target: Option<Type>,
+    /// `#[component(relation, one_to_one)]` - at most one source per target.
+    one_to_one: bool,
+    /// `#[component(requires = T)]` - can't be inserted without `T`.
+    requires: Vec<Type>,
+    /// `#[component(on_missing = reject)]` - reject inserts lacking a
+    /// requirement instead of inserting its default.
+    on_missing: Option<syn::Ident>,
+}
+
+/// Parse all `#[component(...)]` attributes on the derive input.
+fn parse_component_attrs(attrs: &[Attribute]) -> syn::Result<ComponentAttrs> {
+    let mut parsed = ComponentAttrs::default();
     for attr in attrs {
-        if attr.path().is_ident("component") {
-            if let Meta::List(meta_list) = &attr.meta {
-                let tokens = meta_list.tokens.to_string();
-                if tokens.trim() == "opaque" {
-                    return true;
+        if !attr.path().is_ident("component") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("opaque") {
+                parsed.opaque = true;
+            } else if meta.path.is_ident("relation") {
+                parsed.relation = true;
+            } else if meta.path.is_ident("tag") {
+                parsed.tag = true;
+            } else if meta.path.is_ident("target") {
+                parsed.target = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("one_to_one") {
+                parsed.one_to_one = true;
+            } else if meta.path.is_ident("requires") {
+                parsed.requires.push(meta.value()?.parse()?);
+            } else if meta.path.is_ident("on_missing") {
+                let policy: syn::Ident = meta.value()?.parse()?;
+                if policy != "default" && policy != "reject" {
+                    return Err(syn::Error::new(
+                        policy.span(),
+                        "expected `on_missing = default` or `on_missing = reject`",
+                    ));
                 }
+                parsed.on_missing = Some(policy);
+            } else {
+                return Err(meta.error(
+                    "unknown component attribute; expected `opaque`, `relation`, `tag`, `target = Type`, `one_to_one`, `requires = Type`, or `on_missing = default|reject`",
+                ));
             }
-        }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// Validate `relation`/`tag`/`target` usage.
+///
+/// Relations and tags carry no data: a `Pair<R>` stores only its target, so
+/// any fields on `R` would be silently dropped.
+fn check_marker_attrs(
+    input: &DeriveInput,
+    attrs: &ComponentAttrs,
+    errors: &mut Vec<proc_macro2::TokenStream>,
+) {
+    if attrs.target.is_some() && !attrs.relation {
+        errors.push(quote_spanned! {
+            input.ident.span() =>
+            compile_error!("`target = ...` is only valid together with #[component(relation)].");
+        });
+    }
+
+    if attrs.one_to_one && !attrs.relation {
+        errors.push(quote_spanned! {
+            input.ident.span() =>
+            compile_error!("`one_to_one` is only valid together with #[component(relation)].");
+        });
+    }
+
+    if attrs.on_missing.is_some() && attrs.requires.is_empty() {
+        errors.push(quote_spanned! {
+            input.ident.span() =>
+            compile_error!("`on_missing = ...` is only valid together with `requires = Type`.");
+        });
+    }
+
+    if attrs.relation && attrs.opaque {
+        errors.push(quote_spanned! {
+            input.ident.span() =>
+            compile_error!("A component cannot be both `relation` and `opaque`.");
+        });
+    }
+
+    if !(attrs.relation || attrs.tag) {
+        return;
+    }
+
+    let is_unit = match &input.data {
+        Data::Struct(data) => data.fields.is_empty(),
+        Data::Enum(_) | Data::Union(_) => false,
+    };
+    if !is_unit {
+        errors.push(quote_spanned! {
+            input.ident.span() =>
+            compile_error!("Relations and tags must be fieldless structs (e.g. `struct ChildOf;`).\n\
+                           A Pair<R> only stores its target entity, so fields on R would be lost.\n\
+                           Put per-relation data on a separate component instead.");
+        });
     }
-    false
 }
 
 /// Derive macro for ECS components.
@@ -205,17 +347,36 @@ fn is_opaque(attrs: &[Attribute]) -> bool {
 /// #[derive(Component, Clone)]
 /// #[component(opaque)]
 /// struct NetworkHandle { sender: Sender<Bytes> }
+///
+/// // Relation whose targets must be players
+/// #[derive(Component, Clone, Default)]
+/// #[component(relation, target = Player)]
+/// struct Follows;
+///
+/// // Zero-sized marker
+/// #[derive(Component, Clone)]
+/// #[component(tag)]
+/// struct Dead;
+///
+/// // Inserting a Projectile also inserts a default Velocity if missing
+/// #[derive(Component, Clone)]
+/// #[component(requires = Velocity)]
+/// struct Projectile;
 /// ```
 #[proc_macro_derive(Component, attributes(component))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
 
-    // Check for #[component(opaque)]
-    let opaque = is_opaque(&input.attrs);
+    let attrs = match parse_component_attrs(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let opaque = attrs.opaque;
 
     // Collect all field types and check them (unless opaque)
     let mut errors = Vec::new();
+    check_marker_attrs(&input, &attrs, &mut errors);
 
     if !opaque {
         match &input.data {
@@ -245,9 +406,24 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     // Generate the impl
     // Since Component has a blanket impl for all Send + Sync + 'static types,
     // we just need to verify the type meets the constraints.
-    // The derive is primarily for compile-time validation of field types.
+    // The derive is primarily for compile-time validation of field types,
+    // plus reflection metadata (see `reflect_fields`).
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
+    // Enums have no stable field offsets and opaque internals stay hidden.
+    let fields = match &input.data {
+        Data::Struct(data) if !opaque => reflect_fields(&quote!(#name #ty_generics), &data.fields),
+        _ => Vec::new(),
+    };
+
+    let relation_impl = if attrs.relation {
+        relation_impl(&input, &attrs)
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+
+    let requires_impl = requires_impl(&input, &attrs);
+
     let expanded = quote! {
         // Static assertions to verify the type is suitable for ECS
         const _: () = {
@@ -261,11 +437,109 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
                 _assert_component::<#name #ty_generics>();
             }
         };
+
+        // SAFETY: offsets come from `offset_of!` and tags from `TagOf`.
+        #[allow(unsafe_code)]
+        unsafe impl #impl_generics ::rgb_ecs::ComponentReflect for #name #ty_generics #where_clause {
+            const FIELDS: &'static [::rgb_ecs::FieldInfo] = &[#(#fields),*];
+        }
+
+        #relation_impl
+
+        #requires_impl
     };
 
     TokenStream::from(expanded)
 }
 
+/// Generate the `Relation` impl for `#[component(relation)]`.
+fn relation_impl(input: &DeriveInput, attrs: &ComponentAttrs) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let target = attrs.target.as_ref().map(|target| {
+        let target_name = quote!(#target).to_string();
+        quote! {
+            const TARGET: Option<&'static str> = Some(#target_name);
+
+            fn is_valid_target(world: &::rgb_ecs::World, target: ::rgb_ecs::Entity) -> bool {
+                world.has::<#target>(target)
+            }
+        }
+    });
+
+    let cardinality = attrs.one_to_one.then(|| {
+        quote! {
+            const CARDINALITY: ::rgb_ecs::Cardinality = ::rgb_ecs::Cardinality::OneToOne;
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::rgb_ecs::Relation for #name #ty_generics #where_clause {
+            #cardinality
+            #target
+        }
+    }
+}
+
+/// Generate the `ComponentRequires` impl from `requires = T` attributes.
+fn requires_impl(input: &DeriveInput, attrs: &ComponentAttrs) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let reject = attrs
+        .on_missing
+        .as_ref()
+        .is_some_and(|policy| policy == "reject");
+    let require = if reject {
+        quote!(require)
+    } else {
+        quote!(require_or_default)
+    };
+    let requires = &attrs.requires;
+
+    quote! {
+        impl #impl_generics ::rgb_ecs::ComponentRequires for #name #ty_generics #where_clause {
+            fn register_requirements(world: &mut ::rgb_ecs::World) {
+                #(world.#require::<Self, #requires>();)*
+            }
+        }
+    }
+}
+
+/// Build one `FieldInfo` expression per field for the `ComponentReflect` impl.
+fn reflect_fields(
+    self_ty: &proc_macro2::TokenStream,
+    fields: &Fields,
+) -> Vec<proc_macro2::TokenStream> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let (field_name, member) = match &field.ident {
+                Some(ident) => (ident.to_string(), quote!(#ident)),
+                None => {
+                    let index = syn::Index::from(index);
+                    (index.index.to_string(), quote!(#index))
+                }
+            };
+            let ty = &field.ty;
+            let type_name = quote!(#ty).to_string();
+            quote! {
+                ::rgb_ecs::FieldInfo {
+                    name: #field_name,
+                    type_tag: {
+                        use ::rgb_ecs::TagFallback as _;
+                        ::rgb_ecs::TagOf::<#ty>::TAG
+                    },
+                    type_name: #type_name,
+                    offset: ::core::mem::offset_of!(#self_ty, #member),
+                }
+            }
+        })
+        .collect()
+}
+
 fn check_fields(fields: &Fields, errors: &mut Vec<proc_macro2::TokenStream>) {
     match fields {
         Fields::Named(named) => {
@@ -0,0 +1,11 @@
+//! Test that relations cannot carry fields.
+
+use rgb_ecs_derive::Component;
+
+#[derive(Component, Clone, Default)]
+#[component(relation)]
+struct Follows {
+    distance: f32,
+}
+
+fn main() {}
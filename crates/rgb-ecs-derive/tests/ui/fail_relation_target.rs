@@ -0,0 +1,12 @@
+//! Test that `target = ...` requires `relation`.
+
+use rgb_ecs_derive::Component;
+
+#[derive(Component, Clone)]
+struct Player;
+
+#[derive(Component, Clone)]
+#[component(tag, target = Player)]
+struct Marked;
+
+fn main() {}
@@ -0,0 +1,9 @@
+//! Test that `on_missing = ...` requires `requires = Type`.
+
+use rgb_ecs_derive::Component;
+
+#[derive(Component, Clone)]
+#[component(on_missing = reject)]
+struct Gamemode(u8);
+
+fn main() {}
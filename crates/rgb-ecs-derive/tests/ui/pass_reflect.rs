@@ -0,0 +1,40 @@
+//! Test that derived components expose reflection metadata.
+
+use rgb_ecs::{ComponentReflect, Entity, TypeTag};
+use rgb_ecs_derive::Component;
+
+#[derive(Component, Clone)]
+struct Player {
+    id: u64,
+    health: f32,
+    target: Option<Entity>,
+    name: [u8; 16],
+}
+
+#[derive(Component, Clone, Copy)]
+struct Wrapper<T: Copy + Send + Sync + 'static>(T, bool);
+
+#[derive(Component, Clone, Copy)]
+enum GameMode {
+    Survival,
+    Creative,
+}
+
+fn main() {
+    let fields = Player::FIELDS;
+    assert_eq!(fields.len(), 4);
+    assert_eq!(fields[0].name, "id");
+    assert_eq!(fields[0].type_tag, TypeTag::U64);
+    assert_eq!(fields[1].offset, core::mem::offset_of!(Player, health));
+    assert_eq!(fields[2].type_tag, TypeTag::Option);
+    assert_eq!(fields[3].type_tag, TypeTag::Array);
+
+    let wrapper = Wrapper(7_i32, true);
+    // Generic fields are tagged `Other`, whatever they end up being
+    assert_eq!(wrapper.read_field::<i32>("0"), None);
+    assert_eq!(wrapper.read_field::<bool>("1"), Some(true));
+    assert_eq!(Wrapper::<i32>::field("1").unwrap().type_tag, TypeTag::Bool);
+
+    assert!(GameMode::FIELDS.is_empty());
+    let _ = (GameMode::Survival, GameMode::Creative);
+}
@@ -0,0 +1,52 @@
+//! Test that relations and tags derive, with and without target and
+//! cardinality constraints.
+
+use rgb_ecs::{Cardinality, Pair, Relation, World};
+use rgb_ecs_derive::Component;
+
+#[derive(Component, Clone, Copy)]
+struct Player {
+    id: u64,
+}
+
+#[derive(Component, Clone, Copy, Default)]
+#[component(relation, target = Player)]
+struct Follows;
+
+#[derive(Component, Clone, Copy, Default)]
+#[component(relation)]
+struct Likes;
+
+#[derive(Component, Clone, Copy, Default)]
+#[component(relation, one_to_one, target = Player)]
+struct Rides;
+
+#[derive(Component, Clone, Copy)]
+#[component(tag)]
+struct Dead;
+
+fn main() {
+    let mut world = World::new();
+    let player = world.spawn(Player { id: 1 });
+    let rock = world.spawn_empty();
+    let follower = world.spawn(Dead);
+
+    let pair: Pair<Follows> = Follows::pair(player);
+    assert_eq!(pair.target(), player);
+
+    assert_eq!(Follows::TARGET, Some("Player"));
+    assert!(Follows::is_valid_target(&world, player));
+    assert!(!Follows::is_valid_target(&world, rock));
+
+    assert_eq!(Likes::TARGET, None);
+    assert!(Likes::is_valid_target(&world, rock));
+
+    assert!(world.insert_pair::<Follows>(follower, player));
+    assert!(world.insert_pair::<Likes>(follower, rock));
+    assert!(world.has_pair::<Follows>(follower, player));
+
+    assert_eq!(Follows::CARDINALITY, Cardinality::ManyToOne);
+    assert_eq!(Rides::CARDINALITY, Cardinality::OneToOne);
+    assert!(world.insert_pair::<Rides>(follower, player));
+    assert!(!world.insert_pair::<Rides>(rock, player));
+}
@@ -0,0 +1,41 @@
+//! Test that `requires = T` registers requirements, with both policies.
+
+use rgb_ecs::{InsertError, World};
+use rgb_ecs_derive::Component;
+
+#[derive(Component, Clone, Copy, Default, PartialEq, Debug)]
+struct Velocity {
+    x: f64,
+}
+
+#[derive(Component, Clone, Copy)]
+#[component(tag)]
+struct Player;
+
+#[derive(Component, Clone, Copy)]
+#[component(requires = Velocity)]
+struct Projectile {
+    damage: f32,
+}
+
+#[derive(Component, Clone, Copy)]
+#[component(requires = Player, on_missing = reject)]
+struct Gamemode(u8);
+
+fn main() {
+    let mut world = World::new();
+    world.register_requirements::<Projectile>();
+    world.register_requirements::<Gamemode>();
+
+    let arrow = world.spawn(Projectile { damage: 2.0 });
+    assert_eq!(world.get::<Velocity>(arrow), Some(Velocity { x: 0.0 }));
+
+    let rock = world.spawn_empty();
+    assert!(matches!(
+        world.try_insert(rock, Gamemode(0)),
+        Err(InsertError::MissingRequirement { .. })
+    ));
+
+    let player = world.spawn(Player);
+    assert!(world.insert(player, Gamemode(1)));
+}
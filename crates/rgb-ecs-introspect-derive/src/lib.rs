@@ -13,6 +13,31 @@
 //!     pub z: f64,
 //! }
 //!
+//! // Hide sensitive fields from the dashboard
+//! #[derive(Clone, Serialize, Deserialize, Introspectable)]
+//! pub struct Connection {
+//!     #[introspectable(redact)]
+//!     pub address: String,
+//!     #[introspectable(skip)]
+//!     pub token: u128,
+//! }
+//!
+//! // Pre-fill the dashboard's spawn form from `Default`, or from JSON
+//! #[derive(Clone, Default, Serialize, Deserialize, Introspectable)]
+//! #[introspectable(default)]
+//! pub struct Health {
+//!     pub current: u32,
+//!     pub max: u32,
+//! }
+//!
+//! #[derive(Clone, Serialize, Deserialize, Introspectable)]
+//! #[introspectable(default = r#"{"x": 0.0, "y": 64.0, "z": 0.0}"#)]
+//! pub struct Spawn {
+//!     pub x: f64,
+//!     pub y: f64,
+//!     pub z: f64,
+//! }
+//!
 //! // For opaque components (won't serialize internals)
 //! #[derive(Clone, Introspectable)]
 //! #[introspectable(opaque)]
@@ -35,25 +60,55 @@ use syn::{DeriveInput, parse_macro_input};
 /// - `#[introspectable(opaque)]` - Marks the type as opaque, meaning it won't
 ///   serialize its internals. Instead, it returns `null` for JSON and cannot
 ///   be deserialized from the dashboard.
+/// - `#[introspectable(default)]` - Offer the type's `Default` value, as
+///   dashboard JSON, to pre-fill spawn and add-component forms.
+/// - `#[introspectable(default = "{...}")]` - Offer this JSON instead; it's
+///   checked to be valid JSON at compile time.
+///
+/// Field attributes (named fields only):
+///
+/// - `#[introspectable(skip)]` - Omit the field from dashboard JSON.
+/// - `#[introspectable(redact)]` - Show the field as `"<redacted>"`; its value
+///   never leaves the server.
+/// - `#[introspectable(rename = "x_pos")]` - Use a different key in dashboard JSON.
+///
+/// Skipped and redacted fields keep their server-side values when the
+/// dashboard edits the component, and are set to their type's `Default` when
+/// the component is built from JSON alone, so their types must implement
+/// `Default`.
 #[proc_macro_derive(Introspectable, attributes(introspectable))]
 pub fn derive_introspectable(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
-    // Check for #[introspectable(opaque)] attribute
-    let is_opaque = input.attrs.iter().any(|attr| {
-        if !attr.path().is_ident("introspectable") {
-            return false;
-        }
-        let Ok(nested) = attr.parse_args::<syn::Ident>() else {
-            return false;
-        };
-        nested == "opaque"
-    });
+    let container = match container_attrs(&input.attrs) {
+        Ok(container) => container,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let is_opaque = container.opaque;
 
     let type_name_str = name.to_string();
 
+    let default_json = match container.default {
+        Some(DefaultValue::Derived) => quote! {
+            fn default_json() -> Option<serde_json::Value> {
+                Some(rgb_ecs_introspect::Introspectable::to_json(&<Self as ::core::default::Default>::default()))
+            }
+        },
+        Some(DefaultValue::Json(json)) => quote! {
+            fn default_json() -> Option<serde_json::Value> {
+                serde_json::from_str(#json).ok()
+            }
+        },
+        None => quote!(),
+    };
+
+    let FieldAttrs { rules, fills } = match field_attrs(&input.data) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
     let expanded = if is_opaque {
         // Opaque implementation - no serialization
         quote! {
@@ -93,13 +148,17 @@ pub fn derive_introspectable(input: TokenStream) -> TokenStream {
         quote! {
             impl #impl_generics rgb_ecs_introspect::Introspectable for #name #ty_generics #where_clause {
                 fn to_json(&self) -> serde_json::Value {
-                    serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+                    let mut value = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+                    rgb_ecs_introspect::fields::encode(&mut value, Self::field_rules());
+                    value
                 }
 
-                fn from_json(value: serde_json::Value) -> Result<Self, rgb_ecs_introspect::IntrospectError>
+                fn from_json(mut value: serde_json::Value) -> Result<Self, rgb_ecs_introspect::IntrospectError>
                 where
                     Self: Sized,
                 {
+                    rgb_ecs_introspect::fields::decode(&mut value, Self::field_rules());
+                    #(#fills)*
                     serde_json::from_value(value).map_err(|e| {
                         rgb_ecs_introspect::IntrospectError::DeserializationFailed {
                             component: Self::type_name().to_string(),
@@ -108,6 +167,24 @@ pub fn derive_introspectable(input: TokenStream) -> TokenStream {
                     })
                 }
 
+                fn merge_json(&self, value: serde_json::Value) -> Result<Self, rgb_ecs_introspect::IntrospectError>
+                where
+                    Self: Sized,
+                {
+                    let mut base = serde_json::to_value(self)?;
+                    rgb_ecs_introspect::fields::merge(&mut base, value, Self::field_rules());
+                    serde_json::from_value(base).map_err(|e| {
+                        rgb_ecs_introspect::IntrospectError::DeserializationFailed {
+                            component: Self::type_name().to_string(),
+                            error: e.to_string(),
+                        }
+                    })
+                }
+
+                fn field_rules() -> &'static [rgb_ecs_introspect::FieldRule] {
+                    &[#(#rules),*]
+                }
+
                 fn type_name() -> &'static str {
                     #type_name_str
                 }
@@ -124,9 +201,139 @@ pub fn derive_introspectable(input: TokenStream) -> TokenStream {
                     // TODO: Could generate JSON schema from struct fields
                     None
                 }
+
+                #default_json
             }
         }
     };
 
     TokenStream::from(expanded)
 }
+
+/// Where a component's default dashboard JSON comes from.
+enum DefaultValue {
+    /// The type's `Default` impl.
+    Derived,
+    /// A JSON literal.
+    Json(String),
+}
+
+/// Container-level `#[introspectable(...)]` attributes.
+struct ContainerAttrs {
+    opaque: bool,
+    default: Option<DefaultValue>,
+}
+
+/// Parse the `#[introspectable(...)]` attributes on the type itself.
+fn container_attrs(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container = ContainerAttrs {
+        opaque: false,
+        default: None,
+    };
+    for attr in attrs {
+        if !attr.path().is_ident("introspectable") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("opaque") {
+                container.opaque = true;
+            } else if meta.path.is_ident("default") {
+                container.default = Some(if meta.input.peek(syn::Token![=]) {
+                    let json = meta.value()?.parse::<syn::LitStr>()?;
+                    if let Err(err) = serde_json::from_str::<serde_json::Value>(&json.value()) {
+                        return Err(syn::Error::new_spanned(
+                            json,
+                            format!("invalid default JSON: {err}"),
+                        ));
+                    }
+                    DefaultValue::Json(json.value())
+                } else {
+                    DefaultValue::Derived
+                });
+            } else {
+                return Err(meta.error(
+                    "unknown attribute; expected `opaque`, `default`, or `default = \"...\"`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    if container.opaque && container.default.is_some() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "opaque components can't have a default",
+        ));
+    }
+    Ok(container)
+}
+
+/// Code generated from `#[introspectable(...)]` field attributes.
+#[derive(Default)]
+struct FieldAttrs {
+    /// `FieldRule` expressions, one per field with at least one attribute.
+    rules: Vec<proc_macro2::TokenStream>,
+    /// Statements filling hidden fields of decoded input from `Default`.
+    fills: Vec<proc_macro2::TokenStream>,
+}
+
+/// Collect `#[introspectable(...)]` field attributes.
+fn field_attrs(data: &syn::Data) -> syn::Result<FieldAttrs> {
+    let syn::Data::Struct(data) = data else {
+        return Ok(FieldAttrs::default());
+    };
+
+    let mut attrs = FieldAttrs::default();
+    for field in &data.fields {
+        let mut visibility = None;
+        let mut rename = None;
+
+        for attr in &field.attrs {
+            if !attr.path().is_ident("introspectable") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    visibility = Some(quote!(Skip));
+                } else if meta.path.is_ident("redact") {
+                    visibility = Some(quote!(Redact));
+                } else if meta.path.is_ident("rename") {
+                    rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                } else {
+                    return Err(meta.error(
+                        "unknown field attribute; expected `skip`, `redact`, or `rename = \"...\"`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        if visibility.is_none() && rename.is_none() {
+            continue;
+        }
+
+        let Some(ident) = &field.ident else {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[introspectable(...)] field attributes require named fields",
+            ));
+        };
+        let name = ident.to_string();
+        let external = rename.unwrap_or_else(|| name.clone());
+        if visibility.is_some() {
+            let ty = &field.ty;
+            attrs.fills.push(quote! {
+                rgb_ecs_introspect::fields::fill_default::<#ty>(&mut value, #name);
+            });
+        }
+        let visibility = visibility.unwrap_or_else(|| quote!(Visible));
+
+        attrs.rules.push(quote! {
+            rgb_ecs_introspect::FieldRule {
+                name: #name,
+                external: #external,
+                visibility: rgb_ecs_introspect::FieldVisibility::#visibility,
+            }
+        });
+    }
+    Ok(attrs)
+}
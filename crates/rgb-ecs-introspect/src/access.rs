@@ -0,0 +1,503 @@
+//! Component-level access control for dashboard requests.
+//!
+//! An [`AccessPolicy`] grants each auth token [`Access::None`],
+//! [`Access::Read`] or [`Access::Write`] per component. Requests carry the
+//! token the dashboard was given (or none). There is no default policy: a
+//! server picks one explicitly, [`AccessPolicy::deny_all`] if nothing should
+//! be reachable until tokens are configured.
+//!
+//! ```ignore
+//! // Production: anyone may look, only the ops token may change anything
+//! let policy = Arc::new(
+//!     AccessPolicy::read_only()
+//!         .with_token("ops-secret", TokenPolicy::new(Access::Write))
+//!         .with_token(
+//!             "support",
+//!             TokenPolicy::new(Access::Read).with("Connection", Access::None),
+//!         ),
+//! );
+//! let sender = channels.sender(auth, policy.clone());
+//! ```
+//!
+//! [`IntrospectSender`](crate::IntrospectSender) runs every request through
+//! [`AccessPolicy::authorize`] before queueing it, so a request naming a
+//! component its token can't read or write never reaches the world.
+//! Requests whose components depend on the world (despawning, bulk despawns,
+//! prefab spawns and reverts) need write access to every component.
+//! Requests that name no component ([`IntrospectRequest::GetEntity`],
+//! [`IntrospectRequest::GetWorld`], [`IntrospectRequest::ListEntities`])
+//! pass, and their responses have the components the token can't read
+//! removed as the world sends them (see
+//! [`AccessPolicy::filter_response`]).
+//!
+//! Requests without a token, or with one the policy doesn't know, get the
+//! anonymous policy.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::IntrospectError;
+use crate::protocol::{EntityResponse, IntrospectRequest, ListEntitiesResponse, WorldResponse};
+
+/// What a token may do with a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    /// Hidden from the dashboard.
+    None,
+    /// Shown but not editable.
+    Read,
+    /// Shown and editable (including add, remove and despawn).
+    Write,
+}
+
+/// Access of one token: a default, with per-component exceptions.
+#[derive(Debug, Clone)]
+pub struct TokenPolicy {
+    default: Access,
+    components: HashMap<String, Access>,
+}
+
+impl TokenPolicy {
+    /// Grant `default` to every component.
+    #[must_use]
+    pub fn new(default: Access) -> Self {
+        Self {
+            default,
+            components: HashMap::new(),
+        }
+    }
+
+    /// Grant `access` to one component, by short name.
+    #[must_use]
+    pub fn with(mut self, component: impl Into<String>, access: Access) -> Self {
+        self.components.insert(component.into(), access);
+        self
+    }
+
+    /// Access granted to a component.
+    #[must_use]
+    pub fn access(&self, component: &str) -> Access {
+        self.components
+            .get(component)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// The component with the least access, `*` standing for the default.
+    fn weakest(&self) -> (&str, Access) {
+        self.components
+            .iter()
+            .map(|(component, &access)| (component.as_str(), access))
+            .fold(("*", self.default), |weakest, candidate| {
+                if candidate.1 < weakest.1 {
+                    candidate
+                } else {
+                    weakest
+                }
+            })
+    }
+}
+
+/// Per-token, per-component dashboard permissions.
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    anonymous: TokenPolicy,
+    tokens: HashMap<String, TokenPolicy>,
+}
+
+impl AccessPolicy {
+    /// A policy whose anonymous requests get `anonymous`.
+    #[must_use]
+    pub fn new(anonymous: TokenPolicy) -> Self {
+        Self {
+            anonymous,
+            tokens: HashMap::new(),
+        }
+    }
+
+    /// Nothing may be read or written unless a token grants it.
+    #[must_use]
+    pub fn deny_all() -> Self {
+        Self::new(TokenPolicy::new(Access::None))
+    }
+
+    /// Everyone may read and write everything (development).
+    #[must_use]
+    pub fn allow_all() -> Self {
+        Self::new(TokenPolicy::new(Access::Write))
+    }
+
+    /// Everyone may read everything, nothing may be written unless a token
+    /// grants it (production).
+    #[must_use]
+    pub fn read_only() -> Self {
+        Self::new(TokenPolicy::new(Access::Read))
+    }
+
+    /// Give requests carrying `token` their own policy.
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>, policy: TokenPolicy) -> Self {
+        self.tokens.insert(token.into(), policy);
+        self
+    }
+
+    /// Access a request with `token` has to a component.
+    #[must_use]
+    pub fn access(&self, token: Option<&str>, component: &str) -> Access {
+        token
+            .and_then(|token| self.tokens.get(token))
+            .unwrap_or(&self.anonymous)
+            .access(component)
+    }
+
+    /// Whether a request with `token` may see a component.
+    #[must_use]
+    pub fn can_read(&self, token: Option<&str>, component: &str) -> bool {
+        self.access(token, component) >= Access::Read
+    }
+
+    /// Fail unless a request with `token` has at least `needed` access to a
+    /// component.
+    pub fn check(
+        &self,
+        token: Option<&str>,
+        component: &str,
+        needed: Access,
+    ) -> Result<(), IntrospectError> {
+        if self.access(token, component) >= needed {
+            Ok(())
+        } else {
+            Err(IntrospectError::AccessDenied {
+                component: component.to_string(),
+                needed,
+            })
+        }
+    }
+
+    /// Fail unless a request with `token` has at least `needed` access to
+    /// every component, for requests whose components aren't known until
+    /// they reach the world.
+    pub fn check_every(&self, token: Option<&str>, needed: Access) -> Result<(), IntrospectError> {
+        let policy = token
+            .and_then(|token| self.tokens.get(token))
+            .unwrap_or(&self.anonymous);
+        match policy.weakest() {
+            (component, access) if access < needed => Err(IntrospectError::AccessDenied {
+                component: component.to_string(),
+                needed,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail unless the request's token may touch every component the
+    /// request names.
+    ///
+    /// Requests that name no component, like [`IntrospectRequest::GetEntity`],
+    /// pass; [`filter_response`](Self::filter_response) leaves what the
+    /// token can't read out of their responses.
+    pub fn authorize(&self, request: &IntrospectRequest) -> Result<(), IntrospectError> {
+        let token = request.token();
+        match request {
+            IntrospectRequest::GetWorld { .. }
+            | IntrospectRequest::WorldStats { .. }
+            | IntrospectRequest::GetEntity { .. }
+            | IntrospectRequest::GetPrefabs { .. }
+            | IntrospectRequest::GetComponentTypes { .. }
+            | IntrospectRequest::GetSystems { .. }
+            | IntrospectRequest::GetChunks { .. }
+            | IntrospectRequest::TickProfile { .. } => Ok(()),
+            IntrospectRequest::ListEntities { filter, .. } => self.check_all(
+                token,
+                filter.iter().flatten().map(String::as_str),
+                Access::Read,
+            ),
+            IntrospectRequest::GetComponent { component, .. } => {
+                self.check(token, component, Access::Read)
+            }
+            IntrospectRequest::GetHistory { component, .. }
+            | IntrospectRequest::SubscribeHistory { component, .. } => {
+                self.check_all(token, component.as_deref(), Access::Read)
+            }
+            IntrospectRequest::Query { spec, .. } => {
+                // Fields are `Component` or `Component.field`
+                let fields = spec
+                    .fields
+                    .iter()
+                    .map(|field| field.split_once('.').map_or(field.as_str(), |(c, _)| c));
+                let named = spec
+                    .with
+                    .iter()
+                    .chain(&spec.optional)
+                    .chain(&spec.filter)
+                    .chain(&spec.without)
+                    .map(String::as_str);
+                self.check_all(token, named.chain(fields), Access::Read)
+            }
+            IntrospectRequest::UpdateComponent { component, .. }
+            | IntrospectRequest::AddComponent { component, .. }
+            | IntrospectRequest::RemoveComponent { component, .. }
+            | IntrospectRequest::BulkUpdate { component, .. } => {
+                self.check(token, component, Access::Write)
+            }
+            IntrospectRequest::SpawnEntity { components, .. } => self.check_all(
+                token,
+                components.iter().map(|(component, _)| component.as_str()),
+                Access::Write,
+            ),
+            IntrospectRequest::SpawnPrefab { .. }
+            | IntrospectRequest::DespawnEntity { .. }
+            | IntrospectRequest::BulkDespawn { .. }
+            | IntrospectRequest::RevertToEntry { .. } => self.check_every(token, Access::Write),
+        }
+    }
+
+    /// Remove the components `token` can't read from an entity.
+    pub fn filter_entity(&self, response: &mut EntityResponse, token: Option<&str>) {
+        response
+            .components
+            .retain(|component| self.can_read(token, &component.name));
+    }
+
+    /// Remove the globals `token` can't read, keyed by component name.
+    pub fn filter_world(&self, response: &mut WorldResponse, token: Option<&str>) {
+        if let Some(globals) = response.globals.as_object_mut() {
+            globals.retain(|component, _| self.can_read(token, component));
+        }
+    }
+
+    /// Remove the component names `token` can't read from every summary.
+    pub fn filter_list(&self, response: &mut ListEntitiesResponse, token: Option<&str>) {
+        for entity in &mut response.entities {
+            entity
+                .components
+                .retain(|component| self.can_read(token, component));
+        }
+    }
+
+    /// Filter the response to a request that names no component with
+    /// [`filter_entity`](Self::filter_entity),
+    /// [`filter_world`](Self::filter_world) or
+    /// [`filter_list`](Self::filter_list) when the world sends it.
+    ///
+    /// [`IntrospectSender`](crate::IntrospectSender) does this for every
+    /// request it queues.
+    pub fn filter_response(policy: &Arc<Self>, request: &mut IntrospectRequest) {
+        let token = request.token().map(str::to_string);
+        let policy = Arc::clone(policy);
+        match request {
+            IntrospectRequest::GetEntity { response, .. } => {
+                response.set_filter(move |r| policy.filter_entity(r, token.as_deref()));
+            }
+            IntrospectRequest::GetWorld { response, .. } => {
+                response.set_filter(move |r| policy.filter_world(r, token.as_deref()));
+            }
+            IntrospectRequest::ListEntities { response, .. } => {
+                response.set_filter(move |r| policy.filter_list(r, token.as_deref()));
+            }
+            _ => {}
+        }
+    }
+
+    /// [`check`](Self::check) every component, e.g. all of an entity's
+    /// components before despawning it.
+    pub fn check_all<'a>(
+        &self,
+        token: Option<&str>,
+        components: impl IntoIterator<Item = &'a str>,
+        needed: Access,
+    ) -> Result<(), IntrospectError> {
+        components
+            .into_iter()
+            .try_for_each(|component| self.check(token, component, needed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ComponentValue, IntrospectChannels, oneshot};
+    use crate::{AuthConfig, Authenticator, Scopes};
+
+    fn policy() -> AccessPolicy {
+        AccessPolicy::read_only()
+            .with_token("ops", TokenPolicy::new(Access::Write))
+            .with_token(
+                "support",
+                TokenPolicy::new(Access::Read).with("Connection", Access::None),
+            )
+    }
+
+    #[test]
+    fn test_anonymous_and_unknown_tokens_are_read_only() {
+        let policy = policy();
+        for token in [None, Some("guess")] {
+            assert!(policy.can_read(token, "Position"));
+            assert!(policy.check(token, "Position", Access::Write).is_err());
+        }
+    }
+
+    #[test]
+    fn test_token_overrides() {
+        let policy = policy();
+        assert!(policy.check(Some("ops"), "Position", Access::Write).is_ok());
+        assert!(policy.can_read(Some("support"), "Position"));
+        assert!(!policy.can_read(Some("support"), "Connection"));
+    }
+
+    fn get_component(token: Option<&str>, component: &str) -> IntrospectRequest {
+        IntrospectRequest::GetComponent {
+            token: token.map(str::to_string),
+            entity: rgb_ecs::Entity::from_bits(1),
+            component: component.to_string(),
+            response: oneshot::channel().0,
+        }
+    }
+
+    #[test]
+    fn test_denied_component_read_is_rejected() {
+        let channels = IntrospectChannels::new(4);
+        let policy = AccessPolicy::read_only().with_token(
+            "support",
+            TokenPolicy::new(Access::Read).with("Connection", Access::None),
+        );
+        let auth = AuthConfig::new(Scopes::READ).with_token("support", Scopes::READ);
+        let sender = channels.sender(Arc::new(Authenticator::new(auth)), Arc::new(policy));
+
+        let err = sender
+            .send("a", get_component(Some("support"), "Connection"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IntrospectError::AccessDenied { component, needed: Access::Read } if component == "Connection"
+        ));
+        // Denied requests never reach the world
+        assert!(channels.request_rx.try_recv().is_err());
+
+        sender.send("a", get_component(None, "Connection")).unwrap();
+        assert!(channels.request_rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_deny_all_and_world_dependent_requests() {
+        let policy = AccessPolicy::deny_all().with_token(
+            "builder",
+            TokenPolicy::new(Access::Write).with("Player", Access::Read),
+        );
+        assert!(policy.authorize(&get_component(None, "Position")).is_err());
+        assert!(
+            policy
+                .authorize(&get_component(Some("builder"), "Position"))
+                .is_ok()
+        );
+
+        // Despawning could take a Player with it
+        let despawn = IntrospectRequest::DespawnEntity {
+            token: Some("builder".to_string()),
+            entity: rgb_ecs::Entity::from_bits(1),
+            response: oneshot::channel().0,
+        };
+        assert!(matches!(
+            policy.authorize(&despawn),
+            Err(IntrospectError::AccessDenied { component, .. }) if component == "Player"
+        ));
+        assert!(AccessPolicy::allow_all().authorize(&despawn).is_ok());
+    }
+
+    #[test]
+    fn test_check_all_fails_on_any_component() {
+        let policy = AccessPolicy::allow_all().with_token(
+            "limited",
+            TokenPolicy::new(Access::Write).with("Player", Access::Read),
+        );
+        let components = ["Position", "Player"];
+        assert!(policy.check_all(None, components, Access::Write).is_ok());
+        let err = policy
+            .check_all(Some("limited"), components, Access::Write)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            IntrospectError::AccessDenied { component, needed: Access::Write } if component == "Player"
+        ));
+    }
+
+    fn component(name: &str) -> ComponentValue {
+        ComponentValue {
+            name: name.to_string(),
+            full_name: format!("game::{name}"),
+            value: serde_json::Value::Null,
+            is_opaque: false,
+            opaque_info: None,
+            schema: None,
+        }
+    }
+
+    #[test]
+    fn test_get_entity_response_omits_hidden_components() {
+        let channels = IntrospectChannels::new(4);
+        let auth = AuthConfig::new(Scopes::READ).with_token("support", Scopes::READ);
+        let sender = channels.sender(Arc::new(Authenticator::new(auth)), Arc::new(policy()));
+
+        let (tx, rx) = oneshot::channel();
+        sender
+            .send(
+                "a",
+                IntrospectRequest::GetEntity {
+                    token: Some("support".to_string()),
+                    entity: rgb_ecs::Entity::from_bits(1),
+                    response: tx,
+                },
+            )
+            .unwrap();
+
+        // The world answers with everything; the token never sees Connection
+        let Ok(IntrospectRequest::GetEntity { response, .. }) = channels.request_rx.try_recv()
+        else {
+            panic!("expected the GetEntity request");
+        };
+        response
+            .send(EntityResponse {
+                found: true,
+                id: 1,
+                name: None,
+                components: vec![component("Position"), component("Connection")],
+                parent: None,
+                children: Vec::new(),
+            })
+            .unwrap_or_else(|_| panic!("receiver dropped"));
+        let names: Vec<_> = rx
+            .recv()
+            .unwrap()
+            .components
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, ["Position"]);
+    }
+
+    #[test]
+    fn test_world_and_list_responses_omit_hidden_components() {
+        let policy = policy();
+        let mut world = WorldResponse {
+            entity_count: 1,
+            archetype_count: 1,
+            component_count: 2,
+            globals: serde_json::json!({ "Connection": 1, "TickRate": 20 }),
+        };
+        policy.filter_world(&mut world, Some("support"));
+        assert_eq!(world.globals, serde_json::json!({ "TickRate": 20 }));
+
+        let mut list = ListEntitiesResponse {
+            entities: vec![crate::protocol::EntitySummary {
+                id: 1,
+                name: None,
+                components: vec!["Connection".to_string(), "Position".to_string()],
+            }],
+            total: 1,
+        };
+        policy.filter_list(&mut list, Some("ops"));
+        assert_eq!(list.entities[0].components.len(), 2);
+        policy.filter_list(&mut list, Some("support"));
+        assert_eq!(list.entities[0].components, ["Position"]);
+    }
+}
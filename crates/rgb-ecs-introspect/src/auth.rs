@@ -0,0 +1,663 @@
+//! Authentication for dashboard requests.
+//!
+//! Every [`IntrospectRequest`] carries the auth token the dashboard was
+//! given. An [`Authenticator`] decides what the token may do before the
+//! request reaches the world: requests go through an
+//! [`IntrospectSender`](crate::IntrospectSender), which authorizes them and
+//! only then queues them.
+//!
+//! Tokens come in two kinds:
+//!
+//! - **Static tokens** are configured up front in [`AuthConfig`], each with
+//!   the [`Scopes`] it grants. These are the tokens [`AccessPolicy`] knows.
+//! - **Session tokens** are issued in exchange for a static token by
+//!   [`Authenticator::issue_session`] and expire after
+//!   [`AuthConfig::session_ttl`], so the static token doesn't have to live
+//!   in a browser. A session may narrow the scopes of its static token but
+//!   never widen them. Authorizing a request replaces a session token with
+//!   the static token it was issued for, so component access is checked
+//!   against the same [`AccessPolicy`] entry.
+//!
+//! There is no default config: a server picks what anonymous requests get
+//! explicitly, [`Scopes::NONE`] if they should get nothing.
+//!
+//! Requests without a token get [`AuthConfig::anonymous`]. Requests with a
+//! token that is neither count as a failure for the client that sent them;
+//! after [`AuthConfig::max_failures`] within [`AuthConfig::failure_window`]
+//! the client is locked out for [`AuthConfig::lockout`].
+//!
+//! ```ignore
+//! let auth = Arc::new(Authenticator::new(
+//!     AuthConfig::new(Scopes::NONE)
+//!         .with_token("ops-secret", Scopes::all())
+//!         .with_token("viewer", "read,stats".parse()?),
+//! ));
+//! let sender = channels.sender(auth.clone(), Arc::new(AccessPolicy::read_only()));
+//!
+//! let session = auth.issue_session(&client_ip, "viewer", None)?;
+//! sender.send(&client_ip, IntrospectRequest::GetWorld {
+//!     token: Some(session.token),
+//!     response,
+//! })?;
+//! ```
+//!
+//! [`AccessPolicy`]: crate::AccessPolicy
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use bitflags::bitflags;
+use parking_lot::Mutex;
+
+use crate::IntrospectError;
+use crate::protocol::IntrospectRequest;
+
+bitflags! {
+    /// Kinds of requests a token may make.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Scopes: u8 {
+        /// Read the world, entities, components, queries, prefabs, component
+        /// types and chunks.
+        const READ = 1;
+        /// Change the world: update, add and remove components, spawn,
+        /// despawn and revert.
+        const WRITE = 1 << 1;
+        /// Read and subscribe to component history.
+        const HISTORY = 1 << 2;
+        /// Read world statistics, module resources and tick profiles.
+        const STATS = 1 << 3;
+    }
+}
+
+impl Scopes {
+    /// No requests at all.
+    pub const NONE: Self = Self::empty();
+}
+
+impl FromStr for Scopes {
+    type Err = IntrospectError;
+
+    /// Parse a comma-separated list such as `read,history`; `all` and
+    /// `none` stand for every scope and no scope.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|scope| !scope.is_empty())
+            .try_fold(Self::NONE, |scopes, scope| {
+                let scope = match scope.to_ascii_lowercase().as_str() {
+                    "read" => Self::READ,
+                    "write" => Self::WRITE,
+                    "history" => Self::HISTORY,
+                    "stats" => Self::STATS,
+                    "all" => Self::all(),
+                    "none" => Self::NONE,
+                    _ => return Err(IntrospectError::InvalidScope(scope.to_string())),
+                };
+                Ok(scopes | scope)
+            })
+    }
+}
+
+impl fmt::Display for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (Self::READ, "read"),
+            (Self::WRITE, "write"),
+            (Self::HISTORY, "history"),
+            (Self::STATS, "stats"),
+        ];
+        let mut first = true;
+        for (scope, name) in names {
+            if self.contains(scope) {
+                if !first {
+                    f.write_str(",")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        if first {
+            f.write_str("none")?;
+        }
+        Ok(())
+    }
+}
+
+impl IntrospectRequest {
+    /// The scope a token needs to make this request.
+    #[must_use]
+    pub const fn scope(&self) -> Scopes {
+        match self {
+            Self::GetWorld { .. }
+            | Self::ListEntities { .. }
+            | Self::GetEntity { .. }
+            | Self::GetComponent { .. }
+            | Self::GetPrefabs { .. }
+            | Self::Query { .. }
+            | Self::GetComponentTypes { .. }
+            | Self::GetChunks { .. } => Scopes::READ,
+            Self::UpdateComponent { .. }
+            | Self::AddComponent { .. }
+            | Self::RemoveComponent { .. }
+            | Self::SpawnEntity { .. }
+            | Self::SpawnPrefab { .. }
+            | Self::DespawnEntity { .. }
+            | Self::BulkUpdate { .. }
+            | Self::BulkDespawn { .. }
+            | Self::RevertToEntry { .. } => Scopes::WRITE,
+            Self::GetHistory { .. } | Self::SubscribeHistory { .. } => Scopes::HISTORY,
+            Self::WorldStats { .. } | Self::GetSystems { .. } | Self::TickProfile { .. } => {
+                Scopes::STATS
+            }
+        }
+    }
+
+    /// The auth token the request carries.
+    #[must_use]
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            Self::GetWorld { token, .. }
+            | Self::WorldStats { token, .. }
+            | Self::ListEntities { token, .. }
+            | Self::GetEntity { token, .. }
+            | Self::GetComponent { token, .. }
+            | Self::UpdateComponent { token, .. }
+            | Self::AddComponent { token, .. }
+            | Self::RemoveComponent { token, .. }
+            | Self::SpawnEntity { token, .. }
+            | Self::SpawnPrefab { token, .. }
+            | Self::GetPrefabs { token, .. }
+            | Self::DespawnEntity { token, .. }
+            | Self::BulkUpdate { token, .. }
+            | Self::BulkDespawn { token, .. }
+            | Self::Query { token, .. }
+            | Self::GetComponentTypes { token, .. }
+            | Self::GetSystems { token, .. }
+            | Self::GetChunks { token, .. }
+            | Self::TickProfile { token, .. }
+            | Self::GetHistory { token, .. }
+            | Self::SubscribeHistory { token, .. }
+            | Self::RevertToEntry { token, .. } => token.as_deref(),
+        }
+    }
+
+    fn token_slot_mut(&mut self) -> &mut Option<String> {
+        match self {
+            Self::GetWorld { token, .. }
+            | Self::WorldStats { token, .. }
+            | Self::ListEntities { token, .. }
+            | Self::GetEntity { token, .. }
+            | Self::GetComponent { token, .. }
+            | Self::UpdateComponent { token, .. }
+            | Self::AddComponent { token, .. }
+            | Self::RemoveComponent { token, .. }
+            | Self::SpawnEntity { token, .. }
+            | Self::SpawnPrefab { token, .. }
+            | Self::GetPrefabs { token, .. }
+            | Self::DespawnEntity { token, .. }
+            | Self::BulkUpdate { token, .. }
+            | Self::BulkDespawn { token, .. }
+            | Self::Query { token, .. }
+            | Self::GetComponentTypes { token, .. }
+            | Self::GetSystems { token, .. }
+            | Self::GetChunks { token, .. }
+            | Self::TickProfile { token, .. }
+            | Self::GetHistory { token, .. }
+            | Self::SubscribeHistory { token, .. }
+            | Self::RevertToEntry { token, .. } => token,
+        }
+    }
+}
+
+/// Static tokens and the rules for sessions and failed attempts.
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// Scopes of requests without a token.
+    pub anonymous: Scopes,
+    /// Static tokens and their scopes.
+    pub tokens: HashMap<String, Scopes>,
+    /// How long a session token stays valid.
+    pub session_ttl: Duration,
+    /// Failed attempts a client may make within `failure_window`.
+    pub max_failures: u32,
+    pub failure_window: Duration,
+    /// How long a client that made too many failed attempts is turned away.
+    pub lockout: Duration,
+}
+
+impl AuthConfig {
+    /// A config whose anonymous requests get `anonymous`.
+    #[must_use]
+    pub fn new(anonymous: Scopes) -> Self {
+        Self {
+            anonymous,
+            tokens: HashMap::new(),
+            session_ttl: Duration::from_secs(15 * 60),
+            max_failures: 5,
+            failure_window: Duration::from_secs(60),
+            lockout: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Add a static token.
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>, scopes: Scopes) -> Self {
+        self.tokens.insert(token.into(), scopes);
+        self
+    }
+}
+
+/// Who a request was authenticated as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// The static token, or `None` for anonymous requests.
+    pub token: Option<String>,
+    pub scopes: Scopes,
+}
+
+/// A freshly issued session token.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    pub scopes: Scopes,
+    /// How long until it expires.
+    pub expires_in: Duration,
+}
+
+#[derive(Debug)]
+struct SessionEntry {
+    /// The static token it was issued for.
+    token: String,
+    scopes: Scopes,
+    expires: Instant,
+}
+
+#[derive(Debug)]
+struct Failures {
+    count: u32,
+    since: Instant,
+    locked_until: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct AuthState {
+    sessions: HashMap<String, SessionEntry>,
+    /// Failed attempts by client.
+    failures: HashMap<String, Failures>,
+    /// Requests authenticated since the state was last pruned.
+    since_prune: u32,
+}
+
+/// Requests between prunes of expired sessions and stale failures, so
+/// neither grows with every client that ever connected.
+const PRUNE_EVERY: u32 = 1024;
+
+/// Checks tokens, issues sessions and limits failed attempts.
+///
+/// Shared between the web handlers; `client` is whatever identifies the
+/// sender of a request to the web server, usually its IP address.
+#[derive(Debug)]
+pub struct Authenticator {
+    config: AuthConfig,
+    state: Mutex<AuthState>,
+}
+
+impl Authenticator {
+    #[must_use]
+    pub fn new(config: AuthConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(AuthState::default()),
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &AuthConfig {
+        &self.config
+    }
+
+    /// Find out who a request from `client` carrying `token` comes from.
+    pub fn authenticate(
+        &self,
+        client: &str,
+        token: Option<&str>,
+    ) -> Result<Principal, IntrospectError> {
+        self.authenticate_at(client, token, Instant::now())
+    }
+
+    /// Exchange a static token for a session token with at most `scopes`
+    /// (all of the static token's if `None`).
+    pub fn issue_session(
+        &self,
+        client: &str,
+        token: &str,
+        scopes: Option<Scopes>,
+    ) -> Result<Session, IntrospectError> {
+        self.issue_session_at(client, token, scopes, Instant::now())
+    }
+
+    /// End a session early; returns whether it existed.
+    pub fn revoke(&self, session: &str) -> bool {
+        self.state.lock().sessions.remove(session).is_some()
+    }
+
+    /// Check that a request from `client` may be made, and replace a
+    /// session token in it with its static token.
+    pub fn authorize(
+        &self,
+        client: &str,
+        request: &mut IntrospectRequest,
+    ) -> Result<(), IntrospectError> {
+        let principal = self.authenticate(client, request.token())?;
+        let needed = request.scope();
+        if !principal.scopes.contains(needed) {
+            return Err(IntrospectError::Forbidden(needed));
+        }
+        *request.token_slot_mut() = principal.token;
+        Ok(())
+    }
+
+    /// Forget expired sessions and failed attempts that no longer count.
+    ///
+    /// Also done every 1024 authenticated requests.
+    pub fn prune(&self) {
+        self.prune_at(&mut self.state.lock(), Instant::now());
+    }
+
+    fn prune_at(&self, state: &mut AuthState, now: Instant) {
+        state.since_prune = 0;
+        state.sessions.retain(|_, session| session.expires > now);
+        let window = self.config.failure_window;
+        state.failures.retain(|_, failures| {
+            failures.locked_until.is_some_and(|until| until > now)
+                || now.duration_since(failures.since) < window
+        });
+    }
+
+    fn authenticate_at(
+        &self,
+        client: &str,
+        token: Option<&str>,
+        now: Instant,
+    ) -> Result<Principal, IntrospectError> {
+        let mut state = self.state.lock();
+        state.since_prune += 1;
+        if state.since_prune >= PRUNE_EVERY {
+            self.prune_at(&mut state, now);
+        }
+        if let Some(locked_until) = state
+            .failures
+            .get(client)
+            .and_then(|failures| failures.locked_until)
+            && locked_until > now
+        {
+            return Err(IntrospectError::RateLimited {
+                retry_after: locked_until - now,
+            });
+        }
+
+        let Some(token) = token else {
+            return Ok(Principal {
+                token: None,
+                scopes: self.config.anonymous,
+            });
+        };
+        if let Some(&scopes) = self.config.tokens.get(token) {
+            return Ok(Principal {
+                token: Some(token.to_string()),
+                scopes,
+            });
+        }
+        match state.sessions.get(token) {
+            Some(session) if session.expires > now => {
+                return Ok(Principal {
+                    token: Some(session.token.clone()),
+                    scopes: session.scopes,
+                });
+            }
+            Some(_) => {
+                state.sessions.remove(token);
+            }
+            None => {}
+        }
+
+        self.record_failure(&mut state, client, now);
+        Err(IntrospectError::Unauthorized)
+    }
+
+    fn issue_session_at(
+        &self,
+        client: &str,
+        token: &str,
+        scopes: Option<Scopes>,
+        now: Instant,
+    ) -> Result<Session, IntrospectError> {
+        let principal = self.authenticate_at(client, Some(token), now)?;
+        // Sessions are only issued for static tokens, so they can't be
+        // renewed forever
+        if principal.token.as_deref() != Some(token) {
+            return Err(IntrospectError::Unauthorized);
+        }
+        let scopes = scopes.map_or(principal.scopes, |scopes| scopes & principal.scopes);
+
+        let session = new_session_token();
+        self.state.lock().sessions.insert(
+            session.clone(),
+            SessionEntry {
+                token: token.to_string(),
+                scopes,
+                expires: now + self.config.session_ttl,
+            },
+        );
+        Ok(Session {
+            token: session,
+            scopes,
+            expires_in: self.config.session_ttl,
+        })
+    }
+
+    fn record_failure(&self, state: &mut AuthState, client: &str, now: Instant) {
+        let failures = state
+            .failures
+            .entry(client.to_string())
+            .or_insert(Failures {
+                count: 0,
+                since: now,
+                locked_until: None,
+            });
+        if now.duration_since(failures.since) >= self.config.failure_window {
+            failures.count = 0;
+            failures.since = now;
+        }
+        failures.count += 1;
+        if failures.count >= self.config.max_failures {
+            failures.locked_until = Some(now + self.config.lockout);
+            failures.count = 0;
+            failures.since = now;
+        }
+    }
+}
+
+/// An unguessable session token: 256 bits from the OS random number
+/// generator.
+fn new_session_token() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).expect("the OS random number generator failed");
+    bytes
+        .iter()
+        .fold(String::from("session-"), |mut token, byte| {
+            let _ = write!(token, "{byte:02x}");
+            token
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::oneshot;
+
+    fn auth() -> Authenticator {
+        Authenticator::new(
+            AuthConfig::new(Scopes::NONE)
+                .with_token("ops", Scopes::all())
+                .with_token("viewer", Scopes::READ | Scopes::STATS),
+        )
+    }
+
+    fn get_world(token: Option<&str>) -> IntrospectRequest {
+        IntrospectRequest::GetWorld {
+            token: token.map(str::to_string),
+            response: oneshot::channel().0,
+        }
+    }
+
+    fn despawn(token: Option<&str>) -> IntrospectRequest {
+        IntrospectRequest::DespawnEntity {
+            token: token.map(str::to_string),
+            entity: rgb_ecs::Entity::from_bits(1),
+            response: oneshot::channel().0,
+        }
+    }
+
+    #[test]
+    fn test_scopes_parse_and_display() {
+        let scopes: Scopes = "read, History".parse().unwrap();
+        assert_eq!(scopes, Scopes::READ | Scopes::HISTORY);
+        assert_eq!(scopes.to_string(), "read,history");
+        assert_eq!("all".parse::<Scopes>().unwrap(), Scopes::all());
+        assert_eq!(Scopes::NONE.to_string(), "none");
+        assert!(matches!(
+            "admin".parse::<Scopes>(),
+            Err(IntrospectError::InvalidScope(scope)) if scope == "admin"
+        ));
+    }
+
+    #[test]
+    fn test_static_tokens_need_the_request_scope() {
+        let auth = auth();
+        assert!(auth.authorize("a", &mut get_world(Some("viewer"))).is_ok());
+        assert!(auth.authorize("a", &mut despawn(Some("ops"))).is_ok());
+        assert!(matches!(
+            auth.authorize("a", &mut despawn(Some("viewer"))),
+            Err(IntrospectError::Forbidden(Scopes::WRITE))
+        ));
+        // Anonymous requests get nothing here
+        assert!(matches!(
+            auth.authorize("a", &mut get_world(None)),
+            Err(IntrospectError::Forbidden(Scopes::READ))
+        ));
+    }
+
+    #[test]
+    fn test_sessions_narrow_and_resolve_to_their_static_token() {
+        let auth = auth();
+        let session = auth.issue_session("a", "ops", Some(Scopes::READ)).unwrap();
+        assert_eq!(session.scopes, Scopes::READ);
+
+        let mut request = get_world(Some(&session.token));
+        auth.authorize("a", &mut request).unwrap();
+        assert_eq!(request.token(), Some("ops"));
+        assert!(matches!(
+            auth.authorize("a", &mut despawn(Some(&session.token))),
+            Err(IntrospectError::Forbidden(_))
+        ));
+
+        // Sessions can't widen scopes or mint more sessions
+        let viewer = auth
+            .issue_session("a", "viewer", Some(Scopes::all()))
+            .unwrap();
+        assert_eq!(viewer.scopes, Scopes::READ | Scopes::STATS);
+        assert!(auth.issue_session("a", &viewer.token, None).is_err());
+
+        assert!(auth.revoke(&session.token));
+        assert!(matches!(
+            auth.authenticate("a", Some(&session.token)),
+            Err(IntrospectError::Unauthorized)
+        ));
+    }
+
+    #[test]
+    fn test_session_tokens_are_random() {
+        let a = new_session_token();
+        let b = new_session_token();
+        assert_ne!(a, b);
+        let hex = a.strip_prefix("session-").unwrap();
+        assert_eq!(hex.len(), 64);
+        assert!(hex.bytes().all(|byte| byte.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_sessions_expire() {
+        let auth = auth();
+        let now = Instant::now();
+        let session = auth.issue_session_at("a", "viewer", None, now).unwrap();
+        let ttl = auth.config().session_ttl;
+        assert!(
+            auth.authenticate_at("a", Some(&session.token), now + ttl / 2)
+                .is_ok()
+        );
+        assert!(matches!(
+            auth.authenticate_at("a", Some(&session.token), now + ttl),
+            Err(IntrospectError::Unauthorized)
+        ));
+    }
+
+    #[test]
+    fn test_failed_attempts_lock_out_the_client() {
+        let auth = auth();
+        let now = Instant::now();
+        let config = auth.config().clone();
+        for _ in 0..config.max_failures {
+            assert!(matches!(
+                auth.authenticate_at("a", Some("guess"), now),
+                Err(IntrospectError::Unauthorized)
+            ));
+        }
+
+        // Even the right token is turned away, but only for this client
+        assert!(matches!(
+            auth.authenticate_at("a", Some("ops"), now),
+            Err(IntrospectError::RateLimited { retry_after }) if retry_after == config.lockout
+        ));
+        assert!(auth.authenticate_at("b", Some("ops"), now).is_ok());
+        assert!(
+            auth.authenticate_at("a", Some("ops"), now + config.lockout)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_failures_outside_the_window_are_forgotten() {
+        let auth = auth();
+        let now = Instant::now();
+        let config = auth.config().clone();
+        for i in 0..config.max_failures * 2 {
+            let at = now + config.failure_window * i;
+            assert!(matches!(
+                auth.authenticate_at("a", Some("guess"), at),
+                Err(IntrospectError::Unauthorized)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_stale_state_is_pruned_while_authenticating() {
+        let auth = auth();
+        let now = Instant::now();
+        let session = auth.issue_session_at("a", "viewer", None, now).unwrap();
+        for client in 0..10 {
+            let _ = auth.authenticate_at(&client.to_string(), Some("guess"), now);
+        }
+        assert_eq!(auth.state.lock().failures.len(), 10);
+
+        let later = now + auth.config().session_ttl + auth.config().failure_window;
+        for _ in 0..PRUNE_EVERY {
+            auth.authenticate_at("b", None, later).unwrap();
+        }
+        let state = auth.state.lock();
+        assert!(state.failures.is_empty());
+        assert!(!state.sessions.contains_key(&session.token));
+    }
+}
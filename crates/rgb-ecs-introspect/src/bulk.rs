@@ -0,0 +1,342 @@
+//! Bulk edits of every entity a query matches.
+//!
+//! [`IntrospectRequest::BulkUpdate`] and [`IntrospectRequest::BulkDespawn`]
+//! pick their entities with a `query-dsl` query instead of an ID, e.g.
+//! `Zombie, Health, !Boss`. Both are applied while handling the request, so
+//! every match changes in the same tick:
+//!
+//! ```ignore
+//! IntrospectRequest::BulkUpdate { query, component, patch, dry_run, response, .. } => {
+//!     let result = registry.bulk_update(&mut world, &query, &component, &patch, dry_run, Some(&history));
+//!     response.send(result.into());
+//! }
+//! ```
+//!
+//! The patch is a JSON merge patch (RFC 7396) applied to each entity's
+//! current dashboard JSON: objects merge field by field and `null` removes a
+//! field, which then keeps its current value like any field left out of an
+//! edit. With `dry_run` set, nothing changes and the response only says how
+//! many entities would be affected, so the dashboard can ask for
+//! confirmation first.
+//!
+//! Only component terms are supported: `||` and pair terms are refused, and
+//! a query needs at least one component an entity must have, so `*` can't
+//! despawn the world.
+//!
+//! [`IntrospectRequest::BulkUpdate`]: crate::IntrospectRequest::BulkUpdate
+//! [`IntrospectRequest::BulkDespawn`]: crate::IntrospectRequest::BulkDespawn
+
+use query_dsl::{Operator, TermKind, parse_query};
+use rgb_ecs::{ComponentId, Entity, World};
+use serde_json::Value;
+
+use crate::history::{ChangeSource, HistoryStore};
+use crate::protocol::BulkResponse;
+use crate::{IntrospectError, IntrospectRegistry};
+
+/// Outcome of a bulk update or despawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkResult {
+    /// Entities the query matched.
+    pub matched: usize,
+    /// Entities changed, or that would be on a dry run.
+    pub affected: usize,
+    /// Entities the patch couldn't be applied to, with the reason.
+    pub failed: Vec<(u64, String)>,
+}
+
+impl IntrospectRegistry {
+    /// Entities matching a query DSL expression, in ascending ID order.
+    ///
+    /// Every component named in the query must be introspectable.
+    pub fn matching(&self, world: &World, query: &str) -> Result<Vec<Entity>, IntrospectError> {
+        let query = parse_query(query).map_err(|e| IntrospectError::InvalidQuery(e.to_string()))?;
+
+        let mut with = Vec::new();
+        let mut without = Vec::new();
+        for term in &query.terms {
+            let name = match &term.kind {
+                TermKind::Component(name) => name,
+                TermKind::Wildcard => continue,
+                TermKind::Pair(_) => {
+                    return Err(IntrospectError::InvalidQuery(
+                        "pair terms aren't supported".to_string(),
+                    ));
+                }
+            };
+            let id = self
+                .component_id(name)
+                .ok_or_else(|| IntrospectError::NotIntrospectable(name.clone()))?;
+            match term.operator {
+                Operator::And => with.push(id),
+                Operator::Not => without.push(id),
+                Operator::Optional => {}
+                Operator::Or => {
+                    return Err(IntrospectError::InvalidQuery(
+                        "|| terms aren't supported".to_string(),
+                    ));
+                }
+            }
+        }
+        if with.is_empty() {
+            return Err(IntrospectError::InvalidQuery(
+                "a query needs at least one required component".to_string(),
+            ));
+        }
+
+        let matches = |entity: Entity, ids: &[ComponentId], has: bool| {
+            ids.iter().all(|&id| world.has_by_id(entity, id) == has)
+        };
+        let mut entities: Vec<Entity> = world
+            .entities_iter()
+            .filter(|&entity| !world.is_global(entity))
+            .filter(|&entity| matches(entity, &with, true) && matches(entity, &without, false))
+            .collect();
+        entities.sort_unstable_by_key(|entity| entity.to_bits());
+        Ok(entities)
+    }
+
+    /// Apply a JSON merge patch to `component` on every entity `query`
+    /// matches that has it.
+    ///
+    /// Entities the patch can't be applied to are left as they are and
+    /// listed in [`BulkResult::failed`]. Each change is recorded in
+    /// `history`, if given.
+    pub fn bulk_update(
+        &self,
+        world: &mut World,
+        query: &str,
+        component: &str,
+        patch: &Value,
+        dry_run: bool,
+        history: Option<&HistoryStore>,
+    ) -> Result<BulkResult, IntrospectError> {
+        let info = self
+            .get_by_name(component)
+            .ok_or_else(|| IntrospectError::NotIntrospectable(component.to_string()))?;
+        let matched = self.matching(world, query)?;
+        let targets: Vec<Entity> = matched
+            .iter()
+            .copied()
+            .filter(|&entity| world.has_by_id(entity, info.component_id))
+            .collect();
+
+        let mut result = BulkResult {
+            matched: matched.len(),
+            ..BulkResult::default()
+        };
+        if dry_run {
+            result.affected = targets.len();
+            return Ok(result);
+        }
+
+        for entity in targets {
+            let Some(mut value) = info.get_json(world, entity) else {
+                continue;
+            };
+            merge_patch(&mut value, patch);
+            match info.update_json(world, entity, &value) {
+                Ok(update) => {
+                    result.affected += 1;
+                    if let Some(history) = history {
+                        history.record_update(
+                            entity.to_bits(),
+                            info.name.to_string(),
+                            update.old,
+                            update.new,
+                            ChangeSource::Dashboard,
+                        );
+                    }
+                }
+                Err(err) => result.failed.push((entity.to_bits(), err.to_string())),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Despawn every entity `query` matches.
+    pub fn bulk_despawn(
+        &self,
+        world: &mut World,
+        query: &str,
+        dry_run: bool,
+    ) -> Result<BulkResult, IntrospectError> {
+        let matched = self.matching(world, query)?;
+        let mut result = BulkResult {
+            matched: matched.len(),
+            ..BulkResult::default()
+        };
+        if dry_run {
+            result.affected = matched.len();
+            return Ok(result);
+        }
+
+        result.affected = matched
+            .into_iter()
+            .filter(|&entity| world.despawn(entity))
+            .count();
+        Ok(result)
+    }
+}
+
+impl From<Result<BulkResult, IntrospectError>> for BulkResponse {
+    fn from(result: Result<BulkResult, IntrospectError>) -> Self {
+        match result {
+            Ok(result) => Self {
+                success: true,
+                matched: result.matched,
+                affected: result.affected,
+                failed: result.failed,
+                error: None,
+            },
+            Err(err) => Self {
+                success: false,
+                matched: 0,
+                affected: 0,
+                failed: Vec::new(),
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Apply a JSON merge patch (RFC 7396) to `target`.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+    use crate::Introspectable;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+    struct Boss {
+        phase: u32,
+    }
+
+    fn world() -> (World, IntrospectRegistry, Vec<Entity>) {
+        let mut world = World::new();
+        world.register_component::<Health>();
+        world.register_component::<Boss>();
+        let mut registry = IntrospectRegistry::new();
+        registry.register::<Health>(&world);
+        registry.register::<Boss>(&world);
+
+        let mobs = (0..3)
+            .map(|_| {
+                world.spawn(Health {
+                    current: 5,
+                    max: 20,
+                })
+            })
+            .collect();
+        let boss = world.spawn(Health {
+            current: 50,
+            max: 100,
+        });
+        world.insert(boss, Boss { phase: 1 });
+        (world, registry, mobs)
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut value = json!({ "a": 1, "b": { "c": 2, "d": 3 } });
+        merge_patch(&mut value, &json!({ "a": null, "b": { "c": 4 }, "e": [5] }));
+        assert_eq!(value, json!({ "b": { "c": 4, "d": 3 }, "e": [5] }));
+    }
+
+    #[test]
+    fn test_bulk_update_with_dry_run() {
+        let (mut world, registry, mobs) = world();
+        let patch = json!({ "current": 20 });
+
+        let dry = registry
+            .bulk_update(&mut world, "Health, !Boss", "Health", &patch, true, None)
+            .unwrap();
+        assert_eq!((dry.matched, dry.affected), (3, 3));
+        assert_eq!(world.get::<Health>(mobs[0]).unwrap().current, 5);
+
+        let done = registry
+            .bulk_update(&mut world, "Health, !Boss", "Health", &patch, false, None)
+            .unwrap();
+        assert_eq!(done, dry);
+        for mob in mobs {
+            assert_eq!(
+                world.get::<Health>(mob),
+                Some(Health {
+                    current: 20,
+                    max: 20
+                })
+            );
+        }
+        let boss = registry.matching(&world, "Boss").unwrap()[0];
+        assert_eq!(world.get::<Health>(boss).unwrap().current, 50);
+    }
+
+    #[test]
+    fn test_bulk_despawn() {
+        let (mut world, registry, mobs) = world();
+
+        let dry = registry
+            .bulk_despawn(&mut world, "Health, !Boss", true)
+            .unwrap();
+        assert_eq!(dry.affected, 3);
+        assert!(world.is_alive(mobs[0]));
+
+        registry
+            .bulk_despawn(&mut world, "Health, !Boss", false)
+            .unwrap();
+        assert!(mobs.iter().all(|&mob| !world.is_alive(mob)));
+        assert_eq!(registry.matching(&world, "Health").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_refused_queries() {
+        let (world, registry, _) = world();
+        for query in [
+            "*",
+            "!Boss",
+            "Health || Boss",
+            "(ChildOf, Boss)",
+            "Health, !",
+        ] {
+            assert!(
+                matches!(
+                    registry.matching(&world, query),
+                    Err(IntrospectError::InvalidQuery(_))
+                ),
+                "{query}"
+            );
+        }
+        assert!(matches!(
+            registry.matching(&world, "Velocity"),
+            Err(IntrospectError::NotIntrospectable(_))
+        ));
+    }
+}
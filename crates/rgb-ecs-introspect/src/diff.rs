@@ -0,0 +1,133 @@
+//! Field-level diffs between two JSON component values.
+//!
+//! Used to report exactly which fields a dashboard edit changed, both in
+//! [`UpdateResponse`](crate::UpdateResponse) and in the recorded
+//! [`HistoryEntry`](crate::HistoryEntry).
+//!
+//! Diffs are computed on dashboard JSON (after skip/redact rules), so hidden
+//! values never appear in a diff.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single changed field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Dotted path to the field (e.g. `"pos.x"`, `"slots.3"`).
+    /// Empty when the whole value is a scalar that changed.
+    pub path: String,
+    /// Value before the change (`None` if the field was added).
+    pub old: Option<Value>,
+    /// Value after the change (`None` if the field was removed).
+    pub new: Option<Value>,
+}
+
+/// All field changes between two versions of a component.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentDiff {
+    /// Changed fields, in a stable (path-sorted) order.
+    pub changes: Vec<FieldChange>,
+}
+
+impl ComponentDiff {
+    /// Compute the field-level diff from `old` to `new`.
+    ///
+    /// Objects and arrays are compared recursively; any other value is a leaf.
+    #[must_use]
+    pub fn between(old: &Value, new: &Value) -> Self {
+        let mut changes = Vec::new();
+        diff_value(String::new(), Some(old), Some(new), &mut changes);
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { changes }
+    }
+
+    /// Whether nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Number of changed fields.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+fn diff_value(
+    path: String,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (old, new) {
+        (Some(Value::Object(old_map)), Some(Value::Object(new_map))) => {
+            for (key, old_field) in old_map {
+                diff_value(join(&path, key), Some(old_field), new_map.get(key), changes);
+            }
+            for (key, new_field) in new_map {
+                if !old_map.contains_key(key) {
+                    diff_value(join(&path, key), None, Some(new_field), changes);
+                }
+            }
+        }
+        (Some(Value::Array(old_items)), Some(Value::Array(new_items))) => {
+            for index in 0..old_items.len().max(new_items.len()) {
+                diff_value(
+                    join(&path, &index.to_string()),
+                    old_items.get(index),
+                    new_items.get(index),
+                    changes,
+                );
+            }
+        }
+        (old, new) if old != new => changes.push(FieldChange {
+            path,
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_nested_field_changes() {
+        let old = json!({ "x": 1.0, "y": 2.0, "inner": { "a": 1, "b": [1, 2] } });
+        let new = json!({ "x": 1.0, "y": 5.0, "inner": { "a": 1, "b": [1, 3, 4] } });
+
+        let diff = ComponentDiff::between(&old, &new);
+        let paths: Vec<_> = diff.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, ["inner.b.1", "inner.b.2", "y"]);
+
+        assert_eq!(diff.changes[1].old, None);
+        assert_eq!(diff.changes[1].new, Some(json!(4)));
+        assert_eq!(diff.changes[2].old, Some(json!(2.0)));
+    }
+
+    #[test]
+    fn test_identical_values() {
+        let value = json!({ "x": 1 });
+        assert!(ComponentDiff::between(&value, &value).is_empty());
+    }
+
+    #[test]
+    fn test_scalar_change() {
+        let diff = ComponentDiff::between(&json!(1), &json!(2));
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.changes[0].path, "");
+    }
+}
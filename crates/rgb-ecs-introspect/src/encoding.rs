@@ -0,0 +1,199 @@
+//! Wire encodings for introspection payloads.
+//!
+//! Responses are JSON by default, which is what the browser dashboard
+//! speaks. Serializing a large [`QueryResponse`](crate::QueryResponse) to
+//! JSON is slow, though, and loses precision on 64-bit entity IDs in
+//! JavaScript, so non-browser clients (tools, tests, other servers) can ask
+//! for MessagePack instead.
+//!
+//! Binary encoding is opt-in per channel with
+//! [`IntrospectChannels::with_encoding`](crate::IntrospectChannels::with_encoding).
+//! A handler then picks the encoding for each request from its `Accept`
+//! header and encodes the response with it:
+//!
+//! ```ignore
+//! let encoding = sender.negotiate(headers.get("accept").and_then(|v| v.to_str().ok()));
+//! let body = encoding.encode(&response)?;
+//! ([(CONTENT_TYPE, encoding.content_type())], body)
+//! ```
+//!
+//! Clients that don't ask for MessagePack, browsers included, keep getting
+//! JSON. MessagePack payloads encode structs as maps with field names, so
+//! they decode to the same shape as the JSON.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::IntrospectError;
+
+/// Encoding of a request or response body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// JSON, always available.
+    #[default]
+    Json,
+    /// MessagePack, with struct fields by name.
+    MessagePack,
+}
+
+impl Encoding {
+    /// The MIME type of a body in this encoding.
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// The encoding a `Content-Type` or `Accept` entry names, ignoring
+    /// parameters such as `charset`.
+    #[must_use]
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        match mime.to_ascii_lowercase().as_str() {
+            "application/json" => Some(Self::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MessagePack)
+            }
+            _ => None,
+        }
+    }
+
+    /// Pick the encoding for a response: `offered` if the client's `Accept`
+    /// header lists it, JSON otherwise.
+    ///
+    /// Quality values aren't weighed; a client listing MessagePack at all
+    /// can read it.
+    #[must_use]
+    pub fn negotiate(accept: Option<&str>, offered: Self) -> Self {
+        let accepts = |encoding| {
+            accept.is_some_and(|accept| {
+                accept
+                    .split(',')
+                    .any(|entry| Self::from_content_type(entry) == Some(encoding))
+            })
+        };
+        if offered != Self::Json && accepts(offered) {
+            offered
+        } else {
+            Self::Json
+        }
+    }
+
+    /// Serialize `value` in this encoding.
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, IntrospectError> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(value)?),
+            Self::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| IntrospectError::Encoding(e.to_string()))
+            }
+        }
+    }
+
+    /// Deserialize a body in this encoding, e.g. a posted
+    /// [`QuerySpec`](crate::QuerySpec).
+    pub fn decode<T: DeserializeOwned>(self, body: &[u8]) -> Result<T, IntrospectError> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(body)?),
+            Self::MessagePack => {
+                rmp_serde::from_slice(body).map_err(|e| IntrospectError::Encoding(e.to_string()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+        })
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = IntrospectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "msgpack" | "messagepack" => Ok(Self::MessagePack),
+            _ => Err(IntrospectError::Encoding(format!("unknown encoding: {s}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value, json};
+
+    use super::*;
+    use crate::protocol::QueryResultRow;
+    use crate::{QueryResponse, QuerySpec};
+
+    #[test]
+    fn test_negotiate() {
+        let browser = Some("text/html,application/xhtml+xml,*/*;q=0.8");
+        let tool = Some("application/msgpack, application/json;q=0.5");
+
+        assert_eq!(
+            Encoding::negotiate(browser, Encoding::MessagePack),
+            Encoding::Json
+        );
+        assert_eq!(
+            Encoding::negotiate(None, Encoding::MessagePack),
+            Encoding::Json
+        );
+        assert_eq!(
+            Encoding::negotiate(tool, Encoding::MessagePack),
+            Encoding::MessagePack
+        );
+        // The channel doesn't offer binary encoding
+        assert_eq!(Encoding::negotiate(tool, Encoding::Json), Encoding::Json);
+    }
+
+    #[test]
+    fn test_msgpack_matches_json() {
+        let mut components = Map::new();
+        components.insert("Position".into(), json!({"x": 1.5, "y": 64.0, "z": -3.0}));
+        let response = QueryResponse {
+            entities: vec![QueryResultRow {
+                entity: u64::MAX - 1,
+                name: Some("zombie".into()),
+                components,
+            }],
+            total: 1,
+            next_cursor: None,
+            execution_time_us: 42,
+        };
+
+        let msgpack = Encoding::MessagePack.encode(&response).unwrap();
+        let json = Encoding::Json.encode(&response).unwrap();
+        assert!(msgpack.len() < json.len());
+
+        let decoded: Value = Encoding::MessagePack.decode(&msgpack).unwrap();
+        assert_eq!(decoded, serde_json::to_value(&response).unwrap());
+        assert_eq!(decoded["entities"][0]["entity"], u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_decode_query_spec() {
+        let spec = json!({"with": ["Position"], "limit": 10, "fields": ["Position.y"]});
+        let body = Encoding::MessagePack.encode(&spec).unwrap();
+        let spec: QuerySpec = Encoding::MessagePack.decode(&body).unwrap();
+        assert_eq!(spec.with, ["Position"]);
+        assert_eq!(spec.fields, ["Position.y"]);
+        assert_eq!(spec.limit, Some(10));
+
+        assert!(Encoding::MessagePack.decode::<QuerySpec>(b"{").is_err());
+        assert_eq!(
+            "msgpack".parse::<Encoding>().unwrap(),
+            Encoding::MessagePack
+        );
+        assert!("xml".parse::<Encoding>().is_err());
+    }
+}
@@ -2,6 +2,9 @@
 
 use thiserror::Error;
 
+use crate::access::Access;
+use crate::auth::Scopes;
+
 /// Errors that can occur during introspection operations.
 #[derive(Debug, Error)]
 pub enum IntrospectError {
@@ -9,6 +12,10 @@ pub enum IntrospectError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// Binary encoding or decoding failed, or an unknown encoding was named.
+    #[error("Encoding error: {0}")]
+    Encoding(String),
+
     /// Entity was not found in the world.
     #[error("Entity not found: {0}")]
     EntityNotFound(u64),
@@ -37,7 +44,35 @@ pub enum IntrospectError {
     #[error("Channel disconnected")]
     ChannelDisconnected,
 
+    /// No prefab template with this name.
+    #[error("Prefab not found: {0}")]
+    PrefabNotFound(String),
+
     /// Invalid entity ID format.
     #[error("Invalid entity ID: {0}")]
     InvalidEntityId(String),
+
+    /// The request's token lacks the access needed to a component.
+    #[error("Access denied: {component} needs {needed:?} access")]
+    AccessDenied { component: String, needed: Access },
+
+    /// The request's token is neither a static nor a live session token.
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    /// The request's token lacks the scope the request needs.
+    #[error("Forbidden: needs the {0} scope")]
+    Forbidden(Scopes),
+
+    /// The client failed to authenticate too often.
+    #[error("Too many failed attempts, retry after {retry_after:?}")]
+    RateLimited { retry_after: std::time::Duration },
+
+    /// A query DSL expression that can't be parsed or isn't supported.
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// A scope name that doesn't exist.
+    #[error("Invalid scope: {0}")]
+    InvalidScope(String),
 }
@@ -0,0 +1,244 @@
+//! Field-level visibility rules for introspected components.
+//!
+//! `#[derive(Introspectable)]` accepts per-field attributes:
+//!
+//! ```ignore
+//! #[derive(Clone, Serialize, Deserialize, Introspectable)]
+//! pub struct Connection {
+//!     #[introspectable(rename = "x_pos")]
+//!     pub x: f64,
+//!     #[introspectable(redact)]
+//!     pub address: String,
+//!     #[introspectable(skip)]
+//!     pub session_token: u128,
+//! }
+//! ```
+//!
+//! - `skip` - the field is omitted from dashboard JSON entirely.
+//! - `redact` - the field is shown as [`REDACTED`] but its value never leaves the server.
+//! - `rename = "..."` - the field uses a different key in dashboard JSON.
+//!
+//! Skipped and redacted fields are ignored on input. Dashboard edits to the
+//! visible fields keep their current server-side values (see
+//! [`Introspectable::merge_json`](crate::Introspectable::merge_json)); values
+//! built from JSON alone get their type's `Default` (see [`fill_default`]).
+//!
+//! Rules are keyed by the serde field name, so they assume no conflicting
+//! `#[serde(rename)]` on the same field.
+
+use serde_json::{Map, Value};
+
+/// Placeholder shown in place of redacted field values.
+pub const REDACTED: &str = "<redacted>";
+
+/// How a field is exposed to the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldVisibility {
+    /// Shown and editable.
+    Visible,
+    /// Omitted from output; ignored on input.
+    Skip,
+    /// Shown as [`REDACTED`]; ignored on input.
+    Redact,
+}
+
+/// Visibility and naming rule for a single field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldRule {
+    /// Field name as serialized by serde.
+    pub name: &'static str,
+    /// Key used in dashboard JSON.
+    pub external: &'static str,
+    /// How the field is exposed.
+    pub visibility: FieldVisibility,
+}
+
+impl FieldRule {
+    /// Whether dashboard input for this field is accepted.
+    #[must_use]
+    pub const fn is_editable(&self) -> bool {
+        matches!(self.visibility, FieldVisibility::Visible)
+    }
+}
+
+/// Apply rules to a serialized component before it is sent to the dashboard.
+pub fn encode(value: &mut Value, rules: &[FieldRule]) {
+    let Some(map) = value.as_object_mut() else {
+        return;
+    };
+
+    for rule in rules {
+        let Some(field) = map.remove(rule.name) else {
+            continue;
+        };
+        match rule.visibility {
+            FieldVisibility::Visible => {
+                map.insert(rule.external.to_string(), field);
+            }
+            FieldVisibility::Redact => {
+                map.insert(rule.external.to_string(), Value::from(REDACTED));
+            }
+            FieldVisibility::Skip => {}
+        }
+    }
+}
+
+/// Map dashboard input back to serde field names, dropping non-editable fields.
+pub fn decode(value: &mut Value, rules: &[FieldRule]) {
+    let Some(map) = value.as_object_mut() else {
+        return;
+    };
+
+    let mut renamed = Map::new();
+    for rule in rules {
+        if !rule.is_editable() {
+            // Under either key, so a renamed hidden field can't be set by its
+            // serde name
+            map.remove(rule.external);
+            map.remove(rule.name);
+            continue;
+        }
+        if let Some(field) = map.remove(rule.external) {
+            renamed.insert(rule.name.to_string(), field);
+        }
+    }
+    map.extend(renamed);
+}
+
+/// Set a hidden field of decoded dashboard input to its type's default.
+///
+/// Skipped and redacted fields are never taken from input, so
+/// [`Introspectable::from_json`](crate::Introspectable::from_json) fills
+/// them this way before deserializing.
+pub fn fill_default<T: Default + serde::Serialize>(value: &mut Value, name: &str) {
+    let Some(map) = value.as_object_mut() else {
+        return;
+    };
+    if let Ok(default) = serde_json::to_value(T::default()) {
+        map.insert(name.to_string(), default);
+    }
+}
+
+/// Overlay decoded dashboard input onto the full serialized current value.
+///
+/// Fields absent from `patch` (including skipped and redacted ones) keep their
+/// values from `base`.
+pub fn merge(base: &mut Value, mut patch: Value, rules: &[FieldRule]) {
+    decode(&mut patch, rules);
+    match (base.as_object_mut(), patch) {
+        (Some(base_map), Value::Object(patch_map)) => base_map.extend(patch_map),
+        (_, patch) => *base = patch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::Introspectable;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+    struct Connection {
+        #[introspectable(rename = "x_pos")]
+        x: f64,
+        #[introspectable(redact)]
+        address: String,
+        #[introspectable(skip)]
+        token: u64,
+    }
+
+    fn connection() -> Connection {
+        Connection {
+            x: 1.5,
+            address: "10.0.0.1".to_string(),
+            token: 42,
+        }
+    }
+
+    #[test]
+    fn test_to_json_applies_rules() {
+        let json = connection().to_json();
+        assert_eq!(
+            json,
+            serde_json::json!({ "x_pos": 1.5, "address": REDACTED })
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_hidden_fields() {
+        let current = connection();
+        let edited = current
+            .merge_json(serde_json::json!({ "x_pos": 9.0, "address": REDACTED }))
+            .unwrap();
+
+        assert_eq!(edited, Connection { x: 9.0, ..current });
+    }
+
+    #[test]
+    fn test_from_json_fills_hidden_fields_from_default() {
+        let built =
+            Connection::from_json(serde_json::json!({ "x_pos": 9.0, "address": "1.2.3.4" }))
+                .unwrap();
+        assert_eq!(
+            built,
+            Connection {
+                x: 9.0,
+                address: String::new(),
+                token: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_ignores_hidden_input() {
+        let rules = [FieldRule {
+            name: "token",
+            external: "token",
+            visibility: FieldVisibility::Skip,
+        }];
+        let mut value = serde_json::json!({ "token": 7, "other": 1 });
+        decode(&mut value, &rules);
+        assert_eq!(value, serde_json::json!({ "other": 1 }));
+    }
+
+    #[test]
+    fn test_decode_ignores_renamed_hidden_input_by_serde_name() {
+        let rules = [
+            FieldRule {
+                name: "token",
+                external: "session",
+                visibility: FieldVisibility::Skip,
+            },
+            FieldRule {
+                name: "address",
+                external: "addr",
+                visibility: FieldVisibility::Redact,
+            },
+        ];
+        let mut value = serde_json::json!({
+            "token": 7,
+            "session": 8,
+            "address": "1.2.3.4",
+            "addr": "5.6.7.8",
+            "other": 1,
+        });
+        decode(&mut value, &rules);
+        assert_eq!(value, serde_json::json!({ "other": 1 }));
+    }
+
+    #[test]
+    fn test_merge_ignores_renamed_hidden_input_by_serde_name() {
+        #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+        struct Session {
+            #[introspectable(skip, rename = "session")]
+            token: u64,
+        }
+
+        let current = Session { token: 42 };
+        let edited = current
+            .merge_json(serde_json::json!({ "token": 7 }))
+            .unwrap();
+        assert_eq!(edited, current);
+    }
+}
@@ -1,15 +1,37 @@
 //! Component change history tracking with persistent storage.
 //!
 //! Uses nebari's versioned B+tree for persistent history with time-travel.
+//!
+//! A store [opened](HistoryStore::open) normally writes every entry straight
+//! to disk. A [tiered](HistoryStore::tiered) store keeps the entries of the
+//! most recent ticks in memory and spills older ones to disk as the tick
+//! advances; queries read through both tiers, so callers can't tell the
+//! difference. [`HistoryStore::memory_usage`] reports what the memory tier
+//! holds.
+//!
+//! Live views subscribe with a [`HistoryFilter`] and receive each matching
+//! entry as it's recorded, instead of re-querying.
 
+use std::collections::VecDeque;
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crossbeam_channel::{Receiver, Sender, TrySendError, bounded};
 use nebari::tree::{Root, Versioned};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 
+use crate::access::AccessPolicy;
+use crate::diff::ComponentDiff;
+
+/// Entries a subscriber can fall behind by before new ones are dropped for it.
+pub const SUBSCRIPTION_CAPACITY: usize = 1024;
+
+/// New history entries matching a [`HistoryFilter`], as they're recorded.
+pub type HistoryStream = Receiver<HistoryEntry>;
+
 /// Source of a component change.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -31,6 +53,9 @@ pub struct HistoryEntry {
     pub id: u64,
     /// Unix timestamp in milliseconds.
     pub timestamp: u64,
+    /// World tick the change happened in (see [`HistoryStore::set_tick`]).
+    #[serde(default)]
+    pub tick: u64,
     /// Entity ID.
     pub entity: u64,
     /// Component name.
@@ -41,6 +66,76 @@ pub struct HistoryEntry {
     pub new_value: Option<serde_json::Value>,
     /// Source of the change.
     pub source: ChangeSource,
+    /// Field-level changes, when the update was diffed (see [`ComponentDiff`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<ComponentDiff>,
+}
+
+/// Which new entries a subscriber receives.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Only this entity's changes.
+    pub entity: Option<u64>,
+    /// Only changes to this component.
+    pub component: Option<String>,
+    /// Only components this policy lets the token read.
+    access: Option<(Arc<AccessPolicy>, Option<String>)>,
+}
+
+impl HistoryFilter {
+    /// Entries for an entity and/or component (`None` matches any).
+    #[must_use]
+    pub fn new(entity: Option<u64>, component: Option<String>) -> Self {
+        Self {
+            entity,
+            component,
+            access: None,
+        }
+    }
+
+    /// Leave out components a request with `token` can't read.
+    #[must_use]
+    pub fn with_access(mut self, policy: Arc<AccessPolicy>, token: Option<String>) -> Self {
+        self.access = Some((policy, token));
+        self
+    }
+
+    /// Whether an entry passes the filter.
+    #[must_use]
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.entity.is_none_or(|entity| entity == entry.entity)
+            && self
+                .component
+                .as_ref()
+                .is_none_or(|component| *component == entry.component)
+            && self
+                .access
+                .as_ref()
+                .is_none_or(|(policy, token)| policy.can_read(token.as_deref(), &entry.component))
+    }
+}
+
+/// What a store's memory tier holds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct HistoryMemory {
+    /// Entries not yet spilled to disk.
+    pub entries: usize,
+    /// Serialized size of those entries.
+    pub bytes: usize,
+    /// Ticks kept in memory, or `None` if entries are written straight to disk.
+    pub memory_ticks: Option<u64>,
+}
+
+/// Entries of the most recent ticks, oldest first.
+struct MemoryTier {
+    ticks: u64,
+    entries: VecDeque<(HistoryEntry, usize)>,
+    bytes: usize,
+}
+
+struct Subscriber {
+    filter: HistoryFilter,
+    tx: Sender<HistoryEntry>,
 }
 
 /// Persistent history storage using nebari.
@@ -52,6 +147,23 @@ pub struct HistoryStore {
 struct HistoryStoreInner {
     roots: nebari::Roots<nebari::io::fs::StdFile>,
     next_id: RwLock<u64>,
+    tick: AtomicU64,
+    memory: Option<Mutex<MemoryTier>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl Drop for HistoryStoreInner {
+    fn drop(&mut self) {
+        let Some(memory) = &self.memory else {
+            return;
+        };
+        let Ok(tree) = self.roots.tree(Versioned::tree("history")) else {
+            return;
+        };
+        let mut memory = memory.lock();
+        let spilled = memory.entries.drain(..).map(|(entry, _)| entry);
+        write_entries(&tree, spilled, *self.next_id.read());
+    }
 }
 
 impl std::fmt::Debug for HistoryStore {
@@ -71,6 +183,19 @@ impl Default for HistoryStore {
 impl HistoryStore {
     /// Open or create a history store at the given path.
     pub fn open(path: &Path) -> Result<Self, nebari::Error> {
+        Self::open_with(path, None)
+    }
+
+    /// Open or create a history store at the given path that keeps the last
+    /// `memory_ticks` ticks of entries in memory.
+    ///
+    /// Older entries are spilled to disk by [`set_tick`](Self::set_tick),
+    /// and the rest when the last handle to the store is dropped.
+    pub fn tiered(path: &Path, memory_ticks: u64) -> Result<Self, nebari::Error> {
+        Self::open_with(path, Some(memory_ticks))
+    }
+
+    fn open_with(path: &Path, memory_ticks: Option<u64>) -> Result<Self, nebari::Error> {
         std::fs::create_dir_all(path).ok();
         let config = nebari::Config::default_for(path);
         let roots = config.open()?;
@@ -89,10 +214,104 @@ impl HistoryStore {
             inner: Arc::new(HistoryStoreInner {
                 roots,
                 next_id: RwLock::new(next_id),
+                tick: AtomicU64::new(0),
+                memory: memory_ticks.map(|ticks| {
+                    Mutex::new(MemoryTier {
+                        ticks,
+                        entries: VecDeque::new(),
+                        bytes: 0,
+                    })
+                }),
+                subscribers: Mutex::new(Vec::new()),
             }),
         })
     }
 
+    /// Set the world tick recorded with new entries.
+    ///
+    /// In a tiered store, entries that have fallen out of the memory window
+    /// are spilled to disk.
+    pub fn set_tick(&self, tick: u64) {
+        self.inner.tick.store(tick, Ordering::Relaxed);
+        let Some(memory) = &self.inner.memory else {
+            return;
+        };
+        let mut memory = memory.lock();
+        let oldest = tick.saturating_sub(memory.ticks);
+        let expired = memory
+            .entries
+            .iter()
+            .take_while(|(entry, _)| entry.tick < oldest)
+            .count();
+        if expired == 0 {
+            return;
+        }
+        let Ok(tree) = self.tree() else {
+            return;
+        };
+        let spilled: Vec<_> = memory.entries.drain(..expired).collect();
+        memory.bytes -= spilled.iter().map(|(_, bytes)| bytes).sum::<usize>();
+        let spilled = spilled.into_iter().map(|(entry, _)| entry);
+        write_entries(&tree, spilled, *self.inner.next_id.read());
+    }
+
+    /// What the memory tier holds.
+    #[must_use]
+    pub fn memory_usage(&self) -> HistoryMemory {
+        self.inner
+            .memory
+            .as_ref()
+            .map_or_else(HistoryMemory::default, |memory| {
+                let memory = memory.lock();
+                HistoryMemory {
+                    entries: memory.entries.len(),
+                    bytes: memory.bytes,
+                    memory_ticks: Some(memory.ticks),
+                }
+            })
+    }
+
+    /// Entries still in the memory tier that pass `filter`.
+    fn recent(&self, filter: impl Fn(&HistoryEntry) -> bool) -> Vec<HistoryEntry> {
+        self.inner.memory.as_ref().map_or_else(Vec::new, |memory| {
+            memory
+                .lock()
+                .entries
+                .iter()
+                .map(|(entry, _)| entry)
+                .filter(|entry| filter(entry))
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Receive every entry recorded from now on that matches `filter`.
+    ///
+    /// A subscriber more than [`SUBSCRIPTION_CAPACITY`] entries behind misses
+    /// new ones until it catches up. Dropping the stream unsubscribes.
+    pub fn subscribe(&self, filter: HistoryFilter) -> HistoryStream {
+        let (tx, rx) = bounded(SUBSCRIPTION_CAPACITY);
+        self.inner
+            .subscribers
+            .lock()
+            .push(Subscriber { filter, tx });
+        rx
+    }
+
+    /// Send a new entry to matching subscribers, dropping those found to be
+    /// gone.
+    fn publish(&self, entry: &HistoryEntry) {
+        self.inner.subscribers.lock().retain(|subscriber| {
+            if !subscriber.filter.matches(entry) {
+                return true;
+            }
+            !matches!(
+                subscriber.tx.try_send(entry.clone()),
+                Err(TrySendError::Disconnected(_))
+            )
+        });
+    }
+
     /// Get the history tree.
     fn tree(&self) -> Result<nebari::Tree<Versioned, nebari::io::fs::StdFile>, nebari::Error> {
         self.inner.roots.tree(Versioned::tree("history"))
@@ -106,6 +325,38 @@ impl HistoryStore {
         old_value: Option<serde_json::Value>,
         new_value: Option<serde_json::Value>,
         source: ChangeSource,
+    ) -> u64 {
+        self.record_entry(entity, component, old_value, new_value, None, source)
+    }
+
+    /// Record a component update along with its field-level diff.
+    pub fn record_update(
+        &self,
+        entity: u64,
+        component: String,
+        old_value: serde_json::Value,
+        new_value: serde_json::Value,
+        source: ChangeSource,
+    ) -> u64 {
+        let diff = ComponentDiff::between(&old_value, &new_value);
+        self.record_entry(
+            entity,
+            component,
+            Some(old_value),
+            Some(new_value),
+            Some(diff),
+            source,
+        )
+    }
+
+    fn record_entry(
+        &self,
+        entity: u64,
+        component: String,
+        old_value: Option<serde_json::Value>,
+        new_value: Option<serde_json::Value>,
+        diff: Option<ComponentDiff>,
+        source: ChangeSource,
     ) -> u64 {
         let Ok(tree) = self.tree() else {
             return 0;
@@ -126,35 +377,27 @@ impl HistoryStore {
         let entry = HistoryEntry {
             id,
             timestamp,
+            tick: self.inner.tick.load(Ordering::Relaxed),
             entity,
-            component: component.clone(),
+            component,
             old_value,
             new_value,
             source,
+            diff,
         };
 
-        // Serialize entry
-        let Ok(entry_bytes) = serde_json::to_vec(&entry) else {
-            return 0;
-        };
-
-        // Key format: entity:component:id (zero-padded for sorting)
-        let key = format!("{entity:016x}:{component}:{id:016x}");
-
-        // Store the entry
-        let _ = tree.set(key.into_bytes(), entry_bytes);
-
-        // Also store by ID for direct lookup: __id__:id -> key
-        let id_key = format!("__id__:{id:016x}");
-        let entry_key = format!("{entity:016x}:{component}:{id:016x}");
-        let _ = tree.set(id_key.into_bytes(), entry_key.into_bytes());
-
-        // Update next_id
-        let _ = tree.set(
-            b"__next_id__".to_vec(),
-            (*self.inner.next_id.read()).to_le_bytes().to_vec(),
-        );
+        if let Some(memory) = &self.inner.memory {
+            let Ok(bytes) = serde_json::to_vec(&entry).map(|bytes| bytes.len()) else {
+                return 0;
+            };
+            let mut memory = memory.lock();
+            memory.entries.push_back((entry.clone(), bytes));
+            memory.bytes += bytes;
+        } else {
+            write_entries(&tree, [entry.clone()], *self.inner.next_id.read());
+        }
 
+        self.publish(&entry);
         id
     }
 
@@ -187,6 +430,7 @@ impl HistoryStore {
             .into_iter()
             .filter_map(|(_key, value)| serde_json::from_slice(&value).ok())
             .collect();
+        entries.extend(self.recent(|e| e.entity == entity && e.component == component));
 
         // Sort by ID descending (most recent first)
         entries.sort_by(|a, b| b.id.cmp(&a.id));
@@ -217,6 +461,7 @@ impl HistoryStore {
             .into_iter()
             .filter_map(|(_key, value)| serde_json::from_slice(&value).ok())
             .collect();
+        entries.extend(self.recent(|e| e.entity == entity));
 
         entries.sort_by(|a, b| b.id.cmp(&a.id));
         entries.truncate(limit);
@@ -243,6 +488,7 @@ impl HistoryStore {
             .into_iter()
             .filter_map(|(_key, value)| serde_json::from_slice(&value).ok())
             .collect();
+        entries.extend(self.recent(|_| true));
 
         entries.sort_by(|a, b| b.id.cmp(&a.id));
         entries.truncate(limit);
@@ -251,6 +497,10 @@ impl HistoryStore {
 
     /// Get a specific entry by ID.
     pub fn get_entry(&self, id: u64) -> Option<HistoryEntry> {
+        if let Some(entry) = self.recent(|e| e.id == id).pop() {
+            return Some(entry);
+        }
+
         let tree = self.tree().ok()?;
 
         // Look up the key via the ID index
@@ -290,6 +540,38 @@ impl HistoryStore {
     }
 }
 
+/// Write entries to the history tree, then the next unused ID.
+fn write_entries(
+    tree: &nebari::Tree<Versioned, nebari::io::fs::StdFile>,
+    entries: impl IntoIterator<Item = HistoryEntry>,
+    next_id: u64,
+) {
+    for entry in entries {
+        let Ok(entry_bytes) = serde_json::to_vec(&entry) else {
+            continue;
+        };
+
+        // Key format: entity:component:id (zero-padded for sorting)
+        let HistoryEntry {
+            id,
+            entity,
+            component,
+            ..
+        } = entry;
+        let key = format!("{entity:016x}:{component}:{id:016x}");
+
+        // Store the entry
+        let _ = tree.set(key.clone().into_bytes(), entry_bytes);
+
+        // Also store by ID for direct lookup: __id__:id -> key
+        let id_key = format!("__id__:{id:016x}");
+        let _ = tree.set(id_key.into_bytes(), key.into_bytes());
+    }
+
+    // Update next_id
+    let _ = tree.set(b"__next_id__".to_vec(), next_id.to_le_bytes().to_vec());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,6 +628,24 @@ mod tests {
         assert_eq!(history.len(), 2);
     }
 
+    #[test]
+    fn test_record_update_stores_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(dir.path()).unwrap();
+
+        let id = store.record_update(
+            7,
+            "Position".to_string(),
+            serde_json::json!({"x": 0, "y": 0}),
+            serde_json::json!({"x": 0, "y": 5}),
+            ChangeSource::Dashboard,
+        );
+
+        let diff = store.get_entry(id).unwrap().diff.unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.changes[0].path, "y");
+    }
+
     #[test]
     fn test_get_entry_by_id() {
         let dir = tempfile::tempdir().unwrap();
@@ -364,4 +664,107 @@ mod tests {
         assert_eq!(entry.component, "Health");
         assert_eq!(entry.source, ChangeSource::Dashboard);
     }
+
+    #[test]
+    fn test_subscribe_receives_matching_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(dir.path()).unwrap();
+        let stream = store.subscribe(HistoryFilter::new(Some(1), None));
+
+        store.set_tick(12);
+        store.record(
+            2,
+            "Position".to_string(),
+            None,
+            Some(serde_json::json!({"x": 0})),
+            ChangeSource::Spawn,
+        );
+        store.record_update(
+            1,
+            "Position".to_string(),
+            serde_json::json!({"x": 0}),
+            serde_json::json!({"x": 3}),
+            ChangeSource::System,
+        );
+
+        let entry = stream.try_recv().unwrap();
+        assert_eq!(entry.entity, 1);
+        assert_eq!(entry.tick, 12);
+        assert_eq!(entry.source, ChangeSource::System);
+        assert!(entry.diff.is_some());
+        assert!(stream.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_subscribe_hides_unreadable_components() {
+        use crate::access::{Access, TokenPolicy};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(dir.path()).unwrap();
+        let policy = AccessPolicy::new(TokenPolicy::new(Access::Read).with("Secret", Access::None));
+        let stream = store.subscribe(HistoryFilter::default().with_access(Arc::new(policy), None));
+
+        for component in ["Secret", "Health"] {
+            store.record(
+                1,
+                component.to_string(),
+                None,
+                Some(serde_json::json!({})),
+                ChangeSource::Spawn,
+            );
+        }
+
+        assert_eq!(stream.try_recv().unwrap().component, "Health");
+        assert!(stream.try_recv().is_err());
+    }
+
+    fn spawn_position(store: &HistoryStore, entity: u64) -> u64 {
+        store.record(
+            entity,
+            "Position".to_string(),
+            None,
+            Some(serde_json::json!({"x": 0})),
+            ChangeSource::Spawn,
+        )
+    }
+
+    #[test]
+    fn test_tiered_spills_old_ticks() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::tiered(dir.path(), 2).unwrap();
+
+        for tick in 0..5 {
+            store.set_tick(tick);
+            spawn_position(&store, tick);
+        }
+
+        // Ticks 2, 3 and 4 are within two ticks of tick 4
+        let memory = store.memory_usage();
+        assert_eq!(memory.entries, 3);
+        assert!(memory.bytes > 0);
+        assert_eq!(memory.memory_ticks, Some(2));
+
+        // Reads go through both tiers
+        let history = store.get_global_history(None);
+        let ticks: Vec<_> = history.iter().map(|e| e.tick).collect();
+        assert_eq!(ticks, [4, 3, 2, 1, 0]);
+        assert_eq!(store.get_entity_history(0, None).len(), 1);
+        assert_eq!(store.get_component_history(4, "Position", None).len(), 1);
+        assert_eq!(store.get_entry(history[0].id).unwrap().tick, 4);
+        assert_eq!(store.get_entry(history[4].id).unwrap().tick, 0);
+    }
+
+    #[test]
+    fn test_tiered_flushes_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = {
+            let store = HistoryStore::tiered(dir.path(), 100).unwrap();
+            spawn_position(&store, 1)
+        };
+
+        let store = HistoryStore::open(dir.path()).unwrap();
+        assert_eq!(store.memory_usage(), HistoryMemory::default());
+        assert_eq!(store.get_entry(id).unwrap().entity, 1);
+        assert!(spawn_position(&store, 2) > id);
+    }
 }
@@ -14,25 +14,62 @@
 //! #[derive(Component, Clone, Serialize, Deserialize, Introspectable)]
 //! pub struct Position { x: f64, y: f64, z: f64 }
 //! ```
+//!
+//! Individual fields can be renamed, hidden, or redacted with
+//! `#[introspectable(...)]`; see [`fields`]. Whole components can be hidden
+//! or made read-only per auth token; see [`access`]. Tokens themselves,
+//! sessions and request scopes are checked before a request is queued; see
+//! [`auth`]. Large queries are paged and cut down to the requested fields;
+//! see [`query`]. Every entity a query matches can be patched or despawned
+//! at once; see [`bulk`]. Responses can be sent as MessagePack to clients that
+//! aren't browsers; see [`encoding`].
 
 #![allow(unsafe_code)]
 #![allow(missing_docs)]
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
 
+// Lets `#[derive(Introspectable)]` expansions work in this crate's tests.
+#[cfg(test)]
+extern crate self as rgb_ecs_introspect;
+
+pub mod access;
+pub mod auth;
+pub mod bulk;
+pub mod diff;
+pub mod encoding;
 mod error;
+pub mod fields;
 pub mod history;
+pub mod prefab;
+pub mod profile;
 pub mod protocol;
+pub mod query;
 mod registry;
+pub mod stats;
 mod traits;
 
+pub use access::{Access, AccessPolicy, TokenPolicy};
+pub use auth::{AuthConfig, Authenticator, Principal, Scopes, Session};
+pub use bulk::BulkResult;
+pub use diff::{ComponentDiff, FieldChange};
+pub use encoding::Encoding;
 pub use error::IntrospectError;
-pub use history::{ChangeSource, HistoryEntry, HistoryStore};
+pub use fields::{FieldRule, FieldVisibility};
+pub use history::{
+    ChangeSource, HistoryEntry, HistoryFilter, HistoryMemory, HistoryStore, HistoryStream,
+};
+pub use prefab::{PrefabRegistry, PrefabTemplate};
+pub use profile::{FlameNode, ProfilePhase, TickProfiler};
 pub use protocol::{
-    ChunksResponse, ComponentResponse, ComponentTypesResponse, EntityResponse, HistoryResponse,
-    IntrospectChannels, IntrospectIngress, IntrospectRequest, ListEntitiesResponse, QueryResponse,
-    QuerySpec, SpawnResponse, UpdateResponse, WorldResponse,
+    ArchetypeSizeBucket, BulkResponse, ChunksResponse, ComponentMemory, ComponentResponse,
+    ComponentTypesResponse, EntityResponse, HistoryResponse, IntrospectChannels, IntrospectIngress,
+    IntrospectRequest, IntrospectSender, ListEntitiesResponse, ModuleInfo, PrefabsResponse,
+    QueryResponse, QuerySpec, SpawnResponse, SystemsResponse, TickProfileResponse, UpdateResponse,
+    WorldResponse, WorldStatsResponse,
 };
-pub use registry::{AlignedBuffer, IntrospectInfo, IntrospectRegistry};
+pub use query::{Page, Projection};
+pub use registry::{AlignedBuffer, ComponentUpdate, IntrospectInfo, IntrospectRegistry};
 pub use rgb_ecs_introspect_derive::Introspectable;
+pub use stats::{ChurnTracker, world_stats};
 pub use traits::Introspectable;
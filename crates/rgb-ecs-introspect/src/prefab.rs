@@ -0,0 +1,218 @@
+//! Named spawn templates for the dashboard.
+//!
+//! A [`PrefabTemplate`] is a bundle of component names with default JSON
+//! values. The dashboard spawns one via
+//! [`IntrospectRequest::SpawnPrefab`](crate::IntrospectRequest::SpawnPrefab),
+//! optionally overriding individual fields:
+//!
+//! ```ignore
+//! let mut prefabs = PrefabRegistry::new();
+//! prefabs.register(
+//!     PrefabTemplate::new("zombie")
+//!         .with("Position", json!({ "x": 0.0, "y": 64.0, "z": 0.0 }))
+//!         .with("Health", json!({ "current": 20, "max": 20 })),
+//! );
+//!
+//! // "zombie at 10, 64, -5"
+//! let overrides = json!({ "Position": { "x": 10.0, "z": -5.0 } });
+//! registry.spawn_prefab(&mut world, &prefabs, "zombie", overrides.as_object().unwrap())?;
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// A named bundle of components with default JSON values.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefabTemplate {
+    /// Template name used by the dashboard.
+    pub name: String,
+    /// Component short name -> default JSON value.
+    pub components: Map<String, Value>,
+}
+
+impl PrefabTemplate {
+    /// Create an empty template.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            components: Map::new(),
+        }
+    }
+
+    /// Add a component with its default value.
+    #[must_use]
+    pub fn with(mut self, component: impl Into<String>, value: Value) -> Self {
+        self.components.insert(component.into(), value);
+        self
+    }
+
+    /// Apply per-component overrides to the defaults.
+    ///
+    /// Object values are merged field by field, so `{"Position": {"x": 1}}`
+    /// only replaces `x`. Components not in the template are added.
+    #[must_use]
+    pub fn resolve(&self, overrides: &Map<String, Value>) -> Map<String, Value> {
+        let mut components = self.components.clone();
+        for (name, value) in overrides {
+            match components.get_mut(name) {
+                Some(base) => merge_value(base, value),
+                None => {
+                    components.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        components
+    }
+}
+
+/// Deep-merge `patch` into `base`; non-object values replace wholesale.
+fn merge_value(base: &mut Value, patch: &Value) {
+    match (base, patch) {
+        (Value::Object(base_map), Value::Object(patch_map)) => {
+            for (key, value) in patch_map {
+                match base_map.get_mut(key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base_map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, patch) => *base = patch.clone(),
+    }
+}
+
+/// Registry of spawn templates, keyed by name.
+#[derive(Debug, Default)]
+pub struct PrefabRegistry {
+    templates: HashMap<String, PrefabTemplate>,
+}
+
+impl PrefabRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a template, replacing any existing one with the same name.
+    pub fn register(&mut self, template: PrefabTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    /// Get a template by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&PrefabTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Iterate over all templates.
+    pub fn iter(&self) -> impl Iterator<Item = &PrefabTemplate> {
+        self.templates.values()
+    }
+
+    /// Get the number of registered templates.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    /// Check if the registry is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rgb_ecs::World;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{IntrospectError, IntrospectRegistry, Introspectable};
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+    struct Position {
+        x: f64,
+        y: f64,
+        z: f64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    fn zombie() -> PrefabTemplate {
+        PrefabTemplate::new("zombie")
+            .with("Position", json!({ "x": 0.0, "y": 64.0, "z": 0.0 }))
+            .with("Health", json!({ "current": 20, "max": 20 }))
+    }
+
+    #[test]
+    fn test_resolve_merges_fields() {
+        let overrides = json!({ "Position": { "x": 10.0 } });
+        let resolved = zombie().resolve(overrides.as_object().unwrap());
+        assert_eq!(
+            resolved["Position"],
+            json!({ "x": 10.0, "y": 64.0, "z": 0.0 })
+        );
+        assert_eq!(resolved["Health"], json!({ "current": 20, "max": 20 }));
+    }
+
+    #[test]
+    fn test_spawn_prefab() {
+        let mut world = World::new();
+        world.register_component::<Position>();
+        world.register_component::<Health>();
+
+        let mut registry = IntrospectRegistry::new();
+        registry.register::<Position>(&world);
+        registry.register::<Health>(&world);
+
+        let mut prefabs = PrefabRegistry::new();
+        prefabs.register(zombie());
+
+        let overrides = json!({ "Position": { "x": 10.0, "z": -5.0 } });
+        let entity = registry
+            .spawn_prefab(
+                &mut world,
+                &prefabs,
+                "zombie",
+                overrides.as_object().unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            world.get::<Position>(entity),
+            Some(Position {
+                x: 10.0,
+                y: 64.0,
+                z: -5.0
+            })
+        );
+        assert_eq!(world.get::<Health>(entity).unwrap().max, 20);
+    }
+
+    #[test]
+    fn test_spawn_unknown_component_rolls_back() {
+        let mut world = World::new();
+        world.register_component::<Position>();
+        let mut registry = IntrospectRegistry::new();
+        registry.register::<Position>(&world);
+
+        let mut prefabs = PrefabRegistry::new();
+        prefabs.register(zombie());
+
+        let before = world.entity_count();
+        let result = registry.spawn_prefab(&mut world, &prefabs, "zombie", &Map::new());
+        assert!(matches!(result, Err(IntrospectError::NotIntrospectable(_))));
+        assert_eq!(world.entity_count(), before);
+    }
+}
@@ -0,0 +1,324 @@
+//! Per-system timings of recent ticks, as flame graphs.
+//!
+//! The tick loop wraps each tick in [`TickProfiler::begin_tick`] and
+//! [`TickProfiler::end_tick`] and records every system run in between with
+//! the phase it ran in: sequential, or one of the RGB colors. Systems of a
+//! parallel phase are timed on their worker threads and recorded after the
+//! phase's barrier:
+//!
+//! ```ignore
+//! profiler.begin_tick(tick);
+//! profiler.time("collect_rpcs", ProfilePhase::Sequential, || collect(&mut world));
+//! for color in Color::ALL {
+//!     for (system, start, end) in run_phase(&mut world, color) {
+//!         profiler.record(system, color.into(), start, end);
+//!     }
+//! }
+//! profiler.end_tick();
+//! // ...
+//! IntrospectRequest::TickProfile { tick, response, .. } => {
+//!     let _ = response.send(profiler.profile(tick));
+//! }
+//! ```
+//!
+//! [`TickProfiler::profile`] answers with the tick as a tree in the JSON
+//! format of d3-flame-graph (`name`, `value`, `children`): the tick, then
+//! each run of consecutive spans in one phase, then its systems. Values are
+//! busy time in microseconds, so a parallel phase's value is the sum of its
+//! systems and can exceed the wall-clock time it took; every node also has
+//! `start_us`, its offset from the start of the tick, for timeline views.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use rgb_spatial::Color;
+use serde::Serialize;
+
+use crate::protocol::TickProfileResponse;
+
+/// Ticks a profiler keeps by default.
+pub const PROFILE_WINDOW: usize = 200;
+
+/// Where in a tick a system ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfilePhase {
+    /// On the main thread, outside the colored phases.
+    Sequential,
+    Red,
+    Green,
+    Blue,
+}
+
+impl ProfilePhase {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Sequential => "sequential",
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Blue => "blue",
+        }
+    }
+}
+
+impl From<Color> for ProfilePhase {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Red => Self::Red,
+            Color::Green => Self::Green,
+            Color::Blue => Self::Blue,
+        }
+    }
+}
+
+/// One system run, relative to the start of its tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    system: String,
+    phase: ProfilePhase,
+    start: Duration,
+    end: Duration,
+}
+
+/// The spans of one tick.
+#[derive(Debug, Clone)]
+struct TickSpans {
+    tick: u64,
+    started: Instant,
+    /// Wall-clock length, once the tick has ended.
+    duration: Option<Duration>,
+    spans: Vec<Span>,
+}
+
+/// A node of a flame graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlameNode {
+    pub name: String,
+    /// Busy time in microseconds.
+    pub value: u64,
+    /// Offset from the start of the tick in microseconds.
+    pub start_us: u64,
+    pub children: Vec<FlameNode>,
+}
+
+/// Per-system timings of the last [`PROFILE_WINDOW`] ticks.
+#[derive(Debug, Clone)]
+pub struct TickProfiler {
+    ticks: VecDeque<TickSpans>,
+    capacity: usize,
+}
+
+impl Default for TickProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TickProfiler {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_capacity(PROFILE_WINDOW)
+    }
+
+    /// A profiler keeping the last `capacity` ticks (at least one).
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ticks: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Start recording `tick` now.
+    pub fn begin_tick(&mut self, tick: u64) {
+        self.begin_tick_at(tick, Instant::now());
+    }
+
+    /// Start recording `tick`, which started at `started`, dropping the
+    /// oldest tick if the window is full.
+    pub fn begin_tick_at(&mut self, tick: u64, started: Instant) {
+        if self.ticks.len() >= self.capacity {
+            self.ticks.pop_front();
+        }
+        self.ticks.push_back(TickSpans {
+            tick,
+            started,
+            duration: None,
+            spans: Vec::new(),
+        });
+    }
+
+    /// End the current tick now.
+    pub fn end_tick(&mut self) {
+        self.end_tick_at(Instant::now());
+    }
+
+    /// End the current tick at `ended`.
+    pub fn end_tick_at(&mut self, ended: Instant) {
+        if let Some(current) = self.ticks.back_mut() {
+            current.duration = Some(ended.saturating_duration_since(current.started));
+        }
+    }
+
+    /// Record a system that ran from `start` to `end` in the current tick.
+    /// Does nothing outside a tick.
+    pub fn record(
+        &mut self,
+        system: impl Into<String>,
+        phase: ProfilePhase,
+        start: Instant,
+        end: Instant,
+    ) {
+        let Some(current) = self.ticks.back_mut() else {
+            return;
+        };
+        current.spans.push(Span {
+            system: system.into(),
+            phase,
+            start: start.saturating_duration_since(current.started),
+            end: end.saturating_duration_since(current.started),
+        });
+    }
+
+    /// Run a system on this thread, recording how long it took.
+    pub fn time<R>(&mut self, system: &str, phase: ProfilePhase, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record(system, phase, start, Instant::now());
+        result
+    }
+
+    /// Ticks still held, oldest first.
+    pub fn ticks(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ticks.iter().map(|t| t.tick)
+    }
+
+    /// The flame graph of `tick`, or a response with `found: false` once it
+    /// has left the window.
+    #[must_use]
+    pub fn profile(&self, tick: u64) -> TickProfileResponse {
+        let Some(recorded) = self.ticks.iter().find(|t| t.tick == tick) else {
+            return TickProfileResponse {
+                found: false,
+                tick,
+                duration_us: 0,
+                flame: None,
+            };
+        };
+
+        let mut spans = recorded.spans.clone();
+        spans.sort_by_key(|s| s.start);
+
+        // Consecutive spans of one phase share a node
+        let mut phases: Vec<FlameNode> = Vec::new();
+        let mut last_phase = None;
+        for span in spans {
+            let node = FlameNode {
+                name: span.system,
+                value: micros(span.end.saturating_sub(span.start)),
+                start_us: micros(span.start),
+                children: Vec::new(),
+            };
+            match phases.last_mut() {
+                Some(phase) if last_phase == Some(span.phase) => {
+                    phase.value += node.value;
+                    phase.children.push(node);
+                }
+                _ => phases.push(FlameNode {
+                    name: span.phase.name().to_string(),
+                    value: node.value,
+                    start_us: node.start_us,
+                    children: vec![node],
+                }),
+            }
+            last_phase = Some(span.phase);
+        }
+
+        let duration_us = recorded
+            .duration
+            .map_or_else(|| phases.last().map_or(0, |p| p.start_us + p.value), micros);
+        let busy: u64 = phases.iter().map(|p| p.value).sum();
+        TickProfileResponse {
+            found: true,
+            tick,
+            duration_us,
+            flame: Some(FlameNode {
+                name: format!("tick {tick}"),
+                value: duration_us.max(busy),
+                start_us: 0,
+                children: phases,
+            }),
+        }
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_profile_groups_phases() {
+        let mut profiler = TickProfiler::new();
+        let start = Instant::now();
+        profiler.begin_tick_at(7, start);
+        profiler.record("collect", ProfilePhase::Sequential, start, start + MS);
+        // Two red cells in parallel, recorded out of order
+        profiler.record("cells", ProfilePhase::Red, start + 2 * MS, start + 5 * MS);
+        profiler.record("cells", ProfilePhase::Red, start + MS, start + 4 * MS);
+        profiler.record("cells", ProfilePhase::Green, start + 5 * MS, start + 6 * MS);
+        profiler.record(
+            "commit",
+            ProfilePhase::Sequential,
+            start + 6 * MS,
+            start + 8 * MS,
+        );
+        profiler.end_tick_at(start + 10 * MS);
+
+        let profile = profiler.profile(7);
+        assert!(profile.found);
+        assert_eq!(profile.duration_us, 10_000);
+
+        let flame = profile.flame.unwrap();
+        assert_eq!(flame.name, "tick 7");
+        assert_eq!(flame.value, 10_000);
+        let phases: Vec<_> = flame
+            .children
+            .iter()
+            .map(|p| (p.name.as_str(), p.value, p.start_us))
+            .collect();
+        assert_eq!(
+            phases,
+            [
+                ("sequential", 1000, 0),
+                ("red", 6000, 1000),
+                ("green", 1000, 5000),
+                ("sequential", 2000, 6000),
+            ]
+        );
+        assert_eq!(flame.children[1].children.len(), 2);
+        assert_eq!(flame.children[1].children[0].start_us, 1000);
+
+        let json = serde_json::to_value(&flame).unwrap();
+        assert_eq!(json["children"][3]["children"][0]["name"], "commit");
+    }
+
+    #[test]
+    fn test_profile_window() {
+        let mut profiler = TickProfiler::with_capacity(2);
+        for tick in 0..3 {
+            profiler.begin_tick(tick);
+            profiler.time("system", ProfilePhase::Sequential, || {});
+            profiler.end_tick();
+        }
+        assert_eq!(profiler.ticks().collect::<Vec<_>>(), [1, 2]);
+        assert!(!profiler.profile(0).found);
+        assert!(profiler.profile(0).flame.is_none());
+        assert!(profiler.profile(2).found);
+    }
+}
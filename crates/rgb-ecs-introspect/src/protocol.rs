@@ -9,15 +9,27 @@ use crossbeam_channel::{Receiver, Sender, bounded};
 use rgb_ecs::{Component, Entity};
 use serde::{Deserialize, Serialize};
 
-use crate::IntrospectRegistry;
-use crate::history::HistoryEntry;
+use crate::access::AccessPolicy;
+use crate::auth::Authenticator;
+use crate::diff::ComponentDiff;
+use crate::encoding::Encoding;
+use crate::history::{HistoryEntry, HistoryMemory, HistoryStream};
+use crate::prefab::{PrefabRegistry, PrefabTemplate};
+use crate::profile::FlameNode;
+use crate::{IntrospectError, IntrospectRegistry};
 
 /// Channels for dashboard communication.
+///
+/// Requests can only be sent through an [`IntrospectSender`] from
+/// [`sender`](Self::sender), so every one is authorized first.
 pub struct IntrospectChannels {
     /// Send requests to the ECS world.
-    pub request_tx: Sender<IntrospectRequest>,
+    request_tx: Sender<IntrospectRequest>,
     /// Receive requests in the ECS world.
     pub request_rx: Receiver<IntrospectRequest>,
+    /// Binary encoding offered to clients that accept it (see
+    /// [`encoding`](crate::encoding)); JSON only by default.
+    pub encoding: Encoding,
 }
 
 impl IntrospectChannels {
@@ -28,9 +40,17 @@ impl IntrospectChannels {
         Self {
             request_tx,
             request_rx,
+            encoding: Encoding::Json,
         }
     }
 
+    /// Offer `encoding` to clients whose `Accept` header lists it.
+    #[must_use]
+    pub const fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Create channels with default capacity (64).
     #[must_use]
     pub fn default_capacity() -> Self {
@@ -38,6 +58,62 @@ impl IntrospectChannels {
     }
 }
 
+impl IntrospectChannels {
+    /// A sending side that authorizes requests with `auth`, then checks the
+    /// components they name against `policy`.
+    #[must_use]
+    pub fn sender(&self, auth: Arc<Authenticator>, policy: Arc<AccessPolicy>) -> IntrospectSender {
+        IntrospectSender {
+            tx: self.request_tx.clone(),
+            auth,
+            policy,
+            encoding: self.encoding,
+        }
+    }
+}
+
+/// Sending side of the channels for web handlers.
+///
+/// This authorizes every request and checks its components first, so the
+/// world only ever sees requests their token may make.
+#[derive(Clone)]
+pub struct IntrospectSender {
+    tx: Sender<IntrospectRequest>,
+    auth: Arc<Authenticator>,
+    policy: Arc<AccessPolicy>,
+    encoding: Encoding,
+}
+
+impl IntrospectSender {
+    /// Authorize a request from `client`, check the components it names
+    /// and queue it.
+    pub fn send(
+        &self,
+        client: &str,
+        mut request: IntrospectRequest,
+    ) -> Result<(), IntrospectError> {
+        self.auth.authorize(client, &mut request)?;
+        // Sessions resolved to their static token, which the policy knows
+        self.policy.authorize(&request)?;
+        AccessPolicy::filter_response(&self.policy, &mut request);
+        self.tx
+            .send(request)
+            .map_err(|_| IntrospectError::ChannelDisconnected)
+    }
+
+    /// The authenticator, e.g. to issue sessions.
+    #[must_use]
+    pub fn auth(&self) -> &Authenticator {
+        &self.auth
+    }
+
+    /// The encoding to answer a request with, given its `Accept` header.
+    #[must_use]
+    pub fn negotiate(&self, accept: Option<&str>) -> Encoding {
+        Encoding::negotiate(accept, self.encoding)
+    }
+}
+
 impl Default for IntrospectChannels {
     fn default() -> Self {
         Self::default_capacity()
@@ -52,17 +128,37 @@ pub struct IntrospectIngress {
     pub rx: Receiver<IntrospectRequest>,
     /// Shared registry of introspectable components.
     pub registry: Arc<IntrospectRegistry>,
+    /// Spawn templates available to the dashboard.
+    pub prefabs: Arc<PrefabRegistry>,
+    /// Which components each request's token may read and write.
+    pub policy: Arc<AccessPolicy>,
 }
 
 /// Request from web server to ECS world.
+///
+/// Every request carries the dashboard's auth token. [`IntrospectSender`]
+/// authorizes it (see [`auth`](crate::auth)) and checks the components it
+/// names against the [`AccessPolicy`] (see [`access`](crate::access))
+/// before it's queued. Components the token can't read are left out of
+/// the responses to requests that don't name them (see
+/// [`AccessPolicy::filter_response`]).
 pub enum IntrospectRequest {
     /// Get world-level statistics and global components.
     GetWorld {
+        token: Option<String>,
         response: oneshot::Sender<WorldResponse>,
     },
 
+    /// Get entity, archetype, memory and churn statistics for the overview
+    /// page (see [`stats`](crate::stats)).
+    WorldStats {
+        token: Option<String>,
+        response: oneshot::Sender<WorldStatsResponse>,
+    },
+
     /// List entities, optionally filtered by component.
     ListEntities {
+        token: Option<String>,
         filter: Option<Vec<String>>,
         limit: Option<usize>,
         offset: Option<usize>,
@@ -71,12 +167,14 @@ pub enum IntrospectRequest {
 
     /// Get a single entity with all its components.
     GetEntity {
+        token: Option<String>,
         entity: Entity,
         response: oneshot::Sender<EntityResponse>,
     },
 
     /// Get a specific component from an entity.
     GetComponent {
+        token: Option<String>,
         entity: Entity,
         component: String,
         response: oneshot::Sender<ComponentResponse>,
@@ -84,6 +182,7 @@ pub enum IntrospectRequest {
 
     /// Update a component on an entity.
     UpdateComponent {
+        token: Option<String>,
         entity: Entity,
         component: String,
         value: serde_json::Value,
@@ -92,6 +191,7 @@ pub enum IntrospectRequest {
 
     /// Add a component to an entity.
     AddComponent {
+        token: Option<String>,
         entity: Entity,
         component: String,
         value: serde_json::Value,
@@ -100,6 +200,7 @@ pub enum IntrospectRequest {
 
     /// Remove a component from an entity.
     RemoveComponent {
+        token: Option<String>,
         entity: Entity,
         component: String,
         response: oneshot::Sender<UpdateResponse>,
@@ -107,43 +208,113 @@ pub enum IntrospectRequest {
 
     /// Spawn a new entity.
     SpawnEntity {
+        token: Option<String>,
         name: Option<String>,
         components: Vec<(String, serde_json::Value)>,
         response: oneshot::Sender<SpawnResponse>,
     },
 
+    /// Spawn an entity from a named prefab template.
+    ///
+    /// `overrides` maps component names to (partial) JSON values merged over
+    /// the template defaults.
+    SpawnPrefab {
+        token: Option<String>,
+        name: String,
+        overrides: serde_json::Map<String, serde_json::Value>,
+        response: oneshot::Sender<SpawnResponse>,
+    },
+
+    /// List available prefab templates.
+    GetPrefabs {
+        token: Option<String>,
+        response: oneshot::Sender<PrefabsResponse>,
+    },
+
     /// Despawn an entity.
     DespawnEntity {
+        token: Option<String>,
         entity: Entity,
         response: oneshot::Sender<UpdateResponse>,
     },
 
+    /// Apply a JSON merge patch to a component on every entity a query DSL
+    /// expression matches (see [`bulk`](crate::bulk)).
+    ///
+    /// With `dry_run`, only count the entities that would change.
+    BulkUpdate {
+        token: Option<String>,
+        query: String,
+        component: String,
+        patch: serde_json::Value,
+        dry_run: bool,
+        response: oneshot::Sender<BulkResponse>,
+    },
+
+    /// Despawn every entity a query DSL expression matches.
+    ///
+    /// With `dry_run`, only count the entities that would be despawned.
+    BulkDespawn {
+        token: Option<String>,
+        query: String,
+        dry_run: bool,
+        response: oneshot::Sender<BulkResponse>,
+    },
+
     /// Execute a query.
     Query {
+        token: Option<String>,
         spec: QuerySpec,
         response: oneshot::Sender<QueryResponse>,
     },
 
     /// Get all registered component types.
     GetComponentTypes {
+        token: Option<String>,
         response: oneshot::Sender<ComponentTypesResponse>,
     },
 
+    /// Get the resources used by each loaded module.
+    GetSystems {
+        token: Option<String>,
+        response: oneshot::Sender<SystemsResponse>,
+    },
+
     /// Get chunk data for the map view.
     GetChunks {
+        token: Option<String>,
         response: oneshot::Sender<ChunksResponse>,
     },
 
+    /// Get the per-system timings of a recent tick as a flame graph (see
+    /// [`profile`](crate::profile)).
+    TickProfile {
+        token: Option<String>,
+        tick: u64,
+        response: oneshot::Sender<TickProfileResponse>,
+    },
+
     /// Get component history for an entity.
     GetHistory {
+        token: Option<String>,
         entity: Option<Entity>,
         component: Option<String>,
         limit: Option<usize>,
         response: oneshot::Sender<HistoryResponse>,
     },
 
+    /// Stream new history entries for an entity and/or component as they're
+    /// recorded, e.g. to push them over a WebSocket to a live timeline.
+    SubscribeHistory {
+        token: Option<String>,
+        entity: Option<Entity>,
+        component: Option<String>,
+        response: oneshot::Sender<HistoryStream>,
+    },
+
     /// Revert a component to a specific history entry.
     RevertToEntry {
+        token: Option<String>,
         entry_id: u64,
         response: oneshot::Sender<UpdateResponse>,
     },
@@ -164,10 +335,18 @@ pub struct QuerySpec {
     /// Exclude components (must NOT have).
     #[serde(default)]
     pub without: Vec<String>,
-    /// Maximum results to return.
+    /// Maximum results to return (see [`query`](crate::query)).
     pub limit: Option<usize>,
-    /// Offset for pagination.
+    /// Results to skip, after the cursor.
     pub offset: Option<usize>,
+    /// Only return entities after this one, from
+    /// [`QueryResponse::next_cursor`].
+    #[serde(default)]
+    pub cursor: Option<u64>,
+    /// Components or component fields to return, e.g. `Position.y`; all
+    /// of `with` and `optional` if empty.
+    #[serde(default)]
+    pub fields: Vec<String>,
 }
 
 // Response types
@@ -181,6 +360,37 @@ pub struct WorldResponse {
     pub globals: serde_json::Value,
 }
 
+/// World statistics for the overview page.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldStatsResponse {
+    pub entity_count: u32,
+    pub archetype_count: usize,
+    /// Archetypes by entity count, in ascending buckets.
+    pub archetype_sizes: Vec<ArchetypeSizeBucket>,
+    /// Column memory per component, largest first.
+    pub components: Vec<ComponentMemory>,
+    /// Column memory of all components.
+    pub component_bytes: usize,
+    pub spawns_per_tick: f64,
+    pub despawns_per_tick: f64,
+}
+
+/// Archetypes holding `min..=max` entities.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchetypeSizeBucket {
+    pub min: usize,
+    pub max: usize,
+    pub archetypes: usize,
+}
+
+/// Column memory used by one component.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentMemory {
+    pub name: String,
+    pub entities: usize,
+    pub bytes: usize,
+}
+
 /// List of entities.
 #[derive(Debug, Clone, Serialize)]
 pub struct ListEntitiesResponse {
@@ -231,6 +441,50 @@ pub struct ComponentResponse {
 pub struct UpdateResponse {
     pub success: bool,
     pub error: Option<String>,
+    /// Field-level changes, for operations that modify a component value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<ComponentDiff>,
+}
+
+impl UpdateResponse {
+    /// Successful operation with no diff (add/remove/despawn).
+    #[must_use]
+    pub fn ok() -> Self {
+        Self {
+            success: true,
+            error: None,
+            diff: None,
+        }
+    }
+
+    /// Failed operation.
+    #[must_use]
+    pub fn err(error: impl ToString) -> Self {
+        Self {
+            success: false,
+            error: Some(error.to_string()),
+            diff: None,
+        }
+    }
+
+    /// Successful update with its field-level diff.
+    #[must_use]
+    pub fn with_diff(diff: ComponentDiff) -> Self {
+        Self {
+            success: true,
+            error: None,
+            diff: Some(diff),
+        }
+    }
+}
+
+impl From<Result<ComponentDiff, IntrospectError>> for UpdateResponse {
+    fn from(result: Result<ComponentDiff, IntrospectError>) -> Self {
+        match result {
+            Ok(diff) => Self::with_diff(diff),
+            Err(err) => Self::err(err),
+        }
+    }
 }
 
 /// Result of spawning an entity.
@@ -241,11 +495,49 @@ pub struct SpawnResponse {
     pub error: Option<String>,
 }
 
+impl From<Result<Entity, IntrospectError>> for SpawnResponse {
+    fn from(result: Result<Entity, IntrospectError>) -> Self {
+        match result {
+            Ok(entity) => Self {
+                success: true,
+                entity: Some(entity.to_bits()),
+                error: None,
+            },
+            Err(err) => Self {
+                success: false,
+                entity: None,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Result of a bulk update or despawn.
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkResponse {
+    pub success: bool,
+    /// Entities the query matched.
+    pub matched: usize,
+    /// Entities changed, or that would be on a dry run.
+    pub affected: usize,
+    /// Entities the patch couldn't be applied to, with the reason.
+    pub failed: Vec<(u64, String)>,
+    pub error: Option<String>,
+}
+
+/// Available prefab templates.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefabsResponse {
+    pub prefabs: Vec<PrefabTemplate>,
+}
+
 /// Query results.
 #[derive(Debug, Clone, Serialize)]
 pub struct QueryResponse {
     pub entities: Vec<QueryResultRow>,
     pub total: usize,
+    /// Cursor for the next page, if there is one.
+    pub next_cursor: Option<u64>,
     pub execution_time_us: u64,
 }
 
@@ -272,6 +564,29 @@ pub struct ComponentTypeInfo {
     pub size: usize,
     pub is_opaque: bool,
     pub schema: Option<serde_json::Value>,
+    /// Value to pre-fill spawn and add-component forms with.
+    pub default: Option<serde_json::Value>,
+}
+
+/// Resources used by each loaded module.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemsResponse {
+    pub modules: Vec<ModuleInfo>,
+}
+
+/// Resources used by a loaded module.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleInfo {
+    pub name: String,
+    pub version: Option<u32>,
+    /// Entities that are neither components nor systems.
+    pub entities: usize,
+    pub components: usize,
+    pub systems: usize,
+    /// Bytes used by the columns of the module's components.
+    pub column_bytes: usize,
+    /// Cumulative time spent running the module's systems.
+    pub system_time_us: u64,
 }
 
 /// Chunk data for map view.
@@ -289,23 +604,63 @@ pub struct ChunkInfo {
     pub loaded: bool,
 }
 
+/// Per-system timings of one tick.
+#[derive(Debug, Clone, Serialize)]
+pub struct TickProfileResponse {
+    /// Whether the tick is still held by the profiler.
+    pub found: bool,
+    pub tick: u64,
+    /// Wall-clock length of the tick.
+    pub duration_us: u64,
+    /// The tick, its phases and their systems, in d3-flame-graph's format.
+    pub flame: Option<FlameNode>,
+}
+
 /// Component change history response.
 #[derive(Debug, Clone, Serialize)]
 pub struct HistoryResponse {
     pub entries: Vec<HistoryEntry>,
     pub total: usize,
+    /// What the store's memory tier holds (see [`HistoryStore::memory_usage`]).
+    ///
+    /// [`HistoryStore::memory_usage`]: crate::HistoryStore::memory_usage
+    pub memory: HistoryMemory,
 }
 
 /// Simple oneshot channel for responses.
 pub mod oneshot {
     use crossbeam_channel::bounded;
 
-    pub struct Sender<T>(crossbeam_channel::Sender<T>);
+    /// Runs on a response before it's sent.
+    type Filter<T> = Box<dyn FnOnce(&mut T) + Send + Sync>;
+
+    pub struct Sender<T> {
+        tx: crossbeam_channel::Sender<T>,
+        filter: Option<Filter<T>>,
+    }
     pub struct Receiver<T>(crossbeam_channel::Receiver<T>);
 
     impl<T> Sender<T> {
-        pub fn send(self, value: T) -> Result<(), T> {
-            self.0.send(value).map_err(|e| e.0)
+        pub fn send(self, mut value: T) -> Result<(), T> {
+            if let Some(filter) = self.filter {
+                filter(&mut value);
+            }
+            self.tx.send(value).map_err(|e| e.0)
+        }
+
+        /// Run `filter` on the response before it's sent, after any filter
+        /// already set.
+        pub(crate) fn set_filter(&mut self, filter: impl FnOnce(&mut T) + Send + Sync + 'static)
+        where
+            T: 'static,
+        {
+            self.filter = Some(match self.filter.take() {
+                Some(first) => Box::new(move |value: &mut T| {
+                    first(value);
+                    filter(value);
+                }),
+                None => Box::new(filter),
+            });
         }
     }
 
@@ -333,6 +688,6 @@ pub mod oneshot {
 
     pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
         let (tx, rx) = bounded(1);
-        (Sender(tx), Receiver(rx))
+        (Sender { tx, filter: None }, Receiver(rx))
     }
 }
@@ -0,0 +1,280 @@
+//! Pagination and field projection for [`QuerySpec`].
+//!
+//! On a world with 100k matching entities, serializing every component of
+//! every match makes the dashboard crawl. A query should instead pick the
+//! page of entities it returns first, and only serialize what was asked for
+//! on that page:
+//!
+//! ```ignore
+//! let page = spec.page(matching_entities.iter().map(|e| e.to_bits()));
+//! let projection = spec.projection();
+//! let entities = page.entities.iter().map(|&id| {
+//!     let mut components = serde_json::Map::new();
+//!     for name in projection.components(&spec) {
+//!         if let Some(value) = serialize(id, name) {
+//!             if let Some(value) = projection.project(name, value) {
+//!                 components.insert(name.to_string(), value);
+//!             }
+//!         }
+//!     }
+//!     QueryResultRow { entity: id, name: None, components }
+//! });
+//! QueryResponse { entities, total: page.total, next_cursor: page.next_cursor, .. }
+//! ```
+//!
+//! Pages are in entity ID order. [`QuerySpec::cursor`] continues after the
+//! last entity of the previous page, which stays correct while entities are
+//! spawned and despawned between requests; [`QuerySpec::offset`] is still
+//! applied (after the cursor) for jumping to a page number.
+//!
+//! [`QuerySpec::fields`] lists what to return: a component name returns the
+//! whole component, `Component.path.to.field` only that field, nested in
+//! its objects as usual (array elements come back keyed by their index).
+//! Without fields, every `with` and `optional` component is returned whole.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::protocol::QuerySpec;
+
+/// Results per page when a query doesn't set a limit.
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Most results a page may hold, whatever the query asks for.
+pub const MAX_LIMIT: usize = 1000;
+
+/// The entities on one page of query results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// Entity IDs on this page, ascending.
+    pub entities: Vec<u64>,
+    /// Entities matching the query in all.
+    pub total: usize,
+    /// Cursor for the next page, if there is one.
+    pub next_cursor: Option<u64>,
+}
+
+impl QuerySpec {
+    /// Results per page: the query's limit, capped at [`MAX_LIMIT`].
+    #[must_use]
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT)
+    }
+
+    /// Pick this query's page out of the IDs of all matching entities, in
+    /// any order.
+    #[must_use]
+    pub fn page(&self, matches: impl IntoIterator<Item = u64>) -> Page {
+        let mut ids: Vec<u64> = matches.into_iter().collect();
+        ids.sort_unstable();
+        let total = ids.len();
+
+        let start = self
+            .cursor
+            .map_or(0, |cursor| ids.partition_point(|&id| id <= cursor));
+        let start = start.saturating_add(self.offset.unwrap_or(0)).min(total);
+        let end = start.saturating_add(self.page_size()).min(total);
+        let entities = ids[start..end].to_vec();
+        let next_cursor = if end < total {
+            entities.last().copied()
+        } else {
+            None
+        };
+
+        Page {
+            entities,
+            total,
+            next_cursor,
+        }
+    }
+
+    /// What of each component this query returns.
+    #[must_use]
+    pub fn projection(&self) -> Projection {
+        Projection::new(&self.fields)
+    }
+}
+
+/// What of each component a query returns, see [`QuerySpec::fields`].
+#[derive(Debug, Clone, Default)]
+pub struct Projection {
+    /// Requested field paths by component; an empty list is the whole
+    /// component. Empty when the query lists no fields.
+    components: HashMap<String, Vec<Vec<String>>>,
+}
+
+impl Projection {
+    /// Parse field selectors such as `Health` or `Position.y`.
+    #[must_use]
+    pub fn new(fields: &[String]) -> Self {
+        let mut components: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        for field in fields {
+            let mut parts = field.split('.').map(str::to_string);
+            let Some(component) = parts.next().filter(|c| !c.is_empty()) else {
+                continue;
+            };
+            let path: Vec<String> = parts.collect();
+            let paths = components.entry(component).or_default();
+            if path.is_empty() {
+                // The whole component wins over any of its fields
+                paths.clear();
+                paths.push(Vec::new());
+            } else if !paths.iter().any(Vec::is_empty) {
+                paths.push(path);
+            }
+        }
+        Self { components }
+    }
+
+    /// Whether the query returns everything of its components.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Components to serialize for each row: the projected ones, or all of
+    /// `with` and `optional` without a projection.
+    #[must_use]
+    pub fn components<'a>(&'a self, spec: &'a QuerySpec) -> Vec<&'a str> {
+        if self.is_full() {
+            spec.with
+                .iter()
+                .chain(&spec.optional)
+                .map(String::as_str)
+                .collect()
+        } else {
+            self.components.keys().map(String::as_str).collect()
+        }
+    }
+
+    /// Cut a component's JSON value down to the requested fields, or `None`
+    /// if the query doesn't return it.
+    #[must_use]
+    pub fn project(&self, component: &str, value: Value) -> Option<Value> {
+        if self.is_full() {
+            return Some(value);
+        }
+        let paths = self.components.get(component)?;
+        if paths.iter().any(Vec::is_empty) {
+            return Some(value);
+        }
+
+        let mut projected = Value::Object(Map::new());
+        for path in paths {
+            if let Some(field) = lookup(&value, path) {
+                insert(&mut projected, path, field.clone());
+            }
+        }
+        Some(projected)
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| match value {
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => value.get(key),
+    })
+}
+
+/// Set `path` in `target`, creating the objects on the way.
+fn insert(target: &mut Value, path: &[String], field: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut target = target;
+    for key in parents {
+        let Value::Object(map) = target else {
+            return;
+        };
+        target = map
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if let Value::Object(map) = target {
+        map.insert(last.clone(), field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn spec(limit: usize, cursor: Option<u64>, offset: Option<usize>) -> QuerySpec {
+        QuerySpec {
+            with: vec!["Position".into()],
+            optional: vec!["Health".into()],
+            filter: Vec::new(),
+            without: Vec::new(),
+            limit: Some(limit),
+            offset,
+            cursor,
+            fields: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_cursor_pages_through_all_matches() {
+        let matches = [7, 3, 9, 1, 5];
+        let first = spec(2, None, None).page(matches);
+        assert_eq!(first.entities, [1, 3]);
+        assert_eq!(first.total, 5);
+        assert_eq!(first.next_cursor, Some(3));
+
+        // Entity 3 despawning between pages doesn't skip anything
+        let second = spec(2, first.next_cursor, None).page([7, 9, 1, 5]);
+        assert_eq!(second.entities, [5, 7]);
+        let last = spec(2, second.next_cursor, None).page(matches);
+        assert_eq!(last.entities, [9]);
+        assert_eq!(last.next_cursor, None);
+    }
+
+    #[test]
+    fn test_offset_and_limit_cap() {
+        let page = spec(2, None, Some(3)).page(1..=10);
+        assert_eq!(page.entities, [4, 5]);
+        assert!(spec(2, None, Some(20)).page(1..=10).entities.is_empty());
+        assert_eq!(spec(usize::MAX, None, None).page_size(), MAX_LIMIT);
+        assert_eq!(spec(1, None, None).page(1..=10).next_cursor, Some(1));
+    }
+
+    #[test]
+    fn test_projection_keeps_requested_fields() {
+        let projection = Projection::new(&[
+            "Position.y".into(),
+            "Inventory.slots.0".into(),
+            "Health".into(),
+            "Health.max".into(),
+        ]);
+        let position = json!({"x": 1.0, "y": 64.0, "z": 2.0});
+        assert_eq!(
+            projection.project("Position", position),
+            Some(json!({"y": 64.0}))
+        );
+        let inventory = json!({"slots": [{"item": "stone"}, {"item": "dirt"}], "selected": 0});
+        assert_eq!(
+            projection.project("Inventory", inventory),
+            Some(json!({"slots": {"0": {"item": "stone"}}}))
+        );
+        // Asking for the whole component wins
+        let health = json!({"current": 10, "max": 20});
+        assert_eq!(projection.project("Health", health.clone()), Some(health));
+        assert_eq!(projection.project("Velocity", json!({})), None);
+    }
+
+    #[test]
+    fn test_components_to_serialize() {
+        let mut spec = spec(10, None, None);
+        assert_eq!(spec.projection().components(&spec), ["Position", "Health"]);
+
+        spec.fields = vec!["Position.x".into()];
+        let projection = spec.projection();
+        assert_eq!(projection.components(&spec), ["Position"]);
+        assert_eq!(
+            projection.project("Position", json!({"x": 1, "y": 2})),
+            Some(json!({"x": 1}))
+        );
+    }
+}
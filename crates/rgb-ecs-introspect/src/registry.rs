@@ -6,6 +6,10 @@ use std::collections::HashMap;
 
 use rgb_ecs::{ComponentId, World};
 
+use crate::diff::ComponentDiff;
+use crate::history::{ChangeSource, HistoryStore};
+use crate::prefab::PrefabRegistry;
+use crate::protocol::{ComponentTypeInfo, ComponentTypesResponse};
 use crate::{IntrospectError, Introspectable};
 
 /// Type-erased information about an introspectable component.
@@ -24,17 +28,25 @@ pub struct IntrospectInfo {
     pub is_opaque: bool,
     /// JSON schema for the component.
     pub schema: Option<serde_json::Value>,
+    /// Dashboard JSON to pre-fill spawn and add-component forms with.
+    pub default: Option<serde_json::Value>,
     /// Function to serialize component to JSON from raw pointer.
     serialize_fn: SerializeFn,
     /// Function to deserialize JSON to component bytes.
     deserialize_fn: DeserializeFn,
     /// Function to get opaque info summary from raw pointer.
     opaque_info_fn: OpaqueInfoFn,
+    /// Function to apply JSON edits on top of an existing component.
+    merge_fn: MergeFn,
+    /// Function to deserialize JSON and insert it on an entity.
+    insert_fn: InsertFn,
 }
 
 type SerializeFn = fn(*const u8) -> serde_json::Value;
 type DeserializeFn = fn(serde_json::Value) -> Result<AlignedBuffer, IntrospectError>;
 type OpaqueInfoFn = fn(*const u8) -> Option<String>;
+type MergeFn = fn(*const u8, serde_json::Value) -> Result<AlignedBuffer, IntrospectError>;
+type InsertFn = fn(&mut World, rgb_ecs::Entity, serde_json::Value) -> Result<bool, IntrospectError>;
 
 /// Public fields for IntrospectInfo
 impl IntrospectInfo {
@@ -60,6 +72,7 @@ impl IntrospectInfo {
             layout: Layout::new::<T>(),
             is_opaque: T::is_opaque(),
             schema: T::schema(),
+            default: T::default_json(),
             serialize_fn: |ptr| {
                 // SAFETY: Caller ensures ptr points to valid T
                 let value: &T = unsafe { &*(ptr.cast::<T>()) };
@@ -80,6 +93,32 @@ impl IntrospectInfo {
                 let value: &T = unsafe { &*(ptr.cast::<T>()) };
                 value.opaque_info()
             },
+            merge_fn: |ptr, json| {
+                // SAFETY: Caller ensures ptr points to valid T
+                let current: &T = unsafe { &*(ptr.cast::<T>()) };
+                let value = current.merge_json(json)?;
+                let mut buffer = AlignedBuffer::new(Layout::new::<T>());
+                // SAFETY: buffer is properly sized and aligned for T
+                unsafe {
+                    core::ptr::write(buffer.as_mut_ptr().cast::<T>(), value);
+                }
+                Ok(buffer)
+            },
+            insert_fn: |world, entity, json| Ok(world.insert(entity, T::from_json(json)?)),
+        }
+    }
+
+    /// Add (or replace) this component on an entity from JSON.
+    pub fn insert_json(
+        &self,
+        world: &mut World,
+        entity: rgb_ecs::Entity,
+        json: serde_json::Value,
+    ) -> Result<(), IntrospectError> {
+        if (self.insert_fn)(world, entity, json)? {
+            Ok(())
+        } else {
+            Err(IntrospectError::EntityNotFound(entity.to_bits()))
         }
     }
 
@@ -114,8 +153,33 @@ impl IntrospectInfo {
         Some(unsafe { self.serialize(ptr) })
     }
 
+    /// Apply a dashboard edit to a component and report what changed.
+    ///
+    /// The edit is merged like [`merge_json`](Self::merge_json), so skipped
+    /// and redacted fields keep their values. Old and new values are captured
+    /// as dashboard JSON, so the diff never contains those fields.
+    pub fn update_json(
+        &self,
+        world: &mut World,
+        entity: rgb_ecs::Entity,
+        json: &serde_json::Value,
+    ) -> Result<ComponentUpdate, IntrospectError> {
+        let old = self
+            .get_json(world, entity)
+            .ok_or_else(|| IntrospectError::ComponentNotFound(self.name.to_string()))?;
+        self.merge_json(world, entity, json)?;
+        let new = self
+            .get_json(world, entity)
+            .unwrap_or(serde_json::Value::Null);
+        let diff = ComponentDiff::between(&old, &new);
+        Ok(ComponentUpdate { old, new, diff })
+    }
+
     /// Set component from JSON on an entity.
     ///
+    /// Replaces the whole value: skipped and redacted fields are reset to
+    /// their defaults. Use [`merge_json`](Self::merge_json) to keep them.
+    ///
     /// Returns an error if deserialization fails or the component can't be set.
     pub fn set_json(
         &self,
@@ -124,7 +188,36 @@ impl IntrospectInfo {
         json: &serde_json::Value,
     ) -> Result<(), IntrospectError> {
         let buffer = self.deserialize(json.clone())?;
+        self.write(world, entity, &buffer)
+    }
+
+    /// Merge JSON onto a component on an entity.
+    ///
+    /// Fields missing from `json`, including skipped and redacted ones, keep
+    /// their current values.
+    ///
+    /// Returns an error if the entity doesn't have the component or the
+    /// merged value doesn't deserialize.
+    pub fn merge_json(
+        &self,
+        world: &mut World,
+        entity: rgb_ecs::Entity,
+        json: &serde_json::Value,
+    ) -> Result<(), IntrospectError> {
+        let ptr = world
+            .get_raw_ptr(entity, self.type_id)
+            .ok_or_else(|| IntrospectError::ComponentNotFound(self.name.to_string()))?;
+        let buffer = (self.merge_fn)(ptr, json.clone())?;
+        self.write(world, entity, &buffer)
+    }
 
+    /// Overwrite the component on an entity with deserialized bytes.
+    fn write(
+        &self,
+        world: &mut World,
+        entity: rgb_ecs::Entity,
+        buffer: &AlignedBuffer,
+    ) -> Result<(), IntrospectError> {
         // SAFETY: buffer contains valid component data matching the component's layout
         let success = unsafe { world.update_raw(entity, self.component_id, buffer.as_ptr()) };
 
@@ -136,6 +229,31 @@ impl IntrospectInfo {
     }
 }
 
+impl From<&IntrospectInfo> for ComponentTypeInfo {
+    fn from(info: &IntrospectInfo) -> Self {
+        Self {
+            id: info.component_id.as_raw(),
+            name: info.name.to_string(),
+            full_name: info.full_name.to_string(),
+            size: info.size(),
+            is_opaque: info.is_opaque,
+            schema: info.schema.clone(),
+            default: info.default.clone(),
+        }
+    }
+}
+
+/// Outcome of [`IntrospectInfo::update_json`].
+#[derive(Debug, Clone)]
+pub struct ComponentUpdate {
+    /// Dashboard JSON before the update.
+    pub old: serde_json::Value,
+    /// Dashboard JSON after the update.
+    pub new: serde_json::Value,
+    /// Field-level changes from `old` to `new`.
+    pub diff: ComponentDiff,
+}
+
 /// Buffer with proper alignment for component storage.
 pub struct AlignedBuffer {
     data: Box<[u8]>,
@@ -210,12 +328,108 @@ impl IntrospectRegistry {
         self.by_name.get(name).and_then(|id| self.by_id.get(id))
     }
 
+    /// Set the default value the dashboard offers for a component, e.g.
+    /// for a type that can't be annotated. Returns `false` if no component
+    /// has this name.
+    pub fn set_default(&mut self, component: &str, value: serde_json::Value) -> bool {
+        let Some(info) = self
+            .by_name
+            .get(component)
+            .and_then(|id| self.by_id.get_mut(id))
+        else {
+            return false;
+        };
+        info.default = Some(value);
+        true
+    }
+
+    /// Every registered component type, sorted by name, for
+    /// [`IntrospectRequest::GetComponentTypes`](crate::IntrospectRequest::GetComponentTypes).
+    #[must_use]
+    pub fn component_types(&self) -> ComponentTypesResponse {
+        let mut types: Vec<ComponentTypeInfo> = self.iter().map(ComponentTypeInfo::from).collect();
+        types.sort_by(|a, b| a.name.cmp(&b.name));
+        ComponentTypesResponse { types }
+    }
+
     /// Get component ID by short type name.
     #[must_use]
     pub fn component_id(&self, name: &str) -> Option<ComponentId> {
         self.by_name.get(name).copied()
     }
 
+    /// Update a component by short type name, returning the field-level diff.
+    ///
+    /// When `history` is given, the change is recorded there with its diff.
+    pub fn update(
+        &self,
+        world: &mut World,
+        entity: rgb_ecs::Entity,
+        component: &str,
+        json: &serde_json::Value,
+        history: Option<&HistoryStore>,
+    ) -> Result<ComponentDiff, IntrospectError> {
+        let info = self
+            .get_by_name(component)
+            .ok_or_else(|| IntrospectError::NotIntrospectable(component.to_string()))?;
+        let update = info.update_json(world, entity, json)?;
+
+        if let Some(history) = history {
+            history.record_update(
+                entity.to_bits(),
+                info.name.to_string(),
+                update.old,
+                update.new,
+                ChangeSource::Dashboard,
+            );
+        }
+
+        Ok(update.diff)
+    }
+
+    /// Spawn an entity from component name -> JSON value pairs.
+    ///
+    /// Either every component is inserted or the entity is despawned and the
+    /// first error is returned.
+    pub fn spawn_json<'a>(
+        &self,
+        world: &mut World,
+        components: impl IntoIterator<Item = (&'a str, serde_json::Value)>,
+    ) -> Result<rgb_ecs::Entity, IntrospectError> {
+        let entity = world.spawn_empty();
+        for (name, value) in components {
+            let result = self
+                .get_by_name(name)
+                .ok_or_else(|| IntrospectError::NotIntrospectable(name.to_string()))
+                .and_then(|info| info.insert_json(world, entity, value));
+            if let Err(err) = result {
+                world.despawn(entity);
+                return Err(err);
+            }
+        }
+        Ok(entity)
+    }
+
+    /// Spawn an entity from a named prefab template with per-component overrides.
+    pub fn spawn_prefab(
+        &self,
+        world: &mut World,
+        prefabs: &PrefabRegistry,
+        name: &str,
+        overrides: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<rgb_ecs::Entity, IntrospectError> {
+        let template = prefabs
+            .get(name)
+            .ok_or_else(|| IntrospectError::PrefabNotFound(name.to_string()))?;
+        let components = template.resolve(overrides);
+        self.spawn_json(
+            world,
+            components
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone())),
+        )
+    }
+
     /// Iterate over all registered introspectable components.
     pub fn iter(&self) -> impl Iterator<Item = &IntrospectInfo> {
         self.by_id.values()
@@ -233,3 +447,114 @@ impl IntrospectRegistry {
         self.by_id.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rgb_ecs::World;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, Introspectable)]
+    #[introspectable(default)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize, Introspectable)]
+    #[introspectable(default = r#"{"x": 0.0, "y": 64.0, "z": 0.0}"#)]
+    struct Position {
+        x: f64,
+        y: f64,
+        z: f64,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize, Introspectable)]
+    struct Velocity {
+        x: f64,
+        y: f64,
+        z: f64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Introspectable)]
+    struct Session {
+        name: String,
+        #[introspectable(skip)]
+        token: u64,
+    }
+
+    #[test]
+    fn test_set_json_replaces_and_merge_json_keeps_hidden_fields() {
+        let mut world = World::new();
+        world.register_component::<Session>();
+        let mut registry = IntrospectRegistry::new();
+        registry.register::<Session>(&world);
+        let info = registry.get_by_name("Session").unwrap();
+
+        let entity = world.spawn(Session {
+            name: "a".to_string(),
+            token: 7,
+        });
+        info.merge_json(&mut world, entity, &json!({ "name": "b" }))
+            .unwrap();
+        assert_eq!(
+            world.get::<Session>(entity),
+            Some(Session {
+                name: "b".to_string(),
+                token: 7,
+            })
+        );
+
+        info.set_json(&mut world, entity, &json!({ "name": "c" }))
+            .unwrap();
+        assert_eq!(
+            world.get::<Session>(entity),
+            Some(Session {
+                name: "c".to_string(),
+                token: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_component_types_carry_defaults() {
+        let mut world = World::new();
+        world.register_component::<Health>();
+        world.register_component::<Position>();
+        world.register_component::<Velocity>();
+
+        let mut registry = IntrospectRegistry::new();
+        registry.register::<Health>(&world);
+        registry.register::<Position>(&world);
+        registry.register::<Velocity>(&world);
+
+        let defaults = |registry: &IntrospectRegistry| {
+            registry
+                .component_types()
+                .types
+                .into_iter()
+                .map(|info| (info.name, info.default))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            defaults(&registry),
+            [
+                (
+                    "Health".to_string(),
+                    Some(json!({ "current": 0, "max": 0 }))
+                ),
+                (
+                    "Position".to_string(),
+                    Some(json!({ "x": 0.0, "y": 64.0, "z": 0.0 }))
+                ),
+                ("Velocity".to_string(), None),
+            ]
+        );
+
+        assert!(registry.set_default("Velocity", json!({ "x": 0.0, "y": 0.0, "z": 0.0 })));
+        assert!(!registry.set_default("Missing", json!({})));
+        assert!(defaults(&registry)[2].1.is_some());
+    }
+}
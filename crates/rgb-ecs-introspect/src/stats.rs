@@ -0,0 +1,178 @@
+//! World statistics for the dashboard overview.
+//!
+//! [`world_stats`] summarizes the archetype storage: how entities are spread
+//! over archetypes and how much column memory each component takes. Churn
+//! (spawns and despawns per tick) needs samples over time, so the world
+//! calls [`ChurnTracker::record_tick`] once per tick:
+//!
+//! ```ignore
+//! churn.record_tick(&world);
+//! // ...
+//! IntrospectRequest::WorldStats { response, .. } => {
+//!     let _ = response.send(world_stats(&world, &churn));
+//! }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+
+use rgb_ecs::World;
+
+use crate::protocol::{ArchetypeSizeBucket, ComponentMemory, WorldStatsResponse};
+
+/// Ticks churn rates are averaged over.
+pub const CHURN_WINDOW: usize = 20;
+
+/// Spawn and despawn totals of the last [`CHURN_WINDOW`] ticks.
+#[derive(Debug, Clone, Default)]
+pub struct ChurnTracker {
+    samples: VecDeque<(u64, u64)>,
+}
+
+impl ChurnTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sample the world's spawn and despawn totals at the end of a tick.
+    pub fn record_tick(&mut self, world: &World) {
+        if self.samples.len() > CHURN_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back((world.spawned_total(), world.despawned_total()));
+    }
+
+    /// Average spawns and despawns per tick over the window, zero until two
+    /// ticks have been recorded.
+    #[must_use]
+    pub fn rates(&self) -> (f64, f64) {
+        let (Some(first), Some(last)) = (self.samples.front(), self.samples.back()) else {
+            return (0.0, 0.0);
+        };
+        let ticks = (self.samples.len() - 1).max(1) as f64;
+        (
+            (last.0 - first.0) as f64 / ticks,
+            (last.1 - first.1) as f64 / ticks,
+        )
+    }
+}
+
+/// Histogram bucket an archetype of `len` entities falls in: `0` for empty
+/// archetypes, then powers of two (`1`, `2..=3`, `4..=7`, ...).
+const fn bucket(len: usize) -> usize {
+    (usize::BITS - len.leading_zeros()) as usize
+}
+
+/// Current statistics of a world.
+#[must_use]
+pub fn world_stats(world: &World, churn: &ChurnTracker) -> WorldStatsResponse {
+    let mut histogram = Vec::new();
+    let mut components: HashMap<_, ComponentMemory> = HashMap::new();
+    for archetype in world.archetypes().iter() {
+        let index = bucket(archetype.len());
+        if histogram.len() <= index {
+            histogram.resize(index + 1, 0);
+        }
+        histogram[index] += 1;
+
+        for &id in archetype.components() {
+            let Some(info) = world.components().get_info(id) else {
+                continue;
+            };
+            let memory = components.entry(id).or_insert_with(|| ComponentMemory {
+                name: info.name().to_string(),
+                entities: 0,
+                bytes: 0,
+            });
+            memory.entities += archetype.len();
+            memory.bytes += archetype.len() * info.size();
+        }
+    }
+
+    let archetype_sizes = histogram
+        .into_iter()
+        .enumerate()
+        .map(|(index, archetypes)| ArchetypeSizeBucket {
+            min: if index == 0 { 0 } else { 1 << (index - 1) },
+            max: if index == 0 { 0 } else { (1 << index) - 1 },
+            archetypes,
+        })
+        .collect();
+
+    let mut components: Vec<_> = components.into_values().collect();
+    components.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    let (spawns_per_tick, despawns_per_tick) = churn.rates();
+
+    WorldStatsResponse {
+        entity_count: world.entity_count(),
+        archetype_count: world.archetypes().len(),
+        archetype_sizes,
+        component_bytes: components.iter().map(|c| c.bytes).sum(),
+        components,
+        spawns_per_tick,
+        despawns_per_tick,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    struct Position(#[allow(dead_code)] [f32; 3]);
+
+    #[derive(Clone, Copy)]
+    struct Health(#[allow(dead_code)] u32);
+
+    #[test]
+    fn test_world_stats() {
+        let mut world = World::new();
+        for _ in 0..5 {
+            world.spawn(Position([0.0; 3]));
+        }
+        let entity = world.spawn(Position([0.0; 3]));
+        world.insert(entity, Health(20));
+
+        let stats = world_stats(&world, &ChurnTracker::new());
+        assert_eq!(stats.entity_count, 7);
+        assert_eq!(stats.archetype_count, world.archetypes().len());
+
+        let position = stats
+            .components
+            .iter()
+            .find(|c| c.name.ends_with("Position"))
+            .unwrap();
+        assert_eq!(position.entities, 6);
+        assert_eq!(position.bytes, 6 * 12);
+        assert_eq!(stats.components[0].name, position.name);
+
+        // The Position archetype holds 5 entities: the 4..=7 bucket
+        let bucket = &stats.archetype_sizes[3];
+        assert_eq!((bucket.min, bucket.max), (4, 7));
+        assert_eq!(bucket.archetypes, 1);
+        let total: usize = stats.archetype_sizes.iter().map(|b| b.archetypes).sum();
+        assert_eq!(total, stats.archetype_count);
+    }
+
+    #[test]
+    fn test_churn_rates() {
+        let mut world = World::new();
+        let mut churn = ChurnTracker::new();
+        churn.record_tick(&world);
+        assert_eq!(churn.rates(), (0.0, 0.0));
+
+        for _ in 0..2 {
+            let a = world.spawn(Health(1));
+            world.spawn(Health(1));
+            world.despawn(a);
+            churn.record_tick(&world);
+        }
+        assert_eq!(churn.rates(), (2.0, 1.0));
+
+        for _ in 0..CHURN_WINDOW {
+            churn.record_tick(&world);
+        }
+        assert_eq!(churn.rates(), (0.0, 0.0));
+    }
+}
@@ -1,6 +1,7 @@
 //! The Introspectable trait for components that can be serialized to JSON.
 
 use crate::IntrospectError;
+use crate::fields::FieldRule;
 
 /// Trait for components that can be serialized to/from JSON for the dashboard.
 ///
@@ -34,11 +35,30 @@ pub trait Introspectable: rgb_ecs::Component + Clone + Send + Sync + 'static {
 
     /// Deserialize a component from a JSON value.
     ///
-    /// Returns an error for opaque components.
+    /// Skipped and redacted fields aren't read from `value`; they get their
+    /// type's default. Returns an error for opaque components.
     fn from_json(value: serde_json::Value) -> Result<Self, IntrospectError>
     where
         Self: Sized;
 
+    /// Apply a dashboard edit on top of this value.
+    ///
+    /// Fields missing from `value` keep their current values, which is how
+    /// skipped and redacted fields survive edits. Defaults to [`Self::from_json`].
+    fn merge_json(&self, value: serde_json::Value) -> Result<Self, IntrospectError>
+    where
+        Self: Sized,
+    {
+        Self::from_json(value)
+    }
+
+    /// Field-level visibility rules (`skip`, `redact`, `rename`).
+    ///
+    /// Empty unless the derive declares field attributes.
+    fn field_rules() -> &'static [FieldRule] {
+        &[]
+    }
+
     /// Get the JSON schema for this component type.
     ///
     /// Used by the dashboard to generate appropriate editors.
@@ -47,6 +67,14 @@ pub trait Introspectable: rgb_ecs::Component + Clone + Send + Sync + 'static {
         None
     }
 
+    /// Dashboard JSON to pre-fill spawn and add-component forms with.
+    ///
+    /// `None` unless the derive declares `#[introspectable(default)]` or
+    /// `#[introspectable(default = "...")]`.
+    fn default_json() -> Option<serde_json::Value> {
+        None
+    }
+
     /// Whether this is an opaque component (cannot be serialized).
     fn is_opaque() -> bool {
         false
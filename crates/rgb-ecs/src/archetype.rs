@@ -16,7 +16,7 @@ use crate::{
 };
 
 /// Unique identifier for an archetype.
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ArchetypeId(u32);
 
 impl ArchetypeId {
@@ -179,6 +179,22 @@ impl Archetype {
         self.columns.get_mut(index)
     }
 
+    /// Get two distinct mutable columns by index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a == b` or either index is out of bounds.
+    pub(crate) fn column_pair_mut(&mut self, a: usize, b: usize) -> (&mut Column, &mut Column) {
+        assert_ne!(a, b, "Column borrowed mutably twice");
+        let (low, high) = self.columns.split_at_mut(a.max(b));
+        let (first, second) = (&mut low[a.min(b)], &mut high[0]);
+        if a < b {
+            (first, second)
+        } else {
+            (second, first)
+        }
+    }
+
     /// Allocate space for a new entity and return its row index.
     ///
     /// Does NOT initialize component data - caller must write to columns.
@@ -402,6 +418,11 @@ impl ArchetypeStorage {
         self.archetypes.iter()
     }
 
+    /// Iterate mutably over all archetypes.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Archetype> {
+        self.archetypes.iter_mut()
+    }
+
     /// Iterate over archetypes that contain ALL of the given components.
     pub fn iter_matching(
         &self,
@@ -124,6 +124,10 @@ pub struct EntityAllocator {
     free_list: Vec<EntityId>,
     /// Number of currently alive entities.
     alive_count: u32,
+    /// Entities ever allocated.
+    allocated_total: u64,
+    /// Entities ever deallocated.
+    deallocated_total: u64,
 }
 
 impl Default for EntityAllocator {
@@ -140,6 +144,8 @@ impl EntityAllocator {
             generations: Vec::new(),
             free_list: Vec::new(),
             alive_count: 0,
+            allocated_total: 0,
+            deallocated_total: 0,
         }
     }
 
@@ -150,12 +156,15 @@ impl EntityAllocator {
             generations: Vec::with_capacity(capacity),
             free_list: Vec::with_capacity(capacity / 4),
             alive_count: 0,
+            allocated_total: 0,
+            deallocated_total: 0,
         }
     }
 
     /// Allocate a new entity.
     pub fn allocate(&mut self) -> Entity {
         self.alive_count += 1;
+        self.allocated_total += 1;
 
         if let Some(id) = self.free_list.pop() {
             // Reuse a recycled slot
@@ -188,6 +197,7 @@ impl EntityAllocator {
         self.generations[id] = self.generations[id].next();
         self.free_list.push(entity.id());
         self.alive_count -= 1;
+        self.deallocated_total += 1;
         true
     }
 
@@ -198,12 +208,30 @@ impl EntityAllocator {
         id < self.generations.len() && self.generations[id] == entity.generation()
     }
 
+    /// Get the current generation of a slot, or `None` if it was never allocated.
+    #[must_use]
+    pub fn generation_of(&self, id: EntityId) -> Option<Generation> {
+        self.generations.get(id as usize).copied()
+    }
+
     /// Get the number of currently alive entities.
     #[must_use]
     pub const fn alive_count(&self) -> u32 {
         self.alive_count
     }
 
+    /// Get the number of entities ever allocated.
+    #[must_use]
+    pub const fn allocated_total(&self) -> u64 {
+        self.allocated_total
+    }
+
+    /// Get the number of entities ever deallocated.
+    #[must_use]
+    pub const fn deallocated_total(&self) -> u64 {
+        self.deallocated_total
+    }
+
     /// Get the total capacity (including recycled slots).
     #[must_use]
     pub fn capacity(&self) -> usize {
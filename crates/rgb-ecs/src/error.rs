@@ -0,0 +1,50 @@
+//! Error types for world access.
+
+use core::panic::Location;
+
+use thiserror::Error;
+
+use crate::entity::{Entity, Generation};
+
+/// A stale (despawned) or never-allocated [`Entity`] handle was used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "entity {entity} is not alive (slot is at {current:?}){}",
+    .spawned_at.map_or_else(String::new, |site| format!(", spawned at {site}"))
+)]
+pub struct EntityNotAlive {
+    /// The handle that was used.
+    pub entity: Entity,
+    /// Current generation of the handle's slot, or `None` if the slot was never allocated.
+    pub current: Option<Generation>,
+    /// Where the handle was spawned, if spawn tracking is enabled
+    /// (see [`World::track_spawn_sites`](crate::World::track_spawn_sites)).
+    pub spawned_at: Option<&'static Location<'static>>,
+}
+
+/// Why [`World::try_insert`](crate::World::try_insert) or
+/// [`World::try_insert_pair`](crate::World::try_insert_pair) rejected a
+/// component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InsertError {
+    #[error(transparent)]
+    NotAlive(#[from] EntityNotAlive),
+
+    /// The entity lacks a component the inserted one requires (see
+    /// [`World::require`](crate::World::require)).
+    #[error("{component} requires {requires}, which entity {entity} doesn't have")]
+    MissingRequirement {
+        entity: Entity,
+        component: &'static str,
+        requires: &'static str,
+    },
+
+    /// The target of a one-to-one relation already has a source (see
+    /// [`Cardinality::OneToOne`](crate::Cardinality::OneToOne)).
+    #[error("{target} is already the target of {relation} from {holder}")]
+    TargetTaken {
+        relation: &'static str,
+        target: Entity,
+        holder: Entity,
+    },
+}
@@ -0,0 +1,209 @@
+//! Component lifecycle hooks, mirroring Flecs' `on_add`/`on_set`/`on_remove`.
+//!
+//! Hooks are registered per component type and run synchronously with the
+//! operation that triggered them:
+//!
+//! ```ignore
+//! world.on_add::<Player>(|world, entity| world.insert(entity, Health(20.0)));
+//! world.on_set::<Position>(|world, entity| {
+//!     let pos = world.get::<Position>(entity).unwrap();
+//!     history.record(entity, pos);
+//! });
+//! world.on_remove::<Connection>(|world, entity| {
+//!     // The component is still readable here
+//!     let conn = world.get::<Connection>(entity).unwrap();
+//!     conn.close();
+//! });
+//! ```
+//!
+//! - `on_add` runs when an entity gains the component (`spawn`, `insert`).
+//! - `on_set` runs whenever a value is written: after `on_add`, and on
+//!   `insert` of a component the entity already has, `update` and
+//!   `update_raw`.
+//! - `on_remove` runs before the component is dropped (`remove`,
+//!   `despawn`), so the value can still be read.
+//!
+//! Hooks get the world mutably and may change it, including the entity
+//! itself. A component can have any number of hooks of each kind; they run
+//! in registration order.
+
+use std::sync::Arc;
+
+use crate::{World, component::ComponentId, entity::Entity};
+
+/// A lifecycle hook.
+pub type Hook = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+/// Which lifecycle event a hook runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Add,
+    Set,
+    Remove,
+}
+
+/// Every hook registered for one component.
+#[derive(Default, Clone)]
+pub struct ComponentHooks {
+    add: Vec<Hook>,
+    set: Vec<Hook>,
+    remove: Vec<Hook>,
+}
+
+impl ComponentHooks {
+    fn get(&self, kind: HookKind) -> &[Hook] {
+        match kind {
+            HookKind::Add => &self.add,
+            HookKind::Set => &self.set,
+            HookKind::Remove => &self.remove,
+        }
+    }
+
+    fn get_mut(&mut self, kind: HookKind) -> &mut Vec<Hook> {
+        match kind {
+            HookKind::Add => &mut self.add,
+            HookKind::Set => &mut self.set,
+            HookKind::Remove => &mut self.remove,
+        }
+    }
+}
+
+impl World {
+    /// Run `hook` whenever an entity gains a `T`.
+    pub fn on_add<T: 'static + Send + Sync>(
+        &mut self,
+        hook: impl Fn(&mut Self, Entity) + Send + Sync + 'static,
+    ) {
+        self.add_hook::<T>(HookKind::Add, Arc::new(hook));
+    }
+
+    /// Run `hook` whenever an entity's `T` is written.
+    pub fn on_set<T: 'static + Send + Sync>(
+        &mut self,
+        hook: impl Fn(&mut Self, Entity) + Send + Sync + 'static,
+    ) {
+        self.add_hook::<T>(HookKind::Set, Arc::new(hook));
+    }
+
+    /// Run `hook` whenever an entity loses its `T`, before the value is dropped.
+    pub fn on_remove<T: 'static + Send + Sync>(
+        &mut self,
+        hook: impl Fn(&mut Self, Entity) + Send + Sync + 'static,
+    ) {
+        self.add_hook::<T>(HookKind::Remove, Arc::new(hook));
+    }
+
+    fn add_hook<T: 'static + Send + Sync>(&mut self, kind: HookKind, hook: Hook) {
+        let comp_id = self.register::<T>();
+        self.hooks
+            .entry(comp_id)
+            .or_default()
+            .get_mut(kind)
+            .push(hook);
+    }
+
+    /// Whether any hook of `kind` is registered for a component.
+    pub(crate) fn has_hooks(&self, kind: HookKind, comp_id: ComponentId) -> bool {
+        self.hooks
+            .get(&comp_id)
+            .is_some_and(|hooks| !hooks.get(kind).is_empty())
+    }
+
+    /// Run the hooks of `kind` registered for a component on an entity.
+    ///
+    /// Stops early if a hook despawns the entity.
+    pub(crate) fn run_hooks(&mut self, kind: HookKind, comp_id: ComponentId, entity: Entity) {
+        let Some(hooks) = self.hooks.get(&comp_id) else {
+            return;
+        };
+        // Cloned so hooks can register hooks of their own
+        let hooks = hooks.get(kind).to_vec();
+        for hook in hooks {
+            if !self.is_alive(entity) {
+                return;
+            }
+            hook(self, entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Position(f32);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Health(f32);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Player;
+
+    #[test]
+    fn test_hooks_follow_lifecycle() {
+        let mut world = World::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        for kind in [HookKind::Add, HookKind::Set, HookKind::Remove] {
+            let events = events.clone();
+            let hook: Hook = Arc::new(move |world: &mut World, entity| {
+                let value = world.get::<Position>(entity).map(|pos| pos.0);
+                events.lock().unwrap().push((kind, value));
+            });
+            world.add_hook::<Position>(kind, hook);
+        }
+
+        let entity = world.spawn(Position(1.0));
+        world.insert(entity, Position(2.0));
+        world.update(entity, Position(3.0));
+        world.remove::<Position>(entity);
+        world.insert(entity, Position(4.0));
+        world.despawn(entity);
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                (HookKind::Add, Some(1.0)),
+                (HookKind::Set, Some(1.0)),
+                (HookKind::Set, Some(2.0)),
+                (HookKind::Set, Some(3.0)),
+                (HookKind::Remove, Some(3.0)),
+                (HookKind::Add, Some(4.0)),
+                (HookKind::Set, Some(4.0)),
+                (HookKind::Remove, Some(4.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hooks_can_change_the_world() {
+        let mut world = World::new();
+        world.on_add::<Player>(|world, entity| {
+            world.insert(entity, Health(20.0));
+        });
+        world.on_remove::<Player>(|world, entity| {
+            world.remove::<Health>(entity);
+        });
+
+        let player = world.spawn(Player);
+        assert_eq!(world.get::<Health>(player), Some(Health(20.0)));
+
+        world.remove::<Player>(player);
+        assert!(!world.has::<Health>(player));
+        assert!(world.is_alive(player));
+    }
+
+    #[test]
+    fn test_hook_despawning_its_entity() {
+        let mut world = World::new();
+        world.on_add::<Player>(|world, entity| {
+            world.despawn(entity);
+        });
+        world.on_set::<Player>(|_, _| panic!("entity was despawned"));
+
+        let player = world.spawn(Player);
+        assert!(!world.is_alive(player));
+    }
+}
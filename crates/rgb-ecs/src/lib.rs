@@ -24,6 +24,9 @@
 //! - **Archetype**: A unique combination of component types
 //! - **Relation/Pair**: A relationship between entities `(Relation, Target)`
 //! - **Global**: Marker for global entities (read-only in parallel, writable in sequential)
+//! - **Prefab**: Template entity that instances are spawned from (`world.spawn_from(prefab)`)
+//! - **Migration**: Copying entities into another world (`world.clone_entity_into(&mut other, e)`)
+//! - **Hook**: Callback run when a component is added, set or removed (`world.on_set::<T>(f)`)
 //!
 //! # Component Design
 //!
@@ -54,6 +57,12 @@
 //! - `insert<T>()` - Add new component
 //! - `remove<T>()` - Remove and return component
 //!
+//! Stale handles (despawned entities) never see another entity's data. The
+//! `try_get`/`try_insert`/`try_update` variants report them as [`EntityNotAlive`].
+//!
+//! Components can [require](World::require) others; inserting one without
+//! its requirements either fills them in with defaults or is rejected.
+//!
 //! # Global State
 //!
 //! Use `Entity::WORLD` for global state instead of singletons:
@@ -68,26 +77,48 @@
 //! world.update(Entity::WORLD, new_config);
 //! ```
 
+// Lets `#[derive(Component)]` expansions (which name `::rgb_ecs`) work in this crate's tests.
+#[cfg(test)]
+extern crate self as rgb_ecs;
+
 mod archetype;
 mod component;
 mod entity;
+mod error;
+mod hooks;
+mod migrate;
+mod prefab;
 mod query;
+mod reflect;
 mod relation;
+mod requires;
 mod storage;
 mod world;
 
 pub use archetype::{Archetype, ArchetypeId};
 pub use component::{Component, ComponentId, ComponentInfo, ComponentRegistry};
 pub use entity::{Entity, EntityId, Generation};
-pub use query::{Query, QueryBuilder, QueryIter, QueryRow, QueryTerm, TermAccess};
-pub use relation::{ChildOf, ContainedIn, InstanceOf, OwnedBy, Pair, PairId, Requires};
+pub use error::{EntityNotAlive, InsertError};
+pub use hooks::Hook;
+pub use migrate::EntityMap;
+pub use prefab::Prefab;
+pub use query::{
+    Query, QueryBuilder, QueryChunk, QueryChunkMut, QueryIter, QueryRow, QueryTerm, TermAccess,
+};
+pub use reflect::{ComponentReflect, FieldInfo, ReflectPrimitive, TypeTag};
+#[doc(hidden)]
+pub use reflect::{TagFallback, TagOf};
+pub use relation::{
+    Cardinality, ChildOf, ContainedIn, InstanceOf, OwnedBy, Pair, PairId, Relation, Requires,
+};
+pub use requires::ComponentRequires;
 pub use storage::{Column, ComponentStorage};
 pub use world::{Global, Plugin, World};
 
 /// Prelude for convenient imports
 pub mod prelude {
     pub use crate::{
-        ChildOf, Component, ContainedIn, Entity, Global, InstanceOf, OwnedBy, Pair, Plugin, Query,
-        QueryBuilder, QueryRow, Requires, World,
+        ChildOf, Component, ComponentReflect, ContainedIn, Entity, Global, InstanceOf, OwnedBy,
+        Pair, Plugin, Prefab, Query, QueryBuilder, QueryRow, Relation, Requires, World,
     };
 }
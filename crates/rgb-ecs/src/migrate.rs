@@ -0,0 +1,298 @@
+//! Migration - copying entities from one world into another.
+//!
+//! Component IDs are per world, so a component has to be registered as
+//! migratable (with its concrete type) before it can be copied across:
+//!
+//! ```ignore
+//! world.register_migratable::<Position>();
+//! world.register_migratable::<Target>();       // struct Target { entity: Entity }
+//! world.register_migratable_pair::<ChildOf>();
+//!
+//! // One entity, e.g. into a sandbox world
+//! let copy = world.clone_entity_into(&mut sandbox, player).unwrap();
+//!
+//! // A group, e.g. a dimension transfer; references between them are remapped
+//! let map = world.move_entities_into(&mut nether, [player, horse, saddle]);
+//!
+//! // Everything, e.g. a test fixture
+//! let map = world.merge_from(&fixture);
+//! ```
+//!
+//! Entity references are remapped using reflection metadata: every
+//! [`TypeTag::Entity`] field of a migrated component, and the target of every
+//! migrated pair, that points at an entity copied in the same call is
+//! rewritten to point at its copy. References to entities outside the call
+//! are left as they were.
+//!
+//! Components that were never registered as migratable are skipped, as is
+//! `Entity::WORLD`. Entity names are copied unless the name is already taken
+//! in the destination.
+
+use crate::{
+    World,
+    component::ComponentId,
+    entity::Entity,
+    prefab::{PrefabCloneFn, clone_component},
+    reflect::{ComponentReflect, TypeTag},
+    relation::{Pair, Relation},
+};
+
+/// Maps entities in the source world to their copies in the destination.
+pub type EntityMap = hashbrown::HashMap<Entity, Entity>;
+
+/// Copies one component of an entity into another world.
+pub type MigrateFn = fn(&World, Entity, &mut World, Entity, &EntityMap);
+
+fn migrate_component<T>(from: &World, entity: Entity, to: &mut World, copy: Entity, map: &EntityMap)
+where
+    T: 'static + Send + Sync + Clone + ComponentReflect,
+{
+    let Some(mut value) = from.get::<T>(entity) else {
+        return;
+    };
+    remap_fields(&mut value, map);
+    keep_prefab_default::<T>(from, to);
+    to.insert(copy, value);
+}
+
+fn migrate_pair<R>(from: &World, entity: Entity, to: &mut World, copy: Entity, map: &EntityMap)
+where
+    R: Relation + Clone,
+{
+    let Some(pair) = from.get::<Pair<R>>(entity) else {
+        return;
+    };
+    let target = map.get(&pair.target()).copied().unwrap_or(pair.target());
+    keep_prefab_default::<Pair<R>>(from, to);
+    // Not `insert_pair`: the target's components may not be copied yet
+    to.insert(copy, Pair::<R>::new(target));
+}
+
+/// Rewrite the `Entity` fields of a component that point into `map`.
+fn remap_fields<T: ComponentReflect>(value: &mut T, map: &EntityMap) {
+    let base = core::ptr::from_mut(value).cast::<u8>();
+    for field in T::FIELDS {
+        if field.type_tag != TypeTag::Entity {
+            continue;
+        }
+        // SAFETY: the `ComponentReflect` contract makes a field tagged `Entity`
+        // an `Entity` at `offset` within `T`. Packed structs may misalign it.
+        unsafe {
+            let ptr = base.add(field.offset).cast::<Entity>();
+            if let Some(&copy) = map.get(&ptr.read_unaligned()) {
+                ptr.write_unaligned(copy);
+            }
+        }
+    }
+}
+
+/// Carry a prefab default over, so copied prefabs still instantiate `T`.
+fn keep_prefab_default<T: 'static + Send + Sync + Clone>(from: &World, to: &mut World) {
+    let is_default = from
+        .component_id::<T>()
+        .is_some_and(|id| from.prefab_clones.contains_key(&id));
+    if is_default {
+        let id = to.register::<T>();
+        to.prefab_clones
+            .insert(id, clone_component::<T> as PrefabCloneFn);
+    }
+}
+
+impl World {
+    /// Allow `T` to be copied into other worlds, remapping its `Entity` fields.
+    pub fn register_migratable<T>(&mut self) -> ComponentId
+    where
+        T: 'static + Send + Sync + Clone + ComponentReflect,
+    {
+        let comp_id = self.register::<T>();
+        self.migrations
+            .insert(comp_id, migrate_component::<T> as MigrateFn);
+        comp_id
+    }
+
+    /// Allow `(R, target)` pairs to be copied into other worlds, remapping
+    /// the target.
+    pub fn register_migratable_pair<R: Relation + Clone>(&mut self) -> ComponentId {
+        let comp_id = self.register::<Pair<R>>();
+        self.migrations
+            .insert(comp_id, migrate_pair::<R> as MigrateFn);
+        comp_id
+    }
+
+    /// Copy an entity and its migratable components into `other`.
+    ///
+    /// Returns the copy, or `None` if `entity` is not alive (or is
+    /// `Entity::WORLD`).
+    #[track_caller]
+    pub fn clone_entity_into(&self, other: &mut World, entity: Entity) -> Option<Entity> {
+        self.clone_entities_into(other, [entity])
+            .get(&entity)
+            .copied()
+    }
+
+    /// Copy entities into `other`, remapping references between them.
+    ///
+    /// Dead entities and `Entity::WORLD` are skipped.
+    #[track_caller]
+    pub fn clone_entities_into(
+        &self,
+        other: &mut World,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> EntityMap {
+        let mut map = EntityMap::new();
+        for entity in entities {
+            if entity != Entity::WORLD && self.is_alive(entity) && !map.contains_key(&entity) {
+                map.insert(entity, other.spawn_empty());
+            }
+        }
+
+        for (&entity, &copy) in &map {
+            let Some(location) = self.entity_location(entity) else {
+                continue;
+            };
+            let Some(archetype) = self.archetypes().get(location.archetype_id) else {
+                continue;
+            };
+            for comp_id in archetype.components() {
+                if let Some(migrate) = self.migrations.get(comp_id) {
+                    migrate(self, entity, other, copy, &map);
+                }
+            }
+
+            if let Some(name) = self.entity_name(entity)
+                && other.lookup(name).is_none()
+            {
+                other.set_entity_name(copy, name);
+            }
+        }
+
+        map
+    }
+
+    /// Move an entity into `other`: copy it, then despawn the original.
+    ///
+    /// Returns the copy, or `None` if `entity` is not alive (or is
+    /// `Entity::WORLD`).
+    #[track_caller]
+    pub fn move_entity_into(&mut self, other: &mut World, entity: Entity) -> Option<Entity> {
+        self.move_entities_into(other, [entity])
+            .get(&entity)
+            .copied()
+    }
+
+    /// Move entities into `other`, remapping references between them.
+    #[track_caller]
+    pub fn move_entities_into(
+        &mut self,
+        other: &mut World,
+        entities: impl IntoIterator<Item = Entity>,
+    ) -> EntityMap {
+        let map = self.clone_entities_into(other, entities);
+        for &entity in map.keys() {
+            self.despawn(entity);
+        }
+        map
+    }
+
+    /// Copy every entity of `other` into this world, remapping references
+    /// between them.
+    #[track_caller]
+    pub fn merge_from(&mut self, other: &World) -> EntityMap {
+        let entities: Vec<Entity> = other.entities_iter().collect();
+        other.clone_entities_into(self, entities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChildOf, Component, Prefab};
+
+    #[derive(Component, Debug, Clone, Copy, PartialEq)]
+    struct Position {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Component, Debug, Clone, Copy, PartialEq)]
+    struct Follows {
+        leader: Entity,
+        distance: f32,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Scratch(u32);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register_migratable::<Position>();
+        world.register_migratable::<Follows>();
+        world.register_migratable::<Prefab>();
+        world.register_migratable_pair::<ChildOf>();
+        world
+    }
+
+    #[test]
+    fn test_clone_entity_into() {
+        let mut world = world();
+        let entity = world.spawn(Position { x: 1.0, y: 2.0 });
+        world.insert(entity, Scratch(7));
+        world.set_entity_name(entity, b"spawn");
+
+        let mut other = World::new();
+        let copy = world.clone_entity_into(&mut other, entity).unwrap();
+
+        assert_eq!(
+            other.get::<Position>(copy),
+            Some(Position { x: 1.0, y: 2.0 })
+        );
+        assert!(!other.has::<Scratch>(copy));
+        assert_eq!(other.lookup(b"spawn"), Some(copy));
+        assert!(world.is_alive(entity));
+        assert_eq!(world.clone_entity_into(&mut other, Entity::WORLD), None);
+    }
+
+    #[test]
+    fn test_references_are_remapped() {
+        let mut world = world();
+        let outsider = world.spawn(Position { x: 0.0, y: 0.0 });
+        let leader = world.spawn(Position { x: 1.0, y: 1.0 });
+        let follower = world.spawn(Follows {
+            leader,
+            distance: 2.0,
+        });
+        world.set_parent(follower, leader);
+        let stray = world.spawn(Follows {
+            leader: outsider,
+            distance: 3.0,
+        });
+
+        let mut other = World::new();
+        other.spawn(Position { x: 9.0, y: 9.0 });
+        let map = world.move_entities_into(&mut other, [leader, follower, stray]);
+
+        let (leader, follower, stray) = (map[&leader], map[&follower], map[&stray]);
+        assert_eq!(other.get::<Follows>(follower).unwrap().leader, leader);
+        assert_eq!(other.parent(follower), Some(leader));
+        assert_eq!(other.get::<Follows>(stray).unwrap().leader, outsider);
+        assert_eq!(world.entity_count(), 2);
+    }
+
+    #[test]
+    fn test_merge_from_keeps_prefabs() {
+        let mut fixture = world();
+        let zombie = fixture.spawn_prefab();
+        fixture.set_prefab(zombie, Position { x: 5.0, y: 0.0 });
+
+        let mut world = World::new();
+        let map = world.merge_from(&fixture);
+        let zombie = map[&zombie];
+
+        assert!(world.is_prefab(zombie));
+        let instance = world.spawn_from(zombie).unwrap();
+        assert_eq!(
+            world.get::<Position>(instance),
+            Some(Position { x: 5.0, y: 0.0 })
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Prefabs - template entities that other entities are instantiated from.
+//!
+//! A prefab is an ordinary entity marked with [`Prefab`]. Its components are
+//! the defaults for every instance:
+//!
+//! ```ignore
+//! let zombie = world.spawn_prefab();
+//! world.set_prefab(zombie, Health { current: 20, max: 20 });
+//! world.set_prefab(zombie, Speed(0.23));
+//!
+//! let z = world.spawn_from(zombie).unwrap();
+//! assert_eq!(world.prefab_of(z), Some(zombie));
+//!
+//! // Overrides only affect the instance
+//! world.update(z, Speed(0.5));
+//!
+//! // ...and can be dropped again to go back to the prefab value
+//! world.reset_override::<Speed>(z);
+//! ```
+//!
+//! Instances get their own copy of each prefab component plus an
+//! `(InstanceOf, prefab)` pair. Changing the prefab afterwards does not touch
+//! existing instances.
+//!
+//! Prefab entities are skipped by queries unless the query names [`Prefab`]
+//! explicitly, so templates never get picked up by gameplay systems.
+
+use crate::{
+    World,
+    component::ComponentId,
+    entity::Entity,
+    reflect::{ComponentReflect, FieldInfo},
+    relation::{InstanceOf, Pair},
+};
+
+/// Marker component for prefab (template) entities.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Prefab;
+
+// SAFETY: reports no fields.
+unsafe impl ComponentReflect for Prefab {
+    const FIELDS: &'static [FieldInfo] = &[];
+}
+
+/// Copies one component from a prefab to an instance.
+pub type PrefabCloneFn = fn(&mut World, Entity, Entity);
+
+pub fn clone_component<T: 'static + Send + Sync + Clone>(
+    world: &mut World,
+    from: Entity,
+    to: Entity,
+) {
+    if let Some(value) = world.get::<T>(from) {
+        world.insert(to, value);
+    }
+}
+
+/// The `Prefab` component id, unless one of `terms` already mentions it.
+///
+/// Queries use this to hide prefab entities by default.
+pub fn implicit_exclusion(
+    world: &World,
+    mut terms: impl Iterator<Item = ComponentId>,
+) -> Option<ComponentId> {
+    let prefab = world.component_id::<Prefab>()?;
+    (!terms.any(|id| id == prefab)).then_some(prefab)
+}
+
+impl World {
+    /// Spawn a new, empty prefab entity.
+    #[track_caller]
+    pub fn spawn_prefab(&mut self) -> Entity {
+        let prefab = self.spawn_empty();
+        self.insert(prefab, Prefab);
+        prefab
+    }
+
+    /// Check if an entity is a prefab.
+    #[must_use]
+    pub fn is_prefab(&self, entity: Entity) -> bool {
+        self.has::<Prefab>(entity)
+    }
+
+    /// Set a default component on a prefab.
+    ///
+    /// Components must be added through this method (rather than `insert`)
+    /// to be copied onto instances, since copying needs `T: Clone`.
+    pub fn set_prefab<T: 'static + Send + Sync + Clone>(
+        &mut self,
+        prefab: Entity,
+        component: T,
+    ) -> bool {
+        debug_assert!(self.is_prefab(prefab), "{prefab:?} is not a prefab");
+        let comp_id = self.register::<T>();
+        self.prefab_clones
+            .insert(comp_id, clone_component::<T> as PrefabCloneFn);
+        self.insert(prefab, component)
+    }
+
+    /// Instantiate a prefab.
+    ///
+    /// The new entity gets a copy of every component set with
+    /// [`set_prefab`](Self::set_prefab) and an `(InstanceOf, prefab)` pair.
+    /// Returns `None` if `prefab` is not a live prefab entity.
+    #[track_caller]
+    pub fn spawn_from(&mut self, prefab: Entity) -> Option<Entity> {
+        if !self.is_prefab(prefab) {
+            return None;
+        }
+
+        let location = self.entity_location(prefab)?;
+        let clones: Vec<PrefabCloneFn> = self
+            .archetypes()
+            .get(location.archetype_id)?
+            .components()
+            .iter()
+            .filter_map(|id| self.prefab_clones.get(id).copied())
+            .collect();
+
+        let instance = self.spawn_empty();
+        for clone in clones {
+            clone(self, prefab, instance);
+        }
+        self.insert_pair::<InstanceOf>(instance, prefab);
+
+        Some(instance)
+    }
+
+    /// Get the prefab an entity was instantiated from.
+    #[must_use]
+    pub fn prefab_of(&self, instance: Entity) -> Option<Entity> {
+        self.get_pair_target::<InstanceOf>(instance)
+    }
+
+    /// Drop an instance's override of `T`, restoring the prefab's value.
+    ///
+    /// If the prefab has no `T`, the instance's `T` is removed.
+    /// Returns `false` if `instance` is not a prefab instance.
+    pub fn reset_override<T: 'static + Send + Sync + Clone>(&mut self, instance: Entity) -> bool {
+        let Some(prefab) = self.prefab_of(instance) else {
+            return false;
+        };
+        match self.get::<T>(prefab) {
+            Some(value) => self.insert(instance, value),
+            None => {
+                self.remove::<T>(instance);
+                true
+            }
+        }
+    }
+
+    /// Check whether an instance's `T` differs from its prefab's value.
+    #[must_use]
+    pub fn is_overridden<T: 'static + Send + Sync + Clone + PartialEq>(
+        &self,
+        instance: Entity,
+    ) -> bool {
+        let Some(prefab) = self.prefab_of(instance) else {
+            return false;
+        };
+        self.get::<T>(instance) != self.get::<T>(prefab)
+    }
+
+    /// Iterate over all live instances of a prefab.
+    pub fn instances_of(&self, prefab: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.query_single::<Pair<InstanceOf>>()
+            .filter(move |(_, pair)| pair.target() == prefab)
+            .map(|(entity, _)| entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Speed(f32);
+
+    fn zombie(world: &mut World) -> Entity {
+        let zombie = world.spawn_prefab();
+        world.set_prefab(
+            zombie,
+            Health {
+                current: 20,
+                max: 20,
+            },
+        );
+        world.set_prefab(zombie, Speed(0.23));
+        zombie
+    }
+
+    #[test]
+    fn test_spawn_from_copies_components() {
+        let mut world = World::new();
+        let zombie = zombie(&mut world);
+
+        let z = world.spawn_from(zombie).unwrap();
+        assert_eq!(world.get::<Speed>(z), Some(Speed(0.23)));
+        assert_eq!(world.get::<Health>(z).unwrap().max, 20);
+        assert_eq!(world.prefab_of(z), Some(zombie));
+        assert!(!world.is_prefab(z));
+    }
+
+    #[test]
+    fn test_override_shadows_prefab() {
+        let mut world = World::new();
+        let zombie = zombie(&mut world);
+        let a = world.spawn_from(zombie).unwrap();
+        let b = world.spawn_from(zombie).unwrap();
+
+        world.update(a, Speed(0.5));
+        assert!(world.is_overridden::<Speed>(a));
+        assert!(!world.is_overridden::<Speed>(b));
+        assert_eq!(world.get::<Speed>(b), Some(Speed(0.23)));
+        assert_eq!(world.get::<Speed>(zombie), Some(Speed(0.23)));
+
+        assert!(world.reset_override::<Speed>(a));
+        assert_eq!(world.get::<Speed>(a), Some(Speed(0.23)));
+    }
+
+    #[test]
+    fn test_spawn_from_non_prefab() {
+        let mut world = World::new();
+        let entity = world.spawn(Speed(1.0));
+        assert_eq!(world.spawn_from(entity), None);
+    }
+
+    #[test]
+    fn test_queries_skip_prefabs() {
+        let mut world = World::new();
+        let zombie = zombie(&mut world);
+        let z = world.spawn_from(zombie).unwrap();
+
+        let speeds: Vec<_> = world.query_single::<Speed>().map(|(e, _)| e).collect();
+        assert_eq!(speeds, [z]);
+
+        let query = world.query().with::<Speed>().build();
+        assert_eq!(query.iter(&world).count(), 1);
+
+        let prefabs = world.query().filter::<Prefab>().build();
+        assert_eq!(prefabs.iter(&world).next().unwrap().entity(), zombie);
+
+        assert_eq!(world.instances_of(zombie).collect::<Vec<_>>(), [z]);
+    }
+}
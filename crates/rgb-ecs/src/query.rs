@@ -33,6 +33,24 @@
 //! - `.without::<T>()` - Entity must NOT have component T
 //! - `.filter::<T>()` - Entity must have T, but don't fetch data
 //!
+//! Prefab entities (see [`Prefab`](crate::Prefab)) are skipped unless the
+//! query mentions `Prefab` in one of its terms.
+//!
+//! # Chunk Iteration
+//!
+//! Hot systems can skip the per-row clones and work on whole component
+//! columns, one archetype at a time. Column slices are aligned for their type
+//! and parallel to the chunk's entities, so they vectorize:
+//!
+//! ```ignore
+//! for mut chunk in query.iter_chunks_mut(&mut world) {
+//!     let (positions, velocities) = chunk.columns_mut::<Position, Velocity>().unwrap();
+//!     for (pos, vel) in positions.iter_mut().zip(velocities.iter()) {
+//!         pos.x += vel.x;
+//!     }
+//! }
+//! ```
+//!
 //! # Example with Filters
 //!
 //! ```ignore
@@ -47,7 +65,7 @@
 use crate::{
     World,
     archetype::{Archetype, ArchetypeId},
-    component::ComponentId,
+    component::{ComponentId, ComponentRegistry},
     entity::Entity,
 };
 
@@ -160,12 +178,21 @@ impl<'w> QueryBuilder<'w> {
     /// Pre-computes matching archetypes for efficient iteration.
     #[must_use]
     pub fn build(self) -> Query {
+        let prefab = crate::prefab::implicit_exclusion(
+            self.world,
+            self.terms.iter().map(|term| term.component_id),
+        );
+
         // Pre-compute matching archetypes
         let matching_archetypes: Vec<ArchetypeId> = self
             .world
             .archetypes()
             .iter()
             .filter(|arch| {
+                if prefab.is_some_and(|id| arch.contains(id)) {
+                    return false;
+                }
+
                 for term in &self.terms {
                     let has_component = arch.contains(term.component_id);
 
@@ -236,6 +263,40 @@ impl Query {
             f(row);
         }
     }
+
+    /// Iterate over matching archetypes as chunks of whole columns.
+    ///
+    /// Empty archetypes are skipped.
+    pub fn iter_chunks<'w>(&self, world: &'w World) -> impl Iterator<Item = QueryChunk<'w>> {
+        self.matching_archetypes
+            .iter()
+            .filter_map(|&arch_id| world.archetypes().get(arch_id))
+            .filter(|archetype| !archetype.is_empty())
+            .map(move |archetype| QueryChunk { world, archetype })
+    }
+
+    /// Iterate over matching archetypes as chunks of whole, mutable columns.
+    ///
+    /// Empty archetypes are skipped.
+    pub fn iter_chunks_mut<'w>(
+        &self,
+        world: &'w mut World,
+    ) -> impl Iterator<Item = QueryChunkMut<'w>> {
+        let (archetypes, components) = world.archetypes_mut_with_components();
+        archetypes
+            .iter_mut()
+            .filter(|archetype| {
+                !archetype.is_empty()
+                    && self
+                        .matching_archetypes
+                        .binary_search(&archetype.id())
+                        .is_ok()
+            })
+            .map(move |archetype| QueryChunkMut {
+                components,
+                archetype,
+            })
+    }
 }
 
 impl core::fmt::Debug for Query {
@@ -396,6 +457,135 @@ impl core::fmt::Debug for QueryRow<'_> {
     }
 }
 
+// ============================================================================
+// QueryChunk - Column Access
+// ============================================================================
+
+/// One matching archetype: its entities and their component columns.
+pub struct QueryChunk<'w> {
+    world: &'w World,
+    archetype: &'w Archetype,
+}
+
+impl<'w> QueryChunk<'w> {
+    /// Get the entities in this chunk, in column order.
+    #[must_use]
+    pub fn entities(&self) -> &'w [Entity] {
+        self.archetype.entities()
+    }
+
+    /// Get the number of entities in this chunk.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.archetype.len()
+    }
+
+    /// Check if the chunk is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.archetype.is_empty()
+    }
+
+    /// Get a component column, or `None` if the archetype doesn't have it.
+    #[must_use]
+    pub fn column<T: 'static + Send + Sync>(&self) -> Option<&'w [T]> {
+        let comp_id = self.world.component_id::<T>()?;
+        self.archetype
+            .column(comp_id)
+            .map(|column| column.as_slice())
+    }
+}
+
+impl core::fmt::Debug for QueryChunk<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QueryChunk")
+            .field("archetype", &self.archetype.id())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// One matching archetype with mutable access to its component columns.
+///
+/// Borrowing rules are enforced per column: one column can be borrowed
+/// mutably at a time through [`column_mut`](Self::column_mut), or two
+/// distinct ones through [`columns_mut`](Self::columns_mut).
+pub struct QueryChunkMut<'w> {
+    components: &'w ComponentRegistry,
+    archetype: &'w mut Archetype,
+}
+
+impl QueryChunkMut<'_> {
+    /// Get the entities in this chunk, in column order.
+    #[must_use]
+    pub fn entities(&self) -> &[Entity] {
+        self.archetype.entities()
+    }
+
+    /// Get the number of entities in this chunk.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.archetype.len()
+    }
+
+    /// Check if the chunk is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.archetype.is_empty()
+    }
+
+    /// Get a component column, or `None` if the archetype doesn't have it.
+    #[must_use]
+    pub fn column<T: 'static + Send + Sync>(&self) -> Option<&[T]> {
+        let comp_id = self.components.get_id::<T>()?;
+        self.archetype
+            .column(comp_id)
+            .map(|column| column.as_slice())
+    }
+
+    /// Get a mutable component column, or `None` if the archetype doesn't
+    /// have it.
+    #[must_use]
+    pub fn column_mut<T: 'static + Send + Sync>(&mut self) -> Option<&mut [T]> {
+        let comp_id = self.components.get_id::<T>()?;
+        self.archetype
+            .column_mut(comp_id)
+            .map(|column| column.as_mut_slice())
+    }
+
+    /// Get two distinct mutable component columns at once, or `None` if the
+    /// archetype lacks either.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` and `B` are the same component, since the slices would
+    /// alias.
+    #[must_use]
+    pub fn columns_mut<A, B>(&mut self) -> Option<(&mut [A], &mut [B])>
+    where
+        A: 'static + Send + Sync,
+        B: 'static + Send + Sync,
+    {
+        let a = self
+            .archetype
+            .column_index(self.components.get_id::<A>()?)?;
+        let b = self
+            .archetype
+            .column_index(self.components.get_id::<B>()?)?;
+        let (a, b) = self.archetype.column_pair_mut(a, b);
+        Some((a.as_mut_slice(), b.as_mut_slice()))
+    }
+}
+
+impl core::fmt::Debug for QueryChunkMut<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QueryChunkMut")
+            .field("archetype", &self.archetype.id())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -572,6 +762,75 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[test]
+    fn test_iter_chunks() {
+        let mut world = World::new();
+
+        let e1 = world.spawn(Position { x: 1.0, y: 2.0 });
+        let e2 = world.spawn(Position { x: 3.0, y: 4.0 });
+        world.insert(e2, Enemy);
+
+        let query = world.query().with::<Position>().build();
+
+        let mut seen = Vec::new();
+        for chunk in query.iter_chunks(&world) {
+            let positions = chunk.column::<Position>().unwrap();
+            assert_eq!(positions.len(), chunk.len());
+            assert!(chunk.column::<Velocity>().is_none());
+            seen.extend(
+                chunk
+                    .entities()
+                    .iter()
+                    .copied()
+                    .zip(positions.iter().copied()),
+            );
+        }
+
+        seen.sort_by_key(|(entity, _)| entity.id());
+        assert_eq!(
+            seen,
+            vec![
+                (e1, Position { x: 1.0, y: 2.0 }),
+                (e2, Position { x: 3.0, y: 4.0 })
+            ]
+        );
+    }
+
+    #[test]
+    fn test_iter_chunks_mut() {
+        let mut world = World::new();
+
+        let e1 = world.spawn(Position { x: 1.0, y: 2.0 });
+        world.insert(e1, Velocity { x: 0.5, y: -1.0 });
+        let e2 = world.spawn(Position { x: 3.0, y: 4.0 });
+
+        let query = world.query().with::<Position>().with::<Velocity>().build();
+
+        for mut chunk in query.iter_chunks_mut(&mut world) {
+            let (positions, velocities) = chunk.columns_mut::<Position, Velocity>().unwrap();
+            for (pos, vel) in positions.iter_mut().zip(velocities.iter()) {
+                pos.x += vel.x;
+                pos.y += vel.y;
+            }
+        }
+
+        assert_eq!(world.get::<Position>(e1), Some(Position { x: 1.5, y: 1.0 }));
+        assert_eq!(world.get::<Position>(e2), Some(Position { x: 3.0, y: 4.0 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Column borrowed mutably twice")]
+    fn test_columns_mut_rejects_aliasing() {
+        let mut world = World::new();
+        world.spawn(Position { x: 1.0, y: 2.0 });
+
+        let query = world.query().with::<Position>().build();
+
+        for mut chunk in query.iter_chunks_mut(&mut world) {
+            let _ = chunk.columns_mut::<Position, Position>();
+        }
+    }
+
     #[test]
     fn test_query_row_has() {
         let mut world = World::new();
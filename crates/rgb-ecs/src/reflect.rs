@@ -0,0 +1,334 @@
+//! Compile-time reflection metadata for components.
+//!
+//! `#[derive(Component)]` emits a [`ComponentReflect`] impl describing each
+//! field of a struct component: its name, a coarse [`TypeTag`], the source
+//! type as written, and its byte offset within the component.
+//!
+//! This lets the introspection registry, dashboard schema generation, and
+//! dynamic (pointer + offset) field access share one source of truth instead
+//! of each going through serde.
+//!
+//! ```ignore
+//! use rgb_ecs::{Component, ComponentReflect, TypeTag};
+//!
+//! #[derive(Component, Clone)]
+//! struct Position { x: f64, y: f64, z: f64 }
+//!
+//! let y = Position::field("y").unwrap();
+//! assert_eq!(y.type_tag, TypeTag::F64);
+//! assert_eq!(y.offset, core::mem::offset_of!(Position, y));
+//! ```
+//!
+//! Enums and `#[component(opaque)]` components report no fields: enum variant
+//! layouts are not stable, and opaque internals are deliberately hidden.
+//!
+//! A field's tag comes from its type, not from how the type is spelled: the
+//! derive names the field type in [`TagOf`], so a user type that happens to be
+//! called `Entity` or `f64` is tagged [`TypeTag::Other`], and a type alias of
+//! `u32` is tagged [`TypeTag::U32`].
+
+use core::marker::PhantomData;
+
+/// Coarse classification of a field's type.
+///
+/// Primitives map to their own tag. Everything else is classified by shape so
+/// consumers can decide how to render or edit it without knowing the type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TypeTag {
+    Bool,
+    Char,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+    String,
+    /// An [`Entity`](crate::Entity) reference.
+    Entity,
+    /// A fixed-size array `[T; N]`.
+    Array,
+    /// A tuple `(A, B, ..)`.
+    Tuple,
+    /// An `Option<T>`.
+    Option,
+    /// Any other type (nested components, user enums, ...).
+    Other,
+}
+
+impl TypeTag {
+    /// Whether this tag is a numeric primitive.
+    #[must_use]
+    pub const fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Self::I8
+                | Self::I16
+                | Self::I32
+                | Self::I64
+                | Self::I128
+                | Self::Isize
+                | Self::U8
+                | Self::U16
+                | Self::U32
+                | Self::U64
+                | Self::U128
+                | Self::Usize
+                | Self::F32
+                | Self::F64
+        )
+    }
+}
+
+/// Reflection metadata for a single component field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// Field name. Tuple struct fields use their index (`"0"`, `"1"`, ...).
+    pub name: &'static str,
+    /// Coarse type classification.
+    pub type_tag: TypeTag,
+    /// The field type as written in source (e.g. `"[u8 ; 16]"`).
+    pub type_name: &'static str,
+    /// Byte offset of the field from the start of the component.
+    pub offset: usize,
+}
+
+/// Reflection metadata for a component type.
+///
+/// Implemented by `#[derive(Component)]`; there is no need to implement it by hand.
+///
+/// # Safety
+///
+/// [`read_field`](Self::read_field) and entity remapping read and write
+/// through [`FieldInfo::offset`], so every entry of [`FIELDS`](Self::FIELDS)
+/// must describe a field of `Self`:
+///
+/// - `offset` is the field's `offset_of!` within `Self`.
+/// - `type_tag` is a primitive, [`TypeTag::String`] or [`TypeTag::Entity`]
+///   only if the field has exactly that type, which [`TagOf`] guarantees.
+pub unsafe trait ComponentReflect {
+    /// All reflected fields, in declaration order.
+    const FIELDS: &'static [FieldInfo];
+
+    /// Look up a field by name.
+    #[must_use]
+    fn field(name: &str) -> Option<&'static FieldInfo> {
+        Self::FIELDS.iter().find(|field| field.name == name)
+    }
+
+    /// Read a field of type `F` from a component by name.
+    ///
+    /// Returns `None` if the field doesn't exist or its type isn't `F`.
+    /// Only primitive fields with a tag matching `F` are readable this way.
+    fn read_field<F: ReflectPrimitive>(&self, name: &str) -> Option<F>
+    where
+        Self: Sized,
+    {
+        let field = Self::field(name)?;
+        if field.type_tag != F::TAG {
+            return None;
+        }
+        let base = core::ptr::from_ref(self).cast::<u8>();
+        // SAFETY: the trait's contract makes `offset` a field of `Self` whose
+        // type is `F`, as the tags match. Packed structs may misalign it.
+        Some(unsafe { base.add(field.offset).cast::<F>().read_unaligned() })
+    }
+}
+
+/// Primitive types that can be read through [`ComponentReflect::read_field`].
+pub trait ReflectPrimitive: Copy + 'static {
+    /// Tag that a field must carry to be read as `Self`.
+    const TAG: TypeTag;
+}
+
+/// The [`TypeTag`] of `T`, as `TagOf::<T>::TAG`. Used by the derive macro.
+///
+/// Tagged types have an inherent `TAG`, which path resolution picks over
+/// [`TagFallback::TAG`]; every other type, generic parameters included, gets
+/// the fallback's [`TypeTag::Other`]. The fallback must be in scope.
+#[doc(hidden)]
+pub struct TagOf<T: ?Sized>(PhantomData<T>);
+
+/// Tags every type without its own [`TagOf`] impl as [`TypeTag::Other`].
+#[doc(hidden)]
+pub trait TagFallback {
+    const TAG: TypeTag = TypeTag::Other;
+}
+
+impl<T: ?Sized> TagFallback for TagOf<T> {}
+
+macro_rules! impl_tag_of {
+    ($($ty:ty => $tag:ident),* $(,)?) => {
+        $(
+            impl TagOf<$ty> {
+                pub const TAG: TypeTag = TypeTag::$tag;
+            }
+        )*
+    };
+}
+
+impl_tag_of! {
+    String => String,
+    crate::Entity => Entity,
+}
+
+impl<T> TagOf<Option<T>> {
+    pub const TAG: TypeTag = TypeTag::Option;
+}
+
+impl<T, const N: usize> TagOf<[T; N]> {
+    pub const TAG: TypeTag = TypeTag::Array;
+}
+
+macro_rules! impl_tag_of_tuple {
+    ($(($($name:ident),+)),* $(,)?) => {
+        $(
+            impl<$($name),+> TagOf<($($name,)+)> {
+                pub const TAG: TypeTag = TypeTag::Tuple;
+            }
+        )*
+    };
+}
+
+impl_tag_of_tuple! {
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H),
+    (A, B, C, D, E, F, G, H, I),
+    (A, B, C, D, E, F, G, H, I, J),
+    (A, B, C, D, E, F, G, H, I, J, K),
+    (A, B, C, D, E, F, G, H, I, J, K, L),
+}
+
+macro_rules! impl_reflect_primitive {
+    ($($ty:ty => $tag:ident),* $(,)?) => {
+        $(
+            impl ReflectPrimitive for $ty {
+                const TAG: TypeTag = TypeTag::$tag;
+            }
+
+            impl_tag_of!($ty => $tag);
+        )*
+    };
+}
+
+impl_reflect_primitive! {
+    bool => Bool,
+    char => Char,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    isize => Isize,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    usize => Usize,
+    f32 => F32,
+    f64 => F64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Component, Entity};
+
+    #[derive(Component, Clone)]
+    struct Position {
+        x: f64,
+        y: f64,
+        z: f64,
+    }
+
+    #[derive(Component, Clone)]
+    struct Owner(Entity, u8);
+
+    mod lookalike {
+        #[derive(Clone, Copy)]
+        pub struct Entity(pub u64);
+    }
+
+    type Ticks = u32;
+
+    #[derive(Component, Clone)]
+    struct Named {
+        entity: lookalike::Entity,
+        ticks: Ticks,
+    }
+
+    #[derive(Component, Clone, Copy)]
+    #[repr(C, packed)]
+    struct Packed {
+        flag: u8,
+        value: u64,
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        let names: Vec<_> = Position::FIELDS.iter().map(|f| f.name).collect();
+        assert_eq!(names, ["x", "y", "z"]);
+
+        let y = Position::field("y").unwrap();
+        assert_eq!(y.type_tag, TypeTag::F64);
+        assert_eq!(y.type_name, "f64");
+        assert_eq!(y.offset, core::mem::offset_of!(Position, y));
+    }
+
+    #[test]
+    fn test_tuple_fields() {
+        let owner = Owner::field("0").unwrap();
+        assert_eq!(owner.type_tag, TypeTag::Entity);
+        assert_eq!(Owner::field("1").unwrap().type_tag, TypeTag::U8);
+    }
+
+    #[test]
+    fn test_read_field() {
+        let pos = Position {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        assert_eq!(pos.read_field::<f64>("z"), Some(3.0));
+        assert_eq!(pos.read_field::<f32>("z"), None);
+        assert_eq!(pos.read_field::<f64>("w"), None);
+    }
+
+    #[test]
+    fn test_tags_follow_the_type_not_its_name() {
+        assert_eq!(Named::field("entity").unwrap().type_tag, TypeTag::Other);
+        assert_eq!(Named::field("ticks").unwrap().type_tag, TypeTag::U32);
+
+        let named = Named {
+            entity: lookalike::Entity(7),
+            ticks: 20,
+        };
+        assert_eq!(named.read_field::<u32>("ticks"), Some(20));
+        assert_eq!(named.entity.0, 7);
+    }
+
+    #[test]
+    fn test_read_packed_field() {
+        let packed = Packed {
+            flag: 1,
+            value: u64::MAX,
+        };
+        assert_eq!(packed.read_field::<u8>("flag"), Some(1));
+        assert_eq!(packed.read_field::<u64>("value"), Some(u64::MAX));
+    }
+}
@@ -14,12 +14,86 @@
 //! // Query all children of a specific parent
 //! world.query::<(ChildOf, parent_entity)>();
 //! ```
+//!
+//! # Exclusivity and Cardinality
+//!
+//! A pair is stored as a `Pair<R>` component, so every relation is exclusive:
+//! an entity has at most one `(R, *)` pair, and adding another replaces it
+//! (moving an entity to a new dimension drops the old `(InDimension, old)`).
+//!
+//! How many entities may point at the same target is the relation's
+//! [`Cardinality`]. By default any number may (many children, one parent);
+//! a [`Cardinality::OneToOne`] relation rejects a second source:
+//!
+//! ```ignore
+//! #[derive(Component, Clone, Default)]
+//! #[component(relation, one_to_one)]
+//! struct Rides;
+//!
+//! world.insert_pair::<Rides>(player, horse);     // true
+//! world.insert_pair::<Rides>(other, horse);      // false, horse is taken
+//! ```
 
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 
 use crate::entity::Entity;
+use crate::world::World;
+
+/// A type usable as the relation half of a [`Pair`].
+///
+/// Implemented by `#[derive(Component)]` with `#[component(relation)]`, which
+/// also accepts a target constraint:
+///
+/// ```ignore
+/// #[derive(Component, Clone, Default)]
+/// #[component(relation, target = Player)]
+/// struct Follows;
+///
+/// // Equivalent to Pair::<Follows>::new(player)
+/// let pair = Follows::pair(player);
+///
+/// // In debug builds, panics unless `player` has a `Player` component
+/// world.insert_pair::<Follows>(follower, player);
+/// ```
+pub trait Relation: Send + Sync + 'static {
+    /// Name of the component the target must have, if constrained.
+    const TARGET: Option<&'static str> = None;
+
+    /// How many entities may point at the same target.
+    const CARDINALITY: Cardinality = Cardinality::ManyToOne;
+
+    /// Check whether `target` satisfies this relation's target constraint.
+    ///
+    /// Unconstrained relations accept any target.
+    fn is_valid_target(world: &World, target: Entity) -> bool {
+        let _ = (world, target);
+        true
+    }
+
+    /// Build a pair of this relation pointing at `target`.
+    #[must_use]
+    fn pair(target: Entity) -> Pair<Self>
+    where
+        Self: Sized,
+    {
+        Pair::new(target)
+    }
+}
+
+/// How many sources a relation allows per target.
+///
+/// Each source always has at most one target per relation (see the
+/// [module docs](self)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cardinality {
+    /// Any number of sources per target, e.g. `ChildOf`.
+    #[default]
+    ManyToOne,
+    /// At most one source per target, e.g. a rider per mount.
+    OneToOne,
+}
 
 /// A pair combines a relation type with a target entity.
 ///
@@ -124,6 +198,12 @@ pub struct Requires;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct InstanceOf;
 
+impl Relation for ChildOf {}
+impl Relation for OwnedBy {}
+impl Relation for ContainedIn {}
+impl Relation for Requires {}
+impl Relation for InstanceOf {}
+
 // ============================================================================
 // Pair ID - packed representation for storage
 // ============================================================================
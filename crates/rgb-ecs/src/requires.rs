@@ -0,0 +1,189 @@
+//! Component requirements - components that can't be inserted without others.
+//!
+//! A requirement is declared per component type, either by hand or with the
+//! derive attribute:
+//!
+//! ```ignore
+//! #[derive(Component, Clone)]
+//! #[component(requires = Position, requires = Velocity)]
+//! struct Projectile { damage: f32 }
+//!
+//! #[derive(Component, Clone)]
+//! #[component(requires = Player, on_missing = reject)]
+//! struct Gamemode(u8);
+//!
+//! world.register_requirements::<Projectile>();
+//! world.register_requirements::<Gamemode>();
+//! // Equivalent to:
+//! world.require_or_default::<Projectile, Position>();
+//! world.require_or_default::<Projectile, Velocity>();
+//! world.require::<Gamemode, Player>();
+//! ```
+//!
+//! When a component is inserted on an entity lacking something it requires,
+//! the missing component is either inserted with its `Default` value
+//! (`require_or_default`, the derive's default) or the insert is rejected
+//! (`require`, `on_missing = reject`): `insert` returns `false` and
+//! `try_insert` returns [`InsertError::MissingRequirement`]. Replacing a
+//! component the entity already has is never checked.
+//!
+//! Requirements must not form cycles.
+//!
+//! [`InsertError::MissingRequirement`]: crate::InsertError::MissingRequirement
+
+use crate::{World, component::ComponentId, entity::Entity, error::InsertError};
+
+/// Inserts the default value of a required component.
+type InsertDefaultFn = fn(&mut World, Entity) -> bool;
+
+/// One component that another can't be inserted without.
+#[derive(Clone, Copy)]
+pub struct Requirement {
+    /// The required component.
+    component: ComponentId,
+    /// Its type name, for errors.
+    name: &'static str,
+    /// How to fill it in, or `None` to reject the insert.
+    insert_default: Option<InsertDefaultFn>,
+}
+
+fn insert_default<R: 'static + Send + Sync + Default>(world: &mut World, entity: Entity) -> bool {
+    world.insert(entity, R::default())
+}
+
+/// Components whose requirements are declared with `#[component(requires = T)]`.
+///
+/// Implemented by `#[derive(Component)]`; register them with
+/// [`World::register_requirements`].
+pub trait ComponentRequires {
+    /// Declare this component's requirements on `world`.
+    fn register_requirements(world: &mut World);
+}
+
+impl World {
+    /// Reject inserting `T` on entities that lack `R`.
+    pub fn require<T, R>(&mut self)
+    where
+        T: 'static + Send + Sync,
+        R: 'static + Send + Sync,
+    {
+        self.add_requirement::<T, R>(None);
+    }
+
+    /// Insert `R::default()` when `T` is inserted on an entity that lacks `R`.
+    pub fn require_or_default<T, R>(&mut self)
+    where
+        T: 'static + Send + Sync,
+        R: 'static + Send + Sync + Default,
+    {
+        self.add_requirement::<T, R>(Some(insert_default::<R>));
+    }
+
+    /// Register the requirements declared on a derived component.
+    pub fn register_requirements<T: ComponentRequires>(&mut self) {
+        T::register_requirements(self);
+    }
+
+    fn add_requirement<T, R>(&mut self, insert_default: Option<InsertDefaultFn>)
+    where
+        T: 'static + Send + Sync,
+        R: 'static + Send + Sync,
+    {
+        let comp_id = self.register::<T>();
+        let requirement = Requirement {
+            component: self.register::<R>(),
+            name: std::any::type_name::<R>(),
+            insert_default,
+        };
+        let requirements = self.requirements.entry(comp_id).or_default();
+        requirements.retain(|existing| existing.component != requirement.component);
+        requirements.push(requirement);
+    }
+
+    /// Make sure an entity has everything `comp_id` requires, inserting
+    /// defaults where allowed.
+    pub(crate) fn ensure_requirements(
+        &mut self,
+        entity: Entity,
+        comp_id: ComponentId,
+    ) -> Result<(), InsertError> {
+        let Some(requirements) = self.requirements.get(&comp_id).cloned() else {
+            return Ok(());
+        };
+
+        for requirement in requirements {
+            if self.has_by_id(entity, requirement.component) {
+                continue;
+            }
+            let inserted = requirement
+                .insert_default
+                .is_some_and(|insert_default| insert_default(self, entity));
+            if !inserted {
+                return Err(InsertError::MissingRequirement {
+                    entity,
+                    component: self
+                        .components()
+                        .get_info(comp_id)
+                        .map_or("<unknown>", |info| info.name()),
+                    requires: requirement.name,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    struct Position(f32);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    struct Velocity(f32);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Projectile;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Player;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Gamemode(u8);
+
+    #[test]
+    fn test_missing_requirement_inserts_default() {
+        let mut world = World::new();
+        world.require_or_default::<Projectile, Velocity>();
+        world.require_or_default::<Velocity, Position>();
+
+        let entity = world.spawn_empty();
+        assert!(world.insert(entity, Projectile));
+        assert_eq!(world.get::<Velocity>(entity), Some(Velocity(0.0)));
+        assert_eq!(world.get::<Position>(entity), Some(Position(0.0)));
+
+        let moving = world.spawn(Velocity(2.0));
+        assert!(world.insert(moving, Projectile));
+        assert_eq!(world.get::<Velocity>(moving), Some(Velocity(2.0)));
+    }
+
+    #[test]
+    fn test_missing_requirement_rejects_insert() {
+        let mut world = World::new();
+        world.require::<Gamemode, Player>();
+
+        let entity = world.spawn_empty();
+        assert!(!world.insert(entity, Gamemode(1)));
+        assert!(!world.has::<Gamemode>(entity));
+
+        let err = world.try_insert(entity, Gamemode(1)).unwrap_err();
+        assert!(matches!(
+            err,
+            InsertError::MissingRequirement { requires, .. } if requires.ends_with("Player")
+        ));
+
+        world.insert(entity, Player);
+        assert!(world.try_insert(entity, Gamemode(1)).is_ok());
+    }
+}
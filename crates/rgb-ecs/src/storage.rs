@@ -282,6 +282,42 @@ impl Column {
         self.data.as_ptr()
     }
 
+    /// View the components as a typed slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` doesn't match the column's component type.
+    #[must_use]
+    pub fn as_slice<T: 'static>(&self) -> &[T] {
+        assert!(self.info.is::<T>(), "Type mismatch in Column::as_slice");
+
+        // SAFETY: The type matches, so the first `len` slots hold initialized,
+        // aligned `T`s
+        unsafe { std::slice::from_raw_parts(self.typed_ptr::<T>(), self.len) }
+    }
+
+    /// View the components as a mutable typed slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` doesn't match the column's component type.
+    #[must_use]
+    pub fn as_mut_slice<T: 'static>(&mut self) -> &mut [T] {
+        assert!(self.info.is::<T>(), "Type mismatch in Column::as_mut_slice");
+
+        // SAFETY: As in `as_slice`, and `&mut self` makes the borrow unique
+        unsafe { std::slice::from_raw_parts_mut(self.typed_ptr::<T>(), self.len) }
+    }
+
+    /// Pointer to the data as `T`, aligned even when nothing is allocated.
+    fn typed_ptr<T>(&self) -> *mut T {
+        if self.len == 0 || self.info.size() == 0 {
+            NonNull::<T>::dangling().as_ptr()
+        } else {
+            self.data.as_ptr().cast()
+        }
+    }
+
     /// Reserve capacity for at least `additional` more components.
     pub fn reserve(&mut self, additional: usize) {
         let required = self.len.checked_add(additional).expect("Capacity overflow");
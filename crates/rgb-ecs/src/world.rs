@@ -9,11 +9,18 @@
 
 use std::any::TypeId;
 
+use core::panic::Location;
+
 use crate::{
     archetype::{ArchetypeId, ArchetypeStorage},
     component::{ComponentId, ComponentRegistry},
     entity::{Entity, EntityAllocator},
-    relation::Pair,
+    error::{EntityNotAlive, InsertError},
+    hooks::{ComponentHooks, HookKind},
+    migrate::MigrateFn,
+    prefab::PrefabCloneFn,
+    relation::{Cardinality, Pair, Relation},
+    requires::Requirement,
 };
 
 /// Location of an entity within the archetype storage.
@@ -55,6 +62,17 @@ pub struct World {
     name_index: std::collections::BTreeMap<Vec<u8>, Entity>,
     /// Reverse index: Entity -> name bytes (for cleanup on despawn)
     entity_names: Vec<Option<Vec<u8>>>,
+    /// How to copy each prefab component onto a new instance.
+    pub(crate) prefab_clones: hashbrown::HashMap<ComponentId, PrefabCloneFn>,
+    /// How to copy each migratable component into another world.
+    pub(crate) migrations: hashbrown::HashMap<ComponentId, MigrateFn>,
+    /// Components each component can't be inserted without.
+    pub(crate) requirements: hashbrown::HashMap<ComponentId, Vec<Requirement>>,
+    /// Lifecycle hooks of each component.
+    pub(crate) hooks: hashbrown::HashMap<ComponentId, ComponentHooks>,
+    /// Where each entity was spawned, when tracking is enabled (debug builds only).
+    /// Entries outlive their entity so stale handles can still be traced.
+    spawn_sites: Option<hashbrown::HashMap<Entity, &'static Location<'static>>>,
 }
 
 impl Default for World {
@@ -74,6 +92,11 @@ impl World {
             archetypes: ArchetypeStorage::new(),
             name_index: std::collections::BTreeMap::new(),
             entity_names: Vec::new(),
+            prefab_clones: hashbrown::HashMap::new(),
+            migrations: hashbrown::HashMap::new(),
+            requirements: hashbrown::HashMap::new(),
+            hooks: hashbrown::HashMap::new(),
+            spawn_sites: None,
         };
 
         // Reserve Entity::WORLD (id=0) and mark it as global
@@ -94,6 +117,11 @@ impl World {
             archetypes: ArchetypeStorage::new(),
             name_index: std::collections::BTreeMap::new(),
             entity_names: Vec::with_capacity(entity_capacity),
+            prefab_clones: hashbrown::HashMap::new(),
+            migrations: hashbrown::HashMap::new(),
+            requirements: hashbrown::HashMap::new(),
+            hooks: hashbrown::HashMap::new(),
+            spawn_sites: None,
         };
 
         // Reserve Entity::WORLD (id=0) and mark it as global
@@ -114,9 +142,34 @@ impl World {
 
     // ==================== Entity Operations ====================
 
+    /// Allocate an entity ID, recording the spawn site if tracking is enabled.
+    #[track_caller]
+    fn allocate_entity(&mut self) -> Entity {
+        let entity = self.entities.allocate();
+        #[cfg(debug_assertions)]
+        if let Some(sites) = &mut self.spawn_sites {
+            sites.insert(entity, Location::caller());
+        }
+        entity
+    }
+
+    /// Record where each entity is spawned.
+    ///
+    /// [`EntityNotAlive`] errors then report where a stale handle came from.
+    /// Only takes effect in debug builds. Sites are kept after despawn, so
+    /// memory grows with every spawn - enable this while debugging only.
+    pub fn track_spawn_sites(&mut self, enabled: bool) {
+        if !enabled {
+            self.spawn_sites = None;
+        } else if self.spawn_sites.is_none() {
+            self.spawn_sites = Some(hashbrown::HashMap::new());
+        }
+    }
+
     /// Spawn a new empty entity.
+    #[track_caller]
     pub fn spawn_empty(&mut self) -> Entity {
-        let entity = self.entities.allocate();
+        let entity = self.allocate_entity();
         let id = entity.id() as usize;
 
         // Ensure meta vec is large enough
@@ -170,6 +223,7 @@ impl World {
     /// let chunk = world.entity_named(&chunk_key(10, 20));
     /// world.insert(chunk, ChunkData::new());
     /// ```
+    #[track_caller]
     pub fn entity_named(&mut self, name: &[u8]) -> Entity {
         // Check if entity already exists with this name
         if let Some(&entity) = self.name_index.get(name) {
@@ -246,8 +300,12 @@ impl World {
     }
 
     /// Spawn an entity with a single component.
+    ///
+    /// Components `T` [requires](Self::require) are inserted with their
+    /// defaults. In debug builds, panics if a requirement can't be defaulted.
+    #[track_caller]
     pub fn spawn<T: 'static + Send + Sync>(&mut self, component: T) -> Entity {
-        let entity = self.entities.allocate();
+        let entity = self.allocate_entity();
         let id = entity.id() as usize;
 
         // Ensure meta vec is large enough
@@ -279,6 +337,15 @@ impl World {
             },
         });
 
+        // A fresh entity has nothing else, so only defaults can satisfy `T`
+        if self.requirements.contains_key(&comp_id) {
+            let result = self.ensure_requirements(entity, comp_id);
+            debug_assert!(result.is_ok(), "{}", result.unwrap_err());
+        }
+
+        self.run_hooks(HookKind::Add, comp_id, entity);
+        self.run_hooks(HookKind::Set, comp_id, entity);
+
         entity
     }
 
@@ -290,6 +357,29 @@ impl World {
             return false;
         }
 
+        if !self.hooks.is_empty()
+            && let Some(location) = self.entity_location(entity)
+        {
+            let hooked: Vec<_> =
+                self.archetypes
+                    .get(location.archetype_id)
+                    .map_or_else(Vec::new, |archetype| {
+                        archetype
+                            .components()
+                            .iter()
+                            .copied()
+                            .filter(|&comp_id| self.has_hooks(HookKind::Remove, comp_id))
+                            .collect()
+                    });
+            for comp_id in hooked {
+                self.run_hooks(HookKind::Remove, comp_id, entity);
+            }
+            // Hooks may have despawned the entity themselves
+            if !self.entities.is_alive(entity) {
+                return false;
+            }
+        }
+
         let id = entity.id() as usize;
         let meta = match self.entity_meta.get(id).and_then(|m| *m) {
             Some(m) => m,
@@ -343,12 +433,47 @@ impl World {
         self.entities.is_alive(entity)
     }
 
+    /// Check that an entity handle is alive (not despawned or from an older generation).
+    pub fn check_alive(&self, entity: Entity) -> Result<(), EntityNotAlive> {
+        if self.entities.is_alive(entity) {
+            return Ok(());
+        }
+        Err(EntityNotAlive {
+            entity,
+            current: self.entities.generation_of(entity.id()),
+            spawned_at: self
+                .spawn_sites
+                .as_ref()
+                .and_then(|sites| sites.get(&entity).copied()),
+        })
+    }
+
+    /// Metadata for a live entity; `None` for stale handles.
+    fn live_meta(&self, entity: Entity) -> Option<&EntityMeta> {
+        if !self.entities.is_alive(entity) {
+            return None;
+        }
+        self.entity_meta.get(entity.id() as usize)?.as_ref()
+    }
+
     /// Get the number of alive entities.
     #[must_use]
     pub fn entity_count(&self) -> u32 {
         self.entities.alive_count()
     }
 
+    /// Get the number of entities ever spawned, including despawned ones.
+    #[must_use]
+    pub const fn spawned_total(&self) -> u64 {
+        self.entities.allocated_total()
+    }
+
+    /// Get the number of entities ever despawned.
+    #[must_use]
+    pub const fn despawned_total(&self) -> u64 {
+        self.entities.deallocated_total()
+    }
+
     /// Get the location of an entity.
     #[must_use]
     pub fn entity_location(&self, entity: Entity) -> Option<EntityLocation> {
@@ -377,19 +502,27 @@ impl World {
     /// Add a component to an entity.
     ///
     /// If the entity already has this component type, it is replaced.
+    /// Returns `false` if the entity is dead or lacks a component `T`
+    /// [requires](Self::require).
     pub fn insert<T: 'static + Send + Sync>(&mut self, entity: Entity, component: T) -> bool {
         if !self.entities.is_alive(entity) {
             return false;
         }
 
+        let comp_id = self.components.register::<T>();
+        if self.requirements.contains_key(&comp_id)
+            && !self.has_by_id(entity, comp_id)
+            && self.ensure_requirements(entity, comp_id).is_err()
+        {
+            return false;
+        }
+
         let entity_id = entity.id() as usize;
         let meta = match self.entity_meta.get(entity_id).and_then(|m| *m) {
             Some(m) => m,
             None => return false,
         };
 
-        let comp_id = self.components.register::<T>();
-
         // Check if already in correct archetype
         let old_archetype = self.archetypes.get(meta.location.archetype_id).unwrap();
         if old_archetype.contains(comp_id) {
@@ -399,6 +532,7 @@ impl World {
             unsafe {
                 archetype.set_component(comp_id, meta.location.row, component);
             }
+            self.run_hooks(HookKind::Set, comp_id, entity);
             return true;
         }
 
@@ -407,7 +541,12 @@ impl World {
             self.archetypes
                 .with_component(meta.location.archetype_id, comp_id, &self.components);
 
-        self.move_entity_to_archetype(entity, new_arch_id, Some((comp_id, component)))
+        if !self.move_entity_to_archetype(entity, new_arch_id, Some((comp_id, component))) {
+            return false;
+        }
+        self.run_hooks(HookKind::Add, comp_id, entity);
+        self.run_hooks(HookKind::Set, comp_id, entity);
+        true
     }
 
     /// Remove a component from an entity.
@@ -420,6 +559,10 @@ impl World {
 
         let comp_id = self.components.get_id::<T>()?;
 
+        if self.has_hooks(HookKind::Remove, comp_id) && self.has_by_id(entity, comp_id) {
+            self.run_hooks(HookKind::Remove, comp_id, entity);
+        }
+
         let entity_id = entity.id() as usize;
         let meta = self.entity_meta.get(entity_id).and_then(|m| *m)?;
 
@@ -454,7 +597,7 @@ impl World {
     #[must_use]
     pub fn get<T: 'static + Send + Sync + Clone>(&self, entity: Entity) -> Option<T> {
         let comp_id = self.components.get_id::<T>()?;
-        let meta = self.entity_meta.get(entity.id() as usize)?.as_ref()?;
+        let meta = self.live_meta(entity)?;
         let archetype = self.archetypes.get(meta.location.archetype_id)?;
 
         // SAFETY: We verified the entity is alive and in this archetype
@@ -468,7 +611,7 @@ impl World {
     #[must_use]
     pub fn get_ref<T: 'static + Send + Sync>(&self, entity: Entity) -> Option<&T> {
         let comp_id = self.components.get_id::<T>()?;
-        let meta = self.entity_meta.get(entity.id() as usize)?.as_ref()?;
+        let meta = self.live_meta(entity)?;
         let archetype = self.archetypes.get(meta.location.archetype_id)?;
 
         // SAFETY: We verified the entity is alive and in this archetype
@@ -487,7 +630,7 @@ impl World {
     #[must_use]
     pub fn get_raw_ptr(&self, entity: Entity, type_id: TypeId) -> Option<*const u8> {
         let comp_id = self.components.get_id_by_type_id(type_id)?;
-        let meta = self.entity_meta.get(entity.id() as usize)?.as_ref()?;
+        let meta = self.live_meta(entity)?;
         let archetype = self.archetypes.get(meta.location.archetype_id)?;
 
         // Check if archetype has this component
@@ -527,9 +670,48 @@ impl World {
         unsafe {
             archetype.set_component(comp_id, meta.location.row, component);
         }
+        self.run_hooks(HookKind::Set, comp_id, entity);
         true
     }
 
+    /// Like [`get`](Self::get), but distinguishes a stale handle from a missing component.
+    ///
+    /// Returns `Ok(None)` if the entity is alive but lacks `T`.
+    pub fn try_get<T: 'static + Send + Sync + Clone>(
+        &self,
+        entity: Entity,
+    ) -> Result<Option<T>, EntityNotAlive> {
+        self.check_alive(entity)?;
+        Ok(self.get(entity))
+    }
+
+    /// Like [`insert`](Self::insert), but reports why the component was rejected.
+    pub fn try_insert<T: 'static + Send + Sync>(
+        &mut self,
+        entity: Entity,
+        component: T,
+    ) -> Result<(), InsertError> {
+        self.check_alive(entity)?;
+        let comp_id = self.components.register::<T>();
+        if !self.has_by_id(entity, comp_id) {
+            self.ensure_requirements(entity, comp_id)?;
+        }
+        self.insert(entity, component);
+        Ok(())
+    }
+
+    /// Like [`update`](Self::update), but distinguishes a stale handle from a missing component.
+    ///
+    /// Returns `Ok(false)` if the entity is alive but lacks `T`.
+    pub fn try_update<T: 'static + Send + Sync>(
+        &mut self,
+        entity: Entity,
+        component: T,
+    ) -> Result<bool, EntityNotAlive> {
+        self.check_alive(entity)?;
+        Ok(self.update(entity, component))
+    }
+
     /// Update an entity's component from raw bytes.
     ///
     /// This is used by the introspection layer to update components from
@@ -566,7 +748,11 @@ impl World {
         }
 
         // SAFETY: Caller ensures src points to valid component data
-        unsafe { archetype.set_component_raw(component_id, meta.location.row, src) }
+        let updated = unsafe { archetype.set_component_raw(component_id, meta.location.row, src) };
+        if updated {
+            self.run_hooks(HookKind::Set, component_id, entity);
+        }
+        updated
     }
 
     /// Check if an entity has a component.
@@ -575,7 +761,7 @@ impl World {
         let Some(comp_id) = self.components.get_id::<T>() else {
             return false;
         };
-        let Some(Some(meta)) = self.entity_meta.get(entity.id() as usize) else {
+        let Some(meta) = self.live_meta(entity) else {
             return false;
         };
         let Some(archetype) = self.archetypes.get(meta.location.archetype_id) else {
@@ -702,13 +888,64 @@ impl World {
     /// let child = world.spawn_empty();
     /// world.insert_pair::<ChildOf>(child, parent);
     /// ```
-    pub fn insert_pair<R: 'static + Send + Sync + Default>(
+    ///
+    /// Relations are exclusive: any existing `(R, *)` pair on `entity` is
+    /// replaced. Returns `false` if `target` is already taken through a
+    /// [one-to-one](Cardinality::OneToOne) relation.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if `target` violates the relation's target
+    /// constraint (see [`Relation::TARGET`]).
+    pub fn insert_pair<R: Relation + Default>(&mut self, entity: Entity, target: Entity) -> bool {
+        self.try_insert_pair::<R>(entity, target).is_ok()
+    }
+
+    /// Like [`insert_pair`](Self::insert_pair), but reports why the pair was
+    /// rejected.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if `target` violates the relation's target
+    /// constraint (see [`Relation::TARGET`]).
+    pub fn try_insert_pair<R: Relation + Default>(
         &mut self,
         entity: Entity,
         target: Entity,
-    ) -> bool {
+    ) -> Result<(), InsertError> {
+        debug_assert!(
+            R::is_valid_target(self, target),
+            "relation {} requires target {:?} to have component {}",
+            core::any::type_name::<R>(),
+            target,
+            R::TARGET.unwrap_or("<none>"),
+        );
+        if R::CARDINALITY == Cardinality::OneToOne
+            && let Some(holder) = self
+                .sources_of::<R>(target)
+                .find(|&source| source != entity)
+        {
+            return Err(InsertError::TargetTaken {
+                relation: core::any::type_name::<R>(),
+                target,
+                holder,
+            });
+        }
         // Store the pair as a component: Pair<R> where R is the relation type
-        self.insert(entity, Pair::<R>::new(target))
+        self.try_insert(entity, Pair::<R>::new(target))
+    }
+
+    /// Iterate over the entities with an `(R, target)` pair.
+    pub fn sources_of<R: Relation>(&self, target: Entity) -> impl Iterator<Item = Entity> + '_ {
+        let comp_id = self.component_id::<Pair<R>>();
+        self.archetypes
+            .iter()
+            .filter(move |arch| comp_id.is_some_and(|id| arch.contains(id)))
+            .flat_map(|arch| arch.entities().iter().copied())
+            .filter(move |&source| {
+                self.get_ref::<Pair<R>>(source)
+                    .is_some_and(|pair| pair.target() == target)
+            })
     }
 
     /// Get the target of a relation pair.
@@ -773,6 +1010,13 @@ impl World {
         &self.archetypes
     }
 
+    /// Get mutable archetype storage along with the component registry.
+    pub(crate) fn archetypes_mut_with_components(
+        &mut self,
+    ) -> (&mut ArchetypeStorage, &ComponentRegistry) {
+        (&mut self.archetypes, &self.components)
+    }
+
     /// Get mutable archetype storage.
     #[must_use]
     pub fn archetypes_mut(&mut self) -> &mut ArchetypeStorage {
@@ -788,7 +1032,7 @@ impl World {
     /// Check if an entity has a component by component ID.
     #[must_use]
     pub fn has_by_id(&self, entity: Entity, comp_id: ComponentId) -> bool {
-        let Some(Some(meta)) = self.entity_meta.get(entity.id() as usize) else {
+        let Some(meta) = self.live_meta(entity) else {
             return false;
         };
         let Some(archetype) = self.archetypes.get(meta.location.archetype_id) else {
@@ -880,12 +1124,11 @@ pub struct QueryIter<'w, T> {
 
 impl<'w, T: 'static + Send + Sync + Clone> QueryIter<'w, T> {
     fn new(world: &'w World, required: hashbrown::HashSet<ComponentId>) -> Self {
-        let archetype_iter = Box::new(
-            world
-                .archetypes
-                .iter()
-                .filter(move |arch| required.iter().all(|id| arch.contains(*id))),
-        );
+        let prefab = crate::prefab::implicit_exclusion(world, required.iter().copied());
+        let archetype_iter = Box::new(world.archetypes.iter().filter(move |arch| {
+            required.iter().all(|id| arch.contains(*id))
+                && !prefab.is_some_and(|id| arch.contains(id))
+        }));
         Self {
             world: Some(world),
             archetype_iter,
@@ -1186,6 +1429,43 @@ mod tests {
         assert_eq!(world.get::<Position>(e3).unwrap().x, 3.0);
     }
 
+    #[test]
+    fn test_stale_handle_rejected() {
+        let mut world = World::new();
+
+        let stale = world.spawn(Position { x: 1.0, y: 1.0 });
+        world.despawn(stale);
+        // Reuses the slot with a new generation
+        let fresh = world.spawn(Position { x: 2.0, y: 2.0 });
+        assert_eq!(stale.id(), fresh.id());
+
+        assert_eq!(world.get::<Position>(stale), None);
+        assert!(!world.has::<Position>(stale));
+        assert!(!world.update(stale, Position { x: 9.0, y: 9.0 }));
+        assert_eq!(world.get::<Position>(fresh).unwrap().x, 2.0);
+
+        let err = world.try_get::<Position>(stale).unwrap_err();
+        assert_eq!(err.entity, stale);
+        assert_eq!(err.current, Some(fresh.generation()));
+        assert!(world.try_insert(stale, Health(1)).is_err());
+        assert_eq!(world.try_update(fresh, Health(1)), Ok(false));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_stale_handle_spawn_site() {
+        let mut world = World::new();
+        world.track_spawn_sites(true);
+
+        let line = line!() + 1;
+        let entity = world.spawn_empty();
+        world.despawn(entity);
+
+        let site = world.check_alive(entity).unwrap_err().spawned_at.unwrap();
+        assert_eq!(site.file(), file!());
+        assert_eq!(site.line(), line);
+    }
+
     #[test]
     fn test_update_nonexistent_fails() {
         let mut world = World::new();
@@ -1221,6 +1501,31 @@ mod tests {
         assert!(!world.has_relation::<ChildOf>(parent));
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "requires target")]
+    fn test_relation_target_constraint() {
+        use crate::Component;
+
+        #[derive(Component, Clone)]
+        struct Player;
+
+        #[derive(Component, Clone, Default)]
+        #[component(relation, target = Player)]
+        struct Follows;
+
+        let mut world = World::new();
+
+        let player = world.spawn(Player);
+        let follower = world.spawn_empty();
+        let rock = world.spawn_empty();
+
+        assert!(world.insert_pair::<Follows>(follower, player));
+
+        // Rock has no Player component
+        world.insert_pair::<Follows>(follower, rock);
+    }
+
     #[test]
     fn test_relation_pairs() {
         use crate::relation::{ContainedIn, OwnedBy};
@@ -1247,6 +1552,54 @@ mod tests {
         assert!(!world.has_relation::<ContainedIn>(sword));
     }
 
+    #[test]
+    fn test_relations_are_exclusive() {
+        use crate::relation::ContainedIn;
+
+        let mut world = World::new();
+        let chest = world.spawn_empty();
+        let barrel = world.spawn_empty();
+        let apple = world.spawn_empty();
+
+        world.insert_pair::<ContainedIn>(apple, chest);
+        world.insert_pair::<ContainedIn>(apple, barrel);
+
+        assert_eq!(world.get_pair_target::<ContainedIn>(apple), Some(barrel));
+        assert_eq!(world.sources_of::<ContainedIn>(chest).count(), 0);
+        assert_eq!(
+            world.sources_of::<ContainedIn>(barrel).collect::<Vec<_>>(),
+            [apple]
+        );
+    }
+
+    #[test]
+    fn test_one_to_one_relation() {
+        use crate::relation::Cardinality;
+
+        #[derive(Debug, Clone, Copy, Default)]
+        struct Rides;
+
+        impl Relation for Rides {
+            const CARDINALITY: Cardinality = Cardinality::OneToOne;
+        }
+
+        let mut world = World::new();
+        let horse = world.spawn_empty();
+        let alex = world.spawn_empty();
+        let sam = world.spawn_empty();
+
+        assert!(world.insert_pair::<Rides>(alex, horse));
+        // Re-adding the same pair is fine
+        assert!(world.insert_pair::<Rides>(alex, horse));
+
+        let err = world.try_insert_pair::<Rides>(sam, horse).unwrap_err();
+        assert!(matches!(err, InsertError::TargetTaken { holder, .. } if holder == alex));
+        assert!(!world.has_relation::<Rides>(sam));
+
+        world.remove_pair::<Rides>(alex);
+        assert!(world.insert_pair::<Rides>(sam, horse));
+    }
+
     #[test]
     fn test_named_entity_basic() {
         let mut world = World::new();
@@ -20,6 +20,17 @@
 //! - Events targeting `Entity::WORLD` → Global queue (sequential)
 //! - Events with `Position` → RGB queue by `position.cell_color()` (parallel)
 //! - Events with `Target(entity)` → RGB queue by target's position
+//! - Targets without a position → Global queue
+//!
+//! The tick scheduler drains one [`EventPhase`] at a time via
+//! [`EventWorldExt::flush_phase`]; see [`route_event`] for the routing rules.
+//!
+//! Events can also be scheduled for a later tick, once or repeatedly, with
+//! [`SchedulerWorldExt::run_in`] and [`SchedulerWorldExt::run_every`].
+//!
+//! Entities with a `Position` can be found by range through
+//! [`SpatialWorldExt`], e.g. to send a sound to everyone in earshot with
+//! [`SpatialWorldExt::send_within`].
 //!
 //! # Example
 //!
@@ -45,6 +56,9 @@ mod color;
 mod event;
 mod observer;
 mod queue;
+mod route;
+mod scheduler;
+mod spatial;
 mod world_ext;
 
 use rgb_ecs::{Plugin, World};
@@ -53,6 +67,9 @@ pub use color::cell_color;
 pub use event::Event;
 pub use observer::{Observer, ObserverId};
 pub use queue::EventQueue;
+pub use route::{EventPhase, route_event};
+pub use scheduler::{DueTick, Every, SchedulerClock, SchedulerWorldExt};
+pub use spatial::{SpatialIndex, SpatialWorldExt};
 pub use world_ext::{EventSystem, EventWorldExt, Position, Target};
 
 /// Plugin to add the event system to a World.
@@ -81,7 +98,7 @@ impl Plugin for EventPlugin {
 /// Prelude for convenient imports
 pub mod prelude {
     pub use crate::{
-        Event, EventPlugin, EventQueue, EventWorldExt, Observer, ObserverId, Position, Target,
-        cell_color,
+        Event, EventPhase, EventPlugin, EventQueue, EventWorldExt, Observer, ObserverId, Position,
+        SchedulerWorldExt, SpatialWorldExt, Target, cell_color,
     };
 }
@@ -6,6 +6,8 @@ use std::collections::VecDeque;
 use rgb_ecs::Entity;
 use rgb_spatial::Color;
 
+use crate::route::EventPhase;
+
 /// A queued event waiting to be processed.
 pub struct QueuedEvent {
     /// The event entity (contains event data as component)
@@ -27,9 +29,9 @@ impl core::fmt::Debug for QueuedEvent {
 
 /// Event queue with separate buckets for global and RGB-colored events.
 ///
-/// Events are bucketed by the cell color of their target's position:
-/// - Global events (target = Entity::WORLD) → global queue
-/// - Targeted events → RGB queue by target's position color
+/// Events are bucketed by their [`EventPhase`] (see [`route_event`](crate::route_event)):
+/// - Events without a spatial location → global queue
+/// - Positional or targeted events → RGB queue by cell color
 #[derive(Default)]
 pub struct EventQueue {
     /// Global events (no position, sequential processing)
@@ -49,12 +51,18 @@ impl EventQueue {
         Self::default()
     }
 
-    /// Push a global event (target = Entity::WORLD).
+    /// Push an event into the queue for its phase.
+    pub fn push(&mut self, event: QueuedEvent, phase: EventPhase) {
+        match phase {
+            EventPhase::Global => self.global.push_back(event),
+            EventPhase::Color(color) => self.push_colored(event, color),
+        }
+    }
+
+    /// Push a global event.
+    ///
+    /// Used for events targeting `Entity::WORLD` and for targets without a position.
     pub fn push_global(&mut self, event: QueuedEvent) {
-        debug_assert!(
-            event.target == Entity::WORLD,
-            "Global events must target Entity::WORLD"
-        );
         self.global.push_back(event);
     }
 
@@ -94,6 +102,19 @@ impl EventQueue {
         }
     }
 
+    /// Take every event currently queued for a phase as one batch.
+    ///
+    /// Events pushed while the batch is being processed land in the next batch.
+    pub fn take_batch(&mut self, phase: EventPhase) -> Vec<QueuedEvent> {
+        let queue = match phase {
+            EventPhase::Global => &mut self.global,
+            EventPhase::Color(Color::Red) => &mut self.red,
+            EventPhase::Color(Color::Green) => &mut self.green,
+            EventPhase::Color(Color::Blue) => &mut self.blue,
+        };
+        Vec::from(core::mem::take(queue))
+    }
+
     /// Drain all global events.
     pub fn drain_global(&mut self) -> impl Iterator<Item = QueuedEvent> + '_ {
         self.global.drain(..)
@@ -146,6 +167,15 @@ impl EventQueue {
         }
     }
 
+    /// Get number of events queued for a phase.
+    #[must_use]
+    pub fn phase_len(&self, phase: EventPhase) -> usize {
+        match phase {
+            EventPhase::Global => self.global.len(),
+            EventPhase::Color(color) => self.color_len(color),
+        }
+    }
+
     /// Clear all queues.
     pub fn clear(&mut self) {
         self.global.clear();
@@ -210,6 +240,21 @@ mod tests {
         assert!(queue.is_color_empty(Color::Red));
     }
 
+    #[test]
+    fn test_take_batch() {
+        let mut queue = EventQueue::new();
+        let target = Entity::from_bits(1);
+
+        queue.push(dummy_event(target), EventPhase::Color(Color::Blue));
+        queue.push(dummy_event(target), EventPhase::Color(Color::Blue));
+        queue.push(dummy_event(target), EventPhase::Global);
+
+        let batch = queue.take_batch(EventPhase::Color(Color::Blue));
+        assert_eq!(batch.len(), 2);
+        assert_eq!(queue.phase_len(EventPhase::Color(Color::Blue)), 0);
+        assert_eq!(queue.phase_len(EventPhase::Global), 1);
+    }
+
     #[test]
     fn test_drain() {
         let mut queue = EventQueue::new();
@@ -0,0 +1,103 @@
+//! Spatial routing of events into scheduler phases.
+//!
+//! Every queued event runs in exactly one [`EventPhase`]:
+//!
+//! - Event entity has a [`Position`] (`send_at`) → color of that position
+//! - Otherwise, [`Target`](crate::Target) has a [`Position`] → color of the target
+//! - Otherwise (target is `Entity::WORLD` or has no position) → [`EventPhase::Global`]
+//!
+//! The tick scheduler drives the phases in [`EventPhase::ORDER`], calling
+//! [`EventWorldExt::flush_phase`](crate::EventWorldExt::flush_phase) once per
+//! phase. Colored phases may run cells in parallel since same-colored cells
+//! never share an edge; the global phase is always sequential.
+
+use rgb_ecs::{Entity, World};
+use rgb_spatial::Color;
+
+use crate::world_ext::Position;
+
+/// Scheduler phase an event is drained in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventPhase {
+    /// Sequential phase for events without a spatial location.
+    Global,
+    /// Parallel phase for events in cells of this color.
+    Color(Color),
+}
+
+impl EventPhase {
+    /// Order phases are drained in during a tick.
+    ///
+    /// Globals run first so RGB observers see their effects. Globals queued
+    /// during the RGB phases run on the next pass over the global phase.
+    pub const ORDER: [Self; 4] = [
+        Self::Global,
+        Self::Color(Color::Red),
+        Self::Color(Color::Green),
+        Self::Color(Color::Blue),
+    ];
+
+    /// Whether events in this phase may run in parallel (per cell).
+    #[must_use]
+    pub const fn is_parallel(self) -> bool {
+        matches!(self, Self::Color(_))
+    }
+}
+
+impl From<Color> for EventPhase {
+    fn from(color: Color) -> Self {
+        Self::Color(color)
+    }
+}
+
+/// Decide which phase an event runs in (see the [module docs](self)).
+#[must_use]
+pub fn route_event(world: &World, event_entity: Entity, target: Entity) -> EventPhase {
+    let position = world.get::<Position>(event_entity).or_else(|| {
+        if target == Entity::WORLD {
+            None
+        } else {
+            world.get::<Position>(target)
+        }
+    });
+
+    position.map_or(EventPhase::Global, |pos| EventPhase::Color(pos.color()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_by_event_position() {
+        let mut world = World::new();
+        let event = world.spawn(Position::new(16.0, 64.0, 0.0));
+        assert_eq!(
+            route_event(&world, event, Entity::WORLD),
+            EventPhase::Color(Color::Green)
+        );
+    }
+
+    #[test]
+    fn test_route_by_target_position() {
+        let mut world = World::new();
+        let target = world.spawn(Position::new(32.0, 64.0, 0.0));
+        let event = world.spawn_empty();
+        assert_eq!(
+            route_event(&world, event, target),
+            EventPhase::Color(Color::Blue)
+        );
+    }
+
+    #[test]
+    fn test_route_without_position_is_global() {
+        let mut world = World::new();
+        let target = world.spawn_empty();
+        let event = world.spawn_empty();
+        assert_eq!(route_event(&world, event, target), EventPhase::Global);
+        assert_eq!(
+            route_event(&world, event, Entity::WORLD),
+            EventPhase::Global
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Scheduled events.
+//!
+//! A scheduled task is an event payload sent later, not a closure, so tasks
+//! can be scheduled from module dylibs and scripts without code crossing the
+//! boundary:
+//!
+//! ```ignore
+//! // Send `Explode` to a creeper in 30 ticks
+//! world.run_in(30, creeper, Explode { power: 3.0 });
+//!
+//! // Send `Autosave` to the world every 6000 ticks, starting in 6000
+//! let task = world.run_every(6000, Entity::WORLD, Autosave);
+//! world.cancel_task(task);
+//! ```
+//!
+//! A task is a prefab entity holding the payload and its [`Target`], plus a
+//! [`DueTick`] and, if it repeats, [`Every`]. Being a prefab keeps it out of
+//! gameplay queries and lets a repeating task copy its payload each time it
+//! fires. Once per tick, [`SchedulerWorldExt::run_due_tasks`] advances the
+//! [`SchedulerClock`] and queues an instance of every due task as an event,
+//! in due-tick order; observers see it on the next flush like any event
+//! sent with [`EventWorldExt::send`].
+
+use core::any::TypeId;
+
+use rgb_ecs::{Entity, Prefab, World};
+
+use crate::Event;
+use crate::queue::QueuedEvent;
+use crate::route::route_event;
+use crate::world_ext::{EventWorldExt, Target};
+
+/// Tick a scheduled task fires on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DueTick(pub u64);
+
+/// Interval, in ticks, of a repeating task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Every(pub u64);
+
+/// Event type a scheduled task sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ScheduledEvent {
+    event_type_id: TypeId,
+}
+
+/// Ticks counted by the scheduler, stored on `Entity::WORLD`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerClock {
+    pub tick: u64,
+}
+
+/// Extension trait for World to schedule events.
+pub trait SchedulerWorldExt {
+    /// Current scheduler tick (0 before the first [`run_due_tasks`](Self::run_due_tasks)).
+    fn scheduler_tick(&self) -> u64;
+
+    /// Send `event` to `target` in `ticks` ticks (at least one).
+    ///
+    /// Returns the task entity, which [`cancel_task`](Self::cancel_task)
+    /// takes.
+    fn run_in<E: Event + Clone>(&mut self, ticks: u64, target: Entity, event: E) -> Entity;
+
+    /// Send `event` to `target` every `interval` ticks (at least one),
+    /// starting `interval` ticks from now, until cancelled.
+    fn run_every<E: Event + Clone>(&mut self, interval: u64, target: Entity, event: E) -> Entity;
+
+    /// Cancel a task that hasn't fired yet (or a repeating task).
+    ///
+    /// Returns `false` if `task` is not a pending task.
+    fn cancel_task(&mut self, task: Entity) -> bool;
+
+    /// Advance the scheduler by one tick and queue every task due by then.
+    ///
+    /// Returns the number of events queued.
+    fn run_due_tasks(&mut self) -> usize;
+}
+
+impl SchedulerWorldExt for World {
+    fn scheduler_tick(&self) -> u64 {
+        self.get::<SchedulerClock>(Entity::WORLD)
+            .unwrap_or_default()
+            .tick
+    }
+
+    fn run_in<E: Event + Clone>(&mut self, ticks: u64, target: Entity, event: E) -> Entity {
+        let task = spawn_task(self, target, event);
+        let due = self.scheduler_tick() + ticks.max(1);
+        self.insert(task, DueTick(due));
+        task
+    }
+
+    fn run_every<E: Event + Clone>(&mut self, interval: u64, target: Entity, event: E) -> Entity {
+        let interval = interval.max(1);
+        let task = self.run_in(interval, target, event);
+        self.insert(task, Every(interval));
+        task
+    }
+
+    fn cancel_task(&mut self, task: Entity) -> bool {
+        if !self.is_prefab(task) || !self.has::<DueTick>(task) {
+            return false;
+        }
+        self.despawn(task)
+    }
+
+    fn run_due_tasks(&mut self) -> usize {
+        let now = self.scheduler_tick() + 1;
+        self.insert(Entity::WORLD, SchedulerClock { tick: now });
+
+        let tasks = self.query().filter::<Prefab>().with::<DueTick>().build();
+        let mut due: Vec<(DueTick, Entity)> = tasks
+            .iter(self)
+            .map(|row| (row.get::<DueTick>(), row.entity()))
+            .filter(|(tick, _)| tick.0 <= now)
+            .collect();
+        due.sort_by_key(|&(tick, task)| (tick, task.to_bits()));
+
+        self.init_events();
+        let Some(sys) = self.events() else {
+            return 0;
+        };
+        let mut queued = 0;
+        for (_, task) in due {
+            let (Some(event), Some(Target(target))) =
+                (self.get::<ScheduledEvent>(task), self.get::<Target>(task))
+            else {
+                continue;
+            };
+            let Some(event_entity) = self.spawn_from(task) else {
+                continue;
+            };
+            let phase = route_event(self, event_entity, target);
+            sys.push(
+                QueuedEvent {
+                    event_entity,
+                    target,
+                    event_type_id: event.event_type_id,
+                },
+                phase,
+            );
+            queued += 1;
+
+            match self.get::<Every>(task) {
+                Some(Every(interval)) => {
+                    self.update(task, DueTick(now + interval));
+                }
+                None => {
+                    self.despawn(task);
+                }
+            }
+        }
+        queued
+    }
+}
+
+/// A task prefab carrying `event` to `target`, not yet due.
+fn spawn_task<E: Event + Clone>(world: &mut World, target: Entity, event: E) -> Entity {
+    let task = world.spawn_prefab();
+    world.set_prefab(task, event);
+    world.set_prefab(task, Target(target));
+    world.insert(
+        task,
+        ScheduledEvent {
+            event_type_id: TypeId::of::<E>(),
+        },
+    );
+    task
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Ping(u32);
+
+    /// Run `ticks` ticks, returning the `Ping` values observed on each
+    fn run(world: &mut World, ticks: u64) -> Vec<Vec<u32>> {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let s = Arc::clone(&seen);
+        world.observe(move |_world: &mut World, _target: Entity, ping: &Ping| {
+            s.lock().unwrap().push(ping.0);
+        });
+
+        let mut per_tick = Vec::new();
+        for _ in 0..ticks {
+            world.run_due_tasks();
+            world.flush_events();
+            per_tick.push(std::mem::take(&mut *seen.lock().unwrap()));
+        }
+        per_tick
+    }
+
+    #[test]
+    fn test_run_in_fires_once() {
+        let mut world = World::new();
+        world.run_in(2, Entity::WORLD, Ping(1));
+        world.run_in(0, Entity::WORLD, Ping(0));
+
+        let seen = run(&mut world, 4);
+        assert_eq!(seen, [vec![0], vec![1], vec![], vec![]]);
+        assert_eq!(world.scheduler_tick(), 4);
+    }
+
+    #[test]
+    fn test_run_every_repeats_until_cancelled() {
+        let mut world = World::new();
+        let task = world.run_every(2, Entity::WORLD, Ping(7));
+
+        let seen = run(&mut world, 5);
+        assert_eq!(seen, [vec![], vec![7], vec![], vec![7], vec![]]);
+
+        assert!(world.cancel_task(task));
+        assert!(!world.cancel_task(task));
+        world.run_due_tasks();
+        world.flush_events();
+        assert_eq!(world.events().unwrap().global_len(), 0);
+    }
+
+    #[test]
+    fn test_tasks_fire_in_due_order_and_are_hidden_from_queries() {
+        let mut world = World::new();
+        world.run_in(3, Entity::WORLD, Ping(3));
+        world.run_in(1, Entity::WORLD, Ping(1));
+        assert_eq!(world.query_single::<Ping>().count(), 0);
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let o = Arc::clone(&order);
+        world.observe(move |_world: &mut World, _target: Entity, ping: &Ping| {
+            o.lock().unwrap().push(ping.0);
+        });
+        for _ in 0..3 {
+            world.run_due_tasks();
+        }
+        world.flush_events();
+        assert_eq!(*order.lock().unwrap(), [1, 3]);
+    }
+
+    #[test]
+    fn test_targeted_task_routes_by_target_position() {
+        use crate::world_ext::Position;
+        use rgb_spatial::Color;
+
+        let mut world = World::new();
+        let target = world.spawn(Position::new(16.0, 64.0, 0.0));
+        world.run_in(1, target, Ping(1));
+
+        assert_eq!(world.run_due_tasks(), 1);
+        assert_eq!(world.events().unwrap().color_len(Color::Green), 1);
+    }
+
+    #[test]
+    fn test_cancel_non_task() {
+        let mut world = World::new();
+        let entity = world.spawn(Ping(1));
+        assert!(!world.cancel_task(entity));
+        assert!(world.is_alive(entity));
+    }
+}
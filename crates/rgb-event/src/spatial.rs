@@ -0,0 +1,224 @@
+//! Spatial index of positioned entities.
+//!
+//! A [`SpatialGrid`] on `Entity::WORLD` indexes every entity with a
+//! [`Position`] by its `x`/`z`, so range queries don't scan the world:
+//!
+//! ```ignore
+//! world.init_spatial(SpatialGrid::new(256, 256, 16.0));
+//!
+//! // Once per tick, after movement and before flushing events
+//! world.sync_spatial();
+//!
+//! // Interest management: who a player should be told about
+//! let visible = world.visible_to(player, 128.0);
+//!
+//! // Sounds and particles: every entity in earshot gets the event
+//! world.send_within(Position::new(10.0, 64.0, 20.0), 16.0, PlaySound { id: 3 });
+//! ```
+//!
+//! [`SpatialWorldExt::sync_spatial`] is the system keeping the grid in step
+//! with `Position`: it moves every positioned entity to its current cell and
+//! drops the ones that died or lost their position. Queries see positions
+//! as of the last sync. Event entities (those with a [`Target`]) are not
+//! indexed.
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use rgb_ecs::{Entity, World};
+use rgb_spatial::SpatialGrid;
+
+use crate::Event;
+use crate::world_ext::{EventWorldExt, Position, Target};
+
+/// Spatial index handle, stored on `Entity::WORLD`.
+///
+/// Cloning shares the grid, like [`EventSystem`](crate::EventSystem).
+#[derive(Clone)]
+pub struct SpatialIndex {
+    grid: Arc<RwLock<SpatialGrid>>,
+}
+
+impl SpatialIndex {
+    /// Wrap a grid.
+    #[must_use]
+    pub fn new(grid: SpatialGrid) -> Self {
+        Self {
+            grid: Arc::new(RwLock::new(grid)),
+        }
+    }
+
+    /// Entities inside the box between `min` and `max`, ignoring `y`.
+    #[must_use]
+    pub fn entities_in_aabb(&self, min: Position, max: Position) -> Vec<Entity> {
+        self.grid
+            .read()
+            .entities_in_aabb(grid_coords(min), grid_coords(max))
+            .collect()
+    }
+
+    /// Entities at most `radius` away from `center`, ignoring `y`.
+    #[must_use]
+    pub fn entities_within(&self, center: Position, radius: f64) -> Vec<Entity> {
+        self.grid
+            .read()
+            .entities_within(grid_coords(center), radius as f32)
+            .collect()
+    }
+
+    /// Number of indexed entities.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.grid.read().entity_count()
+    }
+
+    /// Check if no entity is indexed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Grid coordinates of a position: world `x` and `z`.
+fn grid_coords(position: Position) -> (f32, f32) {
+    (position.x as f32, position.z as f32)
+}
+
+/// Extension trait for World to query entities by position.
+pub trait SpatialWorldExt {
+    /// Index positioned entities in `grid`, replacing any previous index.
+    ///
+    /// Entities are added on the next [`sync_spatial`](Self::sync_spatial).
+    fn init_spatial(&mut self, grid: SpatialGrid);
+
+    /// Get the spatial index handle.
+    fn spatial(&self) -> Option<SpatialIndex>;
+
+    /// Update the index from every entity's current `Position`.
+    ///
+    /// Returns the number of indexed entities.
+    fn sync_spatial(&mut self) -> usize;
+
+    /// Entities other than `viewer` within `view_distance` of it on both
+    /// horizontal axes.
+    ///
+    /// Empty if `viewer` has no position or there's no index.
+    fn visible_to(&self, viewer: Entity, view_distance: f64) -> Vec<Entity>;
+
+    /// Send `event` to every entity within `radius` of `center`.
+    ///
+    /// Returns the number of entities it was sent to.
+    fn send_within<E: Event + Clone>(&mut self, center: Position, radius: f64, event: E) -> usize;
+}
+
+impl SpatialWorldExt for World {
+    fn init_spatial(&mut self, grid: SpatialGrid) {
+        self.insert(Entity::WORLD, SpatialIndex::new(grid));
+    }
+
+    fn spatial(&self) -> Option<SpatialIndex> {
+        self.get::<SpatialIndex>(Entity::WORLD)
+    }
+
+    fn sync_spatial(&mut self) -> usize {
+        let Some(index) = self.spatial() else {
+            return 0;
+        };
+        let positioned = self.query().with::<Position>().without::<Target>().build();
+
+        let mut grid = index.grid.write();
+        for row in positioned.iter(self) {
+            let (x, z) = grid_coords(row.get::<Position>());
+            grid.update_entity(row.entity(), x, z);
+        }
+        grid.retain_entities(|entity| {
+            self.is_alive(entity) && self.has::<Position>(entity) && !self.has::<Target>(entity)
+        });
+        grid.entity_count()
+    }
+
+    fn visible_to(&self, viewer: Entity, view_distance: f64) -> Vec<Entity> {
+        let (Some(index), Some(center)) = (self.spatial(), self.get::<Position>(viewer)) else {
+            return Vec::new();
+        };
+        let min = Position::new(center.x - view_distance, 0.0, center.z - view_distance);
+        let max = Position::new(center.x + view_distance, 0.0, center.z + view_distance);
+        let mut visible = index.entities_in_aabb(min, max);
+        visible.retain(|&entity| entity != viewer);
+        visible
+    }
+
+    fn send_within<E: Event + Clone>(&mut self, center: Position, radius: f64, event: E) -> usize {
+        let Some(index) = self.spatial() else {
+            return 0;
+        };
+        let targets = index.entities_within(center, radius);
+        for &target in &targets {
+            self.send(target, event.clone());
+        }
+        targets.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Sound;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_spatial(SpatialGrid::new(16, 16, 16.0));
+        world
+    }
+
+    #[test]
+    fn test_sync_follows_position() {
+        let mut world = world();
+        let zombie = world.spawn(Position::new(10.0, 64.0, 10.0));
+        assert_eq!(world.sync_spatial(), 1);
+
+        let index = world.spatial().unwrap();
+        assert_eq!(
+            index.entities_within(Position::new(10.0, 0.0, 10.0), 1.0),
+            [zombie]
+        );
+
+        world.update(zombie, Position::new(100.0, 64.0, 100.0));
+        world.sync_spatial();
+        assert!(
+            index
+                .entities_within(Position::new(10.0, 0.0, 10.0), 1.0)
+                .is_empty()
+        );
+
+        world.despawn(zombie);
+        assert_eq!(world.sync_spatial(), 0);
+    }
+
+    #[test]
+    fn test_visible_to_excludes_viewer() {
+        let mut world = world();
+        let player = world.spawn(Position::new(50.0, 64.0, 50.0));
+        let near = world.spawn(Position::new(60.0, 64.0, 40.0));
+        world.spawn(Position::new(200.0, 64.0, 50.0));
+        world.sync_spatial();
+
+        assert_eq!(world.visible_to(player, 32.0), [near]);
+    }
+
+    #[test]
+    fn test_send_within_skips_far_entities() {
+        let mut world = world();
+        world.spawn(Position::new(5.0, 64.0, 5.0));
+        world.spawn(Position::new(8.0, 64.0, 2.0));
+        world.spawn(Position::new(120.0, 64.0, 5.0));
+        world.sync_spatial();
+
+        let sent = world.send_within(Position::new(5.0, 64.0, 5.0), 8.0, Sound);
+        assert_eq!(sent, 2);
+        // The queued event entities carry a Target, so they stay out of the index
+        assert_eq!(world.sync_spatial(), 3);
+    }
+}
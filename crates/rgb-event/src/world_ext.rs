@@ -12,6 +12,7 @@ use crate::Event;
 use crate::color::cell_color;
 use crate::observer::{ObserverBuilder, ObserverId, ObserverInfo};
 use crate::queue::{EventQueue, QueuedEvent};
+use crate::route::{EventPhase, route_event};
 
 /// Target component - marks which entity an event is targeting.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -82,6 +83,22 @@ impl EventSystem {
         id
     }
 
+    /// Push an event into the queue for its phase.
+    pub fn push(&self, event: QueuedEvent, phase: EventPhase) {
+        self.inner.write().queue.push(event, phase);
+    }
+
+    /// Take every event currently queued for a phase.
+    pub fn take_batch(&self, phase: EventPhase) -> Vec<QueuedEvent> {
+        self.inner.write().queue.take_batch(phase)
+    }
+
+    /// Get the number of events queued for a phase.
+    #[must_use]
+    pub fn phase_len(&self, phase: EventPhase) -> usize {
+        self.inner.read().queue.phase_len(phase)
+    }
+
     /// Push a global event to the queue.
     pub fn push_global(&self, event: QueuedEvent) {
         self.inner.write().queue.push_global(event);
@@ -128,7 +145,7 @@ pub trait EventWorldExt {
 
     /// Send an event to a target entity.
     ///
-    /// - If target is `Entity::WORLD`, the event is global (sequential).
+    /// - If target is `Entity::WORLD` or has no `Position`, the event is global (sequential).
     /// - Otherwise, the event is scheduled by the target's position color (RGB parallel).
     fn send<E: Event + Clone>(&mut self, target: Entity, event: E);
 
@@ -162,6 +179,16 @@ pub trait EventWorldExt {
     /// ```
     fn flush_events(&mut self);
 
+    /// Drain the events queued for a single phase.
+    ///
+    /// This is the scheduler hook: the tick loop calls it once per phase in
+    /// [`EventPhase::ORDER`] (plus a final global pass), with its own
+    /// barriers in between. Events queued for the same phase while it is
+    /// draining are processed before returning.
+    ///
+    /// Returns the number of events processed.
+    fn flush_phase(&mut self, phase: EventPhase) -> usize;
+
     /// Get the event system handle.
     fn events(&self) -> Option<EventSystem>;
 }
@@ -188,17 +215,7 @@ impl EventWorldExt for World {
             event_type_id: TypeId::of::<E>(),
         };
 
-        if target == Entity::WORLD {
-            // Global event - sequential processing
-            sys.push_global(queued);
-        } else {
-            // Targeted event - get target's position for RGB scheduling
-            let color = self
-                .get::<Position>(target)
-                .map_or(Color::Red, |p| p.color());
-
-            sys.push_colored(queued, color);
-        }
+        sys.push(queued, route_event(self, event_entity, target));
     }
 
     fn send_at<E: Event + Clone>(&mut self, pos: Position, event: E) {
@@ -217,7 +234,7 @@ impl EventWorldExt for World {
             event_type_id: TypeId::of::<E>(),
         };
 
-        sys.push_colored(queued, pos.color());
+        sys.push(queued, route_event(self, event_entity, Entity::WORLD));
     }
 
     fn observe<E, F>(&mut self, callback: F) -> ObserverId
@@ -233,50 +250,36 @@ impl EventWorldExt for World {
     }
 
     fn flush_events(&mut self) {
-        // Phase 1: Process global events
-        flush_global_events(self);
-
-        // Phase 2-4: Process RGB events in order (R → G → B)
-        for color in Color::ALL {
-            flush_color_events(self, color);
+        // Phases 1-4: global, then R → G → B
+        for phase in EventPhase::ORDER {
+            self.flush_phase(phase);
         }
 
         // Phase 5: Process any new global events added during RGB phases
-        flush_global_events(self);
+        self.flush_phase(EventPhase::Global);
     }
 
-    fn events(&self) -> Option<EventSystem> {
-        self.get::<EventSystem>(Entity::WORLD)
-    }
-}
-
-/// Flush all global events.
-fn flush_global_events(world: &mut World) {
-    loop {
-        let Some(sys) = world.get::<EventSystem>(Entity::WORLD) else {
-            return;
-        };
+    fn flush_phase(&mut self, phase: EventPhase) -> usize {
+        let mut processed = 0;
+        loop {
+            let Some(sys) = self.get::<EventSystem>(Entity::WORLD) else {
+                return processed;
+            };
 
-        let Some(queued) = sys.pop_global() else {
-            return;
-        };
+            let batch = sys.take_batch(phase);
+            if batch.is_empty() {
+                return processed;
+            }
 
-        process_event(world, queued);
+            processed += batch.len();
+            for queued in batch {
+                process_event(self, queued);
+            }
+        }
     }
-}
 
-/// Flush all events for a specific color.
-fn flush_color_events(world: &mut World, color: Color) {
-    loop {
-        let Some(sys) = world.get::<EventSystem>(Entity::WORLD) else {
-            return;
-        };
-
-        let Some(queued) = sys.pop_colored(color) else {
-            return;
-        };
-
-        process_event(world, queued);
+    fn events(&self) -> Option<EventSystem> {
+        self.get::<EventSystem>(Entity::WORLD)
     }
 }
 
@@ -356,6 +359,37 @@ mod tests {
         assert_eq!(sys.color_len(Color::Red), 1);
     }
 
+    #[test]
+    fn test_send_to_target_without_position_is_global() {
+        let mut world = World::new();
+        world.init_events();
+
+        let target = world.spawn_empty();
+        world.send(target, TestEvent { value: 42 });
+
+        let sys = world.events().unwrap();
+        assert_eq!(sys.global_len(), 1);
+        assert_eq!(sys.color_len(Color::Red), 0);
+    }
+
+    #[test]
+    fn test_flush_single_phase() {
+        let mut world = World::new();
+        world.init_events();
+
+        let red = world.spawn(Position::new(0.0, 64.0, 0.0));
+        let green = world.spawn(Position::new(16.0, 64.0, 0.0));
+        world.send(red, TestEvent { value: 1 });
+        world.send(red, TestEvent { value: 2 });
+        world.send(green, TestEvent { value: 3 });
+
+        assert_eq!(world.flush_phase(EventPhase::Color(Color::Red)), 2);
+
+        let sys = world.events().unwrap();
+        assert_eq!(sys.phase_len(EventPhase::Color(Color::Red)), 0);
+        assert_eq!(sys.phase_len(EventPhase::Color(Color::Green)), 1);
+    }
+
     #[test]
     fn test_send_at_position() {
         let mut world = World::new();
@@ -1,7 +1,7 @@
 use flecs_ecs::prelude::*;
 use rgb_core::{
-    Active, CHUNK_SIZE, CellData, ChunkIndex, ChunkPos, Direction, Dirty, NextCellData, SimColor,
-    get_neighbor, link_chunk_neighbors, spawn_chunk,
+    Active, CHUNK_SIZE, CellData, ChunkIndex, ChunkPos, Direction, Dirty, Neighborhood,
+    PaddedCells, get_neighbor, link_chunk_neighbors, register_automaton, spawn_chunk,
 };
 use std::collections::HashSet;
 
@@ -152,32 +152,21 @@ pub fn check_edge_activity(cells: &CellData) -> HashSet<Direction> {
     needs_neighbors
 }
 
+/// Conway's Game of Life (B3/S23)
+pub fn life_rule(neighborhood: &Neighborhood) -> bool {
+    matches!(
+        (neighborhood.center(), neighborhood.alive_neighbors()),
+        (true, 2 | 3) | (false, 3)
+    )
+}
+
 /// Compute the next generation for a chunk
 pub fn compute_next_generation(
     world: &World,
     chunk_entity: Entity,
-    chunk_pos: ChunkPos,
     cells: &CellData,
 ) -> [[bool; CHUNK_SIZE]; CHUNK_SIZE] {
-    let mut next = [[false; CHUNK_SIZE]; CHUNK_SIZE];
-
-    for y in 0..CHUNK_SIZE {
-        for x in 0..CHUNK_SIZE {
-            let alive = cells.get(x, y);
-            let neighbors =
-                count_neighbors(world, chunk_entity, chunk_pos, cells, x as i32, y as i32);
-
-            let next_alive = match (alive, neighbors) {
-                (true, 2) | (true, 3) => true,
-                (false, 3) => true,
-                _ => false,
-            };
-
-            next[y][x] = next_alive;
-        }
-    }
-
-    next
+    PaddedCells::gather(world, chunk_entity, cells).step(&life_rule)
 }
 
 /// Expand the world by creating empty neighbor chunks where needed
@@ -213,47 +202,7 @@ pub fn expand_world(world: &World, index: &mut ChunkIndex) {
 
 /// Register Game of Life systems with the world
 pub fn register_life_systems(world: &World) {
-    // System to compute next generation for all active chunks
-    // We process by color to ensure no data races (chunks of same color don't share edges)
-    world
-        .system_named::<(&ChunkPos, &CellData, &mut NextCellData, &SimColor)>("ComputeNextGen")
-        .with(Active)
-        .each_entity(|e, (pos, cells, next, color)| {
-            // We use the color for parallel safety but process all in one system
-            // The Flecs scheduler handles the parallelism
-            let _ = color; // Color is used for grouping, not logic
-
-            let world = e.world();
-            next.cells = compute_next_generation(&world, e.id(), *pos, cells);
-        });
-
-    // System to swap buffers (copy next -> current)
-    world
-        .system_named::<(&mut CellData, &NextCellData)>("SwapBuffers")
-        .with(Active)
-        .each(|(cells, next)| {
-            cells.cells = next.cells;
-        });
-
-    // System to mark changed chunks as dirty
-    world
-        .system_named::<&CellData>("MarkDirty")
-        .with(Active)
-        .without(Dirty)
-        .each_entity(|e, _cells| {
-            e.add(Dirty);
-        });
-
-    // System to update Active status based on cell count
-    world
-        .system_named::<&CellData>("UpdateActive")
-        .each_entity(|e, cells| {
-            if cells.is_empty() {
-                e.remove(Active);
-            } else if !e.has(Active) {
-                e.add(Active);
-            }
-        });
+    register_automaton(world, "Life", life_rule);
 }
 
 #[cfg(test)]
@@ -280,9 +229,8 @@ mod tests {
         cells.set(9, 7, true);
 
         let (world, entity) = setup_world_with_chunk(cells.clone());
-        let pos = ChunkPos::new(0, 0);
 
-        let next = compute_next_generation(&world, entity, pos, &cells);
+        let next = compute_next_generation(&world, entity, &cells);
 
         assert!(!next[7][7]);
         assert!(next[6][8]);
@@ -300,9 +248,8 @@ mod tests {
         cells.set(1, 1, true);
 
         let (world, entity) = setup_world_with_chunk(cells.clone());
-        let pos = ChunkPos::new(0, 0);
 
-        let next = compute_next_generation(&world, entity, pos, &cells);
+        let next = compute_next_generation(&world, entity, &cells);
 
         assert!(next[0][0]);
         assert!(next[0][1]);
@@ -1,4 +1,21 @@
 //! Spatial grid with RGB coloring.
+//!
+//! The grid also indexes entities by position, for range queries like "who
+//! hears this sound" or "what can this player see":
+//!
+//! ```ignore
+//! grid.update_entity(zombie, 40.0, 72.5);
+//!
+//! let nearby: Vec<Entity> = grid.entities_within((38.0, 70.0), 16.0).collect();
+//! let in_view: Vec<Entity> = grid.entities_in_aabb((0.0, 0.0), (128.0, 128.0)).collect();
+//! ```
+//!
+//! Entities outside the grid are kept in its border cells, so queries are
+//! still exact there, only slower.
+
+use hashbrown::HashMap;
+use rgb_ecs::Entity;
+use smallvec::SmallVec;
 
 use crate::{Cell, CellId, Color};
 
@@ -12,6 +29,10 @@ pub struct SpatialGrid {
     pub cell_size: f32,
     /// All cells.
     cells: Vec<Cell>,
+    /// Entities in each cell, parallel to `cells`.
+    members: Vec<SmallVec<[Entity; 4]>>,
+    /// Cell and position of each indexed entity.
+    entities: HashMap<Entity, (CellId, (f32, f32))>,
 }
 
 impl SpatialGrid {
@@ -33,7 +54,9 @@ impl SpatialGrid {
             width,
             height,
             cell_size,
+            members: vec![SmallVec::new(); cells.len()],
             cells,
+            entities: HashMap::new(),
         }
     }
 
@@ -92,6 +115,120 @@ impl SpatialGrid {
         }
     }
 
+    /// Grid coordinates of the cell containing a world position, clamped to
+    /// the grid.
+    fn clamped_coords(&self, world_x: f32, world_y: f32) -> (u32, u32) {
+        let clamp = |v: f32, cells: u32| {
+            ((v / self.cell_size).floor().max(0.0) as u32).min(cells.saturating_sub(1))
+        };
+        (clamp(world_x, self.width), clamp(world_y, self.height))
+    }
+
+    /// Move `entity` to a world position, indexing it if it wasn't.
+    ///
+    /// Positions outside the grid are kept in the nearest border cell.
+    pub fn update_entity(&mut self, entity: Entity, world_x: f32, world_y: f32) {
+        if self.cells.is_empty() {
+            return;
+        }
+        let (x, y) = self.clamped_coords(world_x, world_y);
+        let cell = CellId(y * self.width + x);
+        let position = (world_x, world_y);
+
+        match self.entities.insert(entity, (cell, position)) {
+            Some((old, _)) if old == cell => {}
+            Some((old, _)) => {
+                self.members[old.0 as usize].retain(|&mut e| e != entity);
+                self.members[cell.0 as usize].push(entity);
+            }
+            None => self.members[cell.0 as usize].push(entity),
+        }
+    }
+
+    /// Stop indexing `entity`.
+    ///
+    /// Returns `false` if it wasn't indexed.
+    pub fn remove_entity(&mut self, entity: Entity) -> bool {
+        let Some((cell, _)) = self.entities.remove(&entity) else {
+            return false;
+        };
+        self.members[cell.0 as usize].retain(|&mut e| e != entity);
+        true
+    }
+
+    /// Keep only the indexed entities for which `keep` returns `true`.
+    pub fn retain_entities(&mut self, mut keep: impl FnMut(Entity) -> bool) {
+        let removed: Vec<Entity> = self
+            .entities
+            .keys()
+            .copied()
+            .filter(|&entity| !keep(entity))
+            .collect();
+        for entity in removed {
+            self.remove_entity(entity);
+        }
+    }
+
+    /// Indexed position of `entity`.
+    #[must_use]
+    pub fn entity_position(&self, entity: Entity) -> Option<(f32, f32)> {
+        self.entities.get(&entity).map(|&(_, position)| position)
+    }
+
+    /// Number of indexed entities.
+    #[must_use]
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Entities in the cells overlapping a world-space box, with their
+    /// positions.
+    fn candidates(
+        &self,
+        min: (f32, f32),
+        max: (f32, f32),
+    ) -> impl Iterator<Item = (Entity, (f32, f32))> + '_ {
+        let ((x0, y0), (x1, y1)) = if self.cells.is_empty() || min.0 > max.0 || min.1 > max.1 {
+            ((1, 1), (0, 0))
+        } else {
+            (
+                self.clamped_coords(min.0, min.1),
+                self.clamped_coords(max.0, max.1),
+            )
+        };
+        (y0..=y1)
+            .flat_map(move |y| (x0..=x1).map(move |x| (y * self.width + x) as usize))
+            .flat_map(|index| self.members[index].iter())
+            .map(|&entity| (entity, self.entities[&entity].1))
+    }
+
+    /// Entities inside a world-space box, bounds included.
+    pub fn entities_in_aabb(
+        &self,
+        min: (f32, f32),
+        max: (f32, f32),
+    ) -> impl Iterator<Item = Entity> + '_ {
+        self.candidates(min, max)
+            .filter(move |&(_, (x, y))| min.0 <= x && x <= max.0 && min.1 <= y && y <= max.1)
+            .map(|(entity, _)| entity)
+    }
+
+    /// Entities at most `radius` away from `center`.
+    pub fn entities_within(
+        &self,
+        center: (f32, f32),
+        radius: f32,
+    ) -> impl Iterator<Item = Entity> + '_ {
+        let min = (center.0 - radius, center.1 - radius);
+        let max = (center.0 + radius, center.1 + radius);
+        self.candidates(min, max)
+            .filter(move |&(_, (x, y))| {
+                let (dx, dy) = (x - center.0, y - center.1);
+                dx.mul_add(dx, dy * dy) <= radius * radius
+            })
+            .map(|(entity, _)| entity)
+    }
+
     /// Get total number of cells.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -163,4 +300,70 @@ mod tests {
         assert_eq!(blue_count, 27);
         assert_eq!(red_count + green_count + blue_count, 81);
     }
+
+    fn sorted(entities: impl Iterator<Item = Entity>) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = entities.collect();
+        entities.sort_unstable_by_key(|entity| entity.to_bits());
+        entities
+    }
+
+    #[test]
+    fn test_entities_in_aabb() {
+        let mut grid = SpatialGrid::new(10, 10, 16.0);
+        let near = Entity::from_bits(1);
+        let edge = Entity::from_bits(2);
+        let far = Entity::from_bits(3);
+        grid.update_entity(near, 20.0, 20.0);
+        grid.update_entity(edge, 40.0, 20.0);
+        grid.update_entity(far, 100.0, 100.0);
+
+        let found = sorted(grid.entities_in_aabb((10.0, 10.0), (40.0, 30.0)));
+        assert_eq!(found, [near, edge]);
+        // Same cells, but the box stops short of `edge`
+        let found = sorted(grid.entities_in_aabb((10.0, 10.0), (39.0, 30.0)));
+        assert_eq!(found, [near]);
+        assert_eq!(grid.entities_in_aabb((30.0, 30.0), (10.0, 10.0)).count(), 0);
+    }
+
+    #[test]
+    fn test_entities_within_radius() {
+        let mut grid = SpatialGrid::new(10, 10, 16.0);
+        let inside = Entity::from_bits(1);
+        let corner = Entity::from_bits(2);
+        grid.update_entity(inside, 50.0, 58.0);
+        // Inside the bounding box of the circle, but not the circle
+        grid.update_entity(corner, 57.0, 57.0);
+
+        let found = sorted(grid.entities_within((50.0, 50.0), 8.0));
+        assert_eq!(found, [inside]);
+    }
+
+    #[test]
+    fn test_moving_and_removing_entities() {
+        let mut grid = SpatialGrid::new(10, 10, 16.0);
+        let entity = Entity::from_bits(1);
+        grid.update_entity(entity, 8.0, 8.0);
+        grid.update_entity(entity, 150.0, 150.0);
+
+        assert_eq!(grid.entity_count(), 1);
+        assert_eq!(grid.entities_within((8.0, 8.0), 4.0).count(), 0);
+        assert_eq!(grid.entities_within((150.0, 150.0), 4.0).count(), 1);
+
+        assert!(grid.remove_entity(entity));
+        assert!(!grid.remove_entity(entity));
+        assert_eq!(grid.entities_within((150.0, 150.0), 4.0).count(), 0);
+    }
+
+    #[test]
+    fn test_entities_outside_the_grid() {
+        let mut grid = SpatialGrid::new(4, 4, 16.0);
+        let entity = Entity::from_bits(1);
+        grid.update_entity(entity, -100.0, 500.0);
+
+        assert_eq!(grid.entities_within((-100.0, 500.0), 1.0).count(), 1);
+        assert_eq!(grid.entities_in_aabb((0.0, 0.0), (64.0, 64.0)).count(), 0);
+
+        grid.retain_entities(|e| e != entity);
+        assert_eq!(grid.entity_count(), 0);
+    }
 }
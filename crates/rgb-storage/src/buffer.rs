@@ -11,8 +11,11 @@ use rgb_ecs::{ComponentId, Entity};
 
 use crate::ComponentKey;
 
+const TAG_SET: u8 = 0;
+const TAG_REMOVE: u8 = 1;
+
 /// A mutation to be persisted.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Mutation {
     /// Insert or update a component.
     Set { key: ComponentKey, data: Vec<u8> },
@@ -45,6 +48,52 @@ impl Mutation {
             Self::Set { key, .. } | Self::Remove { key } => key,
         }
     }
+
+    /// Append this mutation to a write-ahead log record.
+    ///
+    /// Format: tag (0 = set, 1 = remove), key, then for sets the data length
+    /// (u32 LE) and data.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Set { key, data } => {
+                out.push(TAG_SET);
+                out.extend_from_slice(key.as_bytes());
+                out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                out.extend_from_slice(data);
+            }
+            Self::Remove { key } => {
+                out.push(TAG_REMOVE);
+                out.extend_from_slice(key.as_bytes());
+            }
+        }
+    }
+
+    /// Decode every mutation of a write-ahead log record.
+    ///
+    /// Returns `None` if the record is malformed.
+    #[must_use]
+    pub fn decode_all(mut bytes: &[u8]) -> Option<Vec<Self>> {
+        let key_len = std::mem::size_of::<ComponentKey>();
+        let mut mutations = Vec::new();
+        while let Some((&tag, rest)) = bytes.split_first() {
+            let key = ComponentKey::from_bytes(rest.get(..key_len)?)?;
+            let rest = &rest[key_len..];
+            match tag {
+                TAG_SET => {
+                    let len = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+                    let data = rest.get(4..4 + len)?.to_vec();
+                    mutations.push(Self::Set { key, data });
+                    bytes = &rest[4 + len..];
+                }
+                TAG_REMOVE => {
+                    mutations.push(Self::Remove { key });
+                    bytes = rest;
+                }
+                _ => return None,
+            }
+        }
+        Some(mutations)
+    }
 }
 
 /// Thread-local mutation buffers.
@@ -168,6 +217,28 @@ mod tests {
         assert_eq!(remove.key().component_id(), comp_id);
     }
 
+    #[test]
+    fn test_log_record_roundtrip() {
+        let entity = Entity::new(7, Generation::new());
+        let comp_id = ComponentId::from_raw(3);
+        let mutations = [
+            Mutation::set(entity, comp_id, &TestComponent { value: 5 }),
+            Mutation::remove(entity, comp_id),
+        ];
+
+        let mut record = Vec::new();
+        for mutation in &mutations {
+            mutation.encode(&mut record);
+        }
+        let decoded = Mutation::decode_all(&record).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert!(matches!(&decoded[0], Mutation::Set { data, .. } if data == &5u32.to_le_bytes()));
+        assert_eq!(decoded[1].key(), mutations[1].key());
+
+        // A truncated record is rejected rather than misread
+        assert!(Mutation::decode_all(&record[..record.len() - 2]).is_none());
+    }
+
     #[test]
     fn test_multi_thread() {
         use std::sync::Arc;
@@ -0,0 +1,642 @@
+//! Differences between two world snapshots.
+//!
+//! A snapshot is the raw state [`VersionedWorld::replay`] rebuilds: every
+//! `(entity, component)` key with its component's bytes. [`world_diff`]
+//! compares two of them, e.g. two saves or two ticks of one save, and
+//! reports which entities appeared, disappeared or changed, and for changed
+//! entities which components did:
+//!
+//! ```ignore
+//! let diff = world.diff_ticks(100, 200, &layouts)?;
+//! for entity in &diff.entities {
+//!     println!("{entity}");
+//! }
+//! ```
+//!
+//! Component bytes carry no schema, so a changed component is reported as
+//! the byte ranges that differ, unless a [`ComponentLayout`] for it says
+//! where its fields are. Layouts are registered in [`ComponentLayouts`], or
+//! written one component per line for the `rgb-world-diff` CLI:
+//!
+//! ```text
+//! # component-id name field:kind...
+//! 1 Position x:f32 y:f32 z:f32
+//! 2 Health current:u32 max:u32
+//! ```
+//!
+//! Fields are laid out like a `#[repr(C)]` struct, in order and aligned.
+//!
+//! [`VersionedWorld::replay`]: crate::VersionedWorld::replay
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use rgb_ecs::Entity;
+
+use crate::{ComponentKey, StorageResult, TickId, VersionedWorld};
+
+/// Raw world state: component bytes by key.
+pub type Snapshot = BTreeMap<ComponentKey, Vec<u8>>;
+
+/// Primitive type of a component field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    Bool,
+    /// Opaque bytes, aligned to 1 (`bytesN` in layout files).
+    Bytes(usize),
+}
+
+impl FieldKind {
+    /// Size in bytes.
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::U8 | Self::I8 | Self::Bool => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+            Self::Bytes(len) => len,
+        }
+    }
+
+    /// Alignment in a `#[repr(C)]` struct.
+    #[must_use]
+    pub const fn align(self) -> usize {
+        match self {
+            Self::Bytes(_) => 1,
+            _ => self.size(),
+        }
+    }
+
+    /// Read a value of this kind from the start of `bytes`.
+    #[must_use]
+    pub fn read(self, bytes: &[u8]) -> Option<FieldValue> {
+        let bytes = bytes.get(..self.size())?;
+        let value = match self {
+            Self::U8 => FieldValue::Unsigned(bytes[0].into()),
+            Self::U16 => FieldValue::Unsigned(u16::from_le_bytes(bytes.try_into().ok()?).into()),
+            Self::U32 => FieldValue::Unsigned(u32::from_le_bytes(bytes.try_into().ok()?).into()),
+            Self::U64 => FieldValue::Unsigned(u64::from_le_bytes(bytes.try_into().ok()?)),
+            Self::I8 => FieldValue::Signed(i8::from_le_bytes(bytes.try_into().ok()?).into()),
+            Self::I16 => FieldValue::Signed(i16::from_le_bytes(bytes.try_into().ok()?).into()),
+            Self::I32 => FieldValue::Signed(i32::from_le_bytes(bytes.try_into().ok()?).into()),
+            Self::I64 => FieldValue::Signed(i64::from_le_bytes(bytes.try_into().ok()?)),
+            Self::F32 => FieldValue::Float(f32::from_le_bytes(bytes.try_into().ok()?).into()),
+            Self::F64 => FieldValue::Float(f64::from_le_bytes(bytes.try_into().ok()?)),
+            Self::Bool => FieldValue::Bool(bytes[0] != 0),
+            Self::Bytes(_) => FieldValue::Bytes(bytes.to_vec()),
+        };
+        Some(value)
+    }
+}
+
+impl FromStr for FieldKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "bool" => Self::Bool,
+            _ => Self::Bytes(
+                s.strip_prefix("bytes")
+                    .and_then(|len| len.parse().ok())
+                    .ok_or_else(|| format!("unknown field kind: {s}"))?,
+            ),
+        })
+    }
+}
+
+/// Where one field of a component lives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: usize,
+    pub kind: FieldKind,
+}
+
+/// Field layout of a component's bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComponentLayout {
+    pub name: String,
+    pub fields: Vec<FieldLayout>,
+}
+
+impl ComponentLayout {
+    /// Lay out `fields` like a `#[repr(C)]` struct.
+    #[must_use]
+    pub fn repr_c(name: impl Into<String>, fields: &[(&str, FieldKind)]) -> Self {
+        let mut offset = 0usize;
+        let fields = fields
+            .iter()
+            .map(|&(name, kind)| {
+                offset = offset.next_multiple_of(kind.align());
+                let field = FieldLayout {
+                    name: name.to_string(),
+                    offset,
+                    kind,
+                };
+                offset += kind.size();
+                field
+            })
+            .collect();
+        Self {
+            name: name.into(),
+            fields,
+        }
+    }
+}
+
+/// Component layouts by raw component ID.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentLayouts {
+    layouts: HashMap<u32, ComponentLayout>,
+}
+
+impl ComponentLayouts {
+    /// No layouts: every component is diffed byte-wise.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the layout of a component.
+    #[must_use]
+    pub fn with(mut self, component: u32, layout: ComponentLayout) -> Self {
+        self.layouts.insert(component, layout);
+        self
+    }
+
+    /// The layout of a component, if registered.
+    #[must_use]
+    pub fn get(&self, component: u32) -> Option<&ComponentLayout> {
+        self.layouts.get(&component)
+    }
+
+    /// A component's name, or its ID without a layout.
+    #[must_use]
+    pub fn name(&self, component: u32) -> String {
+        self.get(component)
+            .map_or_else(|| format!("#{component}"), |layout| layout.name.clone())
+    }
+}
+
+impl FromStr for ComponentLayouts {
+    type Err = String;
+
+    /// Parse `id Name field:kind...` lines; blank lines and `#` comments
+    /// are skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layouts = Self::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {message}", number + 1);
+
+            let mut words = line.split_whitespace();
+            let id = words
+                .next()
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(|| error("expected a component ID"))?;
+            let name = words.next().ok_or_else(|| error("expected a name"))?;
+            let fields = words
+                .map(|field| {
+                    let (name, kind) = field
+                        .split_once(':')
+                        .ok_or_else(|| error(&format!("expected name:kind, got {field}")))?;
+                    Ok((name, kind.parse().map_err(|e: String| error(&e))?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            layouts = layouts.with(id, ComponentLayout::repr_c(name, &fields));
+        }
+        Ok(layouts)
+    }
+}
+
+/// A decoded field value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsigned(v) => write!(f, "{v}"),
+            Self::Signed(v) => write!(f, "{v}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::Bytes(bytes) => write_hex(f, bytes),
+        }
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+/// A field whose value differs between the snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDiff {
+    /// Field name, or the byte range (`bytes[4..8]`) without a layout.
+    pub field: String,
+    pub before: FieldValue,
+    pub after: FieldValue,
+}
+
+/// How a component differs between the snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComponentChange {
+    /// Only in the second snapshot, with its bytes.
+    Added(Vec<u8>),
+    /// Only in the first snapshot, with its bytes.
+    Removed(Vec<u8>),
+    /// In both, with these fields differing.
+    Changed(Vec<FieldDiff>),
+}
+
+/// A component that differs between the snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentDiff {
+    pub component: u32,
+    /// Layout name, or `#id` without one.
+    pub name: String,
+    pub change: ComponentChange,
+}
+
+/// How an entity differs between the snapshots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityChange {
+    /// Has no components in the first snapshot.
+    Added,
+    /// Has no components in the second snapshot.
+    Removed,
+    /// Has components in both.
+    Changed,
+}
+
+/// An entity that differs between the snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityDiff {
+    pub entity: Entity,
+    pub change: EntityChange,
+    /// Differing components, by component ID.
+    pub components: Vec<ComponentDiff>,
+}
+
+/// Differences between two snapshots, by entity.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorldDiff {
+    /// Differing entities, by entity bits.
+    pub entities: Vec<EntityDiff>,
+}
+
+impl WorldDiff {
+    /// Whether the snapshots are the same.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Entities of each kind of change: `(added, removed, changed)`.
+    #[must_use]
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let count = |change| self.entities.iter().filter(|e| e.change == change).count();
+        (
+            count(EntityChange::Added),
+            count(EntityChange::Removed),
+            count(EntityChange::Changed),
+        )
+    }
+}
+
+/// Compare two snapshots byte-wise.
+#[must_use]
+pub fn world_diff(a: &Snapshot, b: &Snapshot) -> WorldDiff {
+    world_diff_with(a, b, &ComponentLayouts::new())
+}
+
+/// Compare two snapshots, decoding the fields of components with a layout.
+#[must_use]
+pub fn world_diff_with(a: &Snapshot, b: &Snapshot, layouts: &ComponentLayouts) -> WorldDiff {
+    let keys: BTreeSet<ComponentKey> = a.keys().chain(b.keys()).copied().collect();
+
+    let mut entities: Vec<EntityDiff> = Vec::new();
+    let mut present: BTreeMap<u64, (bool, bool)> = BTreeMap::new();
+    for key in &keys {
+        let (before, after) = (a.get(key), b.get(key));
+        let seen = present.entry(key.entity_bits()).or_default();
+        seen.0 |= before.is_some();
+        seen.1 |= after.is_some();
+
+        let change = match (before, after) {
+            (Some(before), Some(after)) if before == after => continue,
+            (Some(before), Some(after)) => ComponentChange::Changed(field_diffs(
+                before,
+                after,
+                layouts.get(key.component_raw()),
+            )),
+            (None, Some(after)) => ComponentChange::Added(after.clone()),
+            (Some(before), None) => ComponentChange::Removed(before.clone()),
+            (None, None) => continue,
+        };
+
+        let entity_bits = key.entity_bits();
+        if entities
+            .last()
+            .is_none_or(|last| last.entity.to_bits() != entity_bits)
+        {
+            entities.push(EntityDiff {
+                entity: key.entity(),
+                change: EntityChange::Changed,
+                components: Vec::new(),
+            });
+        }
+        if let Some(entity) = entities.last_mut() {
+            entity.components.push(ComponentDiff {
+                component: key.component_raw(),
+                name: layouts.name(key.component_raw()),
+                change,
+            });
+        }
+    }
+
+    for entity in &mut entities {
+        entity.change = match present.get(&entity.entity.to_bits()) {
+            Some((false, _)) => EntityChange::Added,
+            Some((_, false)) => EntityChange::Removed,
+            _ => EntityChange::Changed,
+        };
+    }
+    WorldDiff { entities }
+}
+
+fn field_diffs(before: &[u8], after: &[u8], layout: Option<&ComponentLayout>) -> Vec<FieldDiff> {
+    if let Some(layout) = layout.filter(|_| before.len() == after.len()) {
+        let mut diffs: Vec<FieldDiff> = layout
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let before = field.kind.read(before.get(field.offset..)?)?;
+                let after = field.kind.read(after.get(field.offset..)?)?;
+                (before != after).then(|| FieldDiff {
+                    field: field.name.clone(),
+                    before,
+                    after,
+                })
+            })
+            .collect();
+        // Bytes the layout doesn't cover (padding, or a stale layout)
+        let covered = |i: usize| {
+            layout
+                .fields
+                .iter()
+                .any(|field| (field.offset..field.offset + field.kind.size()).contains(&i))
+        };
+        diffs.extend(
+            byte_diffs(before, after)
+                .into_iter()
+                .filter(|range| range.clone().any(|i| !covered(i)))
+                .map(|range| range_diff(before, after, range)),
+        );
+        return diffs;
+    }
+
+    if before.len() != after.len() {
+        return vec![FieldDiff {
+            field: format!("bytes[..{}]", before.len().max(after.len())),
+            before: FieldValue::Bytes(before.to_vec()),
+            after: FieldValue::Bytes(after.to_vec()),
+        }];
+    }
+    byte_diffs(before, after)
+        .into_iter()
+        .map(|range| range_diff(before, after, range))
+        .collect()
+}
+
+/// Maximal runs of differing bytes.
+fn byte_diffs(before: &[u8], after: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, (x, y)) in before.iter().zip(after).enumerate() {
+        match (x != y, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push(s..before.len().min(after.len()));
+    }
+    ranges
+}
+
+fn range_diff(before: &[u8], after: &[u8], range: Range<usize>) -> FieldDiff {
+    FieldDiff {
+        field: format!("bytes[{}..{}]", range.start, range.end),
+        before: FieldValue::Bytes(before[range.clone()].to_vec()),
+        after: FieldValue::Bytes(after[range].to_vec()),
+    }
+}
+
+impl fmt::Display for EntityDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.change {
+            EntityChange::Added => '+',
+            EntityChange::Removed => '-',
+            EntityChange::Changed => '~',
+        };
+        write!(f, "{sign} entity {}", self.entity)?;
+        for component in &self.components {
+            match &component.change {
+                ComponentChange::Added(bytes) => {
+                    write!(f, "\n  + {}: ", component.name)?;
+                    write_hex(f, bytes)?;
+                }
+                ComponentChange::Removed(bytes) => {
+                    write!(f, "\n  - {}: ", component.name)?;
+                    write_hex(f, bytes)?;
+                }
+                ComponentChange::Changed(fields) => {
+                    for field in fields {
+                        write!(
+                            f,
+                            "\n  ~ {}.{}: {} -> {}",
+                            component.name, field.field, field.before, field.after
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for WorldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entity in &self.entities {
+            writeln!(f, "{entity}")?;
+        }
+        let (added, removed, changed) = self.counts();
+        write!(f, "{added} added, {removed} removed, {changed} changed")
+    }
+}
+
+impl VersionedWorld {
+    /// Compare the raw state at two committed ticks.
+    pub fn diff_ticks(
+        &self,
+        a: TickId,
+        b: TickId,
+        layouts: &ComponentLayouts,
+    ) -> StorageResult<WorldDiff> {
+        Ok(world_diff_with(&self.replay(a)?, &self.replay(b)?, layouts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mutation;
+
+    fn position(x: f32, y: f32, z: f32) -> Vec<u8> {
+        [x, y, z].iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_entity_and_component_changes() {
+        let a = Snapshot::from([
+            (ComponentKey::from_raw(1, 1), position(0.0, 64.0, 0.0)),
+            (ComponentKey::from_raw(1, 2), vec![20]),
+            (ComponentKey::from_raw(2, 1), position(5.0, 5.0, 5.0)),
+        ]);
+        let b = Snapshot::from([
+            (ComponentKey::from_raw(1, 1), position(0.0, 65.5, 0.0)),
+            (ComponentKey::from_raw(1, 3), vec![1]),
+            (ComponentKey::from_raw(3, 1), position(1.0, 2.0, 3.0)),
+        ]);
+        let layouts = "1 Position x:f32 y:f32 z:f32".parse().unwrap();
+
+        let diff = world_diff_with(&a, &b, &layouts);
+        assert_eq!(diff.counts(), (1, 1, 1));
+
+        let changed = &diff.entities[0];
+        assert_eq!(changed.entity.to_bits(), 1);
+        assert_eq!(changed.change, EntityChange::Changed);
+        assert_eq!(
+            changed.components[0].change,
+            ComponentChange::Changed(vec![FieldDiff {
+                field: "y".into(),
+                before: FieldValue::Float(64.0),
+                after: FieldValue::Float(65.5),
+            }])
+        );
+        assert_eq!(
+            changed.components[1].change,
+            ComponentChange::Removed(vec![20])
+        );
+        assert_eq!(changed.components[1].name, "#2");
+        assert_eq!(
+            changed.components[2].change,
+            ComponentChange::Added(vec![1])
+        );
+        assert_eq!(diff.entities[1].change, EntityChange::Removed);
+        assert_eq!(diff.entities[2].change, EntityChange::Added);
+
+        assert!(world_diff(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_byte_ranges_without_layout() {
+        let key = ComponentKey::from_raw(1, 1);
+        let a = Snapshot::from([(key, vec![1, 2, 3, 4, 5])]);
+        let b = Snapshot::from([(key, vec![1, 9, 9, 4, 6])]);
+
+        let diff = world_diff(&a, &b);
+        let ComponentChange::Changed(fields) = &diff.entities[0].components[0].change else {
+            panic!("expected a changed component");
+        };
+        let fields: Vec<_> = fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, ["bytes[1..3]", "bytes[4..5]"]);
+        assert_eq!(
+            diff.to_string(),
+            "~ entity 1v0\n  ~ #1.bytes[1..3]: 02 03 -> 09 09\n  ~ #1.bytes[4..5]: 05 -> 06\n\
+             0 added, 0 removed, 1 changed"
+        );
+    }
+
+    #[test]
+    fn test_layouts() {
+        let layouts: ComponentLayouts =
+            "# comment\n\n7 Mixed flag:bool value:f64 tag:bytes3 id:u32"
+                .parse()
+                .unwrap();
+        let offsets: Vec<_> = layouts
+            .get(7)
+            .unwrap()
+            .fields
+            .iter()
+            .map(|f| f.offset)
+            .collect();
+        assert_eq!(offsets, [0, 8, 16, 20]);
+        assert!("1 Position x:float".parse::<ComponentLayouts>().is_err());
+        assert!("Position x:f32".parse::<ComponentLayouts>().is_err());
+    }
+
+    #[test]
+    fn test_diff_ticks() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = ComponentKey::from_raw(1, 1);
+        let mut world = VersionedWorld::open(dir.path()).unwrap();
+        world.push(Mutation::Set { key, data: vec![1] });
+        let first = world.commit_tick().unwrap();
+        world.push(Mutation::Remove { key });
+        let second = world.commit_tick().unwrap();
+
+        let diff = world
+            .diff_ticks(first, second, &ComponentLayouts::new())
+            .unwrap();
+        assert_eq!(diff.counts(), (0, 1, 0));
+        assert!(
+            world
+                .diff_ticks(first, second + 1, &ComponentLayouts::new())
+                .is_err()
+        );
+    }
+}
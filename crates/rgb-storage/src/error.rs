@@ -21,6 +21,19 @@ pub enum StorageError {
     #[error("invalid tick: {0}")]
     InvalidTick(crate::TickId),
 
+    /// A write-ahead log record couldn't be decoded.
+    #[error("corrupt log record at tick {0}")]
+    CorruptLog(crate::TickId),
+
+    /// The writer thread failed to write a committed tick, and stopped.
+    #[error("failed to write tick {tick}: {message}")]
+    WriteFailed {
+        /// The tick that wasn't written.
+        tick: crate::TickId,
+        /// Why.
+        message: String,
+    },
+
     /// IO error.
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
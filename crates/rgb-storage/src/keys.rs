@@ -52,6 +52,31 @@ impl ComponentKey {
         }
     }
 
+    /// Create a key from raw parts, for callers with their own entity and
+    /// component numbering.
+    #[inline]
+    #[must_use]
+    pub const fn from_raw(entity_bits: u64, component_id: u32) -> Self {
+        Self {
+            entity_bits,
+            component_id,
+        }
+    }
+
+    /// Get the raw entity bits from this key.
+    #[inline]
+    #[must_use]
+    pub const fn entity_bits(&self) -> u64 {
+        self.entity_bits
+    }
+
+    /// Get the raw component ID from this key.
+    #[inline]
+    #[must_use]
+    pub const fn component_raw(&self) -> u32 {
+        self.component_id
+    }
+
     /// Get the entity from this key.
     #[inline]
     #[must_use]
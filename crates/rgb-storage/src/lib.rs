@@ -53,17 +53,22 @@
 //!
 //! // Or read state at any tick without reverting
 //! let old_health = world.get_at_tick::<Health>(player, tick)?;
+//!
+//! // Or see what changed between two ticks
+//! let diff = world.diff_ticks(tick - 100, tick, &ComponentLayouts::new())?;
 //! ```
 
 mod buffer;
+pub mod diff;
 mod error;
 mod keys;
 mod versioned_world;
 
 pub use buffer::{Mutation, MutationBuffers};
+pub use diff::{ComponentLayouts, WorldDiff, world_diff, world_diff_with};
 pub use error::{StorageError, StorageResult};
 pub use keys::ComponentKey;
-pub use versioned_world::VersionedWorld;
+pub use versioned_world::{DEFAULT_CHECKPOINT_INTERVAL, VersionedWorld};
 
 /// A tick identifier (monotonically increasing).
 pub type TickId = u64;
@@ -6,6 +6,35 @@
 //! - **get_at_tick**: Read any component at any historical tick
 //! - **revert_to_tick**: Jump the world back to any tick
 //! - **commit_tick**: Atomically commit all pending changes
+//! - **replay**: Rebuild the raw state at any retained tick from the
+//!   write-ahead log
+//!
+//! # Write-Ahead Log
+//!
+//! Besides the current value of every key, each commit that changes anything
+//! stores its mutations as one log record under `__log__` + the tick
+//! (big-endian). Replaying the records in order rebuilds the state at any
+//! committed tick, which is how crash recovery restores a world.
+//!
+//! # Checkpoints
+//!
+//! Every `checkpoint_interval` ticks (default
+//! [`DEFAULT_CHECKPOINT_INTERVAL`]) the full state is written as one record
+//! under `__checkpoint__` + the tick. Replays start from the nearest
+//! checkpoint at or before the target, so they cost at most an interval of
+//! log records rather than the whole uptime. The last two checkpoints are
+//! kept; log records at or before the oldest one are deleted, along with
+//! older checkpoints. Ticks before the oldest checkpoint can no longer be
+//! replayed ([`VersionedWorld::first_tick`]).
+//!
+//! # Background Writes
+//!
+//! `commit_tick` only hands the tick's mutations to a writer thread, which
+//! writes them to disk in order, so the tick thread never waits on the
+//! database unless the writer falls `WRITE_QUEUE` ticks behind. Reads of
+//! the database wait for the writer to catch up first ([`VersionedWorld::flush`]).
+//! A write that fails stops the writer; every later commit and read returns
+//! the error.
 //!
 //! # Thread-Local Buffers
 //!
@@ -13,16 +42,44 @@
 //! without synchronization, then call `commit_tick_from_buffers` after the
 //! barrier.
 
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::thread::JoinHandle;
+
 use nebari::tree::Root as _;
+use parking_lot::{Condvar, Mutex};
 use rgb_ecs::{Entity, World};
 
 use crate::{
     TickId,
     buffer::{Mutation, MutationBuffers},
-    error::StorageResult,
+    error::{StorageError, StorageResult},
     keys::ComponentKey,
 };
 
+/// Ticks between checkpoints unless opened with
+/// [`VersionedWorld::open_with_checkpoint_interval`]: a minute at 20 TPS.
+pub const DEFAULT_CHECKPOINT_INTERVAL: TickId = 1200;
+
+/// Checkpoints kept; log records at or before the oldest are deleted.
+const RETAINED_CHECKPOINTS: usize = 2;
+
+/// Committed ticks the writer thread may fall behind before `commit_tick`
+/// blocks.
+const WRITE_QUEUE: usize = 64;
+
+/// Key of the latest committed tick.
+const TICK_KEY: &[u8] = b"__tick__";
+
+/// Key of the retained checkpoint ticks, oldest first (u64 LE each).
+const CHECKPOINTS_KEY: &[u8] = b"__checkpoints__";
+
+/// The raw state at a tick: every key's value.
+type State = BTreeMap<ComponentKey, Vec<u8>>;
+
+type ComponentTree = nebari::Tree<nebari::tree::Versioned, nebari::io::fs::StdFile>;
+
 /// A world with versioned persistent storage.
 ///
 /// Every tick is recorded in the database, allowing time-travel queries
@@ -43,6 +100,12 @@ pub struct VersionedWorld {
     current_tick: TickId,
     /// Pending mutations for single-threaded usage.
     pending: Vec<Mutation>,
+    /// Committed ticks on their way to the writer thread.
+    batches: Option<SyncSender<Batch>>,
+    /// The writer thread, joined on drop.
+    writer: Option<JoinHandle<()>>,
+    /// What the writer thread has written so far.
+    shared: Arc<Shared>,
 }
 
 impl VersionedWorld {
@@ -51,6 +114,18 @@ impl VersionedWorld {
     /// If the database already exists, it will be opened and the world
     /// will be restored to the latest committed tick.
     pub fn open(path: impl AsRef<std::path::Path>) -> StorageResult<Self> {
+        Self::open_with_checkpoint_interval(path, DEFAULT_CHECKPOINT_INTERVAL)
+    }
+
+    /// Open a versioned world that checkpoints every `interval` ticks.
+    ///
+    /// A shorter interval makes replays cheaper and keeps less log, at the
+    /// cost of writing the full state more often. An interval of 0 is
+    /// treated as 1.
+    pub fn open_with_checkpoint_interval(
+        path: impl AsRef<std::path::Path>,
+        interval: TickId,
+    ) -> StorageResult<Self> {
         let config = nebari::Config::default_for(path.as_ref());
         let roots = config.open()?;
 
@@ -59,12 +134,37 @@ impl VersionedWorld {
 
         // Read the current tick from a metadata key
         let current_tick = tree
-            .get(b"__tick__")?
+            .get(TICK_KEY)?
             .map(|bytes| {
                 let arr: [u8; 8] = bytes.as_ref().try_into().unwrap_or([0; 8]);
                 u64::from_le_bytes(arr)
             })
             .unwrap_or(0);
+        let checkpoints = tree
+            .get(CHECKPOINTS_KEY)?
+            .map_or_else(|| vec![0], |bytes| decode_ticks(bytes.as_ref()));
+
+        // The writer keeps the latest state to write checkpoints from
+        let state = replay_from(&tree, &checkpoints, current_tick)?;
+        let shared = Arc::new(Shared {
+            progress: Mutex::new(Progress {
+                written: current_tick,
+                checkpoints: checkpoints.clone(),
+                failure: None,
+            }),
+            written: Condvar::new(),
+        });
+        let writer = Writer {
+            tree,
+            state,
+            checkpoints,
+            checkpoint_interval: interval.max(1),
+            shared: Arc::clone(&shared),
+        };
+        let (batches, received) = std::sync::mpsc::sync_channel(WRITE_QUEUE);
+        let writer = std::thread::Builder::new()
+            .name("rgb-storage-writer".to_string())
+            .spawn(move || writer.run(&received))?;
 
         // TODO: Restore world state from the tree
         // For now, start with empty world
@@ -75,6 +175,9 @@ impl VersionedWorld {
             roots,
             current_tick,
             pending: Vec::new(),
+            batches: Some(batches),
+            writer: Some(writer),
+            shared,
         })
     }
 
@@ -194,8 +297,9 @@ impl VersionedWorld {
 
     /// Commit all pending mutations as a new tick.
     ///
-    /// This atomically writes all changes to disk and advances the tick counter.
-    /// Use this for single-threaded usage.
+    /// Advances the tick counter and queues the changes for the writer thread;
+    /// they are on disk once [`flush`](Self::flush) returns. Use this for
+    /// single-threaded usage.
     pub fn commit_tick(&mut self) -> StorageResult<TickId> {
         let mutations = std::mem::take(&mut self.pending);
         self.commit_mutations(mutations)
@@ -231,32 +335,71 @@ impl VersionedWorld {
         self.commit_mutations(mutations)
     }
 
+    /// Queue a raw mutation for the next `commit_tick`.
+    ///
+    /// For callers that persist state kept outside the ECS world, keyed
+    /// with `ComponentKey::from_raw`.
+    pub fn push(&mut self, mutation: Mutation) {
+        self.pending.push(mutation);
+    }
+
     /// Internal: commit a batch of mutations.
+    ///
+    /// Hands the batch to the writer thread; it's on disk once `flush`
+    /// returns.
     fn commit_mutations(&mut self, mutations: Vec<Mutation>) -> StorageResult<TickId> {
-        self.current_tick += 1;
-        let tree = self.tree()?;
+        let tick = self.current_tick + 1;
+        let sent = self
+            .batches
+            .as_ref()
+            .is_some_and(|batches| batches.send(Batch { tick, mutations }).is_ok());
+        if !sent {
+            return Err(self.writer_failure());
+        }
+        self.current_tick = tick;
+        Ok(tick)
+    }
 
-        // Apply all mutations
-        for mutation in mutations {
-            match mutation {
-                Mutation::Set { key, data } => {
-                    let key_bytes: Vec<u8> = key.as_bytes().to_vec();
-                    tree.set(key_bytes, data)?;
-                }
-                Mutation::Remove { key } => {
-                    let key_bytes: Vec<u8> = key.as_bytes().to_vec();
-                    tree.remove(&key_bytes)?;
-                }
+    /// Wait until every committed tick is on disk.
+    ///
+    /// Reads of the database flush first, so they see every commit.
+    pub fn flush(&self) -> StorageResult<()> {
+        let mut progress = self.shared.progress.lock();
+        while progress.written < self.current_tick {
+            if progress.failure.is_some()
+                || self.writer.as_ref().is_none_or(JoinHandle::is_finished)
+            {
+                drop(progress);
+                return Err(self.writer_failure());
             }
+            // Time out now and then in case the writer died without a word
+            self.shared
+                .written
+                .wait_for(&mut progress, std::time::Duration::from_millis(100));
         }
+        Ok(())
+    }
 
-        // Store the current tick
-        tree.set(
-            b"__tick__".to_vec(),
-            self.current_tick.to_le_bytes().to_vec(),
-        )?;
+    /// Why the writer thread stopped.
+    fn writer_failure(&self) -> StorageError {
+        let progress = self.shared.progress.lock();
+        match &progress.failure {
+            Some(failure) => StorageError::WriteFailed {
+                tick: failure.tick,
+                message: failure.message.clone(),
+            },
+            None => StorageError::WriteFailed {
+                tick: progress.written + 1,
+                message: "the writer thread stopped".to_string(),
+            },
+        }
+    }
 
-        Ok(self.current_tick)
+    /// The oldest tick that can still be replayed: the oldest retained
+    /// checkpoint.
+    pub fn first_tick(&self) -> StorageResult<TickId> {
+        self.flush()?;
+        Ok(self.shared.progress.lock().checkpoints[0])
     }
 
     // ==================== Time Travel ====================
@@ -277,6 +420,7 @@ impl VersionedWorld {
             return Ok(self.world.get(entity));
         }
 
+        self.flush()?;
         let tree = self.tree()?;
 
         let component_id = match self.world.component_id::<T>() {
@@ -310,6 +454,7 @@ impl VersionedWorld {
             None => return Ok(None),
         };
 
+        self.flush()?;
         let tree = self.tree()?;
         let key = ComponentKey::new(entity, component_id);
         let key_bytes: Vec<u8> = key.as_bytes().to_vec();
@@ -323,6 +468,63 @@ impl VersionedWorld {
         Ok(None)
     }
 
+    /// Rebuild the raw state at `tick` from the nearest checkpoint at or
+    /// before it and the write-ahead log since.
+    ///
+    /// Fails for ticks after the current one or before
+    /// [`first_tick`](Self::first_tick).
+    pub fn replay(&self, tick: TickId) -> StorageResult<BTreeMap<ComponentKey, Vec<u8>>> {
+        if tick > self.current_tick {
+            return Err(StorageError::InvalidTick(tick));
+        }
+        self.flush()?;
+        let checkpoints = self.shared.progress.lock().checkpoints.clone();
+        replay_from(&self.tree()?, &checkpoints, tick)
+    }
+
+    /// The mutations committed at `tick`, in order.
+    ///
+    /// Empty for ticks that changed nothing. Fails for ticks whose record
+    /// was truncated, at or before [`first_tick`](Self::first_tick).
+    pub fn log(&self, tick: TickId) -> StorageResult<Vec<Mutation>> {
+        let first = self.first_tick()?;
+        if tick > self.current_tick || (first > 0 && tick <= first) {
+            return Err(StorageError::InvalidTick(tick));
+        }
+        read_log(&self.tree()?, tick)
+    }
+
+    /// Make the raw state at `tick` current again.
+    ///
+    /// Commits the difference between the latest state and `tick`'s as a new
+    /// tick, so later ticks stay in the log but the next replay of the
+    /// latest tick yields `tick`'s state. Returns that state.
+    pub fn restore_tick(&mut self, tick: TickId) -> StorageResult<BTreeMap<ComponentKey, Vec<u8>>> {
+        let target = self.replay(tick)?;
+
+        // Carry on from the target to the latest tick rather than replaying
+        // from a checkpoint again
+        let tree = self.tree()?;
+        let mut latest = target.clone();
+        for later in tick + 1..=self.current_tick {
+            apply(&mut latest, read_log(&tree, later)?);
+        }
+
+        for key in latest.keys().filter(|key| !target.contains_key(key)) {
+            self.pending.push(Mutation::Remove { key: *key });
+        }
+        for (key, data) in &target {
+            if latest.get(key) != Some(data) {
+                self.pending.push(Mutation::Set {
+                    key: *key,
+                    data: data.clone(),
+                });
+            }
+        }
+        self.commit_tick()?;
+        Ok(target)
+    }
+
     /// Revert the world to a specific tick.
     ///
     /// This restores the in-memory world state to match the persisted state
@@ -349,6 +551,225 @@ impl VersionedWorld {
     }
 }
 
+impl Drop for VersionedWorld {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer finish the queued ticks and exit
+        self.batches = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// A committed tick on its way to the writer thread.
+struct Batch {
+    tick: TickId,
+    mutations: Vec<Mutation>,
+}
+
+/// State shared with the writer thread.
+struct Shared {
+    progress: Mutex<Progress>,
+    /// Notified whenever `progress` changes.
+    written: Condvar,
+}
+
+/// What the writer thread has written so far.
+struct Progress {
+    /// Latest tick on disk.
+    written: TickId,
+    /// Ticks of the retained checkpoints, oldest first.
+    checkpoints: Vec<TickId>,
+    /// Why the writer stopped, if it did.
+    failure: Option<WriteFailure>,
+}
+
+/// A tick the writer thread failed to write.
+struct WriteFailure {
+    tick: TickId,
+    message: String,
+}
+
+/// Writes committed ticks to disk, off the tick thread.
+struct Writer {
+    tree: ComponentTree,
+    /// The latest state written, checkpointed every `checkpoint_interval`.
+    state: State,
+    /// Ticks of the retained checkpoints, oldest first.
+    checkpoints: Vec<TickId>,
+    checkpoint_interval: TickId,
+    shared: Arc<Shared>,
+}
+
+impl Writer {
+    /// Write batches until the channel closes or a write fails.
+    fn run(mut self, batches: &Receiver<Batch>) {
+        for batch in batches {
+            let tick = batch.tick;
+            let result = self.write(batch);
+            let mut progress = self.shared.progress.lock();
+            match result {
+                Ok(()) => {
+                    progress.written = tick;
+                    progress.checkpoints.clone_from(&self.checkpoints);
+                }
+                Err(error) => {
+                    tracing::error!("failed to write tick {tick}: {error}");
+                    progress.failure = Some(WriteFailure {
+                        tick,
+                        message: error.to_string(),
+                    });
+                }
+            }
+            let failed = progress.failure.is_some();
+            drop(progress);
+            self.shared.written.notify_all();
+            if failed {
+                return;
+            }
+        }
+    }
+
+    /// Write one tick, checkpointing if it's due.
+    fn write(&mut self, batch: Batch) -> StorageResult<()> {
+        let Batch { tick, mutations } = batch;
+
+        // Ticks that change nothing leave no record
+        if !mutations.is_empty() {
+            let mut record = Vec::new();
+            for mutation in &mutations {
+                mutation.encode(&mut record);
+            }
+            self.tree.set(log_key(tick), record)?;
+        }
+
+        for mutation in mutations {
+            match mutation {
+                Mutation::Set { key, data } => {
+                    self.tree.set(key.as_bytes().to_vec(), data.clone())?;
+                    self.state.insert(key, data);
+                }
+                Mutation::Remove { key } => {
+                    self.tree.remove(key.as_bytes())?;
+                    self.state.remove(&key);
+                }
+            }
+        }
+
+        // Store the current tick
+        self.tree
+            .set(TICK_KEY.to_vec(), tick.to_le_bytes().to_vec())?;
+
+        if tick % self.checkpoint_interval == 0 {
+            self.checkpoint(tick)?;
+        }
+        Ok(())
+    }
+
+    /// Write the latest state as a checkpoint, dropping the oldest one and
+    /// the log it made unreachable once more than `RETAINED_CHECKPOINTS` are
+    /// kept.
+    fn checkpoint(&mut self, tick: TickId) -> StorageResult<()> {
+        let mut record = Vec::new();
+        for (key, data) in &self.state {
+            Mutation::Set {
+                key: *key,
+                data: data.clone(),
+            }
+            .encode(&mut record);
+        }
+        self.tree.set(checkpoint_key(tick), record)?;
+        self.checkpoints.push(tick);
+
+        let dropped =
+            (self.checkpoints.len() > RETAINED_CHECKPOINTS).then(|| self.checkpoints.remove(0));
+        self.tree
+            .set(CHECKPOINTS_KEY.to_vec(), encode_ticks(&self.checkpoints))?;
+        if let Some(dropped) = dropped {
+            // Replays start at the oldest retained checkpoint at the earliest
+            self.tree.remove(&checkpoint_key(dropped))?;
+            for truncated in dropped + 1..=self.checkpoints[0] {
+                self.tree.remove(&log_key(truncated))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild the state at `tick` from the nearest of `checkpoints` at or
+/// before it.
+fn replay_from(tree: &ComponentTree, checkpoints: &[TickId], tick: TickId) -> StorageResult<State> {
+    let Some(&base) = checkpoints
+        .iter()
+        .rev()
+        .find(|&&checkpoint| checkpoint <= tick)
+    else {
+        return Err(StorageError::InvalidTick(tick));
+    };
+
+    let mut state = State::new();
+    // Tick 0 is the empty state and has no record
+    if base > 0 {
+        let record = tree
+            .get(&checkpoint_key(base))?
+            .ok_or(StorageError::CorruptLog(base))?;
+        let mutations =
+            Mutation::decode_all(record.as_ref()).ok_or(StorageError::CorruptLog(base))?;
+        apply(&mut state, mutations);
+    }
+    for replayed in base + 1..=tick {
+        apply(&mut state, read_log(tree, replayed)?);
+    }
+    Ok(state)
+}
+
+/// The log record of `tick`, empty if it has none.
+fn read_log(tree: &ComponentTree, tick: TickId) -> StorageResult<Vec<Mutation>> {
+    let Some(record) = tree.get(&log_key(tick))? else {
+        return Ok(Vec::new());
+    };
+    Mutation::decode_all(record.as_ref()).ok_or(StorageError::CorruptLog(tick))
+}
+
+fn apply(state: &mut State, mutations: Vec<Mutation>) {
+    for mutation in mutations {
+        match mutation {
+            Mutation::Set { key, data } => {
+                state.insert(key, data);
+            }
+            Mutation::Remove { key } => {
+                state.remove(&key);
+            }
+        }
+    }
+}
+
+/// Key of a tick's write-ahead log record.
+fn log_key(tick: TickId) -> Vec<u8> {
+    let mut key = b"__log__".to_vec();
+    key.extend_from_slice(&tick.to_be_bytes());
+    key
+}
+
+/// Key of a checkpoint record.
+fn checkpoint_key(tick: TickId) -> Vec<u8> {
+    let mut key = b"__checkpoint__".to_vec();
+    key.extend_from_slice(&tick.to_be_bytes());
+    key
+}
+
+fn encode_ticks(ticks: &[TickId]) -> Vec<u8> {
+    ticks.iter().flat_map(|tick| tick.to_le_bytes()).collect()
+}
+
+fn decode_ticks(bytes: &[u8]) -> Vec<TickId> {
+    let ticks: Vec<TickId> = bytes
+        .chunks_exact(8)
+        .map(|tick| u64::from_le_bytes(tick.try_into().unwrap_or([0; 8])))
+        .collect();
+    if ticks.is_empty() { vec![0] } else { ticks }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -422,4 +843,79 @@ mod tests {
             assert_eq!(world.current_tick(), 1);
         }
     }
+
+    #[test]
+    fn test_replay_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = ComponentKey::from_raw(1, 1);
+        let other = ComponentKey::from_raw(2, 1);
+
+        let mut world = VersionedWorld::open(dir.path()).unwrap();
+        world.push(Mutation::Set { key, data: vec![1] });
+        let first = world.commit_tick().unwrap();
+        world.push(Mutation::Set { key, data: vec![2] });
+        world.push(Mutation::Set {
+            key: other,
+            data: vec![3],
+        });
+        world.commit_tick().unwrap();
+
+        assert_eq!(
+            world.log(first).unwrap(),
+            [Mutation::Set { key, data: vec![1] }]
+        );
+        assert_eq!(world.log(2).unwrap().len(), 2);
+        assert!(world.log(3).is_err());
+
+        let latest = world.replay(world.current_tick()).unwrap();
+        assert_eq!(latest.get(&key), Some(&vec![2]));
+        assert_eq!(latest.get(&other), Some(&vec![3]));
+        assert!(world.replay(world.current_tick() + 1).is_err());
+
+        // Restoring the first tick undoes the second as a new tick
+        let restored = world.restore_tick(first).unwrap();
+        assert_eq!(world.current_tick(), 3);
+        assert_eq!(restored.get(&key), Some(&vec![1]));
+        assert_eq!(world.replay(3).unwrap(), restored);
+    }
+
+    #[test]
+    fn test_checkpoints_truncate_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = ComponentKey::from_raw(1, 1);
+        let other = ComponentKey::from_raw(2, 1);
+
+        {
+            let mut world = VersionedWorld::open_with_checkpoint_interval(dir.path(), 3).unwrap();
+            world.push(Mutation::Set {
+                key: other,
+                data: vec![0],
+            });
+            for value in 1..=10 {
+                world.push(Mutation::Set {
+                    key,
+                    data: vec![value],
+                });
+                world.commit_tick().unwrap();
+            }
+
+            // Checkpoints at 6 and 9 are kept, the log up to 6 is gone
+            assert_eq!(world.first_tick().unwrap(), 6);
+            assert!(world.log(6).is_err());
+            assert_eq!(world.log(7).unwrap().len(), 1);
+            assert!(world.replay(5).is_err());
+
+            let state = world.replay(7).unwrap();
+            assert_eq!(state.get(&key), Some(&vec![7]));
+            assert_eq!(state.get(&other), Some(&vec![0]));
+        }
+
+        // The writer was joined on drop, so everything is on disk
+        let world = VersionedWorld::open_with_checkpoint_interval(dir.path(), 3).unwrap();
+        assert_eq!(world.current_tick(), 10);
+        assert_eq!(world.first_tick().unwrap(), 6);
+        let latest = world.replay(10).unwrap();
+        assert_eq!(latest.get(&key), Some(&vec![10]));
+        assert_eq!(latest.get(&other), Some(&vec![0]));
+    }
 }
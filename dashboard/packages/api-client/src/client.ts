@@ -9,6 +9,7 @@ import type {
   QueryResponse,
   ComponentTypesResponse,
   ChunksResponse,
+  SystemInfo,
   HistoryResponse,
   RevertResponse,
 } from './types';
@@ -148,6 +149,11 @@ export class RgbClient {
     return this.fetch('/api/chunks');
   }
 
+  // Systems and their pipeline phases
+  async getSystems(): Promise<SystemInfo[]> {
+    return this.fetch('/api/systems');
+  }
+
   // History endpoints
   async getGlobalHistory(limit?: number): Promise<HistoryResponse> {
     const params = limit ? `?limit=${limit}` : '';
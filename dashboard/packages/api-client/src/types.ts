@@ -96,6 +96,13 @@ export interface ChunksResponse {
   chunks: ChunkInfo[];
 }
 
+export interface SystemInfo {
+  id: number;
+  name: string;
+  module: string | null;
+  phases: string[];
+}
+
 // History types
 export type ChangeSource = 'dashboard' | 'system' | 'spawn' | 'revert';
 
@@ -106,7 +113,10 @@ export interface HistoryEntry {
   component: string;
   old_value: unknown | null;
   new_value: unknown | null;
-  source: ChangeSource;
+  /** Transaction label (e.g. the console command) for grouped changes */
+  source: ChangeSource | string;
+  /** Id shared by the entries of one transaction */
+  transaction: number | null;
 }
 
 export interface HistoryResponse {
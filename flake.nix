@@ -256,6 +256,75 @@ JAVA_EOF
           ${pkgs.jdk21}/bin/java -cp ".:$CLIENT_JAR" PacketExtractor "$CLIENT_JAR" "$OUTPUT"
         '';
 
+        # Extract per-state collision boxes, opacity and block hardness, which
+        # Mojang's reports don't include. Needs the server libraries a data
+        # generator run unpacks into its `libraries` directory.
+        blockShapeExtractor = pkgs.writeShellScriptBin "extract-block-shapes" ''
+          set -euo pipefail
+          VERSION="''${1:-${mcVersion}}"
+          OUTPUT="$(realpath "''${2:-block-shapes.json}")"
+          LIBRARIES="$(realpath "$3")"
+
+          CLIENT_JAR=$(${downloadUnobfuscatedClient}/bin/download-unobfuscated-client "$VERSION")
+          CLASSPATH="$CLIENT_JAR:$(find "$LIBRARIES" -name '*.jar' | tr '\n' ':')"
+
+          TEMP_DIR=$(mktemp -d)
+          trap "rm -rf $TEMP_DIR" EXIT
+          cd "$TEMP_DIR"
+
+          cat > BlockShapeExtractor.java << 'JAVA_EOF'
+import java.io.*;
+import net.minecraft.SharedConstants;
+import net.minecraft.core.BlockPos;
+import net.minecraft.core.registries.BuiltInRegistries;
+import net.minecraft.server.Bootstrap;
+import net.minecraft.world.level.EmptyBlockGetter;
+import net.minecraft.world.level.block.Block;
+import net.minecraft.world.level.block.state.BlockState;
+import net.minecraft.world.phys.AABB;
+
+public class BlockShapeExtractor {
+    public static void main(String[] args) throws Exception {
+        SharedConstants.tryDetectVersion();
+        Bootstrap.bootStrap();
+
+        StringBuilder json = new StringBuilder("{\n");
+        String blockSep = "";
+        for (Block block : BuiltInRegistries.BLOCK) {
+            StringBuilder collision = new StringBuilder(), opaque = new StringBuilder();
+            String stateSep = "";
+            for (BlockState state : block.getStateDefinition().getPossibleStates()) {
+                String id = "\"" + Block.getId(state) + "\": ";
+                collision.append(stateSep).append(id).append('[');
+                String boxSep = "";
+                for (AABB box : state.getCollisionShape(EmptyBlockGetter.INSTANCE, BlockPos.ZERO).toAabbs()) {
+                    collision.append(boxSep).append('[').append(box.minX).append(", ").append(box.minY)
+                        .append(", ").append(box.minZ).append(", ").append(box.maxX).append(", ")
+                        .append(box.maxY).append(", ").append(box.maxZ).append(']');
+                    boxSep = ", ";
+                }
+                collision.append(']');
+                opaque.append(stateSep).append(id).append(state.isSolidRender());
+                stateSep = ", ";
+            }
+            json.append(blockSep).append("  \"").append(BuiltInRegistries.BLOCK.getKey(block))
+                .append("\": {\"hardness\": ").append(block.defaultDestroyTime())
+                .append(", \"collision\": {").append(collision)
+                .append("}, \"opaque\": {").append(opaque).append("}}");
+            blockSep = ",\n";
+        }
+        json.append("\n}\n");
+
+        try (PrintWriter out = new PrintWriter(args[0])) { out.print(json); }
+        System.out.println("Extracted to: " + args[0]);
+    }
+}
+JAVA_EOF
+
+          ${pkgs.jdk21}/bin/javac -cp "$CLASSPATH" BlockShapeExtractor.java
+          ${pkgs.jdk21}/bin/java -cp ".:$CLASSPATH" BlockShapeExtractor "$OUTPUT"
+        '';
+
         # Run real MC server in offline mode for packet capture
         runMcServer = pkgs.writeShellScriptBin "run-mc-server" ''
           set -euo pipefail
@@ -371,6 +440,22 @@ JAVA_EOF
           echo "Running Mojang data generator for IDs..."
           ${mcDataGen}/bin/mc-data-gen "$VERSION" "$TEMP_DIR" >/dev/null 2>&1
           cp "$TEMP_DIR/generated/reports/packets.json" "$DATA_DIR/packets-ids.json"
+          cp "$TEMP_DIR/generated/reports/registries.json" "$DATA_DIR/registries.json"
+          cp "$TEMP_DIR/generated/reports/items.json" "$DATA_DIR/items.json"
+
+          # Block shapes, opacity and hardness, using the libraries the data
+          # generator unpacked
+          echo "Extracting block shapes..."
+          ${blockShapeExtractor}/bin/extract-block-shapes "$VERSION" "$DATA_DIR/block-shapes.json" "$TEMP_DIR/libraries"
+
+          # Synced registries (sent as Registry Data during configuration)
+          for REGISTRY in cat_variant chicken_variant cow_variant damage_type dimension_type \
+            frog_variant painting_variant pig_variant wolf_sound_variant wolf_variant \
+            worldgen/biome zombie_nautilus_variant; do
+            rm -rf "$DATA_DIR/registries/minecraft/$REGISTRY"
+            mkdir -p "$DATA_DIR/registries/minecraft/$REGISTRY"
+            cp "$TEMP_DIR/generated/data/minecraft/$REGISTRY"/*.json "$DATA_DIR/registries/minecraft/$REGISTRY/"
+          done
 
           # Extract protocol version from client jar
           CLIENT_JAR=$(${downloadUnobfuscatedClient}/bin/download-unobfuscated-client "$VERSION" 2>/dev/null)
@@ -394,6 +479,7 @@ JAVA_EOF
           mc-data-gen = mcDataGen;
           mc-gen = mcGen;
           extract-packets = packetExtractor;
+          extract-block-shapes = blockShapeExtractor;
           download-mc-jar = downloadMcJar;
           run-vanilla-server = runMcServer;
           packet-proxy = packetProxy;
@@ -407,6 +493,7 @@ JAVA_EOF
           mc-data-gen = flake-utils.lib.mkApp { drv = mcDataGen; };
           mc-gen = flake-utils.lib.mkApp { drv = mcGen; };
           extract-packets = flake-utils.lib.mkApp { drv = packetExtractor; };
+          extract-block-shapes = flake-utils.lib.mkApp { drv = blockShapeExtractor; };
           run-vanilla-server = flake-utils.lib.mkApp { drv = runMcServer; };
           packet-proxy = flake-utils.lib.mkApp { drv = packetProxy; };
           build-fabric-test-agent = flake-utils.lib.mkApp { drv = buildFabricTestAgent; };